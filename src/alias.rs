@@ -0,0 +1,136 @@
+use crate::rng::Rng;
+
+/// walker's alias method: after O(n) setup, draws index `i` with probability
+/// `weights[i] / sum(weights)` in O(1), using one uniform index and one coin.
+#[derive(Clone, Debug)]
+pub struct AliasTable {
+    weights: Vec<f64>,
+    // chance of keeping column `i` rather than taking `alias[i]`.
+    prob: Vec<f64>,
+    alias: Vec<usize>,
+}
+
+impl AliasTable {
+    /// build a table over `weights`; returns None if they are empty, any is
+    /// negative or not finite, or they sum to zero.
+    pub fn new(weights: &[f64]) -> Option<Self> {
+        let mut table = AliasTable {
+            weights: Vec::new(),
+            prob: Vec::new(),
+            alias: Vec::new(),
+        };
+        if table.rebuild(weights) {
+            Some(table)
+        } else {
+            None
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.weights.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.weights.is_empty()
+    }
+
+    pub fn weights(&self) -> &[f64] {
+        &self.weights
+    }
+
+    /// probability of drawing `index`.
+    pub fn probability(&self, index: usize) -> f64 {
+        self.weights[index] / self.weights.iter().sum::<f64>()
+    }
+
+    /// replace all weights, returning false and leaving the table unchanged
+    /// if they are invalid (see [`AliasTable::new`]).
+    pub fn rebuild(&mut self, weights: &[f64]) -> bool {
+        let valid = weights.iter().all(|w| w.is_finite() && *w >= 0.0);
+        let total: f64 = weights.iter().sum();
+        if weights.is_empty() || !valid || total <= 0.0 || !total.is_finite() {
+            return false;
+        }
+        // vose's variant: scale to mean 1 and pair each under-full column
+        // with an over-full one.
+        let n = weights.len();
+        let mut scaled: Vec<f64> = weights.iter().map(|w| w * n as f64 / total).collect();
+        let mut alias = vec![0; n];
+        let (mut small, mut large): (Vec<usize>, Vec<usize>) =
+            (0..n).partition(|&i| scaled[i] < 1.0);
+        while let (Some(&s), Some(&l)) = (small.last(), large.last()) {
+            small.pop();
+            alias[s] = l;
+            scaled[l] -= 1.0 - scaled[s];
+            if scaled[l] < 1.0 {
+                large.pop();
+                small.push(l);
+            }
+        }
+        // whatever is left is 1 up to rounding.
+        for i in small.into_iter().chain(large) {
+            scaled[i] = 1.0;
+        }
+        self.weights = weights.to_vec();
+        self.prob = scaled;
+        self.alias = alias;
+        true
+    }
+
+    /// change one weight and rebuild the table.
+    pub fn set_weight(&mut self, index: usize, weight: f64) -> bool {
+        let mut weights = self.weights.clone();
+        weights[index] = weight;
+        self.rebuild(&weights)
+    }
+
+    pub fn sample(&self, rng: &mut Rng) -> usize {
+        let i = rng.index(self.prob.len());
+        if rng.next_f64() < self.prob[i] {
+            i
+        } else {
+            self.alias[i]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frequencies(table: &AliasTable, rng: &mut Rng, draws: usize) -> Vec<f64> {
+        let mut counts = vec![0usize; table.len()];
+        for _ in 0..draws {
+            counts[table.sample(rng)] += 1;
+        }
+        counts.iter().map(|&c| c as f64 / draws as f64).collect()
+    }
+
+    #[test]
+    fn test_sampling() {
+        let mut rng = Rng::new(32);
+        let weights = [1.0, 0.0, 3.0, 6.0, 0.5];
+        let table = AliasTable::new(&weights).unwrap();
+        let freq = frequencies(&table, &mut rng, 200_000);
+        for (i, f) in freq.iter().enumerate() {
+            assert!((f - table.probability(i)).abs() < 0.005, "{:?}", freq);
+        }
+        assert_eq!(freq[1], 0.0);
+    }
+
+    #[test]
+    fn test_rebuild() {
+        let mut rng = Rng::new(33);
+        let mut table = AliasTable::new(&[1.0, 1.0]).unwrap();
+        assert!(table.set_weight(0, 0.0));
+        assert!((0..1000).all(|_| table.sample(&mut rng) == 1));
+        assert!(!table.set_weight(1, 0.0));
+        assert!(!table.rebuild(&[1.0, f64::NAN]));
+        assert_eq!(table.weights(), &[0.0, 1.0]);
+        assert!(table.rebuild(&[2.0, 1.0, 1.0]));
+        let freq = frequencies(&table, &mut rng, 100_000);
+        assert!((freq[0] - 0.5).abs() < 0.01);
+        assert!(AliasTable::new(&[]).is_none());
+        assert!(AliasTable::new(&[-1.0, 2.0]).is_none());
+    }
+}
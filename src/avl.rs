@@ -0,0 +1,516 @@
+use std::cmp::{max, Ordering};
+use std::fmt;
+use std::ops::{Bound, RangeBounds, RangeFull};
+
+use crate::{OrderStatistic, OrderedMap};
+
+type Link<K, V> = Option<Box<Node<K, V>>>;
+
+struct Node<K, V> {
+    key: K,
+    value: V,
+    height: u32,
+    size: usize,
+    left: Link<K, V>,
+    right: Link<K, V>,
+}
+
+impl<K, V> Node<K, V> {
+    fn update(&mut self) {
+        self.height = 1 + max(height(&self.left), height(&self.right));
+        self.size = 1 + size(&self.left) + size(&self.right);
+    }
+
+    fn balance_factor(&self) -> i64 {
+        i64::from(height(&self.left)) - i64::from(height(&self.right))
+    }
+}
+
+fn height<K, V>(link: &Link<K, V>) -> u32 {
+    link.as_ref().map_or(0, |node| node.height)
+}
+
+fn size<K, V>(link: &Link<K, V>) -> usize {
+    link.as_ref().map_or(0, |node| node.size)
+}
+
+fn rotate_right<K, V>(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    let mut left = node.left.take().expect("rotation needs a left child");
+    node.left = left.right.take();
+    node.update();
+    left.right = Some(node);
+    left.update();
+    left
+}
+
+fn rotate_left<K, V>(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    let mut right = node.right.take().expect("rotation needs a right child");
+    node.right = right.left.take();
+    node.update();
+    right.left = Some(node);
+    right.update();
+    right
+}
+
+// restore the avl invariant at `node`, whose children are balanced and differ in height by at most 2.
+fn rebalance<K, V>(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    node.update();
+    let factor = node.balance_factor();
+    if factor > 1 {
+        let left = node.left.take().unwrap();
+        node.left = Some(if left.balance_factor() < 0 {
+            rotate_left(left)
+        } else {
+            left
+        });
+        rotate_right(node)
+    } else if factor < -1 {
+        let right = node.right.take().unwrap();
+        node.right = Some(if right.balance_factor() > 0 {
+            rotate_right(right)
+        } else {
+            right
+        });
+        rotate_left(node)
+    } else {
+        node
+    }
+}
+
+fn insert<K: Ord, V>(link: Link<K, V>, key: K, value: V) -> (Box<Node<K, V>>, Option<V>) {
+    match link {
+        None => (
+            Box::new(Node {
+                key,
+                value,
+                height: 1,
+                size: 1,
+                left: None,
+                right: None,
+            }),
+            None,
+        ),
+        Some(mut node) => match key.cmp(&node.key) {
+            Ordering::Equal => {
+                let old = std::mem::replace(&mut node.value, value);
+                (node, Some(old))
+            }
+            Ordering::Less => {
+                let (left, old) = insert(node.left.take(), key, value);
+                node.left = Some(left);
+                (rebalance(node), old)
+            }
+            Ordering::Greater => {
+                let (right, old) = insert(node.right.take(), key, value);
+                node.right = Some(right);
+                (rebalance(node), old)
+            }
+        },
+    }
+}
+
+// detach the smallest node, returning the rest of the tree and that node.
+fn take_min<K, V>(mut node: Box<Node<K, V>>) -> (Link<K, V>, Box<Node<K, V>>) {
+    match node.left.take() {
+        None => (node.right.take(), node),
+        Some(left) => {
+            let (rest, min) = take_min(left);
+            node.left = rest;
+            (Some(rebalance(node)), min)
+        }
+    }
+}
+
+fn remove<K: Ord, V>(link: Link<K, V>, key: &K) -> (Link<K, V>, Option<V>) {
+    match link {
+        None => (None, None),
+        Some(mut node) => match key.cmp(&node.key) {
+            Ordering::Less => {
+                let (left, old) = remove(node.left.take(), key);
+                node.left = left;
+                (Some(rebalance(node)), old)
+            }
+            Ordering::Greater => {
+                let (right, old) = remove(node.right.take(), key);
+                node.right = right;
+                (Some(rebalance(node)), old)
+            }
+            Ordering::Equal => {
+                let (left, right) = (node.left.take(), node.right.take());
+                let replacement = match right {
+                    None => left,
+                    Some(right) => {
+                        let (rest, mut min) = take_min(right);
+                        min.left = left;
+                        min.right = rest;
+                        Some(rebalance(min))
+                    }
+                };
+                (replacement, Some(node.value))
+            }
+        },
+    }
+}
+
+/// ordered map backed by an avl tree, augmented with subtree sizes for rank and select.
+pub struct AvlTree<K, V> {
+    root: Link<K, V>,
+}
+
+impl<K: Ord, V> AvlTree<K, V> {
+    /// create an empty tree.
+    pub fn new() -> Self {
+        AvlTree { root: None }
+    }
+
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// height of the tree, at most about 1.44 log2(n).
+    pub fn height(&self) -> u32 {
+        height(&self.root)
+    }
+
+    /// returns the value stored for `key`.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let mut link = &self.root;
+        while let Some(node) = link {
+            match key.cmp(&node.key) {
+                Ordering::Less => link = &node.left,
+                Ordering::Equal => return Some(&node.value),
+                Ordering::Greater => link = &node.right,
+            }
+        }
+        None
+    }
+
+    /// returns a mutable reference to the value stored for `key`.
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let mut link = &mut self.root;
+        while let Some(node) = link {
+            match key.cmp(&node.key) {
+                Ordering::Less => link = &mut node.left,
+                Ordering::Equal => return Some(&mut node.value),
+                Ordering::Greater => link = &mut node.right,
+            }
+        }
+        None
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// insert `value` for `key`, returning the previous value if there was one.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let (root, old) = insert(self.root.take(), key, value);
+        self.root = Some(root);
+        old
+    }
+
+    /// remove `key`, returning its value if it was present.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let (root, old) = remove(self.root.take(), key);
+        self.root = root;
+        old
+    }
+
+    /// number of keys strictly smaller than `key`.
+    pub fn rank(&self, key: &K) -> usize {
+        let mut rank = 0;
+        let mut link = &self.root;
+        while let Some(node) = link {
+            if node.key < *key {
+                rank += size(&node.left) + 1;
+                link = &node.right;
+            } else {
+                link = &node.left;
+            }
+        }
+        rank
+    }
+
+    /// returns the `rank`-th smallest entry (0-based).
+    pub fn select(&self, mut rank: usize) -> Option<(&K, &V)> {
+        let mut link = &self.root;
+        while let Some(node) = link {
+            let left_size = size(&node.left);
+            match rank.cmp(&left_size) {
+                Ordering::Less => link = &node.left,
+                Ordering::Equal => return Some((&node.key, &node.value)),
+                Ordering::Greater => {
+                    rank -= left_size + 1;
+                    link = &node.right;
+                }
+            }
+        }
+        None
+    }
+
+    /// entry with the smallest key.
+    pub fn first(&self) -> Option<(&K, &V)> {
+        self.select(0)
+    }
+
+    /// entry with the largest key.
+    pub fn last(&self) -> Option<(&K, &V)> {
+        self.len().checked_sub(1).and_then(|rank| self.select(rank))
+    }
+
+    /// iterate over the entries whose keys lie in `range`, in increasing key order.
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Range<'_, K, V, R> {
+        let mut stack = Vec::new();
+        let mut link = &self.root;
+        while let Some(node) = link {
+            let above_start = match range.start_bound() {
+                Bound::Included(start) => node.key >= *start,
+                Bound::Excluded(start) => node.key > *start,
+                Bound::Unbounded => true,
+            };
+            if above_start {
+                stack.push(&**node);
+                link = &node.left;
+            } else {
+                link = &node.right;
+            }
+        }
+        Range { stack, range }
+    }
+
+    /// iterate in increasing key order.
+    pub fn iter(&self) -> Range<'_, K, V, RangeFull> {
+        self.range(..)
+    }
+}
+
+impl<K: Ord, V> Default for AvlTree<K, V> {
+    fn default() -> Self {
+        AvlTree::new()
+    }
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug> fmt::Debug for AvlTree<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Ord, V> std::iter::FromIterator<(K, V)> for AvlTree<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut tree = AvlTree::new();
+        iter.into_iter().for_each(|(k, v)| {
+            tree.insert(k, v);
+        });
+        tree
+    }
+}
+
+impl<K: Ord, V> OrderedMap<K, V> for AvlTree<K, V> {
+    fn new() -> Self {
+        AvlTree::new()
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn get(&self, key: &K) -> Option<&V> {
+        self.get(key)
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.insert(key, value)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        self.remove(key)
+    }
+
+    fn first(&self) -> Option<(&K, &V)> {
+        self.first()
+    }
+
+    fn last(&self) -> Option<(&K, &V)> {
+        self.last()
+    }
+
+    fn iter<'a>(&'a self) -> Box<dyn Iterator<Item = (&'a K, &'a V)> + 'a>
+    where
+        K: 'a,
+        V: 'a,
+    {
+        Box::new(self.iter())
+    }
+}
+
+impl<K: Ord, V> OrderStatistic<K, V> for AvlTree<K, V> {
+    fn rank(&self, key: &K) -> usize {
+        self.rank(key)
+    }
+
+    fn select(&self, rank: usize) -> Option<(&K, &V)> {
+        self.select(rank)
+    }
+}
+
+/// iterator over a key range of an avl tree.
+pub struct Range<'a, K, V, R> {
+    // nodes whose key and whole right subtree are still to be visited.
+    stack: Vec<&'a Node<K, V>>,
+    range: R,
+}
+
+impl<'a, K: Ord, V, R: RangeBounds<K>> Iterator for Range<'a, K, V, R> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        let below_end = match self.range.end_bound() {
+            Bound::Included(end) => node.key <= *end,
+            Bound::Excluded(end) => node.key < *end,
+            Bound::Unbounded => true,
+        };
+        if !below_end {
+            self.stack.clear();
+            return None;
+        }
+        let mut link = &node.right;
+        while let Some(child) = link {
+            self.stack.push(child);
+            link = &child.left;
+        }
+        Some((&node.key, &node.value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+    use std::collections::BTreeMap;
+
+    // returns the height, asserting the avl and size invariants on the way.
+    fn check<K: Ord, V>(link: &Link<K, V>) -> u32 {
+        match link {
+            None => 0,
+            Some(node) => {
+                let (l, r) = (check(&node.left), check(&node.right));
+                assert!((i64::from(l) - i64::from(r)).abs() <= 1);
+                assert_eq!(node.height, 1 + max(l, r));
+                assert_eq!(node.size, 1 + size(&node.left) + size(&node.right));
+                node.height
+            }
+        }
+    }
+
+    #[test]
+    fn test_against_btree() {
+        let mut rng = Rng::new(21);
+        let mut tree = AvlTree::new();
+        let mut model = BTreeMap::new();
+        for step in 0..3000 {
+            let key = rng.range(0..400);
+            if rng.chance(0.6) {
+                assert_eq!(tree.insert(key, step), model.insert(key, step));
+            } else {
+                assert_eq!(tree.remove(&key), model.remove(&key));
+            }
+            if step % 100 == 0 {
+                check(&tree.root);
+            }
+        }
+        check(&tree.root);
+        assert_eq!(tree.len(), model.len());
+        assert!(tree.iter().eq(model.iter()));
+        for (rank, (key, value)) in model.iter().enumerate() {
+            assert_eq!(tree.rank(key), rank);
+            assert_eq!(tree.select(rank), Some((key, value)));
+        }
+        assert_eq!(tree.select(model.len()), None);
+    }
+
+    #[test]
+    fn test_range() {
+        let tree: AvlTree<i32, ()> = (0..50).map(|k| (k * 2, ())).collect();
+        fn keys<R: RangeBounds<i32>>(it: Range<'_, i32, (), R>) -> Vec<i32> {
+            it.map(|(k, _)| *k).collect()
+        }
+        assert_eq!(keys(tree.range(10..17)), vec![10, 12, 14, 16]);
+        assert_eq!(keys(tree.range(9..=14)), vec![10, 12, 14]);
+        assert_eq!(keys(tree.range(95..)), vec![96, 98]);
+        assert_eq!(keys(tree.range(..3)), vec![0, 2]);
+        assert_eq!(keys(tree.range(31..32)), Vec::<i32>::new());
+        assert_eq!(tree.rank(&31), 16);
+    }
+
+    #[test]
+    fn test_sorted_inserts_stay_balanced() {
+        let mut tree = AvlTree::new();
+        for k in 0..(1 << 12) {
+            tree.insert(k, ());
+        }
+        check(&tree.root);
+        assert!(tree.height() <= 14);
+        for k in 0..(1 << 11) {
+            tree.remove(&k);
+        }
+        check(&tree.root);
+        assert_eq!(tree.first(), Some((&(1 << 11), &())));
+    }
+
+    #[test]
+    fn test_ordered_map_trait() {
+        fn fill<M: OrderedMap<u32, u32>>() -> M {
+            let mut map = M::new();
+            for k in [5, 1, 4, 2, 3].iter() {
+                map.insert(*k, k * 10);
+            }
+            map.remove(&4);
+            map
+        }
+        let map: AvlTree<u32, u32> = fill();
+        assert_eq!(OrderedMap::len(&map), 4);
+        assert!(OrderedMap::contains_key(&map, &3));
+        assert_eq!(OrderedMap::last(&map), Some((&5, &50)));
+        let keys: Vec<u32> = OrderedMap::iter(&map).map(|(k, _)| *k).collect();
+        assert_eq!(keys, vec![1, 2, 3, 5]);
+    }
+
+    #[test]
+    fn test_order_statistic_trait() {
+        fn check_order<M: OrderStatistic<i64, ()>>(seed: u64) {
+            let mut rng = Rng::new(seed);
+            let mut map = M::new();
+            let mut model = Vec::new();
+            for _ in 0..2000 {
+                let k = rng.range(0..500);
+                if rng.chance(0.3) {
+                    map.remove(&k);
+                    model.retain(|&x| x != k);
+                } else if map.insert(k, ()).is_none() {
+                    model.push(k);
+                }
+            }
+            model.sort_unstable();
+            for (i, k) in model.iter().enumerate() {
+                assert_eq!(map.select(i), Some((k, &())));
+                assert_eq!(map.rank(k), i);
+            }
+            assert_eq!(map.select(model.len()), None);
+            assert_eq!(
+                map.median().map(|(k, _)| *k),
+                Some(model[(model.len() - 1) / 2])
+            );
+            let between = model.iter().filter(|&&k| (100..300).contains(&k)).count();
+            assert_eq!(map.count_between(&100, &300), between);
+            assert_eq!(map.count_between(&300, &100), 0);
+        }
+        check_order::<AvlTree<_, _>>(48);
+        check_order::<crate::treap::Treap<_, _>>(49);
+    }
+}
@@ -0,0 +1,579 @@
+use std::fmt;
+use std::ops::{Bound, RangeBounds, RangeFull};
+
+use crate::OrderedMap;
+
+const NIL: usize = usize::MAX;
+
+/// fanout used by `BPlusTree::new`.
+pub const DEFAULT_FANOUT: usize = 64;
+
+struct Node<K, V> {
+    leaf: bool,
+    keys: Vec<K>,
+    // leaves only: `values[i]` belongs to `keys[i]`.
+    values: Vec<V>,
+    // internal nodes only: `children[i]` holds keys below `keys[i]`,
+    // `children[i + 1]` keys at or above it.
+    children: Vec<usize>,
+    // leaves only: the next leaf in key order.
+    next: usize,
+}
+
+impl<K, V> Node<K, V> {
+    fn leaf() -> Self {
+        Node {
+            leaf: true,
+            keys: Vec::new(),
+            values: Vec::new(),
+            children: Vec::new(),
+            next: NIL,
+        }
+    }
+
+    // number of entries for a leaf, of children for an internal node.
+    fn occupancy(&self) -> usize {
+        if self.leaf {
+            self.keys.len()
+        } else {
+            self.children.len()
+        }
+    }
+}
+
+/// ordered map backed by a b+ tree: every entry lives in a leaf, leaves are
+/// chained in key order, and internal nodes only hold separator keys.
+///
+/// wide nodes keep the tree shallow and make range scans sequential, which is
+/// friendlier to caches than binary trees. nodes live in an arena and link to
+/// each other by index.
+pub struct BPlusTree<K, V> {
+    nodes: Vec<Node<K, V>>,
+    free: Vec<usize>,
+    root: usize,
+    fanout: usize,
+    len: usize,
+}
+
+impl<K: Ord + Clone, V> BPlusTree<K, V> {
+    /// create an empty tree with `DEFAULT_FANOUT`.
+    pub fn new() -> Self {
+        BPlusTree::with_fanout(DEFAULT_FANOUT)
+    }
+
+    /// create an empty tree whose nodes hold at most `fanout` entries or children.
+    pub fn with_fanout(fanout: usize) -> Self {
+        assert!(fanout >= 3, "fanout must be at least 3");
+        BPlusTree {
+            nodes: vec![Node::leaf()],
+            free: Vec::new(),
+            root: 0,
+            fanout,
+            len: 0,
+        }
+    }
+
+    pub fn fanout(&self) -> usize {
+        self.fanout
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// number of levels, a lone leaf has depth 1.
+    pub fn depth(&self) -> usize {
+        let mut depth = 1;
+        let mut node = self.root;
+        while !self.nodes[node].leaf {
+            node = self.nodes[node].children[0];
+            depth += 1;
+        }
+        depth
+    }
+
+    /// returns the value stored for `key`.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let leaf = &self.nodes[self.find_leaf(key)];
+        leaf.keys.binary_search(key).ok().map(|i| &leaf.values[i])
+    }
+
+    /// returns a mutable reference to the value stored for `key`.
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let index = self.find_leaf(key);
+        let leaf = &mut self.nodes[index];
+        match leaf.keys.binary_search(key) {
+            Ok(i) => Some(&mut leaf.values[i]),
+            Err(_) => None,
+        }
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// insert `value` for `key`, returning the previous value if there was one.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let (old, split) = self.insert_at(self.root, key, value);
+        if let Some((separator, right)) = split {
+            let root = self.alloc(Node {
+                leaf: false,
+                keys: vec![separator],
+                values: Vec::new(),
+                children: vec![self.root, right],
+                next: NIL,
+            });
+            self.root = root;
+        }
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    /// remove `key`, returning its value if it was present.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let removed = self.remove_at(self.root, key);
+        let root = &self.nodes[self.root];
+        if !root.leaf && root.children.len() == 1 {
+            let old = self.root;
+            self.root = root.children[0];
+            self.release(old);
+        }
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    /// entry with the smallest key.
+    pub fn first(&self) -> Option<(&K, &V)> {
+        self.iter().next()
+    }
+
+    /// entry with the largest key.
+    pub fn last(&self) -> Option<(&K, &V)> {
+        let mut node = &self.nodes[self.root];
+        while !node.leaf {
+            node = &self.nodes[*node.children.last().unwrap()];
+        }
+        node.keys.last().zip(node.values.last())
+    }
+
+    /// iterate over the entries whose keys lie in `range`, in increasing key order.
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Range<'_, K, V, R> {
+        let (leaf, pos) = match range.start_bound() {
+            Bound::Unbounded => {
+                let mut node = self.root;
+                while !self.nodes[node].leaf {
+                    node = self.nodes[node].children[0];
+                }
+                (node, 0)
+            }
+            Bound::Included(start) => {
+                let leaf = self.find_leaf(start);
+                (leaf, self.nodes[leaf].keys.partition_point(|k| k < start))
+            }
+            Bound::Excluded(start) => {
+                let leaf = self.find_leaf(start);
+                (leaf, self.nodes[leaf].keys.partition_point(|k| k <= start))
+            }
+        };
+        Range {
+            tree: self,
+            leaf,
+            pos,
+            range,
+        }
+    }
+
+    /// iterate in increasing key order.
+    pub fn iter(&self) -> Range<'_, K, V, RangeFull> {
+        self.range(..)
+    }
+
+    // minimum occupancy of every node but the root.
+    fn min_occupancy(&self) -> usize {
+        self.fanout.div_ceil(2)
+    }
+
+    fn find_leaf(&self, key: &K) -> usize {
+        let mut node = self.root;
+        while !self.nodes[node].leaf {
+            let n = &self.nodes[node];
+            node = n.children[n.keys.partition_point(|k| k <= key)];
+        }
+        node
+    }
+
+    fn alloc(&mut self, node: Node<K, V>) -> usize {
+        match self.free.pop() {
+            Some(index) => {
+                self.nodes[index] = node;
+                index
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    fn release(&mut self, index: usize) {
+        self.nodes[index] = Node::leaf();
+        self.free.push(index);
+    }
+
+    // returns the previous value and, if the node overflowed, the separator and new right sibling.
+    fn insert_at(&mut self, index: usize, key: K, value: V) -> (Option<V>, Option<(K, usize)>) {
+        if self.nodes[index].leaf {
+            let node = &mut self.nodes[index];
+            match node.keys.binary_search(&key) {
+                Ok(i) => return (Some(std::mem::replace(&mut node.values[i], value)), None),
+                Err(i) => {
+                    node.keys.insert(i, key);
+                    node.values.insert(i, value);
+                }
+            }
+            if node.keys.len() <= self.fanout {
+                return (None, None);
+            }
+            let mid = node.keys.len() / 2;
+            let right = Node {
+                leaf: true,
+                keys: node.keys.split_off(mid),
+                values: node.values.split_off(mid),
+                children: Vec::new(),
+                next: node.next,
+            };
+            let separator = right.keys[0].clone();
+            let right = self.alloc(right);
+            self.nodes[index].next = right;
+            return (None, Some((separator, right)));
+        }
+
+        let slot = self.nodes[index].keys.partition_point(|k| *k <= key);
+        let child = self.nodes[index].children[slot];
+        let (old, split) = self.insert_at(child, key, value);
+        if let Some((separator, right)) = split {
+            let node = &mut self.nodes[index];
+            node.keys.insert(slot, separator);
+            node.children.insert(slot + 1, right);
+            if node.children.len() > self.fanout {
+                let mid = node.keys.len() / 2;
+                let mut keys = node.keys.split_off(mid);
+                let separator = keys.remove(0);
+                let right = Node {
+                    leaf: false,
+                    keys,
+                    values: Vec::new(),
+                    children: node.children.split_off(mid + 1),
+                    next: NIL,
+                };
+                return (old, Some((separator, self.alloc(right))));
+            }
+        }
+        (old, None)
+    }
+
+    fn remove_at(&mut self, index: usize, key: &K) -> Option<V> {
+        if self.nodes[index].leaf {
+            let node = &mut self.nodes[index];
+            return match node.keys.binary_search(key) {
+                Ok(i) => {
+                    node.keys.remove(i);
+                    Some(node.values.remove(i))
+                }
+                Err(_) => None,
+            };
+        }
+        let slot = self.nodes[index].keys.partition_point(|k| k <= key);
+        let child = self.nodes[index].children[slot];
+        let removed = self.remove_at(child, key);
+        if removed.is_some() && self.nodes[child].occupancy() < self.min_occupancy() {
+            self.fix_underflow(index, slot);
+        }
+        removed
+    }
+
+    // refill `children[slot]` of `parent` by borrowing from or merging with a sibling.
+    fn fix_underflow(&mut self, parent: usize, slot: usize) {
+        let min = self.min_occupancy();
+        let children = &self.nodes[parent].children;
+        let (left_slot, right_slot) = if slot > 0 {
+            (slot - 1, slot)
+        } else {
+            (slot, slot + 1)
+        };
+        let (left, right) = (children[left_slot], children[right_slot]);
+        // the sibling that would give entries away.
+        let donor = if left_slot == slot { right } else { left };
+        let spare = self.nodes[donor].occupancy() > min;
+
+        // lift the three nodes out of the arena to work on them together.
+        let mut p = std::mem::replace(&mut self.nodes[parent], Node::leaf());
+        let mut l = std::mem::replace(&mut self.nodes[left], Node::leaf());
+        let mut r = std::mem::replace(&mut self.nodes[right], Node::leaf());
+        if spare && donor == left {
+            borrow_from_left(&mut p, left_slot, &mut l, &mut r);
+        } else if spare {
+            borrow_from_right(&mut p, left_slot, &mut l, &mut r);
+        } else {
+            merge(&mut p, left_slot, &mut l, &mut r);
+        }
+        self.nodes[parent] = p;
+        self.nodes[left] = l;
+        if spare {
+            self.nodes[right] = r;
+        } else {
+            self.release(right);
+        }
+    }
+}
+
+// move the last entry of `l` to the front of its right sibling `r`.
+fn borrow_from_left<K: Clone, V>(
+    p: &mut Node<K, V>,
+    separator: usize,
+    l: &mut Node<K, V>,
+    r: &mut Node<K, V>,
+) {
+    if l.leaf {
+        r.keys.insert(0, l.keys.pop().unwrap());
+        r.values.insert(0, l.values.pop().unwrap());
+        p.keys[separator] = r.keys[0].clone();
+    } else {
+        let down = std::mem::replace(&mut p.keys[separator], l.keys.pop().unwrap());
+        r.keys.insert(0, down);
+        r.children.insert(0, l.children.pop().unwrap());
+    }
+}
+
+// move the first entry of `r` to the end of its left sibling `l`.
+fn borrow_from_right<K: Clone, V>(
+    p: &mut Node<K, V>,
+    separator: usize,
+    l: &mut Node<K, V>,
+    r: &mut Node<K, V>,
+) {
+    if l.leaf {
+        l.keys.push(r.keys.remove(0));
+        l.values.push(r.values.remove(0));
+        p.keys[separator] = r.keys[0].clone();
+    } else {
+        let down = std::mem::replace(&mut p.keys[separator], r.keys.remove(0));
+        l.keys.push(down);
+        l.children.push(r.children.remove(0));
+    }
+}
+
+// fold `r` into `l` and drop the separator between them.
+fn merge<K, V>(p: &mut Node<K, V>, separator: usize, l: &mut Node<K, V>, r: &mut Node<K, V>) {
+    let down = p.keys.remove(separator);
+    p.children.remove(separator + 1);
+    if l.leaf {
+        l.keys.append(&mut r.keys);
+        l.values.append(&mut r.values);
+        l.next = r.next;
+    } else {
+        l.keys.push(down);
+        l.keys.append(&mut r.keys);
+        l.children.append(&mut r.children);
+    }
+}
+
+impl<K: Ord + Clone, V> Default for BPlusTree<K, V> {
+    fn default() -> Self {
+        BPlusTree::new()
+    }
+}
+
+impl<K: Ord + Clone + fmt::Debug, V: fmt::Debug> fmt::Debug for BPlusTree<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Ord + Clone, V> std::iter::FromIterator<(K, V)> for BPlusTree<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut tree = BPlusTree::new();
+        iter.into_iter().for_each(|(k, v)| {
+            tree.insert(k, v);
+        });
+        tree
+    }
+}
+
+impl<K: Ord + Clone, V> OrderedMap<K, V> for BPlusTree<K, V> {
+    fn new() -> Self {
+        BPlusTree::new()
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn get(&self, key: &K) -> Option<&V> {
+        self.get(key)
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.insert(key, value)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        self.remove(key)
+    }
+
+    fn first(&self) -> Option<(&K, &V)> {
+        self.first()
+    }
+
+    fn last(&self) -> Option<(&K, &V)> {
+        self.last()
+    }
+
+    fn iter<'a>(&'a self) -> Box<dyn Iterator<Item = (&'a K, &'a V)> + 'a>
+    where
+        K: 'a,
+        V: 'a,
+    {
+        Box::new(self.iter())
+    }
+}
+
+/// iterator over a key range of a b+ tree, walking the leaf chain.
+pub struct Range<'a, K, V, R> {
+    tree: &'a BPlusTree<K, V>,
+    leaf: usize,
+    pos: usize,
+    range: R,
+}
+
+impl<'a, K: Ord, V, R: RangeBounds<K>> Iterator for Range<'a, K, V, R> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let tree = self.tree;
+        while self.leaf != NIL {
+            let node = &tree.nodes[self.leaf];
+            if self.pos >= node.keys.len() {
+                self.leaf = node.next;
+                self.pos = 0;
+                continue;
+            }
+            let key = &node.keys[self.pos];
+            let below_end = match self.range.end_bound() {
+                Bound::Included(end) => key <= end,
+                Bound::Excluded(end) => key < end,
+                Bound::Unbounded => true,
+            };
+            if !below_end {
+                self.leaf = NIL;
+                return None;
+            }
+            self.pos += 1;
+            return Some((key, &node.values[self.pos - 1]));
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+    use std::collections::BTreeMap;
+
+    // assert ordering, occupancy and uniform depth, returning the depth.
+    fn check<K: Ord + Clone, V>(tree: &BPlusTree<K, V>, index: usize, is_root: bool) -> usize {
+        let node = &tree.nodes[index];
+        assert!(node.keys.windows(2).all(|w| w[0] < w[1]));
+        assert!(node.occupancy() <= tree.fanout);
+        if !is_root {
+            assert!(node.occupancy() >= tree.min_occupancy());
+        }
+        if node.leaf {
+            assert_eq!(node.keys.len(), node.values.len());
+            return 1;
+        }
+        assert_eq!(node.children.len(), node.keys.len() + 1);
+        let depths: Vec<usize> = node
+            .children
+            .iter()
+            .map(|&c| check(tree, c, false))
+            .collect();
+        assert!(depths.windows(2).all(|w| w[0] == w[1]));
+        depths[0] + 1
+    }
+
+    #[test]
+    fn test_against_btree() {
+        for fanout in [3, 4, 5, 16].iter().copied() {
+            let mut rng = Rng::new(fanout as u64);
+            let mut tree = BPlusTree::with_fanout(fanout);
+            let mut model = BTreeMap::new();
+            for step in 0..3000 {
+                let key = rng.range(0..400);
+                if rng.chance(0.55) {
+                    assert_eq!(tree.insert(key, step), model.insert(key, step));
+                } else {
+                    assert_eq!(tree.remove(&key), model.remove(&key));
+                }
+                if step % 100 == 0 {
+                    check(&tree, tree.root, true);
+                }
+            }
+            check(&tree, tree.root, true);
+            assert_eq!(tree.len(), model.len());
+            assert!(tree.iter().eq(model.iter()));
+            assert_eq!(tree.first(), model.iter().next());
+            assert_eq!(tree.last(), model.iter().next_back());
+        }
+    }
+
+    #[test]
+    fn test_range_scan() {
+        let tree: BPlusTree<u32, u32> = (0..1000).map(|k| (k * 3, k)).collect();
+        let keys: Vec<u32> = tree.range(10..25).map(|(k, _)| *k).collect();
+        assert_eq!(keys, vec![12, 15, 18, 21, 24]);
+        let keys: Vec<u32> = tree
+            .range((Bound::Excluded(12), Bound::Included(18)))
+            .map(|(k, _)| *k)
+            .collect();
+        assert_eq!(keys, vec![15, 18]);
+        assert_eq!(tree.range(2990..).count(), 3);
+        assert_eq!(tree.range(..).count(), 1000);
+        assert_eq!(tree.range(5000..).next(), None);
+    }
+
+    #[test]
+    fn test_shallow_with_wide_nodes() {
+        let mut tree = BPlusTree::with_fanout(128);
+        for k in 0..100_000u32 {
+            tree.insert(k, ());
+        }
+        assert!(tree.depth() <= 4);
+        for k in 0..100_000u32 {
+            assert_eq!(tree.remove(&k), Some(()));
+        }
+        assert!(tree.is_empty());
+        assert_eq!(tree.depth(), 1);
+        assert_eq!(tree.first(), None);
+    }
+
+    #[test]
+    fn test_get_mut() {
+        let mut tree = BPlusTree::with_fanout(3);
+        for k in 0..20 {
+            tree.insert(k, k);
+        }
+        *tree.get_mut(&7).unwrap() = 70;
+        assert_eq!(tree.get(&7), Some(&70));
+        assert_eq!(tree.get_mut(&20), None);
+    }
+}
@@ -0,0 +1,280 @@
+use std::fmt;
+use std::iter::FromIterator;
+
+// words per superblock, and ones (or zeros) between select samples.
+const WORDS_PER_SUPER: usize = 8;
+const SAMPLE: usize = 4096;
+
+/// immutable bit vector with O(1) rank and near-O(1) select.
+///
+/// on top of the bits it keeps the count of ones before every 512-bit
+/// superblock and, relative to that, before every 64-bit word, plus the
+/// superblock of every 4096th one and zero: about 37% more space for
+/// `rank`, and a little more for `select`.
+#[derive(Clone)]
+pub struct BitVector {
+    words: Vec<u64>,
+    len: usize,
+    // ones before each superblock, with the total at the end.
+    supers: Vec<u64>,
+    // ones between the start of its superblock and each word.
+    blocks: Vec<u16>,
+    // the superblocks holding the (j * SAMPLE)-th one and zero.
+    ones_samples: Vec<usize>,
+    zeros_samples: Vec<usize>,
+}
+
+impl BitVector {
+    /// wrap little-endian words, bit `i` being `words[i / 64] >> (i % 64)`.
+    /// returns `None` if the word count does not match `len`.
+    pub fn from_words(mut words: Vec<u64>, len: usize) -> Option<Self> {
+        if words.len() != len.div_ceil(64) {
+            return None;
+        }
+        // clear the bits past the end, so they never count as ones.
+        if !len.is_multiple_of(64) {
+            *words.last_mut().unwrap() &= (1 << (len % 64)) - 1;
+        }
+        let mut supers = Vec::with_capacity(words.len() / WORDS_PER_SUPER + 2);
+        let mut blocks = Vec::with_capacity(words.len());
+        let mut total = 0u64;
+        for chunk in words.chunks(WORDS_PER_SUPER) {
+            supers.push(total);
+            let mut within = 0u16;
+            for word in chunk {
+                blocks.push(within);
+                within += word.count_ones() as u16;
+            }
+            total += u64::from(within);
+        }
+        supers.push(total);
+        let mut vector = BitVector {
+            words,
+            len,
+            supers,
+            blocks,
+            ones_samples: Vec::new(),
+            zeros_samples: Vec::new(),
+        };
+        vector.ones_samples = vector.samples(|v, s| v.supers[s] as usize);
+        vector.zeros_samples = vector.samples(|v, s| v.zeros_before_super(s));
+        Some(vector)
+    }
+
+    // for each multiple of SAMPLE below the total, the superblock holding
+    // the one (or zero) of that rank.
+    fn samples<F: Fn(&BitVector, usize) -> usize>(&self, before: F) -> Vec<usize> {
+        let mut samples = Vec::new();
+        let supers = self.supers.len() - 1;
+        for s in 0..supers {
+            while samples.len() * SAMPLE < before(self, s + 1) {
+                samples.push(s);
+            }
+        }
+        samples
+    }
+
+    fn zeros_before_super(&self, s: usize) -> usize {
+        (s * WORDS_PER_SUPER * 64).min(self.len) - self.supers[s] as usize
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn as_words(&self) -> &[u64] {
+        &self.words
+    }
+
+    pub fn get(&self, i: usize) -> Option<bool> {
+        if i < self.len {
+            Some(self.words[i / 64] >> (i % 64) & 1 == 1)
+        } else {
+            None
+        }
+    }
+
+    pub fn count_ones(&self) -> usize {
+        *self.supers.last().unwrap() as usize
+    }
+
+    pub fn count_zeros(&self) -> usize {
+        self.len - self.count_ones()
+    }
+
+    /// number of ones before position `i`, for `i` up to `len`.
+    pub fn rank1(&self, i: usize) -> usize {
+        assert!(i <= self.len, "position {} out of bounds", i);
+        let w = i / 64;
+        if w == self.words.len() {
+            return self.count_ones();
+        }
+        let partial = self.words[w] & ((1 << (i % 64)) - 1);
+        self.supers[w / WORDS_PER_SUPER] as usize
+            + usize::from(self.blocks[w])
+            + partial.count_ones() as usize
+    }
+
+    /// number of zeros before position `i`, for `i` up to `len`.
+    pub fn rank0(&self, i: usize) -> usize {
+        i - self.rank1(i)
+    }
+
+    /// position of the `k`-th one (0-based).
+    pub fn select1(&self, k: usize) -> Option<usize> {
+        if k >= self.count_ones() {
+            return None;
+        }
+        Some(self.select(k, &self.ones_samples, |v, s| v.supers[s] as usize, |w| w))
+    }
+
+    /// position of the `k`-th zero (0-based).
+    pub fn select0(&self, k: usize) -> Option<usize> {
+        if k >= self.count_zeros() {
+            return None;
+        }
+        Some(
+            self.select(k, &self.zeros_samples, BitVector::zeros_before_super, |w| {
+                !w
+            }),
+        )
+    }
+
+    fn select<B, W>(&self, k: usize, samples: &[usize], before: B, bits: W) -> usize
+    where
+        B: Fn(&BitVector, usize) -> usize,
+        W: Fn(u64) -> u64,
+    {
+        // the sampled superblocks bound a binary search for the last
+        // superblock with at most `k` before it.
+        let (mut lo, mut hi) = (
+            samples[k / SAMPLE],
+            samples
+                .get(k / SAMPLE + 1)
+                .map_or(self.supers.len() - 2, |&s| s),
+        );
+        while lo < hi {
+            let mid = (lo + hi).div_ceil(2);
+            if before(self, mid) <= k {
+                lo = mid;
+            } else {
+                hi = mid - 1;
+            }
+        }
+        let mut rest = k - before(self, lo);
+        let first = lo * WORDS_PER_SUPER;
+        for (w, &word) in self
+            .words
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(WORDS_PER_SUPER)
+        {
+            let word = bits(word);
+            let count = word.count_ones() as usize;
+            if rest < count {
+                return w * 64 + select_in_word(word, rest);
+            }
+            rest -= count;
+        }
+        unreachable!("select ran past its superblock")
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.len).map(move |i| self.words[i / 64] >> (i % 64) & 1 == 1)
+    }
+}
+
+// position of the `k`-th set bit of `word`, which has more than `k`.
+fn select_in_word(mut word: u64, k: usize) -> usize {
+    for _ in 0..k {
+        word &= word - 1;
+    }
+    word.trailing_zeros() as usize
+}
+
+impl FromIterator<bool> for BitVector {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        let mut words = Vec::new();
+        let mut len = 0usize;
+        for bit in iter {
+            if len.is_multiple_of(64) {
+                words.push(0);
+            }
+            if bit {
+                *words.last_mut().unwrap() |= 1 << (len % 64);
+            }
+            len += 1;
+        }
+        BitVector::from_words(words, len).unwrap()
+    }
+}
+
+impl fmt::Debug for BitVector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BitVector")
+            .field("len", &self.len)
+            .field("ones", &self.count_ones())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_against_naive() {
+        let mut rng = Rng::new(51);
+        // dense, sparse and all-zero stretches, to cross the samples.
+        for &(n, density) in &[
+            (0, 0.5),
+            (1, 1.0),
+            (100_000, 0.5),
+            (70_001, 0.02),
+            (9000, 0.0),
+        ] {
+            let bits: Vec<bool> = (0..n).map(|_| rng.chance(density)).collect();
+            let vector: BitVector = bits.iter().copied().collect();
+            assert_eq!(vector.len(), n);
+            assert!(vector.iter().eq(bits.iter().copied()));
+            let mut ones = 0;
+            let (mut one_positions, mut zero_positions) = (Vec::new(), Vec::new());
+            for (i, &bit) in bits.iter().enumerate() {
+                assert_eq!(vector.rank1(i), ones);
+                if bit {
+                    one_positions.push(i);
+                    ones += 1;
+                } else {
+                    zero_positions.push(i);
+                }
+            }
+            assert_eq!(vector.rank1(n), ones);
+            for (k, &p) in one_positions.iter().enumerate() {
+                assert_eq!(vector.select1(k), Some(p));
+            }
+            for (k, &p) in zero_positions.iter().enumerate() {
+                assert_eq!(vector.select0(k), Some(p));
+            }
+            assert_eq!(vector.select1(ones), None);
+            assert_eq!(vector.select0(n - ones), None);
+        }
+    }
+
+    #[test]
+    fn test_from_words() {
+        assert!(BitVector::from_words(vec![0; 2], 200).is_none());
+        // bits past `len` are ignored.
+        let vector = BitVector::from_words(vec![u64::MAX, u64::MAX], 70).unwrap();
+        assert_eq!((vector.count_ones(), vector.count_zeros()), (70, 0));
+        assert_eq!(vector.rank0(70), 0);
+        assert_eq!(vector.get(69), Some(true));
+        assert_eq!(vector.get(70), None);
+        assert_eq!(vector.select1(69), Some(69));
+    }
+}
@@ -0,0 +1,226 @@
+use std::convert::TryInto;
+use std::f64::consts::LN_2;
+use std::fmt;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+use crate::hash::hash_pair;
+
+/// number of bits for `expected_items` entries at false-positive rate
+/// `fp_rate`: `m = -n ln p / (ln 2)^2`.
+pub fn optimal_num_bits(expected_items: usize, fp_rate: f64) -> usize {
+    assert!(
+        fp_rate > 0.0 && fp_rate < 1.0,
+        "false-positive rate must lie in (0, 1)"
+    );
+    let n = expected_items.max(1) as f64;
+    ((-n * fp_rate.ln() / (LN_2 * LN_2)).ceil() as usize).max(1)
+}
+
+/// number of hash functions minimising false positives for `num_bits` bits
+/// and `expected_items` entries: `k = m / n ln 2`.
+pub fn optimal_num_hashes(num_bits: usize, expected_items: usize) -> u32 {
+    let k = num_bits as f64 / expected_items.max(1) as f64 * LN_2;
+    (k.round() as u32).max(1)
+}
+
+/// the `num_hashes` slots probed for `item` among `num_slots`, by
+/// kirsch-mitzenmacher double hashing.
+pub(crate) fn probes<T: Hash + ?Sized>(
+    item: &T,
+    num_hashes: u32,
+    num_slots: usize,
+) -> impl Iterator<Item = usize> {
+    let (h1, h2) = hash_pair(item);
+    let m = num_slots as u64;
+    (0..u64::from(num_hashes)).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % m) as usize)
+}
+
+/// set-membership filter with false positives but no false negatives.
+///
+/// hashes are stable across processes, so a filter exported with
+/// [`BloomFilter::to_bytes`] can be loaded elsewhere, provided `T` hashes
+/// identically there.
+pub struct BloomFilter<T: ?Sized> {
+    words: Vec<u64>,
+    num_bits: usize,
+    num_hashes: u32,
+    marker: PhantomData<fn(&T)>,
+}
+
+impl<T: Hash + ?Sized> BloomFilter<T> {
+    /// create an empty filter with `num_bits` bits and `num_hashes` probes.
+    pub fn new(num_bits: usize, num_hashes: u32) -> Self {
+        assert!(num_bits > 0 && num_hashes > 0, "filter must not be empty");
+        BloomFilter {
+            words: vec![0; num_bits.div_ceil(64)],
+            num_bits,
+            num_hashes,
+            marker: PhantomData,
+        }
+    }
+
+    /// create a filter sized for `expected_items` entries at `fp_rate`.
+    pub fn with_rate(expected_items: usize, fp_rate: f64) -> Self {
+        let num_bits = optimal_num_bits(expected_items, fp_rate);
+        BloomFilter::new(num_bits, optimal_num_hashes(num_bits, expected_items))
+    }
+
+    pub fn num_bits(&self) -> usize {
+        self.num_bits
+    }
+
+    pub fn num_hashes(&self) -> u32 {
+        self.num_hashes
+    }
+
+    pub fn insert(&mut self, item: &T) {
+        for bit in probes(item, self.num_hashes, self.num_bits) {
+            self.words[bit / 64] |= 1 << (bit % 64);
+        }
+    }
+
+    /// check whether `item` may have been inserted.
+    pub fn contains(&self, item: &T) -> bool {
+        probes(item, self.num_hashes, self.num_bits)
+            .all(|bit| self.words[bit / 64] >> (bit % 64) & 1 == 1)
+    }
+
+    pub fn clear(&mut self) {
+        for word in &mut self.words {
+            *word = 0;
+        }
+    }
+
+    /// number of set bits.
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// false-positive rate implied by the current fill, `(ones / m)^k`.
+    pub fn estimated_fp_rate(&self) -> f64 {
+        (self.count_ones() as f64 / self.num_bits as f64).powi(self.num_hashes as i32)
+    }
+
+    /// add every item of `other`, which must have the same parameters.
+    pub fn union(&mut self, other: &BloomFilter<T>) {
+        assert!(
+            self.num_bits == other.num_bits && self.num_hashes == other.num_hashes,
+            "filters have different parameters"
+        );
+        for (word, &o) in self.words.iter_mut().zip(&other.words) {
+            *word |= o;
+        }
+    }
+
+    /// the bit set as little-endian words; bit `i` is `words[i / 64] >> (i % 64)`.
+    pub fn as_words(&self) -> &[u64] {
+        &self.words
+    }
+
+    /// rebuild a filter from [`BloomFilter::as_words`] and its parameters.
+    /// returns `None` if the word count does not match `num_bits`.
+    pub fn from_words(words: Vec<u64>, num_bits: usize, num_hashes: u32) -> Option<Self> {
+        if num_bits == 0 || num_hashes == 0 || words.len() != num_bits.div_ceil(64) {
+            return None;
+        }
+        Some(BloomFilter {
+            words,
+            num_bits,
+            num_hashes,
+            marker: PhantomData,
+        })
+    }
+
+    /// serialize as `num_bits: u64`, `num_hashes: u32`, then the words, all
+    /// little-endian.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(12 + 8 * self.words.len());
+        bytes.extend_from_slice(&(self.num_bits as u64).to_le_bytes());
+        bytes.extend_from_slice(&self.num_hashes.to_le_bytes());
+        for word in &self.words {
+            bytes.extend_from_slice(&word.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// parse the output of [`BloomFilter::to_bytes`], returning `None` on
+    /// malformed input.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < 12 || !(bytes.len() - 12).is_multiple_of(8) {
+            return None;
+        }
+        let num_bits = u64::from_le_bytes(bytes[..8].try_into().ok()?);
+        let num_hashes = u32::from_le_bytes(bytes[8..12].try_into().ok()?);
+        let words = bytes[12..]
+            .chunks_exact(8)
+            .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()))
+            .collect();
+        BloomFilter::from_words(words, num_bits.try_into().ok()?, num_hashes)
+    }
+}
+
+impl<T: ?Sized> Clone for BloomFilter<T> {
+    fn clone(&self) -> Self {
+        BloomFilter {
+            words: self.words.clone(),
+            num_bits: self.num_bits,
+            num_hashes: self.num_hashes,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for BloomFilter<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BloomFilter")
+            .field("num_bits", &self.num_bits)
+            .field("num_hashes", &self.num_hashes)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sizing() {
+        // 1% at 1000 items needs about 9.6 bits per item and 7 hashes.
+        let bits = optimal_num_bits(1000, 0.01);
+        assert_eq!(bits, 9586);
+        assert_eq!(optimal_num_hashes(bits, 1000), 7);
+        let filter = BloomFilter::<str>::with_rate(1000, 0.01);
+        assert_eq!((filter.num_bits(), filter.num_hashes()), (9586, 7));
+    }
+
+    #[test]
+    fn test_no_false_negatives_and_rate() {
+        let mut filter = BloomFilter::with_rate(10_000, 0.01);
+        for i in 0..10_000u64 {
+            filter.insert(&i);
+        }
+        assert!((0..10_000u64).all(|i| filter.contains(&i)));
+        let false_positives = (10_000..110_000u64).filter(|i| filter.contains(i)).count();
+        let rate = false_positives as f64 / 100_000.0;
+        assert!(rate < 0.015, "false-positive rate {}", rate);
+        assert!((filter.estimated_fp_rate() - 0.01).abs() < 0.005);
+    }
+
+    #[test]
+    fn test_export_import_and_union() {
+        let mut a = BloomFilter::<str>::new(1000, 4);
+        let mut b = a.clone();
+        a.insert("apple");
+        b.insert("banana");
+        let restored = BloomFilter::<str>::from_bytes(&a.to_bytes()).unwrap();
+        assert_eq!(restored.as_words(), a.as_words());
+        assert!(restored.contains("apple"));
+        assert!(BloomFilter::<str>::from_bytes(&a.to_bytes()[..20]).is_none());
+        assert!(BloomFilter::<str>::from_words(vec![0; 3], 1000, 4).is_none());
+        a.union(&b);
+        assert!(a.contains("apple") && a.contains("banana"));
+        a.clear();
+        assert_eq!(a.count_ones(), 0);
+    }
+}
@@ -0,0 +1,319 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+
+use super::list::{List, Slab};
+use super::{Cache, CacheStats};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Which {
+    // resident, seen once recently.
+    T1,
+    // resident, seen at least twice recently.
+    T2,
+    // keys recently evicted from t1 and t2.
+    B1,
+    B2,
+}
+
+#[derive(Clone, Debug)]
+struct Entry<K, V> {
+    key: K,
+    // none for ghost entries.
+    value: Option<V>,
+    which: Which,
+}
+
+/// adaptive replacement cache (megiddo and modha): splits its capacity
+/// between entries used once and entries used repeatedly, and shifts the
+/// split using the keys it recently evicted from each side. a one-off scan
+/// therefore only churns the first part, unlike in an lru.
+///
+/// besides the `capacity` resident entries it remembers up to `capacity`
+/// evicted keys, without their values.
+#[derive(Clone)]
+pub struct ArcCache<K, V> {
+    map: HashMap<K, usize>,
+    entries: Slab<Entry<K, V>>,
+    t1: List,
+    t2: List,
+    b1: List,
+    b2: List,
+    // target size of t1.
+    target: usize,
+    capacity: usize,
+    stats: CacheStats,
+}
+
+impl<K: Hash + Eq + Clone, V> ArcCache<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity must be positive");
+        ArcCache {
+            map: HashMap::with_capacity(2 * capacity),
+            entries: Slab::new(),
+            t1: List::new(),
+            t2: List::new(),
+            b1: List::new(),
+            b2: List::new(),
+            target: 0,
+            capacity,
+            stats: CacheStats::default(),
+        }
+    }
+
+    /// how many resident entries the cache currently aims to reserve for
+    /// keys seen only once.
+    pub fn target(&self) -> usize {
+        self.target
+    }
+
+    /// number of evicted keys remembered.
+    pub fn ghost_len(&self) -> usize {
+        self.b1.len() + self.b2.len()
+    }
+
+    fn list(&mut self, which: Which) -> &mut List {
+        match which {
+            Which::T1 => &mut self.t1,
+            Which::T2 => &mut self.t2,
+            Which::B1 => &mut self.b1,
+            Which::B2 => &mut self.b2,
+        }
+    }
+
+    fn unlink(&mut self, i: usize) {
+        let which = self.entries.get(i).which;
+        // the list and the slab are separate fields.
+        let mut list = *self.list(which);
+        list.unlink(&mut self.entries, i);
+        *self.list(which) = list;
+    }
+
+    fn link(&mut self, i: usize, which: Which) {
+        self.entries.get_mut(i).which = which;
+        let mut list = *self.list(which);
+        list.push_front(&mut self.entries, i);
+        *self.list(which) = list;
+    }
+
+    fn move_to(&mut self, i: usize, which: Which) {
+        self.unlink(i);
+        self.link(i, which);
+    }
+
+    fn remove_at(&mut self, i: usize) -> Entry<K, V> {
+        self.unlink(i);
+        let entry = self.entries.remove(i);
+        self.map.remove(&entry.key);
+        entry
+    }
+
+    fn drop_lru(&mut self, which: Which) {
+        if let Some(i) = self.list(which).back() {
+            self.remove_at(i);
+        }
+    }
+
+    // evict a resident entry into its ghost list, from t1 if it is over
+    // target (or `in_b2` and at target) and from t2 otherwise.
+    fn replace(&mut self, in_b2: bool) {
+        let len = self.t1.len();
+        let from_t1 = len > 0 && (len > self.target || (in_b2 && len == self.target));
+        let (from, to) = if from_t1 || self.t2.is_empty() {
+            (Which::T1, Which::B1)
+        } else {
+            (Which::T2, Which::B2)
+        };
+        if let Some(i) = self.list(from).back() {
+            self.entries.get_mut(i).value = None;
+            self.move_to(i, to);
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> Cache<K, V> for ArcCache<K, V> {
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn len(&self) -> usize {
+        self.t1.len() + self.t2.len()
+    }
+
+    fn get(&mut self, key: &K) -> Option<&V> {
+        let i = match self.map.get(key) {
+            Some(&i) if self.entries.get(i).value.is_some() => i,
+            _ => {
+                self.stats.misses += 1;
+                return None;
+            }
+        };
+        self.stats.hits += 1;
+        self.move_to(i, Which::T2);
+        self.entries.get(i).value.as_ref()
+    }
+
+    fn peek(&self, key: &K) -> Option<&V> {
+        let &i = self.map.get(key)?;
+        self.entries.get(i).value.as_ref()
+    }
+
+    fn put(&mut self, key: K, value: V) -> Option<V> {
+        let c = self.capacity;
+        if let Some(&i) = self.map.get(&key) {
+            let which = self.entries.get(i).which;
+            match which {
+                Which::T1 | Which::T2 => {
+                    self.move_to(i, Which::T2);
+                    return self.entries.get_mut(i).value.replace(value);
+                }
+                Which::B1 => {
+                    let step = (self.b2.len() / self.b1.len()).max(1);
+                    self.target = (self.target + step).min(c);
+                }
+                Which::B2 => {
+                    let step = (self.b1.len() / self.b2.len()).max(1);
+                    self.target = self.target.saturating_sub(step);
+                }
+            }
+            // a ghost hit: the key comes back as a repeated one.
+            if self.len() >= c {
+                self.replace(which == Which::B2);
+            }
+            self.entries.get_mut(i).value = Some(value);
+            self.move_to(i, Which::T2);
+            return None;
+        }
+        if self.t1.len() + self.b1.len() >= c {
+            if self.t1.len() < c {
+                self.drop_lru(Which::B1);
+                if self.len() >= c {
+                    self.replace(false);
+                }
+            } else {
+                self.drop_lru(Which::T1);
+            }
+        } else if self.len() + self.ghost_len() >= c {
+            if self.len() + self.ghost_len() >= 2 * c {
+                self.drop_lru(Which::B2);
+            }
+            if self.len() >= c {
+                self.replace(false);
+            }
+        }
+        let i = self.entries.insert(Entry {
+            key: key.clone(),
+            value: Some(value),
+            which: Which::T1,
+        });
+        self.link(i, Which::T1);
+        self.map.insert(key, i);
+        None
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        let &i = self.map.get(key)?;
+        self.remove_at(i).value
+    }
+
+    fn clear(&mut self) {
+        self.map.clear();
+        self.entries.clear();
+        self.t1 = List::new();
+        self.t2 = List::new();
+        self.b1 = List::new();
+        self.b2 = List::new();
+        self.target = 0;
+    }
+
+    fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    fn reset_stats(&mut self) {
+        self.stats = CacheStats::default();
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for ArcCache<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.map.values().filter_map(|&i| {
+                let entry = self.entries.get(i);
+                entry.value.as_ref().map(|v| (&entry.key, v))
+            }))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::lru::LruCache;
+    use crate::rng::Rng;
+
+    // a warm hot set, a long scan of one-off keys, then the hot set again.
+    fn hot_hits_after_scan(cache: &mut dyn Cache<u32, u32>) -> usize {
+        for _ in 0..2 {
+            for key in 0..4 {
+                if cache.get(&key).is_none() {
+                    cache.put(key, key);
+                }
+            }
+        }
+        for key in 100..200 {
+            cache.put(key, key);
+        }
+        (0..4).filter(|key| cache.get(key).is_some()).count()
+    }
+
+    #[test]
+    fn test_scan_resistance() {
+        let mut arc = ArcCache::new(4);
+        let arc_hits = hot_hits_after_scan(&mut arc);
+        let lru_hits = hot_hits_after_scan(&mut LruCache::new(4));
+        // the first scanned key displaces one hot key; the rest stay.
+        assert_eq!(arc_hits, 3);
+        assert_eq!(lru_hits, 0);
+        assert_eq!(arc.stats().hits, 4 + 3);
+        assert_eq!(arc.stats().misses, 4 + 1);
+        assert!((arc.stats().hit_rate() - 7.0 / 12.0).abs() < 1e-12);
+        assert_eq!(arc.len(), 4);
+        assert!(arc.ghost_len() <= 4);
+    }
+
+    #[test]
+    fn test_adaptation() {
+        let mut cache = ArcCache::new(2);
+        cache.put(1, "a");
+        cache.put(2, "b");
+        cache.get(&2);
+        // 1 is evicted into the first ghost list.
+        cache.put(3, "c");
+        assert_eq!(cache.peek(&1), None);
+        assert_eq!(cache.target(), 0);
+        // and its return grows the share for keys seen once.
+        assert_eq!(cache.put(1, "A"), None);
+        assert_eq!(cache.target(), 1);
+        assert_eq!(cache.get(&1), Some(&"A"));
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.put(1, "AA"), Some("A"));
+        assert_eq!(cache.remove(&1), Some("AA"));
+        cache.clear();
+        assert!(cache.is_empty() && cache.ghost_len() == 0);
+        cache.reset_stats();
+        assert_eq!(cache.stats(), CacheStats::default());
+        let mut rng = Rng::new(35);
+        let mut cache = ArcCache::new(5);
+        for _ in 0..10_000 {
+            let key = rng.below(12);
+            match cache.get(&key) {
+                Some(&v) => assert_eq!(v, key),
+                None => assert_eq!(cache.put(key, key), None),
+            }
+            assert!(cache.len() <= 5 && cache.len() + cache.ghost_len() <= 10);
+            assert!(cache.b2.len() <= 5 && cache.t1.len() + cache.b1.len() <= 5);
+        }
+        assert_eq!(cache.map.len(), cache.len() + cache.ghost_len());
+    }
+}
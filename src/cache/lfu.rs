@@ -0,0 +1,233 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+
+use super::list::{List, Slab};
+use super::{Cache, CacheStats};
+
+#[derive(Clone, Debug)]
+struct Entry<K, V> {
+    key: K,
+    value: V,
+    uses: u64,
+}
+
+/// least-frequently-used cache: when full, a new entry replaces the one
+/// used the fewest times, and among those the least recently used. `get`
+/// and `put` are O(1).
+///
+/// every entry sits in the list for its use count, most recent first, and
+/// the cache tracks the smallest count that has a list.
+#[derive(Clone)]
+pub struct LfuCache<K, V> {
+    map: HashMap<K, usize>,
+    entries: Slab<Entry<K, V>>,
+    by_uses: HashMap<u64, List>,
+    min_uses: u64,
+    capacity: usize,
+    stats: CacheStats,
+}
+
+impl<K: Hash + Eq + Clone, V> LfuCache<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity must be positive");
+        LfuCache {
+            map: HashMap::with_capacity(capacity),
+            entries: Slab::new(),
+            by_uses: HashMap::new(),
+            min_uses: 0,
+            capacity,
+            stats: CacheStats::default(),
+        }
+    }
+
+    /// number of times `key` was put or read since it was inserted.
+    pub fn frequency(&self, key: &K) -> Option<u64> {
+        self.map.get(key).map(|&i| self.entries.get(i).uses)
+    }
+
+    fn unlink(&mut self, i: usize) {
+        let uses = self.entries.get(i).uses;
+        let list = self.by_uses.get_mut(&uses).unwrap();
+        list.unlink(&mut self.entries, i);
+        if list.is_empty() {
+            self.by_uses.remove(&uses);
+            if self.min_uses == uses {
+                self.min_uses += 1;
+            }
+        }
+    }
+
+    fn link(&mut self, i: usize) {
+        let uses = self.entries.get(i).uses;
+        self.by_uses
+            .entry(uses)
+            .or_insert_with(List::new)
+            .push_front(&mut self.entries, i);
+    }
+
+    fn touch(&mut self, i: usize) {
+        self.unlink(i);
+        self.entries.get_mut(i).uses += 1;
+        self.link(i);
+    }
+
+    fn remove_at(&mut self, i: usize) -> Entry<K, V> {
+        self.unlink(i);
+        let entry = self.entries.remove(i);
+        self.map.remove(&entry.key);
+        entry
+    }
+
+    /// returns the entry that would be evicted next.
+    pub fn peek_lfu(&self) -> Option<(&K, &V)> {
+        let i = self.by_uses.get(&self.min_uses)?.back()?;
+        let entry = self.entries.get(i);
+        Some((&entry.key, &entry.value))
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> Cache<K, V> for LfuCache<K, V> {
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    fn get(&mut self, key: &K) -> Option<&V> {
+        let i = match self.map.get(key) {
+            Some(&i) => i,
+            None => {
+                self.stats.misses += 1;
+                return None;
+            }
+        };
+        self.stats.hits += 1;
+        self.touch(i);
+        Some(&self.entries.get(i).value)
+    }
+
+    fn peek(&self, key: &K) -> Option<&V> {
+        self.map.get(key).map(|&i| &self.entries.get(i).value)
+    }
+
+    fn put(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(&i) = self.map.get(&key) {
+            self.touch(i);
+            return Some(std::mem::replace(&mut self.entries.get_mut(i).value, value));
+        }
+        if self.map.len() == self.capacity {
+            let victim = self.by_uses[&self.min_uses].back().unwrap();
+            self.remove_at(victim);
+        }
+        let i = self.entries.insert(Entry {
+            key: key.clone(),
+            value,
+            uses: 1,
+        });
+        self.link(i);
+        self.min_uses = 1;
+        self.map.insert(key, i);
+        None
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        let i = *self.map.get(key)?;
+        let value = self.remove_at(i).value;
+        // the smallest count may now be anywhere above the removed one.
+        if !self.by_uses.contains_key(&self.min_uses) {
+            self.min_uses = self.by_uses.keys().copied().min().unwrap_or(0);
+        }
+        Some(value)
+    }
+
+    fn clear(&mut self) {
+        self.map.clear();
+        self.entries.clear();
+        self.by_uses.clear();
+        self.min_uses = 0;
+    }
+
+    fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    fn reset_stats(&mut self) {
+        self.stats = CacheStats::default();
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for LfuCache<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.map.values().map(|&i| {
+                let entry = self.entries.get(i);
+                (&entry.key, &entry.value)
+            }))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::lru::LruCache;
+
+    #[test]
+    fn test_eviction() {
+        let mut cache = LfuCache::new(3);
+        cache.put("a", 1);
+        cache.put("b", 2);
+        cache.put("c", 3);
+        cache.get(&"a");
+        cache.get(&"a");
+        cache.get(&"b");
+        assert_eq!(cache.frequency(&"a"), Some(3));
+        // c has the fewest uses.
+        cache.put("d", 4);
+        assert!(!cache.contains_key(&"c"));
+        // b and d tie after this; d is older.
+        cache.get(&"d");
+        cache.get(&"b");
+        assert_eq!(cache.peek_lfu(), Some((&"d", &4)));
+        cache.put("e", 5);
+        assert!(!cache.contains_key(&"d"));
+        assert_eq!(cache.put("e", 50), Some(5));
+        assert_eq!(cache.frequency(&"e"), Some(2));
+        assert_eq!(cache.remove(&"b"), Some(2));
+        assert_eq!(cache.peek_lfu(), Some((&"e", &50)));
+        cache.clear();
+        assert!(cache.is_empty() && cache.peek_lfu().is_none());
+    }
+
+    // three hot keys read every round, followed by two one-off keys.
+    fn hits(cache: &mut dyn Cache<u32, u32>) -> usize {
+        for key in 0..3 {
+            cache.put(key, key);
+            cache.get(&key);
+        }
+        let mut hits = 0;
+        for round in 0..1000u32 {
+            for key in [0, 1, 2, 1000 + round, 2000 + round] {
+                if cache.get(&key).is_some() {
+                    hits += 1;
+                } else {
+                    cache.put(key, key);
+                }
+            }
+        }
+        hits
+    }
+
+    #[test]
+    fn test_swap_policies() {
+        // the one-off keys flush the hot set from the lru but not the lfu.
+        let lfu = hits(&mut LfuCache::new(4));
+        let lru = hits(&mut LruCache::new(4));
+        assert_eq!(lfu, 3 * 1000);
+        // only the first round, right after the warm-up, hits.
+        assert_eq!(lru, 3);
+    }
+}
@@ -0,0 +1,198 @@
+// doubly linked lists threaded through a shared slab, so an entry can be
+// unlinked or moved between lists in O(1) by its index.
+
+pub(crate) const NIL: usize = usize::MAX;
+
+#[derive(Clone, Debug)]
+struct Node<T> {
+    value: Option<T>,
+    prev: usize,
+    next: usize,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Slab<T> {
+    nodes: Vec<Node<T>>,
+    free: Vec<usize>,
+}
+
+impl<T> Slab<T> {
+    pub(crate) fn new() -> Self {
+        Slab {
+            nodes: Vec::new(),
+            free: Vec::new(),
+        }
+    }
+
+    pub(crate) fn insert(&mut self, value: T) -> usize {
+        let node = Node {
+            value: Some(value),
+            prev: NIL,
+            next: NIL,
+        };
+        match self.free.pop() {
+            Some(i) => {
+                self.nodes[i] = node;
+                i
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    // the node must already be unlinked from its list.
+    pub(crate) fn remove(&mut self, i: usize) -> T {
+        self.free.push(i);
+        self.nodes[i].value.take().unwrap()
+    }
+
+    pub(crate) fn get(&self, i: usize) -> &T {
+        self.nodes[i].value.as_ref().unwrap()
+    }
+
+    pub(crate) fn get_mut(&mut self, i: usize) -> &mut T {
+        self.nodes[i].value.as_mut().unwrap()
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.nodes.clear();
+        self.free.clear();
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct List {
+    head: usize,
+    tail: usize,
+    len: usize,
+}
+
+impl List {
+    pub(crate) fn new() -> Self {
+        List {
+            head: NIL,
+            tail: NIL,
+            len: 0,
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub(crate) fn back(&self) -> Option<usize> {
+        Some(self.tail).filter(|&i| i != NIL)
+    }
+
+    pub(crate) fn push_front<T>(&mut self, slab: &mut Slab<T>, i: usize) {
+        slab.nodes[i].prev = NIL;
+        slab.nodes[i].next = self.head;
+        if self.head == NIL {
+            self.tail = i;
+        } else {
+            slab.nodes[self.head].prev = i;
+        }
+        self.head = i;
+        self.len += 1;
+    }
+
+    pub(crate) fn unlink<T>(&mut self, slab: &mut Slab<T>, i: usize) {
+        let (prev, next) = (slab.nodes[i].prev, slab.nodes[i].next);
+        if prev == NIL {
+            self.head = next;
+        } else {
+            slab.nodes[prev].next = next;
+        }
+        if next == NIL {
+            self.tail = prev;
+        } else {
+            slab.nodes[next].prev = prev;
+        }
+        self.len -= 1;
+    }
+
+    // indices from front to back.
+    pub(crate) fn iter<'a, T>(&self, slab: &'a Slab<T>) -> impl Iterator<Item = usize> + 'a {
+        let mut i = self.head;
+        std::iter::from_fn(move || {
+            if i == NIL {
+                return None;
+            }
+            let current = i;
+            i = slab.nodes[i].next;
+            Some(current)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+    use std::collections::VecDeque;
+
+    #[test]
+    fn test_against_model() {
+        // three lists sharing one slab, against a deque for each.
+        let mut rng = Rng::new(115);
+        let mut slab = Slab::new();
+        let mut lists = [List::new(); 3];
+        let mut model: Vec<VecDeque<usize>> = vec![VecDeque::new(); 3];
+        // the value stored at each live slab index.
+        let mut live: Vec<(usize, u64)> = Vec::new();
+        for step in 0..5000u64 {
+            let which = rng.index(3);
+            match rng.index(4) {
+                0 | 1 => {
+                    let i = slab.insert(step);
+                    lists[which].push_front(&mut slab, i);
+                    model[which].push_front(i);
+                    live.push((i, step));
+                }
+                2 if !live.is_empty() => {
+                    // unlink from the middle, then free or move elsewhere.
+                    let (i, value) = live[rng.index(live.len())];
+                    let from = (0..3).find(|&l| model[l].contains(&i)).unwrap();
+                    lists[from].unlink(&mut slab, i);
+                    model[from].retain(|&j| j != i);
+                    if rng.chance(0.5) {
+                        assert_eq!(slab.remove(i), value);
+                        live.retain(|&(j, _)| j != i);
+                    } else {
+                        lists[which].push_front(&mut slab, i);
+                        model[which].push_front(i);
+                    }
+                }
+                _ => {
+                    if let Some(i) = lists[which].back() {
+                        *slab.get_mut(i) += 1;
+                        live.iter_mut().find(|(j, _)| *j == i).unwrap().1 += 1;
+                    }
+                }
+            }
+            for (list, expected) in lists.iter().zip(&model) {
+                assert_eq!(list.len(), expected.len());
+                assert_eq!(list.is_empty(), expected.is_empty());
+                assert_eq!(list.back(), expected.back().copied());
+                assert!(list.iter(&slab).eq(expected.iter().copied()));
+            }
+        }
+        for &(i, value) in &live {
+            assert_eq!(*slab.get(i), value);
+        }
+        // freed slots are reused before the slab grows.
+        let used = slab.nodes.len();
+        let i = slab.insert(0);
+        slab.remove(i);
+        assert_eq!(slab.insert(1), i);
+        assert!(slab.nodes.len() <= used + 1);
+        slab.clear();
+        assert!(slab.nodes.is_empty() && slab.free.is_empty());
+    }
+}
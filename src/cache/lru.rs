@@ -0,0 +1,271 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use std::time::Duration;
+
+use super::list::{List, Slab};
+use super::{Cache, CacheStats};
+use crate::clock::{Clock, SystemClock};
+
+#[derive(Clone, Debug)]
+struct Entry<K, V> {
+    key: K,
+    value: V,
+    // time after which the entry is gone, if it has a ttl.
+    expires: Option<Duration>,
+}
+
+/// least-recently-used cache: when full, a new entry replaces the one that
+/// was read or written longest ago. all operations are O(1).
+///
+/// entries put with [`LruCache::put_with_ttl`] expire once the clock passes
+/// their deadline. expired entries are invisible to every lookup and are
+/// dropped when touched, evicted, or purged; until then they still count
+/// towards [`Cache::len`].
+#[derive(Clone)]
+pub struct LruCache<K, V, C = SystemClock> {
+    map: HashMap<K, usize>,
+    entries: Slab<Entry<K, V>>,
+    // most recently used first.
+    order: List,
+    capacity: usize,
+    clock: C,
+    stats: CacheStats,
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        LruCache::with_clock(capacity, SystemClock::new())
+    }
+}
+
+impl<K: Hash + Eq + Clone, V, C: Clock> LruCache<K, V, C> {
+    /// create a cache that reads time for ttls from `clock`.
+    pub fn with_clock(capacity: usize, clock: C) -> Self {
+        assert!(capacity > 0, "capacity must be positive");
+        LruCache {
+            map: HashMap::with_capacity(capacity),
+            entries: Slab::new(),
+            order: List::new(),
+            capacity,
+            clock,
+            stats: CacheStats::default(),
+        }
+    }
+
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    fn is_expired(&self, i: usize) -> bool {
+        self.entries
+            .get(i)
+            .expires
+            .is_some_and(|t| self.clock.now() >= t)
+    }
+
+    fn remove_at(&mut self, i: usize) -> Entry<K, V> {
+        self.order.unlink(&mut self.entries, i);
+        let entry = self.entries.remove(i);
+        self.map.remove(&entry.key);
+        entry
+    }
+
+    // the live entry for `key`, dropping it if it has expired.
+    fn find(&mut self, key: &K) -> Option<usize> {
+        let i = *self.map.get(key)?;
+        if self.is_expired(i) {
+            self.remove_at(i);
+            return None;
+        }
+        Some(i)
+    }
+
+    fn insert(&mut self, key: K, value: V, expires: Option<Duration>) -> Option<V> {
+        if let Some(i) = self.find(&key) {
+            self.order.unlink(&mut self.entries, i);
+            self.order.push_front(&mut self.entries, i);
+            let entry = self.entries.get_mut(i);
+            entry.expires = expires;
+            return Some(std::mem::replace(&mut entry.value, value));
+        }
+        if self.map.len() == self.capacity {
+            let lru = self.order.back().unwrap();
+            self.remove_at(lru);
+        }
+        let i = self.entries.insert(Entry {
+            key: key.clone(),
+            value,
+            expires,
+        });
+        self.order.push_front(&mut self.entries, i);
+        self.map.insert(key, i);
+        None
+    }
+
+    /// insert `value` for `key`, to expire `ttl` from now.
+    pub fn put_with_ttl(&mut self, key: K, value: V, ttl: Duration) -> Option<V> {
+        let expires = self.clock.now() + ttl;
+        self.insert(key, value, Some(expires))
+    }
+
+    /// time left before `key` expires; None if it is absent or has no ttl.
+    pub fn ttl(&self, key: &K) -> Option<Duration> {
+        let &i = self.map.get(key)?;
+        let expires = self.entries.get(i).expires?;
+        expires
+            .checked_sub(self.clock.now())
+            .filter(|d| *d > Duration::ZERO)
+    }
+
+    /// returns the least recently used entry, which is evicted next.
+    pub fn peek_lru(&self) -> Option<(&K, &V)> {
+        self.iter().last()
+    }
+
+    /// drop every expired entry, returning how many there were.
+    pub fn purge_expired(&mut self) -> usize {
+        let expired: Vec<usize> = self
+            .order
+            .iter(&self.entries)
+            .filter(|&i| self.is_expired(i))
+            .collect();
+        for &i in &expired {
+            self.remove_at(i);
+        }
+        expired.len()
+    }
+
+    /// iterate over the live entries, most recently used first.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> + '_ {
+        self.order
+            .iter(&self.entries)
+            .filter(move |&i| !self.is_expired(i))
+            .map(move |i| {
+                let entry = self.entries.get(i);
+                (&entry.key, &entry.value)
+            })
+    }
+}
+
+impl<K: Hash + Eq + Clone, V, C: Clock> Cache<K, V> for LruCache<K, V, C> {
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    fn get(&mut self, key: &K) -> Option<&V> {
+        let i = match self.find(key) {
+            Some(i) => i,
+            None => {
+                self.stats.misses += 1;
+                return None;
+            }
+        };
+        self.stats.hits += 1;
+        self.order.unlink(&mut self.entries, i);
+        self.order.push_front(&mut self.entries, i);
+        Some(&self.entries.get(i).value)
+    }
+
+    fn peek(&self, key: &K) -> Option<&V> {
+        let &i = self.map.get(key)?;
+        if self.is_expired(i) {
+            return None;
+        }
+        Some(&self.entries.get(i).value)
+    }
+
+    fn put(&mut self, key: K, value: V) -> Option<V> {
+        self.insert(key, value, None)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        let i = self.find(key)?;
+        Some(self.remove_at(i).value)
+    }
+
+    fn clear(&mut self) {
+        self.map.clear();
+        self.entries.clear();
+        self.order = List::new();
+    }
+
+    fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    fn reset_stats(&mut self) {
+        self.stats = CacheStats::default();
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, C> fmt::Debug for LruCache<K, V, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.order.iter(&self.entries).map(|i| {
+                let entry = self.entries.get(i);
+                (&entry.key, &entry.value)
+            }))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+
+    #[test]
+    fn test_eviction_order() {
+        let mut cache = LruCache::new(3);
+        assert_eq!(cache.put(1, "a"), None);
+        cache.put(2, "b");
+        cache.put(3, "c");
+        assert_eq!(cache.get(&1), Some(&"a"));
+        // 2 is now the least recently used.
+        cache.put(4, "d");
+        assert!(!cache.contains_key(&2));
+        assert_eq!(cache.put(3, "C"), Some("c"));
+        let order: Vec<_> = cache.iter().map(|(k, _)| *k).collect();
+        assert_eq!(order, vec![3, 4, 1]);
+        assert_eq!(cache.peek_lru(), Some((&1, &"a")));
+        // peeking does not refresh.
+        assert_eq!(cache.peek(&1), Some(&"a"));
+        cache.put(5, "e");
+        assert_eq!(cache.peek(&1), None);
+        assert_eq!(cache.remove(&4), Some("d"));
+        assert_eq!(cache.len(), 2);
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_ttl() {
+        let clock = ManualClock::new();
+        let mut cache = LruCache::with_clock(4, clock.clone());
+        cache.put_with_ttl("session", 1, Duration::from_secs(10));
+        cache.put_with_ttl("token", 2, Duration::from_secs(60));
+        cache.put("config", 3);
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(cache.get(&"session"), Some(&1));
+        assert_eq!(cache.ttl(&"session"), Some(Duration::from_secs(5)));
+        assert_eq!(cache.ttl(&"config"), None);
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(cache.get(&"session"), None);
+        assert_eq!(cache.len(), 2);
+        clock.advance(Duration::from_secs(60));
+        assert!(cache.peek(&"token").is_none());
+        assert_eq!(cache.iter().count(), 1);
+        assert_eq!(cache.purge_expired(), 1);
+        assert_eq!(cache.len(), 1);
+        // a plain put clears the ttl.
+        cache.put_with_ttl("config", 4, Duration::from_secs(1));
+        cache.put("config", 5);
+        clock.advance(Duration::from_secs(2));
+        assert_eq!(cache.get(&"config"), Some(&5));
+    }
+}
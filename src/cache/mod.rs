@@ -0,0 +1,130 @@
+pub mod arc;
+pub mod lfu;
+mod list;
+pub mod lru;
+
+/// a bounded key-value store that evicts entries by some policy when full,
+/// implemented by the caches of this module so they can be swapped for one
+/// another.
+pub trait Cache<K, V> {
+    /// maximum number of entries.
+    fn capacity(&self) -> usize;
+    /// number of entries.
+    fn len(&self) -> usize;
+    /// check whether the cache has no entries.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// returns the value stored for `key`, counting as a use of it.
+    fn get(&mut self, key: &K) -> Option<&V>;
+    /// returns the value stored for `key` without counting as a use.
+    fn peek(&self, key: &K) -> Option<&V>;
+    /// check whether `key` is present, without counting as a use.
+    fn contains_key(&self, key: &K) -> bool {
+        self.peek(key).is_some()
+    }
+    /// insert `value` for `key`, evicting an entry if the cache is full, and
+    /// return the previous value if there was one.
+    fn put(&mut self, key: K, value: V) -> Option<V>;
+    /// remove `key`, returning its value if it was present.
+    fn remove(&mut self, key: &K) -> Option<V>;
+    /// remove every entry.
+    fn clear(&mut self);
+    /// hits and misses of [`Cache::get`] so far, to compare policies on a
+    /// trace.
+    fn stats(&self) -> CacheStats;
+    /// zero the hit and miss counts, leaving the entries alone.
+    fn reset_stats(&mut self);
+}
+
+/// hit and miss counts of cache lookups.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl CacheStats {
+    pub fn lookups(&self) -> u64 {
+        self.hits + self.misses
+    }
+
+    /// fraction of lookups that hit, or 0 if there were none.
+    pub fn hit_rate(&self) -> f64 {
+        if self.lookups() == 0 {
+            0.0
+        } else {
+            self.hits as f64 / self.lookups() as f64
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::arc::ArcCache;
+    use super::lfu::LfuCache;
+    use super::lru::LruCache;
+    use super::*;
+    use crate::rng::Rng;
+
+    // a hot set read at random, broken up by scans of keys never seen again.
+    fn scan_heavy_trace(seed: u64) -> Vec<u32> {
+        let mut rng = Rng::new(seed);
+        let mut trace = Vec::new();
+        let mut next_cold = 1000;
+        for _ in 0..200 {
+            for _ in 0..20 {
+                trace.push(rng.below(6) as u32);
+            }
+            for _ in 0..rng.range(5..30) {
+                trace.push(next_cold);
+                next_cold += 1;
+            }
+        }
+        trace
+    }
+
+    fn replay(cache: &mut dyn Cache<u32, u32>, trace: &[u32]) -> CacheStats {
+        for &key in trace {
+            if cache.get(&key).is_none() {
+                cache.put(key, key);
+            }
+        }
+        cache.stats()
+    }
+
+    #[test]
+    fn test_compare_policies() {
+        let trace = scan_heavy_trace(114);
+        let lru = replay(&mut LruCache::new(8), &trace);
+        let lfu = replay(&mut LfuCache::new(8), &trace);
+        let arc = replay(&mut ArcCache::new(8), &trace);
+        for stats in [lru, lfu, arc].iter() {
+            assert_eq!(stats.lookups(), trace.len() as u64);
+        }
+        // the scans flush the hot set from the lru between bursts, while
+        // the other two keep it: once it is warm, next to every read of the
+        // hot set hits.
+        let hot = trace.iter().filter(|&&key| key < 6).count() as u64;
+        assert!(lfu.hits + 10 >= hot && arc.hits + 10 >= hot);
+        assert!(lru.hit_rate() + 0.1 < lfu.hit_rate().min(arc.hit_rate()));
+    }
+
+    #[test]
+    fn test_stats() {
+        assert_eq!(CacheStats::default().hit_rate(), 0.0);
+        let mut cache = LruCache::new(2);
+        cache.put(1, 1);
+        cache.get(&1);
+        cache.get(&2);
+        cache.get(&1);
+        // peeks are not lookups.
+        cache.peek(&3);
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.lookups()), (2, 1, 3));
+        assert!((stats.hit_rate() - 2.0 / 3.0).abs() < 1e-12);
+        cache.reset_stats();
+        assert_eq!(cache.stats(), CacheStats::default());
+        assert_eq!(cache.len(), 1);
+    }
+}
@@ -0,0 +1,106 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// source of the current time for structures with expiry, as a duration
+/// since some fixed starting point.
+pub trait Clock {
+    fn now(&self) -> Duration;
+}
+
+/// monotonic wall clock, measuring from when it was created.
+#[derive(Clone, Copy, Debug)]
+pub struct SystemClock {
+    start: Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> Self {
+        SystemClock {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        SystemClock::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+/// clock that only moves when told to, for tests and simulations. clones
+/// share the same time, so a copy handed to a cache can be driven from
+/// outside.
+#[derive(Clone, Debug, Default)]
+pub struct ManualClock {
+    nanos: Arc<AtomicU64>,
+}
+
+impl ManualClock {
+    /// create a clock reading zero.
+    pub fn new() -> Self {
+        ManualClock::default()
+    }
+
+    pub fn advance(&self, by: Duration) {
+        self.nanos.fetch_add(by.as_nanos() as u64, Ordering::SeqCst);
+    }
+
+    pub fn set(&self, now: Duration) {
+        self.nanos.store(now.as_nanos() as u64, Ordering::SeqCst);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        Duration::from_nanos(self.nanos.load(Ordering::SeqCst))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_manual_clock() {
+        // clones driven in turn, against a plain duration.
+        let mut rng = Rng::new(116);
+        let clocks = vec![ManualClock::new(); 3];
+        let mut model = Duration::ZERO;
+        assert_eq!(clocks[0].now(), model);
+        for _ in 0..1000 {
+            let clock = &clocks[rng.index(clocks.len())];
+            if rng.chance(0.1) {
+                model = Duration::from_micros(rng.below(1 << 30));
+                clock.set(model);
+            } else {
+                let by = Duration::from_nanos(rng.below(1 << 20));
+                clock.advance(by);
+                model += by;
+            }
+            assert!(clocks.iter().all(|c| c.now() == model));
+        }
+        // a fresh clock does not share.
+        assert_eq!(ManualClock::new().now(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_system_clock() {
+        let clock = SystemClock::new();
+        let mut last = clock.now();
+        for _ in 0..100 {
+            let now = clock.now();
+            assert!(now >= last);
+            last = now;
+        }
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(clock.now() >= last + Duration::from_millis(5));
+    }
+}
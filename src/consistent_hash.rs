@@ -0,0 +1,307 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::hash::Hash;
+
+use crate::hash::hash64;
+
+// keys and virtual nodes are hashed under different seeds.
+const KEY_SEED: u64 = 0;
+const NODE_SEED: u64 = 1;
+
+/// a stretch of the ring that changed owner: the keys hashing into
+/// `(start, end]`, wrapping around past `u64::MAX` when `start >= end`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Span<N> {
+    pub start: u64,
+    pub end: u64,
+    pub from: N,
+    pub to: N,
+}
+
+impl<N> Span<N> {
+    fn contains(&self, h: u64) -> bool {
+        if self.start < self.end {
+            self.start < h && h <= self.end
+        } else {
+            self.start < h || h <= self.end
+        }
+    }
+}
+
+/// the part of the key space that moved in an [`HashRing::add`] or
+/// [`HashRing::remove`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Moved<N> {
+    pub spans: Vec<Span<N>>,
+}
+
+impl<N> Moved<N> {
+    /// the previous and new member of `key`, if it moved.
+    pub fn of<K: Hash + ?Sized>(&self, key: &K) -> Option<(&N, &N)> {
+        let h = hash64(key, KEY_SEED);
+        self.spans
+            .iter()
+            .find(|span| span.contains(h))
+            .map(|span| (&span.from, &span.to))
+    }
+
+    /// the keys among `keys` that moved.
+    pub fn filter<'a, K: Hash>(&'a self, keys: &'a [K]) -> impl Iterator<Item = &'a K> + 'a {
+        keys.iter().filter(move |key| self.of(*key).is_some())
+    }
+}
+
+/// consistent-hash ring: keys go to the member owning the next point
+/// clockwise on a ring of hashes, and each member has `weight *
+/// vnodes_per_weight` points. adding or removing a member only moves the
+/// keys on its own spans, about a `1 / members` share of them.
+pub struct HashRing<N> {
+    ring: BTreeMap<u64, N>,
+    weights: HashMap<N, u32>,
+    vnodes_per_weight: usize,
+}
+
+impl<N: Hash + Eq + Clone> HashRing<N> {
+    /// an empty ring; around 100 to 200 points per member keep the load
+    /// within a few percent of even.
+    pub fn new(vnodes_per_weight: usize) -> Self {
+        assert!(vnodes_per_weight > 0, "vnodes_per_weight must be positive");
+        HashRing {
+            ring: BTreeMap::new(),
+            weights: HashMap::new(),
+            vnodes_per_weight,
+        }
+    }
+
+    /// number of members.
+    pub fn len(&self) -> usize {
+        self.weights.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.weights.is_empty()
+    }
+
+    pub fn weight(&self, member: &N) -> Option<u32> {
+        self.weights.get(member).copied()
+    }
+
+    pub fn members(&self) -> impl Iterator<Item = (&N, u32)> {
+        self.weights.iter().map(|(m, &w)| (m, w))
+    }
+
+    fn points(&self, member: &N, weight: u32) -> impl Iterator<Item = u64> {
+        let replicas = weight as usize * self.vnodes_per_weight;
+        let member = member.clone();
+        (0..replicas).map(move |i| hash64(&(&member, i), NODE_SEED))
+    }
+
+    fn insert_points(&mut self, member: &N, weight: u32) {
+        for point in self.points(member, weight).collect::<Vec<_>>() {
+            // on the rare collision the earlier point keeps its place.
+            self.ring.entry(point).or_insert_with(|| member.clone());
+        }
+    }
+
+    fn remove_points(&mut self, member: &N, weight: u32) {
+        for point in self.points(member, weight).collect::<Vec<_>>() {
+            if self.ring.get(&point) == Some(member) {
+                self.ring.remove(&point);
+            }
+        }
+    }
+
+    /// add `member` with `weight`, returning the spans that changed owner.
+    /// adding a member again changes its weight; the spans are then the ones
+    /// it gained, and those it lost when the weight shrinks.
+    pub fn add(&mut self, member: N, weight: u32) -> Moved<N> {
+        assert!(weight > 0, "weight must be positive");
+        let before = self.ring.clone();
+        if let Some(old) = self.weights.insert(member.clone(), weight) {
+            self.remove_points(&member, old);
+        }
+        self.insert_points(&member, weight);
+        Moved {
+            spans: diff(&before, &self.ring),
+        }
+    }
+
+    /// remove `member`, returning the spans handed to the others.
+    pub fn remove(&mut self, member: &N) -> Moved<N> {
+        let weight = match self.weights.remove(member) {
+            Some(weight) => weight,
+            None => return Moved { spans: Vec::new() },
+        };
+        let before = self.ring.clone();
+        self.remove_points(member, weight);
+        Moved {
+            spans: diff(&before, &self.ring),
+        }
+    }
+
+    /// the member that owns `key`.
+    pub fn node_for<K: Hash + ?Sized>(&self, key: &K) -> Option<&N> {
+        owner(&self.ring, hash64(key, KEY_SEED))
+    }
+
+    /// up to `n` distinct members for `key`, in ring order from its owner:
+    /// where to put replicas.
+    pub fn nodes_for<K: Hash + ?Sized>(&self, key: &K, n: usize) -> Vec<&N> {
+        let h = hash64(key, KEY_SEED);
+        let mut nodes: Vec<&N> = Vec::with_capacity(n.min(self.len()));
+        for (_, owner) in self.ring.range(h..).chain(self.ring.range(..h)) {
+            if nodes.len() == n.min(self.len()) {
+                break;
+            }
+            if !nodes.contains(&owner) {
+                nodes.push(owner);
+            }
+        }
+        nodes
+    }
+}
+
+// the owner of the keys hashing up to `h` on `ring` since its previous point.
+fn owner<N>(ring: &BTreeMap<u64, N>, h: u64) -> Option<&N> {
+    ring.range(h..).chain(ring).next().map(|(_, owner)| owner)
+}
+
+// the spans owned by someone else in `after` than in `before`, cut at the
+// points of either ring and merged again where neighbors moved alike. empty
+// if either ring is: the keys have no owner to move from or to. O(p log p)
+// for p points.
+fn diff<N: Eq + Clone>(before: &BTreeMap<u64, N>, after: &BTreeMap<u64, N>) -> Vec<Span<N>> {
+    if before.is_empty() || after.is_empty() {
+        return Vec::new();
+    }
+    let mut points: Vec<u64> = before.keys().chain(after.keys()).copied().collect();
+    points.sort_unstable();
+    points.dedup();
+    let mut spans: Vec<Span<N>> = Vec::new();
+    let mut start = *points.last().unwrap();
+    for &end in &points {
+        let (from, to) = (owner(before, end).unwrap(), owner(after, end).unwrap());
+        if from != to {
+            match spans.last_mut() {
+                Some(last) if last.end == start && last.from == *from && last.to == *to => {
+                    last.end = end;
+                }
+                _ => spans.push(Span {
+                    start,
+                    end,
+                    from: from.clone(),
+                    to: to.clone(),
+                }),
+            }
+        }
+        start = end;
+    }
+    // the last span may go on round past the first point.
+    if spans.len() > 1 {
+        let last = spans.last().unwrap();
+        let first = &spans[0];
+        if last.end == first.start && last.from == first.from && last.to == first.to {
+            let last = spans.pop().unwrap();
+            spans[0].start = last.start;
+        }
+    }
+    spans
+}
+
+impl<N: fmt::Debug> fmt::Debug for HashRing<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HashRing")
+            .field("members", &self.weights)
+            .field("points", &self.ring.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn owners(ring: &HashRing<&'static str>, keys: &[u32]) -> Vec<&'static str> {
+        keys.iter().map(|k| *ring.node_for(k).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_weighted_balance() {
+        let mut ring = HashRing::new(160);
+        assert_eq!(ring.node_for(&1), None);
+        ring.add("a", 1);
+        ring.add("b", 1);
+        ring.add("c", 2);
+        let keys: Vec<u32> = (0..40_000).collect();
+        let counts = owners(&ring, &keys)
+            .iter()
+            .fold(HashMap::new(), |mut m, o| {
+                *m.entry(*o).or_insert(0) += 1;
+                m
+            });
+        let share = |m: &str| f64::from(counts[m]) / 40_000.0;
+        assert!((share("a") - 0.25).abs() < 0.04, "{:?}", counts);
+        assert!((share("c") - 0.5).abs() < 0.05, "{:?}", counts);
+        let replicas = ring.nodes_for(&7, 5);
+        assert_eq!(replicas.len(), 3);
+        assert_eq!(replicas[0], ring.node_for(&7).unwrap());
+    }
+
+    #[test]
+    fn test_moved_keys() {
+        let mut ring = HashRing::new(50);
+        for member in ["a", "b", "c"] {
+            ring.add(member, 1);
+        }
+        let keys: Vec<u32> = (0..5000).collect();
+        let before = owners(&ring, &keys);
+        let moved = ring.add("d", 1);
+        let after = owners(&ring, &keys);
+        for (i, key) in keys.iter().enumerate() {
+            match moved.of(key) {
+                Some((from, to)) => assert_eq!((*from, *to), (before[i], "d")),
+                None => assert_eq!(before[i], after[i]),
+            }
+        }
+        let count = moved.filter(&keys).count();
+        assert!(count > 800 && count < 1700, "{}", count);
+        // removing it again moves exactly those keys back.
+        let back = ring.remove(&"d");
+        assert_eq!(owners(&ring, &keys), before);
+        assert_eq!(back.filter(&keys).count(), count);
+        assert!(ring.remove(&"d").spans.is_empty());
+        let mut single = HashRing::new(10);
+        assert!(single.add("only", 3).spans.is_empty());
+        assert_eq!(single.weight(&"only"), Some(3));
+        assert!(single.add("only", 1).spans.is_empty());
+    }
+
+    #[test]
+    fn test_reweight() {
+        let mut ring = HashRing::new(40);
+        for member in ["a", "b", "c"] {
+            ring.add(member, 2);
+        }
+        let keys: Vec<u32> = (0..5000).collect();
+        assert!(ring.add("b", 2).spans.is_empty());
+        // shrinking hands keys away, growing takes them and some back.
+        for &weight in &[1, 4, 3] {
+            let before = owners(&ring, &keys);
+            let moved = ring.add("b", weight);
+            let after = owners(&ring, &keys);
+            assert_eq!(ring.weight(&"b"), Some(weight));
+            let mut count = 0;
+            for (i, key) in keys.iter().enumerate() {
+                if before[i] == after[i] {
+                    assert_eq!(moved.of(key), None);
+                } else {
+                    assert_eq!(moved.of(key), Some((&before[i], &after[i])));
+                    assert!(before[i] == "b" || after[i] == "b");
+                    count += 1;
+                }
+            }
+            assert!(count > 0);
+            assert!(moved.spans.iter().all(|span| span.from != span.to));
+        }
+    }
+}
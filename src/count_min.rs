@@ -0,0 +1,219 @@
+use std::f64::consts::E;
+use std::fmt;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+use crate::bloom::probes;
+
+/// count-min sketch: approximate frequencies of a stream in fixed memory.
+///
+/// estimates never undercount. with width `w = ceil(e / epsilon)` and depth
+/// `d = ceil(ln(1 / delta))` they overcount by more than `epsilon * total`
+/// with probability at most `delta`.
+pub struct CountMinSketch<T: ?Sized> {
+    counters: Vec<u64>,
+    width: usize,
+    depth: usize,
+    total: u64,
+    conservative: bool,
+    marker: PhantomData<fn(&T)>,
+}
+
+impl<T: Hash + ?Sized> CountMinSketch<T> {
+    /// create a sketch with `depth` rows of `width` counters.
+    pub fn new(width: usize, depth: usize) -> Self {
+        assert!(width > 0 && depth > 0, "sketch must not be empty");
+        CountMinSketch {
+            counters: vec![0; width * depth],
+            width,
+            depth,
+            total: 0,
+            conservative: false,
+            marker: PhantomData,
+        }
+    }
+
+    /// create a sketch whose estimates exceed the true count by more than
+    /// `epsilon * total` with probability at most `delta`.
+    pub fn with_error(epsilon: f64, delta: f64) -> Self {
+        assert!(
+            epsilon > 0.0 && delta > 0.0 && delta < 1.0,
+            "invalid error bounds"
+        );
+        let width = (E / epsilon).ceil() as usize;
+        let depth = (1.0 / delta).ln().ceil().max(1.0) as usize;
+        CountMinSketch::new(width, depth)
+    }
+
+    /// switch to conservative update: only the counters that determine the
+    /// estimate are raised. this reduces overcounting, but such sketches
+    /// can no longer be merged exactly.
+    pub fn conservative(mut self) -> Self {
+        self.conservative = true;
+        self
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// sum of all counts added.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// the `epsilon` of the error bound, `e / width`.
+    pub fn epsilon(&self) -> f64 {
+        E / self.width as f64
+    }
+
+    /// the `delta` of the error bound, `e^-depth`.
+    pub fn delta(&self) -> f64 {
+        (-(self.depth as f64)).exp()
+    }
+
+    fn cells<'a>(&self, item: &'a T) -> impl Iterator<Item = usize> + 'a {
+        let width = self.width;
+        probes(item, self.depth as u32, width)
+            .enumerate()
+            .map(move |(row, col)| row * width + col)
+    }
+
+    /// add `count` occurrences of `item`.
+    pub fn add(&mut self, item: &T, count: u64) {
+        self.total += count;
+        if self.conservative {
+            let target = self.estimate(item) + count;
+            for cell in self.cells(item) {
+                self.counters[cell] = self.counters[cell].max(target);
+            }
+        } else {
+            for cell in self.cells(item) {
+                self.counters[cell] += count;
+            }
+        }
+    }
+
+    pub fn increment(&mut self, item: &T) {
+        self.add(item, 1);
+    }
+
+    /// upper bound on the number of occurrences of `item`.
+    pub fn estimate(&self, item: &T) -> u64 {
+        self.cells(item)
+            .map(|cell| self.counters[cell])
+            .min()
+            .unwrap_or(0)
+    }
+
+    /// add the counts of `other`, a sketch with the same dimensions, as if
+    /// both streams had been fed into this one.
+    pub fn merge(&mut self, other: &CountMinSketch<T>) {
+        assert!(
+            self.width == other.width && self.depth == other.depth,
+            "sketches have different dimensions"
+        );
+        for (c, &o) in self.counters.iter_mut().zip(&other.counters) {
+            *c += o;
+        }
+        self.total += other.total;
+    }
+
+    pub fn clear(&mut self) {
+        for c in &mut self.counters {
+            *c = 0;
+        }
+        self.total = 0;
+    }
+}
+
+impl<T: ?Sized> Clone for CountMinSketch<T> {
+    fn clone(&self) -> Self {
+        CountMinSketch {
+            counters: self.counters.clone(),
+            width: self.width,
+            depth: self.depth,
+            total: self.total,
+            conservative: self.conservative,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for CountMinSketch<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CountMinSketch")
+            .field("width", &self.width)
+            .field("depth", &self.depth)
+            .field("total", &self.total)
+            .field("conservative", &self.conservative)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+    use std::collections::HashMap;
+
+    // a skewed stream: item i appears about 1000 / (i + 1) times.
+    fn stream() -> Vec<u32> {
+        let mut rng = Rng::new(28);
+        let mut items: Vec<u32> = (0..500u32)
+            .flat_map(|i| std::iter::repeat_n(i, 1000 / (i as usize + 1)))
+            .collect();
+        rng.shuffle(&mut items);
+        items
+    }
+
+    #[test]
+    fn test_error_bound() {
+        let items = stream();
+        let mut exact = HashMap::new();
+        let mut plain = CountMinSketch::with_error(0.01, 0.01);
+        let mut conservative = CountMinSketch::with_error(0.01, 0.01).conservative();
+        for item in &items {
+            *exact.entry(*item).or_insert(0u64) += 1;
+            plain.increment(item);
+            conservative.increment(item);
+        }
+        assert_eq!((plain.width(), plain.depth()), (272, 5));
+        let bound = (plain.epsilon() * plain.total() as f64) as u64;
+        let (mut plain_error, mut conservative_error) = (0, 0);
+        for (item, &count) in &exact {
+            let (p, c) = (plain.estimate(item), conservative.estimate(item));
+            assert!(c >= count && p >= c);
+            assert!(p - count <= bound);
+            plain_error += p - count;
+            conservative_error += c - count;
+        }
+        assert!(conservative_error < plain_error);
+    }
+
+    #[test]
+    fn test_merge() {
+        let items = stream();
+        let (left, right) = items.split_at(items.len() / 2);
+        let mut whole = CountMinSketch::new(100, 4);
+        let mut a = CountMinSketch::new(100, 4);
+        let mut b = a.clone();
+        for item in left {
+            a.increment(item);
+            whole.increment(item);
+        }
+        for item in right {
+            b.add(item, 1);
+            whole.increment(item);
+        }
+        a.merge(&b);
+        assert_eq!(a.total(), whole.total());
+        assert!((0..600u32).all(|i| a.estimate(&i) == whole.estimate(&i)));
+        a.clear();
+        assert_eq!(a.estimate(&0), 0);
+    }
+}
@@ -0,0 +1,147 @@
+use std::fmt;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+use crate::bloom::{optimal_num_bits, optimal_num_hashes, probes};
+
+/// bloom filter with a small counter per slot, so that items can be removed.
+///
+/// counters saturate at `u8::MAX` and then stay there: decrementing a
+/// saturated counter could create false negatives.
+pub struct CountingBloomFilter<T: ?Sized> {
+    counters: Vec<u8>,
+    num_hashes: u32,
+    marker: PhantomData<fn(&T)>,
+}
+
+impl<T: Hash + ?Sized> CountingBloomFilter<T> {
+    /// create an empty filter with `num_counters` slots and `num_hashes` probes.
+    pub fn new(num_counters: usize, num_hashes: u32) -> Self {
+        assert!(
+            num_counters > 0 && num_hashes > 0,
+            "filter must not be empty"
+        );
+        CountingBloomFilter {
+            counters: vec![0; num_counters],
+            num_hashes,
+            marker: PhantomData,
+        }
+    }
+
+    /// create a filter sized like [`crate::bloom::BloomFilter::with_rate`].
+    pub fn with_rate(expected_items: usize, fp_rate: f64) -> Self {
+        let num_counters = optimal_num_bits(expected_items, fp_rate);
+        CountingBloomFilter::new(
+            num_counters,
+            optimal_num_hashes(num_counters, expected_items),
+        )
+    }
+
+    pub fn num_counters(&self) -> usize {
+        self.counters.len()
+    }
+
+    pub fn num_hashes(&self) -> u32 {
+        self.num_hashes
+    }
+
+    pub fn insert(&mut self, item: &T) {
+        for slot in probes(item, self.num_hashes, self.counters.len()) {
+            self.counters[slot] = self.counters[slot].saturating_add(1);
+        }
+    }
+
+    /// remove one insertion of `item`. returns false, leaving the filter
+    /// unchanged, if `item` is certainly absent. removing an item that was
+    /// never inserted but tests positive corrupts the filter.
+    pub fn remove(&mut self, item: &T) -> bool {
+        if !self.contains(item) {
+            return false;
+        }
+        for slot in probes(item, self.num_hashes, self.counters.len()) {
+            if self.counters[slot] != u8::MAX {
+                self.counters[slot] -= 1;
+            }
+        }
+        true
+    }
+
+    /// check whether `item` may be present.
+    pub fn contains(&self, item: &T) -> bool {
+        self.estimate_count(item) > 0
+    }
+
+    /// upper bound on the number of times `item` is present, the smallest
+    /// of its counters.
+    pub fn estimate_count(&self, item: &T) -> u8 {
+        probes(item, self.num_hashes, self.counters.len())
+            .map(|slot| self.counters[slot])
+            .min()
+            .unwrap_or(0)
+    }
+
+    pub fn clear(&mut self) {
+        for counter in &mut self.counters {
+            *counter = 0;
+        }
+    }
+}
+
+impl<T: ?Sized> Clone for CountingBloomFilter<T> {
+    fn clone(&self) -> Self {
+        CountingBloomFilter {
+            counters: self.counters.clone(),
+            num_hashes: self.num_hashes,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for CountingBloomFilter<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CountingBloomFilter")
+            .field("num_counters", &self.counters.len())
+            .field("num_hashes", &self.num_hashes)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_remove() {
+        let mut filter = CountingBloomFilter::with_rate(1000, 0.01);
+        for i in 0..1000u32 {
+            filter.insert(&i);
+        }
+        filter.insert(&7);
+        assert_eq!(filter.estimate_count(&7), 2);
+        for i in 0..500u32 {
+            assert!(filter.remove(&i));
+        }
+        assert!(filter.contains(&7));
+        assert!(filter.remove(&7));
+        assert!((500..1000u32).all(|i| filter.contains(&i)));
+        let false_positives = (0..500u32).filter(|i| filter.contains(i)).count();
+        assert!(false_positives < 20);
+        assert!(!filter.remove(&123_456));
+        filter.clear();
+        assert!(!filter.contains(&600));
+    }
+
+    #[test]
+    fn test_saturation() {
+        let mut filter = CountingBloomFilter::<str>::new(64, 2);
+        for _ in 0..300 {
+            filter.insert("hot");
+        }
+        assert_eq!(filter.estimate_count("hot"), u8::MAX);
+        for _ in 0..300 {
+            filter.remove("hot");
+        }
+        // saturated counters never drop, so the item cannot vanish wrongly.
+        assert!(filter.contains("hot"));
+    }
+}
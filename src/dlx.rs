@@ -0,0 +1,362 @@
+// node 0 is the root header, nodes 1..=columns the column headers, and the
+// rest the 1s of the matrix.
+const ROOT: usize = 0;
+
+/// exact-cover problem: choose rows of a 0/1 matrix, added one at a time,
+/// that have exactly one 1 in every column. solved by knuth's algorithm x
+/// with dancing links.
+///
+/// columns may also be secondary: covered at most once rather than exactly
+/// once, as needed e.g. for the diagonals of n queens.
+pub struct ExactCover {
+    left: Vec<usize>,
+    right: Vec<usize>,
+    up: Vec<usize>,
+    down: Vec<usize>,
+    // the column header of each node, and the row of each non-header node.
+    column: Vec<usize>,
+    row: Vec<usize>,
+    // number of 1s left in each column, indexed by header node.
+    size: Vec<usize>,
+    columns: usize,
+    rows: usize,
+}
+
+impl ExactCover {
+    /// `primary` columns must be covered exactly once, the `secondary`
+    /// columns after them at most once.
+    pub fn new(primary: usize, secondary: usize) -> Self {
+        let columns = primary + secondary;
+        let n = columns + 1;
+        let mut cover = ExactCover {
+            left: (0..n).collect(),
+            right: (0..n).collect(),
+            up: (0..n).collect(),
+            down: (0..n).collect(),
+            column: (0..n).collect(),
+            row: vec![usize::MAX; n],
+            size: vec![0; n],
+            columns,
+            rows: 0,
+        };
+        // only primary columns are linked into the header list, so the
+        // search never has to pick a secondary one.
+        for c in 1..=primary {
+            cover.left[c] = c - 1;
+            cover.right[c - 1] = c;
+        }
+        cover.left[ROOT] = primary;
+        cover.right[primary] = ROOT;
+        cover
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// add a row with 1s in the given (0-based) columns, returning its
+    /// index.
+    pub fn add_row(&mut self, columns: &[usize]) -> usize {
+        let row = self.rows;
+        self.rows += 1;
+        let mut first = None;
+        for &c in columns {
+            assert!(c < self.columns, "column {} out of range", c);
+            let header = c + 1;
+            let node = self.left.len();
+            self.column.push(header);
+            self.row.push(row);
+            // append at the bottom of the column.
+            self.up.push(self.up[header]);
+            self.down.push(header);
+            let above = self.up[header];
+            self.down[above] = node;
+            self.up[header] = node;
+            self.size[header] += 1;
+            // and at the end of the row's circular list.
+            match first {
+                None => {
+                    self.left.push(node);
+                    self.right.push(node);
+                    first = Some(node);
+                }
+                Some(first) => {
+                    let last = self.left[first];
+                    self.left.push(last);
+                    self.right.push(first);
+                    self.right[last] = node;
+                    self.left[first] = node;
+                }
+            }
+        }
+        row
+    }
+
+    fn cover(&mut self, c: usize) {
+        let (l, r) = (self.left[c], self.right[c]);
+        self.right[l] = r;
+        self.left[r] = l;
+        let mut i = self.down[c];
+        while i != c {
+            let mut j = self.right[i];
+            while j != i {
+                let (u, d) = (self.up[j], self.down[j]);
+                self.down[u] = d;
+                self.up[d] = u;
+                self.size[self.column[j]] -= 1;
+                j = self.right[j];
+            }
+            i = self.down[i];
+        }
+    }
+
+    fn uncover(&mut self, c: usize) {
+        let mut i = self.up[c];
+        while i != c {
+            let mut j = self.left[i];
+            while j != i {
+                let (u, d) = (self.up[j], self.down[j]);
+                self.down[u] = j;
+                self.up[d] = j;
+                self.size[self.column[j]] += 1;
+                j = self.left[j];
+            }
+            i = self.up[i];
+        }
+        let (l, r) = (self.left[c], self.right[c]);
+        self.right[l] = c;
+        self.left[r] = c;
+    }
+
+    /// run the search, calling `visit` with the rows of each solution; the
+    /// search stops early when `visit` returns false. returns the number
+    /// of solutions visited.
+    pub fn solve<F: FnMut(&[usize]) -> bool>(&mut self, mut visit: F) -> usize {
+        let mut partial = Vec::new();
+        let mut count = 0;
+        self.search(&mut partial, &mut count, &mut visit);
+        count
+    }
+
+    // returns false once `visit` asks to stop.
+    fn search<F: FnMut(&[usize]) -> bool>(
+        &mut self,
+        partial: &mut Vec<usize>,
+        count: &mut usize,
+        visit: &mut F,
+    ) -> bool {
+        if self.right[ROOT] == ROOT {
+            *count += 1;
+            return visit(partial);
+        }
+        // branch on the column with the fewest choices.
+        let mut c = self.right[ROOT];
+        let mut j = self.right[c];
+        while j != ROOT {
+            if self.size[j] < self.size[c] {
+                c = j;
+            }
+            j = self.right[j];
+        }
+        self.cover(c);
+        let mut r = self.down[c];
+        let mut go_on = true;
+        while r != c && go_on {
+            partial.push(self.row[r]);
+            let mut j = self.right[r];
+            while j != r {
+                self.cover(self.column[j]);
+                j = self.right[j];
+            }
+            go_on = self.search(partial, count, visit);
+            let mut j = self.left[r];
+            while j != r {
+                self.uncover(self.column[j]);
+                j = self.left[j];
+            }
+            partial.pop();
+            r = self.down[r];
+        }
+        self.uncover(c);
+        go_on
+    }
+
+    /// the first solution found, with its rows in increasing order.
+    pub fn first_solution(&mut self) -> Option<Vec<usize>> {
+        let mut found = None;
+        self.solve(|rows| {
+            let mut rows = rows.to_vec();
+            rows.sort_unstable();
+            found = Some(rows);
+            false
+        });
+        found
+    }
+
+    /// the number of solutions.
+    pub fn count_solutions(&mut self) -> usize {
+        self.solve(|_| true)
+    }
+}
+
+/// solve a 9x9 sudoku given row by row, with 0 for empty cells. returns
+/// the completed grid, or None if the puzzle has no solution.
+pub fn solve_sudoku(grid: &[[u8; 9]; 9]) -> Option<[[u8; 9]; 9]> {
+    // columns: cell filled, digit in row, digit in column, digit in box.
+    let mut cover = ExactCover::new(4 * 81, 0);
+    let mut choices = Vec::new();
+    for (r, cells) in grid.iter().enumerate() {
+        for (c, &given) in cells.iter().enumerate() {
+            assert!(given <= 9, "cells must be 0 to 9");
+            for d in 1..=9u8 {
+                if given != 0 && given != d {
+                    continue;
+                }
+                let b = r / 3 * 3 + c / 3;
+                let k = usize::from(d - 1);
+                cover.add_row(&[r * 9 + c, 81 + r * 9 + k, 162 + c * 9 + k, 243 + b * 9 + k]);
+                choices.push((r, c, d));
+            }
+        }
+    }
+    let rows = cover.first_solution()?;
+    let mut solved = [[0; 9]; 9];
+    for row in rows {
+        let (r, c, d) = choices[row];
+        solved[r][c] = d;
+    }
+    Some(solved)
+}
+
+/// count the ways to tile a `width` x `height` board with copies of the
+/// given pieces (each a list of `(x, y)` cells), each piece used exactly
+/// once and in any rotation or reflection.
+pub fn count_tilings(width: usize, height: usize, pieces: &[Vec<(i32, i32)>]) -> usize {
+    let cells = width * height;
+    let mut cover = ExactCover::new(pieces.len() + cells, 0);
+    for (p, piece) in pieces.iter().enumerate() {
+        for shape in orientations(piece) {
+            let (w, h) = shape
+                .iter()
+                .fold((0, 0), |(w, h), &(x, y)| (w.max(x + 1), h.max(y + 1)));
+            for dy in 0..=(height as i32 - h) {
+                for dx in 0..=(width as i32 - w) {
+                    let mut columns = vec![p];
+                    columns.extend(shape.iter().map(|&(x, y)| {
+                        pieces.len() + (y + dy) as usize * width + (x + dx) as usize
+                    }));
+                    cover.add_row(&columns);
+                }
+            }
+        }
+    }
+    cover.count_solutions()
+}
+
+// the distinct rotations and reflections of `piece`, each shifted to the
+// origin and sorted.
+fn orientations(piece: &[(i32, i32)]) -> Vec<Vec<(i32, i32)>> {
+    let mut shapes: Vec<Vec<(i32, i32)>> = Vec::new();
+    let mut current = piece.to_vec();
+    for flip in 0..2 {
+        for _ in 0..4 {
+            current = current.iter().map(|&(x, y)| (y, -x)).collect();
+            let (mx, my) = current
+                .iter()
+                .fold((i32::MAX, i32::MAX), |(a, b), &(x, y)| (a.min(x), b.min(y)));
+            let mut shape: Vec<(i32, i32)> =
+                current.iter().map(|&(x, y)| (x - mx, y - my)).collect();
+            shape.sort_unstable();
+            if !shapes.contains(&shape) {
+                shapes.push(shape);
+            }
+        }
+        if flip == 0 {
+            current = current.iter().map(|&(x, y)| (-x, y)).collect();
+        }
+    }
+    shapes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_knuth_example() {
+        // the example from knuth's paper; the unique cover is rows 0, 3, 4.
+        let mut cover = ExactCover::new(7, 0);
+        for row in [
+            vec![2, 4, 5],
+            vec![0, 3, 6],
+            vec![1, 2, 5],
+            vec![0, 3],
+            vec![1, 6],
+            vec![3, 4, 6],
+        ] {
+            cover.add_row(&row);
+        }
+        assert_eq!(cover.first_solution(), Some(vec![0, 3, 4]));
+        assert_eq!(cover.count_solutions(), 1);
+        let mut impossible = ExactCover::new(2, 0);
+        impossible.add_row(&[0]);
+        assert_eq!(impossible.first_solution(), None);
+    }
+
+    #[test]
+    fn test_queens_with_secondary_columns() {
+        // ranks and files are primary; diagonals are secondary.
+        let queens = |n: usize| {
+            let mut cover = ExactCover::new(2 * n, 2 * (2 * n - 1));
+            for r in 0..n {
+                for c in 0..n {
+                    let diagonal = 2 * n + r + c;
+                    let anti = 2 * n + (2 * n - 1) + (r + n - 1 - c);
+                    cover.add_row(&[r, n + c, diagonal, anti]);
+                }
+            }
+            cover.count_solutions()
+        };
+        assert_eq!(queens(6), 4);
+        assert_eq!(queens(8), 92);
+    }
+
+    #[test]
+    fn test_sudoku_and_tilings() {
+        let puzzle = [
+            [5, 3, 0, 0, 7, 0, 0, 0, 0],
+            [6, 0, 0, 1, 9, 5, 0, 0, 0],
+            [0, 9, 8, 0, 0, 0, 0, 6, 0],
+            [8, 0, 0, 0, 6, 0, 0, 0, 3],
+            [4, 0, 0, 8, 0, 3, 0, 0, 1],
+            [7, 0, 0, 0, 2, 0, 0, 0, 6],
+            [0, 6, 0, 0, 0, 0, 2, 8, 0],
+            [0, 0, 0, 4, 1, 9, 0, 0, 5],
+            [0, 0, 0, 0, 8, 0, 0, 7, 9],
+        ];
+        let solved = solve_sudoku(&puzzle).unwrap();
+        assert_eq!(solved[0], [5, 3, 4, 6, 7, 8, 9, 1, 2]);
+        for i in 0..9 {
+            let mut row: Vec<u8> = solved[i].to_vec();
+            let mut column: Vec<u8> = solved.iter().map(|r| r[i]).collect();
+            row.sort_unstable();
+            column.sort_unstable();
+            assert_eq!(row, (1..=9).collect::<Vec<u8>>());
+            assert_eq!(column, (1..=9).collect::<Vec<u8>>());
+        }
+        let mut broken = puzzle;
+        broken[0][2] = 5;
+        assert_eq!(solve_sudoku(&broken), None);
+        // two l-trominoes tile a 2x3 board in two ways.
+        let l = vec![(0, 0), (0, 1), (1, 0)];
+        assert_eq!(count_tilings(3, 2, &[l.clone(), l]), 4);
+        // dominoes: the 2x4 board has 5 tilings, counted once per order of
+        // the four (distinguishable) pieces.
+        let domino = vec![(0, 0), (1, 0)];
+        assert_eq!(count_tilings(4, 2, &vec![domino; 4]), 5 * 24);
+    }
+}
@@ -0,0 +1,373 @@
+use std::ops::{Add, Sub};
+
+/// union-find over the elements `0..n`, with path compression and union by rank.
+#[derive(Clone, Debug)]
+pub struct DisjointSet {
+    parent: Vec<usize>,
+    rank: Vec<u8>,
+    size: Vec<usize>,
+    count: usize,
+}
+
+impl DisjointSet {
+    /// create `n` singleton sets.
+    pub fn new(n: usize) -> DisjointSet {
+        DisjointSet {
+            parent: (0..n).collect(),
+            rank: vec![0; n],
+            size: vec![1; n],
+            count: n,
+        }
+    }
+
+    /// number of elements.
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    /// returns the representative of the set containing `x`.
+    pub fn find(&mut self, x: usize) -> usize {
+        let mut root = x;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        let mut cur = x;
+        while self.parent[cur] != root {
+            let next = self.parent[cur];
+            self.parent[cur] = root;
+            cur = next;
+        }
+        root
+    }
+
+    /// merge the sets containing `a` and `b`, returns false if they were already merged.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        if self.rank[a] < self.rank[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        if self.rank[a] == self.rank[b] {
+            self.rank[a] += 1;
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+        self.count -= 1;
+        true
+    }
+
+    /// check whether `a` and `b` belong to the same set.
+    pub fn same_set(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// number of elements in the set containing `x`.
+    pub fn set_size(&mut self, x: usize) -> usize {
+        let root = self.find(x);
+        self.size[root]
+    }
+
+    /// number of disjoint sets.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// all sets, each listed in increasing element order.
+    pub fn groups(&mut self) -> Vec<Vec<usize>> {
+        let mut index = vec![usize::MAX; self.len()];
+        let mut groups: Vec<Vec<usize>> = Vec::with_capacity(self.count);
+        for x in 0..self.len() {
+            let root = self.find(x);
+            if index[root] == usize::MAX {
+                index[root] = groups.len();
+                groups.push(Vec::new());
+            }
+            groups[index[root]].push(x);
+        }
+        groups
+    }
+}
+
+/// union-find that also tracks the potential difference between elements of the same set.
+///
+/// `union(a, b, w)` records `potential(b) - potential(a) == w`.
+#[derive(Clone, Debug)]
+pub struct WeightedDisjointSet<T> {
+    parent: Vec<usize>,
+    rank: Vec<u8>,
+    // potential of an element relative to its parent.
+    weight: Vec<T>,
+    count: usize,
+}
+
+impl<T> WeightedDisjointSet<T>
+where
+    T: Copy + Default + Add<Output = T> + Sub<Output = T>,
+{
+    /// create `n` singleton sets, every potential zero.
+    pub fn new(n: usize) -> WeightedDisjointSet<T> {
+        WeightedDisjointSet {
+            parent: (0..n).collect(),
+            rank: vec![0; n],
+            weight: vec![T::default(); n],
+            count: n,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    /// returns the representative of `x` and the potential of `x` relative to it.
+    pub fn find(&mut self, x: usize) -> (usize, T) {
+        let mut path = Vec::new();
+        let mut root = x;
+        while self.parent[root] != root {
+            path.push(root);
+            root = self.parent[root];
+        }
+        // walk from the node closest to the root downwards, accumulating offsets.
+        let mut acc = T::default();
+        for &node in path.iter().rev() {
+            acc = acc + self.weight[node];
+            self.weight[node] = acc;
+            self.parent[node] = root;
+        }
+        (
+            root,
+            if path.is_empty() {
+                T::default()
+            } else {
+                self.weight[x]
+            },
+        )
+    }
+
+    /// merge the sets of `a` and `b` so that `potential(b) - potential(a) == w`.
+    ///
+    /// returns false, leaving the structure untouched, if they were already in the same set.
+    pub fn union(&mut self, a: usize, b: usize, w: T) -> bool {
+        let (ra, wa) = self.find(a);
+        let (rb, wb) = self.find(b);
+        if ra == rb {
+            return false;
+        }
+        // potential(rb) - potential(ra) == wa + w - wb
+        let offset = wa + w - wb;
+        if self.rank[ra] < self.rank[rb] {
+            self.parent[ra] = rb;
+            self.weight[ra] = T::default() - offset;
+        } else {
+            if self.rank[ra] == self.rank[rb] {
+                self.rank[ra] += 1;
+            }
+            self.parent[rb] = ra;
+            self.weight[rb] = offset;
+        }
+        self.count -= 1;
+        true
+    }
+
+    /// returns `potential(b) - potential(a)`, or `None` if they are in different sets.
+    pub fn diff(&mut self, a: usize, b: usize) -> Option<T> {
+        let (ra, wa) = self.find(a);
+        let (rb, wb) = self.find(b);
+        if ra == rb {
+            Some(wb - wa)
+        } else {
+            None
+        }
+    }
+
+    pub fn same_set(&mut self, a: usize, b: usize) -> bool {
+        self.find(a).0 == self.find(b).0
+    }
+
+    /// number of disjoint sets.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
+
+/// union-find whose unions can be undone, for offline dynamic connectivity.
+///
+/// path compression is not used so that every union is a single pointer change.
+#[derive(Clone, Debug)]
+pub struct RollbackDisjointSet {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    // (attached root, root it was attached under) for every successful union.
+    history: Vec<(usize, usize)>,
+    count: usize,
+}
+
+impl RollbackDisjointSet {
+    /// create `n` singleton sets.
+    pub fn new(n: usize) -> RollbackDisjointSet {
+        RollbackDisjointSet {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+            history: Vec::new(),
+            count: n,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    /// returns the representative of the set containing `x`, in O(log n).
+    pub fn find(&self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            x = self.parent[x];
+        }
+        x
+    }
+
+    /// merge the sets containing `a` and `b`, returns false if they were already merged.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        if self.size[a] < self.size[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+        self.history.push((b, a));
+        self.count -= 1;
+        true
+    }
+
+    pub fn same_set(&self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// number of disjoint sets.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// returns a token that `rollback` can return to.
+    pub fn snapshot(&self) -> usize {
+        self.history.len()
+    }
+
+    /// undo the most recent successful union, returns false if there is none.
+    pub fn undo(&mut self) -> bool {
+        match self.history.pop() {
+            Some((child, root)) => {
+                self.parent[child] = child;
+                self.size[root] -= self.size[child];
+                self.count += 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// undo every union performed since `snapshot` was taken.
+    pub fn rollback(&mut self, snapshot: usize) {
+        while self.history.len() > snapshot {
+            self.undo();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_union_find() {
+        let mut dsu = DisjointSet::new(6);
+        assert_eq!(dsu.count(), 6);
+        assert!(dsu.union(0, 1));
+        assert!(dsu.union(2, 3));
+        assert!(!dsu.union(1, 0));
+        assert!(dsu.same_set(0, 1));
+        assert!(!dsu.same_set(1, 2));
+        assert!(dsu.union(1, 3));
+        assert!(dsu.same_set(0, 2));
+        assert_eq!(dsu.count(), 3);
+        assert_eq!(dsu.set_size(3), 4);
+        assert_eq!(dsu.set_size(5), 1);
+    }
+
+    #[test]
+    fn test_groups() {
+        let mut dsu = DisjointSet::new(5);
+        dsu.union(4, 0);
+        dsu.union(1, 3);
+        assert_eq!(dsu.groups(), vec![vec![0, 4], vec![1, 3], vec![2]]);
+    }
+
+    #[test]
+    fn test_long_chain() {
+        let n = 10_000;
+        let mut dsu = DisjointSet::new(n);
+        for i in 1..n {
+            dsu.union(i - 1, i);
+        }
+        assert_eq!(dsu.count(), 1);
+        assert_eq!(dsu.find(0), dsu.find(n - 1));
+    }
+
+    #[test]
+    fn test_empty() {
+        let dsu = DisjointSet::new(0);
+        assert!(dsu.is_empty());
+        assert_eq!(dsu.count(), 0);
+    }
+
+    #[test]
+    fn test_weighted() {
+        let mut dsu = WeightedDisjointSet::<i64>::new(5);
+        assert!(dsu.union(0, 1, 3));
+        assert!(dsu.union(2, 1, -2));
+        assert!(dsu.union(3, 4, 10));
+        assert_eq!(dsu.diff(0, 1), Some(3));
+        assert_eq!(dsu.diff(0, 2), Some(5));
+        assert_eq!(dsu.diff(2, 0), Some(-5));
+        assert_eq!(dsu.diff(0, 3), None);
+        assert!(dsu.union(2, 4, 1));
+        assert_eq!(dsu.diff(0, 3), Some(-4));
+        assert!(!dsu.union(0, 4, 0));
+        assert_eq!(dsu.count(), 1);
+    }
+
+    #[test]
+    fn test_rollback() {
+        let mut dsu = RollbackDisjointSet::new(4);
+        dsu.union(0, 1);
+        let snapshot = dsu.snapshot();
+        dsu.union(2, 3);
+        assert!(!dsu.union(3, 2));
+        dsu.union(1, 2);
+        assert!(dsu.same_set(0, 3));
+        assert_eq!(dsu.count(), 1);
+        dsu.rollback(snapshot);
+        assert!(dsu.same_set(0, 1));
+        assert!(!dsu.same_set(1, 2));
+        assert!(!dsu.same_set(2, 3));
+        assert_eq!(dsu.count(), 3);
+        assert!(dsu.undo());
+        assert!(!dsu.undo());
+        assert_eq!(dsu.count(), 4);
+    }
+}
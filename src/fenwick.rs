@@ -0,0 +1,288 @@
+use std::ops::{Add, AddAssign, RangeBounds, Sub};
+
+use crate::resolve_range;
+
+/// binary indexed tree over `0..len` with point updates and prefix sums.
+#[derive(Clone, Debug)]
+pub struct Fenwick<T> {
+    // 1-based: `tree[i]` holds the sum of `(i - lowbit(i))..i`.
+    tree: Vec<T>,
+}
+
+impl<T> Fenwick<T>
+where
+    T: Copy + Default + Add<Output = T> + AddAssign + Sub<Output = T>,
+{
+    /// create a tree of `len` zeros.
+    pub fn new(len: usize) -> Fenwick<T> {
+        Fenwick {
+            tree: vec![T::default(); len + 1],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.tree.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// add `delta` to the element at `index`.
+    pub fn add(&mut self, index: usize, delta: T) {
+        assert!(index < self.len(), "index {} out of bounds", index);
+        let mut i = index + 1;
+        while i < self.tree.len() {
+            self.tree[i] += delta;
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    /// sum of the first `end` elements.
+    pub fn prefix_sum(&self, end: usize) -> T {
+        assert!(end <= self.len(), "index {} out of bounds", end);
+        let mut sum = T::default();
+        let mut i = end;
+        while i > 0 {
+            sum += self.tree[i];
+            i &= i - 1;
+        }
+        sum
+    }
+
+    /// sum of the elements in `range`.
+    pub fn sum(&self, range: impl RangeBounds<usize>) -> T {
+        let (start, end) = resolve_range(range, self.len());
+        self.prefix_sum(end) - self.prefix_sum(start)
+    }
+
+    /// returns the element at `index`.
+    pub fn get(&self, index: usize) -> T {
+        self.sum(index..=index)
+    }
+
+    /// replace the element at `index`.
+    pub fn set(&mut self, index: usize, value: T) {
+        let old = self.get(index);
+        self.add(index, value - old);
+    }
+}
+
+impl<T> Fenwick<T>
+where
+    T: Copy + Default + Add<Output = T> + AddAssign + Sub<Output = T> + PartialOrd,
+{
+    /// returns the smallest `k` such that `prefix_sum(k + 1) >= target`, or `None`
+    /// if the total is smaller than `target`.
+    ///
+    /// every element must be non-negative, which makes the prefix sums monotone.
+    pub fn kth(&self, target: T) -> Option<usize> {
+        let n = self.len();
+        if n == 0 {
+            return None;
+        }
+        let mut pos = 0;
+        let mut remaining = target;
+        let mut step = 1 << (usize::BITS - 1 - n.leading_zeros());
+        while step > 0 {
+            let next = pos + step;
+            if next <= n && self.tree[next] < remaining {
+                pos = next;
+                remaining = remaining - self.tree[next];
+            }
+            step >>= 1;
+        }
+        if pos < n {
+            Some(pos)
+        } else {
+            None
+        }
+    }
+}
+
+impl<T> From<Vec<T>> for Fenwick<T>
+where
+    T: Copy + Default + Add<Output = T> + AddAssign + Sub<Output = T>,
+{
+    /// build in O(n).
+    fn from(values: Vec<T>) -> Fenwick<T> {
+        let mut tree = Vec::with_capacity(values.len() + 1);
+        tree.push(T::default());
+        tree.extend(values);
+        for i in 1..tree.len() {
+            let parent = i + (i & i.wrapping_neg());
+            if parent < tree.len() {
+                let v = tree[i];
+                tree[parent] += v;
+            }
+        }
+        Fenwick { tree }
+    }
+}
+
+/// two-dimensional binary indexed tree with point updates and rectangle sums.
+#[derive(Clone, Debug)]
+pub struct Fenwick2D<T> {
+    rows: usize,
+    cols: usize,
+    // 1-based in both dimensions, row-major with `cols + 1` columns.
+    tree: Vec<T>,
+}
+
+impl<T> Fenwick2D<T>
+where
+    T: Copy + Default + Add<Output = T> + AddAssign + Sub<Output = T>,
+{
+    /// create a `rows` x `cols` grid of zeros.
+    pub fn new(rows: usize, cols: usize) -> Fenwick2D<T> {
+        Fenwick2D {
+            rows,
+            cols,
+            tree: vec![T::default(); (rows + 1) * (cols + 1)],
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// add `delta` to the cell at (`row`, `col`).
+    pub fn add(&mut self, row: usize, col: usize, delta: T) {
+        assert!(
+            row < self.rows && col < self.cols,
+            "cell ({}, {}) out of bounds",
+            row,
+            col
+        );
+        let mut i = row + 1;
+        while i <= self.rows {
+            let mut j = col + 1;
+            while j <= self.cols {
+                self.tree[i * (self.cols + 1) + j] += delta;
+                j += j & j.wrapping_neg();
+            }
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    /// sum of the cells in rows `0..row_end` and columns `0..col_end`.
+    pub fn prefix_sum(&self, row_end: usize, col_end: usize) -> T {
+        assert!(
+            row_end <= self.rows && col_end <= self.cols,
+            "prefix out of bounds"
+        );
+        let mut sum = T::default();
+        let mut i = row_end;
+        while i > 0 {
+            let mut j = col_end;
+            while j > 0 {
+                sum += self.tree[i * (self.cols + 1) + j];
+                j &= j - 1;
+            }
+            i &= i - 1;
+        }
+        sum
+    }
+
+    /// sum of the cells inside the rectangle `rows` x `cols`.
+    pub fn sum(&self, rows: impl RangeBounds<usize>, cols: impl RangeBounds<usize>) -> T {
+        let (r0, r1) = resolve_range(rows, self.rows);
+        let (c0, c1) = resolve_range(cols, self.cols);
+        self.prefix_sum(r1, c1) - self.prefix_sum(r0, c1) - self.prefix_sum(r1, c0)
+            + self.prefix_sum(r0, c0)
+    }
+
+    /// returns the cell at (`row`, `col`).
+    pub fn get(&self, row: usize, col: usize) -> T {
+        self.sum(row..=row, col..=col)
+    }
+
+    /// replace the cell at (`row`, `col`).
+    pub fn set(&mut self, row: usize, col: usize, value: T) {
+        let old = self.get(row, col);
+        self.add(row, col, value - old);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefix_and_range() {
+        let mut tree = Fenwick::from(vec![3i64, 1, 4, 1, 5, 9, 2, 6]);
+        assert_eq!(tree.prefix_sum(0), 0);
+        assert_eq!(tree.prefix_sum(3), 8);
+        assert_eq!(tree.sum(..), 31);
+        assert_eq!(tree.sum(2..5), 10);
+        tree.add(3, 10);
+        assert_eq!(tree.sum(2..5), 20);
+        tree.set(0, -3);
+        assert_eq!(tree.get(0), -3);
+        assert_eq!(tree.sum(..=1), -2);
+    }
+
+    #[test]
+    fn test_matches_incremental_build() {
+        let values: Vec<u64> = (0..37).map(|i| (i * 7 + 3) % 11).collect();
+        let built = Fenwick::from(values.clone());
+        let mut incremental = Fenwick::new(values.len());
+        for (i, &v) in values.iter().enumerate() {
+            incremental.add(i, v);
+        }
+        for end in 0..=values.len() {
+            assert_eq!(built.prefix_sum(end), incremental.prefix_sum(end));
+            assert_eq!(built.prefix_sum(end), values[..end].iter().sum::<u64>());
+        }
+    }
+
+    #[test]
+    fn test_kth() {
+        // frequencies of the values 0..6
+        let tree = Fenwick::from(vec![2u32, 0, 3, 1, 0, 4]);
+        assert_eq!(tree.kth(1), Some(0));
+        assert_eq!(tree.kth(2), Some(0));
+        assert_eq!(tree.kth(3), Some(2));
+        assert_eq!(tree.kth(5), Some(2));
+        assert_eq!(tree.kth(6), Some(3));
+        assert_eq!(tree.kth(7), Some(5));
+        assert_eq!(tree.kth(10), Some(5));
+        assert_eq!(tree.kth(11), None);
+        assert_eq!(Fenwick::<u32>::new(0).kth(1), None);
+    }
+
+    #[test]
+    fn test_2d() {
+        let (rows, cols) = (5, 7);
+        let mut grid = vec![vec![0i64; cols]; rows];
+        let mut tree = Fenwick2D::new(rows, cols);
+        for (r, row) in grid.iter_mut().enumerate() {
+            for (c, cell) in row.iter_mut().enumerate() {
+                let v = ((r * 31 + c * 17) % 13) as i64 - 6;
+                *cell = v;
+                tree.add(r, c, v);
+            }
+        }
+        tree.set(2, 3, 100);
+        grid[2][3] = 100;
+        for r0 in 0..=rows {
+            for r1 in r0..=rows {
+                for c0 in 0..=cols {
+                    for c1 in c0..=cols {
+                        let expected: i64 = grid[r0..r1]
+                            .iter()
+                            .map(|row| row[c0..c1].iter().sum::<i64>())
+                            .sum();
+                        assert_eq!(tree.sum(r0..r1, c0..c1), expected);
+                    }
+                }
+            }
+        }
+        assert_eq!(tree.get(2, 3), 100);
+        assert_eq!(tree.sum(.., ..), grid.iter().flatten().sum::<i64>());
+    }
+}
@@ -0,0 +1,121 @@
+use super::{EdgeType, Graph, NodeId, ShortestPaths, Weight};
+
+/// single-source shortest paths allowing negative weights, in O(V * E),
+/// stopping early once a round changes nothing.
+///
+/// if a cycle of negative total weight is reachable from `source`, returns
+/// its nodes in edge order instead. an undirected edge counts both ways, so
+/// a single negative one is such a cycle.
+pub fn bellman_ford<N, E: Weight, Ty: EdgeType>(
+    graph: &Graph<N, E, Ty>,
+    source: NodeId,
+) -> Result<ShortestPaths<E>, Vec<NodeId>> {
+    let n = graph.node_count();
+    let mut paths = ShortestPaths::start(n, source);
+    for round in 0..n {
+        let mut changed = None;
+        for node in graph.node_ids() {
+            let d = match paths.distance(node) {
+                Some(d) => d,
+                None => continue,
+            };
+            for edge in graph.edges_from(node) {
+                if paths.relax(node, edge.id, edge.target, d + *edge.weight) {
+                    changed = Some(edge.target);
+                }
+            }
+        }
+        match changed {
+            None => return Ok(paths),
+            // still improving after n - 1 rounds.
+            Some(node) if round == n - 1 => return Err(negative_cycle(&paths, node, n)),
+            Some(_) => {}
+        }
+    }
+    Ok(paths)
+}
+
+// `node` was improved in the last round, so walking back n parents from it
+// lands on a cycle of the parent pointers, which is negative.
+fn negative_cycle<W: Weight>(paths: &ShortestPaths<W>, mut node: NodeId, n: usize) -> Vec<NodeId> {
+    for _ in 0..n {
+        node = paths.parent(node).unwrap().0;
+    }
+    let mut cycle = vec![node];
+    let mut current = paths.parent(node).unwrap().0;
+    while current != node {
+        cycle.push(current);
+        current = paths.parent(current).unwrap().0;
+    }
+    cycle.reverse();
+    cycle
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{DiGraph, UnGraph};
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_negative_weights() {
+        let graph: DiGraph<(), i32> = Graph::from_edges(
+            5,
+            vec![(0, 1, 4), (0, 2, 5), (1, 2, -3), (2, 3, 4), (3, 1, 2)],
+        );
+        let paths = bellman_ford(&graph, 0).unwrap();
+        let distances: Vec<_> = graph.node_ids().map(|v| paths.distance(v)).collect();
+        assert_eq!(distances, vec![Some(0), Some(4), Some(1), Some(5), None]);
+        assert_eq!(paths.path_to(3), Some(vec![0, 1, 2, 3]));
+        assert_eq!(paths.parent(2), Some((1, 2)));
+        assert_eq!(paths.path_to(4), None);
+
+        let cyclic: DiGraph<(), i32> = Graph::from_edges(
+            5,
+            vec![(0, 1, 1), (1, 2, 1), (2, 3, -2), (3, 1, -1), (3, 4, 1)],
+        );
+        let cycle = bellman_ford(&cyclic, 0).unwrap_err();
+        let mut sorted = cycle.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, vec![1, 2, 3]);
+        // the cycle is unreachable from 4.
+        assert!(bellman_ford(&cyclic, 4).is_ok());
+        let undirected: UnGraph<(), i64> = Graph::from_edges(2, vec![(0, 1, -1)]);
+        assert!(bellman_ford(&undirected, 0).is_err());
+    }
+
+    #[test]
+    fn test_random_cycles() {
+        let mut rng = Rng::new(58);
+        for _ in 0..100 {
+            let n = 1 + rng.index(10);
+            let edges: Vec<(usize, usize, i64)> = (0..rng.index(3 * n))
+                .map(|_| (rng.index(n), rng.index(n), rng.range(-3..20)))
+                .collect();
+            let graph: DiGraph<(), i64> = Graph::from_edges(n, edges);
+            match bellman_ford(&graph, 0) {
+                Ok(paths) => {
+                    // no edge can still be relaxed.
+                    for e in graph.edges() {
+                        if let Some(d) = paths.distance(e.source) {
+                            assert!(paths.distance(e.target).unwrap() <= d + e.weight);
+                        }
+                    }
+                }
+                Err(cycle) => {
+                    let mut total = 0;
+                    for (i, &v) in cycle.iter().enumerate() {
+                        let next = cycle[(i + 1) % cycle.len()];
+                        total += graph
+                            .edges_from(v)
+                            .filter(|e| e.target == next)
+                            .map(|e| *e.weight)
+                            .min()
+                            .unwrap();
+                    }
+                    assert!(total < 0);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use super::{EdgeId, NodeId, UnGraph};
+
+/// the weak points of an undirected graph.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Biconnectivity {
+    /// edges whose removal disconnects their endpoints, in increasing order.
+    pub bridges: Vec<EdgeId>,
+    /// nodes whose removal disconnects some other nodes, in increasing
+    /// order.
+    pub articulation_points: Vec<NodeId>,
+    /// the edges of each biconnected component: a maximal set where any two
+    /// edges lie on a common simple cycle, or a single bridge. self-loops
+    /// belong to none.
+    pub components: Vec<Vec<EdgeId>>,
+}
+
+const UNVISITED: usize = usize::MAX;
+
+/// find bridges, articulation points and biconnected components with one
+/// depth-first search in O(V + E). parallel edges are never bridges.
+pub fn biconnectivity<N, E>(graph: &UnGraph<N, E>) -> Biconnectivity {
+    let n = graph.node_count();
+    let mut discovered = vec![UNVISITED; n];
+    let mut low = vec![0; n];
+    let mut is_articulation = vec![false; n];
+    let mut result = Biconnectivity {
+        bridges: Vec::new(),
+        articulation_points: Vec::new(),
+        components: Vec::new(),
+    };
+    // edges of the components not yet closed off.
+    let mut pending: Vec<EdgeId> = Vec::new();
+    // the nodes being explored, the edge they were entered by and the next
+    // edge to follow.
+    let mut calls: Vec<(NodeId, EdgeId, usize)> = Vec::new();
+    let mut time = 0;
+    for root in graph.node_ids() {
+        if discovered[root] != UNVISITED {
+            continue;
+        }
+        discovered[root] = time;
+        low[root] = time;
+        time += 1;
+        let mut root_children = 0;
+        calls.push((root, usize::MAX, 0));
+        while let Some(&mut (node, entered_by, ref mut edge)) = calls.last_mut() {
+            if let Some(&(next, id)) = graph.outgoing[node].get(*edge) {
+                *edge += 1;
+                if id == entered_by {
+                    continue;
+                }
+                if discovered[next] == UNVISITED {
+                    discovered[next] = time;
+                    low[next] = time;
+                    time += 1;
+                    pending.push(id);
+                    calls.push((next, id, 0));
+                    if node == root {
+                        root_children += 1;
+                    }
+                } else if discovered[next] < discovered[node] {
+                    // a back edge to an ancestor.
+                    pending.push(id);
+                    low[node] = low[node].min(discovered[next]);
+                }
+                continue;
+            }
+            calls.pop();
+            let parent = match calls.last() {
+                Some(&(parent, _, _)) => parent,
+                None => break,
+            };
+            low[parent] = low[parent].min(low[node]);
+            if low[node] > discovered[parent] {
+                result.bridges.push(entered_by);
+            }
+            if low[node] >= discovered[parent] {
+                // nothing below `node` climbs above `parent`.
+                if parent != root {
+                    is_articulation[parent] = true;
+                }
+                let at = pending.iter().rposition(|&e| e == entered_by).unwrap();
+                result.components.push(pending.split_off(at));
+            }
+        }
+        if root_children > 1 {
+            is_articulation[root] = true;
+        }
+    }
+    result.bridges.sort_unstable();
+    result.articulation_points = (0..n).filter(|&v| is_articulation[v]).collect();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsu::DisjointSet;
+    use crate::graph::Graph;
+    use crate::rng::Rng;
+
+    fn components_without(
+        graph: &UnGraph<(), ()>,
+        node: Option<NodeId>,
+        edge: Option<EdgeId>,
+    ) -> usize {
+        let mut sets = DisjointSet::new(graph.node_count());
+        for e in graph.edges() {
+            let touches = node.is_some_and(|v| e.source == v || e.target == v);
+            if !touches && Some(e.id) != edge {
+                sets.union(e.source, e.target);
+            }
+        }
+        sets.count() - usize::from(node.is_some())
+    }
+
+    #[test]
+    fn test_example() {
+        // two triangles joined by the bridge 2-3, with a pendant 6 and a
+        // doubled edge 4-5.
+        let graph: UnGraph<(), ()> = Graph::from_edges(
+            7,
+            vec![
+                (0, 1),
+                (1, 2),
+                (2, 0),
+                (2, 3),
+                (3, 4),
+                (4, 5),
+                (5, 3),
+                (4, 5),
+                (5, 6),
+                (6, 6),
+            ],
+        );
+        let result = biconnectivity(&graph);
+        assert_eq!(result.bridges, vec![3, 8]);
+        assert_eq!(result.articulation_points, vec![2, 3, 5]);
+        let mut components: Vec<Vec<EdgeId>> = result
+            .components
+            .into_iter()
+            .map(|mut c| {
+                c.sort_unstable();
+                c
+            })
+            .collect();
+        components.sort();
+        assert_eq!(
+            components,
+            vec![vec![0, 1, 2], vec![3], vec![4, 5, 6, 7], vec![8]]
+        );
+    }
+
+    #[test]
+    fn test_against_removal() {
+        let mut rng = Rng::new(57);
+        for _ in 0..60 {
+            let n = 1 + rng.index(15);
+            let edges: Vec<(usize, usize)> = (0..rng.index(2 * n))
+                .map(|_| (rng.index(n), rng.index(n)))
+                .collect();
+            let graph: UnGraph<(), ()> = Graph::from_edges(n, edges);
+            let result = biconnectivity(&graph);
+            let base = components_without(&graph, None, None);
+            let bridges: Vec<EdgeId> = (0..graph.edge_count())
+                .filter(|&e| components_without(&graph, None, Some(e)) > base)
+                .collect();
+            assert_eq!(result.bridges, bridges);
+            let points: Vec<NodeId> = graph
+                .node_ids()
+                .filter(|&v| components_without(&graph, Some(v), None) > base)
+                .collect();
+            assert_eq!(result.articulation_points, points);
+            let mut covered: Vec<EdgeId> = result.components.concat();
+            covered.sort_unstable();
+            let non_loops: Vec<EdgeId> = graph
+                .edges()
+                .filter(|e| e.source != e.target)
+                .map(|e| e.id)
+                .collect();
+            assert_eq!(covered, non_loops);
+        }
+    }
+}
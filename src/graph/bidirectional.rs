@@ -0,0 +1,171 @@
+use super::dijkstra::min_priority;
+use super::{EdgeType, Graph, NodeId, Weight};
+use crate::{PriorityQueue, PriorityQueueImpl};
+
+/// a shortest path between two nodes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Route<W> {
+    pub distance: W,
+    pub path: Vec<NodeId>,
+    /// number of nodes the search settled, over both directions.
+    pub settled: usize,
+}
+
+/// point-to-point dijkstra searching forward from `source` and backward
+/// from `target` at once, always advancing the side that is closer, until
+/// the two frontiers together can no longer beat the best meeting found.
+///
+/// on graphs like road networks each side explores a ball about half the
+/// radius of a one-sided search. weights must not be negative.
+pub fn bidirectional_dijkstra<N, E: Weight, Ty: EdgeType>(
+    graph: &Graph<N, E, Ty>,
+    source: NodeId,
+    target: NodeId,
+) -> Option<Route<E>> {
+    let n = graph.node_count();
+    assert!(source < n && target < n, "node out of bounds");
+    // index 0 is the forward search, 1 the backward one.
+    let mut distance = [vec![None; n], vec![None; n]];
+    let mut parent = [vec![None; n], vec![None; n]];
+    let mut done = [vec![false; n], vec![false; n]];
+    let mut queues = [PriorityQueueImpl::new(), PriorityQueueImpl::new()];
+    for (side, &start) in [source, target].iter().enumerate() {
+        distance[side][start] = Some(E::zero());
+        queues[side].insert((start, E::zero()), min_priority(E::zero()));
+    }
+    let mut best: Option<(E, NodeId)> = if source == target {
+        Some((E::zero(), source))
+    } else {
+        None
+    };
+    let mut settled = 0;
+    loop {
+        for side in 0..2 {
+            while let Some(&(node, _)) = queues[side].peek() {
+                if !done[side][node] {
+                    break;
+                }
+                queues[side].pop();
+            }
+        }
+        let (forward, backward) = match (queues[0].peek(), queues[1].peek()) {
+            (Some(&(_, f)), Some(&(_, b))) => (f, b),
+            _ => break,
+        };
+        if best.is_some_and(|(b, _)| forward + backward >= b) {
+            break;
+        }
+        let side = if forward <= backward { 0 } else { 1 };
+        let (node, d) = queues[side].pop().unwrap();
+        done[side][node] = true;
+        settled += 1;
+        let edges: Box<dyn Iterator<Item = _>> = if side == 0 {
+            Box::new(graph.edges_from(node).map(|e| (e.target, *e.weight)))
+        } else {
+            Box::new(graph.edges_to(node).map(|e| (e.source, *e.weight)))
+        };
+        for (next, w) in edges {
+            assert!(w >= E::zero(), "negative edge weight");
+            let through = d + w;
+            if distance[side][next].is_none_or(|old| through < old) {
+                distance[side][next] = Some(through);
+                parent[side][next] = Some(node);
+                queues[side].insert((next, through), min_priority(through));
+            }
+            if let Some(rest) = distance[1 - side][next] {
+                let total = distance[side][next].unwrap() + rest;
+                if best.is_none_or(|(b, _)| total < b) {
+                    best = Some((total, next));
+                }
+            }
+        }
+    }
+    let (total, meet) = best?;
+    let mut path = vec![meet];
+    let mut current = meet;
+    while let Some(previous) = parent[0][current] {
+        path.push(previous);
+        current = previous;
+    }
+    path.reverse();
+    current = meet;
+    while let Some(next) = parent[1][current] {
+        path.push(next);
+        current = next;
+    }
+    Some(Route {
+        distance: total,
+        path,
+        settled,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::dijkstra::dijkstra;
+    use crate::graph::{DiGraph, UnGraph};
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_against_dijkstra() {
+        let mut rng = Rng::new(63);
+        for _ in 0..200 {
+            let n = 1 + rng.index(25);
+            let edges: Vec<(usize, usize, u64)> = (0..rng.index(4 * n))
+                .map(|_| (rng.index(n), rng.index(n), rng.below(30)))
+                .collect();
+            let graph: DiGraph<(), u64> = Graph::from_edges(n, edges);
+            let (source, target) = (rng.index(n), rng.index(n));
+            let expected = dijkstra(&graph, source).distance(target);
+            let route = bidirectional_dijkstra(&graph, source, target);
+            assert_eq!(route.as_ref().map(|r| r.distance), expected);
+            if let Some(route) = route {
+                assert_eq!(route.path[0], source);
+                assert_eq!(*route.path.last().unwrap(), target);
+                let total: u64 = route
+                    .path
+                    .windows(2)
+                    .map(|w| {
+                        graph
+                            .edges_from(w[0])
+                            .filter(|e| e.target == w[1])
+                            .map(|e| *e.weight)
+                            .min()
+                            .unwrap()
+                    })
+                    .sum();
+                assert_eq!(total, route.distance);
+            }
+        }
+    }
+
+    #[test]
+    fn test_grid_explores_less() {
+        // a 40x40 grid with unit weights, across the middle.
+        let side = 40;
+        let mut graph: UnGraph<(), u32> = Graph::new();
+        for _ in 0..side * side {
+            graph.add_node(());
+        }
+        for r in 0..side {
+            for c in 0..side {
+                if c + 1 < side {
+                    graph.add_edge(r * side + c, r * side + c + 1, 1);
+                }
+                if r + 1 < side {
+                    graph.add_edge(r * side + c, (r + 1) * side + c, 1);
+                }
+            }
+        }
+        let (source, target) = (20 * side + 5, 20 * side + 35);
+        let route = bidirectional_dijkstra(&graph, source, target).unwrap();
+        assert_eq!(route.distance, 30);
+        assert_eq!(route.path.len(), 31);
+        // a one-sided search settles every node within distance 30 first.
+        let ball = (0..graph.node_count())
+            .filter(|&v| (v / side).abs_diff(20) + (v % side).abs_diff(5) < 30)
+            .count();
+        assert!(route.settled < ball * 3 / 4, "{} vs {}", route.settled, ball);
+    }
+}
@@ -0,0 +1,185 @@
+use super::tree::RootedTree;
+use super::{NodeId, UnGraph};
+
+/// centroid decomposition of an undirected forest in O(n log n).
+///
+/// each component is split at a centroid, a node whose removal leaves
+/// pieces of at most half its size, and the pieces are split in turn, so
+/// every node lies below O(log n) centroids. `visit(centroid, removed)` is
+/// called on each centroid before its pieces, with `removed` marking it and
+/// every centroid chosen before it: the nodes reachable from `centroid`
+/// without crossing a removed one are its component. path problems are
+/// solved by handling, at each centroid, the paths through it.
+///
+/// returns the centroid tree, where each centroid is the parent of the
+/// centroids of its pieces.
+pub fn centroid_decomposition<N, E, F>(tree: &UnGraph<N, E>, mut visit: F) -> RootedTree
+where
+    F: FnMut(NodeId, &[bool]),
+{
+    let n = tree.node_count();
+    let mut removed = vec![false; n];
+    let mut parent = vec![None; n];
+    // scratch for the bfs over one component.
+    let mut order = Vec::new();
+    let mut bfs_parent = vec![usize::MAX; n];
+    let mut size = vec![0; n];
+    for start in 0..n {
+        if removed[start] {
+            continue;
+        }
+        // components to split, with the centroid they hang from.
+        let mut pending = vec![(start, None)];
+        while let Some((node, above)) = pending.pop() {
+            order.clear();
+            order.push(node);
+            bfs_parent[node] = usize::MAX;
+            let mut i = 0;
+            while i < order.len() {
+                let v = order[i];
+                for w in tree.neighbors(v) {
+                    if w != bfs_parent[v] && w != v && !removed[w] {
+                        bfs_parent[w] = v;
+                        order.push(w);
+                    }
+                }
+                i += 1;
+            }
+            for &v in order.iter().rev() {
+                size[v] = 1 + tree
+                    .neighbors(v)
+                    .filter(|&w| bfs_parent[w] == v && w != v && !removed[w])
+                    .map(|w| size[w])
+                    .sum::<usize>();
+            }
+            // walk towards the heavy side until no piece is too big.
+            let total = order.len();
+            let mut centroid = node;
+            while let Some(heavy) = tree.neighbors(centroid).find(|&w| {
+                bfs_parent[w] == centroid && w != centroid && !removed[w] && 2 * size[w] > total
+            }) {
+                centroid = heavy;
+            }
+            removed[centroid] = true;
+            parent[centroid] = above;
+            visit(centroid, &removed);
+            for w in tree.neighbors(centroid) {
+                if !removed[w] {
+                    pending.push((w, Some(centroid)));
+                }
+            }
+        }
+    }
+    RootedTree::from_parents(parent)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use super::*;
+    use crate::graph::test_util;
+    use crate::graph::Graph;
+    use crate::rng::Rng;
+
+    // distances from `from` to the nodes reachable without crossing `removed`.
+    fn depths(
+        tree: &UnGraph<(), ()>,
+        from: NodeId,
+        removed: &[bool],
+        blocked: NodeId,
+    ) -> Vec<usize> {
+        let mut seen = vec![false; tree.node_count()];
+        seen[from] = true;
+        seen[blocked] = true;
+        let mut queue = VecDeque::new();
+        queue.push_back((from, 1));
+        let mut out = Vec::new();
+        while let Some((v, d)) = queue.pop_front() {
+            out.push(d);
+            for w in tree.neighbors(v) {
+                if !seen[w] && !removed[w] {
+                    seen[w] = true;
+                    queue.push_back((w, d + 1));
+                }
+            }
+        }
+        out
+    }
+
+    // pairs from `depths` whose sum is at most `k`.
+    fn pairs_within(mut depths: Vec<usize>, k: usize) -> usize {
+        depths.sort_unstable();
+        let (mut count, mut hi) = (0, depths.len());
+        for lo in 0..depths.len() {
+            while hi > lo && depths[lo] + depths[hi - 1] > k {
+                hi -= 1;
+            }
+            if hi <= lo {
+                break;
+            }
+            count += hi - lo - 1;
+        }
+        count
+    }
+
+    fn random_forest(rng: &mut Rng, n: usize) -> UnGraph<(), ()> {
+        let parents = test_util::random_forest(rng, n);
+        let edges = parents
+            .iter()
+            .enumerate()
+            .filter_map(|(v, &p)| Some((v, p?)));
+        Graph::from_edges(n, edges)
+    }
+
+    #[test]
+    fn test_centroid_tree() {
+        // a path splits in the middle, then in the middle of each half.
+        let path: UnGraph<(), ()> = Graph::from_edges(7, (0..6).map(|v| (v, v + 1)));
+        let mut visited = Vec::new();
+        let centroids = centroid_decomposition(&path, |c, _| visited.push(c));
+        assert_eq!(visited[0], 3);
+        assert_eq!(centroids.parent(1), Some(3));
+        assert_eq!(centroids.parent(6), Some(5));
+        let mut rng = Rng::new(78);
+        for _ in 0..20 {
+            let n = 1 + rng.index(300);
+            let centroids = centroid_decomposition(&random_forest(&mut rng, n), |_, _| {});
+            let height = (0..n).map(|v| centroids.depth(v)).max().unwrap();
+            assert!(1 << height <= n);
+        }
+    }
+
+    #[test]
+    fn test_count_paths() {
+        let mut rng = Rng::new(79);
+        for _ in 0..30 {
+            let n = 1 + rng.index(60);
+            let k = rng.index(8);
+            let tree = random_forest(&mut rng, n);
+            let mut count = 0;
+            centroid_decomposition(&tree, |centroid, removed| {
+                // paths through `centroid`: all pairs of its component,
+                // less those within one piece.
+                let (mut all, mut inside) = (vec![0], 0);
+                for piece in tree.neighbors(centroid).filter(|&w| !removed[w]) {
+                    let below = depths(&tree, piece, removed, centroid);
+                    inside += pairs_within(below.clone(), k);
+                    all.extend(below);
+                }
+                count += pairs_within(all, k) - inside;
+            });
+            let mut expected = 0;
+            let nothing = vec![false; n];
+            for v in 0..n {
+                // distances from `v`, with itself at distance 0.
+                let within = depths(&tree, v, &nothing, v)
+                    .iter()
+                    .filter(|&&d| d - 1 <= k)
+                    .count();
+                expected += within - 1;
+            }
+            assert_eq!(count, expected / 2);
+        }
+    }
+}
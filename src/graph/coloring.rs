@@ -0,0 +1,171 @@
+use std::collections::BTreeSet;
+
+use super::{NodeId, UnGraph};
+use crate::rng::Rng;
+
+/// a proper coloring: adjacent nodes get different colors `0..count`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Coloring {
+    pub colors: Vec<usize>,
+    pub count: usize,
+}
+
+/// the order greedy coloring visits the nodes in. every order gives the
+/// same coloring on every run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Order {
+    /// by node id.
+    Index,
+    /// by decreasing degree, ties by id (welsh-powell).
+    LargestFirst,
+    /// the reverse of repeatedly removing a node of smallest degree; uses
+    /// at most one more color than the graph's degeneracy.
+    SmallestLast,
+    /// shuffled by an rng seeded with the given value.
+    Random(u64),
+}
+
+/// give each node in `order` the smallest color none of its neighbors has.
+/// at most one more color than the largest degree; self-loops are ignored.
+pub fn greedy_coloring<N, E>(graph: &UnGraph<N, E>, order: Order) -> Coloring {
+    let n = graph.node_count();
+    let mut nodes: Vec<NodeId> = (0..n).collect();
+    match order {
+        Order::Index => {}
+        Order::LargestFirst => nodes.sort_by_key(|&v| std::cmp::Reverse(degree(graph, v))),
+        Order::SmallestLast => nodes = smallest_last(graph),
+        Order::Random(seed) => Rng::new(seed).shuffle(&mut nodes),
+    }
+    let mut colors = vec![usize::MAX; n];
+    let mut taken = vec![usize::MAX; n + 1];
+    let mut count = 0;
+    for node in nodes {
+        for neighbor in graph.neighbors(node) {
+            if colors[neighbor] != usize::MAX {
+                taken[colors[neighbor]] = node;
+            }
+        }
+        let color = (0..).find(|&c| taken[c] != node).unwrap();
+        colors[node] = color;
+        count = count.max(color + 1);
+    }
+    Coloring { colors, count }
+}
+
+/// dsatur: always color next the node with the most distinct colors among
+/// its neighbors, ties by degree and then by id, giving it the smallest
+/// free color. exact on bipartite graphs and often better than greedy
+/// elsewhere. O((V + E) log V).
+pub fn dsatur<N, E>(graph: &UnGraph<N, E>) -> Coloring {
+    let n = graph.node_count();
+    let mut colors = vec![usize::MAX; n];
+    let mut seen: Vec<BTreeSet<usize>> = vec![BTreeSet::new(); n];
+    // uncolored nodes by (saturation, degree, reversed id).
+    let key = |v: NodeId, seen: &[BTreeSet<usize>]| (seen[v].len(), degree(graph, v), n - v);
+    let mut queue: BTreeSet<(usize, usize, usize)> = (0..n).map(|v| key(v, &seen)).collect();
+    let mut count = 0;
+    while let Some(top) = queue.pop_last() {
+        let node = n - top.2;
+        let color = (0..).find(|c| !seen[node].contains(c)).unwrap();
+        colors[node] = color;
+        count = count.max(color + 1);
+        for neighbor in graph.neighbors(node) {
+            if colors[neighbor] == usize::MAX && !seen[neighbor].contains(&color) {
+                queue.remove(&key(neighbor, &seen));
+                seen[neighbor].insert(color);
+                queue.insert(key(neighbor, &seen));
+            }
+        }
+    }
+    Coloring { colors, count }
+}
+
+// the degree without self-loops.
+fn degree<N, E>(graph: &UnGraph<N, E>, node: NodeId) -> usize {
+    graph.neighbors(node).filter(|&v| v != node).count()
+}
+
+fn smallest_last<N, E>(graph: &UnGraph<N, E>) -> Vec<NodeId> {
+    let n = graph.node_count();
+    let mut remaining: Vec<usize> = (0..n).map(|v| degree(graph, v)).collect();
+    let mut removed = vec![false; n];
+    let mut queue: BTreeSet<(usize, NodeId)> = (0..n).map(|v| (remaining[v], v)).collect();
+    let mut order = Vec::with_capacity(n);
+    while let Some((_, node)) = queue.pop_first() {
+        removed[node] = true;
+        order.push(node);
+        for neighbor in graph.neighbors(node) {
+            if !removed[neighbor] {
+                queue.remove(&(remaining[neighbor], neighbor));
+                remaining[neighbor] -= 1;
+                queue.insert((remaining[neighbor], neighbor));
+            }
+        }
+    }
+    order.reverse();
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Graph;
+
+    fn assert_proper(graph: &UnGraph<(), ()>, coloring: &Coloring) {
+        for edge in graph.edges() {
+            if edge.source != edge.target {
+                assert_ne!(coloring.colors[edge.source], coloring.colors[edge.target]);
+            }
+        }
+        assert!(coloring.colors.iter().all(|&c| c < coloring.count));
+    }
+
+    #[test]
+    fn test_small() {
+        // a crown graph: two colors suffice, but index order needs four.
+        let mut edges = Vec::new();
+        for a in 0..4 {
+            for b in 0..4 {
+                if a != b {
+                    edges.push((2 * a, 2 * b + 1));
+                }
+            }
+        }
+        let crown: UnGraph<(), ()> = Graph::from_edges(8, edges);
+        assert_eq!(greedy_coloring(&crown, Order::Index).count, 4);
+        assert_eq!(dsatur(&crown).count, 2);
+        assert_proper(&crown, &dsatur(&crown));
+        let triangle: UnGraph<(), ()> = Graph::from_edges(3, vec![(0, 1), (1, 2), (2, 0), (1, 1)]);
+        assert_eq!(dsatur(&triangle).count, 3);
+        assert_eq!(greedy_coloring(&triangle, Order::SmallestLast).count, 3);
+        let empty: UnGraph<(), ()> = Graph::new();
+        assert_eq!(dsatur(&empty).count, 0);
+    }
+
+    #[test]
+    fn test_random() {
+        let mut rng = Rng::new(72);
+        for _ in 0..50 {
+            let n = 1 + rng.index(30);
+            let edges: Vec<(usize, usize)> = (0..rng.index(3 * n))
+                .map(|_| (rng.index(n), rng.index(n)))
+                .collect();
+            let graph: UnGraph<(), ()> = Graph::from_edges(n, edges);
+            let max_degree = graph.node_ids().map(|v| degree(&graph, v)).max().unwrap();
+            for &order in &[
+                Order::Index,
+                Order::LargestFirst,
+                Order::SmallestLast,
+                Order::Random(7),
+            ] {
+                let coloring = greedy_coloring(&graph, order);
+                assert_proper(&graph, &coloring);
+                assert!(coloring.count <= max_degree + 1);
+                assert_eq!(coloring, greedy_coloring(&graph, order));
+            }
+            let coloring = dsatur(&graph);
+            assert_proper(&graph, &coloring);
+            assert!(coloring.count <= max_degree + 1);
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+
+use super::{NodeId, UnGraph};
+use crate::rng::Rng;
+
+/// a partition of the nodes into communities `0..count`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Communities {
+    pub labels: Vec<usize>,
+    pub count: usize,
+}
+
+/// asynchronous label propagation: every node starts in a community of its
+/// own, then, in a random order each round, joins the community most of its
+/// neighbors are in, ties broken at random. stops when no node would move,
+/// or after `max_rounds` rounds. near-linear per round, and the same `seed`
+/// always gives the same communities.
+pub fn label_propagation<N, E>(graph: &UnGraph<N, E>, seed: u64, max_rounds: usize) -> Communities {
+    let n = graph.node_count();
+    let mut rng = Rng::new(seed);
+    let mut labels: Vec<usize> = (0..n).collect();
+    let mut order: Vec<NodeId> = (0..n).collect();
+    let mut counts: HashMap<usize, usize> = HashMap::new();
+    let mut best = Vec::new();
+    for _ in 0..max_rounds {
+        rng.shuffle(&mut order);
+        let mut moved = false;
+        for &node in &order {
+            counts.clear();
+            for to in graph.neighbors(node).filter(|&to| to != node) {
+                *counts.entry(labels[to]).or_insert(0) += 1;
+            }
+            let top = match counts.values().max() {
+                Some(&top) => top,
+                None => continue,
+            };
+            if counts.get(&labels[node]) == Some(&top) {
+                continue;
+            }
+            // sorted, so the pick does not depend on the map's order.
+            best.clear();
+            best.extend(counts.iter().filter(|&(_, &c)| c == top).map(|(&l, _)| l));
+            best.sort_unstable();
+            labels[node] = best[rng.index(best.len())];
+            moved = true;
+        }
+        if !moved {
+            break;
+        }
+    }
+    // renumber the surviving labels by first appearance.
+    let mut renamed = vec![usize::MAX; n];
+    let mut count = 0;
+    for label in &mut labels {
+        if renamed[*label] == usize::MAX {
+            renamed[*label] = count;
+            count += 1;
+        }
+        *label = renamed[*label];
+    }
+    Communities { labels, count }
+}
+
+/// the modularity of a partition, given as the community of each node: the
+/// fraction of edges inside communities less its expectation in a random
+/// graph with the same degrees. between -1/2 and 1, and 0 without edges.
+pub fn modularity<N, E>(graph: &UnGraph<N, E>, labels: &[usize]) -> f64 {
+    assert_eq!(labels.len(), graph.node_count(), "one label per node");
+    let m = graph.edge_count() as f64;
+    if m == 0.0 {
+        return 0.0;
+    }
+    let communities = labels.iter().max().map_or(0, |&l| l + 1);
+    let mut inside = vec![0.0; communities];
+    let mut degree = vec![0.0; communities];
+    for edge in graph.edges() {
+        let (a, b) = (labels[edge.source], labels[edge.target]);
+        if a == b {
+            inside[a] += 1.0;
+        }
+        degree[a] += 1.0;
+        degree[b] += 1.0;
+    }
+    inside
+        .iter()
+        .zip(&degree)
+        .map(|(l, d)| l / m - (d / (2.0 * m)).powi(2))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Graph;
+
+    // `k` cliques of `size` nodes, joined in a ring by one edge each.
+    fn cliques(k: usize, size: usize) -> UnGraph<(), ()> {
+        let mut edges = Vec::new();
+        for c in 0..k {
+            for a in 0..size {
+                for b in a + 1..size {
+                    edges.push((c * size + a, c * size + b));
+                }
+            }
+            edges.push((c * size, (c + 1) % k * size + 1));
+        }
+        Graph::from_edges(k * size, edges)
+    }
+
+    #[test]
+    fn test_modularity() {
+        let graph = cliques(2, 3);
+        let split = modularity(&graph, &[0, 0, 0, 1, 1, 1]);
+        assert!(split > 0.2);
+        assert!(modularity(&graph, &[0; 6]).abs() < 1e-12);
+        assert!(modularity(&graph, &[0, 1, 0, 1, 0, 1]) < split);
+        let path: UnGraph<(), ()> = Graph::from_edges(2, vec![(0, 1)]);
+        assert!((modularity(&path, &[0, 1]) + 0.5).abs() < 1e-12);
+        let empty: UnGraph<(), ()> = Graph::from_edges(3, Vec::<(usize, usize)>::new());
+        assert_eq!(modularity(&empty, &[0, 1, 2]), 0.0);
+    }
+
+    #[test]
+    fn test_label_propagation() {
+        let graph = cliques(6, 8);
+        for seed in 0..5 {
+            let found = label_propagation(&graph, seed, 100);
+            assert_eq!(found, label_propagation(&graph, seed, 100));
+            assert!(found.labels.iter().all(|&l| l < found.count));
+            // every clique ends up in a single community.
+            for c in 0..6 {
+                let members = &found.labels[c * 8..(c + 1) * 8];
+                assert!(members.iter().all(|&l| l == members[0]));
+            }
+            if found.count == 6 {
+                let expected: Vec<usize> = (0..48).map(|v| v / 8).collect();
+                let best = modularity(&graph, &expected);
+                assert!((modularity(&graph, &found.labels) - best).abs() < 1e-12);
+            }
+        }
+        let lonely: UnGraph<(), ()> = Graph::from_edges(3, vec![(1, 1)]);
+        assert_eq!(label_propagation(&lonely, 1, 10).count, 3);
+    }
+}
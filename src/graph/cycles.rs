@@ -0,0 +1,218 @@
+use super::scc::tarjan_scc;
+use super::{DiGraph, EdgeId, EdgeType, Graph, NodeId};
+
+/// some cycle of `graph` as the nodes along it, each with an edge to the
+/// next and the last to the first, or `None` if it is acyclic (a forest,
+/// if undirected). a self-loop is a cycle of one node and two parallel
+/// undirected edges one of two. iterative dfs in O(V + E).
+pub fn find_cycle<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>) -> Option<Vec<NodeId>> {
+    let n = graph.node_count();
+    let mut discovered = vec![false; n];
+    let mut on_path = vec![false; n];
+    // the dfs path: each node, the edge it was entered by and its next
+    // outgoing edge to try.
+    let mut path: Vec<(NodeId, Option<EdgeId>, usize)> = Vec::new();
+    for root in 0..n {
+        if discovered[root] {
+            continue;
+        }
+        discovered[root] = true;
+        on_path[root] = true;
+        path.push((root, None, 0));
+        while let Some(&mut (node, via, ref mut next)) = path.last_mut() {
+            let (to, id) = match graph.outgoing[node].get(*next) {
+                Some(&arc) => arc,
+                None => {
+                    on_path[node] = false;
+                    path.pop();
+                    continue;
+                }
+            };
+            *next += 1;
+            if !Ty::DIRECTED && via == Some(id) {
+                continue;
+            }
+            if on_path[to] {
+                let start = path.iter().position(|&(v, _, _)| v == to).unwrap();
+                return Some(path[start..].iter().map(|&(v, _, _)| v).collect());
+            }
+            if !discovered[to] {
+                discovered[to] = true;
+                on_path[to] = true;
+                path.push((to, Some(id), 0));
+            }
+        }
+    }
+    None
+}
+
+/// every simple cycle of a directed graph, each once and starting from its
+/// smallest node, by johnson's algorithm in O((V + E) * (C + V)) for C
+/// cycles. there can be exponentially many, so this is for small graphs.
+pub fn simple_cycles<N, E>(graph: &DiGraph<N, E>) -> Vec<Vec<NodeId>> {
+    let n = graph.node_count();
+    let mut adjacency: Vec<Vec<NodeId>> = graph
+        .node_ids()
+        .map(|v| graph.neighbors(v).collect())
+        .collect();
+    for targets in &mut adjacency {
+        targets.sort_unstable();
+        targets.dedup();
+    }
+    let mut cycles = Vec::new();
+    let mut blocked = vec![false; n];
+    let mut blocking: Vec<Vec<NodeId>> = vec![Vec::new(); n];
+    for start in 0..n {
+        // the strongly connected component of `start` among nodes >= start.
+        let rest: DiGraph<(), ()> = Graph::from_edges(
+            n,
+            (start..n)
+                .flat_map(|v| adjacency[v].iter().map(move |&w| (v, w)))
+                .filter(|&(_, w)| w >= start),
+        );
+        let components = tarjan_scc(&rest);
+        let component = components.of(start);
+        let inside = |v: NodeId| v >= start && components.of(v) == component;
+        for v in (start..n).filter(|&v| inside(v)) {
+            blocked[v] = false;
+            blocking[v].clear();
+        }
+        blocked[start] = true;
+        let mut path = vec![start];
+        // per node on the path: its next neighbor to try and whether a
+        // cycle was found through it.
+        let mut frames: Vec<(usize, bool)> = vec![(0, false)];
+        while let Some(&mut (ref mut next, ref mut found)) = frames.last_mut() {
+            let node = *path.last().unwrap();
+            if let Some(&to) = adjacency[node].get(*next) {
+                *next += 1;
+                if !inside(to) {
+                    continue;
+                }
+                if to == start {
+                    cycles.push(path.clone());
+                    *found = true;
+                } else if !blocked[to] {
+                    blocked[to] = true;
+                    path.push(to);
+                    frames.push((0, false));
+                }
+                continue;
+            }
+            let found = *found;
+            if found {
+                unblock(node, &mut blocked, &mut blocking);
+            } else {
+                for &to in adjacency[node].iter().filter(|&&to| inside(to)) {
+                    if !blocking[to].contains(&node) {
+                        blocking[to].push(node);
+                    }
+                }
+            }
+            path.pop();
+            frames.pop();
+            if let Some(parent) = frames.last_mut() {
+                parent.1 |= found;
+            }
+        }
+    }
+    cycles
+}
+
+// unblock `node` and, transitively, the nodes waiting on it.
+fn unblock(node: NodeId, blocked: &mut [bool], blocking: &mut [Vec<NodeId>]) {
+    let mut stack = vec![node];
+    while let Some(node) = stack.pop() {
+        if !blocked[node] {
+            continue;
+        }
+        blocked[node] = false;
+        stack.append(&mut blocking[node]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsu::DisjointSet;
+    use crate::graph::topo::toposort;
+    use crate::graph::UnGraph;
+    use crate::rng::Rng;
+
+    fn assert_cycle<Ty: EdgeType>(graph: &Graph<(), (), Ty>, cycle: &[NodeId]) {
+        for (i, &node) in cycle.iter().enumerate() {
+            assert!(graph.contains_edge(node, cycle[(i + 1) % cycle.len()]));
+        }
+    }
+
+    // the simple cycles starting from their smallest node, by trying every
+    // path.
+    fn brute_force(graph: &DiGraph<(), ()>) -> Vec<Vec<NodeId>> {
+        fn go(graph: &DiGraph<(), ()>, path: &mut Vec<NodeId>, out: &mut Vec<Vec<NodeId>>) {
+            let (start, node) = (path[0], *path.last().unwrap());
+            for to in 0..graph.node_count() {
+                if !graph.contains_edge(node, to) {
+                    continue;
+                }
+                if to == start {
+                    out.push(path.clone());
+                } else if to > start && !path.contains(&to) {
+                    path.push(to);
+                    go(graph, path, out);
+                    path.pop();
+                }
+            }
+        }
+        let mut out = Vec::new();
+        for start in graph.node_ids() {
+            go(graph, &mut vec![start], &mut out);
+        }
+        out
+    }
+
+    #[test]
+    fn test_small() {
+        let dag: DiGraph<(), ()> = Graph::from_edges(4, vec![(0, 1), (1, 2), (0, 2), (2, 3)]);
+        assert_eq!(find_cycle(&dag), None);
+        assert!(simple_cycles(&dag).is_empty());
+        let graph: DiGraph<(), ()> =
+            Graph::from_edges(4, vec![(0, 1), (1, 2), (2, 0), (2, 3), (3, 3), (1, 0)]);
+        assert_eq!(find_cycle(&graph), Some(vec![0, 1, 2]));
+        let mut cycles = simple_cycles(&graph);
+        cycles.sort();
+        assert_eq!(cycles, vec![vec![0, 1], vec![0, 1, 2], vec![3]]);
+        let tree: UnGraph<(), ()> = Graph::from_edges(4, vec![(0, 1), (1, 2), (1, 3)]);
+        assert_eq!(find_cycle(&tree), None);
+        let double: UnGraph<(), ()> = Graph::from_edges(3, vec![(0, 1), (1, 2), (2, 1)]);
+        assert_eq!(find_cycle(&double), Some(vec![1, 2]));
+    }
+
+    #[test]
+    fn test_random() {
+        let mut rng = Rng::new(73);
+        for _ in 0..100 {
+            let n = 1 + rng.index(7);
+            let edges: Vec<(usize, usize)> = (0..rng.index(2 * n))
+                .map(|_| (rng.index(n), rng.index(n)))
+                .collect();
+            let directed: DiGraph<(), ()> = Graph::from_edges(n, edges.clone());
+            match find_cycle(&directed) {
+                Some(cycle) => assert_cycle(&directed, &cycle),
+                None => assert!(toposort(&directed).is_ok()),
+            }
+            let mut cycles = simple_cycles(&directed);
+            let mut expected = brute_force(&directed);
+            cycles.sort();
+            expected.sort();
+            assert_eq!(cycles, expected);
+            let undirected: UnGraph<(), ()> = Graph::from_edges(n, edges.clone());
+            let mut sets = DisjointSet::new(n);
+            let forest = edges.iter().all(|&(a, b)| sets.union(a, b));
+            match find_cycle(&undirected) {
+                Some(cycle) => assert_cycle(&undirected, &cycle),
+                None => assert!(forest),
+            }
+            assert_eq!(find_cycle(&undirected).is_none(), forest);
+        }
+    }
+}
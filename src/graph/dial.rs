@@ -0,0 +1,203 @@
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+
+use super::dijkstra::dijkstra_by;
+use super::{EdgeType, Graph, NodeId, ShortestPaths};
+
+// the most buckets `dial` allocates; beyond, dijkstra with a heap is used.
+const MAX_BUCKETS: usize = 1 << 20;
+
+/// monotone priority queue over integer keys, as a ring of buckets: every
+/// key pushed must lie in `last..last + span`, where `last` is the key
+/// popped last (0 at first). push is O(1) and pop is amortized O(1) plus
+/// the empty buckets skipped.
+#[derive(Clone, Debug)]
+pub struct BucketQueue<T> {
+    buckets: Vec<Vec<T>>,
+    // the key of the bucket popped from last.
+    current: u64,
+    len: usize,
+}
+
+impl<T> BucketQueue<T> {
+    pub fn new(span: usize) -> Self {
+        assert!(span > 0, "span must be positive");
+        BucketQueue {
+            buckets: (0..span).map(|_| Vec::new()).collect(),
+            current: 0,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn push(&mut self, key: u64, item: T) {
+        let span = self.buckets.len() as u64;
+        assert!(
+            key >= self.current && key - self.current < span,
+            "key {} outside the window from {}",
+            key,
+            self.current
+        );
+        self.buckets[(key % span) as usize].push(item);
+        self.len += 1;
+    }
+
+    /// remove an item with the smallest key, returning it with its key.
+    pub fn pop(&mut self) -> Option<(u64, T)> {
+        if self.len == 0 {
+            return None;
+        }
+        let span = self.buckets.len() as u64;
+        loop {
+            if let Some(item) = self.buckets[(self.current % span) as usize].pop() {
+                self.len -= 1;
+                return Some((self.current, item));
+            }
+            self.current += 1;
+        }
+    }
+}
+
+/// shortest paths when every weight is 0 or 1, in O(V + E): nodes reached
+/// by a 0-edge go to the front of a deque, by a 1-edge to the back.
+pub fn zero_one_bfs<N, E, Ty>(graph: &Graph<N, E, Ty>, source: NodeId) -> ShortestPaths<u64>
+where
+    E: Copy + Into<u64>,
+    Ty: EdgeType,
+{
+    let mut paths = ShortestPaths::start(graph.node_count(), source);
+    let mut done = vec![false; graph.node_count()];
+    let mut deque = VecDeque::new();
+    deque.push_back((source, 0));
+    while let Some((node, d)) = deque.pop_front() {
+        if done[node] {
+            continue;
+        }
+        done[node] = true;
+        for edge in graph.edges_from(node) {
+            let w: u64 = (*edge.weight).into();
+            assert!(w <= 1, "weight {} is not 0 or 1", w);
+            if paths.relax(node, edge.id, edge.target, d + w) {
+                if w == 0 {
+                    deque.push_front((edge.target, d));
+                } else {
+                    deque.push_back((edge.target, d + 1));
+                }
+            }
+        }
+    }
+    paths
+}
+
+/// dial's algorithm: dijkstra on a [`BucketQueue`], in O(V * C + E) for
+/// integer weights up to `C`. beats a heap when weights are small; with
+/// weights past about a million it falls back to plain dijkstra rather
+/// than allocate a bucket for each.
+pub fn dial<N, E, Ty>(graph: &Graph<N, E, Ty>, source: NodeId) -> ShortestPaths<u64>
+where
+    E: Copy + Into<u64>,
+    Ty: EdgeType,
+{
+    let max = graph
+        .edges
+        .iter()
+        .map(|e| e.weight.into())
+        .max()
+        .unwrap_or(0);
+    let span = usize::try_from(max)
+        .ok()
+        .and_then(|max| max.checked_add(1))
+        .filter(|&span| span <= MAX_BUCKETS);
+    let span = match span {
+        Some(span) => span,
+        None => return dijkstra_by(graph, source, |edge| (*edge.weight).into()),
+    };
+    let mut paths = ShortestPaths::start(graph.node_count(), source);
+    let mut done = vec![false; graph.node_count()];
+    let mut queue = BucketQueue::new(span);
+    queue.push(0, source);
+    while let Some((d, node)) = queue.pop() {
+        if done[node] {
+            continue;
+        }
+        done[node] = true;
+        for edge in graph.edges_from(node) {
+            let through = d + (*edge.weight).into();
+            if paths.relax(node, edge.id, edge.target, through) {
+                queue.push(through, edge.target);
+            }
+        }
+    }
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::dijkstra::dijkstra;
+    use crate::graph::{DiGraph, UnGraph};
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_bucket_queue() {
+        let mut queue = BucketQueue::new(4);
+        queue.push(3, 'c');
+        queue.push(0, 'a');
+        queue.push(2, 'b');
+        assert_eq!(queue.pop(), Some((0, 'a')));
+        assert_eq!(queue.pop(), Some((2, 'b')));
+        // the window has moved on to 2..6, wrapping around the ring.
+        queue.push(5, 'd');
+        assert_eq!(queue.len(), 2);
+        let order: Vec<_> = std::iter::from_fn(|| queue.pop()).collect();
+        assert_eq!(order, vec![(3, 'c'), (5, 'd')]);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_against_dijkstra() {
+        let mut rng = Rng::new(64);
+        for _ in 0..60 {
+            let n = 1 + rng.index(40);
+            let picks: Vec<(usize, usize, u8)> = (0..rng.index(4 * n))
+                .map(|_| (rng.index(n), rng.index(n), rng.below(6) as u8))
+                .collect();
+            let source = rng.index(n);
+            let graph: DiGraph<(), u8> = Graph::from_edges(n, picks.clone());
+            let expected = dijkstra(&graph, source);
+            let paths = dial(&graph, source);
+            let binary: UnGraph<(), bool> =
+                Graph::from_edges(n, picks.iter().map(|&(a, b, w)| (a, b, w % 2 == 1)));
+            let as_u8: UnGraph<(), u8> =
+                Graph::from_edges(n, picks.iter().map(|&(a, b, w)| (a, b, w % 2)));
+            let bfs = zero_one_bfs(&binary, source);
+            let expected_bfs = dijkstra(&as_u8, source);
+            for v in graph.node_ids() {
+                assert_eq!(paths.distance(v), expected.distance(v).map(u64::from));
+                assert_eq!(bfs.distance(v), expected_bfs.distance(v).map(u64::from));
+            }
+        }
+    }
+
+    #[test]
+    fn test_large_weights() {
+        // too many buckets for these: the heap takes over.
+        for &big in &[u64::MAX / 4, u64::from(u32::MAX), MAX_BUCKETS as u64] {
+            let graph: DiGraph<(), u64> =
+                Graph::from_edges(4, vec![(0, 1, big), (0, 2, 1), (2, 1, 2), (1, 3, big)]);
+            let paths = dial(&graph, 0);
+            assert_eq!(paths.distance(1), Some(3));
+            assert_eq!(paths.distance(3), Some(3 + big));
+            assert_eq!(paths.path_to(3), Some(vec![0, 2, 1, 3]));
+        }
+        let wide: DiGraph<(), u64> = Graph::from_edges(2, vec![(0, 1, u64::MAX)]);
+        assert_eq!(dial(&wide, 0).distance(1), Some(u64::MAX));
+    }
+}
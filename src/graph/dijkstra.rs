@@ -0,0 +1,93 @@
+use super::{EdgeRef, EdgeType, Graph, NodeId, ShortestPaths, Weight};
+use crate::{PriorityQueue, PriorityQueueImpl};
+
+/// single-source shortest paths for non-negative weights, in
+/// O((V + E) log V) with the crate's priority queue.
+pub fn dijkstra<N, E: Weight, Ty: EdgeType>(
+    graph: &Graph<N, E, Ty>,
+    source: NodeId,
+) -> ShortestPaths<E> {
+    dijkstra_by(graph, source, |edge| *edge.weight)
+}
+
+// dijkstra over weights computed from each edge, which must not be negative.
+pub(crate) fn dijkstra_by<N, E, Ty, W, F>(
+    graph: &Graph<N, E, Ty>,
+    source: NodeId,
+    weight: F,
+) -> ShortestPaths<W>
+where
+    Ty: EdgeType,
+    W: Weight,
+    F: Fn(EdgeRef<'_, E>) -> W,
+{
+    let mut paths = ShortestPaths::start(graph.node_count(), source);
+    let mut done = vec![false; graph.node_count()];
+    let mut queue = PriorityQueueImpl::new();
+    queue.insert((source, W::zero()), min_priority(W::zero()));
+    while let Some((node, d)) = queue.pop() {
+        if done[node] {
+            continue;
+        }
+        done[node] = true;
+        for edge in graph.edges_from(node) {
+            let w = weight(edge);
+            assert!(w >= W::zero(), "negative edge weight");
+            if !done[edge.target] && paths.relax(node, edge.id, edge.target, d + w) {
+                queue.insert((edge.target, d + w), min_priority(d + w));
+            }
+        }
+    }
+    paths
+}
+
+// the queue pops the largest priority first.
+pub(crate) fn min_priority<W: Weight>(distance: W) -> u64 {
+    u64::MAX - distance.order_key()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::bellman_ford::bellman_ford;
+    use crate::graph::{DiGraph, UnGraph};
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_small() {
+        let graph: UnGraph<(), f64> = Graph::from_edges(
+            5,
+            vec![
+                (0, 1, 7.0),
+                (0, 2, 9.0),
+                (0, 4, 14.0),
+                (1, 2, 10.0),
+                (2, 4, 2.0),
+                (3, 4, 9.0),
+            ],
+        );
+        let paths = dijkstra(&graph, 0);
+        assert_eq!(paths.distance(4), Some(11.0));
+        assert_eq!(paths.distance(3), Some(20.0));
+        assert_eq!(paths.path_to(3), Some(vec![0, 2, 4, 3]));
+        assert_eq!(paths.source(), 0);
+    }
+
+    #[test]
+    fn test_against_bellman_ford() {
+        let mut rng = Rng::new(60);
+        for _ in 0..50 {
+            let n = 1 + rng.index(30);
+            let edges: Vec<(usize, usize, u32)> = (0..rng.index(4 * n))
+                .map(|_| (rng.index(n), rng.index(n), rng.below(50) as u32))
+                .collect();
+            let graph: DiGraph<(), u32> = Graph::from_edges(n, edges);
+            let source = rng.index(n);
+            let expected = bellman_ford(&graph, source).unwrap();
+            let paths = dijkstra(&graph, source);
+            for v in graph.node_ids() {
+                assert_eq!(paths.distance(v), expected.distance(v));
+            }
+        }
+    }
+}
@@ -0,0 +1,443 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use super::{EdgeType, Graph, NodeId};
+
+impl<N, E, Ty: EdgeType> Graph<N, E, Ty> {
+    /// the graph in graphviz's dot language, nodes and edges labelled with
+    /// their weights.
+    pub fn to_dot(&self) -> String
+    where
+        N: fmt::Display,
+        E: fmt::Display,
+    {
+        self.to_dot_with(|n| n.to_string(), |e| e.to_string())
+    }
+
+    /// the graph in dot, labelling nodes and edges by the given functions;
+    /// empty labels are left out. nodes are named by their ids.
+    pub fn to_dot_with<F, G>(&self, node_label: F, edge_label: G) -> String
+    where
+        F: Fn(&N) -> String,
+        G: Fn(&E) -> String,
+    {
+        let (keyword, arrow) = if Ty::DIRECTED {
+            ("digraph", "->")
+        } else {
+            ("graph", "--")
+        };
+        let mut out = format!("{} {{\n", keyword);
+        for (id, node) in self.nodes.iter().enumerate() {
+            out += &format!("    {}{};\n", id, attributes(&node_label(node)));
+        }
+        for edge in &self.edges {
+            let label = attributes(&edge_label(&edge.weight));
+            out += &format!("    {} {} {}{};\n", edge.source, arrow, edge.target, label);
+        }
+        out += "}\n";
+        out
+    }
+}
+
+fn attributes(label: &str) -> String {
+    if label.is_empty() {
+        return String::new();
+    }
+    let escaped = label.replace('\\', "\\\\").replace('"', "\\\"");
+    format!(" [label=\"{}\"]", escaped)
+}
+
+/// a node read from dot, with its name and label.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DotNode {
+    pub name: String,
+    pub label: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DotEdge {
+    pub source: NodeId,
+    pub target: NodeId,
+    pub label: Option<String>,
+}
+
+/// a graph read from the dot language: nodes in order of first mention,
+/// and edges in order, with their `label` attributes.
+///
+/// only a minimal subset is read: `graph` or `digraph` bodies of node
+/// statements, edge statements (chains too) and attribute lists. other
+/// attributes and attribute statements are skipped; subgraphs and ports are
+/// errors.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Dot {
+    pub directed: bool,
+    pub nodes: Vec<DotNode>,
+    pub edges: Vec<DotEdge>,
+}
+
+/// why dot input could not be read.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DotError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for DotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for DotError {}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    // an identifier, number or string; `true` if it was quoted, so it is
+    // never a keyword.
+    Id(String, bool),
+    Symbol(&'static str),
+}
+
+const SYMBOLS: [&str; 8] = ["->", "--", "{", "}", "[", "]", "=", ";"];
+
+fn tokenize(input: &str) -> Result<Vec<(Token, usize)>, DotError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let (mut i, mut line) = (0, 1);
+    let error = |line, message: &str| DotError {
+        line,
+        message: message.to_string(),
+    };
+    while i < chars.len() {
+        let c = chars[i];
+        let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+        if c == '\n' {
+            line += 1;
+            i += 1;
+        } else if c.is_whitespace() || c == ',' {
+            i += 1;
+        } else if rest == "//" || (c == '#' && line_start(&chars[..i])) {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if rest == "/*" {
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                line += (chars[i] == '\n') as usize;
+                i += 1;
+            }
+            if i >= chars.len() {
+                return Err(error(line, "unterminated comment"));
+            }
+            i += 2;
+        } else if c == '"' {
+            let start = line;
+            let mut text = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    None => return Err(error(start, "unterminated string")),
+                    Some('"') => break,
+                    Some('\\') if matches!(chars.get(i + 1), Some('"') | Some('\\')) => {
+                        text.push(chars[i + 1]);
+                        i += 2;
+                    }
+                    Some(&c) => {
+                        line += (c == '\n') as usize;
+                        text.push(c);
+                        i += 1;
+                    }
+                }
+            }
+            i += 1;
+            tokens.push((Token::Id(text, true), start));
+        } else if c.is_alphanumeric()
+            || c == '_'
+            || c == '.'
+            || c == '-' && rest != "->" && rest != "--"
+        {
+            let start = i;
+            i += 1;
+            while i < chars.len()
+                && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '.')
+            {
+                i += 1;
+            }
+            tokens.push((Token::Id(chars[start..i].iter().collect(), false), line));
+        } else if let Some(&symbol) = SYMBOLS.iter().find(|s| rest.starts_with(*s)) {
+            i += symbol.len();
+            tokens.push((Token::Symbol(symbol), line));
+        } else {
+            return Err(error(line, &format!("unexpected character {:?}", c)));
+        }
+    }
+    Ok(tokens)
+}
+
+// whether only blanks come before the end of `before` on its line.
+fn line_start(before: &[char]) -> bool {
+    before
+        .iter()
+        .rev()
+        .take_while(|&&c| c != '\n')
+        .all(|c| c.is_whitespace())
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    at: usize,
+    dot: Dot,
+    names: HashMap<String, NodeId>,
+}
+
+impl Parser {
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.at)
+            .or_else(|| self.tokens.last())
+            .map_or(1, |t| t.1)
+    }
+
+    fn error<T>(&self, message: &str) -> Result<T, DotError> {
+        Err(DotError {
+            line: self.line(),
+            message: message.to_string(),
+        })
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.at).map(|t| &t.0)
+    }
+
+    fn eat(&mut self, symbol: &str) -> bool {
+        let found = matches!(self.peek(), Some(Token::Symbol(s)) if *s == symbol);
+        self.at += found as usize;
+        found
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), DotError> {
+        if self.eat(symbol) {
+            Ok(())
+        } else {
+            self.error(&format!("expected {:?}", symbol))
+        }
+    }
+
+    fn id(&mut self) -> Result<String, DotError> {
+        match self.peek() {
+            Some(Token::Id(text, _)) => {
+                let id = text.clone();
+                self.at += 1;
+                Ok(id)
+            }
+            _ => self.error("expected an identifier"),
+        }
+    }
+
+    fn keyword(&self, word: &str) -> bool {
+        matches!(self.peek(), Some(Token::Id(text, false)) if text.eq_ignore_ascii_case(word))
+    }
+
+    // `[a=b, ...]` lists, returning the last `label`.
+    fn attributes(&mut self) -> Result<Option<String>, DotError> {
+        let mut label = None;
+        while self.eat("[") {
+            while !self.eat("]") {
+                let key = self.id()?;
+                self.expect("=")?;
+                let value = self.id()?;
+                if key == "label" {
+                    label = Some(value);
+                }
+                self.eat(";");
+            }
+        }
+        Ok(label)
+    }
+
+    fn node(&mut self, name: String) -> NodeId {
+        let nodes = &mut self.dot.nodes;
+        *self.names.entry(name.clone()).or_insert_with(|| {
+            nodes.push(DotNode { name, label: None });
+            nodes.len() - 1
+        })
+    }
+
+    fn statement(&mut self) -> Result<(), DotError> {
+        if self.keyword("subgraph") || self.peek() == Some(&Token::Symbol("{")) {
+            return self.error("subgraphs are not supported");
+        }
+        if self.keyword("graph") || self.keyword("node") || self.keyword("edge") {
+            self.at += 1;
+            return self.attributes().map(|_| ());
+        }
+        let first = self.id()?;
+        if self.eat("=") {
+            return self.id().map(|_| ());
+        }
+        let mut chain = vec![self.node(first)];
+        let arrow = if self.dot.directed { "->" } else { "--" };
+        loop {
+            if self.eat(arrow) {
+                let name = self.id()?;
+                chain.push(self.node(name));
+            } else if self.eat(if self.dot.directed { "--" } else { "->" }) {
+                self.at -= 1;
+                return self.error(&format!("edges must be written {}", arrow));
+            } else {
+                break;
+            }
+        }
+        let label = self.attributes()?;
+        if chain.len() == 1 {
+            if label.is_some() {
+                self.dot.nodes[chain[0]].label = label;
+            }
+        } else {
+            for pair in chain.windows(2) {
+                self.dot.edges.push(DotEdge {
+                    source: pair[0],
+                    target: pair[1],
+                    label: label.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Dot {
+    pub fn parse(input: &str) -> Result<Dot, DotError> {
+        let mut parser = Parser {
+            tokens: tokenize(input)?,
+            at: 0,
+            dot: Dot {
+                directed: false,
+                nodes: Vec::new(),
+                edges: Vec::new(),
+            },
+            names: HashMap::new(),
+        };
+        if parser.keyword("strict") {
+            parser.at += 1;
+        }
+        if parser.keyword("digraph") {
+            parser.dot.directed = true;
+        } else if !parser.keyword("graph") {
+            return parser.error("expected graph or digraph");
+        }
+        parser.at += 1;
+        if let Some(Token::Id(..)) = parser.peek() {
+            parser.at += 1;
+        }
+        parser.expect("{")?;
+        while !parser.eat("}") {
+            if parser.peek().is_none() {
+                return parser.error("expected \"}\"");
+            }
+            parser.statement()?;
+            parser.eat(";");
+        }
+        if parser.peek().is_some() {
+            return parser.error("trailing input after the graph");
+        }
+        Ok(parser.dot)
+    }
+
+    /// build a graph with weights made from the nodes and edges read. panics
+    /// if the dot graph's direction does not match `Ty`.
+    pub fn to_graph<N, E, Ty, F, G>(&self, mut node: F, mut edge: G) -> Graph<N, E, Ty>
+    where
+        Ty: EdgeType,
+        F: FnMut(&DotNode) -> N,
+        G: FnMut(&DotEdge) -> E,
+    {
+        assert_eq!(self.directed, Ty::DIRECTED, "dot graph direction mismatch");
+        let mut graph = Graph::new();
+        for n in &self.nodes {
+            graph.add_node(node(n));
+        }
+        for e in &self.edges {
+            graph.add_edge(e.source, e.target, edge(e));
+        }
+        graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{DiGraph, UnGraph};
+
+    #[test]
+    fn test_round_trip() {
+        let graph: DiGraph<&str, u32> = {
+            let mut g = Graph::new();
+            let a = g.add_node("a \"quoted\" node");
+            let b = g.add_node("b");
+            g.add_edge(a, b, 3);
+            g.add_edge(b, b, 7);
+            g
+        };
+        let text = graph.to_dot();
+        assert_eq!(
+            text,
+            "digraph {\n    0 [label=\"a \\\"quoted\\\" node\"];\n    1 [label=\"b\"];\n    0 -> 1 [label=\"3\"];\n    1 -> 1 [label=\"7\"];\n}\n"
+        );
+        let dot = Dot::parse(&text).unwrap();
+        let back: DiGraph<String, u32> = dot.to_graph(
+            |n| n.label.clone().unwrap(),
+            |e| e.label.as_ref().unwrap().parse().unwrap(),
+        );
+        assert_eq!(back.node(0).unwrap(), "a \"quoted\" node");
+        assert_eq!(
+            back.edges()
+                .map(|e| (e.source, e.target, *e.weight))
+                .collect::<Vec<_>>(),
+            vec![(0, 1, 3), (1, 1, 7)]
+        );
+        let plain: UnGraph<(), ()> = Graph::from_edges(3, vec![(0, 1), (1, 2)]);
+        assert_eq!(
+            plain.to_dot_with(|_| String::new(), |_| String::new()),
+            "graph {\n    0;\n    1;\n    2;\n    0 -- 1;\n    1 -- 2;\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        let text = r#"
+            /* a fixture */
+            strict graph "roads" {
+                graph [rankdir=LR]
+                node [shape=box];
+                rankdir = TB
+                a -- b -- c [label=5, color=red]  // a chain
+            # a comment line
+                "d e" [label="far away"]
+                c -- "d e"
+            }
+        "#;
+        let dot = Dot::parse(text).unwrap();
+        assert!(!dot.directed);
+        let names: Vec<&str> = dot.nodes.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(names, vec!["a", "b", "c", "d e"]);
+        assert_eq!(dot.nodes[3].label.as_deref(), Some("far away"));
+        assert_eq!(dot.edges.len(), 3);
+        assert_eq!(dot.edges[1].label.as_deref(), Some("5"));
+        assert_eq!(dot.edges[2].label, None);
+        let graph: UnGraph<(), ()> = dot.to_graph(|_| (), |_| ());
+        assert!(graph.contains_edge(3, 2));
+        let error = |text: &str| Dot::parse(text).unwrap_err();
+        assert_eq!(
+            error("graph { a -> b }").message,
+            "edges must be written --"
+        );
+        assert_eq!(error("digraph {\n a -> { b c } }").line, 2);
+        assert_eq!(error("digraph { a -> b").message, "expected \"}\"");
+        assert_eq!(
+            error("digraph { a:n -> b }").message,
+            "unexpected character ':'"
+        );
+        assert_eq!(error("tree { }").message, "expected graph or digraph");
+    }
+}
@@ -0,0 +1,180 @@
+use super::{EdgeId, EdgeType, Graph, NodeId};
+
+/// a walk using every edge exactly once.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Trail {
+    /// the nodes visited, one more than the edges (or none at all).
+    pub nodes: Vec<NodeId>,
+    pub edges: Vec<EdgeId>,
+}
+
+/// why a graph has no eulerian path or circuit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EulerError {
+    /// the edges do not all lie in one connected component.
+    Disconnected,
+    /// these nodes have degrees no such walk allows: odd ones for an
+    /// undirected graph, ones whose in- and out-degree differ for a directed
+    /// graph.
+    Unbalanced(Vec<NodeId>),
+}
+
+/// an eulerian path, starting and ending at the nodes it must; a circuit
+/// if the degrees allow one. hierholzer's algorithm in O(V + E).
+pub fn euler_path<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>) -> Result<Trail, EulerError> {
+    trail(graph, false)
+}
+
+/// an eulerian circuit, a closed walk returning to where it started.
+pub fn euler_circuit<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>) -> Result<Trail, EulerError> {
+    trail(graph, true)
+}
+
+fn trail<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>, closed: bool) -> Result<Trail, EulerError> {
+    // out-degree minus in-degree if directed, degree parity otherwise.
+    let mut balance = vec![0i64; graph.node_count()];
+    for edge in graph.edges() {
+        if Ty::DIRECTED {
+            balance[edge.source] += 1;
+            balance[edge.target] -= 1;
+        } else if edge.source != edge.target {
+            balance[edge.source] ^= 1;
+            balance[edge.target] ^= 1;
+        }
+    }
+    let unbalanced: Vec<NodeId> = graph.node_ids().filter(|&v| balance[v] != 0).collect();
+    let allowed = if closed {
+        unbalanced.is_empty()
+    } else if Ty::DIRECTED {
+        unbalanced.is_empty()
+            || (unbalanced.len() == 2 && unbalanced.iter().all(|&v| balance[v].abs() == 1))
+    } else {
+        unbalanced.len() <= 2
+    };
+    if !allowed {
+        return Err(EulerError::Unbalanced(unbalanced));
+    }
+    let start = match unbalanced
+        .iter()
+        .find(|&&v| !Ty::DIRECTED || balance[v] == 1)
+    {
+        Some(&v) => v,
+        None => match graph.edges().next() {
+            Some(edge) => edge.source,
+            None => {
+                return Ok(Trail {
+                    nodes: Vec::new(),
+                    edges: Vec::new(),
+                })
+            }
+        },
+    };
+    let mut used = vec![false; graph.edge_count()];
+    let mut next = vec![0; graph.node_count()];
+    let mut stack: Vec<(NodeId, Option<EdgeId>)> = vec![(start, None)];
+    let mut nodes = Vec::with_capacity(graph.edge_count() + 1);
+    let mut edges = Vec::with_capacity(graph.edge_count());
+    while let Some(&(node, via)) = stack.last() {
+        let adjacency = &graph.outgoing[node];
+        while next[node] < adjacency.len() && used[adjacency[next[node]].1] {
+            next[node] += 1;
+        }
+        match adjacency.get(next[node]) {
+            Some(&(to, id)) => {
+                used[id] = true;
+                stack.push((to, Some(id)));
+            }
+            None => {
+                stack.pop();
+                nodes.push(node);
+                edges.extend(via);
+            }
+        }
+    }
+    if edges.len() < graph.edge_count() {
+        return Err(EulerError::Disconnected);
+    }
+    nodes.reverse();
+    edges.reverse();
+    Ok(Trail { nodes, edges })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{DiGraph, UnGraph};
+    use crate::rng::Rng;
+
+    // check that `trail` walks every edge of `graph` once.
+    fn assert_walks<Ty: EdgeType>(graph: &Graph<(), (), Ty>, trail: &Trail) {
+        let mut edges = trail.edges.clone();
+        edges.sort_unstable();
+        assert_eq!(edges, (0..graph.edge_count()).collect::<Vec<_>>());
+        for (i, &id) in trail.edges.iter().enumerate() {
+            let edge = graph.edge(id).unwrap();
+            let (a, b) = (trail.nodes[i], trail.nodes[i + 1]);
+            let forward = (edge.source, edge.target) == (a, b);
+            assert!(forward || (!Ty::DIRECTED && (edge.target, edge.source) == (a, b)));
+        }
+    }
+
+    #[test]
+    fn test_small() {
+        let graph: UnGraph<(), ()> = Graph::from_edges(
+            5,
+            vec![(0, 1), (1, 2), (2, 0), (0, 3), (3, 4), (4, 0), (2, 2)],
+        );
+        let circuit = euler_circuit(&graph).unwrap();
+        assert_walks(&graph, &circuit);
+        assert_eq!(circuit.nodes[0], *circuit.nodes.last().unwrap());
+        let open: DiGraph<(), ()> = Graph::from_edges(4, vec![(0, 1), (1, 2), (2, 0), (0, 3)]);
+        let path = euler_path(&open).unwrap();
+        assert_eq!(path.nodes, vec![0, 1, 2, 0, 3]);
+        assert_eq!(
+            euler_circuit(&open),
+            Err(EulerError::Unbalanced(vec![0, 3]))
+        );
+        let split: UnGraph<(), ()> = Graph::from_edges(4, vec![(0, 1), (2, 3)]);
+        assert_eq!(
+            euler_path(&split),
+            Err(EulerError::Unbalanced(vec![0, 1, 2, 3]))
+        );
+        let loops: UnGraph<(), ()> = Graph::from_edges(4, vec![(0, 1), (1, 0), (2, 3), (3, 2)]);
+        assert_eq!(euler_path(&loops), Err(EulerError::Disconnected));
+        let star: DiGraph<(), ()> = Graph::from_edges(3, vec![(0, 1), (0, 2)]);
+        assert!(euler_path(&star).is_err());
+        assert_eq!(
+            euler_path(&UnGraph::<(), ()>::from_edges(
+                3,
+                Vec::<(usize, usize)>::new()
+            ))
+            .unwrap()
+            .nodes,
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_random_walks() {
+        // graphs made of one random walk always have an eulerian path.
+        let mut rng = Rng::new(69);
+        for _ in 0..100 {
+            let n = 1 + rng.index(8);
+            let mut walk = vec![rng.index(n)];
+            for _ in 0..rng.index(30) {
+                walk.push(rng.index(n));
+            }
+            let mut edges: Vec<(usize, usize)> = walk.windows(2).map(|w| (w[0], w[1])).collect();
+            rng.shuffle(&mut edges);
+            let directed: DiGraph<(), ()> = Graph::from_edges(n, edges.clone());
+            let undirected: UnGraph<(), ()> = Graph::from_edges(n, edges);
+            assert_walks(&directed, &euler_path(&directed).unwrap());
+            assert_walks(&undirected, &euler_path(&undirected).unwrap());
+            let closed = walk[0] == *walk.last().unwrap();
+            assert_eq!(
+                euler_circuit(&directed).is_ok(),
+                closed || directed.edge_count() == 0
+            );
+        }
+    }
+}
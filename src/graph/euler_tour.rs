@@ -0,0 +1,166 @@
+use std::ops::Range;
+
+use super::tree::RootedTree;
+use super::NodeId;
+
+/// a rooted forest flattened by dfs: each node gets an entry time, and its
+/// subtree is exactly the nodes entered before it is left. subtree
+/// aggregates become range queries on a fenwick or segment tree laid out
+/// in tour order.
+///
+/// for sums along the path up to the root, add `x` at `enter(v)` and `-x`
+/// at `exit(v)` of a tree of `len() + 1` elements: the prefix sum up to and
+/// including `enter(u)` then holds the values of `u`'s ancestors.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EulerTour {
+    enter: Vec<usize>,
+    exit: Vec<usize>,
+    // the node entered at each time.
+    order: Vec<NodeId>,
+}
+
+impl EulerTour {
+    /// the tour of `tree` in O(n), visiting roots and children in order.
+    pub fn new(tree: &RootedTree) -> Self {
+        let n = tree.len();
+        let mut enter = vec![0; n];
+        let mut exit = vec![0; n];
+        let mut order = Vec::with_capacity(n);
+        // nodes to enter, and to leave once their subtree is done.
+        let mut stack: Vec<(NodeId, bool)> = tree.roots().map(|v| (v, false)).collect();
+        stack.reverse();
+        while let Some((node, done)) = stack.pop() {
+            if done {
+                exit[node] = order.len();
+                continue;
+            }
+            enter[node] = order.len();
+            order.push(node);
+            stack.push((node, true));
+            stack.extend(tree.children(node).iter().rev().map(|&c| (c, false)));
+        }
+        EulerTour { enter, exit, order }
+    }
+
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    /// the time `node` is entered, its index in tour order.
+    pub fn enter(&self, node: NodeId) -> usize {
+        self.enter[node]
+    }
+
+    /// the time after the last node in the subtree of `node` is entered.
+    pub fn exit(&self, node: NodeId) -> usize {
+        self.exit[node]
+    }
+
+    /// the times of the subtree of `node`, itself first.
+    pub fn subtree(&self, node: NodeId) -> Range<usize> {
+        self.enter[node]..self.exit[node]
+    }
+
+    /// whether `ancestor` is `node` or above it.
+    pub fn is_ancestor(&self, ancestor: NodeId, node: NodeId) -> bool {
+        self.subtree(ancestor).contains(&self.enter[node])
+    }
+
+    /// the nodes in the order they are entered.
+    pub fn order(&self) -> &[NodeId] {
+        &self.order
+    }
+
+    /// `values` indexed by node rearranged into tour order, ready to build a
+    /// range structure from.
+    pub fn flatten<T: Clone>(&self, values: &[T]) -> Vec<T> {
+        assert_eq!(values.len(), self.len(), "one value per node");
+        self.order.iter().map(|&v| values[v].clone()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fenwick::Fenwick;
+    use crate::rng::Rng;
+    use crate::segment_tree::{Max, SegmentTree};
+
+    #[test]
+    fn test_small() {
+        let tree = RootedTree::from_parents(vec![None, Some(0), Some(0), Some(1), None, Some(1)]);
+        let tour = EulerTour::new(&tree);
+        assert_eq!(tour.order(), &[0, 1, 3, 5, 2, 4]);
+        assert_eq!(tour.subtree(1), 1..4);
+        assert_eq!(tour.subtree(4), 5..6);
+        assert!(tour.is_ancestor(0, 5) && !tour.is_ancestor(2, 5));
+        assert_eq!(
+            tour.flatten(&['a', 'b', 'c', 'd', 'e', 'f']),
+            vec!['a', 'b', 'd', 'f', 'c', 'e']
+        );
+    }
+
+    #[test]
+    fn test_aggregates() {
+        let mut rng = Rng::new(77);
+        let n = 200;
+        let parents: Vec<Option<NodeId>> = (0..n)
+            .map(|v| {
+                if v == 0 || rng.chance(0.05) {
+                    None
+                } else {
+                    Some(rng.index(v))
+                }
+            })
+            .collect();
+        let tree = RootedTree::from_parents(parents);
+        let tour = EulerTour::new(&tree);
+        let mut values: Vec<i64> = (0..n).map(|_| rng.range(0..100)).collect();
+        let mut sums = Fenwick::from(tour.flatten(&values));
+        let mut maxima: SegmentTree<i64, Max> = SegmentTree::from(tour.flatten(&values));
+        // ancestor sums, with each value added at entry and taken off at exit.
+        let mut ancestors: Fenwick<i64> = Fenwick::new(n + 1);
+        for (v, &value) in values.iter().enumerate() {
+            ancestors.add(tour.enter(v), value);
+            ancestors.add(tour.exit(v), -value);
+        }
+        let in_subtree = |root: NodeId, mut v: NodeId| loop {
+            if v == root {
+                return true;
+            }
+            match tree.parent(v) {
+                Some(p) => v = p,
+                None => return false,
+            }
+        };
+        for _ in 0..300 {
+            let v = rng.index(n);
+            if rng.chance(0.3) {
+                let value = rng.range(0..100);
+                sums.set(tour.enter(v), value);
+                maxima.set(tour.enter(v), value);
+                ancestors.add(tour.enter(v), value - values[v]);
+                ancestors.add(tour.exit(v), values[v] - value);
+                values[v] = value;
+                continue;
+            }
+            let below: Vec<i64> = (0..n)
+                .filter(|&u| in_subtree(v, u))
+                .map(|u| values[u])
+                .collect();
+            assert_eq!(sums.sum(tour.subtree(v)), below.iter().sum::<i64>());
+            assert_eq!(maxima.query(tour.subtree(v)), *below.iter().max().unwrap());
+            let above: i64 = (0..n)
+                .filter(|&u| in_subtree(u, v))
+                .map(|u| values[u])
+                .sum();
+            assert_eq!(ancestors.prefix_sum(tour.enter(v) + 1), above);
+            let other = rng.index(n);
+            assert_eq!(tour.is_ancestor(v, other), in_subtree(v, other));
+        }
+    }
+}
@@ -0,0 +1,289 @@
+use std::collections::VecDeque;
+
+use super::NodeId;
+
+#[derive(Clone, Debug)]
+struct FlowArc {
+    to: NodeId,
+    capacity: i64,
+    flow: i64,
+}
+
+/// a network of capacitated directed edges for maximum flow.
+///
+/// every edge is stored next to its reverse, which starts with capacity 0:
+/// edge `e` is arc `2e` and its reverse `2e + 1`, so one arc's partner is
+/// `arc ^ 1`.
+#[derive(Clone, Debug)]
+pub struct FlowNetwork {
+    arcs: Vec<FlowArc>,
+    adjacency: Vec<Vec<usize>>,
+    // per node, the bfs level and the next arc to try, for dinic's phases.
+    level: Vec<usize>,
+    next: Vec<usize>,
+}
+
+const UNREACHED: usize = usize::MAX;
+
+impl FlowNetwork {
+    /// a network of `n` nodes and no edges.
+    pub fn new(n: usize) -> Self {
+        FlowNetwork {
+            arcs: Vec::new(),
+            adjacency: vec![Vec::new(); n],
+            level: vec![UNREACHED; n],
+            next: vec![0; n],
+        }
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.adjacency.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.arcs.len() / 2
+    }
+
+    pub fn add_node(&mut self) -> NodeId {
+        self.adjacency.push(Vec::new());
+        self.level.push(UNREACHED);
+        self.next.push(0);
+        self.adjacency.len() - 1
+    }
+
+    /// add an edge of `capacity` from `from` to `to`, returning its id.
+    pub fn add_edge(&mut self, from: NodeId, to: NodeId, capacity: i64) -> usize {
+        let n = self.adjacency.len();
+        assert!(from < n && to < n, "node out of bounds");
+        assert!(capacity >= 0, "negative capacity");
+        let id = self.arcs.len() / 2;
+        self.adjacency[from].push(self.arcs.len());
+        self.arcs.push(FlowArc {
+            to,
+            capacity,
+            flow: 0,
+        });
+        self.adjacency[to].push(self.arcs.len());
+        self.arcs.push(FlowArc {
+            to: from,
+            capacity: 0,
+            flow: 0,
+        });
+        id
+    }
+
+    pub fn capacity(&self, edge: usize) -> i64 {
+        self.arcs[2 * edge].capacity
+    }
+
+    /// the flow on `edge` after the last [`FlowNetwork::max_flow`].
+    pub fn flow(&self, edge: usize) -> i64 {
+        self.arcs[2 * edge].flow
+    }
+
+    /// the endpoints of `edge`.
+    pub fn endpoints(&self, edge: usize) -> (NodeId, NodeId) {
+        (self.arcs[2 * edge + 1].to, self.arcs[2 * edge].to)
+    }
+
+    /// set every flow back to zero.
+    pub fn reset(&mut self) {
+        self.arcs.iter_mut().for_each(|a| a.flow = 0);
+    }
+
+    fn residual(&self, arc: usize) -> i64 {
+        self.arcs[arc].capacity - self.arcs[arc].flow
+    }
+
+    /// dinic's algorithm from scratch, in O(V^2 * E) (O(E * sqrt V) for unit
+    /// capacities). returns the flow value; the flows of each edge and the
+    /// minimum cut can be read afterwards.
+    pub fn max_flow(&mut self, source: NodeId, sink: NodeId) -> i64 {
+        self.reset();
+        self.augment(source, sink, 1)
+    }
+
+    /// dinic's algorithm with capacity scaling: phases only use arcs with at
+    /// least `delta` capacity left, halving `delta` from the largest power of
+    /// two below the top capacity. O(V * E * log C), and often faster when
+    /// capacities differ widely.
+    pub fn max_flow_scaling(&mut self, source: NodeId, sink: NodeId) -> i64 {
+        self.reset();
+        let top = self.arcs.iter().map(|a| a.capacity).max().unwrap_or(0);
+        if top == 0 {
+            return 0;
+        }
+        let mut delta = 1i64 << (63 - top.leading_zeros());
+        let mut total = 0;
+        while delta > 0 {
+            total += self.augment(source, sink, delta);
+            delta /= 2;
+        }
+        total
+    }
+
+    // dinic's phases over the arcs with at least `delta` residual capacity.
+    fn augment(&mut self, source: NodeId, sink: NodeId, delta: i64) -> i64 {
+        assert!(source != sink, "source and sink must differ");
+        let mut total = 0;
+        while self.levels(source, sink, delta) {
+            self.next.iter_mut().for_each(|i| *i = 0);
+            total += self.blocking_flow(source, sink, delta);
+        }
+        total
+    }
+
+    // bfs levels from the source; returns whether the sink was reached.
+    fn levels(&mut self, source: NodeId, sink: NodeId, delta: i64) -> bool {
+        self.level.iter_mut().for_each(|l| *l = UNREACHED);
+        self.level[source] = 0;
+        let mut queue = VecDeque::new();
+        queue.push_back(source);
+        while let Some(node) = queue.pop_front() {
+            for &arc in &self.adjacency[node] {
+                let to = self.arcs[arc].to;
+                if self.level[to] == UNREACHED && self.residual(arc) >= delta {
+                    self.level[to] = self.level[node] + 1;
+                    queue.push_back(to);
+                }
+            }
+        }
+        self.level[sink] != UNREACHED
+    }
+
+    // push flow along level-increasing paths until none is left, walking
+    // the paths with an explicit stack.
+    fn blocking_flow(&mut self, source: NodeId, sink: NodeId, delta: i64) -> i64 {
+        let mut total = 0;
+        let mut path: Vec<usize> = Vec::new();
+        let mut node = source;
+        loop {
+            if node == sink {
+                let pushed = path.iter().map(|&a| self.residual(a)).min().unwrap();
+                for &arc in &path {
+                    self.arcs[arc].flow += pushed;
+                    self.arcs[arc ^ 1].flow -= pushed;
+                }
+                total += pushed;
+                // back up to the tail of the first arc that ran out.
+                let saturated = path.iter().position(|&a| self.residual(a) < delta).unwrap();
+                path.truncate(saturated);
+                node = path.last().map_or(source, |&a| self.arcs[a].to);
+                continue;
+            }
+            let mut advanced = false;
+            while let Some(&arc) = self.adjacency[node].get(self.next[node]) {
+                let to = self.arcs[arc].to;
+                if self.residual(arc) >= delta && self.level[to] == self.level[node] + 1 {
+                    path.push(arc);
+                    node = to;
+                    advanced = true;
+                    break;
+                }
+                self.next[node] += 1;
+            }
+            if advanced {
+                continue;
+            }
+            // a dead end: no path goes through `node` in this phase.
+            self.level[node] = UNREACHED;
+            match path.pop() {
+                Some(arc) => {
+                    node = self.arcs[arc ^ 1].to;
+                    self.next[node] += 1;
+                }
+                None => return total,
+            }
+        }
+    }
+
+    /// the source side of a minimum cut after [`FlowNetwork::max_flow`]: the
+    /// nodes still reachable from `source` in the residual network. the
+    /// edges leaving it are saturated and their capacities sum to the flow.
+    pub fn min_cut(&self, source: NodeId) -> Vec<bool> {
+        let mut reached = vec![false; self.adjacency.len()];
+        reached[source] = true;
+        let mut stack = vec![source];
+        while let Some(node) = stack.pop() {
+            for &arc in &self.adjacency[node] {
+                let to = self.arcs[arc].to;
+                if !reached[to] && self.residual(arc) > 0 {
+                    reached[to] = true;
+                    stack.push(to);
+                }
+            }
+        }
+        reached
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    fn cut_of(network: &FlowNetwork, side: impl Fn(NodeId) -> bool) -> i64 {
+        (0..network.edge_count())
+            .filter(|&e| {
+                let (a, b) = network.endpoints(e);
+                side(a) && !side(b)
+            })
+            .map(|e| network.capacity(e))
+            .sum()
+    }
+
+    #[test]
+    fn test_small() {
+        let mut network = FlowNetwork::new(4);
+        let edges = [
+            network.add_edge(0, 1, 3),
+            network.add_edge(0, 2, 2),
+            network.add_edge(1, 2, 5),
+            network.add_edge(1, 3, 2),
+            network.add_edge(2, 3, 3),
+        ];
+        assert_eq!(network.max_flow(0, 3), 5);
+        let flows: Vec<i64> = edges.iter().map(|&e| network.flow(e)).collect();
+        assert_eq!(flows.iter().sum::<i64>(), 3 + 2 + 1 + 2 + 3);
+        assert_eq!(network.min_cut(0), vec![true, false, false, false]);
+        assert_eq!(network.max_flow_scaling(0, 3), 5);
+        assert_eq!(network.endpoints(edges[2]), (1, 2));
+        let sink = network.add_node();
+        assert_eq!(network.max_flow(0, sink), 0);
+    }
+
+    #[test]
+    fn test_against_cut_enumeration() {
+        let mut rng = Rng::new(65);
+        for _ in 0..100 {
+            let n = 2 + rng.index(7);
+            let mut network = FlowNetwork::new(n);
+            for _ in 0..rng.index(4 * n) {
+                network.add_edge(rng.index(n), rng.index(n), rng.below(20) as i64);
+            }
+            let (source, sink) = (0, n - 1);
+            // the minimum cut over every set containing the source only.
+            let best = (0..1u32 << n)
+                .filter(|mask| mask & 1 == 1 && mask >> sink & 1 == 0)
+                .map(|mask| cut_of(&network, |v| mask >> v & 1 == 1))
+                .min()
+                .unwrap();
+            assert_eq!(network.max_flow_scaling(source, sink), best);
+            assert_eq!(network.max_flow(source, sink), best);
+            let side = network.min_cut(source);
+            assert!(side[source] && !side[sink]);
+            assert_eq!(cut_of(&network, |v| side[v]), best);
+            for v in 1..n - 1 {
+                let net: i64 = (0..network.edge_count())
+                    .map(|e| {
+                        let (a, b) = network.endpoints(e);
+                        let f = network.flow(e);
+                        assert!(0 <= f && f <= network.capacity(e));
+                        (if b == v { f } else { 0 }) - (if a == v { f } else { 0 })
+                    })
+                    .sum();
+                assert_eq!(net, 0);
+            }
+        }
+    }
+}
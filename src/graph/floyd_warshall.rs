@@ -0,0 +1,163 @@
+use super::{AdjacencyMatrix, NodeId, Weight};
+
+/// shortest distances between all pairs of nodes, with the first step of
+/// each path.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AllPairs<W> {
+    n: usize,
+    distance: Vec<Option<W>>,
+    // the node after `from` on a shortest path from `from` to `to`.
+    next: Vec<NodeId>,
+}
+
+impl<W: Weight> AllPairs<W> {
+    /// number of nodes.
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    pub fn distance(&self, from: NodeId, to: NodeId) -> Option<W> {
+        self.distance[from * self.n + to]
+    }
+
+    /// the nodes of a shortest path from `from` to `to`.
+    pub fn path(&self, from: NodeId, to: NodeId) -> Option<Vec<NodeId>> {
+        self.distance(from, to)?;
+        let mut path = vec![from];
+        let mut current = from;
+        while current != to {
+            current = self.next[current * self.n + to];
+            path.push(current);
+        }
+        Some(path)
+    }
+}
+
+/// floyd-warshall all-pairs shortest paths in O(V^3), allowing negative
+/// weights.
+///
+/// if a cycle of negative weight exists, returns the nodes found to lie on
+/// one, in increasing order: at least one, but not necessarily all of them.
+/// it stops at the first phase where one shows up, before any distance
+/// could overflow.
+pub fn floyd_warshall<W: Weight>(matrix: &AdjacencyMatrix<W>) -> Result<AllPairs<W>, Vec<NodeId>> {
+    let n = matrix.len();
+    let mut distance = vec![None; n * n];
+    let mut next = vec![0; n * n];
+    for from in 0..n {
+        for to in 0..n {
+            distance[from * n + to] = matrix.get(from, to);
+            next[from * n + to] = to;
+        }
+        let diagonal = &mut distance[from * n + from];
+        if diagonal.is_none_or(|w| w > W::zero()) {
+            *diagonal = Some(W::zero());
+        }
+    }
+    for via in 0..n {
+        for from in 0..n {
+            let first = match distance[from * n + via] {
+                Some(d) => d,
+                None => continue,
+            };
+            for to in 0..n {
+                if let Some(second) = distance[via * n + to] {
+                    let through = first + second;
+                    let cell = &mut distance[from * n + to];
+                    if cell.is_none_or(|d| through < d) {
+                        *cell = Some(through);
+                        next[from * n + to] = next[from * n + via];
+                    }
+                }
+            }
+        }
+        let negative: Vec<NodeId> = (0..n)
+            .filter(|&v| distance[v * n + v].is_some_and(|d| d < W::zero()))
+            .collect();
+        if !negative.is_empty() {
+            return Err(negative);
+        }
+    }
+    Ok(AllPairs { n, distance, next })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::bellman_ford::bellman_ford;
+    use crate::graph::{DiGraph, Graph, UnGraph};
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_small() {
+        let graph: DiGraph<(), i32> = Graph::from_edges(
+            4,
+            vec![
+                (0, 2, -2),
+                (2, 3, 2),
+                (3, 1, -1),
+                (1, 0, 4),
+                (1, 2, 3),
+                (1, 2, 9),
+            ],
+        );
+        let matrix = AdjacencyMatrix::from_graph(&graph);
+        assert_eq!(matrix.get(1, 2), Some(3));
+        let all = floyd_warshall(&matrix).unwrap();
+        assert_eq!(all.distance(1, 3), Some(4));
+        assert_eq!(all.path(1, 3), Some(vec![1, 0, 2, 3]));
+        assert_eq!(all.distance(0, 1), Some(-1));
+        assert_eq!(all.path(2, 2), Some(vec![2]));
+        let mut disconnected = AdjacencyMatrix::new(3);
+        disconnected.set(0, 1, Some(2.5));
+        let all = floyd_warshall(&disconnected).unwrap();
+        assert_eq!((all.distance(0, 1), all.distance(1, 0)), (Some(2.5), None));
+        assert_eq!(all.path(2, 0), None);
+        let negative: UnGraph<(), i32> = Graph::from_edges(3, vec![(0, 1, 3), (1, 2, -1)]);
+        assert_eq!(
+            floyd_warshall(&AdjacencyMatrix::from_graph(&negative)),
+            Err(vec![2])
+        );
+    }
+
+    #[test]
+    fn test_against_bellman_ford() {
+        let mut rng = Rng::new(59);
+        for _ in 0..60 {
+            let n = 1 + rng.index(12);
+            let edges: Vec<(usize, usize, i64)> = (0..rng.index(4 * n))
+                .map(|_| (rng.index(n), rng.index(n), rng.range(-2..15)))
+                .collect();
+            let graph: DiGraph<(), i64> = Graph::from_edges(n, edges);
+            let matrix = AdjacencyMatrix::from_graph(&graph);
+            let result = floyd_warshall(&matrix);
+            let single: Vec<_> = graph.node_ids().map(|s| bellman_ford(&graph, s)).collect();
+            match result {
+                Ok(all) => {
+                    for (from, paths) in single.iter().enumerate() {
+                        let paths = paths.as_ref().unwrap();
+                        for to in graph.node_ids() {
+                            assert_eq!(all.distance(from, to), paths.distance(to));
+                            if let Some(path) = all.path(from, to) {
+                                let total: i64 = path
+                                    .windows(2)
+                                    .map(|w| matrix.get(w[0], w[1]).unwrap())
+                                    .sum();
+                                assert_eq!(Some(total), all.distance(from, to));
+                            }
+                        }
+                    }
+                }
+                Err(nodes) => {
+                    for v in nodes {
+                        assert!(single[v].is_err());
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,229 @@
+use super::{DiGraph, EdgeType, Graph, NodeId, UnGraph};
+use crate::rng::Rng;
+
+/// the erdős–rényi graph g(n, p): every pair of distinct nodes (ordered,
+/// if directed) gets an edge with chance `p`. O(n + m) for m edges, by
+/// skipping the pairs left out geometrically.
+pub fn erdos_renyi<Ty: EdgeType>(n: usize, p: f64, rng: &mut Rng) -> Graph<(), (), Ty> {
+    let mut graph = Graph::from_edges(n, Vec::<(NodeId, NodeId)>::new());
+    if n < 2 {
+        return graph;
+    }
+    let total = if Ty::DIRECTED {
+        n * (n - 1)
+    } else {
+        n * (n - 1) / 2
+    };
+    for k in sample_indices(total, p, rng) {
+        let (a, b) = if Ty::DIRECTED {
+            let (a, b) = (k / (n - 1), k % (n - 1));
+            (a, if b >= a { b + 1 } else { b })
+        } else {
+            lower_pair(k)
+        };
+        graph.add_edge(a, b, ());
+    }
+    graph
+}
+
+/// a random directed acyclic graph: the nodes are put in a random order
+/// and each pair gets an edge, from earlier to later, with chance `p`.
+pub fn random_dag(n: usize, p: f64, rng: &mut Rng) -> DiGraph<(), ()> {
+    let mut order: Vec<NodeId> = (0..n).collect();
+    rng.shuffle(&mut order);
+    let mut graph = Graph::from_edges(n, Vec::<(NodeId, NodeId)>::new());
+    for k in sample_indices(n * n.saturating_sub(1) / 2, p, rng) {
+        let (later, earlier) = lower_pair(k);
+        graph.add_edge(order[earlier], order[later], ());
+    }
+    graph
+}
+
+/// a uniformly random labelled tree on `n` nodes, decoded from a random
+/// prüfer sequence in O(n).
+pub fn random_tree(n: usize, rng: &mut Rng) -> UnGraph<(), ()> {
+    if n < 2 {
+        return Graph::from_edges(n, Vec::<(NodeId, NodeId)>::new());
+    }
+    let code: Vec<NodeId> = (0..n - 2).map(|_| rng.index(n)).collect();
+    let mut degree = vec![1; n];
+    code.iter().for_each(|&v| degree[v] += 1);
+    let mut edges = Vec::with_capacity(n - 1);
+    // the smallest leaf, found by a pointer that only moves forward unless
+    // a removal makes a smaller node a leaf, which is then used at once.
+    let mut pointer = degree.iter().position(|&d| d == 1).unwrap();
+    let mut leaf = pointer;
+    for &v in &code {
+        edges.push((leaf, v));
+        degree[v] -= 1;
+        if degree[v] == 1 && v < pointer {
+            leaf = v;
+        } else {
+            pointer += 1;
+            while degree[pointer] != 1 {
+                pointer += 1;
+            }
+            leaf = pointer;
+        }
+    }
+    edges.push((leaf, n - 1));
+    Graph::from_edges(n, edges)
+}
+
+/// a barabási–albert preferential attachment graph: after `m` isolated
+/// starting nodes, each new node joins `m` distinct earlier ones, chosen
+/// with chance proportional to their degree. m * (n - m) edges.
+pub fn barabasi_albert(n: usize, m: usize, rng: &mut Rng) -> UnGraph<(), ()> {
+    assert!(m >= 1, "each node must attach to at least one other");
+    let mut graph = Graph::from_edges(n, Vec::<(NodeId, NodeId)>::new());
+    // every node once per edge end, so a uniform pick is by degree.
+    let mut ends: Vec<NodeId> = Vec::new();
+    let mut targets: Vec<NodeId> = Vec::with_capacity(m);
+    for node in m..n {
+        targets.clear();
+        if node == m {
+            targets.extend(0..m);
+        }
+        while targets.len() < m {
+            let pick = ends[rng.index(ends.len())];
+            if !targets.contains(&pick) {
+                targets.push(pick);
+            }
+        }
+        for &target in &targets {
+            graph.add_edge(node, target, ());
+            ends.push(node);
+            ends.push(target);
+        }
+    }
+    graph
+}
+
+// the pair `(a, b)` with `b < a` at position `k` in the order
+// (1, 0), (2, 0), (2, 1), (3, 0), ...
+fn lower_pair(k: usize) -> (NodeId, NodeId) {
+    let mut a = ((1.0 + (1.0 + 8.0 * k as f64).sqrt()) / 2.0) as usize;
+    while a * (a - 1) / 2 > k {
+        a -= 1;
+    }
+    while (a + 1) * a / 2 <= k {
+        a += 1;
+    }
+    (a, k - a * (a - 1) / 2)
+}
+
+// each index in `0..total` with chance `p`, in increasing order, jumping
+// over the gaps between them by their geometric distribution.
+fn sample_indices(total: usize, p: f64, rng: &mut Rng) -> Vec<usize> {
+    assert!((0.0..=1.0).contains(&p), "probability outside [0, 1]");
+    if p == 0.0 {
+        return Vec::new();
+    }
+    if p == 1.0 {
+        return (0..total).collect();
+    }
+    let scale = (1.0 - p).ln();
+    let mut picked = Vec::new();
+    let mut next = 0usize;
+    loop {
+        let gap = ((1.0 - rng.next_f64()).ln() / scale) as usize;
+        next = match next.checked_add(gap) {
+            Some(k) if k < total => k,
+            _ => return picked,
+        };
+        picked.push(next);
+        next += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::topo::toposort;
+    use crate::graph::tree::RootedTree;
+    use std::collections::HashMap;
+
+    fn simple<Ty: EdgeType>(graph: &Graph<(), (), Ty>) -> bool {
+        let mut pairs: Vec<(NodeId, NodeId)> = graph
+            .edges()
+            .map(|e| {
+                if Ty::DIRECTED {
+                    (e.source, e.target)
+                } else {
+                    (e.source.max(e.target), e.source.min(e.target))
+                }
+            })
+            .collect();
+        let count = pairs.len();
+        pairs.sort_unstable();
+        pairs.dedup();
+        pairs.len() == count && pairs.iter().all(|&(a, b)| a != b)
+    }
+
+    #[test]
+    fn test_erdos_renyi_and_dag() {
+        let mut rng = Rng::new(82);
+        assert_eq!(
+            (0..10).map(lower_pair).collect::<Vec<_>>()[..4],
+            [(1, 0), (2, 0), (2, 1), (3, 0)]
+        );
+        for k in 0..10000 {
+            let (a, b) = lower_pair(k);
+            assert!(b < a && a * (a - 1) / 2 + b == k);
+        }
+        let n = 400;
+        let undirected: UnGraph<(), ()> = erdos_renyi(n, 0.05, &mut rng);
+        let expected = 0.05 * (n * (n - 1) / 2) as f64;
+        assert!((undirected.edge_count() as f64 - expected).abs() < 0.1 * expected);
+        assert!(simple(&undirected));
+        let directed: DiGraph<(), ()> = erdos_renyi(n, 0.05, &mut rng);
+        assert!((directed.edge_count() as f64 - 2.0 * expected).abs() < 0.2 * expected);
+        assert!(simple(&directed));
+        let complete: DiGraph<(), ()> = erdos_renyi(5, 1.0, &mut rng);
+        assert_eq!(complete.edge_count(), 20);
+        assert!(simple(&complete));
+        let dag = random_dag(n, 0.05, &mut rng);
+        assert!(toposort(&dag).is_ok() && simple(&dag));
+        assert_eq!(random_dag(6, 1.0, &mut rng).edge_count(), 15);
+    }
+
+    #[test]
+    fn test_random_tree() {
+        let mut rng = Rng::new(83);
+        for n in 0..50 {
+            let tree = random_tree(n, &mut rng);
+            assert_eq!(tree.edge_count(), n.saturating_sub(1));
+            if n > 0 {
+                // panics unless connected and acyclic.
+                RootedTree::new(&tree, 0);
+            }
+        }
+        // all 16 labelled trees on 4 nodes are about equally likely.
+        let mut counts: HashMap<Vec<(NodeId, NodeId)>, usize> = HashMap::new();
+        for _ in 0..16000 {
+            let tree = random_tree(4, &mut rng);
+            let mut edges: Vec<_> = tree
+                .edges()
+                .map(|e| (e.source.min(e.target), e.source.max(e.target)))
+                .collect();
+            edges.sort_unstable();
+            *counts.entry(edges).or_insert(0) += 1;
+        }
+        assert_eq!(counts.len(), 16);
+        assert!(counts.values().all(|&c| (800..1200).contains(&c)));
+    }
+
+    #[test]
+    fn test_barabasi_albert() {
+        let mut rng = Rng::new(84);
+        let (n, m) = (2000, 3);
+        let graph = barabasi_albert(n, m, &mut rng);
+        assert_eq!(graph.edge_count(), m * (n - m));
+        assert!(simple(&graph));
+        assert!((m..n).all(|v| graph.out_degree(v) >= m));
+        // preferential attachment grows hubs far above the mean degree.
+        let max = graph.node_ids().map(|v| graph.out_degree(v)).max().unwrap();
+        assert!(max > 10 * 2 * m);
+        assert_eq!(barabasi_albert(2, 3, &mut rng).edge_count(), 0);
+    }
+}
@@ -0,0 +1,285 @@
+use std::ops::Range;
+
+use super::tree::RootedTree;
+use super::NodeId;
+use crate::segment_tree::{Action, LazySegmentTree, Monoid};
+
+/// heavy-light decomposition of a rooted forest: the nodes laid out in an
+/// array so that every path splits into O(log n) contiguous ranges and
+/// every subtree is one range.
+#[derive(Clone, Debug)]
+pub struct HeavyLight {
+    parent: Vec<Option<NodeId>>,
+    depth: Vec<usize>,
+    size: Vec<usize>,
+    // the top of each node's heavy chain.
+    head: Vec<NodeId>,
+    position: Vec<usize>,
+}
+
+impl HeavyLight {
+    /// decompose in O(n): each node's heavy child, the one with the largest
+    /// subtree, continues its chain and is laid out right after it.
+    pub fn new(tree: &RootedTree) -> Self {
+        let n = tree.len();
+        let mut size = vec![1; n];
+        for &node in tree.order().iter().rev() {
+            if let Some(parent) = tree.parent(node) {
+                size[parent] += size[node];
+            }
+        }
+        let mut head: Vec<NodeId> = (0..n).collect();
+        let mut position = vec![0; n];
+        let mut next = 0;
+        let mut stack: Vec<NodeId> = tree.roots().collect();
+        stack.reverse();
+        while let Some(node) = stack.pop() {
+            position[node] = next;
+            next += 1;
+            let children = tree.children(node);
+            let heavy = children.iter().copied().max_by_key(|&c| (size[c], n - c));
+            stack.extend(children.iter().copied().filter(|&c| Some(c) != heavy));
+            if let Some(heavy) = heavy {
+                head[heavy] = head[node];
+                stack.push(heavy);
+            }
+        }
+        HeavyLight {
+            parent: (0..n).map(|v| tree.parent(v)).collect(),
+            depth: (0..n).map(|v| tree.depth(v)).collect(),
+            size,
+            head,
+            position,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    /// where `node` is laid out.
+    pub fn position(&self, node: NodeId) -> usize {
+        self.position[node]
+    }
+
+    /// the positions of the subtree of `node`.
+    pub fn subtree(&self, node: NodeId) -> Range<usize> {
+        self.position[node]..self.position[node] + self.size[node]
+    }
+
+    /// the ranges of positions covering the path from `a` to `b`, both
+    /// included, or `None` if they are in different trees.
+    pub fn path(&self, mut a: NodeId, mut b: NodeId) -> Option<Vec<Range<usize>>> {
+        let mut ranges = Vec::new();
+        while self.head[a] != self.head[b] {
+            if self.depth[self.head[a]] < self.depth[self.head[b]] {
+                std::mem::swap(&mut a, &mut b);
+            }
+            ranges.push(self.position[self.head[a]]..self.position[a] + 1);
+            a = self.parent[self.head[a]]?;
+        }
+        let (low, high) = (self.position[a], self.position[b]);
+        ranges.push(low.min(high)..low.max(high) + 1);
+        Some(ranges)
+    }
+
+    /// the lowest common ancestor of `a` and `b`, in O(log n).
+    pub fn lca(&self, mut a: NodeId, mut b: NodeId) -> Option<NodeId> {
+        while self.head[a] != self.head[b] {
+            if self.depth[self.head[a]] < self.depth[self.head[b]] {
+                std::mem::swap(&mut a, &mut b);
+            }
+            a = self.parent[self.head[a]]?;
+        }
+        Some(if self.depth[a] <= self.depth[b] { a } else { b })
+    }
+}
+
+/// values on the nodes of a rooted forest, with folds and updates over
+/// paths and subtrees in O(log^2 n) by a [`LazySegmentTree`] over the
+/// [`HeavyLight`] layout. a path is folded in no particular order, so
+/// `Op` should be commutative, like sums, minima and maxima. to keep
+/// values on edges instead, store each on its lower endpoint and leave the
+/// lca out.
+pub struct PathTree<T, Op: Monoid<T>, Act: Action<T, Op>> {
+    layout: HeavyLight,
+    values: LazySegmentTree<T, Op, Act>,
+}
+
+impl<T, Op, Act> PathTree<T, Op, Act>
+where
+    T: Clone,
+    Op: Monoid<T>,
+    Act: Action<T, Op>,
+{
+    /// `values[node]` on each node of `tree`.
+    pub fn new(tree: &RootedTree, values: Vec<T>) -> Self {
+        assert_eq!(values.len(), tree.len(), "one value per node");
+        let layout = HeavyLight::new(tree);
+        let mut laid_out = vec![Op::identity(); values.len()];
+        for (node, value) in values.into_iter().enumerate() {
+            laid_out[layout.position[node]] = value;
+        }
+        PathTree {
+            layout,
+            values: LazySegmentTree::from(laid_out),
+        }
+    }
+
+    pub fn layout(&self) -> &HeavyLight {
+        &self.layout
+    }
+
+    pub fn get(&mut self, node: NodeId) -> T {
+        self.values.get(self.layout.position[node])
+    }
+
+    pub fn set(&mut self, node: NodeId, value: T) {
+        self.values.set(self.layout.position[node], value);
+    }
+
+    /// fold the values on the path from `a` to `b`, or `None` if there is
+    /// no such path.
+    pub fn query_path(&mut self, a: NodeId, b: NodeId) -> Option<T> {
+        let ranges = self.layout.path(a, b)?;
+        let values = &mut self.values;
+        Some(ranges.into_iter().fold(Op::identity(), |acc, range| {
+            Op::combine(&acc, &values.query(range))
+        }))
+    }
+
+    /// apply `f` to every value on the path from `a` to `b`; returns
+    /// whether there is such a path.
+    pub fn update_path(&mut self, a: NodeId, b: NodeId, f: Act::Map) -> bool {
+        match self.layout.path(a, b) {
+            Some(ranges) => {
+                for range in ranges {
+                    self.values.apply(range, f.clone());
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn query_subtree(&mut self, node: NodeId) -> T {
+        self.values.query(self.layout.subtree(node))
+    }
+
+    pub fn update_subtree(&mut self, node: NodeId, f: Act::Map) {
+        self.values.apply(self.layout.subtree(node), f);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::test_util;
+    use crate::rng::Rng;
+    use crate::segment_tree::{Max, Min, RangeAdd, RangeAssign, Sum};
+
+    fn random_forest(rng: &mut Rng, n: usize) -> RootedTree {
+        RootedTree::from_parents(test_util::random_forest(rng, n))
+    }
+
+    // the path from `a` to `b` by walking up from both.
+    fn naive_path(tree: &RootedTree, mut a: NodeId, mut b: NodeId) -> Option<Vec<NodeId>> {
+        let mut path = Vec::new();
+        while a != b {
+            if tree.depth(a) < tree.depth(b) {
+                std::mem::swap(&mut a, &mut b);
+            }
+            path.push(a);
+            a = tree.parent(a)?;
+        }
+        path.push(a);
+        Some(path)
+    }
+
+    #[test]
+    fn test_layout() {
+        let tree =
+            RootedTree::from_parents(vec![None, Some(0), Some(0), Some(1), Some(1), Some(3)]);
+        let layout = HeavyLight::new(&tree);
+        assert_eq!(
+            (0..6).map(|v| layout.position(v)).collect::<Vec<_>>(),
+            vec![0, 1, 5, 2, 4, 3]
+        );
+        assert_eq!(layout.subtree(1), 1..5);
+        assert_eq!(layout.path(5, 2), Some(vec![5..6, 0..4]));
+        assert_eq!(layout.lca(4, 5), Some(1));
+    }
+
+    #[test]
+    fn test_sums() {
+        let mut rng = Rng::new(75);
+        for _ in 0..20 {
+            let n = 1 + rng.index(100);
+            let tree = random_forest(&mut rng, n);
+            let mut values: Vec<i64> = (0..n).map(|_| rng.range(-50..50)).collect();
+            let mut path_tree: PathTree<i64, Sum, RangeAdd> = PathTree::new(&tree, values.clone());
+            for _ in 0..200 {
+                let (a, b) = (rng.index(n), rng.index(n));
+                let path = naive_path(&tree, a, b);
+                match rng.index(4) {
+                    0 => {
+                        let value = rng.range(-50..50);
+                        values[a] = value;
+                        path_tree.set(a, value);
+                    }
+                    1 => {
+                        let add = rng.range(-5..5);
+                        assert_eq!(path_tree.update_path(a, b, add), path.is_some());
+                        path.iter().flatten().for_each(|&v| values[v] += add);
+                    }
+                    2 => {
+                        let expected = path.map(|p| p.iter().map(|&v| values[v]).sum());
+                        assert_eq!(path_tree.query_path(a, b), expected);
+                    }
+                    _ => {
+                        let range = path_tree.layout().subtree(a);
+                        let expected: i64 = (0..n)
+                            .filter(|&v| range.contains(&path_tree.layout().position(v)))
+                            .map(|v| values[v])
+                            .sum();
+                        assert_eq!(path_tree.query_subtree(a), expected);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_extrema() {
+        let mut rng = Rng::new(76);
+        let n = 300;
+        let tree = random_forest(&mut rng, n);
+        let mut values: Vec<i32> = (0..n).map(|_| rng.range(0..1000) as i32).collect();
+        let mut minima: PathTree<i32, Min, RangeAssign> = PathTree::new(&tree, values.clone());
+        let mut maxima: PathTree<i32, Max, RangeAssign> = PathTree::new(&tree, values.clone());
+        for _ in 0..500 {
+            let (a, b) = (rng.index(n), rng.index(n));
+            let path = naive_path(&tree, a, b);
+            if rng.chance(0.3) {
+                let value = rng.range(0..1000) as i32;
+                minima.update_path(a, b, Some(value));
+                maxima.update_path(a, b, Some(value));
+                path.iter().flatten().for_each(|&v| values[v] = value);
+            } else {
+                let on_path = || path.iter().flatten().map(|&v| values[v]);
+                assert_eq!(
+                    minima.query_path(a, b),
+                    path.as_ref().map(|_| on_path().min().unwrap())
+                );
+                assert_eq!(
+                    maxima.query_path(a, b),
+                    path.as_ref().map(|_| on_path().max().unwrap())
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use super::bellman_ford::bellman_ford;
+use super::dijkstra::dijkstra_by;
+use super::{DiGraph, EdgeType, Graph, NodeId, ShortestPaths, Weight};
+
+/// johnson's all-pairs shortest paths for sparse graphs with negative
+/// weights, in O(V * E log V): bellman-ford finds potentials `h` making
+/// every weight `w(u, v) + h(u) - h(v)` non-negative, then dijkstra runs
+/// from every node.
+///
+/// returns the shortest paths from each node, indexed by source, or the
+/// nodes of a negative cycle if there is one.
+pub fn johnson<N, E: Weight, Ty: EdgeType>(
+    graph: &Graph<N, E, Ty>,
+) -> Result<Vec<ShortestPaths<E>>, Vec<NodeId>> {
+    let n = graph.node_count();
+    // an extra node with a zero edge to every other one reaches them all.
+    let mut extended: DiGraph<(), E> = Graph::new();
+    for _ in 0..=n {
+        extended.add_node(());
+    }
+    for node in graph.node_ids() {
+        for edge in graph.edges_from(node) {
+            extended.add_edge(node, edge.target, *edge.weight);
+        }
+        extended.add_edge(n, node, E::zero());
+    }
+    let potentials = bellman_ford(&extended, n)?;
+    let h: Vec<E> = (0..n).map(|v| potentials.distance(v).unwrap()).collect();
+    let all = graph
+        .node_ids()
+        .map(|source| {
+            let reweighted = dijkstra_by(graph, source, |edge| {
+                let w = *edge.weight + h[edge.source] - h[edge.target];
+                // zero on tight edges, but floats may round it just below.
+                if w < E::zero() {
+                    E::zero()
+                } else {
+                    w
+                }
+            });
+            ShortestPaths {
+                source,
+                distance: (0..n)
+                    .map(|v| reweighted.distance[v].map(|d| d - h[source] + h[v]))
+                    .collect(),
+                parent: reweighted.parent,
+            }
+        })
+        .collect();
+    Ok(all)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::floyd_warshall::floyd_warshall;
+    use crate::graph::AdjacencyMatrix;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_against_floyd_warshall() {
+        let mut rng = Rng::new(61);
+        let mut cycles = 0;
+        for _ in 0..80 {
+            let n = 1 + rng.index(12);
+            let edges: Vec<(usize, usize, i64)> = (0..rng.index(3 * n))
+                .map(|_| (rng.index(n), rng.index(n), rng.range(-4..20)))
+                .collect();
+            let graph: DiGraph<(), i64> = Graph::from_edges(n, edges);
+            match (
+                johnson(&graph),
+                floyd_warshall(&AdjacencyMatrix::from_graph(&graph)),
+            ) {
+                (Ok(all), Ok(expected)) => {
+                    for (source, paths) in all.iter().enumerate() {
+                        assert_eq!(paths.source(), source);
+                        for v in graph.node_ids() {
+                            assert_eq!(paths.distance(v), expected.distance(source, v));
+                            if let Some(path) = paths.path_to(v) {
+                                assert_eq!((path[0], *path.last().unwrap()), (source, v));
+                            }
+                        }
+                    }
+                }
+                (Err(_), Err(_)) => cycles += 1,
+                (a, b) => panic!("disagree: {:?} {:?}", a.is_ok(), b.is_ok()),
+            }
+        }
+        assert!(cycles > 0);
+    }
+
+    #[test]
+    fn test_negative_edges() {
+        let graph: DiGraph<(), i32> = Graph::from_edges(
+            4,
+            vec![(0, 1, -2), (1, 2, 3), (2, 0, 1), (0, 3, 5), (1, 3, -4)],
+        );
+        let all = johnson(&graph).unwrap();
+        assert_eq!(all[2].distance(3), Some(-5));
+        assert_eq!(all[2].path_to(3), Some(vec![2, 0, 1, 3]));
+        assert_eq!(all[3].distance(0), None);
+        let cyclic: DiGraph<(), i32> = Graph::from_edges(2, vec![(0, 1, -2), (1, 0, 1)]);
+        assert_eq!(johnson(&cyclic).unwrap_err().len(), 2);
+    }
+
+    #[test]
+    fn test_float_weights() {
+        // thirds and tenths, so the reweighted tight edges round to either
+        // side of zero. edges only go forward, so there is no negative cycle
+        // however negative they are.
+        let mut rng = Rng::new(118);
+        for _ in 0..200 {
+            let n = 2 + rng.index(20);
+            let edges: Vec<(usize, usize, f64)> = (0..3 * n)
+                .map(|_| {
+                    let a = rng.index(n - 1);
+                    let w = rng.range(-30..30) as f64 / 10.0 + rng.range(0..3) as f64 / 3.0;
+                    (a, a + 1 + rng.index(n - 1 - a), w)
+                })
+                .collect();
+            let graph: DiGraph<(), f64> = Graph::from_edges(n, edges);
+            let expected = floyd_warshall(&AdjacencyMatrix::from_graph(&graph)).unwrap();
+            let all = johnson(&graph).unwrap();
+            for (source, paths) in all.iter().enumerate() {
+                for v in graph.node_ids() {
+                    match (paths.distance(v), expected.distance(source, v)) {
+                        (Some(a), Some(b)) => assert!((a - b).abs() < 1e-9),
+                        (a, b) => assert_eq!(a, b),
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use super::tree::RootedTree;
+use super::NodeId;
+
+/// lowest common ancestors by binary lifting: O(n log n) to build, then
+/// O(log n) per query.
+#[derive(Clone, Debug)]
+pub struct Lca {
+    depth: Vec<usize>,
+    // `up[k][v]` is the 2^k-th ancestor of `v`, or its root if there is
+    // none that high.
+    up: Vec<Vec<NodeId>>,
+    root: Vec<NodeId>,
+}
+
+impl Lca {
+    pub fn new(tree: &RootedTree) -> Self {
+        let n = tree.len();
+        let mut first: Vec<NodeId> = (0..n).collect();
+        let mut root: Vec<NodeId> = (0..n).collect();
+        for &node in tree.order() {
+            if let Some(parent) = tree.parent(node) {
+                first[node] = parent;
+                root[node] = root[parent];
+            }
+        }
+        let height = (0..n).map(|v| tree.depth(v)).max().unwrap_or(0);
+        let mut up = vec![first];
+        while 1 << up.len() <= height {
+            let last = &up[up.len() - 1];
+            let next = (0..n).map(|v| last[last[v]]).collect();
+            up.push(next);
+        }
+        Lca {
+            depth: (0..n).map(|v| tree.depth(v)).collect(),
+            up,
+            root,
+        }
+    }
+
+    /// the ancestor `k` edges above `node`, if the tree is that deep.
+    pub fn kth_ancestor(&self, mut node: NodeId, k: usize) -> Option<NodeId> {
+        if k > self.depth[node] {
+            return None;
+        }
+        for (bit, level) in self.up.iter().enumerate() {
+            if k >> bit & 1 == 1 {
+                node = level[node];
+            }
+        }
+        Some(node)
+    }
+
+    /// the deepest node that is an ancestor of both `a` and `b` (a node is
+    /// its own ancestor), or `None` if they are in different trees.
+    pub fn lca(&self, a: NodeId, b: NodeId) -> Option<NodeId> {
+        if self.root[a] != self.root[b] {
+            return None;
+        }
+        let (deep, shallow) = if self.depth[a] >= self.depth[b] {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        let mut a = self
+            .kth_ancestor(deep, self.depth[deep] - self.depth[shallow])
+            .unwrap();
+        let mut b = shallow;
+        if a == b {
+            return Some(a);
+        }
+        for level in self.up.iter().rev() {
+            if level[a] != level[b] {
+                a = level[a];
+                b = level[b];
+            }
+        }
+        Some(self.up[0][a])
+    }
+
+    /// the number of edges on the path from `a` to `b`.
+    pub fn distance(&self, a: NodeId, b: NodeId) -> Option<usize> {
+        let top = self.lca(a, b)?;
+        Some(self.depth[a] + self.depth[b] - 2 * self.depth[top])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    // the ancestors of `node`, itself first.
+    fn ancestors(tree: &RootedTree, mut node: NodeId) -> Vec<NodeId> {
+        let mut path = vec![node];
+        while let Some(parent) = tree.parent(node) {
+            path.push(parent);
+            node = parent;
+        }
+        path
+    }
+
+    #[test]
+    fn test_small() {
+        let tree = RootedTree::from_parents(vec![
+            None,
+            Some(0),
+            Some(0),
+            Some(1),
+            Some(1),
+            Some(3),
+            None,
+        ]);
+        let lca = Lca::new(&tree);
+        assert_eq!(lca.lca(5, 4), Some(1));
+        assert_eq!(lca.lca(5, 2), Some(0));
+        assert_eq!(lca.lca(3, 5), Some(3));
+        assert_eq!(lca.lca(5, 6), None);
+        assert_eq!(lca.distance(5, 2), Some(4));
+        assert_eq!(lca.kth_ancestor(5, 3), Some(0));
+        assert_eq!(lca.kth_ancestor(5, 4), None);
+    }
+
+    #[test]
+    fn test_random() {
+        let mut rng = Rng::new(74);
+        for _ in 0..30 {
+            let n = 1 + rng.index(200);
+            // a random forest, deep when the parents are close by.
+            let spread = 1 + rng.index(n);
+            let parents = (0..n)
+                .map(|v| {
+                    if v == 0 || rng.chance(0.02) {
+                        None
+                    } else {
+                        Some(v - 1 - rng.index(v.min(spread)))
+                    }
+                })
+                .collect();
+            let tree = RootedTree::from_parents(parents);
+            let lca = Lca::new(&tree);
+            for _ in 0..100 {
+                let (a, b) = (rng.index(n), rng.index(n));
+                let (up_a, up_b) = (ancestors(&tree, a), ancestors(&tree, b));
+                let expected = up_a.iter().copied().find(|v| up_b.contains(v));
+                assert_eq!(lca.lca(a, b), expected);
+                let k = rng.index(up_a.len() + 1);
+                assert_eq!(lca.kth_ancestor(a, k), up_a.get(k).copied());
+                if let Some(top) = expected {
+                    let steps = |path: &[NodeId]| path.iter().position(|&v| v == top).unwrap();
+                    assert_eq!(lca.distance(a, b), Some(steps(&up_a) + steps(&up_b)));
+                }
+            }
+        }
+    }
+}
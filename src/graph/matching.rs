@@ -0,0 +1,225 @@
+use std::collections::VecDeque;
+
+/// a maximum matching of a bipartite graph, between the left nodes
+/// `0..left` and the right nodes `0..right`.
+#[derive(Clone, Debug)]
+pub struct Matching {
+    adjacency: Vec<Vec<usize>>,
+    left_match: Vec<Option<usize>>,
+    right_match: Vec<Option<usize>>,
+}
+
+const INFINITE: usize = usize::MAX;
+
+/// hopcroft-karp maximum bipartite matching in O(E * sqrt V): each phase
+/// finds the shortest augmenting paths by bfs and a maximal set of disjoint
+/// ones by dfs. `edges` holds `(left, right)` pairs.
+pub fn hopcroft_karp(left: usize, right: usize, edges: &[(usize, usize)]) -> Matching {
+    let mut adjacency = vec![Vec::new(); left];
+    for &(l, r) in edges {
+        assert!(l < left && r < right, "edge ({}, {}) out of bounds", l, r);
+        adjacency[l].push(r);
+    }
+    let mut matching = Matching {
+        adjacency,
+        left_match: vec![None; left],
+        right_match: vec![None; right],
+    };
+    let mut distance = vec![INFINITE; left];
+    let mut next = vec![0; left];
+    while let Some(length) = matching.layers(&mut distance) {
+        next.iter_mut().for_each(|i| *i = 0);
+        for l in 0..left {
+            if matching.left_match[l].is_none() {
+                matching.augment(l, length, &mut distance, &mut next);
+            }
+        }
+    }
+    matching
+}
+
+impl Matching {
+    /// number of matched pairs.
+    pub fn len(&self) -> usize {
+        self.left_match.iter().filter(|m| m.is_some()).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// the right node matched to left node `l`.
+    pub fn left_partner(&self, l: usize) -> Option<usize> {
+        self.left_match[l]
+    }
+
+    /// the left node matched to right node `r`.
+    pub fn right_partner(&self, r: usize) -> Option<usize> {
+        self.right_match[r]
+    }
+
+    /// the matched `(left, right)` pairs, by left node.
+    pub fn pairs(&self) -> Vec<(usize, usize)> {
+        (0..self.left_match.len())
+            .filter_map(|l| self.left_match[l].map(|r| (l, r)))
+            .collect()
+    }
+
+    // bfs from the free left nodes along alternating paths, setting the
+    // layer of each left node; returns the length of the shortest
+    // augmenting paths, in left nodes, if there is any.
+    fn layers(&self, distance: &mut [usize]) -> Option<usize> {
+        let mut queue = VecDeque::new();
+        for (l, d) in distance.iter_mut().enumerate() {
+            if self.left_match[l].is_none() {
+                *d = 0;
+                queue.push_back(l);
+            } else {
+                *d = INFINITE;
+            }
+        }
+        let mut found = INFINITE;
+        while let Some(l) = queue.pop_front() {
+            if distance[l] >= found {
+                continue;
+            }
+            for &r in &self.adjacency[l] {
+                match self.right_match[r] {
+                    None => found = found.min(distance[l] + 1),
+                    Some(next) if distance[next] == INFINITE => {
+                        distance[next] = distance[l] + 1;
+                        queue.push_back(next);
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+        if found == INFINITE {
+            None
+        } else {
+            Some(found)
+        }
+    }
+
+    // follow the layers from the free node `start` to a free right node and
+    // flip the path, using an explicit stack.
+    fn augment(&mut self, start: usize, length: usize, distance: &mut [usize], next: &mut [usize]) {
+        let mut lefts = vec![start];
+        let mut rights: Vec<usize> = Vec::new();
+        while let Some(&l) = lefts.last() {
+            let r = match self.adjacency[l].get(next[l]) {
+                Some(&r) => r,
+                None => {
+                    // no augmenting path continues through `l` this phase.
+                    distance[l] = INFINITE;
+                    lefts.pop();
+                    rights.pop();
+                    continue;
+                }
+            };
+            next[l] += 1;
+            match self.right_match[r] {
+                None if distance[l] + 1 == length => {
+                    rights.push(r);
+                    for (&l, &r) in lefts.iter().zip(&rights) {
+                        self.left_match[l] = Some(r);
+                        self.right_match[r] = Some(l);
+                    }
+                    return;
+                }
+                Some(other) if distance[other] == distance[l] + 1 => {
+                    lefts.push(other);
+                    rights.push(r);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// a minimum vertex cover, as `(left nodes, right nodes)`, of the same
+    /// size as the matching by könig's theorem: with `Z` the nodes reachable
+    /// from free left nodes by alternating paths, the left nodes outside `Z`
+    /// and the right nodes inside it.
+    pub fn vertex_cover(&self) -> (Vec<usize>, Vec<usize>) {
+        let mut left_seen = vec![false; self.left_match.len()];
+        let mut right_seen = vec![false; self.right_match.len()];
+        let mut stack: Vec<usize> = (0..self.left_match.len())
+            .filter(|&l| self.left_match[l].is_none())
+            .collect();
+        stack.iter().for_each(|&l| left_seen[l] = true);
+        while let Some(l) = stack.pop() {
+            for &r in &self.adjacency[l] {
+                if right_seen[r] {
+                    continue;
+                }
+                right_seen[r] = true;
+                // `r` is matched, or `l`'s path would augment.
+                if let Some(next) = self.right_match[r] {
+                    if !left_seen[next] {
+                        left_seen[next] = true;
+                        stack.push(next);
+                    }
+                }
+            }
+        }
+        let lefts = (0..left_seen.len()).filter(|&l| !left_seen[l]).collect();
+        let rights = (0..right_seen.len()).filter(|&r| right_seen[r]).collect();
+        (lefts, rights)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::flow::FlowNetwork;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_small() {
+        let edges = [(0, 0), (0, 1), (1, 0), (2, 1), (2, 2), (3, 2)];
+        let matching = hopcroft_karp(4, 3, &edges);
+        assert_eq!(matching.len(), 3);
+        for (l, r) in matching.pairs() {
+            assert!(edges.contains(&(l, r)));
+            assert_eq!(matching.right_partner(r), Some(l));
+        }
+        let (lefts, rights) = matching.vertex_cover();
+        assert_eq!(lefts.len() + rights.len(), 3);
+        assert!(hopcroft_karp(3, 0, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_against_max_flow() {
+        let mut rng = Rng::new(68);
+        for _ in 0..100 {
+            let (left, right) = (1 + rng.index(15), 1 + rng.index(15));
+            let edges: Vec<(usize, usize)> = (0..rng.index(3 * (left + right)))
+                .map(|_| (rng.index(left), rng.index(right)))
+                .collect();
+            let matching = hopcroft_karp(left, right, &edges);
+            let mut network = FlowNetwork::new(left + right + 2);
+            let (source, sink) = (left + right, left + right + 1);
+            (0..left).for_each(|l| {
+                network.add_edge(source, l, 1);
+            });
+            (0..right).for_each(|r| {
+                network.add_edge(left + r, sink, 1);
+            });
+            for &(l, r) in &edges {
+                network.add_edge(l, left + r, 1);
+            }
+            assert_eq!(matching.len() as i64, network.max_flow(source, sink));
+            let pairs = matching.pairs();
+            let mut used = vec![false; right];
+            for &(l, r) in &pairs {
+                assert!(edges.contains(&(l, r)) && !used[r]);
+                used[r] = true;
+            }
+            let (lefts, rights) = matching.vertex_cover();
+            assert_eq!(lefts.len() + rights.len(), matching.len());
+            for &(l, r) in &edges {
+                assert!(lefts.contains(&l) || rights.contains(&r));
+            }
+        }
+    }
+}
@@ -0,0 +1,251 @@
+use super::NodeId;
+use crate::{PriorityQueue, PriorityQueueImpl};
+
+#[derive(Clone, Debug)]
+struct CostArc {
+    to: NodeId,
+    capacity: i64,
+    flow: i64,
+    cost: i64,
+}
+
+/// a network of edges with capacities and per-unit costs, for minimum-cost
+/// flow. edges are stored with their reverses like in
+/// [`FlowNetwork`](super::flow::FlowNetwork).
+#[derive(Clone, Debug)]
+pub struct CostFlowNetwork {
+    arcs: Vec<CostArc>,
+    adjacency: Vec<Vec<usize>>,
+}
+
+impl CostFlowNetwork {
+    /// a network of `n` nodes and no edges.
+    pub fn new(n: usize) -> Self {
+        CostFlowNetwork {
+            arcs: Vec::new(),
+            adjacency: vec![Vec::new(); n],
+        }
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.adjacency.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.arcs.len() / 2
+    }
+
+    pub fn add_node(&mut self) -> NodeId {
+        self.adjacency.push(Vec::new());
+        self.adjacency.len() - 1
+    }
+
+    /// add an edge from `from` to `to` carrying up to `capacity` units at
+    /// `cost` each, returning its id. costs may be negative as long as no
+    /// cycle of them is.
+    pub fn add_edge(&mut self, from: NodeId, to: NodeId, capacity: i64, cost: i64) -> usize {
+        let n = self.adjacency.len();
+        assert!(from < n && to < n, "node out of bounds");
+        assert!(capacity >= 0, "negative capacity");
+        let id = self.arcs.len() / 2;
+        self.adjacency[from].push(self.arcs.len());
+        self.arcs.push(CostArc {
+            to,
+            capacity,
+            flow: 0,
+            cost,
+        });
+        self.adjacency[to].push(self.arcs.len());
+        self.arcs.push(CostArc {
+            to: from,
+            capacity: 0,
+            flow: 0,
+            cost: -cost,
+        });
+        id
+    }
+
+    /// the flow on `edge` after the last solve.
+    pub fn flow(&self, edge: usize) -> i64 {
+        self.arcs[2 * edge].flow
+    }
+
+    fn residual(&self, arc: usize) -> i64 {
+        self.arcs[arc].capacity - self.arcs[arc].flow
+    }
+
+    /// send as much flow as possible from `source` to `sink` at the least
+    /// total cost, returning `(flow, cost)`.
+    pub fn min_cost_max_flow(&mut self, source: NodeId, sink: NodeId) -> (i64, i64) {
+        self.min_cost_flow(source, sink, i64::MAX)
+    }
+
+    /// send up to `limit` units from `source` to `sink` at the least total
+    /// cost, returning `(flow, cost)`.
+    ///
+    /// successive shortest paths: bellman-ford sets node potentials once,
+    /// then each augmenting path is found by dijkstra over the reduced
+    /// costs, which the potentials keep non-negative. O(F * E log V) for a
+    /// flow of F.
+    pub fn min_cost_flow(&mut self, source: NodeId, sink: NodeId, limit: i64) -> (i64, i64) {
+        assert!(source != sink, "source and sink must differ");
+        self.arcs.iter_mut().for_each(|a| a.flow = 0);
+        let n = self.adjacency.len();
+        let mut potential = self.initial_potentials(source);
+        let (mut flow, mut cost) = (0, 0);
+        while flow < limit {
+            let (distance, parent) = self.shortest_paths(source, &potential);
+            if distance[sink].is_none() {
+                break;
+            }
+            for v in 0..n {
+                if let Some(d) = distance[v] {
+                    potential[v] += d;
+                }
+            }
+            let mut pushed = limit - flow;
+            let mut node = sink;
+            while node != source {
+                let arc = parent[node];
+                pushed = pushed.min(self.residual(arc));
+                node = self.arcs[arc ^ 1].to;
+            }
+            node = sink;
+            while node != source {
+                let arc = parent[node];
+                self.arcs[arc].flow += pushed;
+                self.arcs[arc ^ 1].flow -= pushed;
+                cost += pushed * self.arcs[arc].cost;
+                node = self.arcs[arc ^ 1].to;
+            }
+            flow += pushed;
+        }
+        (flow, cost)
+    }
+
+    // bellman-ford distances from the source over the arcs with capacity,
+    // or 0 for nodes it cannot reach (which no augmenting path visits).
+    fn initial_potentials(&self, source: NodeId) -> Vec<i64> {
+        let n = self.adjacency.len();
+        let mut distance: Vec<Option<i64>> = vec![None; n];
+        distance[source] = Some(0);
+        for _ in 0..n {
+            let mut changed = false;
+            for node in 0..n {
+                let d = match distance[node] {
+                    Some(d) => d,
+                    None => continue,
+                };
+                for &arc in &self.adjacency[node] {
+                    let (to, through) = (self.arcs[arc].to, d + self.arcs[arc].cost);
+                    if self.residual(arc) > 0 && distance[to].is_none_or(|old| through < old) {
+                        distance[to] = Some(through);
+                        changed = true;
+                    }
+                }
+            }
+            if !changed {
+                return distance.into_iter().map(|d| d.unwrap_or(0)).collect();
+            }
+        }
+        panic!("negative cost cycle");
+    }
+
+    // dijkstra over reduced costs: distances and the arc entering each node.
+    fn shortest_paths(&self, source: NodeId, potential: &[i64]) -> (Vec<Option<i64>>, Vec<usize>) {
+        let n = self.adjacency.len();
+        let mut distance: Vec<Option<i64>> = vec![None; n];
+        let mut parent = vec![usize::MAX; n];
+        let mut done = vec![false; n];
+        let mut queue = PriorityQueueImpl::new();
+        distance[source] = Some(0);
+        queue.insert((source, 0), u64::MAX);
+        while let Some((node, d)) = queue.pop() {
+            if done[node] {
+                continue;
+            }
+            done[node] = true;
+            for &arc in &self.adjacency[node] {
+                let to = self.arcs[arc].to;
+                if self.residual(arc) <= 0 || done[to] {
+                    continue;
+                }
+                let reduced = self.arcs[arc].cost + potential[node] - potential[to];
+                let through = d + reduced;
+                if distance[to].is_none_or(|old| through < old) {
+                    distance[to] = Some(through);
+                    parent[to] = arc;
+                    queue.insert((to, through), u64::MAX - through as u64);
+                }
+            }
+        }
+        (distance, parent)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::flow::FlowNetwork;
+    use crate::graph::test_util::permutations;
+    use crate::rng::Rng;
+
+    // every permutation of `0..n`, by heap's algorithm.
+    #[test]
+    fn test_assignment() {
+        let mut rng = Rng::new(66);
+        for _ in 0..30 {
+            let n = 1 + rng.index(5);
+            let cost: Vec<Vec<i64>> = (0..n)
+                .map(|_| (0..n).map(|_| rng.range(-20..50)).collect())
+                .collect();
+            // source, workers, jobs, sink.
+            let mut network = CostFlowNetwork::new(2 * n + 2);
+            let (source, sink) = (2 * n, 2 * n + 1);
+            let mut edges = vec![vec![0; n]; n];
+            for (worker, row) in cost.iter().enumerate() {
+                network.add_edge(source, worker, 1, 0);
+                network.add_edge(n + worker, sink, 1, 0);
+                for (job, &c) in row.iter().enumerate() {
+                    edges[worker][job] = network.add_edge(worker, n + job, 1, c);
+                }
+            }
+            let best = permutations(n)
+                .iter()
+                .map(|p| (0..n).map(|w| cost[w][p[w]]).sum::<i64>())
+                .min()
+                .unwrap();
+            assert_eq!(network.min_cost_max_flow(source, sink), (n as i64, best));
+            let chosen: i64 = (0..n)
+                .flat_map(|w| (0..n).map(move |j| (w, j)))
+                .filter(|&(w, j)| network.flow(edges[w][j]) == 1)
+                .map(|(w, j)| cost[w][j])
+                .sum();
+            assert_eq!(chosen, best);
+        }
+    }
+
+    #[test]
+    fn test_against_max_flow() {
+        let mut rng = Rng::new(67);
+        for _ in 0..50 {
+            let n = 2 + rng.index(10);
+            let mut network = CostFlowNetwork::new(n);
+            let mut plain = FlowNetwork::new(n);
+            for _ in 0..rng.index(4 * n) {
+                let (a, b, c) = (rng.index(n), rng.index(n), rng.below(10) as i64);
+                network.add_edge(a, b, c, rng.below(10) as i64);
+                plain.add_edge(a, b, c);
+            }
+            let (flow, cost) = network.min_cost_max_flow(0, n - 1);
+            assert_eq!(flow, plain.max_flow(0, n - 1));
+            assert!(cost >= 0);
+            // one unit less can only be cheaper.
+            if flow > 0 {
+                let (less, cheaper) = network.min_cost_flow(0, n - 1, flow - 1);
+                assert_eq!(less, flow - 1);
+                assert!(cheaper <= cost);
+            }
+        }
+    }
+}
@@ -0,0 +1,593 @@
+pub mod bellman_ford;
+pub mod biconnected;
+pub mod bidirectional;
+pub mod centroid;
+pub mod coloring;
+pub mod community;
+pub mod cycles;
+pub mod dial;
+pub mod dijkstra;
+pub mod dot;
+pub mod euler;
+pub mod euler_tour;
+pub mod flow;
+pub mod floyd_warshall;
+pub mod generators;
+pub mod hld;
+pub mod johnson;
+pub mod lca;
+pub mod matching;
+pub mod min_cost_flow;
+pub mod mst;
+pub mod pagerank;
+pub mod scc;
+pub mod topo;
+pub mod traversal;
+pub mod tree;
+pub mod tsp;
+pub mod two_sat;
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Add, Range, Sub};
+
+/// index of a node, in the order the nodes were added.
+pub type NodeId = usize;
+/// index of an edge, in the order the edges were added.
+pub type EdgeId = usize;
+
+/// marks whether a [`Graph`]'s edges have a direction.
+pub trait EdgeType {
+    const DIRECTED: bool;
+}
+
+/// edges go from their source to their target only.
+#[derive(Clone, Copy, Debug)]
+pub enum Directed {}
+
+/// edges connect their endpoints both ways.
+#[derive(Clone, Copy, Debug)]
+pub enum Undirected {}
+
+impl EdgeType for Directed {
+    const DIRECTED: bool = true;
+}
+
+impl EdgeType for Undirected {
+    const DIRECTED: bool = false;
+}
+
+/// edge weights the shortest path and spanning tree algorithms work with.
+///
+/// `order_key` maps weights to integers in the same order, so they can
+/// serve as priorities in the crate's [`PriorityQueueImpl`].
+///
+/// [`PriorityQueueImpl`]: crate::PriorityQueueImpl
+pub trait Weight: Copy + PartialOrd + Add<Output = Self> + Sub<Output = Self> {
+    fn zero() -> Self;
+    fn order_key(self) -> u64;
+}
+
+macro_rules! unsigned_weight {
+    ($($t:ty),*) => {$(
+        impl Weight for $t {
+            fn zero() -> Self {
+                0
+            }
+
+            fn order_key(self) -> u64 {
+                self as u64
+            }
+        }
+    )*};
+}
+
+macro_rules! signed_weight {
+    ($($t:ty),*) => {$(
+        impl Weight for $t {
+            fn zero() -> Self {
+                0
+            }
+
+            fn order_key(self) -> u64 {
+                // flipping the sign bit orders negatives first.
+                (self as i64 as u64) ^ (1 << 63)
+            }
+        }
+    )*};
+}
+
+unsigned_weight!(u8, u16, u32, u64, usize);
+signed_weight!(i8, i16, i32, i64, isize);
+
+impl Weight for f64 {
+    fn zero() -> Self {
+        0.0
+    }
+
+    fn order_key(self) -> u64 {
+        let bits = self.to_bits();
+        if bits >> 63 == 1 {
+            !bits
+        } else {
+            bits | (1 << 63)
+        }
+    }
+}
+
+impl Weight for f32 {
+    fn zero() -> Self {
+        0.0
+    }
+
+    fn order_key(self) -> u64 {
+        f64::from(self).order_key()
+    }
+}
+
+/// a stored edge.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Edge<E> {
+    pub source: NodeId,
+    pub target: NodeId,
+    pub weight: E,
+}
+
+/// an edge seen from one of its endpoints: for undirected graphs `source`
+/// is the endpoint it was reached from, whichever way it was added.
+#[derive(Debug)]
+pub struct EdgeRef<'a, E> {
+    pub id: EdgeId,
+    pub source: NodeId,
+    pub target: NodeId,
+    pub weight: &'a E,
+}
+
+impl<E> Clone for EdgeRef<'_, E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<E> Copy for EdgeRef<'_, E> {}
+
+/// what [`Graph::from_edges`] accepts: `(source, target)` pairs with a
+/// default weight, or `(source, target, weight)` triples.
+pub trait IntoWeightedEdge<E> {
+    fn into_weighted_edge(self) -> (NodeId, NodeId, E);
+}
+
+impl<E: Default> IntoWeightedEdge<E> for (NodeId, NodeId) {
+    fn into_weighted_edge(self) -> (NodeId, NodeId, E) {
+        (self.0, self.1, E::default())
+    }
+}
+
+impl<E> IntoWeightedEdge<E> for (NodeId, NodeId, E) {
+    fn into_weighted_edge(self) -> (NodeId, NodeId, E) {
+        self
+    }
+}
+
+impl<E: Clone> IntoWeightedEdge<E> for &(NodeId, NodeId, E) {
+    fn into_weighted_edge(self) -> (NodeId, NodeId, E) {
+        (self.0, self.1, self.2.clone())
+    }
+}
+
+impl<E: Default> IntoWeightedEdge<E> for &(NodeId, NodeId) {
+    fn into_weighted_edge(self) -> (NodeId, NodeId, E) {
+        (self.0, self.1, E::default())
+    }
+}
+
+/// graph stored as adjacency lists, with a payload `N` on every node and a
+/// weight `E` on every edge (`()` for unweighted graphs).
+///
+/// nodes and edges are identified by their insertion index. parallel edges
+/// and self-loops are allowed; an undirected self-loop appears once in its
+/// node's adjacency list.
+#[derive(Clone)]
+pub struct Graph<N, E, Ty = Directed> {
+    nodes: Vec<N>,
+    edges: Vec<Edge<E>>,
+    // (neighbor, edge) pairs leaving each node.
+    outgoing: Vec<Vec<(NodeId, EdgeId)>>,
+    // (neighbor, edge) pairs entering each node; empty for undirected graphs.
+    incoming: Vec<Vec<(NodeId, EdgeId)>>,
+    ty: PhantomData<Ty>,
+}
+
+pub type DiGraph<N, E> = Graph<N, E, Directed>;
+pub type UnGraph<N, E> = Graph<N, E, Undirected>;
+
+impl<N, E, Ty: EdgeType> Graph<N, E, Ty> {
+    pub fn new() -> Self {
+        Graph {
+            nodes: Vec::new(),
+            edges: Vec::new(),
+            outgoing: Vec::new(),
+            incoming: Vec::new(),
+            ty: PhantomData,
+        }
+    }
+
+    /// a graph of `node_count` default nodes and the given edges.
+    pub fn from_edges<I>(node_count: usize, edges: I) -> Self
+    where
+        N: Default,
+        I: IntoIterator,
+        I::Item: IntoWeightedEdge<E>,
+    {
+        let mut graph = Graph::new();
+        for _ in 0..node_count {
+            graph.add_node(N::default());
+        }
+        for edge in edges {
+            let (a, b, weight) = edge.into_weighted_edge();
+            graph.add_edge(a, b, weight);
+        }
+        graph
+    }
+
+    pub fn is_directed(&self) -> bool {
+        Ty::DIRECTED
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    pub fn add_node(&mut self, payload: N) -> NodeId {
+        self.nodes.push(payload);
+        self.outgoing.push(Vec::new());
+        if Ty::DIRECTED {
+            self.incoming.push(Vec::new());
+        }
+        self.nodes.len() - 1
+    }
+
+    /// add an edge from `source` to `target`; both must exist.
+    pub fn add_edge(&mut self, source: NodeId, target: NodeId, weight: E) -> EdgeId {
+        let n = self.nodes.len();
+        assert!(source < n, "node {} out of bounds", source);
+        assert!(target < n, "node {} out of bounds", target);
+        let id = self.edges.len();
+        self.edges.push(Edge {
+            source,
+            target,
+            weight,
+        });
+        self.outgoing[source].push((target, id));
+        if Ty::DIRECTED {
+            self.incoming[target].push((source, id));
+        } else if source != target {
+            self.outgoing[target].push((source, id));
+        }
+        id
+    }
+
+    pub fn node(&self, id: NodeId) -> Option<&N> {
+        self.nodes.get(id)
+    }
+
+    pub fn node_mut(&mut self, id: NodeId) -> Option<&mut N> {
+        self.nodes.get_mut(id)
+    }
+
+    pub fn edge(&self, id: EdgeId) -> Option<&Edge<E>> {
+        self.edges.get(id)
+    }
+
+    pub fn weight_mut(&mut self, id: EdgeId) -> Option<&mut E> {
+        self.edges.get_mut(id).map(|e| &mut e.weight)
+    }
+
+    pub fn node_ids(&self) -> Range<NodeId> {
+        0..self.nodes.len()
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = &N> + '_ {
+        self.nodes.iter()
+    }
+
+    /// all edges, in insertion order and as they were added.
+    pub fn edges(&self) -> impl Iterator<Item = EdgeRef<'_, E>> + '_ {
+        self.edges.iter().enumerate().map(|(id, e)| EdgeRef {
+            id,
+            source: e.source,
+            target: e.target,
+            weight: &e.weight,
+        })
+    }
+
+    /// the nodes reachable over one edge from `node`, once per edge.
+    pub fn neighbors(&self, node: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.outgoing[node].iter().map(|&(n, _)| n)
+    }
+
+    /// the edges leaving `node` (all its edges, if undirected).
+    pub fn edges_from(&self, node: NodeId) -> impl Iterator<Item = EdgeRef<'_, E>> + '_ {
+        self.outgoing[node]
+            .iter()
+            .map(move |&(target, id)| EdgeRef {
+                id,
+                source: node,
+                target,
+                weight: &self.edges[id].weight,
+            })
+    }
+
+    /// the edges entering `node` (all its edges, if undirected).
+    pub fn edges_to(&self, node: NodeId) -> impl Iterator<Item = EdgeRef<'_, E>> + '_ {
+        let list = if Ty::DIRECTED {
+            &self.incoming[node]
+        } else {
+            &self.outgoing[node]
+        };
+        list.iter().map(move |&(source, id)| EdgeRef {
+            id,
+            source,
+            target: node,
+            weight: &self.edges[id].weight,
+        })
+    }
+
+    pub fn out_degree(&self, node: NodeId) -> usize {
+        self.outgoing[node].len()
+    }
+
+    pub fn in_degree(&self, node: NodeId) -> usize {
+        if Ty::DIRECTED {
+            self.incoming[node].len()
+        } else {
+            self.outgoing[node].len()
+        }
+    }
+
+    /// the first edge added from `source` to `target`.
+    pub fn find_edge(&self, source: NodeId, target: NodeId) -> Option<EdgeId> {
+        self.outgoing
+            .get(source)?
+            .iter()
+            .find(|&&(n, _)| n == target)
+            .map(|&(_, id)| id)
+    }
+
+    pub fn contains_edge(&self, source: NodeId, target: NodeId) -> bool {
+        self.find_edge(source, target).is_some()
+    }
+}
+
+impl<N, E, Ty: EdgeType> Default for Graph<N, E, Ty> {
+    fn default() -> Self {
+        Graph::new()
+    }
+}
+
+impl<N, E, Ty: EdgeType> fmt::Debug for Graph<N, E, Ty> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Graph")
+            .field("directed", &Ty::DIRECTED)
+            .field("nodes", &self.nodes.len())
+            .field("edges", &self.edges.len())
+            .finish()
+    }
+}
+
+/// dense view of a graph's weights: the lightest edge from each node to
+/// each other, for algorithms that work on all pairs at once.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AdjacencyMatrix<W> {
+    n: usize,
+    weights: Vec<Option<W>>,
+}
+
+impl<W: Weight> AdjacencyMatrix<W> {
+    /// `n` nodes and no edges.
+    pub fn new(n: usize) -> Self {
+        AdjacencyMatrix {
+            n,
+            weights: vec![None; n * n],
+        }
+    }
+
+    pub fn from_graph<N, Ty: EdgeType>(graph: &Graph<N, W, Ty>) -> Self {
+        let mut matrix = AdjacencyMatrix::new(graph.node_count());
+        for node in graph.node_ids() {
+            for edge in graph.edges_from(node) {
+                let cell = &mut matrix.weights[node * matrix.n + edge.target];
+                if cell.is_none_or(|w| *edge.weight < w) {
+                    *cell = Some(*edge.weight);
+                }
+            }
+        }
+        matrix
+    }
+
+    /// number of nodes.
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    pub fn get(&self, from: NodeId, to: NodeId) -> Option<W> {
+        assert!(from < self.n && to < self.n, "node out of bounds");
+        self.weights[from * self.n + to]
+    }
+
+    pub fn set(&mut self, from: NodeId, to: NodeId, weight: Option<W>) {
+        assert!(from < self.n && to < self.n, "node out of bounds");
+        self.weights[from * self.n + to] = weight;
+    }
+}
+
+/// distances from one source, with the tree of shortest paths.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShortestPaths<W> {
+    source: NodeId,
+    distance: Vec<Option<W>>,
+    // the node and edge each node is reached from.
+    parent: Vec<Option<(NodeId, EdgeId)>>,
+}
+
+impl<W: Weight> ShortestPaths<W> {
+    // only the source reached so far.
+    pub(crate) fn start(node_count: usize, source: NodeId) -> Self {
+        assert!(source < node_count, "node {} out of bounds", source);
+        let mut paths = ShortestPaths {
+            source,
+            distance: vec![None; node_count],
+            parent: vec![None; node_count],
+        };
+        paths.distance[source] = Some(W::zero());
+        paths
+    }
+
+    // record `node` reached at `distance` over `edge` from `from`, if that
+    // is an improvement.
+    pub(crate) fn relax(&mut self, from: NodeId, edge: EdgeId, node: NodeId, distance: W) -> bool {
+        if self.distance[node].is_some_and(|d| d <= distance) {
+            return false;
+        }
+        self.distance[node] = Some(distance);
+        self.parent[node] = Some((from, edge));
+        true
+    }
+
+    pub fn source(&self) -> NodeId {
+        self.source
+    }
+
+    /// the length of a shortest path to `node`, if it is reachable.
+    pub fn distance(&self, node: NodeId) -> Option<W> {
+        self.distance[node]
+    }
+
+    /// the last edge of the shortest path to `node`, with the node it
+    /// leaves.
+    pub fn parent(&self, node: NodeId) -> Option<(NodeId, EdgeId)> {
+        self.parent[node]
+    }
+
+    /// the nodes of a shortest path from the source to `node`.
+    pub fn path_to(&self, node: NodeId) -> Option<Vec<NodeId>> {
+        self.distance[node]?;
+        let mut path = vec![node];
+        let mut current = node;
+        while let Some((previous, _)) = self.parent[current] {
+            path.push(previous);
+            current = previous;
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+// helpers shared by the tests of the graph modules.
+#[cfg(test)]
+pub(crate) mod test_util {
+    use super::NodeId;
+    use crate::rng::Rng;
+
+    /// parents of a random forest on `n` nodes, each node hanging below one
+    /// of the `spread` nodes before it, so the shapes range from paths to stars.
+    pub(crate) fn random_forest(rng: &mut Rng, n: usize) -> Vec<Option<NodeId>> {
+        let spread = 1 + rng.index(n);
+        (0..n)
+            .map(|v| {
+                if v == 0 || rng.chance(0.05) {
+                    None
+                } else {
+                    Some(v - 1 - rng.index(v.min(spread)))
+                }
+            })
+            .collect()
+    }
+
+    /// every permutation of `0..n`, in lexicographic order.
+    pub(crate) fn permutations(n: usize) -> Vec<Vec<usize>> {
+        fn extend(prefix: &mut Vec<usize>, n: usize, out: &mut Vec<Vec<usize>>) {
+            if prefix.len() == n {
+                out.push(prefix.clone());
+                return;
+            }
+            for v in 0..n {
+                if !prefix.contains(&v) {
+                    prefix.push(v);
+                    extend(prefix, n, out);
+                    prefix.pop();
+                }
+            }
+        }
+        let mut out = Vec::new();
+        extend(&mut Vec::new(), n, &mut out);
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_directed() {
+        let mut graph: DiGraph<&str, u32> = Graph::new();
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        let c = graph.add_node("c");
+        graph.add_edge(a, b, 3);
+        graph.add_edge(a, c, 1);
+        let cb = graph.add_edge(c, b, 7);
+        assert!(graph.is_directed());
+        assert_eq!((graph.node_count(), graph.edge_count()), (3, 3));
+        assert_eq!(graph.neighbors(a).collect::<Vec<_>>(), vec![b, c]);
+        assert_eq!(graph.neighbors(b).count(), 0);
+        assert_eq!((graph.in_degree(b), graph.out_degree(b)), (2, 0));
+        let into_b: Vec<_> = graph.edges_to(b).map(|e| (e.source, *e.weight)).collect();
+        assert_eq!(into_b, vec![(a, 3), (c, 7)]);
+        assert_eq!(graph.find_edge(c, b), Some(cb));
+        assert!(!graph.contains_edge(b, c));
+        *graph.weight_mut(cb).unwrap() = 2;
+        assert_eq!(graph.edge(cb).unwrap().weight, 2);
+        assert_eq!(graph.node(c), Some(&"c"));
+        assert_eq!(
+            format!("{:?}", graph),
+            "Graph { directed: true, nodes: 3, edges: 3 }"
+        );
+    }
+
+    #[test]
+    fn test_undirected_from_edges() {
+        let graph: UnGraph<(), ()> = Graph::from_edges(4, vec![(0, 1), (1, 2), (2, 2)]);
+        assert!(!graph.is_directed());
+        assert_eq!(graph.neighbors(1).collect::<Vec<_>>(), vec![0, 2]);
+        assert_eq!(graph.neighbors(2).collect::<Vec<_>>(), vec![1, 2]);
+        assert!(graph.contains_edge(1, 0) && !graph.contains_edge(0, 3));
+        let from_two: Vec<_> = graph.edges_from(2).map(|e| (e.source, e.target)).collect();
+        assert_eq!(from_two, vec![(2, 1), (2, 2)]);
+        assert_eq!(graph.in_degree(0), 1);
+        let weighted: UnGraph<(), f64> = Graph::from_edges(3, vec![(0, 1, 0.5), (1, 2, 1.5)]);
+        let total: f64 = weighted.edges().map(|e| *e.weight).sum();
+        assert_eq!(total, 2.0);
+    }
+
+    #[test]
+    fn test_order_key() {
+        let ints = [i64::MIN, -5, -1, 0, 1, 7, i64::MAX];
+        assert!(ints.windows(2).all(|w| w[0].order_key() < w[1].order_key()));
+        let floats = [f64::NEG_INFINITY, -2.5, -0.0, 0.5, 3.0, f64::INFINITY];
+        assert!(floats
+            .windows(2)
+            .all(|w| w[0].order_key() <= w[1].order_key()));
+        assert!(1.0f32.order_key() < 1.5f32.order_key());
+        assert!(3u32.order_key() < 4u32.order_key());
+    }
+}
@@ -0,0 +1,122 @@
+use std::cmp::Ordering;
+
+use super::dijkstra::min_priority;
+use super::{EdgeId, UnGraph, Weight};
+use crate::dsu::DisjointSet;
+use crate::{PriorityQueue, PriorityQueueImpl};
+
+/// a minimum spanning forest: a minimum spanning tree of every connected
+/// component.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpanningForest<W> {
+    /// the chosen edges, in the order they were picked.
+    pub edges: Vec<EdgeId>,
+    pub weight: W,
+}
+
+/// kruskal's algorithm in O(E log E): take the edges from lightest to
+/// heaviest, skipping those whose endpoints are already connected.
+pub fn kruskal<N, E: Weight>(graph: &UnGraph<N, E>) -> SpanningForest<E> {
+    let mut order: Vec<EdgeId> = (0..graph.edge_count()).collect();
+    order.sort_by(|&a, &b| {
+        let (a, b) = (&graph.edges[a].weight, &graph.edges[b].weight);
+        a.partial_cmp(b).unwrap_or(Ordering::Equal)
+    });
+    let mut sets = DisjointSet::new(graph.node_count());
+    let mut forest = SpanningForest {
+        edges: Vec::new(),
+        weight: E::zero(),
+    };
+    for id in order {
+        let edge = &graph.edges[id];
+        if sets.union(edge.source, edge.target) {
+            forest.edges.push(id);
+            forest.weight = forest.weight + edge.weight;
+        }
+    }
+    forest
+}
+
+/// prim's algorithm in O(E log V) with the crate's priority queue: grow
+/// each tree from its smallest node by the lightest edge leaving it.
+pub fn prim<N, E: Weight>(graph: &UnGraph<N, E>) -> SpanningForest<E> {
+    let mut in_tree = vec![false; graph.node_count()];
+    let mut forest = SpanningForest {
+        edges: Vec::new(),
+        weight: E::zero(),
+    };
+    let mut queue = PriorityQueueImpl::new();
+    for root in graph.node_ids() {
+        if in_tree[root] {
+            continue;
+        }
+        in_tree[root] = true;
+        for edge in graph.edges_from(root) {
+            queue.insert((edge.id, edge.target), min_priority(*edge.weight));
+        }
+        while let Some((id, node)) = queue.pop() {
+            if in_tree[node] {
+                continue;
+            }
+            in_tree[node] = true;
+            forest.edges.push(id);
+            forest.weight = forest.weight + graph.edges[id].weight;
+            for edge in graph.edges_from(node) {
+                if !in_tree[edge.target] {
+                    queue.insert((edge.id, edge.target), min_priority(*edge.weight));
+                }
+            }
+        }
+    }
+    forest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Graph;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_small() {
+        let graph: UnGraph<(), u32> = Graph::from_edges(
+            6,
+            vec![
+                (0, 1, 4),
+                (0, 2, 3),
+                (1, 2, 1),
+                (1, 3, 2),
+                (2, 3, 4),
+                (3, 4, 2),
+                (4, 4, 0),
+            ],
+        );
+        let forest = kruskal(&graph);
+        assert_eq!(forest.edges, vec![2, 3, 5, 1]);
+        assert_eq!(forest.weight, 8);
+        assert_eq!(prim(&graph).weight, 8);
+        let empty: UnGraph<(), f64> = Graph::new();
+        assert_eq!(kruskal(&empty).edges, vec![]);
+    }
+
+    #[test]
+    fn test_kruskal_matches_prim() {
+        let mut rng = Rng::new(62);
+        for _ in 0..50 {
+            let n = 1 + rng.index(40);
+            let edges: Vec<(usize, usize, i64)> = (0..rng.index(3 * n))
+                .map(|_| (rng.index(n), rng.index(n), rng.range(-10..100)))
+                .collect();
+            let graph: UnGraph<(), i64> = Graph::from_edges(n, edges);
+            let (a, b) = (kruskal(&graph), prim(&graph));
+            assert_eq!(a.weight, b.weight);
+            assert_eq!(a.edges.len(), b.edges.len());
+            // a spanning forest has one edge fewer than nodes per component.
+            let mut sets = DisjointSet::new(n);
+            for e in graph.edges() {
+                sets.union(e.source, e.target);
+            }
+            assert_eq!(a.edges.len(), n - sets.count());
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use super::{EdgeType, Graph};
+
+/// pagerank by power iteration.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PageRank {
+    /// the chance of following an edge rather than teleporting.
+    pub damping: f64,
+    /// stop once the scores move by less than this in total.
+    pub tolerance: f64,
+    pub max_iterations: usize,
+    /// where teleports land, in proportion to each node's entry; uniform if
+    /// `None`. dangling nodes, those without outgoing edges, teleport too.
+    pub personalization: Option<Vec<f64>>,
+}
+
+impl Default for PageRank {
+    fn default() -> Self {
+        PageRank {
+            damping: 0.85,
+            tolerance: 1e-10,
+            max_iterations: 100,
+            personalization: None,
+        }
+    }
+}
+
+impl PageRank {
+    /// the score of each node, summing to 1. each iteration is O(V + E);
+    /// edges are followed both ways if the graph is undirected, and
+    /// parallel edges count once each.
+    pub fn rank<N, E, Ty: EdgeType>(&self, graph: &Graph<N, E, Ty>) -> Vec<f64> {
+        let n = graph.node_count();
+        assert!(
+            (0.0..=1.0).contains(&self.damping),
+            "damping outside [0, 1]"
+        );
+        if n == 0 {
+            return Vec::new();
+        }
+        let teleport: Vec<f64> = match &self.personalization {
+            Some(weights) => {
+                assert_eq!(weights.len(), n, "one weight per node");
+                assert!(weights.iter().all(|&w| w >= 0.0), "negative weight");
+                let total: f64 = weights.iter().sum();
+                assert!(total > 0.0, "weights sum to zero");
+                weights.iter().map(|w| w / total).collect()
+            }
+            None => vec![1.0 / n as f64; n],
+        };
+        let mut scores = teleport.clone();
+        let mut next = vec![0.0; n];
+        for _ in 0..self.max_iterations {
+            let mut dangling = 0.0;
+            next.iter_mut().for_each(|s| *s = 0.0);
+            for node in graph.node_ids() {
+                let degree = graph.out_degree(node);
+                if degree == 0 {
+                    dangling += scores[node];
+                    continue;
+                }
+                let share = scores[node] / degree as f64;
+                for to in graph.neighbors(node) {
+                    next[to] += share;
+                }
+            }
+            let mut change = 0.0;
+            for (v, score) in next.iter_mut().enumerate() {
+                *score = self.damping * (*score + dangling * teleport[v])
+                    + (1.0 - self.damping) * teleport[v];
+                change += (*score - scores[v]).abs();
+            }
+            std::mem::swap(&mut scores, &mut next);
+            if change < self.tolerance {
+                break;
+            }
+        }
+        scores
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{DiGraph, UnGraph};
+    use crate::rng::Rng;
+
+    fn close(a: &[f64], b: &[f64]) -> bool {
+        a.iter().zip(b).all(|(x, y)| (x - y).abs() < 1e-8)
+    }
+
+    // one iteration of `options` started from `scores`.
+    fn step(options: &PageRank, graph: &DiGraph<(), ()>, scores: &[f64]) -> Vec<f64> {
+        let n = graph.node_count();
+        let weights = options.personalization.clone().unwrap();
+        let total: f64 = weights.iter().sum();
+        let mut next = vec![0.0; n];
+        let mut dangling = 0.0;
+        for v in graph.node_ids() {
+            let degree = graph.out_degree(v);
+            if degree == 0 {
+                dangling += scores[v];
+            }
+            for to in graph.neighbors(v) {
+                next[to] += scores[v] / degree as f64;
+            }
+        }
+        (0..n)
+            .map(|v| {
+                let t = weights[v] / total;
+                options.damping * (next[v] + dangling * t) + (1.0 - options.damping) * t
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_small() {
+        // a cycle is uniform; a star's center collects the most.
+        let cycle: DiGraph<(), ()> = Graph::from_edges(4, vec![(0, 1), (1, 2), (2, 3), (3, 0)]);
+        assert!(close(&PageRank::default().rank(&cycle), &[0.25; 4]));
+        let ring: UnGraph<(), ()> = Graph::from_edges(5, (0..5).map(|v| (v, (v + 1) % 5)));
+        assert!(close(&PageRank::default().rank(&ring), &[0.2; 5]));
+        let star: DiGraph<(), ()> = Graph::from_edges(4, vec![(1, 0), (2, 0), (3, 0)]);
+        let scores = PageRank::default().rank(&star);
+        assert!((scores.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!(scores[0] > 2.0 * scores[1] && close(&scores[1..2], &scores[2..3]));
+        // without damping, everything teleports.
+        let options = PageRank {
+            damping: 0.0,
+            personalization: Some(vec![0.0, 1.0, 3.0, 0.0]),
+            ..PageRank::default()
+        };
+        assert!(close(&options.rank(&star), &[0.0, 0.25, 0.75, 0.0]));
+        assert!(PageRank::default()
+            .rank(&DiGraph::<(), ()>::new())
+            .is_empty());
+    }
+
+    #[test]
+    fn test_stationary() {
+        // the scores are a fixed point of one more step.
+        let mut rng = Rng::new(81);
+        for _ in 0..20 {
+            let n = 1 + rng.index(30);
+            let edges: Vec<(usize, usize)> = (0..rng.index(3 * n))
+                .map(|_| (rng.index(n), rng.index(n)))
+                .collect();
+            let personalization: Vec<f64> = (0..n).map(|_| 0.1 + rng.next_f64()).collect();
+            let options = PageRank {
+                tolerance: 1e-14,
+                max_iterations: 1000,
+                personalization: Some(personalization),
+                ..PageRank::default()
+            };
+            let directed: DiGraph<(), ()> = Graph::from_edges(n, edges);
+            let scores = options.rank(&directed);
+            assert!((scores.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+            assert!(close(&scores, &step(&options, &directed, &scores)));
+        }
+    }
+}
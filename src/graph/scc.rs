@@ -0,0 +1,187 @@
+use super::{DiGraph, Graph, NodeId};
+
+/// the strongly connected components of a directed graph.
+///
+/// components are numbered in reverse topological order: every edge
+/// between two components goes from a higher id to a lower one, so
+/// component 0 has no edges leaving it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Components {
+    component: Vec<usize>,
+    count: usize,
+}
+
+impl Components {
+    /// number of components.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// the id of the component containing `node`.
+    pub fn of(&self, node: NodeId) -> usize {
+        self.component[node]
+    }
+
+    /// the nodes of each component, indexed by id and in increasing order.
+    pub fn groups(&self) -> Vec<Vec<NodeId>> {
+        let mut groups = vec![Vec::new(); self.count];
+        for (node, &c) in self.component.iter().enumerate() {
+            groups[c].push(node);
+        }
+        groups
+    }
+}
+
+const UNVISITED: usize = usize::MAX;
+
+/// tarjan's algorithm in O(V + E), without recursion.
+pub fn tarjan_scc<N, E>(graph: &DiGraph<N, E>) -> Components {
+    let n = graph.node_count();
+    let mut index = vec![UNVISITED; n];
+    let mut low = vec![0; n];
+    let mut on_stack = vec![false; n];
+    let mut stack = Vec::new();
+    // the nodes being explored, with the next edge to follow from each.
+    let mut calls: Vec<(NodeId, usize)> = Vec::new();
+    let mut component = vec![0; n];
+    let (mut next_index, mut count) = (0, 0);
+    for root in graph.node_ids() {
+        if index[root] != UNVISITED {
+            continue;
+        }
+        index[root] = next_index;
+        low[root] = next_index;
+        next_index += 1;
+        stack.push(root);
+        on_stack[root] = true;
+        calls.push((root, 0));
+        while let Some(&mut (node, ref mut edge)) = calls.last_mut() {
+            if let Some(&(next, _)) = graph.outgoing[node].get(*edge) {
+                *edge += 1;
+                if index[next] == UNVISITED {
+                    index[next] = next_index;
+                    low[next] = next_index;
+                    next_index += 1;
+                    stack.push(next);
+                    on_stack[next] = true;
+                    calls.push((next, 0));
+                } else if on_stack[next] {
+                    low[node] = low[node].min(index[next]);
+                }
+                continue;
+            }
+            calls.pop();
+            if low[node] == index[node] {
+                loop {
+                    let member = stack.pop().unwrap();
+                    on_stack[member] = false;
+                    component[member] = count;
+                    if member == node {
+                        break;
+                    }
+                }
+                count += 1;
+            }
+            if let Some(&(parent, _)) = calls.last() {
+                low[parent] = low[parent].min(low[node]);
+            }
+        }
+    }
+    Components { component, count }
+}
+
+/// the graph of the components: node `c` holds the members of component
+/// `c`, and there is one edge from `a` to `b` whenever some edge leads from
+/// a member of `a` to a member of `b != a`. it is acyclic, and the node ids
+/// are a reverse topological order.
+pub fn condensation<N, E>(
+    graph: &DiGraph<N, E>,
+    components: &Components,
+) -> DiGraph<Vec<NodeId>, ()> {
+    let mut condensed = Graph::new();
+    for group in components.groups() {
+        condensed.add_node(group);
+    }
+    let mut edges: Vec<(usize, usize)> = graph
+        .edges()
+        .map(|e| (components.of(e.source), components.of(e.target)))
+        .filter(|(a, b)| a != b)
+        .collect();
+    edges.sort_unstable();
+    edges.dedup();
+    for (a, b) in edges {
+        condensed.add_edge(a, b, ());
+    }
+    condensed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::topo::toposort;
+    use crate::graph::traversal::Dfs;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_components() {
+        let graph: DiGraph<(), ()> = Graph::from_edges(
+            8,
+            vec![
+                (0, 1),
+                (1, 2),
+                (2, 0),
+                (2, 3),
+                (3, 4),
+                (4, 5),
+                (5, 3),
+                (6, 5),
+                (6, 7),
+                (7, 6),
+            ],
+        );
+        let components = tarjan_scc(&graph);
+        assert_eq!(components.count(), 3);
+        assert_eq!(
+            components.groups(),
+            vec![vec![3, 4, 5], vec![0, 1, 2], vec![6, 7]]
+        );
+        let condensed = condensation(&graph, &components);
+        assert_eq!(condensed.node(1), Some(&vec![0, 1, 2]));
+        let edges: Vec<_> = condensed.edges().map(|e| (e.source, e.target)).collect();
+        assert_eq!(edges, vec![(1, 0), (2, 0)]);
+        let empty = tarjan_scc(&DiGraph::<(), ()>::new());
+        assert_eq!(empty.count(), 0);
+    }
+
+    #[test]
+    fn test_against_reachability() {
+        let mut rng = Rng::new(56);
+        for _ in 0..40 {
+            let n = 1 + rng.index(25);
+            let edges: Vec<(usize, usize)> = (0..rng.index(3 * n))
+                .map(|_| (rng.index(n), rng.index(n)))
+                .collect();
+            let graph: DiGraph<(), ()> = Graph::from_edges(n, edges);
+            // transitive closure by repeated dfs.
+            let reach: Vec<Vec<bool>> = graph
+                .node_ids()
+                .map(|s| {
+                    let mut seen = vec![false; n];
+                    let mut dfs = Dfs::new(&graph, s);
+                    dfs.iter(&graph).for_each(|v| seen[v] = true);
+                    seen
+                })
+                .collect();
+            let components = tarjan_scc(&graph);
+            for (a, from_a) in reach.iter().enumerate() {
+                for (b, &a_to_b) in from_a.iter().enumerate() {
+                    let same = a_to_b && reach[b][a];
+                    assert_eq!(components.of(a) == components.of(b), same);
+                }
+            }
+            let condensed = condensation(&graph, &components);
+            assert!(condensed.edges().all(|e| e.source > e.target));
+            assert!(toposort(&condensed).is_ok());
+        }
+    }
+}
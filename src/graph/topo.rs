@@ -0,0 +1,178 @@
+use std::collections::VecDeque;
+
+use super::{DiGraph, NodeId};
+use crate::{PriorityQueue, PriorityQueueImpl};
+
+/// order the nodes so that every edge goes forward, with kahn's algorithm
+/// in O(V + E): nodes are taken in the order they become free.
+///
+/// if there is none, returns the nodes of one cycle, in edge order.
+pub fn toposort<N, E>(graph: &DiGraph<N, E>) -> Result<Vec<NodeId>, Vec<NodeId>> {
+    kahn(graph, VecDeque::new())
+}
+
+/// the lexicographically smallest topological order, taking the smallest
+/// free node each time, in O(V log V + E).
+pub fn toposort_smallest<N, E>(graph: &DiGraph<N, E>) -> Result<Vec<NodeId>, Vec<NodeId>> {
+    kahn(graph, PriorityQueueImpl::new())
+}
+
+// the nodes whose predecessors have all been ordered.
+trait Free {
+    fn push(&mut self, node: NodeId);
+    fn take(&mut self) -> Option<NodeId>;
+}
+
+impl Free for VecDeque<NodeId> {
+    fn push(&mut self, node: NodeId) {
+        self.push_back(node);
+    }
+
+    fn take(&mut self) -> Option<NodeId> {
+        self.pop_front()
+    }
+}
+
+impl Free for PriorityQueueImpl<NodeId> {
+    fn push(&mut self, node: NodeId) {
+        self.insert(node, u64::MAX - node as u64);
+    }
+
+    fn take(&mut self) -> Option<NodeId> {
+        self.pop()
+    }
+}
+
+fn kahn<N, E, F: Free>(graph: &DiGraph<N, E>, mut free: F) -> Result<Vec<NodeId>, Vec<NodeId>> {
+    let mut remaining: Vec<usize> = graph.node_ids().map(|n| graph.in_degree(n)).collect();
+    for node in graph.node_ids() {
+        if remaining[node] == 0 {
+            free.push(node);
+        }
+    }
+    let mut order = Vec::with_capacity(graph.node_count());
+    while let Some(node) = free.take() {
+        order.push(node);
+        for next in graph.neighbors(node) {
+            remaining[next] -= 1;
+            if remaining[next] == 0 {
+                free.push(next);
+            }
+        }
+    }
+    if order.len() == graph.node_count() {
+        Ok(order)
+    } else {
+        Err(find_cycle(graph, &remaining))
+    }
+}
+
+// every node kahn's algorithm left behind has a predecessor left behind too,
+// so walking predecessors from one of them must close a cycle.
+fn find_cycle<N, E>(graph: &DiGraph<N, E>, remaining: &[usize]) -> Vec<NodeId> {
+    let mut position = vec![usize::MAX; graph.node_count()];
+    let mut walk = Vec::new();
+    let mut node = graph.node_ids().find(|&n| remaining[n] > 0).unwrap();
+    while position[node] == usize::MAX {
+        position[node] = walk.len();
+        walk.push(node);
+        node = graph
+            .edges_to(node)
+            .map(|e| e.source)
+            .find(|&p| remaining[p] > 0)
+            .unwrap();
+    }
+    let mut cycle = walk.split_off(position[node]);
+    cycle.reverse();
+    // start the cycle at its smallest node, for a stable answer.
+    let smallest = (0..cycle.len()).min_by_key(|&i| cycle[i]).unwrap();
+    cycle.rotate_left(smallest);
+    cycle
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::test_util::permutations;
+    use crate::graph::Graph;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_orders() {
+        let graph: DiGraph<(), ()> =
+            Graph::from_edges(6, vec![(5, 2), (5, 0), (4, 0), (4, 1), (2, 3), (3, 1)]);
+        assert_eq!(toposort(&graph), Ok(vec![4, 5, 2, 0, 3, 1]));
+        assert_eq!(toposort_smallest(&graph), Ok(vec![4, 5, 0, 2, 3, 1]));
+        let cyclic: DiGraph<(), ()> =
+            Graph::from_edges(6, vec![(0, 1), (1, 2), (2, 3), (3, 4), (4, 2), (4, 5)]);
+        assert_eq!(toposort(&cyclic), Err(vec![2, 3, 4]));
+        assert_eq!(toposort_smallest(&cyclic), Err(vec![2, 3, 4]));
+        let looped: DiGraph<(), ()> = Graph::from_edges(2, vec![(0, 1), (1, 1)]);
+        assert_eq!(toposort(&looped), Err(vec![1]));
+        assert_eq!(toposort(&DiGraph::<(), ()>::new()), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_random() {
+        let mut rng = Rng::new(55);
+        for _ in 0..50 {
+            let n = 1 + rng.index(30);
+            let mut graph: DiGraph<(), ()> = Graph::new();
+            (0..n).for_each(|_| {
+                graph.add_node(());
+            });
+            for _ in 0..rng.index(2 * n) {
+                graph.add_edge(rng.index(n), rng.index(n), ());
+            }
+            match toposort_smallest(&graph) {
+                Ok(order) => {
+                    let mut position = vec![0; n];
+                    for (i, &node) in order.iter().enumerate() {
+                        position[node] = i;
+                    }
+                    assert!(graph
+                        .edges()
+                        .all(|e| position[e.source] < position[e.target]));
+                    assert!(toposort(&graph).is_ok());
+                }
+                Err(cycle) => {
+                    for (i, &node) in cycle.iter().enumerate() {
+                        assert!(graph.contains_edge(node, cycle[(i + 1) % cycle.len()]));
+                    }
+                    assert!(toposort(&graph).is_err());
+                }
+            }
+        }
+    }
+
+    // every ordering of `0..n`, in lexicographic order.
+    #[test]
+    fn test_smallest_against_brute_force() {
+        let mut rng = Rng::new(117);
+        for _ in 0..200 {
+            let n = 1 + rng.index(7);
+            // edges that go forward in a random order, so there is no cycle.
+            let mut rank: Vec<usize> = (0..n).collect();
+            rng.shuffle(&mut rank);
+            let mut edges = Vec::new();
+            for _ in 0..rng.index(2 * n) {
+                let (a, b) = (rng.index(n), rng.index(n));
+                if rank[a] < rank[b] {
+                    edges.push((a, b));
+                }
+            }
+            let graph: DiGraph<(), ()> = Graph::from_edges(n, edges.clone());
+            let smallest = permutations(n)
+                .into_iter()
+                .find(|order| {
+                    let mut position = vec![0; n];
+                    for (i, &node) in order.iter().enumerate() {
+                        position[node] = i;
+                    }
+                    edges.iter().all(|&(a, b)| position[a] < position[b])
+                })
+                .unwrap();
+            assert_eq!(toposort_smallest(&graph), Ok(smallest));
+        }
+    }
+}
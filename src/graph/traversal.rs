@@ -0,0 +1,229 @@
+use std::collections::VecDeque;
+use std::iter;
+
+use super::{Graph, NodeId};
+
+/// breadth-first search, yielding nodes in the order they are discovered.
+///
+/// the search borrows the graph only while stepping, and keeps track of the
+/// nodes it has discovered: calling [`Bfs::start`] again continues from
+/// another node without revisiting any, e.g. to walk every component.
+#[derive(Clone, Debug)]
+pub struct Bfs {
+    queue: VecDeque<NodeId>,
+    discovered: Vec<bool>,
+}
+
+impl Bfs {
+    /// a search from `start`.
+    pub fn new<N, E, Ty>(graph: &Graph<N, E, Ty>, start: NodeId) -> Self {
+        let mut bfs = Bfs::empty(graph.nodes.len());
+        bfs.start(start);
+        bfs
+    }
+
+    /// a search over `node_count` nodes with nothing to visit yet.
+    pub fn empty(node_count: usize) -> Self {
+        Bfs {
+            queue: VecDeque::new(),
+            discovered: vec![false; node_count],
+        }
+    }
+
+    /// queue `node` if it was not discovered yet, returning whether it was
+    /// queued.
+    pub fn start(&mut self, node: NodeId) -> bool {
+        if self.discovered[node] {
+            return false;
+        }
+        self.discovered[node] = true;
+        self.queue.push_back(node);
+        true
+    }
+
+    pub fn is_discovered(&self, node: NodeId) -> bool {
+        self.discovered[node]
+    }
+
+    /// forget every discovered node.
+    pub fn reset(&mut self) {
+        self.queue.clear();
+        self.discovered.iter_mut().for_each(|d| *d = false);
+    }
+
+    pub fn next<N, E, Ty>(&mut self, graph: &Graph<N, E, Ty>) -> Option<NodeId> {
+        let node = self.queue.pop_front()?;
+        for &(next, _) in &graph.outgoing[node] {
+            if !self.discovered[next] {
+                self.discovered[next] = true;
+                self.queue.push_back(next);
+            }
+        }
+        Some(node)
+    }
+
+    pub fn iter<'a, N, E, Ty>(
+        &'a mut self,
+        graph: &'a Graph<N, E, Ty>,
+    ) -> impl Iterator<Item = NodeId> + 'a {
+        iter::from_fn(move || self.next(graph))
+    }
+}
+
+/// a step of a depth-first search: a node is discovered when the search
+/// enters it and finished once all of its descendants are.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DfsEvent {
+    Discover(NodeId),
+    Finish(NodeId),
+}
+
+/// depth-first search, yielding nodes in preorder, or as discover and
+/// finish events, in the same order as the recursive search.
+///
+/// like [`Bfs`] it can be restarted from further nodes, keeping the
+/// discovered ones.
+#[derive(Clone, Debug)]
+pub struct Dfs {
+    // nodes entered and not yet finished, with the index of the next edge.
+    stack: Vec<(NodeId, usize)>,
+    starts: VecDeque<NodeId>,
+    discovered: Vec<bool>,
+}
+
+impl Dfs {
+    /// a search from `start`.
+    pub fn new<N, E, Ty>(graph: &Graph<N, E, Ty>, start: NodeId) -> Self {
+        let mut dfs = Dfs::empty(graph.nodes.len());
+        dfs.start(start);
+        dfs
+    }
+
+    /// a search over `node_count` nodes with nothing to visit yet.
+    pub fn empty(node_count: usize) -> Self {
+        Dfs {
+            stack: Vec::new(),
+            starts: VecDeque::new(),
+            discovered: vec![false; node_count],
+        }
+    }
+
+    /// search from `node` once the current search is done, unless it has
+    /// been discovered by then; returns false if it already is.
+    pub fn start(&mut self, node: NodeId) -> bool {
+        assert!(node < self.discovered.len(), "node {} out of bounds", node);
+        if self.discovered[node] {
+            return false;
+        }
+        self.starts.push_back(node);
+        true
+    }
+
+    pub fn is_discovered(&self, node: NodeId) -> bool {
+        self.discovered[node]
+    }
+
+    /// forget every discovered node.
+    pub fn reset(&mut self) {
+        self.stack.clear();
+        self.starts.clear();
+        self.discovered.iter_mut().for_each(|d| *d = false);
+    }
+
+    pub fn next_event<N, E, Ty>(&mut self, graph: &Graph<N, E, Ty>) -> Option<DfsEvent> {
+        loop {
+            if let Some((node, edge)) = self.stack.last_mut() {
+                let node = *node;
+                match graph.outgoing[node].get(*edge) {
+                    Some(&(next, _)) => {
+                        *edge += 1;
+                        if !self.discovered[next] {
+                            self.discovered[next] = true;
+                            self.stack.push((next, 0));
+                            return Some(DfsEvent::Discover(next));
+                        }
+                    }
+                    None => {
+                        self.stack.pop();
+                        return Some(DfsEvent::Finish(node));
+                    }
+                }
+            } else {
+                let start = self.starts.pop_front()?;
+                if !self.discovered[start] {
+                    self.discovered[start] = true;
+                    self.stack.push((start, 0));
+                    return Some(DfsEvent::Discover(start));
+                }
+            }
+        }
+    }
+
+    /// the next node in preorder.
+    pub fn next<N, E, Ty>(&mut self, graph: &Graph<N, E, Ty>) -> Option<NodeId> {
+        loop {
+            if let DfsEvent::Discover(node) = self.next_event(graph)? {
+                return Some(node);
+            }
+        }
+    }
+
+    pub fn iter<'a, N, E, Ty>(
+        &'a mut self,
+        graph: &'a Graph<N, E, Ty>,
+    ) -> impl Iterator<Item = NodeId> + 'a {
+        iter::from_fn(move || self.next(graph))
+    }
+
+    pub fn events<'a, N, E, Ty>(
+        &'a mut self,
+        graph: &'a Graph<N, E, Ty>,
+    ) -> impl Iterator<Item = DfsEvent> + 'a {
+        iter::from_fn(move || self.next_event(graph))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{DiGraph, UnGraph};
+
+    #[test]
+    fn test_bfs() {
+        let graph: UnGraph<(), ()> =
+            Graph::from_edges(7, vec![(0, 1), (0, 2), (1, 3), (2, 3), (3, 4), (5, 6)]);
+        let mut bfs = Bfs::new(&graph, 0);
+        assert_eq!(bfs.iter(&graph).collect::<Vec<_>>(), vec![0, 1, 2, 3, 4]);
+        // reuse the discovered set to split the rest into components.
+        let mut components = 1;
+        for node in graph.node_ids() {
+            if bfs.start(node) {
+                components += 1;
+                while bfs.next(&graph).is_some() {}
+            }
+        }
+        assert_eq!(components, 2);
+        bfs.reset();
+        assert!(!bfs.is_discovered(4));
+        bfs.start(3);
+        // stop as soon as the target shows up.
+        assert_eq!(bfs.iter(&graph).position(|n| n == 0), Some(4));
+    }
+
+    #[test]
+    fn test_dfs_events() {
+        let graph: DiGraph<(), ()> =
+            Graph::from_edges(6, vec![(0, 1), (1, 2), (0, 3), (3, 2), (4, 5)]);
+        let mut dfs = Dfs::new(&graph, 0);
+        let events: Vec<DfsEvent> = dfs.events(&graph).collect();
+        use DfsEvent::{Discover as D, Finish as F};
+        assert_eq!(events, vec![D(0), D(1), D(2), F(2), F(1), D(3), F(3), F(0)]);
+        assert!(!dfs.start(2));
+        assert!(dfs.start(5) && dfs.start(4));
+        assert_eq!(dfs.iter(&graph).collect::<Vec<_>>(), vec![5, 4]);
+        dfs.reset();
+        dfs.start(4);
+        dfs.start(0);
+        assert_eq!(dfs.iter(&graph).collect::<Vec<_>>(), vec![4, 5, 0, 1, 2, 3]);
+    }
+}
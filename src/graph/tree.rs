@@ -0,0 +1,139 @@
+use std::collections::VecDeque;
+
+use super::{NodeId, UnGraph};
+
+/// a rooted forest, with each node's parent, children and depth.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RootedTree {
+    parent: Vec<Option<NodeId>>,
+    children: Vec<Vec<NodeId>>,
+    depth: Vec<usize>,
+    // breadth-first, so every node comes after its parent.
+    order: Vec<NodeId>,
+}
+
+impl RootedTree {
+    /// root the undirected tree `tree` at `root`. panics if it has a cycle
+    /// or more than one component.
+    pub fn new<N, E>(tree: &UnGraph<N, E>, root: NodeId) -> Self {
+        let n = tree.node_count();
+        assert!(root < n, "node {} out of bounds", root);
+        let mut parent = vec![None; n];
+        // the edge each node was reached by, not to be walked back.
+        let mut via = vec![usize::MAX; n];
+        let mut seen = vec![false; n];
+        seen[root] = true;
+        let mut queue = VecDeque::new();
+        queue.push_back(root);
+        while let Some(node) = queue.pop_front() {
+            for edge in tree.edges_from(node) {
+                if edge.id == via[node] {
+                    continue;
+                }
+                let to = edge.target;
+                assert!(!seen[to], "not a tree: cycle through {}", to);
+                seen[to] = true;
+                parent[to] = Some(node);
+                via[to] = edge.id;
+                queue.push_back(to);
+            }
+        }
+        assert!(seen.iter().all(|&s| s), "not a tree: disconnected");
+        RootedTree::from_parents(parent)
+    }
+
+    /// the forest where each node hangs from `parents[node]`, or is a root
+    /// for `None`. panics if the parents form a cycle.
+    pub fn from_parents(parent: Vec<Option<NodeId>>) -> Self {
+        let n = parent.len();
+        let mut children = vec![Vec::new(); n];
+        let mut order = Vec::with_capacity(n);
+        for (node, &p) in parent.iter().enumerate() {
+            match p {
+                Some(p) => {
+                    assert!(p < n, "node {} out of bounds", p);
+                    children[p].push(node);
+                }
+                None => order.push(node),
+            }
+        }
+        let mut depth = vec![0; n];
+        let mut i = 0;
+        while i < order.len() {
+            let node = order[i];
+            for &child in &children[node] {
+                depth[child] = depth[node] + 1;
+                order.push(child);
+            }
+            i += 1;
+        }
+        assert!(order.len() == n, "parents form a cycle");
+        RootedTree {
+            parent,
+            children,
+            depth,
+            order,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    pub fn parent(&self, node: NodeId) -> Option<NodeId> {
+        self.parent[node]
+    }
+
+    pub fn children(&self, node: NodeId) -> &[NodeId] {
+        &self.children[node]
+    }
+
+    /// the number of edges from `node` up to its root.
+    pub fn depth(&self, node: NodeId) -> usize {
+        self.depth[node]
+    }
+
+    pub fn roots(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.order
+            .iter()
+            .copied()
+            .take_while(move |&v| self.parent[v].is_none())
+    }
+
+    /// every node in breadth-first order, so parents come before their
+    /// children; walk it backwards to see children first.
+    pub fn order(&self) -> &[NodeId] {
+        &self.order
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Graph;
+
+    #[test]
+    fn test_rooting() {
+        let graph: UnGraph<(), ()> = Graph::from_edges(5, vec![(0, 1), (1, 2), (1, 3), (4, 0)]);
+        let tree = RootedTree::new(&graph, 1);
+        assert_eq!(tree.parent(1), None);
+        assert_eq!(tree.children(1), &[0, 2, 3]);
+        assert_eq!(tree.parent(4), Some(0));
+        assert_eq!(tree.depth(4), 2);
+        assert_eq!(tree.order(), &[1, 0, 2, 3, 4]);
+        let forest = RootedTree::from_parents(vec![None, Some(0), None, Some(2), Some(1)]);
+        assert_eq!(forest.roots().collect::<Vec<_>>(), vec![0, 2]);
+        assert_eq!(forest.depth(4), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "not a tree")]
+    fn test_cycle() {
+        let graph: UnGraph<(), ()> = Graph::from_edges(3, vec![(0, 1), (1, 2), (2, 1)]);
+        RootedTree::new(&graph, 0);
+    }
+}
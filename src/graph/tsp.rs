@@ -0,0 +1,293 @@
+use super::{AdjacencyMatrix, NodeId, Weight};
+
+/// a closed tour visiting every node once, starting from `order[0]` and
+/// returning to it after the last.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tour<W> {
+    pub order: Vec<NodeId>,
+    pub length: W,
+}
+
+/// the length of the closed tour through `order`, if every leg has an edge.
+pub fn tour_length<W: Weight>(matrix: &AdjacencyMatrix<W>, order: &[NodeId]) -> Option<W> {
+    let mut length = W::zero();
+    for (i, &node) in order.iter().enumerate().skip(1) {
+        length = length + matrix.get(order[i - 1], node)?;
+    }
+    if order.len() > 1 {
+        length = length + matrix.get(order[order.len() - 1], order[0])?;
+    }
+    Some(length)
+}
+
+/// an optimal tour from node 0 by held-karp dynamic programming over the
+/// subsets of the other nodes, in O(2^n * n^2) time and O(2^n * n) space,
+/// so only for about 20 nodes at most. missing entries are missing edges;
+/// returns `None` if no tour exists.
+pub fn held_karp<W: Weight>(matrix: &AdjacencyMatrix<W>) -> Option<Tour<W>> {
+    let n = matrix.len();
+    assert!(n <= 24, "held-karp on {} nodes", n);
+    if n <= 1 {
+        return Some(Tour {
+            order: (0..n).collect(),
+            length: W::zero(),
+        });
+    }
+    // nodes 1..n are bits 0..n-1; best[mask * m + j] is the shortest path
+    // from 0 through exactly `mask`, ending at node j + 1.
+    let m = n - 1;
+    let mut best: Vec<Option<W>> = vec![None; (1 << m) * m];
+    let mut parent = vec![usize::MAX; (1 << m) * m];
+    for j in 0..m {
+        best[(1 << j) * m + j] = matrix.get(0, j + 1);
+    }
+    for mask in 1usize..1 << m {
+        for j in (0..m).filter(|&j| mask >> j & 1 == 1) {
+            let here = match best[mask * m + j] {
+                Some(d) => d,
+                None => continue,
+            };
+            for k in (0..m).filter(|&k| mask >> k & 1 == 0) {
+                let leg = match matrix.get(j + 1, k + 1) {
+                    Some(w) => w,
+                    None => continue,
+                };
+                let cell = (mask | 1 << k) * m + k;
+                if best[cell].is_none_or(|old| here + leg < old) {
+                    best[cell] = Some(here + leg);
+                    parent[cell] = j;
+                }
+            }
+        }
+    }
+    let full = (1 << m) - 1;
+    let mut end: Option<(usize, W)> = None;
+    for j in 0..m {
+        if let (Some(d), Some(back)) = (best[full * m + j], matrix.get(j + 1, 0)) {
+            if end.is_none_or(|(_, old)| d + back < old) {
+                end = Some((j, d + back));
+            }
+        }
+    }
+    let (mut j, length) = end?;
+    let mut order = Vec::with_capacity(n);
+    let mut mask = full;
+    while j != usize::MAX {
+        order.push(j + 1);
+        let previous = parent[mask * m + j];
+        mask &= !(1 << j);
+        j = previous;
+    }
+    order.push(0);
+    order.reverse();
+    Some(Tour { order, length })
+}
+
+/// the nearest-neighbor tour from `start`: always move to the closest
+/// unvisited node. O(n^2); `None` if it gets stuck on a missing edge.
+pub fn nearest_neighbor<W: Weight>(matrix: &AdjacencyMatrix<W>, start: NodeId) -> Option<Tour<W>> {
+    let n = matrix.len();
+    assert!(start < n, "node {} out of bounds", start);
+    let mut visited = vec![false; n];
+    visited[start] = true;
+    let mut order = vec![start];
+    let mut node = start;
+    for _ in 1..n {
+        let mut nearest: Option<(NodeId, W)> = None;
+        for next in (0..n).filter(|&v| !visited[v]) {
+            if let Some(w) = matrix.get(node, next) {
+                if nearest.is_none_or(|(_, best)| w < best) {
+                    nearest = Some((next, w));
+                }
+            }
+        }
+        node = nearest?.0;
+        visited[node] = true;
+        order.push(node);
+    }
+    let length = tour_length(matrix, &order)?;
+    Some(Tour { order, length })
+}
+
+/// improve `tour` by 2-opt moves until none helps: replace two legs
+/// `a -> b` and `c -> d` with `a -> c` and `b -> d`, reversing the path in
+/// between. each of the O(n^2) moves per round is costed in O(n) over the
+/// whole reversed path, so distances may be asymmetric and edges missing:
+/// only moves onto existing edges that shorten the tour are taken.
+///
+/// returns the new length, or `None`, leaving the tour alone, if it uses a
+/// missing edge to begin with.
+pub fn two_opt<W: Weight>(matrix: &AdjacencyMatrix<W>, tour: &mut Tour<W>) -> Option<W> {
+    let n = tour.order.len();
+    let order = &mut tour.order;
+    tour_length(matrix, order)?;
+    // the legs along `order[from..=to]`, forwards or backwards.
+    let path = |order: &[NodeId], from: usize, to: usize, backwards: bool| {
+        (from..to).try_fold(W::zero(), |sum, k| {
+            let (x, y) = (order[k], order[k + 1]);
+            let leg = if backwards {
+                matrix.get(y, x)
+            } else {
+                matrix.get(x, y)
+            };
+            Some(sum + leg?)
+        })
+    };
+    let mut improved = true;
+    while improved {
+        improved = false;
+        for i in 0..n.saturating_sub(2) {
+            for j in i + 2..n {
+                let (a, b) = (order[i], order[i + 1]);
+                let (c, d) = (order[j], order[(j + 1) % n]);
+                if a == d {
+                    continue;
+                }
+                let new =
+                    || Some(matrix.get(a, c)? + path(order, i + 1, j, true)? + matrix.get(b, d)?);
+                let new = match new() {
+                    Some(new) => new,
+                    None => continue,
+                };
+                // the tour is complete, so its own legs all exist.
+                let old = matrix.get(a, b)? + path(order, i + 1, j, false)? + matrix.get(c, d)?;
+                if new < old {
+                    order[i + 1..=j].reverse();
+                    improved = true;
+                }
+            }
+        }
+    }
+    tour.length = tour_length(matrix, order)?;
+    Some(tour.length)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    // every tour from node 0, by trying each order of the others.
+    fn brute_force(matrix: &AdjacencyMatrix<u32>) -> Option<u32> {
+        fn go(matrix: &AdjacencyMatrix<u32>, order: &mut Vec<NodeId>, best: &mut Option<u32>) {
+            if order.len() == matrix.len() {
+                if let Some(length) = tour_length(matrix, order) {
+                    *best = Some(best.map_or(length, |b| b.min(length)));
+                }
+                return;
+            }
+            for v in 0..matrix.len() {
+                if !order.contains(&v) {
+                    order.push(v);
+                    go(matrix, order, best);
+                    order.pop();
+                }
+            }
+        }
+        let mut best = None;
+        go(matrix, &mut vec![0], &mut best);
+        best
+    }
+
+    fn random_matrix(rng: &mut Rng, n: usize, missing: bool) -> AdjacencyMatrix<u32> {
+        let mut matrix = AdjacencyMatrix::new(n);
+        for a in 0..n {
+            for b in a + 1..n {
+                if !missing || rng.below(4) != 0 {
+                    let w = Some(rng.below(100) as u32);
+                    matrix.set(a, b, w);
+                    matrix.set(b, a, w);
+                }
+            }
+        }
+        matrix
+    }
+
+    #[test]
+    fn test_held_karp() {
+        let mut rng = Rng::new(70);
+        for _ in 0..60 {
+            let n = 1 + rng.index(7);
+            let matrix = random_matrix(&mut rng, n, true);
+            let tour = held_karp(&matrix);
+            assert_eq!(tour.as_ref().map(|t| t.length), brute_force(&matrix));
+            if let Some(tour) = tour {
+                assert_eq!(tour_length(&matrix, &tour.order), Some(tour.length));
+                let mut nodes = tour.order.clone();
+                nodes.sort_unstable();
+                assert_eq!(nodes, (0..n).collect::<Vec<_>>());
+            }
+        }
+    }
+
+    #[test]
+    fn test_heuristics() {
+        // points on a circle: the optimal tour goes around it.
+        let n = 12;
+        let mut matrix = AdjacencyMatrix::new(n);
+        let point = |i: usize| {
+            let angle = (i * 5 % n) as f64 * std::f64::consts::TAU / n as f64;
+            (angle.cos(), angle.sin())
+        };
+        for a in 0..n {
+            for b in 0..n {
+                let ((x0, y0), (x1, y1)) = (point(a), point(b));
+                matrix.set(a, b, Some(((x0 - x1).powi(2) + (y0 - y1).powi(2)).sqrt()));
+            }
+        }
+        let optimal = held_karp(&matrix).unwrap().length;
+        let mut tour = nearest_neighbor(&matrix, 3).unwrap();
+        assert_eq!(tour.order[0], 3);
+        assert_eq!(two_opt(&matrix, &mut tour), Some(tour.length));
+        assert!((tour.length - optimal).abs() < 1e-9);
+        let mut rng = Rng::new(71);
+        for _ in 0..30 {
+            let n = 2 + rng.index(7);
+            let matrix = random_matrix(&mut rng, n, false);
+            let mut tour = nearest_neighbor(&matrix, rng.index(n)).unwrap();
+            let greedy = tour.length;
+            assert_eq!(two_opt(&matrix, &mut tour), Some(tour.length));
+            assert!(tour.length <= greedy);
+            assert!(tour.length >= held_karp(&matrix).unwrap().length);
+            assert_eq!(tour_length(&matrix, &tour.order), Some(tour.length));
+        }
+    }
+
+    #[test]
+    fn test_two_opt_missing_edges() {
+        // asymmetric weights with edges missing: moves stay on real edges.
+        let mut rng = Rng::new(119);
+        let mut checked = 0;
+        for _ in 0..200 {
+            let n = 3 + rng.index(6);
+            let mut matrix = AdjacencyMatrix::new(n);
+            for a in 0..n {
+                for b in (0..n).filter(|&b| b != a) {
+                    if rng.below(5) != 0 {
+                        matrix.set(a, b, Some(rng.below(100) as u32));
+                    }
+                }
+            }
+            let mut tour = match nearest_neighbor(&matrix, 0) {
+                Some(tour) => tour,
+                None => continue,
+            };
+            let greedy = tour.length;
+            let length = two_opt(&matrix, &mut tour).unwrap();
+            assert_eq!(tour_length(&matrix, &tour.order), Some(length));
+            assert!(length <= greedy && length >= brute_force(&matrix).unwrap());
+            checked += 1;
+        }
+        assert!(checked > 20);
+        // a tour over a missing edge is left alone.
+        let mut matrix = AdjacencyMatrix::new(3);
+        matrix.set(0, 1, Some(1));
+        matrix.set(1, 2, Some(1));
+        let mut tour = Tour {
+            order: vec![0, 1, 2],
+            length: 0,
+        };
+        assert_eq!(two_opt(&matrix, &mut tour), None);
+        assert_eq!(tour.order, vec![0, 1, 2]);
+    }
+}
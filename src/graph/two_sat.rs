@@ -0,0 +1,184 @@
+use std::ops::Not;
+
+use super::scc::tarjan_scc;
+use super::{DiGraph, NodeId};
+
+/// a variable or its negation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Literal {
+    variable: usize,
+    negated: bool,
+}
+
+impl Literal {
+    /// "`variable` is true".
+    pub fn positive(variable: usize) -> Self {
+        Literal {
+            variable,
+            negated: false,
+        }
+    }
+
+    /// "`variable` is false".
+    pub fn negative(variable: usize) -> Self {
+        Literal {
+            variable,
+            negated: true,
+        }
+    }
+
+    pub fn variable(self) -> usize {
+        self.variable
+    }
+
+    pub fn is_negated(self) -> bool {
+        self.negated
+    }
+
+    // literals are the nodes `2 * variable` and `2 * variable + 1`.
+    fn node(self) -> NodeId {
+        2 * self.variable + self.negated as usize
+    }
+}
+
+impl Not for Literal {
+    type Output = Literal;
+
+    fn not(self) -> Literal {
+        Literal {
+            variable: self.variable,
+            negated: !self.negated,
+        }
+    }
+}
+
+/// a 2-sat instance: a conjunction of clauses of two literals each,
+/// solved in linear time through the strongly connected components of its
+/// implication graph.
+#[derive(Clone, Debug, Default)]
+pub struct TwoSat {
+    implications: DiGraph<(), ()>,
+}
+
+impl TwoSat {
+    /// an instance over `variables` variables and no clauses.
+    pub fn new(variables: usize) -> Self {
+        let mut sat = TwoSat::default();
+        for _ in 0..variables {
+            sat.add_variable();
+        }
+        sat
+    }
+
+    pub fn variable_count(&self) -> usize {
+        self.implications.node_count() / 2
+    }
+
+    pub fn add_variable(&mut self) -> usize {
+        self.implications.add_node(());
+        self.implications.add_node(());
+        self.variable_count() - 1
+    }
+
+    /// require `a || b`, that is `!a -> b` and `!b -> a`.
+    pub fn add_clause(&mut self, a: Literal, b: Literal) {
+        let n = self.variable_count();
+        assert!(a.variable < n && b.variable < n, "variable out of bounds");
+        self.implications.add_edge((!a).node(), b.node(), ());
+        self.implications.add_edge((!b).node(), a.node(), ());
+    }
+
+    /// require `a -> b`.
+    pub fn add_implication(&mut self, a: Literal, b: Literal) {
+        self.add_clause(!a, b);
+    }
+
+    /// require `a` to hold.
+    pub fn require(&mut self, a: Literal) {
+        self.add_clause(a, a);
+    }
+
+    /// a satisfying assignment of every variable, or `None` if there is
+    /// none, which happens exactly when some `x` and `!x` imply each other.
+    ///
+    /// the components come numbered in reverse topological order, so `x`
+    /// is set when its component comes after that of `!x` topologically: a
+    /// true literal then never implies a false one.
+    pub fn solve(&self) -> Option<Vec<bool>> {
+        let components = tarjan_scc(&self.implications);
+        (0..self.variable_count())
+            .map(|v| {
+                let (yes, no) = (Literal::positive(v).node(), Literal::negative(v).node());
+                let (yes, no) = (components.of(yes), components.of(no));
+                if yes == no {
+                    None
+                } else {
+                    Some(yes < no)
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    fn holds(literal: Literal, assignment: &[bool]) -> bool {
+        assignment[literal.variable()] != literal.is_negated()
+    }
+
+    #[test]
+    fn test_small() {
+        let (x, y, z) = (
+            Literal::positive(0),
+            Literal::positive(1),
+            Literal::positive(2),
+        );
+        let mut sat = TwoSat::new(3);
+        sat.add_clause(x, y);
+        sat.add_implication(x, z);
+        sat.add_clause(!z, !y);
+        sat.require(x);
+        assert_eq!(sat.solve(), Some(vec![true, false, true]));
+        sat.add_clause(!x, y);
+        assert_eq!(sat.solve(), None);
+        assert_eq!(TwoSat::new(0).solve(), Some(vec![]));
+    }
+
+    #[test]
+    fn test_against_brute_force() {
+        let mut rng = Rng::new(80);
+        for _ in 0..200 {
+            let n = 1 + rng.index(6);
+            let mut literal = || {
+                let v = rng.index(n);
+                if rng.chance(0.5) {
+                    Literal::positive(v)
+                } else {
+                    Literal::negative(v)
+                }
+            };
+            let clauses: Vec<(Literal, Literal)> =
+                (0..1 + n * 2).map(|_| (literal(), literal())).collect();
+            let mut sat = TwoSat::new(n);
+            for &(a, b) in &clauses {
+                sat.add_clause(a, b);
+            }
+            let satisfies = |assignment: &[bool]| {
+                clauses
+                    .iter()
+                    .all(|&(a, b)| holds(a, assignment) || holds(b, assignment))
+            };
+            let exists = (0..1u32 << n).any(|mask| {
+                let assignment: Vec<bool> = (0..n).map(|v| mask >> v & 1 == 1).collect();
+                satisfies(&assignment)
+            });
+            match sat.solve() {
+                Some(assignment) => assert!(satisfies(&assignment)),
+                None => assert!(!exists),
+            }
+        }
+    }
+}
@@ -0,0 +1,439 @@
+use std::fmt;
+use std::hash::Hash;
+use std::iter::FromIterator;
+use std::rc::Rc;
+
+use crate::hash::hash64;
+
+const BITS: u32 = 5;
+const MASK: u64 = (1 << BITS) - 1;
+
+struct Leaf<K, V> {
+    hash: u64,
+    key: K,
+    value: V,
+}
+
+enum Entry<K, V> {
+    Leaf(Leaf<K, V>),
+    Node(Rc<Node<K, V>>),
+}
+
+enum Node<K, V> {
+    // one bit per occupied slot of 32, with the entries in slot order.
+    Branch {
+        bitmap: u32,
+        entries: Vec<Entry<K, V>>,
+    },
+    // keys whose full hashes are equal.
+    Collision {
+        hash: u64,
+        leaves: Vec<Leaf<K, V>>,
+    },
+}
+
+impl<K: Clone, V: Clone> Clone for Leaf<K, V> {
+    fn clone(&self) -> Self {
+        Leaf {
+            hash: self.hash,
+            key: self.key.clone(),
+            value: self.value.clone(),
+        }
+    }
+}
+
+impl<K: Clone, V: Clone> Clone for Entry<K, V> {
+    fn clone(&self) -> Self {
+        match self {
+            Entry::Leaf(leaf) => Entry::Leaf(leaf.clone()),
+            Entry::Node(node) => Entry::Node(node.clone()),
+        }
+    }
+}
+
+fn slot(hash: u64, shift: u32) -> u32 {
+    ((hash >> shift) & MASK) as u32
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> Node<K, V> {
+    fn empty() -> Self {
+        Node::Branch {
+            bitmap: 0,
+            entries: Vec::new(),
+        }
+    }
+
+    fn get(&self, shift: u32, hash: u64, key: &K) -> Option<&V> {
+        match self {
+            Node::Branch { bitmap, entries } => {
+                let bit = 1 << slot(hash, shift);
+                if bitmap & bit == 0 {
+                    return None;
+                }
+                match &entries[(bitmap & (bit - 1)).count_ones() as usize] {
+                    Entry::Leaf(leaf) if leaf.key == *key => Some(&leaf.value),
+                    Entry::Leaf(_) => None,
+                    Entry::Node(node) => node.get(shift + BITS, hash, key),
+                }
+            }
+            Node::Collision { leaves, .. } => leaves
+                .iter()
+                .find(|leaf| leaf.key == *key)
+                .map(|leaf| &leaf.value),
+        }
+    }
+
+    // a copy with `leaf` inserted, and whether it was a new key.
+    fn insert(&self, shift: u32, leaf: Leaf<K, V>) -> (Node<K, V>, bool) {
+        match self {
+            Node::Branch { bitmap, entries } => {
+                let bit = 1 << slot(leaf.hash, shift);
+                let i = (bitmap & (bit - 1)).count_ones() as usize;
+                let mut entries = entries.clone();
+                if bitmap & bit == 0 {
+                    entries.insert(i, Entry::Leaf(leaf));
+                    let node = Node::Branch {
+                        bitmap: bitmap | bit,
+                        entries,
+                    };
+                    return (node, true);
+                }
+                let (entry, added) = match &entries[i] {
+                    Entry::Leaf(old) if old.key == leaf.key => (Entry::Leaf(leaf), false),
+                    Entry::Leaf(old) => {
+                        let node = pair(shift + BITS, Entry::Leaf(old.clone()), old.hash, leaf);
+                        (Entry::Node(Rc::new(node)), true)
+                    }
+                    Entry::Node(node) => {
+                        let (node, added) = node.insert(shift + BITS, leaf);
+                        (Entry::Node(Rc::new(node)), added)
+                    }
+                };
+                entries[i] = entry;
+                (
+                    Node::Branch {
+                        bitmap: *bitmap,
+                        entries,
+                    },
+                    added,
+                )
+            }
+            Node::Collision { hash, leaves } if *hash == leaf.hash => {
+                let mut leaves = leaves.clone();
+                let added = match leaves.iter_mut().find(|old| old.key == leaf.key) {
+                    Some(old) => {
+                        *old = leaf;
+                        false
+                    }
+                    None => {
+                        leaves.push(leaf);
+                        true
+                    }
+                };
+                (
+                    Node::Collision {
+                        hash: *hash,
+                        leaves,
+                    },
+                    added,
+                )
+            }
+            Node::Collision { hash, .. } => {
+                // a different hash with the same prefix: split off below.
+                let this = Entry::Node(Rc::new(self.clone()));
+                (pair(shift, this, *hash, leaf), true)
+            }
+        }
+    }
+
+    // a copy without `key`: None if it is absent, Some(None) if nothing is
+    // left, and otherwise the entry to put in the parent's slot.
+    fn remove(&self, shift: u32, hash: u64, key: &K) -> Option<Option<Entry<K, V>>> {
+        match self {
+            Node::Branch { bitmap, entries } => {
+                let bit = 1 << slot(hash, shift);
+                if bitmap & bit == 0 {
+                    return None;
+                }
+                let i = (bitmap & (bit - 1)).count_ones() as usize;
+                let replacement = match &entries[i] {
+                    Entry::Leaf(leaf) if leaf.key == *key => None,
+                    Entry::Leaf(_) => return None,
+                    Entry::Node(node) => node.remove(shift + BITS, hash, key)?,
+                };
+                let mut entries = entries.clone();
+                let mut bitmap = *bitmap;
+                match replacement {
+                    Some(entry) => entries[i] = entry,
+                    None => {
+                        entries.remove(i);
+                        bitmap &= !bit;
+                    }
+                }
+                Some(match entries.len() {
+                    0 => None,
+                    // a lone leaf moves up to the parent's slot.
+                    1 if matches!(entries[0], Entry::Leaf(_)) => entries.pop(),
+                    _ => Some(Entry::Node(Rc::new(Node::Branch { bitmap, entries }))),
+                })
+            }
+            Node::Collision { hash, leaves } => {
+                let i = leaves.iter().position(|leaf| leaf.key == *key)?;
+                let mut leaves = leaves.clone();
+                leaves.remove(i);
+                Some(if leaves.len() == 1 {
+                    leaves.pop().map(Entry::Leaf)
+                } else {
+                    Some(Entry::Node(Rc::new(Node::Collision {
+                        hash: *hash,
+                        leaves,
+                    })))
+                })
+            }
+        }
+    }
+}
+
+impl<K: Clone, V: Clone> Clone for Node<K, V> {
+    fn clone(&self) -> Self {
+        match self {
+            Node::Branch { bitmap, entries } => Node::Branch {
+                bitmap: *bitmap,
+                entries: entries.clone(),
+            },
+            Node::Collision { hash, leaves } => Node::Collision {
+                hash: *hash,
+                leaves: leaves.clone(),
+            },
+        }
+    }
+}
+
+// a node at `shift` holding `a`, whose hash is `a_hash`, and `b`.
+fn pair<K, V>(shift: u32, a: Entry<K, V>, a_hash: u64, b: Leaf<K, V>) -> Node<K, V> {
+    if a_hash == b.hash {
+        if let Entry::Leaf(a) = a {
+            return Node::Collision {
+                hash: a_hash,
+                leaves: vec![a, b],
+            };
+        }
+    }
+    let (sa, sb) = (slot(a_hash, shift), slot(b.hash, shift));
+    if sa == sb {
+        let child = pair(shift + BITS, a, a_hash, b);
+        return Node::Branch {
+            bitmap: 1 << sa,
+            entries: vec![Entry::Node(Rc::new(child))],
+        };
+    }
+    let entries = if sa < sb {
+        vec![a, Entry::Leaf(b)]
+    } else {
+        vec![Entry::Leaf(b), a]
+    };
+    Node::Branch {
+        bitmap: (1 << sa) | (1 << sb),
+        entries,
+    }
+}
+
+/// immutable hash map whose versions share structure (a hash array mapped
+/// trie): `insert` and `remove` return a new map, copying only the
+/// O(log n) nodes on the path to the key, and `clone` is O(1).
+///
+/// each level of the trie consumes five bits of the key's hash and stores
+/// only its occupied slots; keys with equal hashes share a collision node.
+pub struct PersistentMap<K, V> {
+    root: Rc<Node<K, V>>,
+    len: usize,
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> PersistentMap<K, V> {
+    pub fn new() -> Self {
+        PersistentMap {
+            root: Rc::new(Node::empty()),
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.root.get(0, hash64(key, 0), key)
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// a new map with `key` set to `value`.
+    pub fn insert(&self, key: K, value: V) -> Self {
+        let leaf = Leaf {
+            hash: hash64(&key, 0),
+            key,
+            value,
+        };
+        let (root, added) = self.root.insert(0, leaf);
+        PersistentMap {
+            root: Rc::new(root),
+            len: self.len + added as usize,
+        }
+    }
+
+    /// a new map without `key`.
+    pub fn remove(&self, key: &K) -> Self {
+        let hash = hash64(key, 0);
+        let root = match self.root.remove(0, hash, key) {
+            None => return self.clone(),
+            Some(None) => Node::empty(),
+            Some(Some(Entry::Node(node))) => (*node).clone(),
+            Some(Some(Entry::Leaf(leaf))) => Node::Branch {
+                bitmap: 1 << slot(leaf.hash, 0),
+                entries: vec![Entry::Leaf(leaf)],
+            },
+        };
+        PersistentMap {
+            root: Rc::new(root),
+            len: self.len - 1,
+        }
+    }
+
+    /// iterate over the entries in an unspecified order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            stack: vec![(&*self.root, 0)],
+        }
+    }
+}
+
+impl<K, V> Clone for PersistentMap<K, V> {
+    fn clone(&self) -> Self {
+        PersistentMap {
+            root: self.root.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> Default for PersistentMap<K, V> {
+    fn default() -> Self {
+        PersistentMap::new()
+    }
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> FromIterator<(K, V)> for PersistentMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        iter.into_iter()
+            .fold(PersistentMap::new(), |map, (k, v)| map.insert(k, v))
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for PersistentMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(Iter {
+                stack: vec![(&*self.root, 0)],
+            })
+            .finish()
+    }
+}
+
+/// iterator over the entries of a [`PersistentMap`].
+pub struct Iter<'a, K, V> {
+    // nodes being visited, with the position of the next entry.
+    stack: Vec<(&'a Node<K, V>, usize)>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        while let Some((node, i)) = self.stack.pop() {
+            match node {
+                Node::Branch { entries, .. } if i < entries.len() => {
+                    self.stack.push((node, i + 1));
+                    match &entries[i] {
+                        Entry::Leaf(leaf) => return Some((&leaf.key, &leaf.value)),
+                        Entry::Node(child) => self.stack.push((child, 0)),
+                    }
+                }
+                Node::Collision { leaves, .. } if i < leaves.len() => {
+                    self.stack.push((node, i + 1));
+                    return Some((&leaves[i].key, &leaves[i].value));
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+    use std::collections::HashMap;
+    use std::hash::Hasher;
+
+    // keys that only hash their value modulo 8, to force collisions.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    struct Colliding(u32);
+
+    impl Hash for Colliding {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            (self.0 % 8).hash(state);
+        }
+    }
+
+    #[test]
+    fn test_versions() {
+        let mut rng = Rng::new(40);
+        let mut versions = vec![(PersistentMap::new(), HashMap::new())];
+        for _ in 0..5000 {
+            let (map, mut model) = versions[rng.index(versions.len())].clone();
+            let key = rng.below(500);
+            let map = if rng.chance(0.3) {
+                model.remove(&key);
+                map.remove(&key)
+            } else {
+                model.insert(key, rng.below(100));
+                map.insert(key, model[&key])
+            };
+            versions.push((map, model));
+        }
+        for (map, model) in &versions {
+            assert_eq!(map.len(), model.len());
+            assert!((0..500).all(|k| map.get(&k) == model.get(&k)));
+            let mut entries: Vec<_> = map.iter().map(|(&k, &v)| (k, v)).collect();
+            let mut expected: Vec<_> = model.iter().map(|(&k, &v)| (k, v)).collect();
+            entries.sort_unstable();
+            expected.sort_unstable();
+            assert_eq!(entries, expected);
+        }
+    }
+
+    #[test]
+    fn test_collisions() {
+        let full: PersistentMap<Colliding, u32> = (0..64).map(|i| (Colliding(i), i)).collect();
+        assert_eq!(full.len(), 64);
+        assert!((0..64).all(|i| full.get(&Colliding(i)) == Some(&i)));
+        let mut map = full.insert(Colliding(3), 300);
+        assert_eq!(map.len(), 64);
+        assert_eq!(map.get(&Colliding(3)), Some(&300));
+        for i in 0..64 {
+            map = map.remove(&Colliding(i));
+            assert_eq!(map.get(&Colliding(i)), None);
+            assert!((i + 1..64).all(|j| map.contains_key(&Colliding(j))));
+        }
+        assert!(map.is_empty() && map.iter().next().is_none());
+        assert_eq!(full.get(&Colliding(3)), Some(&3));
+        assert_eq!(full.remove(&Colliding(100)).len(), 64);
+    }
+}
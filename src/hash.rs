@@ -0,0 +1,87 @@
+use std::hash::{Hash, Hasher};
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+/// 64-bit fnv-1a hasher.
+///
+/// unlike `DefaultHasher` its output is fixed, so hashes can be persisted or
+/// compared between processes. pair it with [`mix64`] (as [`hash64`] does)
+/// when the low bits must be well distributed.
+#[derive(Clone, Debug)]
+pub struct Fnv1a {
+    state: u64,
+}
+
+impl Fnv1a {
+    pub fn new() -> Self {
+        Fnv1a { state: FNV_OFFSET }
+    }
+
+    /// a hasher whose output differs from the unseeded one for every input.
+    pub fn with_seed(seed: u64) -> Self {
+        let mut hasher = Fnv1a::new();
+        hasher.write_u64(seed);
+        hasher
+    }
+}
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Fnv1a::new()
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.state ^= u64::from(b);
+            self.state = self.state.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.state
+    }
+}
+
+/// splitmix64 finalizer: a bijection on `u64` with good avalanche.
+pub fn mix64(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// stable, well-mixed 64-bit hash of `value` under `seed`.
+pub fn hash64<T: Hash + ?Sized>(value: &T, seed: u64) -> u64 {
+    let mut hasher = Fnv1a::with_seed(seed);
+    value.hash(&mut hasher);
+    mix64(hasher.finish())
+}
+
+/// two hashes of `value` for double hashing, the second one odd so that
+/// `h1 + i * h2` visits distinct values modulo any power of two.
+pub fn hash_pair<T: Hash + ?Sized>(value: &T) -> (u64, u64) {
+    let mut hasher = Fnv1a::new();
+    value.hash(&mut hasher);
+    let h = hasher.finish();
+    (mix64(h), mix64(h ^ 0x9e37_79b9_7f4a_7c15) | 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stable_values() {
+        // reference values of 64-bit fnv-1a.
+        let mut hasher = Fnv1a::new();
+        hasher.write(b"");
+        assert_eq!(hasher.finish(), 0xcbf2_9ce4_8422_2325);
+        hasher.write(b"a");
+        assert_eq!(hasher.finish(), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(hash64("key", 1), hash64("key", 1));
+        assert_ne!(hash64("key", 1), hash64("key", 2));
+        assert_eq!(hash_pair(&42u32).1 % 2, 1);
+    }
+}
@@ -0,0 +1,220 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+use crate::hash::hash64;
+
+#[derive(Clone, Debug)]
+enum Registers {
+    // only the non-zero registers, while there are few of them.
+    Sparse(BTreeMap<u32, u8>),
+    Dense(Vec<u8>),
+}
+
+/// hyperloglog distinct-count estimator with `2^precision` registers; the
+/// standard error of the estimate is `1.04 / sqrt(2^precision)`.
+///
+/// a fresh estimator stores only its non-zero registers and switches to a
+/// plain register array once that would take less memory, so many small
+/// estimators stay cheap.
+pub struct HyperLogLog<T: ?Sized> {
+    precision: u8,
+    registers: Registers,
+    marker: PhantomData<fn(&T)>,
+}
+
+impl<T: Hash + ?Sized> HyperLogLog<T> {
+    /// create an empty estimator; `precision` must lie in `4..=18`.
+    pub fn new(precision: u8) -> Self {
+        assert!(
+            (4..=18).contains(&precision),
+            "precision must lie in 4..=18"
+        );
+        HyperLogLog {
+            precision,
+            registers: Registers::Sparse(BTreeMap::new()),
+            marker: PhantomData,
+        }
+    }
+
+    pub fn precision(&self) -> u8 {
+        self.precision
+    }
+
+    /// number of registers, `2^precision`.
+    pub fn num_registers(&self) -> usize {
+        1 << self.precision
+    }
+
+    /// standard error of the estimate relative to the true count.
+    pub fn relative_error(&self) -> f64 {
+        1.04 / (self.num_registers() as f64).sqrt()
+    }
+
+    pub fn is_sparse(&self) -> bool {
+        matches!(self.registers, Registers::Sparse(_))
+    }
+
+    pub fn insert(&mut self, item: &T) {
+        let p = u32::from(self.precision);
+        let h = hash64(item, 0);
+        let index = (h >> (64 - p)) as u32;
+        // position of the first one bit among the remaining 64 - p bits.
+        let rank = ((h << p).leading_zeros().min(64 - p) + 1) as u8;
+        self.update(index, rank);
+    }
+
+    fn update(&mut self, index: u32, rank: u8) {
+        match &mut self.registers {
+            Registers::Sparse(map) => {
+                let r = map.entry(index).or_insert(0);
+                *r = (*r).max(rank);
+                // a sparse entry costs several bytes, a dense register one.
+                if map.len() * 8 > self.num_registers() {
+                    self.densify();
+                }
+            }
+            Registers::Dense(regs) => {
+                let r = &mut regs[index as usize];
+                *r = (*r).max(rank);
+            }
+        }
+    }
+
+    fn densify(&mut self) {
+        if let Registers::Sparse(map) = &self.registers {
+            let mut regs = vec![0; self.num_registers()];
+            for (&i, &r) in map {
+                regs[i as usize] = r;
+            }
+            self.registers = Registers::Dense(regs);
+        }
+    }
+
+    /// estimated number of distinct items inserted.
+    pub fn estimate(&self) -> f64 {
+        let m = self.num_registers() as f64;
+        let (sum, zeros) = match &self.registers {
+            Registers::Sparse(map) => {
+                let zeros = self.num_registers() - map.len();
+                let sum: f64 = map.values().map(|&r| 2f64.powi(-i32::from(r))).sum();
+                (sum + zeros as f64, zeros)
+            }
+            Registers::Dense(regs) => {
+                let sum = regs.iter().map(|&r| 2f64.powi(-i32::from(r))).sum();
+                (sum, regs.iter().filter(|&&r| r == 0).count())
+            }
+        };
+        let alpha = match self.num_registers() {
+            16 => 0.673,
+            32 => 0.697,
+            64 => 0.709,
+            _ => 0.7213 / (1.0 + 1.079 / m),
+        };
+        let raw = alpha * m * m / sum;
+        if raw <= 2.5 * m && zeros > 0 {
+            // small-range correction: linear counting on the empty registers.
+            m * (m / zeros as f64).ln()
+        } else {
+            raw
+        }
+    }
+
+    /// fold in the items of `other`, which must have the same precision.
+    pub fn merge(&mut self, other: &HyperLogLog<T>) {
+        assert_eq!(
+            self.precision, other.precision,
+            "estimators have different precisions"
+        );
+        match &other.registers {
+            Registers::Sparse(map) => {
+                for (&i, &r) in map {
+                    self.update(i, r);
+                }
+            }
+            Registers::Dense(other_regs) => {
+                self.densify();
+                if let Registers::Dense(regs) = &mut self.registers {
+                    for (r, &o) in regs.iter_mut().zip(other_regs) {
+                        *r = (*r).max(o);
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.registers = Registers::Sparse(BTreeMap::new());
+    }
+}
+
+impl<T: ?Sized> Clone for HyperLogLog<T> {
+    fn clone(&self) -> Self {
+        HyperLogLog {
+            precision: self.precision,
+            registers: self.registers.clone(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for HyperLogLog<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HyperLogLog")
+            .field("precision", &self.precision)
+            .field("sparse", &matches!(self.registers, Registers::Sparse(_)))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn relative(estimate: f64, truth: usize) -> f64 {
+        (estimate - truth as f64).abs() / truth as f64
+    }
+
+    #[test]
+    fn test_estimates() {
+        let mut hll = HyperLogLog::new(12);
+        assert_eq!(hll.estimate(), 0.0);
+        for i in 0..100u64 {
+            hll.insert(&i);
+            hll.insert(&i);
+        }
+        assert!(hll.is_sparse());
+        // linear counting is nearly exact at small cardinalities.
+        assert!(relative(hll.estimate(), 100) < 0.02);
+        for i in 100..200_000u64 {
+            hll.insert(&i);
+        }
+        assert!(!hll.is_sparse());
+        assert!(relative(hll.estimate(), 200_000) < 3.0 * hll.relative_error());
+        hll.clear();
+        assert_eq!(hll.estimate(), 0.0);
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut a = HyperLogLog::new(10);
+        let mut b = HyperLogLog::new(10);
+        let mut both = HyperLogLog::new(10);
+        for i in 0..30_000u32 {
+            a.insert(&i);
+            both.insert(&i);
+        }
+        for i in 20_000..50_000u32 {
+            b.insert(&i);
+            both.insert(&i);
+        }
+        let mut small = HyperLogLog::new(10);
+        small.insert(&20_000u32);
+        small.merge(&b);
+        a.merge(&b);
+        assert_eq!(a.estimate(), both.estimate());
+        assert!(relative(a.estimate(), 50_000) < 3.0 * a.relative_error());
+        assert_eq!(small.estimate(), b.estimate());
+    }
+}
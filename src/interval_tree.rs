@@ -0,0 +1,398 @@
+use std::cmp::{max, Ordering};
+use std::fmt;
+use std::ops::Range;
+
+type Link<T, V> = Option<Box<Node<T, V>>>;
+
+struct Node<T, V> {
+    interval: Range<T>,
+    value: V,
+    height: u32,
+    // largest end in the subtree.
+    max_end: T,
+    left: Link<T, V>,
+    right: Link<T, V>,
+}
+
+impl<T: Ord + Clone, V> Node<T, V> {
+    fn update(&mut self) {
+        self.height = 1 + max(height(&self.left), height(&self.right));
+        let mut max_end = self.interval.end.clone();
+        for child in self.left.iter().chain(&self.right) {
+            if child.max_end > max_end {
+                max_end = child.max_end.clone();
+            }
+        }
+        self.max_end = max_end;
+    }
+
+    fn balance_factor(&self) -> i64 {
+        i64::from(height(&self.left)) - i64::from(height(&self.right))
+    }
+}
+
+fn height<T, V>(link: &Link<T, V>) -> u32 {
+    link.as_ref().map_or(0, |node| node.height)
+}
+
+// intervals are ordered by start, then end.
+fn cmp<T: Ord>(a: &Range<T>, b: &Range<T>) -> Ordering {
+    a.start.cmp(&b.start).then_with(|| a.end.cmp(&b.end))
+}
+
+fn rotate_right<T: Ord + Clone, V>(mut node: Box<Node<T, V>>) -> Box<Node<T, V>> {
+    let mut left = node.left.take().expect("rotation needs a left child");
+    node.left = left.right.take();
+    node.update();
+    left.right = Some(node);
+    left.update();
+    left
+}
+
+fn rotate_left<T: Ord + Clone, V>(mut node: Box<Node<T, V>>) -> Box<Node<T, V>> {
+    let mut right = node.right.take().expect("rotation needs a right child");
+    node.right = right.left.take();
+    node.update();
+    right.left = Some(node);
+    right.update();
+    right
+}
+
+// restore the avl invariant at `node`, whose children are balanced and differ in height by at most 2.
+fn rebalance<T: Ord + Clone, V>(mut node: Box<Node<T, V>>) -> Box<Node<T, V>> {
+    node.update();
+    let factor = node.balance_factor();
+    if factor > 1 {
+        let left = node.left.take().unwrap();
+        node.left = Some(if left.balance_factor() < 0 {
+            rotate_left(left)
+        } else {
+            left
+        });
+        rotate_right(node)
+    } else if factor < -1 {
+        let right = node.right.take().unwrap();
+        node.right = Some(if right.balance_factor() > 0 {
+            rotate_right(right)
+        } else {
+            right
+        });
+        rotate_left(node)
+    } else {
+        node
+    }
+}
+
+fn insert<T: Ord + Clone, V>(
+    link: Link<T, V>,
+    interval: Range<T>,
+    value: V,
+) -> (Box<Node<T, V>>, Option<V>) {
+    match link {
+        None => (
+            Box::new(Node {
+                max_end: interval.end.clone(),
+                interval,
+                value,
+                height: 1,
+                left: None,
+                right: None,
+            }),
+            None,
+        ),
+        Some(mut node) => match cmp(&interval, &node.interval) {
+            Ordering::Equal => {
+                let old = std::mem::replace(&mut node.value, value);
+                (node, Some(old))
+            }
+            Ordering::Less => {
+                let (left, old) = insert(node.left.take(), interval, value);
+                node.left = Some(left);
+                (rebalance(node), old)
+            }
+            Ordering::Greater => {
+                let (right, old) = insert(node.right.take(), interval, value);
+                node.right = Some(right);
+                (rebalance(node), old)
+            }
+        },
+    }
+}
+
+// detach the smallest node, returning the rest of the tree and that node.
+fn take_min<T: Ord + Clone, V>(mut node: Box<Node<T, V>>) -> (Link<T, V>, Box<Node<T, V>>) {
+    match node.left.take() {
+        None => (node.right.take(), node),
+        Some(left) => {
+            let (rest, min) = take_min(left);
+            node.left = rest;
+            (Some(rebalance(node)), min)
+        }
+    }
+}
+
+fn remove<T: Ord + Clone, V>(link: Link<T, V>, interval: &Range<T>) -> (Link<T, V>, Option<V>) {
+    match link {
+        None => (None, None),
+        Some(mut node) => match cmp(interval, &node.interval) {
+            Ordering::Less => {
+                let (left, old) = remove(node.left.take(), interval);
+                node.left = left;
+                (Some(rebalance(node)), old)
+            }
+            Ordering::Greater => {
+                let (right, old) = remove(node.right.take(), interval);
+                node.right = right;
+                (Some(rebalance(node)), old)
+            }
+            Ordering::Equal => {
+                let (left, right) = (node.left.take(), node.right.take());
+                let replacement = match right {
+                    None => left,
+                    Some(right) => {
+                        let (rest, mut min) = take_min(right);
+                        min.left = left;
+                        min.right = rest;
+                        Some(rebalance(min))
+                    }
+                };
+                (replacement, Some(node.value))
+            }
+        },
+    }
+}
+
+// push the intervals in `link` overlapping `query`, in order. subtrees
+// whose ends all lie at or before `query.start`, or whose starts lie at or
+// after `query.end`, are skipped.
+fn collect<'a, T: Ord, V>(
+    link: &'a Link<T, V>,
+    query: &Range<T>,
+    out: &mut Vec<(&'a Range<T>, &'a V)>,
+) {
+    let node = match link {
+        Some(node) if node.max_end > query.start => node,
+        _ => return,
+    };
+    collect(&node.left, query, out);
+    if node.interval.start < query.end {
+        if node.interval.end > query.start {
+            out.push((&node.interval, &node.value));
+        }
+        collect(&node.right, query, out);
+    }
+}
+
+/// a map from half-open intervals `start..end` to values, as an avl tree
+/// ordered by start and augmented with the largest end in each subtree.
+///
+/// finding the `k` intervals that contain a point or overlap a range takes
+/// O(log n) per interval found, O(min(n, (k + 1) log n)) in all, and
+/// checking for any overlap O(log n). intervals must be non-empty, and
+/// since they are half-open, `1..3` and `3..5` do not overlap.
+pub struct IntervalTree<T, V> {
+    root: Link<T, V>,
+    len: usize,
+}
+
+impl<T: Ord + Clone, V> IntervalTree<T, V> {
+    pub fn new() -> Self {
+        IntervalTree { root: None, len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// insert `interval` with `value`, returning the previous value stored
+    /// for exactly this interval.
+    pub fn insert(&mut self, interval: Range<T>, value: V) -> Option<V> {
+        assert!(interval.start < interval.end, "interval must not be empty");
+        let (root, old) = insert(self.root.take(), interval, value);
+        self.root = Some(root);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    /// remove exactly `interval`, returning its value.
+    pub fn remove(&mut self, interval: &Range<T>) -> Option<V> {
+        let (root, old) = remove(self.root.take(), interval);
+        self.root = root;
+        if old.is_some() {
+            self.len -= 1;
+        }
+        old
+    }
+
+    pub fn get(&self, interval: &Range<T>) -> Option<&V> {
+        let mut link = &self.root;
+        while let Some(node) = link {
+            match cmp(interval, &node.interval) {
+                Ordering::Less => link = &node.left,
+                Ordering::Equal => return Some(&node.value),
+                Ordering::Greater => link = &node.right,
+            }
+        }
+        None
+    }
+
+    /// the intervals overlapping `query`, ordered by start.
+    pub fn overlapping(&self, query: &Range<T>) -> Vec<(&Range<T>, &V)> {
+        let mut out = Vec::new();
+        if query.start < query.end {
+            collect(&self.root, query, &mut out);
+        }
+        out
+    }
+
+    /// check whether any interval overlaps `query`, e.g. whether a new
+    /// booking conflicts with an existing one.
+    pub fn overlaps(&self, query: &Range<T>) -> bool {
+        if query.start >= query.end {
+            return false;
+        }
+        let mut link = &self.root;
+        while let Some(node) = link {
+            if node.interval.start < query.end && query.start < node.interval.end {
+                return true;
+            }
+            // if the left subtree reaches past `query.start` it holds an
+            // overlap whenever the right one could.
+            link = match &node.left {
+                Some(left) if left.max_end > query.start => &node.left,
+                _ => &node.right,
+            };
+        }
+        false
+    }
+
+    /// the intervals containing `point` (stabbing query), ordered by start.
+    pub fn stab(&self, point: &T) -> Vec<(&Range<T>, &V)> {
+        let mut out = Vec::new();
+        stab(&self.root, point, &mut out);
+        out
+    }
+
+    /// iterate over the intervals ordered by start, then end.
+    pub fn iter(&self) -> impl Iterator<Item = (&Range<T>, &V)> {
+        let mut stack = Vec::new();
+        let mut link = &self.root;
+        std::iter::from_fn(move || {
+            while let Some(node) = link {
+                stack.push(node);
+                link = &node.left;
+            }
+            let node = stack.pop()?;
+            link = &node.right;
+            Some((&node.interval, &node.value))
+        })
+    }
+}
+
+fn stab<'a, T: Ord, V>(link: &'a Link<T, V>, point: &T, out: &mut Vec<(&'a Range<T>, &'a V)>) {
+    let node = match link {
+        Some(node) if node.max_end > *point => node,
+        _ => return,
+    };
+    stab(&node.left, point, out);
+    if node.interval.start <= *point {
+        if *point < node.interval.end {
+            out.push((&node.interval, &node.value));
+        }
+        stab(&node.right, point, out);
+    }
+}
+
+impl<T: Ord + Clone, V> Default for IntervalTree<T, V> {
+    fn default() -> Self {
+        IntervalTree::new()
+    }
+}
+
+impl<T: fmt::Debug, V: fmt::Debug> fmt::Debug for IntervalTree<T, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn entries<'a, T, V>(link: &'a Link<T, V>, out: &mut Vec<(&'a Range<T>, &'a V)>) {
+            if let Some(node) = link {
+                entries(&node.left, out);
+                out.push((&node.interval, &node.value));
+                entries(&node.right, out);
+            }
+        }
+        let mut out = Vec::new();
+        entries(&self.root, &mut out);
+        f.debug_map().entries(out).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_calendar() {
+        let mut calendar = IntervalTree::new();
+        calendar.insert(9..10, "standup");
+        calendar.insert(13..15, "review");
+        calendar.insert(14..16, "interview");
+        assert!(!calendar.overlaps(&(10..13)));
+        assert!(calendar.overlaps(&(12..14)));
+        let at_14: Vec<_> = calendar.stab(&14).into_iter().map(|(_, v)| *v).collect();
+        assert_eq!(at_14, vec!["review", "interview"]);
+        assert_eq!(calendar.stab(&10), vec![]);
+        assert_eq!(calendar.insert(9..10, "sync"), Some("standup"));
+        assert_eq!(calendar.remove(&(13..15)), Some("review"));
+        assert_eq!(calendar.remove(&(13..15)), None);
+        assert_eq!(calendar.len(), 2);
+        let all: Vec<_> = calendar.iter().map(|(r, _)| r.clone()).collect();
+        assert_eq!(all, vec![9..10, 14..16]);
+        assert!(!calendar.overlaps(&(15..15)));
+    }
+
+    #[test]
+    fn test_against_brute_force() {
+        let mut rng = Rng::new(41);
+        let mut tree = IntervalTree::new();
+        let mut model: Vec<Range<i64>> = Vec::new();
+        for _ in 0..3000 {
+            let start = rng.range(0..1000);
+            let interval = start..start + rng.range(1..60);
+            if rng.chance(0.3) {
+                let i = rng.index(model.len().max(1));
+                if let Some(old) = model.get(i).cloned() {
+                    assert_eq!(tree.remove(&old), Some(()));
+                    model.swap_remove(i);
+                }
+            } else if tree.insert(interval.clone(), ()).is_none() {
+                model.push(interval);
+            }
+            let query = {
+                let s = rng.range(0..1000);
+                s..s + rng.range(0..40)
+            };
+            let mut expected: Vec<_> = model
+                .iter()
+                .filter(|r| r.start < query.end && query.start < r.end && query.start < query.end)
+                .cloned()
+                .collect();
+            expected.sort_by(cmp);
+            let found: Vec<_> = tree
+                .overlapping(&query)
+                .into_iter()
+                .map(|(r, _)| r.clone())
+                .collect();
+            assert_eq!(found, expected);
+            assert_eq!(tree.overlaps(&query), !expected.is_empty());
+            let point = rng.range(0..1000);
+            let stabbed = tree.stab(&point).len();
+            assert_eq!(stabbed, model.iter().filter(|r| r.contains(&point)).count());
+        }
+        assert_eq!(tree.len(), model.len());
+    }
+}
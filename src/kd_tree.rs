@@ -0,0 +1,274 @@
+use std::fmt;
+use std::iter::FromIterator;
+
+use crate::{PriorityQueue, PriorityQueueImpl};
+
+/// squared euclidean distance.
+fn dist2<const D: usize>(a: &[f64; D], b: &[f64; D]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum()
+}
+
+/// static k-d tree over points in `D` dimensions, built in O(n log n).
+///
+/// the points are stored in one array laid out as the tree: the median of
+/// each range along the splitting axis sits at the middle of the range, with
+/// its two halves on either side. the axis cycles with the depth.
+pub struct KdTree<V, const D: usize> {
+    points: Vec<([f64; D], V)>,
+}
+
+impl<V, const D: usize> KdTree<V, D> {
+    /// build a tree over `points`; coordinates must not be nan.
+    pub fn build(mut points: Vec<([f64; D], V)>) -> Self {
+        assert!(D > 0, "points need at least one dimension");
+        assert!(
+            points.iter().all(|(p, _)| p.iter().all(|x| !x.is_nan())),
+            "coordinates must not be nan"
+        );
+        arrange(&mut points, 0);
+        KdTree { points }
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// the point closest to `query`, with its value and distance.
+    pub fn nearest(&self, query: &[f64; D]) -> Option<(&[f64; D], &V, f64)> {
+        let mut best = None;
+        self.nearest_in(0, self.points.len(), 0, query, &mut best);
+        best.map(|(i, d2)| {
+            let (point, value) = &self.points[i];
+            (point, value, f64::sqrt(d2))
+        })
+    }
+
+    fn nearest_in(
+        &self,
+        lo: usize,
+        hi: usize,
+        axis: usize,
+        query: &[f64; D],
+        best: &mut Option<(usize, f64)>,
+    ) {
+        if lo >= hi {
+            return;
+        }
+        let mid = (lo + hi) / 2;
+        let d2 = dist2(query, &self.points[mid].0);
+        if best.is_none_or(|(_, b)| d2 < b) {
+            *best = Some((mid, d2));
+        }
+        let diff = query[axis] - self.points[mid].0[axis];
+        let next = (axis + 1) % D;
+        let (near, far) = if diff < 0.0 {
+            ((lo, mid), (mid + 1, hi))
+        } else {
+            ((mid + 1, hi), (lo, mid))
+        };
+        self.nearest_in(near.0, near.1, next, query, best);
+        if best.is_none_or(|(_, b)| diff * diff < b) {
+            self.nearest_in(far.0, far.1, next, query, best);
+        }
+    }
+
+    /// the `k` points closest to `query`, nearest first.
+    pub fn k_nearest(&self, query: &[f64; D], k: usize) -> Vec<(&[f64; D], &V, f64)> {
+        // a max-queue on distance holding the best `k` found so far.
+        let mut heap = PriorityQueueImpl::new();
+        if k > 0 {
+            self.k_nearest_in(0, self.points.len(), 0, query, k, &mut heap);
+        }
+        let mut result = Vec::with_capacity(heap.len());
+        while let Some(i) = heap.pop() {
+            let (point, value) = &self.points[i];
+            result.push((point, value, dist2(query, point).sqrt()));
+        }
+        result.reverse();
+        result
+    }
+
+    fn k_nearest_in(
+        &self,
+        lo: usize,
+        hi: usize,
+        axis: usize,
+        query: &[f64; D],
+        k: usize,
+        heap: &mut PriorityQueueImpl<usize>,
+    ) {
+        if lo >= hi {
+            return;
+        }
+        // distance of the k-th best so far, if there are k.
+        let bound = |heap: &PriorityQueueImpl<usize>| {
+            heap.peek()
+                .filter(|_| heap.len() == k)
+                .map(|&i| dist2(query, &self.points[i].0))
+        };
+        let mid = (lo + hi) / 2;
+        let d2 = dist2(query, &self.points[mid].0);
+        if bound(heap).is_none_or(|b| d2 < b) {
+            // squared distances are non-negative, so their bits sort like them.
+            heap.insert(mid, d2.to_bits());
+            if heap.len() > k {
+                heap.pop();
+            }
+        }
+        let diff = query[axis] - self.points[mid].0[axis];
+        let next = (axis + 1) % D;
+        let (near, far) = if diff < 0.0 {
+            ((lo, mid), (mid + 1, hi))
+        } else {
+            ((mid + 1, hi), (lo, mid))
+        };
+        self.k_nearest_in(near.0, near.1, next, query, k, heap);
+        if bound(heap).is_none_or(|b| diff * diff < b) {
+            self.k_nearest_in(far.0, far.1, next, query, k, heap);
+        }
+    }
+
+    /// the points inside the box from `min` to `max`, bounds included.
+    pub fn within(&self, min: &[f64; D], max: &[f64; D]) -> Vec<(&[f64; D], &V)> {
+        let mut out = Vec::new();
+        self.within_in(0, self.points.len(), 0, min, max, &mut out);
+        out
+    }
+
+    fn within_in<'a>(
+        &'a self,
+        lo: usize,
+        hi: usize,
+        axis: usize,
+        min: &[f64; D],
+        max: &[f64; D],
+        out: &mut Vec<(&'a [f64; D], &'a V)>,
+    ) {
+        if lo >= hi {
+            return;
+        }
+        let mid = (lo + hi) / 2;
+        let (point, value) = &self.points[mid];
+        if (0..D).all(|d| min[d] <= point[d] && point[d] <= max[d]) {
+            out.push((point, value));
+        }
+        let next = (axis + 1) % D;
+        if min[axis] <= point[axis] {
+            self.within_in(lo, mid, next, min, max, out);
+        }
+        if point[axis] <= max[axis] {
+            self.within_in(mid + 1, hi, next, min, max, out);
+        }
+    }
+
+    /// iterate over the points in storage order.
+    pub fn iter(&self) -> impl Iterator<Item = (&[f64; D], &V)> {
+        self.points.iter().map(|(p, v)| (p, v))
+    }
+}
+
+// lay out `points` as a tree whose root splits along `axis`.
+fn arrange<V, const D: usize>(points: &mut [([f64; D], V)], axis: usize) {
+    if points.len() <= 1 {
+        return;
+    }
+    let mid = points.len() / 2;
+    points.select_nth_unstable_by(mid, |a, b| a.0[axis].partial_cmp(&b.0[axis]).unwrap());
+    let (left, right) = points.split_at_mut(mid);
+    let next = (axis + 1) % D;
+    arrange(left, next);
+    arrange(&mut right[1..], next);
+}
+
+impl<V, const D: usize> FromIterator<([f64; D], V)> for KdTree<V, D> {
+    fn from_iter<I: IntoIterator<Item = ([f64; D], V)>>(iter: I) -> Self {
+        KdTree::build(iter.into_iter().collect())
+    }
+}
+
+impl<V, const D: usize> fmt::Debug for KdTree<V, D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KdTree")
+            .field("dimensions", &D)
+            .field("len", &self.points.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    fn random_points<const D: usize>(rng: &mut Rng, n: usize) -> Vec<([f64; D], usize)> {
+        (0..n)
+            .map(|i| {
+                let mut p = [0.0; D];
+                for x in p.iter_mut() {
+                    // a coarse grid, so that ties and equal coordinates occur.
+                    *x = rng.below(50) as f64;
+                }
+                (p, i)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_against_brute_force() {
+        let mut rng = Rng::new(42);
+        let points = random_points::<3>(&mut rng, 500);
+        let tree = KdTree::build(points.clone());
+        for _ in 0..200 {
+            let query = [
+                rng.next_f64() * 50.0,
+                rng.next_f64() * 50.0,
+                rng.next_f64() * 50.0,
+            ];
+            let mut by_distance: Vec<f64> = points
+                .iter()
+                .map(|(p, _)| dist2(&query, p).sqrt())
+                .collect();
+            by_distance.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            let (_, _, d) = tree.nearest(&query).unwrap();
+            assert_eq!(d, by_distance[0]);
+            let knn: Vec<f64> = tree.k_nearest(&query, 10).iter().map(|n| n.2).collect();
+            assert_eq!(knn, by_distance[..10].to_vec());
+            let (min, max) = ([10.0, 0.0, 20.0], [30.0, 25.0, query[2]]);
+            let mut inside: Vec<usize> = tree.within(&min, &max).iter().map(|(_, &v)| v).collect();
+            let mut expected: Vec<usize> = points
+                .iter()
+                .filter(|(p, _)| (0..3).all(|d| min[d] <= p[d] && p[d] <= max[d]))
+                .map(|&(_, v)| v)
+                .collect();
+            inside.sort_unstable();
+            expected.sort_unstable();
+            assert_eq!(inside, expected);
+        }
+    }
+
+    #[test]
+    fn test_small() {
+        let tree: KdTree<&str, 2> = vec![
+            ([0.0, 0.0], "origin"),
+            ([3.0, 4.0], "far"),
+            ([1.0, 1.0], "near"),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(tree.nearest(&[0.9, 0.8]).map(|n| *n.1), Some("near"));
+        assert_eq!(tree.nearest(&[0.0, 0.0]).unwrap().2, 0.0);
+        let all: Vec<&str> = tree
+            .k_nearest(&[0.0, 0.0], 5)
+            .iter()
+            .map(|n| *n.1)
+            .collect();
+        assert_eq!(all, vec!["origin", "near", "far"]);
+        assert!(tree.k_nearest(&[0.0, 0.0], 0).is_empty());
+        let empty = KdTree::<(), 2>::build(Vec::new());
+        assert!(empty.nearest(&[1.0, 2.0]).is_none() && empty.is_empty());
+    }
+}
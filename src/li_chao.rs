@@ -0,0 +1,426 @@
+use std::fmt;
+
+/// whether a structure answers with the smallest or the largest value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Extremum {
+    Min,
+    Max,
+}
+
+impl Extremum {
+    // both structures look for the smallest key: the value itself for
+    // minima, negated for maxima. in i128, so negating cannot overflow.
+    fn key(self, value: i128) -> i128 {
+        match self {
+            Extremum::Min => value,
+            Extremum::Max => -value,
+        }
+    }
+}
+
+/// the line `y = slope * x + intercept`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Line {
+    pub slope: i64,
+    pub intercept: i64,
+}
+
+impl Line {
+    pub fn new(slope: i64, intercept: i64) -> Self {
+        Line { slope, intercept }
+    }
+
+    pub fn eval(&self, x: i64) -> i64 {
+        self.slope * x + self.intercept
+    }
+}
+
+struct Node {
+    // none until a line reaches the node.
+    line: Option<Line>,
+    // children in the arena, 0 for none (the root is never a child).
+    left: usize,
+    right: usize,
+}
+
+/// li chao tree over the integers `lo..=hi`: insert lines (or segments)
+/// in any order and query the minimum or maximum over them at a point,
+/// both in O(log(hi - lo)).
+///
+/// nodes are created only where lines are stored, so the domain may be
+/// huge. values must fit in an `i64` over the whole domain.
+pub struct LiChaoTree {
+    lo: i64,
+    hi: i64,
+    extremum: Extremum,
+    nodes: Vec<Node>,
+    len: usize,
+}
+
+impl LiChaoTree {
+    pub fn new(lo: i64, hi: i64, extremum: Extremum) -> Self {
+        assert!(lo <= hi, "empty domain");
+        LiChaoTree {
+            lo,
+            hi,
+            extremum,
+            nodes: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// number of lines and segments inserted.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn insert(&mut self, line: Line) {
+        self.insert_segment(line, self.lo, self.hi);
+    }
+
+    /// insert `line` restricted to `from..=to`, which is clamped to the
+    /// domain.
+    pub fn insert_segment(&mut self, line: Line, from: i64, to: i64) {
+        let (from, to) = (from.max(self.lo), to.min(self.hi));
+        self.len += 1;
+        if from > to {
+            return;
+        }
+        if self.nodes.is_empty() {
+            self.nodes.push(Node {
+                line: None,
+                left: 0,
+                right: 0,
+            });
+        }
+        self.add_segment(0, self.lo, self.hi, from, to, line);
+    }
+
+    fn child(&mut self, node: usize, right: bool) -> usize {
+        let existing = if right {
+            self.nodes[node].right
+        } else {
+            self.nodes[node].left
+        };
+        if existing != 0 {
+            return existing;
+        }
+        let index = self.nodes.len();
+        self.nodes.push(Node {
+            line: None,
+            left: 0,
+            right: 0,
+        });
+        if right {
+            self.nodes[node].right = index;
+        } else {
+            self.nodes[node].left = index;
+        }
+        index
+    }
+
+    fn add_segment(&mut self, node: usize, l: i64, r: i64, from: i64, to: i64, line: Line) {
+        if to < l || r < from {
+            return;
+        }
+        let mid = midpoint(l, r);
+        if from <= l && r <= to {
+            self.add_line(node, l, r, line);
+            return;
+        }
+        let left = self.child(node, false);
+        self.add_segment(left, l, mid, from, to, line);
+        let right = self.child(node, true);
+        self.add_segment(right, mid + 1, r, from, to, line);
+    }
+
+    fn add_line(&mut self, mut node: usize, mut l: i64, mut r: i64, mut line: Line) {
+        loop {
+            let mid = midpoint(l, r);
+            let mut current = match self.nodes[node].line {
+                Some(current) => current,
+                None => {
+                    self.nodes[node].line = Some(line);
+                    return;
+                }
+            };
+            if self.key(&line, mid) < self.key(&current, mid) {
+                std::mem::swap(&mut current, &mut line);
+                self.nodes[node].line = Some(current);
+            }
+            if l == r {
+                return;
+            }
+            // the worse line can still win on one side of `mid` at most.
+            if self.key(&line, l) < self.key(&current, l) {
+                node = self.child(node, false);
+                r = mid;
+            } else if self.key(&line, r) < self.key(&current, r) {
+                node = self.child(node, true);
+                l = mid + 1;
+            } else {
+                return;
+            }
+        }
+    }
+
+    /// the minimum (or maximum) at `x` over the lines covering it.
+    pub fn query(&self, x: i64) -> Option<i64> {
+        assert!(self.lo <= x && x <= self.hi, "{} is outside the domain", x);
+        if self.nodes.is_empty() {
+            return None;
+        }
+        let (mut l, mut r) = (self.lo, self.hi);
+        let mut node = 0;
+        let mut best: Option<i128> = None;
+        loop {
+            if let Some(line) = &self.nodes[node].line {
+                let key = self.key(line, x);
+                best = Some(best.map_or(key, |best| best.min(key)));
+            }
+            let mid = midpoint(l, r);
+            let next = if x <= mid {
+                r = mid;
+                self.nodes[node].left
+            } else {
+                l = mid + 1;
+                self.nodes[node].right
+            };
+            if next == 0 {
+                break;
+            }
+            node = next;
+        }
+        best.map(|key| self.extremum.key(key) as i64)
+    }
+
+    fn key(&self, line: &Line, x: i64) -> i128 {
+        self.extremum.key(value(line, x))
+    }
+}
+
+// the middle of `l..=r` rounded down, without overflowing on huge domains.
+fn midpoint(l: i64, r: i64) -> i64 {
+    (l & r) + ((l ^ r) >> 1)
+}
+
+// values are compared in i128, so that lines far out of range cannot
+// overflow.
+fn value(line: &Line, x: i64) -> i128 {
+    i128::from(line.slope) * i128::from(x) + i128::from(line.intercept)
+}
+
+impl fmt::Debug for LiChaoTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LiChaoTree")
+            .field("domain", &(self.lo..=self.hi))
+            .field("extremum", &self.extremum)
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+/// convex hull trick for lines added in slope order: decreasing slopes for
+/// [`Extremum::Min`], increasing for [`Extremum::Max`]. adding is amortized
+/// O(1) and a query O(log n), at any x.
+///
+/// this is the classic dp speedup, e.g. `dp[i] = min_j(dp[j] + b[j] *
+/// a[i])` with sorted `b`.
+pub struct MonotoneHull {
+    extremum: Extremum,
+    // the lines on the envelope, in the order they came.
+    hull: Vec<Line>,
+}
+
+impl MonotoneHull {
+    pub fn new(extremum: Extremum) -> Self {
+        MonotoneHull {
+            extremum,
+            hull: Vec::new(),
+        }
+    }
+
+    /// number of lines on the envelope.
+    pub fn len(&self) -> usize {
+        self.hull.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hull.is_empty()
+    }
+
+    /// add a line; panics if its slope breaks the order.
+    pub fn push(&mut self, line: Line) {
+        let key = |v: i64| self.extremum.key(i128::from(v));
+        if let Some(last) = self.hull.last() {
+            assert!(
+                key(line.slope) <= key(last.slope),
+                "slopes must arrive in order"
+            );
+            if line.slope == last.slope {
+                if key(line.intercept) >= key(last.intercept) {
+                    return;
+                }
+                self.hull.pop();
+            }
+        }
+        while self.hull.len() >= 2 {
+            let (a, b) = (
+                self.hull[self.hull.len() - 2],
+                self.hull[self.hull.len() - 1],
+            );
+            if redundant(&a, &b, &line) {
+                self.hull.pop();
+            } else {
+                break;
+            }
+        }
+        self.hull.push(line);
+    }
+
+    pub fn query(&self, x: i64) -> Option<i64> {
+        if self.hull.is_empty() {
+            return None;
+        }
+        // the best line moves towards the smaller slopes as x grows.
+        let (mut lo, mut hi) = (0, self.hull.len() - 1);
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            let key = |line: &Line| self.extremum.key(value(line, x));
+            if key(&self.hull[mid]) > key(&self.hull[mid + 1]) {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        let i = lo;
+        Some(self.hull[i].eval(x))
+    }
+}
+
+// with slopes a > b > c, `b` is never strictly lowest when `a` and `c`
+// cross no later than `a` and `b` do. negating all three lines leaves the
+// test unchanged, so it serves maxima, with increasing slopes, as well.
+fn redundant(a: &Line, b: &Line, c: &Line) -> bool {
+    let (ab_slope, ac_slope) = (
+        i128::from(a.slope) - i128::from(b.slope),
+        i128::from(a.slope) - i128::from(c.slope),
+    );
+    let (ab_intercept, ac_intercept) = (
+        i128::from(b.intercept) - i128::from(a.intercept),
+        i128::from(c.intercept) - i128::from(a.intercept),
+    );
+    ac_intercept * ab_slope <= ab_intercept * ac_slope
+}
+
+impl fmt::Debug for MonotoneHull {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MonotoneHull")
+            .field("extremum", &self.extremum)
+            .field("len", &self.hull.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_against_brute_force() {
+        let mut rng = Rng::new(54);
+        for &extremum in &[Extremum::Min, Extremum::Max] {
+            let pick = |values: &mut dyn Iterator<Item = i64>| match extremum {
+                Extremum::Min => values.min(),
+                Extremum::Max => values.max(),
+            };
+            let mut tree = LiChaoTree::new(-1000, 1000, extremum);
+            let mut segments = Vec::new();
+            for _ in 0..300 {
+                let line = Line::new(rng.range(-50..50), rng.range(-10_000..10_000));
+                let from = rng.range(-1100..1000);
+                let to = from + rng.range(0..800);
+                tree.insert_segment(line, from, to);
+                segments.push((line, from, to));
+                let x = rng.range(-1000..1001);
+                let expected = pick(
+                    &mut segments
+                        .iter()
+                        .filter(|s| s.1 <= x && x <= s.2)
+                        .map(|s| s.0.eval(x)),
+                );
+                assert_eq!(tree.query(x), expected);
+            }
+            let mut lines: Vec<Line> = (0..200)
+                .map(|_| Line::new(rng.range(-40..40), rng.range(-5000..5000)))
+                .collect();
+            lines.sort_by_key(|l| match extremum {
+                Extremum::Min => -l.slope,
+                Extremum::Max => l.slope,
+            });
+            let mut hull = MonotoneHull::new(extremum);
+            let mut all = LiChaoTree::new(-1000, 1000, extremum);
+            for &line in &lines {
+                hull.push(line);
+                all.insert(line);
+                let x = rng.range(-1000..1001);
+                assert_eq!(hull.query(x), all.query(x));
+            }
+            assert!(hull.len() < lines.len());
+        }
+    }
+
+    #[test]
+    fn test_small() {
+        let mut hull = MonotoneHull::new(Extremum::Min);
+        assert_eq!(hull.query(0), None);
+        hull.push(Line::new(2, 0));
+        hull.push(Line::new(0, 3));
+        hull.push(Line::new(0, 1));
+        hull.push(Line::new(-1, 10));
+        assert_eq!(hull.len(), 3);
+        assert_eq!(hull.query(-5), Some(-10));
+        assert_eq!(hull.query(4), Some(1));
+        assert_eq!(hull.query(20), Some(-10));
+        let mut tree = LiChaoTree::new(i64::MIN / 4, i64::MAX / 4, Extremum::Max);
+        assert_eq!(tree.query(0), None);
+        tree.insert_segment(Line::new(1, 0), 0, 10);
+        assert_eq!(tree.query(5), Some(5));
+        assert_eq!(tree.query(11), None);
+        tree.insert(Line::new(0, 7));
+        assert_eq!(
+            (tree.query(5), tree.query(9), tree.len()),
+            (Some(7), Some(9), 2)
+        );
+    }
+
+    #[test]
+    fn test_extreme_values() {
+        // the whole of i64 as the domain.
+        let mut tree = LiChaoTree::new(i64::MIN, i64::MAX, Extremum::Min);
+        tree.insert_segment(Line::new(1, 0), -10, i64::MAX);
+        tree.insert(Line::new(0, i64::MAX));
+        assert_eq!(tree.query(i64::MIN), Some(i64::MAX));
+        assert_eq!(tree.query(-10), Some(-10));
+        assert_eq!(tree.query(i64::MAX), Some(i64::MAX));
+        assert_eq!(tree.query(i64::MAX - 1), Some(i64::MAX - 1));
+        // lines that negating would overflow.
+        let mut tree = LiChaoTree::new(-5, 5, Extremum::Max);
+        tree.insert(Line::new(0, i64::MIN));
+        assert_eq!(tree.query(3), Some(i64::MIN));
+        tree.insert_segment(Line::new(i64::MIN / 8, 0), 0, 5);
+        assert_eq!(tree.query(0), Some(0));
+        assert_eq!(tree.query(1), Some(i64::MIN / 8));
+        let mut hull = MonotoneHull::new(Extremum::Max);
+        hull.push(Line::new(i64::MIN, 0));
+        hull.push(Line::new(0, i64::MIN));
+        hull.push(Line::new(1, i64::MIN));
+        assert_eq!(hull.query(0), Some(0));
+        assert_eq!(hull.query(1), Some(i64::MIN + 1));
+    }
+}
@@ -1,4 +1,54 @@
 use std::collections::BTreeMap;
+use std::ops::{Bound, RangeBounds};
+
+pub mod alias;
+pub mod avl;
+pub mod b_plus_tree;
+pub mod bit_vector;
+pub mod bloom;
+pub mod cache;
+pub mod clock;
+pub mod consistent_hash;
+pub mod count_min;
+pub mod counting_bloom;
+pub mod cuckoo_filter;
+pub mod dlx;
+pub mod dsu;
+pub mod fenwick;
+pub mod graph;
+pub mod hamt;
+pub mod hash;
+pub mod hyperloglog;
+pub mod interval_tree;
+pub mod kd_tree;
+pub mod li_chao;
+pub mod link_cut;
+pub mod merkle;
+pub mod monotonic_stack;
+pub mod numeric;
+pub mod persistent_vec;
+pub mod quadtree;
+pub mod r_tree;
+pub mod radix_tree;
+pub mod range_set;
+pub mod red_black;
+pub mod reservoir;
+pub mod ring_buffer;
+pub mod rng;
+pub mod rope;
+pub mod search;
+pub mod segment_tree;
+pub mod skip_list;
+pub mod sort;
+pub mod sparse_table;
+pub mod splay;
+pub mod sqrt_decomposition;
+pub mod string;
+pub mod tdigest;
+pub mod treap;
+pub mod trie;
+pub mod van_emde_boas;
+pub mod wavelet_tree;
 
 pub trait PriorityQueue<Element> {
     /// create a new priority queue.
@@ -13,12 +63,102 @@ pub trait PriorityQueue<Element> {
     fn pop(&mut self) -> Option<Element>;
 }
 
+/// a map that keeps its keys sorted, implemented by the balanced trees of this crate
+/// so they can be swapped for one another.
+pub trait OrderedMap<K: Ord, V> {
+    /// create an empty map.
+    fn new() -> Self;
+    /// number of entries.
+    fn len(&self) -> usize;
+    /// check whether the map has no entries.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// returns the value stored for `key`.
+    fn get(&self, key: &K) -> Option<&V>;
+    /// check whether `key` is present.
+    fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+    /// insert `value` for `key`, returning the previous value if there was one.
+    fn insert(&mut self, key: K, value: V) -> Option<V>;
+    /// remove `key`, returning its value if it was present.
+    fn remove(&mut self, key: &K) -> Option<V>;
+    /// returns the entry with the smallest key.
+    fn first(&self) -> Option<(&K, &V)>;
+    /// returns the entry with the largest key.
+    fn last(&self) -> Option<(&K, &V)>;
+    /// iterate over the entries in increasing key order.
+    fn iter<'a>(&'a self) -> Box<dyn Iterator<Item = (&'a K, &'a V)> + 'a>
+    where
+        K: 'a,
+        V: 'a;
+}
+
+/// an [`OrderedMap`] augmented with subtree sizes, so entries can also be
+/// found by their position in key order in O(log n).
+pub trait OrderStatistic<K: Ord, V>: OrderedMap<K, V> {
+    /// number of keys strictly smaller than `key`.
+    fn rank(&self, key: &K) -> usize;
+    /// returns the `rank`-th smallest entry (0-based).
+    fn select(&self, rank: usize) -> Option<(&K, &V)>;
+    /// returns the lower median entry.
+    fn median(&self) -> Option<(&K, &V)> {
+        self.select(self.len().saturating_sub(1) / 2)
+    }
+    /// number of keys in `lo..hi`.
+    fn count_between(&self, lo: &K, hi: &K) -> usize {
+        self.rank(hi).saturating_sub(self.rank(lo))
+    }
+}
+
+impl<K: Ord, V> OrderedMap<K, V> for BTreeMap<K, V> {
+    fn new() -> Self {
+        BTreeMap::new()
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn get(&self, key: &K) -> Option<&V> {
+        self.get(key)
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.insert(key, value)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        self.remove(key)
+    }
+
+    fn first(&self) -> Option<(&K, &V)> {
+        self.iter().next()
+    }
+
+    fn last(&self) -> Option<(&K, &V)> {
+        self.iter().next_back()
+    }
+
+    fn iter<'a>(&'a self) -> Box<dyn Iterator<Item = (&'a K, &'a V)> + 'a>
+    where
+        K: 'a,
+        V: 'a,
+    {
+        Box::new(self.iter())
+    }
+}
+
 type KeyValueStore<Element> = BTreeMap<CustomQueueEntry, Element>;
 
 // Additional requirement: the underlying data structure needs to be a key-value stores
 // Note: you may simulate other data structure with key-value store
 pub struct PriorityQueueImpl<Element> {
     data: KeyValueStore<Element>,
+    // insertion counter breaking ties between equal priorities, so that keys
+    // stay unique after pops; the most recent insertion wins a tie.
+    inserted: usize,
 }
 #[derive(Copy, Clone, PartialOrd, PartialEq, Ord, Eq)]
 
@@ -41,6 +181,8 @@ impl<Element> From<Vec<(u64, Element)>> for PriorityQueueImpl<Element> {
     }
 }
 impl<Element> PriorityQueueImpl<Element> {
+    // `is_empty` is provided through the `PriorityQueue` trait.
+    #[allow(clippy::len_without_is_empty)]
     pub fn len(&self) -> usize {
         self.data.len()
     }
@@ -50,6 +192,7 @@ impl<Element> PriorityQueue<Element> for PriorityQueueImpl<Element> {
     fn new() -> Self {
         PriorityQueueImpl {
             data: BTreeMap::new(),
+            inserted: 0,
         }
     }
 
@@ -58,14 +201,15 @@ impl<Element> PriorityQueue<Element> for PriorityQueueImpl<Element> {
     }
 
     fn peek(&self) -> Option<&Element> {
-        self.data.iter().next_back().map(|(_, v)| v.clone())
+        self.data.iter().next_back().map(|(_, v)| v)
     }
 
     fn insert(&mut self, element: Element, priority: u64) {
         self.data.insert(
-            CustomQueueEntry::new(self.data.len(),priority),
+            CustomQueueEntry::new(self.inserted, priority),
             element,
         );
+        self.inserted += 1;
     }
 
     fn pop(&mut self) -> Option<Element> {
@@ -74,6 +218,28 @@ impl<Element> PriorityQueue<Element> for PriorityQueueImpl<Element> {
     }
 }
 
+// resolve a range over `0..len` into half-open `(start, end)` indices.
+pub(crate) fn resolve_range(range: impl RangeBounds<usize>, len: usize) -> (usize, usize) {
+    let start = match range.start_bound() {
+        Bound::Included(&s) => s,
+        Bound::Excluded(&s) => s + 1,
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&e) => e + 1,
+        Bound::Excluded(&e) => e,
+        Bound::Unbounded => len,
+    };
+    assert!(
+        start <= end && end <= len,
+        "range {}..{} out of bounds for length {}",
+        start,
+        end,
+        len
+    );
+    (start, end)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -115,6 +281,18 @@ mod tests {
         assert_eq!(queue.len(), 3);
     }
 
+    #[test]
+    fn test_equal_priorities_after_pop() {
+        let mut queue = PriorityQueueImpl::new();
+        queue.insert("a", 5);
+        queue.insert("b", 1);
+        assert_eq!(queue.pop(), Some("a"));
+        queue.insert("c", 1);
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.pop(), Some("c"));
+        assert_eq!(queue.pop(), Some("b"));
+    }
+
     #[test]
     fn test_empty_peek() {
         let queue = PriorityQueueImpl::<i32>::new();
@@ -0,0 +1,355 @@
+use std::marker::PhantomData;
+
+use crate::segment_tree::Monoid;
+
+const NIL: usize = usize::MAX;
+
+struct Node<T> {
+    value: T,
+    // the combined values of the splay subtree, in path order and reversed.
+    sum: T,
+    reversed: T,
+    parent: usize,
+    left: usize,
+    right: usize,
+    // the children's subtrees are still to be reversed.
+    flipped: bool,
+}
+
+/// a forest of the nodes `0..len` under links and cuts, answering whether
+/// two nodes are connected and combining the values along the path between
+/// them, all in amortized O(log n).
+///
+/// each tree is split into preferred paths, kept as splay trees ordered by
+/// depth; the roots of those hang from the path above by a parent pointer
+/// their parent does not know about. `Op` need not be commutative: paths are
+/// combined in order from their first node to their last.
+pub struct LinkCutTree<T, Op> {
+    nodes: Vec<Node<T>>,
+    op: PhantomData<Op>,
+}
+
+impl<T: Clone, Op: Monoid<T>> LinkCutTree<T, Op> {
+    /// a forest of isolated nodes, each with its own value.
+    pub fn new(values: Vec<T>) -> Self {
+        let nodes = values
+            .into_iter()
+            .map(|value| Node {
+                sum: value.clone(),
+                reversed: value.clone(),
+                value,
+                parent: NIL,
+                left: NIL,
+                right: NIL,
+                flipped: false,
+            })
+            .collect();
+        LinkCutTree {
+            nodes,
+            op: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn get(&self, v: usize) -> &T {
+        &self.nodes[v].value
+    }
+
+    pub fn set(&mut self, v: usize, value: T) {
+        self.access(v);
+        self.nodes[v].value = value;
+        self.update(v);
+    }
+
+    /// add the edge `a - b`, unless it would close a cycle.
+    ///
+    /// returns whether the edge was added.
+    pub fn link(&mut self, a: usize, b: usize) -> bool {
+        if self.connected(a, b) {
+            return false;
+        }
+        self.make_root(a);
+        self.nodes[a].parent = b;
+        true
+    }
+
+    /// remove the edge `a - b`.
+    ///
+    /// returns whether there was such an edge.
+    pub fn cut(&mut self, a: usize, b: usize) -> bool {
+        if a == b {
+            return false;
+        }
+        self.make_root(a);
+        self.access(b);
+        // the path from `a` to `b` is now all of `b`'s splay tree; they are
+        // adjacent iff it holds nothing else.
+        if self.nodes[b].left != a {
+            return false;
+        }
+        self.push(a);
+        if self.nodes[a].right != NIL {
+            return false;
+        }
+        self.nodes[b].left = NIL;
+        self.nodes[a].parent = NIL;
+        self.update(b);
+        true
+    }
+
+    /// check whether `a` and `b` are in the same tree.
+    pub fn connected(&mut self, a: usize, b: usize) -> bool {
+        a == b || self.find_root(a) == self.find_root(b)
+    }
+
+    /// the values on the path from `a` to `b`, both included, combined in
+    /// that order; `None` if they are not connected.
+    pub fn path(&mut self, a: usize, b: usize) -> Option<T> {
+        if !self.connected(a, b) {
+            return None;
+        }
+        self.make_root(a);
+        self.access(b);
+        Some(self.nodes[b].sum.clone())
+    }
+
+    // the root of the tree holding `v`, splayed to the top of its path.
+    fn find_root(&mut self, v: usize) -> usize {
+        self.access(v);
+        let mut root = v;
+        loop {
+            self.push(root);
+            match self.nodes[root].left {
+                NIL => break,
+                left => root = left,
+            }
+        }
+        self.splay(root);
+        root
+    }
+
+    // make `v` the root of its tree by reversing the path up to the old one.
+    fn make_root(&mut self, v: usize) {
+        self.access(v);
+        self.toggle(v);
+    }
+
+    // make the path from the root to `v` preferred, ending at `v`, and splay
+    // `v` to the top of it.
+    fn access(&mut self, v: usize) {
+        let mut below = NIL;
+        let mut cur = v;
+        while cur != NIL {
+            self.splay(cur);
+            self.nodes[cur].right = below;
+            self.update(cur);
+            below = cur;
+            cur = self.nodes[cur].parent;
+        }
+        self.splay(v);
+    }
+
+    fn is_splay_root(&self, x: usize) -> bool {
+        let parent = self.nodes[x].parent;
+        parent == NIL || (self.nodes[parent].left != x && self.nodes[parent].right != x)
+    }
+
+    fn splay(&mut self, x: usize) {
+        // push the pending flips down from the top of the splay tree first.
+        let mut stack = vec![x];
+        let mut top = x;
+        while !self.is_splay_root(top) {
+            top = self.nodes[top].parent;
+            stack.push(top);
+        }
+        while let Some(node) = stack.pop() {
+            self.push(node);
+        }
+        while !self.is_splay_root(x) {
+            let parent = self.nodes[x].parent;
+            if !self.is_splay_root(parent) {
+                let grand = self.nodes[parent].parent;
+                let zig_zig = (self.nodes[grand].left == parent) == (self.nodes[parent].left == x);
+                self.rotate(if zig_zig { parent } else { x });
+            }
+            self.rotate(x);
+        }
+    }
+
+    // move `x` above its parent, keeping the order of the splay tree.
+    fn rotate(&mut self, x: usize) {
+        let parent = self.nodes[x].parent;
+        let grand = self.nodes[parent].parent;
+        if !self.is_splay_root(parent) {
+            if self.nodes[grand].left == parent {
+                self.nodes[grand].left = x;
+            } else {
+                self.nodes[grand].right = x;
+            }
+        }
+        self.nodes[x].parent = grand;
+        if self.nodes[parent].left == x {
+            let child = self.nodes[x].right;
+            self.nodes[parent].left = child;
+            if child != NIL {
+                self.nodes[child].parent = parent;
+            }
+            self.nodes[x].right = parent;
+        } else {
+            let child = self.nodes[x].left;
+            self.nodes[parent].right = child;
+            if child != NIL {
+                self.nodes[child].parent = parent;
+            }
+            self.nodes[x].left = parent;
+        }
+        self.nodes[parent].parent = x;
+        self.update(parent);
+        self.update(x);
+    }
+
+    // reverse the splay subtree of `x`, leaving its children's for later.
+    fn toggle(&mut self, x: usize) {
+        if x == NIL {
+            return;
+        }
+        let node = &mut self.nodes[x];
+        std::mem::swap(&mut node.left, &mut node.right);
+        std::mem::swap(&mut node.sum, &mut node.reversed);
+        node.flipped = !node.flipped;
+    }
+
+    fn push(&mut self, x: usize) {
+        if self.nodes[x].flipped {
+            self.nodes[x].flipped = false;
+            let (left, right) = (self.nodes[x].left, self.nodes[x].right);
+            self.toggle(left);
+            self.toggle(right);
+        }
+    }
+
+    fn update(&mut self, x: usize) {
+        let (left, right) = (self.nodes[x].left, self.nodes[x].right);
+        let (sum, reversed) = {
+            let side = |child: usize, reversed: bool| match child {
+                NIL => Op::identity(),
+                _ if reversed => self.nodes[child].reversed.clone(),
+                _ => self.nodes[child].sum.clone(),
+            };
+            let value = &self.nodes[x].value;
+            (
+                Op::combine(&Op::combine(&side(left, false), value), &side(right, false)),
+                Op::combine(&Op::combine(&side(right, true), value), &side(left, true)),
+            )
+        };
+        self.nodes[x].sum = sum;
+        self.nodes[x].reversed = reversed;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+    use crate::segment_tree::Sum;
+
+    // concatenation, to see the order paths are combined in.
+    struct Concat;
+
+    impl Monoid<Vec<usize>> for Concat {
+        fn identity() -> Vec<usize> {
+            Vec::new()
+        }
+
+        fn combine(a: &Vec<usize>, b: &Vec<usize>) -> Vec<usize> {
+            a.iter().chain(b).copied().collect()
+        }
+    }
+
+    // the path from `a` to `b` in the forest given by its adjacency lists.
+    fn find_path(adjacent: &[Vec<usize>], a: usize, b: usize) -> Option<Vec<usize>> {
+        let mut parent = vec![usize::MAX; adjacent.len()];
+        parent[a] = a;
+        let mut stack = vec![a];
+        while let Some(v) = stack.pop() {
+            for &to in &adjacent[v] {
+                if parent[to] == usize::MAX {
+                    parent[to] = v;
+                    stack.push(to);
+                }
+            }
+        }
+        if parent[b] == usize::MAX {
+            return None;
+        }
+        let mut path = vec![b];
+        while *path.last().unwrap() != a {
+            path.push(parent[*path.last().unwrap()]);
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    #[test]
+    fn test_small() {
+        let mut forest: LinkCutTree<i64, Sum> = LinkCutTree::new(vec![1, 2, 4, 8, 16]);
+        assert!(forest.link(0, 1) && forest.link(1, 2) && forest.link(3, 2));
+        assert!(!forest.link(0, 3));
+        assert!(forest.connected(0, 3) && !forest.connected(0, 4));
+        assert_eq!(forest.path(0, 3), Some(15));
+        assert_eq!(forest.path(1, 1), Some(2));
+        assert_eq!(forest.path(4, 0), None);
+        forest.set(2, 100);
+        assert_eq!(forest.path(3, 1), Some(110));
+        assert_eq!(*forest.get(2), 100);
+        assert!(!forest.cut(0, 2));
+        assert!(forest.cut(2, 1));
+        assert!(!forest.cut(1, 2));
+        assert!(!forest.connected(0, 3));
+        assert!(forest.link(4, 0) && forest.link(3, 4));
+        assert_eq!(forest.path(2, 1), Some(100 + 8 + 16 + 1 + 2));
+    }
+
+    #[test]
+    fn test_against_brute_force() {
+        let mut rng = Rng::new(85);
+        for _ in 0..20 {
+            let n = 1 + rng.index(30);
+            let mut forest: LinkCutTree<Vec<usize>, Concat> =
+                LinkCutTree::new((0..n).map(|v| vec![v]).collect());
+            let mut adjacent: Vec<Vec<usize>> = vec![Vec::new(); n];
+            for _ in 0..500 {
+                let (a, b) = (rng.index(n), rng.index(n));
+                let expected = find_path(&adjacent, a, b);
+                match rng.index(3) {
+                    0 => {
+                        assert_eq!(forest.link(a, b), expected.is_none());
+                        if expected.is_none() {
+                            adjacent[a].push(b);
+                            adjacent[b].push(a);
+                        }
+                    }
+                    1 => {
+                        let adjacent_ab = a != b && adjacent[a].contains(&b);
+                        assert_eq!(forest.cut(a, b), adjacent_ab);
+                        if adjacent_ab {
+                            adjacent[a].retain(|&v| v != b);
+                            adjacent[b].retain(|&v| v != a);
+                        }
+                    }
+                    _ => {
+                        assert_eq!(forest.connected(a, b), expected.is_some());
+                        assert_eq!(forest.path(a, b), expected);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,260 @@
+use std::fmt;
+use std::hash::Hasher;
+
+use crate::hash::{mix64, Fnv1a};
+
+/// the hash function of a [`MerkleTree`]. leaves and inner nodes are hashed
+/// differently, so that an inner node cannot pass for a leaf.
+pub trait MerkleHasher {
+    type Digest: Clone + Eq + fmt::Debug;
+    fn hash_leaf(&self, data: &[u8]) -> Self::Digest;
+    fn hash_node(&self, left: &Self::Digest, right: &Self::Digest) -> Self::Digest;
+}
+
+/// 64-bit fnv-1a based hasher. it catches accidental corruption but is not
+/// collision resistant; plug in a cryptographic hash where an adversary
+/// controls the data.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Fnv64;
+
+impl MerkleHasher for Fnv64 {
+    type Digest = u64;
+
+    fn hash_leaf(&self, data: &[u8]) -> u64 {
+        let mut hasher = Fnv1a::with_seed(0);
+        hasher.write(data);
+        mix64(hasher.finish())
+    }
+
+    fn hash_node(&self, left: &u64, right: &u64) -> u64 {
+        let mut hasher = Fnv1a::with_seed(1);
+        hasher.write_u64(*left);
+        hasher.write_u64(*right);
+        mix64(hasher.finish())
+    }
+}
+
+/// one step of a [`Proof`]: the sibling digest to combine with, and on
+/// which side it goes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Step<D> {
+    Left(D),
+    Right(D),
+}
+
+/// inclusion proof for one leaf: the sibling digests on the path up to the
+/// root, bottom first.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Proof<D> {
+    pub index: usize,
+    pub steps: Vec<Step<D>>,
+}
+
+impl<D: Clone + Eq + fmt::Debug> Proof<D> {
+    /// the root the proof leads to from `data`.
+    pub fn root_for<H: MerkleHasher<Digest = D>>(&self, hasher: &H, data: &[u8]) -> D {
+        self.steps
+            .iter()
+            .fold(hasher.hash_leaf(data), |digest, step| match step {
+                Step::Left(sibling) => hasher.hash_node(sibling, &digest),
+                Step::Right(sibling) => hasher.hash_node(&digest, sibling),
+            })
+    }
+
+    /// check that `data` is the leaf of a tree with `root`.
+    pub fn verify<H: MerkleHasher<Digest = D>>(&self, hasher: &H, data: &[u8], root: &D) -> bool {
+        self.root_for(hasher, data) == *root
+    }
+}
+
+/// binary hash tree over a sequence of byte payloads: the root digest
+/// commits to all of them, and a proof of O(log n) digests shows that one
+/// payload is among them without the others.
+///
+/// a node without a sibling at the end of a level is carried up unchanged.
+pub struct MerkleTree<H: MerkleHasher = Fnv64> {
+    hasher: H,
+    // levels[0] holds the leaf digests and the last level the root.
+    levels: Vec<Vec<H::Digest>>,
+}
+
+impl MerkleTree<Fnv64> {
+    pub fn new<T: AsRef<[u8]>>(leaves: &[T]) -> Self {
+        MerkleTree::with_hasher(Fnv64, leaves)
+    }
+}
+
+impl<H: MerkleHasher> MerkleTree<H> {
+    pub fn with_hasher<T: AsRef<[u8]>>(hasher: H, leaves: &[T]) -> Self {
+        let digests = leaves
+            .iter()
+            .map(|l| hasher.hash_leaf(l.as_ref()))
+            .collect();
+        let mut tree = MerkleTree {
+            hasher,
+            levels: vec![digests],
+        };
+        tree.rebuild();
+        tree
+    }
+
+    fn rebuild(&mut self) {
+        self.levels.truncate(1);
+        while self.levels.last().unwrap().len() > 1 {
+            let next = self
+                .levels
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => self.hasher.hash_node(left, right),
+                    [single] => single.clone(),
+                    _ => unreachable!(),
+                })
+                .collect();
+            self.levels.push(next);
+        }
+    }
+
+    pub fn hasher(&self) -> &H {
+        &self.hasher
+    }
+
+    pub fn len(&self) -> usize {
+        self.levels[0].len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.levels[0].is_empty()
+    }
+
+    /// the root digest; None for a tree without leaves.
+    pub fn root(&self) -> Option<&H::Digest> {
+        self.levels.last().unwrap().first()
+    }
+
+    pub fn leaf(&self, index: usize) -> Option<&H::Digest> {
+        self.levels[0].get(index)
+    }
+
+    /// the inclusion proof for the leaf at `index`.
+    pub fn proof(&self, index: usize) -> Option<Proof<H::Digest>> {
+        if index >= self.len() {
+            return None;
+        }
+        let mut steps = Vec::new();
+        let mut i = index;
+        for level in &self.levels[..self.levels.len() - 1] {
+            let sibling = i ^ 1;
+            if sibling < level.len() {
+                steps.push(if sibling < i {
+                    Step::Left(level[sibling].clone())
+                } else {
+                    Step::Right(level[sibling].clone())
+                });
+            }
+            i /= 2;
+        }
+        Some(Proof { index, steps })
+    }
+
+    /// replace the leaf at `index` and recompute its path, in O(log n).
+    pub fn update(&mut self, index: usize, data: &[u8]) {
+        assert!(index < self.len(), "index {} out of bounds", index);
+        self.levels[0][index] = self.hasher.hash_leaf(data);
+        let mut i = index;
+        for depth in 1..self.levels.len() {
+            i /= 2;
+            let below = &self.levels[depth - 1];
+            let digest = match below.get(2 * i + 1) {
+                Some(right) => self.hasher.hash_node(&below[2 * i], right),
+                None => below[2 * i].clone(),
+            };
+            self.levels[depth][i] = digest;
+        }
+    }
+
+    /// append a leaf. rebuilds the tree, so prefer building from all
+    /// leaves at once.
+    pub fn push(&mut self, data: &[u8]) {
+        let digest = self.hasher.hash_leaf(data);
+        self.levels[0].push(digest);
+        self.rebuild();
+    }
+}
+
+impl<H: MerkleHasher> fmt::Debug for MerkleTree<H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MerkleTree")
+            .field("len", &self.len())
+            .field("root", &self.root())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proofs() {
+        for n in 1..40usize {
+            let leaves: Vec<String> = (0..n).map(|i| format!("block {}", i)).collect();
+            let tree = MerkleTree::new(&leaves);
+            let root = *tree.root().unwrap();
+            for (i, leaf) in leaves.iter().enumerate() {
+                let proof = tree.proof(i).unwrap();
+                assert!(proof.steps.len() <= 6);
+                assert!(proof.verify(&Fnv64, leaf.as_bytes(), &root));
+                assert!(!proof.verify(&Fnv64, b"forged", &root));
+            }
+            assert!(tree.proof(n).is_none());
+        }
+        let empty = MerkleTree::new::<&[u8]>(&[]);
+        assert!(empty.is_empty() && empty.root().is_none());
+    }
+
+    #[test]
+    fn test_update_matches_rebuild() {
+        let mut leaves: Vec<Vec<u8>> = (0..13u8).map(|i| vec![i; 3]).collect();
+        let mut tree = MerkleTree::new(&leaves);
+        let before = *tree.root().unwrap();
+        leaves[6] = b"changed".to_vec();
+        tree.update(6, &leaves[6]);
+        assert_ne!(tree.root(), Some(&before));
+        assert_eq!(tree.root(), MerkleTree::new(&leaves).root());
+        leaves.push(b"new".to_vec());
+        tree.push(b"new");
+        assert_eq!(tree.root(), MerkleTree::new(&leaves).root());
+        // a proof from before the change no longer verifies.
+        let proof = tree.proof(0).unwrap();
+        assert!(!proof.verify(&Fnv64, &leaves[0], &before));
+    }
+
+    #[test]
+    fn test_custom_hasher() {
+        // a transparent "hash" to check the shape of the tree.
+        struct Concat;
+        impl MerkleHasher for Concat {
+            type Digest = String;
+            fn hash_leaf(&self, data: &[u8]) -> String {
+                String::from_utf8(data.to_vec()).unwrap()
+            }
+            fn hash_node(&self, left: &String, right: &String) -> String {
+                format!("({}{})", left, right)
+            }
+        }
+        let tree = MerkleTree::with_hasher(Concat, &["a", "b", "c", "d", "e"]);
+        assert_eq!(tree.root().unwrap(), "(((ab)(cd))e)");
+        let proof = tree.proof(2).unwrap();
+        assert_eq!(
+            proof.steps,
+            vec![
+                Step::Right("d".to_string()),
+                Step::Left("(ab)".to_string()),
+                Step::Right("e".to_string()),
+            ]
+        );
+        assert_eq!(proof.root_for(&Concat, b"c"), "(((ab)(cd))e)");
+    }
+}
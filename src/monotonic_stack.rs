@@ -0,0 +1,184 @@
+use std::ops::Range;
+
+/// a stack whose items stay sorted from bottom to top: pushing an item first
+/// pops every item that would break the order.
+#[derive(Clone, Debug)]
+pub struct MonotonicStack<T> {
+    items: Vec<T>,
+    increasing: bool,
+}
+
+impl<T: Ord> MonotonicStack<T> {
+    /// a stack that is non-decreasing from bottom to top; a push pops the
+    /// items greater than the new one.
+    pub fn increasing() -> Self {
+        MonotonicStack {
+            items: Vec::new(),
+            increasing: true,
+        }
+    }
+
+    /// a stack that is non-increasing from bottom to top; a push pops the
+    /// items less than the new one.
+    pub fn decreasing() -> Self {
+        MonotonicStack {
+            items: Vec::new(),
+            increasing: false,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn top(&self) -> Option<&T> {
+        self.items.last()
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        self.items.pop()
+    }
+
+    /// the items from bottom to top.
+    pub fn as_slice(&self) -> &[T] {
+        &self.items
+    }
+
+    pub fn push(&mut self, item: T) {
+        self.push_with(item, drop);
+    }
+
+    /// push `item`, handing each item it displaces to `popped`, top first.
+    pub fn push_with(&mut self, item: T, mut popped: impl FnMut(T)) {
+        while let Some(top) = self.items.last() {
+            let breaks = if self.increasing {
+                *top > item
+            } else {
+                *top < item
+            };
+            if !breaks {
+                break;
+            }
+            popped(self.items.pop().unwrap());
+        }
+        self.items.push(item);
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+}
+
+/// for each position, the index of the next strictly greater value.
+pub fn next_greater<T: Ord>(values: &[T]) -> Vec<Option<usize>> {
+    let mut result = vec![None; values.len()];
+    let mut stack: Vec<usize> = Vec::new();
+    for (i, v) in values.iter().enumerate() {
+        while let Some(&top) = stack.last() {
+            if values[top] >= *v {
+                break;
+            }
+            result[top] = Some(i);
+            stack.pop();
+        }
+        stack.push(i);
+    }
+    result
+}
+
+/// for each position, the index of the previous strictly smaller value.
+pub fn previous_smaller<T: Ord>(values: &[T]) -> Vec<Option<usize>> {
+    let mut result = vec![None; values.len()];
+    let mut stack: Vec<usize> = Vec::new();
+    for (i, v) in values.iter().enumerate() {
+        while stack.last().is_some_and(|&top| values[top] >= *v) {
+            stack.pop();
+        }
+        result[i] = stack.last().copied();
+        stack.push(i);
+    }
+    result
+}
+
+/// largest rectangle under a histogram of bar `heights` of width one,
+/// returned as its area and the range of bars it spans.
+pub fn largest_rectangle(heights: &[u64]) -> (u64, Range<usize>) {
+    let mut best = (0, 0..0);
+    // bars of increasing height still waiting for a lower bar on the right.
+    let mut stack: Vec<usize> = Vec::new();
+    for i in 0..=heights.len() {
+        let h = heights.get(i).copied().unwrap_or(0);
+        while let Some(&top) = stack.last() {
+            if heights[top] < h {
+                break;
+            }
+            stack.pop();
+            let start = stack.last().map_or(0, |&s| s + 1);
+            let area = heights[top] * (i - start) as u64;
+            if area > best.0 {
+                best = (area, start..i);
+            }
+        }
+        stack.push(i);
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_stack() {
+        let mut stack = MonotonicStack::increasing();
+        for &x in &[3, 1, 4, 1, 5] {
+            stack.push(x);
+        }
+        assert_eq!(stack.as_slice(), &[1, 1, 5]);
+        let mut popped = Vec::new();
+        stack.push_with(2, |x| popped.push(x));
+        assert_eq!(popped, vec![5]);
+        assert_eq!(stack.top(), Some(&2));
+        let mut stack = MonotonicStack::decreasing();
+        for &x in &[3, 1, 4, 1, 5, 9, 2, 6] {
+            stack.push(x);
+        }
+        assert_eq!(stack.as_slice(), &[9, 6]);
+    }
+
+    #[test]
+    fn test_against_brute_force() {
+        let mut rng = Rng::new(37);
+        for _ in 0..200 {
+            let n = rng.index(20);
+            let values: Vec<u64> = (0..n).map(|_| rng.below(6)).collect();
+            let greater: Vec<_> = (0..n)
+                .map(|i| (i + 1..n).find(|&j| values[j] > values[i]))
+                .collect();
+            let smaller: Vec<_> = (0..n)
+                .map(|i| (0..i).rev().find(|&j| values[j] < values[i]))
+                .collect();
+            assert_eq!(next_greater(&values), greater);
+            assert_eq!(previous_smaller(&values), smaller);
+            let mut best = 0;
+            for a in 0..n {
+                for b in a + 1..=n {
+                    let low = *values[a..b].iter().min().unwrap();
+                    best = best.max(low * (b - a) as u64);
+                }
+            }
+            let (area, range) = largest_rectangle(&values);
+            assert_eq!(area, best);
+            if area > 0 {
+                let low = *values[range.clone()].iter().min().unwrap();
+                assert_eq!(low * range.len() as u64, area);
+            }
+        }
+        assert_eq!(largest_rectangle(&[2, 1, 5, 6, 2, 3]), (10, 2..4));
+    }
+}
@@ -0,0 +1,149 @@
+use super::modular::ModInt;
+
+/// the factorials and their inverses modulo the prime `M`, up to a limit
+/// below `M`, in O(limit): each binomial is then O(1).
+pub struct Factorials<const M: u64> {
+    factorial: Vec<ModInt<M>>,
+    inverse: Vec<ModInt<M>>,
+}
+
+impl<const M: u64> Factorials<M> {
+    /// the tables for `0..=limit`. past `M - 1` the factorials are zero
+    /// and have no inverse, so `limit` must be below `M`.
+    pub fn new(limit: usize) -> Self {
+        assert!(
+            (limit as u64) < M,
+            "limit {} not below the modulus {}",
+            limit,
+            M
+        );
+        let mut factorial = Vec::with_capacity(limit + 1);
+        factorial.push(ModInt::new(1));
+        for i in 1..=limit {
+            factorial.push(factorial[i - 1] * ModInt::new(i as u64));
+        }
+        let mut inverse = vec![ModInt::new(0); limit + 1];
+        // one inversion, then down by (i - 1)! = i! / i.
+        inverse[limit] = factorial[limit].inverse().expect("modulus is not prime");
+        for i in (1..=limit).rev() {
+            inverse[i - 1] = inverse[i] * ModInt::new(i as u64);
+        }
+        Factorials { factorial, inverse }
+    }
+
+    /// the largest `n` the tables cover.
+    pub fn limit(&self) -> usize {
+        self.factorial.len() - 1
+    }
+
+    pub fn factorial(&self, n: usize) -> ModInt<M> {
+        self.factorial[n]
+    }
+
+    pub fn inverse_factorial(&self, n: usize) -> ModInt<M> {
+        self.inverse[n]
+    }
+
+    /// `n` choose `k`, zero if `k > n`.
+    pub fn binomial(&self, n: usize, k: usize) -> ModInt<M> {
+        if k > n {
+            return ModInt::new(0);
+        }
+        self.factorial[n] * self.inverse[k] * self.inverse[n - k]
+    }
+
+    /// the ordered selections of `k` out of `n`, `n! / (n - k)!`.
+    pub fn permutations(&self, n: usize, k: usize) -> ModInt<M> {
+        if k > n {
+            return ModInt::new(0);
+        }
+        self.factorial[n] * self.inverse[n - k]
+    }
+
+    /// the `n`-th catalan number, `binomial(2n, n) / (n + 1)`; needs the
+    /// tables up to `2n`.
+    pub fn catalan(&self, n: usize) -> ModInt<M> {
+        self.binomial(2 * n, n) * self.inverse[n + 1] * self.factorial[n]
+    }
+
+    /// `n` choose `k` for `n` of any size, by lucas' theorem: the product
+    /// of the binomials of the base `M` digits of `n` and `k`. needs the
+    /// tables up to `M - 1`, so `M` should be small. O(log n / log M).
+    pub fn lucas(&self, mut n: u64, mut k: u64) -> ModInt<M> {
+        assert_eq!(
+            self.limit() as u64,
+            M - 1,
+            "lucas needs the tables up to M - 1"
+        );
+        let mut result = ModInt::new(1);
+        while k > 0 {
+            let (a, b) = ((n % M) as usize, (k % M) as usize);
+            result *= self.binomial(a, b);
+            if result == ModInt::new(0) {
+                break;
+            }
+            n /= M;
+            k /= M;
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::numeric::modular::ModInt1000000007;
+
+    // the rows of pascal's triangle below `n`, modulo `m`.
+    fn pascal(n: usize, m: u64) -> Vec<Vec<u64>> {
+        let mut rows: Vec<Vec<u64>> = Vec::new();
+        for i in 0..n {
+            let row: Vec<u64> = (0..=i)
+                .map(|k| match k {
+                    0 => 1,
+                    _ if k == i => 1,
+                    _ => (rows[i - 1][k - 1] + rows[i - 1][k]) % m,
+                })
+                .collect();
+            rows.push(row);
+        }
+        rows
+    }
+
+    #[test]
+    fn test_tables() {
+        let tables: Factorials<1_000_000_007> = Factorials::new(400);
+        let rows = pascal(201, 1_000_000_007);
+        for (n, row) in rows.iter().enumerate() {
+            for k in 0..=n + 1 {
+                let expected = row.get(k).copied().unwrap_or(0);
+                assert_eq!(tables.binomial(n, k).value(), expected);
+            }
+        }
+        assert_eq!(tables.factorial(10).value(), 3_628_800);
+        assert_eq!(
+            tables.factorial(7) * tables.inverse_factorial(7),
+            ModInt1000000007::new(1)
+        );
+        assert_eq!(tables.permutations(10, 3).value(), 720);
+        assert_eq!(tables.permutations(3, 4).value(), 0);
+        let catalan: Vec<u64> = (0..10).map(|n| tables.catalan(n).value()).collect();
+        assert_eq!(catalan, [1, 1, 2, 5, 14, 42, 132, 429, 1430, 4862]);
+        assert_eq!(Factorials::<7>::new(0).binomial(0, 0).value(), 1);
+    }
+
+    #[test]
+    fn test_lucas() {
+        let tables: Factorials<13> = Factorials::new(12);
+        let rows = pascal(400, 13);
+        for (n, row) in rows.iter().enumerate() {
+            for (k, &expected) in row.iter().enumerate() {
+                assert_eq!(tables.lucas(n as u64, k as u64).value(), expected);
+            }
+        }
+        // a base 13 digit of 13^14 is above that of 13^15, so 13 divides it.
+        assert_eq!(tables.lucas(13u64.pow(15), 13u64.pow(14)).value(), 0);
+        assert_eq!(tables.lucas(u64::MAX, 0).value(), 1);
+        assert_eq!(tables.lucas(5, 7).value(), 0);
+    }
+}
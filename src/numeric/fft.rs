@@ -0,0 +1,317 @@
+use std::f64::consts::PI;
+use std::ops::{Add, Mul, Sub};
+use std::sync::Mutex;
+
+use super::modular::ModInt;
+use super::primality::factorize;
+
+// below this length, the product is taken term by term.
+const NAIVE: usize = 32;
+
+// the primitive root found for each modulus `ntt` was used with, since
+// finding one means factoring `M - 1`.
+static ROOTS: Mutex<Vec<(u64, u64)>> = Mutex::new(Vec::new());
+
+/// a complex number, just enough for the fft.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Complex {
+    pub re: f64,
+    pub im: f64,
+}
+
+impl Complex {
+    pub fn new(re: f64, im: f64) -> Self {
+        Complex { re, im }
+    }
+
+    /// `e^(i * angle)`.
+    pub fn from_angle(angle: f64) -> Self {
+        Complex::new(angle.cos(), angle.sin())
+    }
+}
+
+impl Add for Complex {
+    type Output = Complex;
+
+    fn add(self, other: Complex) -> Complex {
+        Complex::new(self.re + other.re, self.im + other.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Complex;
+
+    fn sub(self, other: Complex) -> Complex {
+        Complex::new(self.re - other.re, self.im - other.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Complex;
+
+    fn mul(self, other: Complex) -> Complex {
+        Complex::new(
+            self.re * other.re - self.im * other.im,
+            self.re * other.im + self.im * other.re,
+        )
+    }
+}
+
+// reorder `values`, of a power of two length, by bit-reversed index.
+fn bit_reverse<T>(values: &mut [T]) {
+    let n = values.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            values.swap(i, j);
+        }
+    }
+}
+
+// the iterative radix-2 butterflies, with `root(len)` a primitive `len`-th
+// root of unity.
+fn transform<T, R>(values: &mut [T], mut root: R)
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+    R: FnMut(usize) -> T,
+{
+    let n = values.len();
+    assert!(n.is_power_of_two(), "length {} is not a power of two", n);
+    bit_reverse(values);
+    let mut len = 2;
+    while len <= n {
+        let step = root(len);
+        let mut twiddles = Vec::with_capacity(len / 2);
+        let mut w = root(1);
+        for _ in 0..len / 2 {
+            twiddles.push(w);
+            w = w * step;
+        }
+        for chunk in values.chunks_mut(len) {
+            let (low, high) = chunk.split_at_mut(len / 2);
+            for ((a, b), &w) in low.iter_mut().zip(high.iter_mut()).zip(&twiddles) {
+                let (x, y) = (*a, *b * w);
+                *a = x + y;
+                *b = x - y;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+/// the discrete fourier transform of `values` in place, in O(n log n) for a
+/// power of two length: `X[k]` is the sum of `x[j] * e^(-2 pi i j k / n)`.
+/// the inverse uses `e^(+2 pi i j k / n)` and divides by the length too, so
+/// it undoes the forward transform. an empty slice is left as it is.
+pub fn fft(values: &mut [Complex], inverse: bool) {
+    if values.is_empty() {
+        return;
+    }
+    let sign = if inverse { 1.0 } else { -1.0 };
+    transform(values, |len| {
+        if len == 1 {
+            Complex::new(1.0, 0.0)
+        } else {
+            Complex::from_angle(sign * 2.0 * PI / len as f64)
+        }
+    });
+    if inverse {
+        let scale = 1.0 / values.len() as f64;
+        values
+            .iter_mut()
+            .for_each(|v| *v = Complex::new(v.re * scale, v.im * scale));
+    }
+}
+
+/// the number theoretic transform modulo the prime `M` in place: the fft
+/// with a root of unity modulo `M` in place of a complex one, so exact.
+/// the length must be a power of two dividing `M - 1`, or zero.
+pub fn ntt<const M: u64>(values: &mut [ModInt<M>], inverse: bool) {
+    if values.is_empty() {
+        return;
+    }
+    let n = values.len() as u64;
+    assert!(
+        (M - 1).is_multiple_of(n),
+        "length {} does not divide {} - 1",
+        n,
+        M
+    );
+    let g = primitive_root::<M>();
+    let g = if inverse { g.inverse().unwrap() } else { g };
+    transform(values, |len| g.pow((M - 1) / len as u64));
+    if inverse {
+        let scale = ModInt::new(n).inverse().unwrap();
+        values.iter_mut().for_each(|v| *v *= scale);
+    }
+}
+
+// the least generator of the multiplicative group modulo the prime `M`,
+// worked out on first use.
+fn primitive_root<const M: u64>() -> ModInt<M> {
+    let mut roots = ROOTS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(&(_, g)) = roots.iter().find(|&&(m, _)| m == M) {
+        return ModInt::new(g);
+    }
+    let factors = factorize(M - 1);
+    let g = (1..M)
+        .map(ModInt::new)
+        .find(|g| {
+            factors
+                .iter()
+                .all(|&(q, _)| g.pow((M - 1) / q) != ModInt::new(1))
+        })
+        .unwrap();
+    roots.push((M, g.value()));
+    g
+}
+
+fn naive<T: Copy + Default + Add<Output = T> + Mul<Output = T>>(a: &[T], b: &[T]) -> Vec<T> {
+    let mut out = vec![T::default(); a.len() + b.len() - 1];
+    for (i, &x) in a.iter().enumerate() {
+        for (j, &y) in b.iter().enumerate() {
+            out[i + j] = out[i + j] + x * y;
+        }
+    }
+    out
+}
+
+/// the convolution of `a` and `b`, `out[k]` being the sum of `a[i] * b[j]`
+/// over `i + j == k`: the product of the polynomials they are coefficients
+/// of. O((n + m) log(n + m)) by the fft, with the rounding error of floats;
+/// empty if either is.
+pub fn convolve(a: &[f64], b: &[f64]) -> Vec<f64> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    if a.len().min(b.len()) <= NAIVE {
+        return naive(a, b);
+    }
+    let len = a.len() + b.len() - 1;
+    let n = len.next_power_of_two();
+    // both go in one transform, `a` as the real part and `b` the imaginary:
+    // the square then holds `2i * a * b` in its imaginary part.
+    let mut values = vec![Complex::default(); n];
+    for (i, v) in values.iter_mut().enumerate() {
+        *v = Complex::new(
+            a.get(i).copied().unwrap_or(0.0),
+            b.get(i).copied().unwrap_or(0.0),
+        );
+    }
+    fft(&mut values, false);
+    values.iter_mut().for_each(|v| *v = *v * *v);
+    fft(&mut values, true);
+    values[..len].iter().map(|v| v.im / 2.0).collect()
+}
+
+/// [`convolve`] modulo the prime `M`, exact, by the ntt. `M - 1` must be
+/// divisible by a power of two at least the output length, as it is for
+/// 998244353 below 2^23.
+pub fn convolve_mod<const M: u64>(a: &[ModInt<M>], b: &[ModInt<M>]) -> Vec<ModInt<M>> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    if a.len().min(b.len()) <= NAIVE {
+        return naive(a, b);
+    }
+    let len = a.len() + b.len() - 1;
+    let n = len.next_power_of_two();
+    let (mut fa, mut fb) = (a.to_vec(), b.to_vec());
+    fa.resize(n, ModInt::new(0));
+    fb.resize(n, ModInt::new(0));
+    ntt(&mut fa, false);
+    ntt(&mut fb, false);
+    fa.iter_mut().zip(&fb).for_each(|(x, &y)| *x *= y);
+    ntt(&mut fa, true);
+    fa.truncate(len);
+    fa
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::numeric::modular::ModInt998244353;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_fft() {
+        let mut values: Vec<Complex> = (0..8).map(|i| Complex::new(i as f64, 0.0)).collect();
+        let original = values.clone();
+        fft(&mut values, false);
+        assert!((values[0].re - 28.0).abs() < 1e-9);
+        // the usual sign: a unit impulse at 1 transforms to e^(-2 pi i k / n).
+        let mut impulse = vec![Complex::default(); 4];
+        impulse[1] = Complex::new(1.0, 0.0);
+        fft(&mut impulse, false);
+        let expected = [(1.0, 0.0), (0.0, -1.0), (-1.0, 0.0), (0.0, 1.0)];
+        assert!(impulse
+            .iter()
+            .zip(&expected)
+            .all(|(v, &(re, im))| (v.re - re).abs() < 1e-12 && (v.im - im).abs() < 1e-12));
+        fft(&mut values, true);
+        assert!(values
+            .iter()
+            .zip(&original)
+            .all(|(a, b)| (a.re - b.re).abs() < 1e-9 && a.im.abs() < 1e-9));
+        assert_eq!(convolve(&[1.0, 2.0], &[3.0, 4.0]), [3.0, 10.0, 8.0]);
+        assert!(convolve(&[], &[1.0]).is_empty());
+        let mut empty: Vec<Complex> = Vec::new();
+        fft(&mut empty, false);
+        fft(&mut empty, true);
+        assert!(empty.is_empty());
+        let mut rng = Rng::new(108);
+        for _ in 0..20 {
+            let a: Vec<f64> = (0..1 + rng.index(300))
+                .map(|_| rng.range(-100..100) as f64)
+                .collect();
+            let b: Vec<f64> = (0..1 + rng.index(300))
+                .map(|_| rng.range(-100..100) as f64)
+                .collect();
+            let (fast, slow) = (convolve(&a, &b), naive(&a, &b));
+            assert!(fast.iter().zip(&slow).all(|(x, y)| (x - y).abs() < 1e-6));
+            assert_eq!(fast.len(), slow.len());
+        }
+    }
+
+    #[test]
+    fn test_ntt() {
+        type F = ModInt998244353;
+        let mut rng = Rng::new(109);
+        for _ in 0..20 {
+            let a: Vec<F> = (0..1 + rng.index(300))
+                .map(|_| F::new(rng.next_u64()))
+                .collect();
+            let b: Vec<F> = (0..1 + rng.index(300))
+                .map(|_| F::new(rng.next_u64()))
+                .collect();
+            assert_eq!(convolve_mod(&a, &b), naive(&a, &b));
+        }
+        // 7340033 = 7 * 2^20 + 1, with primitive root 3.
+        assert_eq!(primitive_root::<7_340_033>().value(), 3);
+        // found once, then read back.
+        assert_eq!(primitive_root::<7_340_033>().value(), 3);
+        let cached = ROOTS.lock().unwrap();
+        assert_eq!(cached.iter().filter(|&&(m, _)| m == 7_340_033).count(), 1);
+        drop(cached);
+        let a: Vec<ModInt<7_340_033>> = (0..5000).map(ModInt::new).collect();
+        let squared = convolve_mod(&a, &a);
+        assert_eq!(squared, naive(&a, &a));
+        let mut empty: Vec<F> = Vec::new();
+        ntt(&mut empty, false);
+        ntt(&mut empty, true);
+        assert!(empty.is_empty());
+        let mut values: Vec<F> = (0..16).map(F::new).collect();
+        ntt(&mut values, false);
+        ntt(&mut values, true);
+        assert!(values
+            .iter()
+            .enumerate()
+            .all(|(i, v)| v.value() == i as u64));
+    }
+}
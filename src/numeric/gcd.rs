@@ -0,0 +1,113 @@
+use super::{Integer, Signed};
+
+/// the greatest common divisor, never negative, by the binary gcd: only
+/// shifts and subtractions, O(log a + log b) of them. `gcd(0, 0) == 0`.
+pub fn gcd<T: Integer>(a: T, b: T) -> T {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    if a == T::ZERO {
+        return b;
+    }
+    if b == T::ZERO {
+        return a;
+    }
+    // the common factors of two, put back at the end.
+    let shift = a.trailing_zeros().min(b.trailing_zeros());
+    a = a >> a.trailing_zeros();
+    loop {
+        // `a` is odd here.
+        b = b >> b.trailing_zeros();
+        if a > b {
+            std::mem::swap(&mut a, &mut b);
+        }
+        b = b - a;
+        if b == T::ZERO {
+            return a << shift;
+        }
+    }
+}
+
+/// the least common multiple, never negative; zero if either is.
+/// overflows when the result does not fit.
+pub fn lcm<T: Integer>(a: T, b: T) -> T {
+    if a == T::ZERO || b == T::ZERO {
+        return T::ZERO;
+    }
+    (a / gcd(a, b) * b).abs()
+}
+
+/// `(g, x, y)` with `g = gcd(a, b)` and `a * x + b * y == g`, by the
+/// extended euclidean algorithm. the bézout coefficients found are small,
+/// `|x| <= |b / g|` and `|y| <= |a / g|` short of the degenerate cases, so
+/// they fit wherever `a` and `b` do.
+pub fn extended_gcd<T: Signed>(a: T, b: T) -> (T, T, T) {
+    // invariant: `old * (a, b) = (old_x, old_y)` and so on for `r`.
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (T::ONE, T::ZERO);
+    let (mut old_y, mut y) = (T::ZERO, T::ONE);
+    while r != T::ZERO {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+    if old_r < T::ZERO {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    fn euclid(mut a: i128, mut b: i128) -> i128 {
+        while b != 0 {
+            let r = a % b;
+            a = b;
+            b = r;
+        }
+        a.abs()
+    }
+
+    #[test]
+    fn test_gcd_lcm() {
+        assert_eq!(gcd(12u32, 18), 6);
+        assert_eq!(gcd(-12i64, 18), 6);
+        assert_eq!(gcd(0u8, 0), 0);
+        assert_eq!(gcd(0i16, -7), 7);
+        assert_eq!(gcd(u64::MAX, u64::MAX - 1), 1);
+        assert_eq!(gcd(1u128 << 100, 3 << 90), 1 << 90);
+        assert_eq!(lcm(4usize, 6), 12);
+        assert_eq!(lcm(-4i32, 6), 12);
+        assert_eq!(lcm(0u16, 6), 0);
+        let mut rng = Rng::new(103);
+        for _ in 0..1000 {
+            let shift = rng.index(40) as u32;
+            let a = (rng.next_u64() >> shift) as i64 * if rng.chance(0.5) { -1 } else { 1 };
+            let b = (rng.next_u64() >> rng.index(64)) as i64;
+            assert_eq!(gcd(a, b) as i128, euclid(a as i128, b as i128));
+            let (a, b) = (a as u64 as u32, b as u32);
+            assert_eq!(gcd(a, b) as i128, euclid(a as i128, b as i128));
+        }
+    }
+
+    #[test]
+    fn test_extended_gcd() {
+        assert_eq!(extended_gcd(240i32, 46), (2, -9, 47));
+        assert_eq!(extended_gcd(0i64, 0), (0, 1, 0));
+        assert_eq!(extended_gcd(0i64, -5), (5, 0, -1));
+        let mut rng = Rng::new(104);
+        for _ in 0..1000 {
+            let a = rng.range(-1_000_000_000..1_000_000_000);
+            let b = rng.range(-1_000_000_000..1_000_000_000);
+            let (g, x, y) = extended_gcd(a, b);
+            assert_eq!(g, gcd(a, b));
+            assert_eq!(a * x + b * y, g);
+            if g != 0 {
+                assert!(x.abs() <= (b / g).abs().max(1) && y.abs() <= (a / g).abs().max(1));
+            }
+        }
+    }
+}
@@ -0,0 +1,327 @@
+use std::ops::Div;
+
+use super::matrix::{Matrix, Ring};
+use super::modular::ModInt;
+
+// below this, a float counts as zero when picking pivots.
+const EPSILON: f64 = 1e-10;
+
+/// the elements gaussian elimination works over: a ring with division.
+pub trait Field: Ring + Div<Output = Self> {
+    /// whether the value is zero, or close enough that it cannot pivot.
+    fn is_zero(self) -> bool;
+    /// how good a pivot the value is, largest first.
+    fn magnitude(self) -> f64;
+}
+
+impl Field for f64 {
+    fn is_zero(self) -> bool {
+        self.abs() < EPSILON
+    }
+
+    fn magnitude(self) -> f64 {
+        self.abs()
+    }
+}
+
+impl Field for f32 {
+    fn is_zero(self) -> bool {
+        (self.abs() as f64) < EPSILON
+    }
+
+    fn magnitude(self) -> f64 {
+        self.abs() as f64
+    }
+}
+
+/// for a prime `M` only; with `M = 2` this is gf(2).
+impl<const M: u64> Field for ModInt<M> {
+    fn is_zero(self) -> bool {
+        self.value() == 0
+    }
+
+    fn magnitude(self) -> f64 {
+        if self.is_zero() {
+            0.0
+        } else {
+            1.0
+        }
+    }
+}
+
+/// a solution of a linear system.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Solution<T> {
+    /// one solution, with every free variable zero.
+    pub values: Vec<T>,
+    /// the rank of the coefficient matrix.
+    pub rank: usize,
+    /// the variables that can take any value, the others following from
+    /// them; empty iff the solution is unique.
+    pub free: Vec<usize>,
+}
+
+// bring the first `columns` columns of `m` to reduced row echelon form,
+// picking the pivot of largest magnitude in each column. returns the pivot
+// columns and the determinant of those columns if they are square.
+fn reduce<T: Field>(m: &mut Matrix<T>, columns: usize) -> (Vec<usize>, T) {
+    let mut pivots = Vec::new();
+    let mut determinant = T::one();
+    for col in 0..columns {
+        let row = pivots.len();
+        if row == m.rows() {
+            break;
+        }
+        let best = (row..m.rows())
+            .max_by(|&a, &b| m[(a, col)].magnitude().total_cmp(&m[(b, col)].magnitude()))
+            .unwrap();
+        if m[(best, col)].is_zero() {
+            determinant = T::zero();
+            continue;
+        }
+        if best != row {
+            m.swap_rows(best, row);
+            determinant = T::zero() - determinant;
+        }
+        let pivot = m[(row, col)];
+        determinant = determinant * pivot;
+        for j in col..m.cols() {
+            m[(row, j)] = m[(row, j)] / pivot;
+        }
+        for r in 0..m.rows() {
+            let factor = m[(r, col)];
+            if r == row || factor.is_zero() {
+                continue;
+            }
+            for j in col..m.cols() {
+                m[(r, j)] = m[(r, j)] - factor * m[(row, j)];
+            }
+        }
+        pivots.push(col);
+    }
+    if pivots.len() < columns {
+        determinant = T::zero();
+    }
+    (pivots, determinant)
+}
+
+/// the rank of `m`, by gaussian elimination in O(r * c * min(r, c)).
+pub fn rank<T: Field>(m: &Matrix<T>) -> usize {
+    let mut m = m.clone();
+    let columns = m.cols();
+    reduce(&mut m, columns).0.len()
+}
+
+/// the determinant of the square matrix `m`, in O(n^3).
+pub fn determinant<T: Field>(m: &Matrix<T>) -> T {
+    assert_eq!(m.rows(), m.cols(), "only square matrices have determinants");
+    let mut m = m.clone();
+    let n = m.cols();
+    reduce(&mut m, n).1
+}
+
+/// solve `a * x = b` by gaussian elimination with partial pivoting, in
+/// O(r * c * min(r, c)); `None` if there is no solution.
+pub fn solve<T: Field>(a: &Matrix<T>, b: &[T]) -> Option<Solution<T>> {
+    assert_eq!(a.rows(), b.len(), "one right-hand side per equation");
+    let n = a.cols();
+    let mut augmented = Matrix::new(a.rows(), n + 1);
+    for i in 0..a.rows() {
+        for j in 0..n {
+            augmented[(i, j)] = a[(i, j)];
+        }
+        augmented[(i, n)] = b[i];
+    }
+    let (pivots, _) = reduce(&mut augmented, n);
+    // a row left with no pivot must come out as 0 = 0.
+    if (pivots.len()..a.rows()).any(|i| !augmented[(i, n)].is_zero()) {
+        return None;
+    }
+    let mut values = vec![T::zero(); n];
+    for (row, &col) in pivots.iter().enumerate() {
+        values[col] = augmented[(row, n)];
+    }
+    let free = (0..n).filter(|col| !pivots.contains(col)).collect();
+    Some(Solution {
+        values,
+        rank: pivots.len(),
+        free,
+    })
+}
+
+/// solve `a * x = b` over gf(2), with the rows packed 64 bits to a word so
+/// that eliminating a row is a run of xors: O(r * c * min(r, c) / 64).
+pub fn solve_gf2(a: &[Vec<bool>], b: &[bool]) -> Option<Solution<bool>> {
+    assert_eq!(a.len(), b.len(), "one right-hand side per equation");
+    let n = a.first().map_or(0, Vec::len);
+    assert!(a.iter().all(|row| row.len() == n), "rows differ in length");
+    let words = (n + 1).div_ceil(64);
+    let bit = |row: &[u64], j: usize| row[j / 64] >> (j % 64) & 1 == 1;
+    // column `n` holds the right-hand side.
+    let mut rows: Vec<Vec<u64>> = a
+        .iter()
+        .zip(b)
+        .map(|(coefficients, &rhs)| {
+            let mut packed = vec![0u64; words];
+            for (j, &set) in coefficients.iter().chain(std::iter::once(&rhs)).enumerate() {
+                packed[j / 64] |= (set as u64) << (j % 64);
+            }
+            packed
+        })
+        .collect();
+    let mut pivots = Vec::new();
+    for col in 0..n {
+        let row = pivots.len();
+        let found = match (row..rows.len()).find(|&r| bit(&rows[r], col)) {
+            Some(found) => found,
+            None => continue,
+        };
+        rows.swap(row, found);
+        let pivot = rows[row].clone();
+        for (r, other) in rows.iter_mut().enumerate() {
+            if r != row && bit(other, col) {
+                other.iter_mut().zip(&pivot).for_each(|(w, p)| *w ^= p);
+            }
+        }
+        pivots.push(col);
+    }
+    if rows[pivots.len()..].iter().any(|row| bit(row, n)) {
+        return None;
+    }
+    let mut values = vec![false; n];
+    for (row, &col) in pivots.iter().enumerate() {
+        values[col] = bit(&rows[row], n);
+    }
+    let free = (0..n).filter(|col| !pivots.contains(col)).collect();
+    Some(Solution {
+        values,
+        rank: pivots.len(),
+        free,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    type F = ModInt<1_000_000_007>;
+
+    // the determinant as a sum over permutations, for small matrices.
+    fn leibniz(m: &Matrix<F>) -> F {
+        fn go(m: &Matrix<F>, row: usize, used: &mut Vec<bool>, sign: bool) -> F {
+            if row == m.rows() {
+                return if sign { F::new(1) } else { -F::new(1) };
+            }
+            let mut total = F::new(0);
+            for col in 0..m.cols() {
+                if used[col] {
+                    continue;
+                }
+                // each used column to the right is one inversion.
+                let inversions = used[col..].iter().filter(|&&u| u).count();
+                used[col] = true;
+                total += m[(row, col)] * go(m, row + 1, used, sign ^ (inversions % 2 == 1));
+                used[col] = false;
+            }
+            total
+        }
+        go(m, 0, &mut vec![false; m.cols()], true)
+    }
+
+    #[test]
+    fn test_floats() {
+        let a: Matrix<f64> = Matrix::from_rows(vec![
+            vec![2.0, 1.0, -1.0],
+            vec![-3.0, -1.0, 2.0],
+            vec![-2.0, 1.0, 2.0],
+        ]);
+        let solution = solve(&a, &[8.0, -11.0, -3.0]).unwrap();
+        let expected = [2.0, 3.0, -1.0];
+        assert!(solution
+            .values
+            .iter()
+            .zip(&expected)
+            .all(|(x, y)| (x - y).abs() < 1e-9));
+        assert_eq!((solution.rank, solution.free.len()), (3, 0));
+        assert!((determinant(&a) + 1.0).abs() < 1e-9);
+        // a needs a row swap to pivot at all.
+        let swap = Matrix::from_rows(vec![vec![0.0, 1.0], vec![1.0, 0.0]]);
+        assert_eq!(determinant(&swap), -1.0);
+        let singular = Matrix::from_rows(vec![vec![1.0, 2.0], vec![2.0, 4.0]]);
+        assert_eq!(rank(&singular), 1);
+        assert_eq!(determinant(&singular), 0.0);
+        assert!(solve(&singular, &[1.0, 3.0]).is_none());
+        let many = solve(&singular, &[1.0, 2.0]).unwrap();
+        assert_eq!(
+            (many.values, many.rank, many.free),
+            (vec![1.0, 0.0], 1, vec![1])
+        );
+    }
+
+    #[test]
+    fn test_mod_prime() {
+        let mut rng = Rng::new(111);
+        for _ in 0..100 {
+            let (rows, cols) = (1 + rng.index(5), 1 + rng.index(5));
+            let mut entry = || F::new(if rng.chance(0.3) { 0 } else { rng.below(5) });
+            let a = Matrix::from_rows(
+                (0..rows)
+                    .map(|_| (0..cols).map(|_| entry()).collect())
+                    .collect(),
+            );
+            if rows == cols {
+                assert_eq!(determinant(&a), leibniz(&a));
+                assert_eq!(rank(&a) == rows, determinant(&a) != F::new(0));
+            }
+            let x: Vec<F> = (0..cols).map(|_| F::new(rng.below(10))).collect();
+            let b = a.mul_vector(&x);
+            let solution = solve(&a, &b).unwrap();
+            assert_eq!(a.mul_vector(&solution.values), b);
+            assert_eq!(solution.rank, rank(&a));
+            assert_eq!(solution.rank + solution.free.len(), cols);
+            assert!(solution
+                .free
+                .iter()
+                .all(|&j| solution.values[j] == F::new(0)));
+        }
+    }
+
+    #[test]
+    fn test_gf2() {
+        let mut rng = Rng::new(112);
+        for _ in 0..200 {
+            let (rows, cols) = (1 + rng.index(7), 1 + rng.index(7));
+            let a: Vec<Vec<bool>> = (0..rows)
+                .map(|_| (0..cols).map(|_| rng.chance(0.5)).collect())
+                .collect();
+            let b: Vec<bool> = (0..rows).map(|_| rng.chance(0.5)).collect();
+            let satisfies = |x: &[bool]| {
+                a.iter().zip(&b).all(|(row, &rhs)| {
+                    row.iter().zip(x).filter(|&(&c, &v)| c && v).count() % 2 == rhs as usize
+                })
+            };
+            let count = (0..1u32 << cols)
+                .filter(|mask| {
+                    satisfies(&(0..cols).map(|j| mask >> j & 1 == 1).collect::<Vec<_>>())
+                })
+                .count();
+            // agrees with the generic elimination over ModInt<2>.
+            let generic = Matrix::from_rows(
+                a.iter()
+                    .map(|row| row.iter().map(|&c| ModInt::<2>::new(c as u64)).collect())
+                    .collect(),
+            );
+            match solve_gf2(&a, &b) {
+                Some(solution) => {
+                    assert!(satisfies(&solution.values));
+                    assert_eq!(count, 1 << solution.free.len());
+                    assert_eq!(solution.rank, rank(&generic));
+                }
+                None => assert_eq!(count, 0),
+            }
+        }
+        let wide = vec![vec![true; 130]];
+        assert_eq!(solve_gf2(&wide, &[true]).unwrap().free.len(), 129);
+    }
+}
@@ -0,0 +1,284 @@
+use std::fmt::Debug;
+use std::ops::{Add, Index, IndexMut, Mul, Sub};
+
+use super::modular::ModInt;
+
+/// the elements a matrix can hold: numbers with addition, subtraction and
+/// multiplication, and their identities.
+pub trait Ring:
+    Copy + PartialEq + Debug + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+{
+    fn zero() -> Self;
+    fn one() -> Self;
+}
+
+macro_rules! ring {
+    ($($t:ty => $zero:expr, $one:expr;)*) => {
+        $(
+            impl Ring for $t {
+                fn zero() -> $t {
+                    $zero
+                }
+
+                fn one() -> $t {
+                    $one
+                }
+            }
+        )*
+    };
+}
+
+ring! {
+    u8 => 0, 1; u16 => 0, 1; u32 => 0, 1; u64 => 0, 1; u128 => 0, 1; usize => 0, 1;
+    i8 => 0, 1; i16 => 0, 1; i32 => 0, 1; i64 => 0, 1; i128 => 0, 1; isize => 0, 1;
+    f32 => 0.0, 1.0; f64 => 0.0, 1.0;
+}
+
+impl<const M: u64> Ring for ModInt<M> {
+    fn zero() -> Self {
+        ModInt::new(0)
+    }
+
+    fn one() -> Self {
+        ModInt::new(1)
+    }
+}
+
+/// a dense matrix, stored row by row.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Matrix<T> {
+    rows: usize,
+    cols: usize,
+    data: Vec<T>,
+}
+
+impl<T: Ring> Matrix<T> {
+    /// the zero matrix.
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Matrix {
+            rows,
+            cols,
+            data: vec![T::zero(); rows * cols],
+        }
+    }
+
+    pub fn identity(n: usize) -> Self {
+        let mut identity = Matrix::new(n, n);
+        for i in 0..n {
+            identity[(i, i)] = T::one();
+        }
+        identity
+    }
+
+    /// panics unless all rows have the same length.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Self {
+        let cols = rows.first().map_or(0, Vec::len);
+        assert!(
+            rows.iter().all(|row| row.len() == cols),
+            "rows differ in length"
+        );
+        Matrix {
+            rows: rows.len(),
+            cols,
+            data: rows.into_iter().flatten().collect(),
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn row(&self, i: usize) -> &[T] {
+        &self.data[i * self.cols..(i + 1) * self.cols]
+    }
+
+    pub fn swap_rows(&mut self, a: usize, b: usize) {
+        for j in 0..self.cols {
+            self.data.swap(a * self.cols + j, b * self.cols + j);
+        }
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut transposed = Matrix::new(self.cols, self.rows);
+        for i in 0..self.rows {
+            for j in 0..self.cols {
+                transposed[(j, i)] = self[(i, j)];
+            }
+        }
+        transposed
+    }
+
+    /// the product with the column vector `v`.
+    pub fn mul_vector(&self, v: &[T]) -> Vec<T> {
+        assert_eq!(v.len(), self.cols, "dimension mismatch");
+        (0..self.rows)
+            .map(|i| {
+                self.row(i)
+                    .iter()
+                    .zip(v)
+                    .fold(T::zero(), |sum, (&a, &b)| sum + a * b)
+            })
+            .collect()
+    }
+
+    /// the matrix to the power `exp`, by repeated squaring: O(k^3 log exp)
+    /// for a k by k matrix.
+    pub fn pow(&self, mut exp: u64) -> Self {
+        assert_eq!(self.rows, self.cols, "only square matrices have powers");
+        let mut result = Matrix::identity(self.rows);
+        let mut base = self.clone();
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = &result * &base;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = &base * &base;
+            }
+        }
+        result
+    }
+}
+
+impl<T> Index<(usize, usize)> for Matrix<T> {
+    type Output = T;
+
+    fn index(&self, (i, j): (usize, usize)) -> &T {
+        assert!(i < self.rows && j < self.cols, "index out of bounds");
+        &self.data[i * self.cols + j]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Matrix<T> {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut T {
+        assert!(i < self.rows && j < self.cols, "index out of bounds");
+        &mut self.data[i * self.cols + j]
+    }
+}
+
+impl<T: Ring> Mul for &Matrix<T> {
+    type Output = Matrix<T>;
+
+    fn mul(self, other: &Matrix<T>) -> Matrix<T> {
+        assert_eq!(self.cols, other.rows, "dimension mismatch");
+        let mut product = Matrix::new(self.rows, other.cols);
+        for i in 0..self.rows {
+            for k in 0..self.cols {
+                let a = self[(i, k)];
+                if a == T::zero() {
+                    continue;
+                }
+                for j in 0..other.cols {
+                    product[(i, j)] = product[(i, j)] + a * other[(k, j)];
+                }
+            }
+        }
+        product
+    }
+}
+
+impl<T: Ring> Add for &Matrix<T> {
+    type Output = Matrix<T>;
+
+    fn add(self, other: &Matrix<T>) -> Matrix<T> {
+        assert!(
+            self.rows == other.rows && self.cols == other.cols,
+            "dimension mismatch"
+        );
+        Matrix {
+            rows: self.rows,
+            cols: self.cols,
+            data: self
+                .data
+                .iter()
+                .zip(&other.data)
+                .map(|(&a, &b)| a + b)
+                .collect(),
+        }
+    }
+}
+
+/// the term `n` of the linear recurrence `a[k] = c[0] * a[k - 1] + ... +
+/// c[d - 1] * a[k - d]` starting from `initial = a[0..d]`, by raising its
+/// companion matrix to a power: O(d^3 log n).
+pub fn linear_recurrence<T: Ring>(coefficients: &[T], initial: &[T], n: u64) -> T {
+    let d = coefficients.len();
+    assert!(d > 0, "a recurrence needs at least one term");
+    assert_eq!(initial.len(), d, "one initial term per coefficient");
+    if n < d as u64 {
+        return initial[n as usize];
+    }
+    // maps (a[k - 1], ..., a[k - d]) to (a[k], ..., a[k - d + 1]).
+    let mut companion = Matrix::new(d, d);
+    for (j, &c) in coefficients.iter().enumerate() {
+        companion[(0, j)] = c;
+    }
+    for i in 1..d {
+        companion[(i, i - 1)] = T::one();
+    }
+    let last: Vec<T> = initial.iter().rev().copied().collect();
+    companion.pow(n - (d as u64 - 1)).mul_vector(&last)[0]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::numeric::modular::ModInt1000000007;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_matrix() {
+        let a = Matrix::from_rows(vec![vec![1, 2, 3], vec![4, 5, 6]]);
+        let b = a.transpose();
+        assert_eq!(&a * &b, Matrix::from_rows(vec![vec![14, 32], vec![32, 77]]));
+        assert_eq!(a.mul_vector(&[1, 0, -1]), [-2, -2]);
+        assert_eq!(&a * &Matrix::identity(3), a);
+        assert_eq!(
+            &a + &a,
+            Matrix::from_rows(vec![vec![2, 4, 6], vec![8, 10, 12]])
+        );
+        assert_eq!(Matrix::<i32>::from_rows(vec![]).rows(), 0);
+        type F = ModInt1000000007;
+        let mut rng = Rng::new(110);
+        for _ in 0..20 {
+            let k = 1 + rng.index(5);
+            let rows = (0..k)
+                .map(|_| (0..k).map(|_| F::new(rng.next_u64())).collect())
+                .collect();
+            let m = Matrix::from_rows(rows);
+            let exp = rng.below(30);
+            let mut slow = Matrix::identity(k);
+            for _ in 0..exp {
+                slow = &slow * &m;
+            }
+            assert_eq!(m.pow(exp), slow);
+        }
+    }
+
+    #[test]
+    fn test_linear_recurrence() {
+        let fib = |n| linear_recurrence(&[1u64, 1], &[0, 1], n);
+        assert_eq!(
+            (0..10).map(fib).collect::<Vec<_>>(),
+            [0, 1, 1, 2, 3, 5, 8, 13, 21, 34]
+        );
+        assert_eq!(fib(90), 2_880_067_194_370_816_120);
+        // tribonacci, checked against direct iteration modulo a prime.
+        type F = ModInt1000000007;
+        let (c, start) = ([F::new(1); 3], [F::new(0), F::new(0), F::new(1)]);
+        let mut terms = start.to_vec();
+        for k in 3..500 {
+            let next = terms[k - 1] + terms[k - 2] + terms[k - 3];
+            terms.push(next);
+        }
+        assert!((0..500).all(|n| linear_recurrence(&c, &start, n) == terms[n as usize]));
+        // the fibonacci numbers modulo 10^9 + 7 repeat every 2 * 10^9 + 16.
+        let fib_mod = |n| linear_recurrence(&[F::new(1), F::new(1)], &[F::new(0), F::new(1)], n);
+        assert_eq!(fib_mod(2_000_000_016 + 77), fib_mod(77));
+        assert_eq!(linear_recurrence(&[2.0], &[1.0], 10), 1024.0);
+    }
+}
@@ -0,0 +1,78 @@
+use std::fmt::Debug;
+use std::ops::{Add, Div, Mul, Rem, Shl, Shr, Sub};
+
+pub mod combinatorics;
+pub mod fft;
+pub mod gcd;
+pub mod linear;
+pub mod matrix;
+pub mod modular;
+pub mod primality;
+pub mod sieve;
+pub mod simplex;
+
+/// the primitive integer types, for the number theory of this module.
+pub trait Integer:
+    Copy
+    + Ord
+    + Debug
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Rem<Output = Self>
+    + Shl<u32, Output = Self>
+    + Shr<u32, Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+    /// the absolute value; overflows on the least value of a signed type.
+    fn abs(self) -> Self;
+    fn trailing_zeros(self) -> u32;
+}
+
+/// the signed primitive integer types.
+pub trait Signed: Integer + std::ops::Neg<Output = Self> {}
+
+macro_rules! unsigned_integer {
+    ($($t:ty),*) => {
+        $(
+            impl Integer for $t {
+                const ZERO: $t = 0;
+                const ONE: $t = 1;
+
+                fn abs(self) -> $t {
+                    self
+                }
+
+                fn trailing_zeros(self) -> u32 {
+                    <$t>::trailing_zeros(self)
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! signed_integer {
+    ($($t:ty),*) => {
+        $(
+            impl Integer for $t {
+                const ZERO: $t = 0;
+                const ONE: $t = 1;
+
+                fn abs(self) -> $t {
+                    <$t>::abs(self)
+                }
+
+                fn trailing_zeros(self) -> u32 {
+                    <$t>::trailing_zeros(self)
+                }
+            }
+
+            impl Signed for $t {}
+        )*
+    };
+}
+
+unsigned_integer!(u8, u16, u32, u64, u128, usize);
+signed_integer!(i8, i16, i32, i64, i128, isize);
@@ -0,0 +1,275 @@
+use std::fmt;
+use std::iter::{Product, Sum};
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use super::gcd::{extended_gcd, gcd};
+
+/// `a * b % m`, through 128 bits so nothing overflows.
+pub fn mod_mul(a: u64, b: u64, m: u64) -> u64 {
+    (a as u128 * b as u128 % m as u128) as u64
+}
+
+/// `base` to the power `exp`, modulo `m`, by squaring: O(log exp).
+pub fn mod_pow(mut base: u64, mut exp: u64, m: u64) -> u64 {
+    assert!(m > 0, "modulus must be positive");
+    let mut result = 1 % m;
+    base %= m;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mod_mul(result, base, m);
+        }
+        base = mod_mul(base, base, m);
+        exp >>= 1;
+    }
+    result
+}
+
+/// the `x` in `0..m` with `a * x % m == 1`, which exists iff `a` and `m`
+/// are coprime.
+pub fn mod_inverse(a: u64, m: u64) -> Option<u64> {
+    assert!(m > 0, "modulus must be positive");
+    let (g, x, _) = extended_gcd((a % m) as i128, m as i128);
+    if g == 1 {
+        Some(x.rem_euclid(m as i128) as u64)
+    } else {
+        None
+    }
+}
+
+/// combine congruences `x ≡ r (mod m)`, given as `(r, m)`, by the chinese
+/// remainder theorem into a single `(x, lcm of the moduli)`, with `x` the
+/// least solution. the moduli need not be coprime; `None` if the
+/// congruences contradict each other. panics if the lcm overflows.
+pub fn crt(congruences: &[(u64, u64)]) -> Option<(u64, u64)> {
+    let (mut x, mut m) = (0u128, 1u128);
+    for &(r, n) in congruences {
+        assert!(n > 0, "modulus must be positive");
+        let (r, n) = (r as u128 % n as u128, n as u128);
+        // x + m * t ≡ r (mod n), solvable iff g divides r - x.
+        let g = gcd(m, n);
+        let diff = (r + n - x % n) % n;
+        if diff % g != 0 {
+            return None;
+        }
+        let (step, reduced) = (m / g, n / g);
+        let inverse = mod_inverse((step % reduced) as u64, reduced as u64).unwrap() as u128;
+        let t = diff / g * inverse % reduced;
+        let lcm = m * reduced;
+        assert!(lcm <= u64::MAX as u128, "lcm of the moduli overflows");
+        x = (x + m * t) % lcm;
+        m = lcm;
+    }
+    Some((x as u64, m as u64))
+}
+
+/// an integer modulo `M`, always kept in `0..M`. division multiplies by
+/// the inverse, and panics if there is none.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ModInt<const M: u64> {
+    value: u64,
+}
+
+/// modulo the prime 998244353, the usual choice for number theoretic
+/// transforms.
+pub type ModInt998244353 = ModInt<998_244_353>;
+/// modulo the prime 10^9 + 7.
+pub type ModInt1000000007 = ModInt<1_000_000_007>;
+
+impl<const M: u64> ModInt<M> {
+    pub fn new(value: u64) -> Self {
+        ModInt { value: value % M }
+    }
+
+    pub fn value(self) -> u64 {
+        self.value
+    }
+
+    pub fn pow(self, exp: u64) -> Self {
+        ModInt {
+            value: mod_pow(self.value, exp, M),
+        }
+    }
+
+    pub fn inverse(self) -> Option<Self> {
+        mod_inverse(self.value, M).map(|value| ModInt { value })
+    }
+}
+
+impl<const M: u64> From<u64> for ModInt<M> {
+    fn from(value: u64) -> Self {
+        ModInt::new(value)
+    }
+}
+
+impl<const M: u64> From<i64> for ModInt<M> {
+    fn from(value: i64) -> Self {
+        ModInt {
+            value: (value as i128).rem_euclid(M as i128) as u64,
+        }
+    }
+}
+
+impl<const M: u64> fmt::Display for ModInt<M> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+impl<const M: u64> Add for ModInt<M> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        let sum = self.value as u128 + other.value as u128;
+        ModInt {
+            value: (sum % M as u128) as u64,
+        }
+    }
+}
+
+impl<const M: u64> Sub for ModInt<M> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self + -other
+    }
+}
+
+impl<const M: u64> Neg for ModInt<M> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        ModInt {
+            value: (M - self.value) % M,
+        }
+    }
+}
+
+impl<const M: u64> Mul for ModInt<M> {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        ModInt {
+            value: mod_mul(self.value, other.value, M),
+        }
+    }
+}
+
+impl<const M: u64> Div for ModInt<M> {
+    type Output = Self;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, other: Self) -> Self {
+        self * other.inverse().expect("divisor has no inverse")
+    }
+}
+
+macro_rules! assign_ops {
+    ($($trait:ident, $method:ident, $op:tt;)*) => {
+        $(
+            impl<const M: u64> $trait for ModInt<M> {
+                fn $method(&mut self, other: Self) {
+                    *self = *self $op other;
+                }
+            }
+        )*
+    };
+}
+
+assign_ops! {
+    AddAssign, add_assign, +;
+    SubAssign, sub_assign, -;
+    MulAssign, mul_assign, *;
+    DivAssign, div_assign, /;
+}
+
+impl<const M: u64> Sum for ModInt<M> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(ModInt::new(0), Add::add)
+    }
+}
+
+impl<const M: u64> Product for ModInt<M> {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(ModInt::new(1), Mul::mul)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_pow_and_inverse() {
+        assert_eq!(mod_pow(2, 10, 1000), 24);
+        assert_eq!(mod_pow(7, 0, 1), 0);
+        assert_eq!(
+            mod_pow(u64::MAX, u64::MAX, u64::MAX - 58),
+            mod_pow(58, u64::MAX, u64::MAX - 58)
+        );
+        assert_eq!(mod_inverse(3, 7), Some(5));
+        assert_eq!(mod_inverse(4, 8), None);
+        assert_eq!(mod_inverse(5, 1), Some(0));
+        let mut rng = Rng::new(105);
+        for _ in 0..500 {
+            let m = 1 + rng.below(200);
+            let (a, e) = (rng.below(1000), rng.below(20));
+            let brute = (0..e).fold(1 % m, |p, _| p * (a % m) % m);
+            assert_eq!(mod_pow(a, e, m), brute);
+            let inverse = (0..m).find(|&x| a % m * x % m == 1 % m);
+            assert_eq!(
+                mod_inverse(a, m),
+                if gcd(a, m) == 1 { inverse } else { None }
+            );
+        }
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(crt(&[(1, 4), (3, 6)]), Some((9, 12)));
+        assert_eq!(crt(&[(1, 4), (2, 6)]), None);
+        assert_eq!(crt(&[]), Some((0, 1)));
+        let mut rng = Rng::new(106);
+        for _ in 0..300 {
+            let congruences: Vec<(u64, u64)> = (0..1 + rng.index(3))
+                .map(|_| (rng.below(100), 1 + rng.below(30)))
+                .collect();
+            let lcm = congruences.iter().fold(1, |l, &(_, m)| l / gcd(l, m) * m);
+            let brute = (0..lcm).find(|x| congruences.iter().all(|&(r, m)| x % m == r % m));
+            assert_eq!(crt(&congruences), brute.map(|x| (x, lcm)));
+        }
+    }
+
+    #[test]
+    fn test_mod_int() {
+        type F = ModInt<13>;
+        for a in 0..13u64 {
+            for b in 0..13u64 {
+                let (x, y) = (F::new(a), F::new(b));
+                assert_eq!((x + y).value(), (a + b) % 13);
+                assert_eq!((x - y).value(), (a + 13 - b) % 13);
+                assert_eq!((x * y).value(), a * b % 13);
+                if b != 0 {
+                    assert_eq!(x / y * y, x);
+                }
+            }
+        }
+        assert_eq!(F::from(-1i64).value(), 12);
+        assert_eq!(F::new(0).inverse(), None);
+        let mut x = ModInt1000000007::new(1_000_000_006);
+        x += ModInt::new(5);
+        x *= ModInt::new(3);
+        assert_eq!(x.to_string(), "12");
+        let factorial: ModInt998244353 = (1..=20u64).map(ModInt::new).product();
+        assert_eq!(
+            factorial.value(),
+            (1..=20u64).fold(1, |f, i| f * i % 998_244_353)
+        );
+        assert_eq!((1..=4u64).map(F::new).sum::<F>(), F::new(10));
+        assert_eq!(
+            ModInt::<{ u64::MAX }>::new(u64::MAX - 1) + ModInt::new(5),
+            ModInt::new(4)
+        );
+    }
+}
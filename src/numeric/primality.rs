@@ -0,0 +1,182 @@
+use super::gcd::gcd;
+use super::modular::{mod_mul, mod_pow};
+
+// these bases make miller-rabin exact for every 64-bit number.
+const BASES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+/// deterministic miller-rabin: O(log n) multiplications for each of
+/// twelve bases, which between them leave no 64-bit strong pseudoprime.
+pub fn is_prime(n: u64) -> bool {
+    if n < 2 {
+        return false;
+    }
+    for &p in &BASES {
+        if n.is_multiple_of(p) {
+            return n == p;
+        }
+    }
+    // n - 1 = d * 2^s with d odd.
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+    BASES.iter().all(|&a| {
+        let mut x = mod_pow(a, d, n);
+        if x == 1 || x == n - 1 {
+            return true;
+        }
+        for _ in 1..s {
+            x = mod_mul(x, x, n);
+            if x == n - 1 {
+                return true;
+            }
+        }
+        false
+    })
+}
+
+/// the prime factorization of `n >= 1` as `(prime, exponent)` pairs by
+/// increasing prime, splitting off small primes by trial division and the
+/// rest by pollard's rho, in about O(n^(1/4)) expected per factor.
+pub fn factorize(n: u64) -> Vec<(u64, u32)> {
+    assert!(n >= 1, "zero has no factorization");
+    let mut primes = Vec::new();
+    let mut rest = n;
+    for p in 2..100 {
+        while rest.is_multiple_of(p) {
+            primes.push(p);
+            rest /= p;
+        }
+    }
+    split(rest, &mut primes);
+    primes.sort_unstable();
+    let mut factors: Vec<(u64, u32)> = Vec::new();
+    for p in primes {
+        match factors.last_mut() {
+            Some((last, exponent)) if *last == p => *exponent += 1,
+            _ => factors.push((p, 1)),
+        }
+    }
+    factors
+}
+
+// push the prime factors of `n`, which has none below 100.
+fn split(n: u64, primes: &mut Vec<u64>) {
+    if n == 1 {
+        return;
+    }
+    if is_prime(n) {
+        primes.push(n);
+        return;
+    }
+    let d = pollard_rho(n);
+    split(d, primes);
+    split(n / d, primes);
+}
+
+// a proper divisor of the odd composite `n`, by brent's variant of
+// pollard's rho: the differences are multiplied together in batches, so
+// most steps skip the gcd.
+fn pollard_rho(n: u64) -> u64 {
+    const BATCH: u64 = 128;
+    for c in 1.. {
+        let step = |x: u64| ((mod_mul(x, x, n) as u128 + c) % n as u128) as u64;
+        let (mut x, mut y, mut saved) = (2, 2, 2);
+        let (mut g, mut product, mut r) = (1, 1, 1);
+        while g == 1 {
+            x = y;
+            for _ in 0..r {
+                y = step(y);
+            }
+            let mut k = 0;
+            while k < r && g == 1 {
+                saved = y;
+                for _ in 0..BATCH.min(r - k) {
+                    y = step(y);
+                    product = mod_mul(product, x.abs_diff(y), n);
+                }
+                g = gcd(product, n);
+                k += BATCH;
+            }
+            r *= 2;
+        }
+        if g == n {
+            // the batch overshot: redo it one step at a time.
+            loop {
+                saved = step(saved);
+                g = gcd(x.abs_diff(saved), n);
+                if g > 1 {
+                    break;
+                }
+            }
+        }
+        if g != n {
+            return g;
+        }
+    }
+    unreachable!()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::numeric::sieve::Sieve;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_is_prime() {
+        let sieve = Sieve::new(100_000);
+        assert!((0..100_000).all(|n| is_prime(n as u64) == sieve.is_prime(n)));
+        assert!(is_prime(u64::MAX - 58));
+        assert!(is_prime((1 << 61) - 1));
+        assert!(is_prime(1_000_000_007));
+        // the least strong pseudoprimes to the first few bases together.
+        for &n in &[
+            2047,
+            1_373_653,
+            25_326_001,
+            3_215_031_751,
+            2_152_302_898_747,
+            3_474_749_660_383,
+            341_550_071_728_321,
+            3_825_123_056_546_413_051,
+        ] {
+            assert!(!is_prime(n));
+        }
+        assert!(!is_prime(u64::MAX));
+    }
+
+    #[test]
+    fn test_factorize() {
+        assert_eq!(factorize(1), []);
+        assert_eq!(factorize(360), [(2, 3), (3, 2), (5, 1)]);
+        assert_eq!(
+            factorize(u64::MAX),
+            [
+                (3, 1),
+                (5, 1),
+                (17, 1),
+                (257, 1),
+                (641, 1),
+                (65_537, 1),
+                (6_700_417, 1)
+            ]
+        );
+        assert_eq!(
+            factorize(4_294_967_291 * 4_294_967_279),
+            [(4_294_967_279, 1), (4_294_967_291, 1)]
+        );
+        assert_eq!(factorize(1 << 63), [(2, 63)]);
+        assert_eq!(factorize(999_999_937 * 999_999_937), [(999_999_937, 2)]);
+        let mut rng = Rng::new(107);
+        for _ in 0..200 {
+            let n = rng.next_u64() >> rng.index(40);
+            if n == 0 {
+                continue;
+            }
+            let factors = factorize(n);
+            assert!(factors.windows(2).all(|w| w[0].0 < w[1].0));
+            assert!(factors.iter().all(|&(p, _)| is_prime(p)));
+            let product = factors.iter().fold(1u64, |acc, &(p, e)| acc * p.pow(e));
+            assert_eq!(product, n);
+        }
+    }
+}
@@ -0,0 +1,268 @@
+use crate::bit_vector::BitVector;
+
+/// the sieve of eratosthenes over `0..limit`, in O(n log log n). the
+/// primes are kept as a rank/select bit vector, so counting the primes
+/// below a bound and finding the `k`-th prime are O(1) and near-O(1).
+pub struct Sieve {
+    primes: BitVector,
+}
+
+impl Sieve {
+    pub fn new(limit: usize) -> Self {
+        let mut prime = vec![true; limit];
+        prime.iter_mut().take(2).for_each(|p| *p = false);
+        let mut p = 2;
+        while p * p < limit {
+            if prime[p] {
+                for multiple in (p * p..limit).step_by(p) {
+                    prime[multiple] = false;
+                }
+            }
+            p += 1;
+        }
+        Sieve {
+            primes: prime.into_iter().collect(),
+        }
+    }
+
+    /// the sieve covers `0..limit()`.
+    pub fn limit(&self) -> usize {
+        self.primes.len()
+    }
+
+    pub fn is_prime(&self, n: usize) -> bool {
+        self.primes.get(n).expect("number past the sieve")
+    }
+
+    /// the number of primes below `n`, for `n` up to the limit.
+    pub fn count_below(&self, n: usize) -> usize {
+        self.primes.rank1(n)
+    }
+
+    /// the `k`-th prime (0-based), if it is below the limit.
+    pub fn nth(&self, k: usize) -> Option<usize> {
+        self.primes.select1(k)
+    }
+
+    pub fn primes(&self) -> impl Iterator<Item = usize> + '_ {
+        self.primes
+            .iter()
+            .enumerate()
+            .filter(|&(_, p)| p)
+            .map(|(n, _)| n)
+    }
+}
+
+/// the linear sieve over `0..limit`: every composite is crossed out once,
+/// by its smallest prime factor, which is kept. O(n), and then any number
+/// below the limit factors in O(log n).
+pub struct LinearSieve {
+    smallest: Vec<usize>,
+    primes: Vec<usize>,
+}
+
+impl LinearSieve {
+    pub fn new(limit: usize) -> Self {
+        let mut smallest = vec![0; limit];
+        let mut primes = Vec::new();
+        for i in 2..limit {
+            if smallest[i] == 0 {
+                smallest[i] = i;
+                primes.push(i);
+            }
+            for &p in &primes {
+                if p > smallest[i] || i * p >= limit {
+                    break;
+                }
+                smallest[i * p] = p;
+            }
+        }
+        LinearSieve { smallest, primes }
+    }
+
+    pub fn limit(&self) -> usize {
+        self.smallest.len()
+    }
+
+    pub fn is_prime(&self, n: usize) -> bool {
+        n >= 2 && self.smallest[n] == n
+    }
+
+    /// the least prime dividing `n`, for `n >= 2`.
+    pub fn smallest_factor(&self, n: usize) -> usize {
+        assert!(n >= 2, "{} has no prime factors", n);
+        self.smallest[n]
+    }
+
+    /// the primes below the limit, in order.
+    pub fn primes(&self) -> &[usize] {
+        &self.primes
+    }
+
+    /// the prime factorization of `n >= 1` as `(prime, exponent)` pairs, by
+    /// increasing prime.
+    pub fn factorize(&self, mut n: usize) -> Vec<(usize, u32)> {
+        assert!(n >= 1, "zero has no factorization");
+        let mut factors: Vec<(usize, u32)> = Vec::new();
+        while n > 1 {
+            let p = self.smallest[n];
+            match factors.last_mut() {
+                Some((last, exponent)) if *last == p => *exponent += 1,
+                _ => factors.push((p, 1)),
+            }
+            n /= p;
+        }
+        factors
+    }
+}
+
+// the width of each segment sieved at once.
+const SEGMENT: u64 = 1 << 16;
+
+/// the primes in `lo..hi`, by a segmented sieve: the primes up to the
+/// square root of `hi` cross out their multiples one segment at a time, so
+/// a range far from zero takes O(sqrt(hi) + segment) memory.
+pub fn segmented_primes(lo: u64, hi: u64) -> SegmentedPrimes {
+    let root = isqrt(hi.saturating_sub(1));
+    let base = Sieve::new(root as usize + 1)
+        .primes()
+        .map(|p| p as u64)
+        .collect();
+    SegmentedPrimes {
+        base,
+        next: lo.max(2),
+        hi,
+        found: Vec::new(),
+        index: 0,
+    }
+}
+
+/// an iterator over the primes of a range, from [`segmented_primes`].
+pub struct SegmentedPrimes {
+    base: Vec<u64>,
+    // the start of the next segment.
+    next: u64,
+    hi: u64,
+    found: Vec<u64>,
+    index: usize,
+}
+
+impl SegmentedPrimes {
+    fn sieve_segment(&mut self) {
+        let lo = self.next;
+        let hi = lo.saturating_add(SEGMENT).min(self.hi);
+        let mut prime = vec![true; (hi - lo) as usize];
+        for &p in &self.base {
+            if p * p >= hi {
+                break;
+            }
+            let first = match lo.div_ceil(p).checked_mul(p) {
+                Some(first) => first.max(p * p),
+                None => continue,
+            };
+            let mut multiple = first;
+            while multiple < hi {
+                prime[(multiple - lo) as usize] = false;
+                multiple += p;
+            }
+        }
+        self.found.clear();
+        self.found
+            .extend((lo..hi).filter(|&n| prime[(n - lo) as usize]));
+        self.index = 0;
+        self.next = hi;
+    }
+}
+
+impl Iterator for SegmentedPrimes {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        while self.index == self.found.len() {
+            if self.next >= self.hi {
+                return None;
+            }
+            self.sieve_segment();
+        }
+        self.index += 1;
+        Some(self.found[self.index - 1])
+    }
+}
+
+// the floor of the square root.
+fn isqrt(n: u64) -> u64 {
+    let mut root = (n as f64).sqrt() as u64;
+    while root.checked_mul(root).is_none_or(|r| r > n) {
+        root -= 1;
+    }
+    while (root + 1).checked_mul(root + 1).is_some_and(|r| r <= n) {
+        root += 1;
+    }
+    root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trial_division(n: u64) -> bool {
+        n >= 2
+            && (2..)
+                .take_while(|d| d * d <= n)
+                .all(|d| !n.is_multiple_of(d))
+    }
+
+    #[test]
+    fn test_sieves() {
+        let (sieve, linear) = (Sieve::new(2000), LinearSieve::new(2000));
+        let expected: Vec<usize> = (0..2000).filter(|&n| trial_division(n as u64)).collect();
+        assert_eq!(sieve.primes().collect::<Vec<_>>(), expected);
+        assert_eq!(linear.primes(), &expected[..]);
+        assert!((0..2000).all(|n| sieve.is_prime(n) == linear.is_prime(n)));
+        assert_eq!(Sieve::new(1_000_000).count_below(1_000_000), 78_498);
+        assert_eq!(sieve.count_below(100), 25);
+        assert_eq!(sieve.nth(0), Some(2));
+        assert_eq!(sieve.nth(24), Some(97));
+        assert_eq!(sieve.nth(1000), None);
+        assert_eq!(Sieve::new(0).primes().count(), 0);
+        assert_eq!(Sieve::new(3).primes().collect::<Vec<_>>(), [2]);
+    }
+
+    #[test]
+    fn test_factorize() {
+        let linear = LinearSieve::new(100_000);
+        assert_eq!(linear.factorize(1), []);
+        assert_eq!(linear.factorize(360), [(2, 3), (3, 2), (5, 1)]);
+        assert_eq!(linear.factorize(99_991), [(99_991, 1)]);
+        for n in 2..100_000 {
+            let factors = linear.factorize(n);
+            assert_eq!(factors[0].0, linear.smallest_factor(n));
+            let product: usize = factors.iter().map(|&(p, e)| p.pow(e)).product();
+            assert!(product == n && factors.iter().all(|&(p, _)| linear.is_prime(p)));
+        }
+    }
+
+    #[test]
+    fn test_segmented() {
+        let sieve = Sieve::new(300_000);
+        let small: Vec<u64> = sieve.primes().map(|p| p as u64).collect();
+        assert_eq!(segmented_primes(0, 300_000).collect::<Vec<_>>(), small);
+        let middle: Vec<u64> = small
+            .iter()
+            .copied()
+            .filter(|&p| (65_000..200_001).contains(&p))
+            .collect();
+        assert_eq!(
+            segmented_primes(65_000, 200_001).collect::<Vec<_>>(),
+            middle
+        );
+        let far = 1_000_000_000_000;
+        let expected: Vec<u64> = (far..far + 300).filter(|&n| trial_division(n)).collect();
+        assert_eq!(
+            segmented_primes(far, far + 300).collect::<Vec<_>>(),
+            expected
+        );
+        assert_eq!(segmented_primes(10, 10).count(), 0);
+        assert_eq!(isqrt(u64::MAX), u32::MAX as u64);
+    }
+}
@@ -0,0 +1,373 @@
+// below this, a float counts as zero.
+const EPSILON: f64 = 1e-9;
+
+/// how the two sides of a constraint compare.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Relation {
+    LessEq,
+    GreaterEq,
+    Equal,
+}
+
+/// how a linear program came out.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Outcome {
+    /// the best value of the objective, and where it is reached.
+    Optimal { value: f64, assignment: Vec<f64> },
+    /// the objective improves without bound.
+    Unbounded,
+    /// no assignment meets every constraint.
+    Infeasible,
+}
+
+/// a linear program over non-negative variables, built up a constraint at
+/// a time and solved by the two-phase simplex method.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LinearProgram {
+    objective: Vec<f64>,
+    maximize: bool,
+    constraints: Vec<(Vec<f64>, Relation, f64)>,
+}
+
+impl LinearProgram {
+    /// maximize `objective · x` over `x >= 0`, one variable per entry.
+    pub fn maximize(objective: Vec<f64>) -> Self {
+        LinearProgram {
+            objective,
+            maximize: true,
+            constraints: Vec::new(),
+        }
+    }
+
+    /// minimize `objective · x` over `x >= 0`.
+    pub fn minimize(objective: Vec<f64>) -> Self {
+        LinearProgram {
+            maximize: false,
+            ..LinearProgram::maximize(objective)
+        }
+    }
+
+    /// require `coefficients · x <relation> rhs`.
+    pub fn constraint(mut self, coefficients: Vec<f64>, relation: Relation, rhs: f64) -> Self {
+        assert_eq!(
+            coefficients.len(),
+            self.objective.len(),
+            "one coefficient per variable"
+        );
+        self.constraints.push((coefficients, relation, rhs));
+        self
+    }
+
+    pub fn variable_count(&self) -> usize {
+        self.objective.len()
+    }
+
+    /// solve with a dense tableau. the first phase drives out artificial
+    /// variables to find a feasible vertex, the second moves to the best
+    /// one; bland's rule picks every pivot, so degenerate programs cannot
+    /// cycle. each pivot is O(m * (n + m)) for m constraints.
+    pub fn solve(&self) -> Outcome {
+        let n = self.variable_count();
+        // every constraint with a non-negative right-hand side.
+        let rows: Vec<(Vec<f64>, Relation, f64)> = self
+            .constraints
+            .iter()
+            .map(|(coefficients, relation, rhs)| {
+                if *rhs >= 0.0 {
+                    (coefficients.clone(), *relation, *rhs)
+                } else {
+                    let flipped = match relation {
+                        Relation::LessEq => Relation::GreaterEq,
+                        Relation::GreaterEq => Relation::LessEq,
+                        Relation::Equal => Relation::Equal,
+                    };
+                    (coefficients.iter().map(|c| -c).collect(), flipped, -rhs)
+                }
+            })
+            .collect();
+        // the columns: variables, then a slack or surplus per inequality,
+        // then an artificial per row not starting with a slack.
+        let slacks = rows.iter().filter(|r| r.1 != Relation::Equal).count();
+        let artificials = rows.iter().filter(|r| r.1 != Relation::LessEq).count();
+        let columns = n + slacks + artificials;
+        let mut tableau = Tableau {
+            rows: Vec::with_capacity(rows.len()),
+            basis: Vec::with_capacity(rows.len()),
+        };
+        let (mut slack, mut artificial) = (n, n + slacks);
+        for (coefficients, relation, rhs) in rows {
+            let mut row = coefficients;
+            row.resize(columns + 1, 0.0);
+            row[columns] = rhs;
+            match relation {
+                Relation::LessEq => {
+                    row[slack] = 1.0;
+                    tableau.basis.push(slack);
+                    slack += 1;
+                }
+                Relation::GreaterEq => {
+                    row[slack] = -1.0;
+                    slack += 1;
+                    row[artificial] = 1.0;
+                    tableau.basis.push(artificial);
+                    artificial += 1;
+                }
+                Relation::Equal => {
+                    row[artificial] = 1.0;
+                    tableau.basis.push(artificial);
+                    artificial += 1;
+                }
+            }
+            tableau.rows.push(row);
+        }
+        // phase one: minimize the sum of the artificials.
+        if artificials > 0 {
+            let mut cost = vec![0.0; columns];
+            cost[n + slacks..].iter_mut().for_each(|c| *c = -1.0);
+            tableau.optimize(&cost, columns);
+            if tableau.value(&cost) < -EPSILON {
+                return Outcome::Infeasible;
+            }
+            tableau.drop_artificials(n + slacks);
+        }
+        // phase two, without the artificial columns.
+        let sign = if self.maximize { 1.0 } else { -1.0 };
+        let mut cost: Vec<f64> = self.objective.iter().map(|c| sign * c).collect();
+        cost.resize(columns, 0.0);
+        if !tableau.optimize(&cost, n + slacks) {
+            return Outcome::Unbounded;
+        }
+        let mut assignment = vec![0.0; n];
+        for (row, &column) in tableau.basis.iter().enumerate() {
+            if column < n {
+                assignment[column] = tableau.rows[row][columns];
+            }
+        }
+        let value = assignment
+            .iter()
+            .zip(&self.objective)
+            .map(|(x, c)| x * c)
+            .sum();
+        Outcome::Optimal { value, assignment }
+    }
+}
+
+// the constraint rows with the right-hand side last, and the column basic
+// in each row.
+struct Tableau {
+    rows: Vec<Vec<f64>>,
+    basis: Vec<usize>,
+}
+
+impl Tableau {
+    // the objective `cost · x` at the current vertex.
+    fn value(&self, cost: &[f64]) -> f64 {
+        let rhs = cost.len();
+        self.basis
+            .iter()
+            .zip(&self.rows)
+            .map(|(&column, row)| cost[column] * row[rhs])
+            .sum()
+    }
+
+    // maximize `cost · x` pivoting only on the first `allowed` columns.
+    // returns `false` if it is unbounded.
+    fn optimize(&mut self, cost: &[f64], allowed: usize) -> bool {
+        let rhs = cost.len();
+        loop {
+            // bland's rule: the first column that improves the objective...
+            let entering = (0..allowed).find(|&j| {
+                let reduced: f64 = self
+                    .basis
+                    .iter()
+                    .zip(&self.rows)
+                    .map(|(&b, row)| cost[b] * row[j])
+                    .sum();
+                cost[j] - reduced > EPSILON
+            });
+            let entering = match entering {
+                Some(j) => j,
+                None => return true,
+            };
+            // ...leaving by the tightest ratio, ties to the lowest basic column.
+            let mut leaving: Option<(usize, f64)> = None;
+            for (i, row) in self.rows.iter().enumerate() {
+                if row[entering] <= EPSILON {
+                    continue;
+                }
+                let ratio = row[rhs] / row[entering];
+                let better = leaving.is_none_or(|(best, best_ratio)| {
+                    ratio < best_ratio - EPSILON
+                        || (ratio < best_ratio + EPSILON && self.basis[i] < self.basis[best])
+                });
+                if better {
+                    leaving = Some((i, ratio));
+                }
+            }
+            match leaving {
+                Some((row, _)) => self.pivot(row, entering),
+                None => return false,
+            }
+        }
+    }
+
+    fn pivot(&mut self, row: usize, column: usize) {
+        let pivot = self.rows[row][column];
+        self.rows[row].iter_mut().for_each(|v| *v /= pivot);
+        let pivot_row = self.rows[row].clone();
+        for (i, other) in self.rows.iter_mut().enumerate() {
+            let factor = other[column];
+            if i != row && factor.abs() > 0.0 {
+                other
+                    .iter_mut()
+                    .zip(&pivot_row)
+                    .for_each(|(v, p)| *v -= factor * p);
+            }
+        }
+        self.basis[row] = column;
+    }
+
+    // after phase one, pivot the artificials, columns from `first` on, out
+    // of the basis, dropping the rows where that is impossible: those are
+    // redundant constraints.
+    fn drop_artificials(&mut self, first: usize) {
+        let mut row = 0;
+        while row < self.rows.len() {
+            if self.basis[row] < first {
+                row += 1;
+                continue;
+            }
+            match (0..first).find(|&j| self.rows[row][j].abs() > EPSILON) {
+                Some(column) => {
+                    self.pivot(row, column);
+                    row += 1;
+                }
+                None => {
+                    self.rows.remove(row);
+                    self.basis.remove(row);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+    use Relation::*;
+
+    fn optimal(outcome: Outcome) -> (f64, Vec<f64>) {
+        match outcome {
+            Outcome::Optimal { value, assignment } => (value, assignment),
+            other => panic!("expected an optimum, got {:?}", other),
+        }
+    }
+
+    fn close(a: &[f64], b: &[f64]) -> bool {
+        a.len() == b.len() && a.iter().zip(b).all(|(x, y)| (x - y).abs() < 1e-7)
+    }
+
+    #[test]
+    fn test_small() {
+        let (value, x) = optimal(
+            LinearProgram::maximize(vec![3.0, 5.0])
+                .constraint(vec![1.0, 0.0], LessEq, 4.0)
+                .constraint(vec![0.0, 2.0], LessEq, 12.0)
+                .constraint(vec![3.0, 2.0], LessEq, 18.0)
+                .solve(),
+        );
+        assert!((value - 36.0).abs() < 1e-9 && close(&x, &[2.0, 6.0]));
+        // needs the first phase: the origin is not feasible.
+        let (value, x) = optimal(
+            LinearProgram::minimize(vec![2.0, 3.0])
+                .constraint(vec![1.0, 1.0], GreaterEq, 4.0)
+                .constraint(vec![1.0, 3.0], GreaterEq, 6.0)
+                .solve(),
+        );
+        assert!((value - 9.0).abs() < 1e-9 && close(&x, &[3.0, 1.0]));
+        let (value, x) = optimal(
+            LinearProgram::maximize(vec![1.0, 1.0])
+                .constraint(vec![1.0, 2.0], Equal, 4.0)
+                .constraint(vec![1.0, 0.0], LessEq, 2.0)
+                // the same equation again, redundant.
+                .constraint(vec![2.0, 4.0], Equal, 8.0)
+                .solve(),
+        );
+        assert!((value - 3.0).abs() < 1e-9 && close(&x, &[2.0, 1.0]));
+        let (value, _) = optimal(
+            LinearProgram::minimize(vec![1.0])
+                .constraint(vec![-1.0], LessEq, -1.5)
+                .solve(),
+        );
+        assert!((value - 1.5).abs() < 1e-9);
+        let infeasible = LinearProgram::maximize(vec![1.0])
+            .constraint(vec![1.0], LessEq, 1.0)
+            .constraint(vec![1.0], GreaterEq, 2.0);
+        assert_eq!(infeasible.solve(), Outcome::Infeasible);
+        let unbounded =
+            LinearProgram::maximize(vec![1.0, 0.0]).constraint(vec![1.0, -1.0], LessEq, 1.0);
+        assert_eq!(unbounded.solve(), Outcome::Unbounded);
+        assert_eq!(
+            LinearProgram::maximize(vec![]).solve(),
+            Outcome::Optimal {
+                value: 0.0,
+                assignment: vec![]
+            }
+        );
+    }
+
+    #[test]
+    fn test_degenerate() {
+        // beale's example, on which the textbook pivoting rule cycles.
+        let (value, x) = optimal(
+            LinearProgram::maximize(vec![0.75, -20.0, 0.5, -6.0])
+                .constraint(vec![0.25, -8.0, -1.0, 9.0], LessEq, 0.0)
+                .constraint(vec![0.5, -12.0, -0.5, 3.0], LessEq, 0.0)
+                .constraint(vec![0.0, 0.0, 1.0, 0.0], LessEq, 1.0)
+                .solve(),
+        );
+        assert!((value - 1.25).abs() < 1e-9 && close(&x, &[1.0, 0.0, 1.0, 0.0]));
+    }
+
+    #[test]
+    fn test_random() {
+        // packing programs, feasible at the origin and bounded.
+        let mut rng = Rng::new(113);
+        for _ in 0..100 {
+            let (n, m) = (1 + rng.index(5), 1 + rng.index(5));
+            let objective: Vec<f64> = (0..n).map(|_| rng.range(-5..10) as f64).collect();
+            let mut program = LinearProgram::maximize(objective.clone());
+            let mut constraints = Vec::new();
+            for _ in 0..m {
+                let row: Vec<f64> = (0..n).map(|_| rng.range(0..5) as f64).collect();
+                let rhs = rng.range(0..20) as f64;
+                constraints.push((row.clone(), rhs));
+                program = program.constraint(row, LessEq, rhs);
+            }
+            // every variable bounded on its own.
+            for j in 0..n {
+                let mut row = vec![0.0; n];
+                row[j] = 1.0;
+                constraints.push((row.clone(), 10.0));
+                program = program.constraint(row, LessEq, 10.0);
+            }
+            let (value, x) = optimal(program.solve());
+            let feasible = |x: &[f64]| {
+                x.iter().all(|&v| v >= -1e-7)
+                    && constraints.iter().all(|(row, rhs)| {
+                        row.iter().zip(x).map(|(a, b)| a * b).sum::<f64>() <= rhs + 1e-7
+                    })
+            };
+            assert!(feasible(&x));
+            // no feasible point on a grid does better.
+            for _ in 0..200 {
+                let point: Vec<f64> = (0..n).map(|_| rng.range(0..11) as f64).collect();
+                if feasible(&point) {
+                    let other: f64 = point.iter().zip(&objective).map(|(a, b)| a * b).sum();
+                    assert!(other <= value + 1e-7);
+                }
+            }
+        }
+    }
+}
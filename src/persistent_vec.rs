@@ -0,0 +1,401 @@
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops::Index;
+use std::rc::Rc;
+
+const BITS: u32 = 5;
+const WIDTH: usize = 1 << BITS;
+const MASK: usize = WIDTH - 1;
+
+enum Node<T> {
+    Branch(Vec<Rc<Node<T>>>),
+    Leaf(Vec<T>),
+}
+
+impl<T> Node<T> {
+    fn children(&self) -> &[Rc<Node<T>>] {
+        match self {
+            Node::Branch(children) => children,
+            Node::Leaf(_) => unreachable!("expected a branch"),
+        }
+    }
+
+    fn values(&self) -> &[T] {
+        match self {
+            Node::Leaf(values) => values,
+            Node::Branch(_) => unreachable!("expected a leaf"),
+        }
+    }
+}
+
+// a chain of single-child branches from `level` down to `leaf`.
+fn new_path<T>(level: u32, leaf: Rc<Node<T>>) -> Rc<Node<T>> {
+    if level == 0 {
+        leaf
+    } else {
+        Rc::new(Node::Branch(vec![new_path(level - BITS, leaf)]))
+    }
+}
+
+/// immutable vector whose versions share structure: `push`, `set` and `pop`
+/// return a new vector in O(log n) (with a base-32 logarithm) and leave the
+/// original untouched, and `clone` is O(1).
+///
+/// values live in the leaves of a 32-way trie, except for the last partial
+/// leaf, which is kept aside so most pushes only copy that.
+pub struct PersistentVec<T> {
+    len: usize,
+    // bits of the index consumed below the root.
+    shift: u32,
+    root: Rc<Node<T>>,
+    tail: Rc<Vec<T>>,
+}
+
+impl<T: Clone> PersistentVec<T> {
+    pub fn new() -> Self {
+        PersistentVec {
+            len: 0,
+            shift: BITS,
+            root: Rc::new(Node::Branch(Vec::new())),
+            tail: Rc::new(Vec::new()),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // index of the first value in the tail.
+    fn tail_offset(&self) -> usize {
+        if self.len < WIDTH {
+            0
+        } else {
+            ((self.len - 1) >> BITS) << BITS
+        }
+    }
+
+    // the leaf holding index `i`.
+    fn leaf(&self, i: usize) -> &[T] {
+        if i >= self.tail_offset() {
+            return &self.tail;
+        }
+        let mut node = &self.root;
+        let mut level = self.shift;
+        while level > 0 {
+            node = &node.children()[(i >> level) & MASK];
+            level -= BITS;
+        }
+        node.values()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+        Some(&self.leaf(index)[index & MASK])
+    }
+
+    pub fn first(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    pub fn last(&self) -> Option<&T> {
+        self.len.checked_sub(1).and_then(|i| self.get(i))
+    }
+
+    /// a new vector with `value` appended.
+    pub fn push(&self, value: T) -> Self {
+        if self.len - self.tail_offset() < WIDTH {
+            let mut tail = (*self.tail).clone();
+            tail.push(value);
+            return PersistentVec {
+                len: self.len + 1,
+                shift: self.shift,
+                root: self.root.clone(),
+                tail: Rc::new(tail),
+            };
+        }
+        // the tail is full: move it into the trie.
+        let leaf = Rc::new(Node::Leaf((*self.tail).clone()));
+        let (root, shift) = if (self.len >> BITS) > (1 << self.shift) {
+            let children = vec![self.root.clone(), new_path(self.shift, leaf)];
+            (Rc::new(Node::Branch(children)), self.shift + BITS)
+        } else {
+            (self.push_leaf(self.shift, &self.root, leaf), self.shift)
+        };
+        PersistentVec {
+            len: self.len + 1,
+            shift,
+            root,
+            tail: Rc::new(vec![value]),
+        }
+    }
+
+    // a copy of `node` at `level` with `leaf` added as the last leaf.
+    fn push_leaf(&self, level: u32, node: &Node<T>, leaf: Rc<Node<T>>) -> Rc<Node<T>> {
+        let mut children = node.children().to_vec();
+        let i = ((self.len - 1) >> level) & MASK;
+        let child = if level == BITS {
+            leaf
+        } else if i < children.len() {
+            self.push_leaf(level - BITS, &children[i], leaf)
+        } else {
+            new_path(level - BITS, leaf)
+        };
+        if i < children.len() {
+            children[i] = child;
+        } else {
+            children.push(child);
+        }
+        Rc::new(Node::Branch(children))
+    }
+
+    /// a new vector with the value at `index` replaced. panics if `index`
+    /// is out of bounds.
+    pub fn set(&self, index: usize, value: T) -> Self {
+        assert!(index < self.len, "index {} out of bounds", index);
+        let mut result = self.clone();
+        if index >= self.tail_offset() {
+            let mut tail = (*self.tail).clone();
+            tail[index & MASK] = value;
+            result.tail = Rc::new(tail);
+        } else {
+            result.root = set_in(self.shift, &self.root, index, value);
+        }
+        result
+    }
+
+    /// a new vector without the last value; None if this one is empty.
+    pub fn pop(&self) -> Option<Self> {
+        match self.len {
+            0 => return None,
+            1 => return Some(PersistentVec::new()),
+            _ => {}
+        }
+        if self.len - self.tail_offset() > 1 {
+            let mut tail = (*self.tail).clone();
+            tail.pop();
+            return Some(PersistentVec {
+                len: self.len - 1,
+                shift: self.shift,
+                root: self.root.clone(),
+                tail: Rc::new(tail),
+            });
+        }
+        // the last leaf of the trie becomes the tail.
+        let tail = Rc::new(self.leaf(self.len - 2).to_vec());
+        let mut root = self
+            .pop_leaf(self.shift, &self.root)
+            .unwrap_or_else(|| Rc::new(Node::Branch(Vec::new())));
+        let mut shift = self.shift;
+        if shift > BITS && root.children().len() == 1 {
+            root = root.children()[0].clone();
+            shift -= BITS;
+        }
+        Some(PersistentVec {
+            len: self.len - 1,
+            shift,
+            root,
+            tail,
+        })
+    }
+
+    // a copy of `node` at `level` without its last leaf, or None if that
+    // leaves it empty.
+    fn pop_leaf(&self, level: u32, node: &Node<T>) -> Option<Rc<Node<T>>> {
+        let i = ((self.len - 2) >> level) & MASK;
+        let mut children = node.children()[..=i].to_vec();
+        if level > BITS {
+            match self.pop_leaf(level - BITS, &children[i]) {
+                Some(child) => children[i] = child,
+                None => {
+                    children.pop();
+                }
+            }
+        } else {
+            children.pop();
+        }
+        if children.is_empty() {
+            None
+        } else {
+            Some(Rc::new(Node::Branch(children)))
+        }
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            vec: self,
+            index: 0,
+            leaf: &[],
+        }
+    }
+}
+
+fn set_in<T: Clone>(level: u32, node: &Node<T>, index: usize, value: T) -> Rc<Node<T>> {
+    match node {
+        Node::Leaf(values) => {
+            let mut values = values.clone();
+            values[index & MASK] = value;
+            Rc::new(Node::Leaf(values))
+        }
+        Node::Branch(children) => {
+            let mut children = children.clone();
+            let i = (index >> level) & MASK;
+            children[i] = set_in(level - BITS, &children[i], index, value);
+            Rc::new(Node::Branch(children))
+        }
+    }
+}
+
+impl<T> Clone for PersistentVec<T> {
+    fn clone(&self) -> Self {
+        PersistentVec {
+            len: self.len,
+            shift: self.shift,
+            root: self.root.clone(),
+            tail: self.tail.clone(),
+        }
+    }
+}
+
+impl<T: Clone> Default for PersistentVec<T> {
+    fn default() -> Self {
+        PersistentVec::new()
+    }
+}
+
+impl<T: Clone> Index<usize> for PersistentVec<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        self.get(index).expect("index out of bounds")
+    }
+}
+
+impl<T: Clone> FromIterator<T> for PersistentVec<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        iter.into_iter()
+            .fold(PersistentVec::new(), |vec, value| vec.push(value))
+    }
+}
+
+impl<T: Clone + PartialEq> PartialEq for PersistentVec<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Clone + fmt::Debug> fmt::Debug for PersistentVec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// iterator over a [`PersistentVec`], a leaf at a time.
+pub struct Iter<'a, T> {
+    vec: &'a PersistentVec<T>,
+    index: usize,
+    leaf: &'a [T],
+}
+
+impl<'a, T: Clone> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.index >= self.vec.len {
+            return None;
+        }
+        if self.index & MASK == 0 {
+            self.leaf = self.vec.leaf(self.index);
+        }
+        let value = &self.leaf[self.index & MASK];
+        self.index += 1;
+        Some(value)
+    }
+}
+
+impl<'a, T: Clone> IntoIterator for &'a PersistentVec<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_versions() {
+        let mut rng = Rng::new(39);
+        let mut versions = vec![(PersistentVec::new(), Vec::new())];
+        for _ in 0..3000 {
+            let (vec, model) = versions[rng.index(versions.len())].clone();
+            let next = match rng.index(5) {
+                0 if !model.is_empty() => {
+                    let mut model = model;
+                    model.pop();
+                    (vec.pop().unwrap(), model)
+                }
+                1 if !model.is_empty() => {
+                    let i = rng.index(model.len());
+                    let mut model = model;
+                    model[i] = rng.below(1000);
+                    (vec.set(i, model[i]), model)
+                }
+                _ => {
+                    // bursts of pushes to grow past several levels.
+                    let (mut vec, mut model) = (vec, model);
+                    for _ in 0..rng.index(200) {
+                        let value = rng.below(1000);
+                        vec = vec.push(value);
+                        model.push(value);
+                    }
+                    (vec, model)
+                }
+            };
+            versions.push(next);
+        }
+        for (vec, model) in &versions {
+            assert_eq!(vec.len(), model.len());
+            assert!(vec.iter().eq(model.iter()));
+            assert_eq!(vec.last(), model.last());
+            assert_eq!(vec.get(model.len()), None);
+        }
+        assert!(versions.iter().any(|(vec, _)| vec.shift > BITS));
+    }
+
+    #[test]
+    fn test_sharing() {
+        let a: PersistentVec<u32> = (0..5000).collect();
+        let b = a.set(1234, 0).push(5000);
+        assert_eq!(a[1234], 1234);
+        assert_eq!((b[1234], b[5000]), (0, 5000));
+        assert_eq!(a.len(), 5000);
+        // only the path to index 1234 was copied.
+        assert!(Rc::ptr_eq(&a.root.children()[0], &b.root.children()[0]));
+        assert!(!Rc::ptr_eq(&a.root.children()[1], &b.root.children()[1]));
+        let mut c = b.clone();
+        while let Some(shorter) = c.pop() {
+            c = shorter;
+        }
+        assert!(c.is_empty() && c.root.children().is_empty());
+        assert_eq!(b.first(), Some(&0));
+        let mut big: PersistentVec<u32> = (0..40_000).collect();
+        assert_eq!(big.shift, 3 * BITS);
+        while big.len() > 1000 {
+            big = big.pop().unwrap();
+        }
+        assert_eq!(big.shift, BITS);
+        assert!(big.iter().copied().eq(0..1000));
+        let small: PersistentVec<u32> = (0..3).collect();
+        assert_eq!(format!("{:?}", small), "[0, 1, 2]");
+    }
+}
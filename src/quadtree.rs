@@ -0,0 +1,313 @@
+use std::fmt;
+
+use crate::r_tree::Rect;
+
+// below this depth buckets split; past it they just grow, so that many
+// copies of one point cannot recurse forever.
+const MAX_DEPTH: usize = 24;
+
+enum Node<V> {
+    Leaf(Vec<([f64; 2], V)>),
+    Inner(Box<[Node<V>; 4]>),
+}
+
+// the quadrant of `bounds` holding `point`: bit 0 for the upper x half,
+// bit 1 for the upper y half.
+fn quadrant(bounds: &Rect<2>, point: &[f64; 2]) -> usize {
+    (0..2)
+        .filter(|&d| point[d] >= (bounds.min[d] + bounds.max[d]) / 2.0)
+        .fold(0, |i, d| i | 1 << d)
+}
+
+fn child_bounds(bounds: &Rect<2>, i: usize) -> Rect<2> {
+    let mut child = *bounds;
+    for d in 0..2 {
+        let mid = (bounds.min[d] + bounds.max[d]) / 2.0;
+        if i & (1 << d) == 0 {
+            child.max[d] = mid;
+        } else {
+            child.min[d] = mid;
+        }
+    }
+    child
+}
+
+impl<V> Node<V> {
+    fn empty_children() -> Box<[Node<V>; 4]> {
+        Box::new([
+            Node::Leaf(Vec::new()),
+            Node::Leaf(Vec::new()),
+            Node::Leaf(Vec::new()),
+            Node::Leaf(Vec::new()),
+        ])
+    }
+
+    fn insert(&mut self, bounds: &Rect<2>, depth: usize, bucket: usize, point: [f64; 2], value: V) {
+        match self {
+            Node::Leaf(entries) => {
+                entries.push((point, value));
+                if entries.len() > bucket && depth < MAX_DEPTH {
+                    let mut children = Node::empty_children();
+                    for (p, v) in entries.drain(..) {
+                        let i = quadrant(bounds, &p);
+                        children[i].insert(&child_bounds(bounds, i), depth + 1, bucket, p, v);
+                    }
+                    *self = Node::Inner(children);
+                }
+            }
+            Node::Inner(children) => {
+                let i = quadrant(bounds, &point);
+                children[i].insert(&child_bounds(bounds, i), depth + 1, bucket, point, value);
+            }
+        }
+    }
+
+    // remove one entry at `point`, merging children back into a bucket once
+    // they fit in one.
+    fn remove(&mut self, bounds: &Rect<2>, bucket: usize, point: &[f64; 2]) -> Option<V> {
+        match self {
+            Node::Leaf(entries) => {
+                let i = entries.iter().position(|(p, _)| p == point)?;
+                Some(entries.swap_remove(i).1)
+            }
+            Node::Inner(children) => {
+                let i = quadrant(bounds, point);
+                let value = children[i].remove(&child_bounds(bounds, i), bucket, point)?;
+                let mergeable = children
+                    .iter()
+                    .map(|child| match child {
+                        Node::Leaf(entries) => Some(entries.len()),
+                        Node::Inner(_) => None,
+                    })
+                    .sum::<Option<usize>>()
+                    .is_some_and(|n| n <= bucket);
+                if mergeable {
+                    let mut merged = Vec::new();
+                    for child in children.iter_mut() {
+                        if let Node::Leaf(entries) = child {
+                            merged.append(entries);
+                        }
+                    }
+                    *self = Node::Leaf(merged);
+                }
+                Some(value)
+            }
+        }
+    }
+
+    fn depth(&self) -> usize {
+        match self {
+            Node::Leaf(_) => 1,
+            Node::Inner(children) => 1 + children.iter().map(Node::depth).max().unwrap(),
+        }
+    }
+}
+
+/// region quadtree over points in a fixed rectangle: each node splits its
+/// region into four equal quadrants once it holds more than `bucket_size`
+/// points, and merges them back when removals let them fit again.
+///
+/// simpler and cheaper to update than an [`RTree`](crate::r_tree::RTree)
+/// for points that move around, at the cost of depending on how evenly the
+/// points spread over the region.
+pub struct QuadTree<V> {
+    bounds: Rect<2>,
+    bucket_size: usize,
+    root: Node<V>,
+    len: usize,
+}
+
+impl<V> QuadTree<V> {
+    /// a tree over `bounds` with buckets of 8 points.
+    pub fn new(bounds: Rect<2>) -> Self {
+        QuadTree::with_bucket_size(bounds, 8)
+    }
+
+    pub fn with_bucket_size(bounds: Rect<2>, bucket_size: usize) -> Self {
+        assert!(bucket_size > 0, "bucket size must be positive");
+        QuadTree {
+            bounds,
+            bucket_size,
+            root: Node::Leaf(Vec::new()),
+            len: 0,
+        }
+    }
+
+    pub fn bounds(&self) -> &Rect<2> {
+        &self.bounds
+    }
+
+    pub fn bucket_size(&self) -> usize {
+        self.bucket_size
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// number of levels of nodes.
+    pub fn depth(&self) -> usize {
+        self.root.depth()
+    }
+
+    /// insert `value` at `point`, or give it back if `point` lies outside
+    /// the tree's bounds.
+    pub fn insert(&mut self, point: [f64; 2], value: V) -> Result<(), V> {
+        if !self.bounds.contains(&Rect::point(point)) {
+            return Err(value);
+        }
+        self.root
+            .insert(&self.bounds, 0, self.bucket_size, point, value);
+        self.len += 1;
+        Ok(())
+    }
+
+    /// remove a value stored at exactly `point`.
+    pub fn remove(&mut self, point: &[f64; 2]) -> Option<V> {
+        if !self.bounds.contains(&Rect::point(*point)) {
+            return None;
+        }
+        let value = self.root.remove(&self.bounds, self.bucket_size, point)?;
+        self.len -= 1;
+        Some(value)
+    }
+
+    /// the points inside `window`, bounds included.
+    pub fn range(&self, window: &Rect<2>) -> Vec<(&[f64; 2], &V)> {
+        let mut out = Vec::new();
+        let mut stack = vec![(&self.root, self.bounds)];
+        while let Some((node, bounds)) = stack.pop() {
+            match node {
+                Node::Leaf(entries) => out.extend(
+                    entries
+                        .iter()
+                        .filter(|(p, _)| window.contains(&Rect::point(*p)))
+                        .map(|(p, v)| (p, v)),
+                ),
+                Node::Inner(children) => {
+                    for (i, child) in children.iter().enumerate() {
+                        let b = child_bounds(&bounds, i);
+                        if b.intersects(window) {
+                            stack.push((child, b));
+                        }
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    /// the points within distance `radius` of `center`; none if `radius` is
+    /// negative or nan.
+    pub fn within_radius(&self, center: &[f64; 2], radius: f64) -> Vec<(&[f64; 2], &V)> {
+        if radius.is_nan() || radius < 0.0 {
+            return Vec::new();
+        }
+        let window = Rect::new(
+            [center[0] - radius, center[1] - radius],
+            [center[0] + radius, center[1] + radius],
+        );
+        let mut out = self.range(&window);
+        out.retain(|(p, _)| Rect::point(**p).distance2(center) <= radius * radius);
+        out
+    }
+
+    /// iterate over all points, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&[f64; 2], &V)> {
+        let mut stack = vec![&self.root];
+        let mut leaf: std::slice::Iter<'_, ([f64; 2], V)> = [].iter();
+        std::iter::from_fn(move || loop {
+            if let Some((p, v)) = leaf.next() {
+                return Some((p, v));
+            }
+            match stack.pop()? {
+                Node::Leaf(entries) => leaf = entries.iter(),
+                Node::Inner(children) => stack.extend(children.iter()),
+            }
+        })
+    }
+}
+
+impl<V> fmt::Debug for QuadTree<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QuadTree")
+            .field("bounds", &self.bounds)
+            .field("bucket_size", &self.bucket_size)
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_against_brute_force() {
+        let mut rng = Rng::new(44);
+        let area = Rect::new([0.0, 0.0], [100.0, 100.0]);
+        let mut tree = QuadTree::with_bucket_size(area, 4);
+        let mut model = Vec::new();
+        for i in 0..3000 {
+            if rng.chance(0.3) && !model.is_empty() {
+                let (point, _) = model.swap_remove(rng.index(model.len()));
+                assert!(tree.remove(&point).is_some());
+            } else {
+                // a coarse grid, so that duplicates occur.
+                let point = [rng.below(100) as f64, rng.below(100) as f64];
+                tree.insert(point, i).unwrap();
+                model.push((point, i));
+            }
+            if i % 30 == 0 {
+                let (x, y) = (rng.next_f64() * 100.0, rng.next_f64() * 100.0);
+                let window = Rect::new([x, y], [x + 20.0, y + 10.0]);
+                let mut found: Vec<[f64; 2]> =
+                    tree.range(&window).iter().map(|(p, _)| **p).collect();
+                let mut expected: Vec<[f64; 2]> = model
+                    .iter()
+                    .map(|(p, _)| *p)
+                    .filter(|p| window.contains(&Rect::point(*p)))
+                    .collect();
+                found.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                assert_eq!(found, expected);
+                let near = tree.within_radius(&[x, y], 7.5).len();
+                let distance = |p: &[f64; 2]| ((p[0] - x).powi(2) + (p[1] - y).powi(2)).sqrt();
+                assert_eq!(
+                    near,
+                    model.iter().filter(|(p, _)| distance(p) <= 7.5).count()
+                );
+            }
+        }
+        assert_eq!(tree.len(), model.len());
+        assert_eq!(tree.iter().count(), model.len());
+        while let Some((point, _)) = model.pop() {
+            assert!(tree.remove(&point).is_some());
+        }
+        assert!(tree.is_empty());
+        assert_eq!(tree.depth(), 1);
+    }
+
+    #[test]
+    fn test_bounds_and_duplicates() {
+        let mut tree = QuadTree::with_bucket_size(Rect::new([0.0, 0.0], [1.0, 1.0]), 1);
+        assert_eq!(tree.insert([2.0, 0.5], "outside"), Err("outside"));
+        assert_eq!(tree.remove(&[2.0, 0.5]), None);
+        for _ in 0..100 {
+            tree.insert([0.5, 0.5], "same").unwrap();
+        }
+        tree.insert([1.0, 1.0], "corner").unwrap();
+        assert!(tree.depth() <= MAX_DEPTH + 1);
+        assert_eq!(tree.range(&Rect::point([1.0, 1.0])).len(), 1);
+        assert_eq!(tree.within_radius(&[1.0, 1.0], 0.0).len(), 1);
+        assert!(tree.within_radius(&[0.5, 0.5], -1.0).is_empty());
+        assert!(tree.within_radius(&[0.5, 0.5], f64::NAN).is_empty());
+        assert_eq!(tree.remove(&[1.0, 1.0]), Some("corner"));
+        assert_eq!(tree.len(), 100);
+    }
+}
@@ -0,0 +1,452 @@
+use std::fmt;
+
+use crate::{PriorityQueue, PriorityQueueImpl};
+
+// most and fewest entries per node (the root may have fewer).
+const MAX_ENTRIES: usize = 16;
+const MIN_ENTRIES: usize = 6;
+
+/// an axis-aligned box in `D` dimensions, bounds included.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rect<const D: usize> {
+    pub min: [f64; D],
+    pub max: [f64; D],
+}
+
+impl<const D: usize> Rect<D> {
+    pub fn new(min: [f64; D], max: [f64; D]) -> Self {
+        assert!((0..D).all(|d| min[d] <= max[d]), "min must not exceed max");
+        Rect { min, max }
+    }
+
+    /// the degenerate box holding just `point`.
+    pub fn point(point: [f64; D]) -> Self {
+        Rect::new(point, point)
+    }
+
+    /// volume (area in two dimensions).
+    pub fn area(&self) -> f64 {
+        (0..D).map(|d| self.max[d] - self.min[d]).product()
+    }
+
+    /// sum of the side lengths.
+    pub fn margin(&self) -> f64 {
+        (0..D).map(|d| self.max[d] - self.min[d]).sum()
+    }
+
+    /// the smallest box containing both.
+    pub fn union(&self, other: &Rect<D>) -> Rect<D> {
+        let mut result = *self;
+        for d in 0..D {
+            result.min[d] = result.min[d].min(other.min[d]);
+            result.max[d] = result.max[d].max(other.max[d]);
+        }
+        result
+    }
+
+    pub fn intersects(&self, other: &Rect<D>) -> bool {
+        (0..D).all(|d| self.min[d] <= other.max[d] && other.min[d] <= self.max[d])
+    }
+
+    pub fn contains(&self, other: &Rect<D>) -> bool {
+        (0..D).all(|d| self.min[d] <= other.min[d] && other.max[d] <= self.max[d])
+    }
+
+    fn overlap(&self, other: &Rect<D>) -> f64 {
+        (0..D)
+            .map(|d| (self.max[d].min(other.max[d]) - self.min[d].max(other.min[d])).max(0.0))
+            .product()
+    }
+
+    /// squared distance from `point` to the nearest point of the box.
+    pub fn distance2(&self, point: &[f64; D]) -> f64 {
+        (0..D)
+            .map(|d| {
+                let gap = (self.min[d] - point[d])
+                    .max(point[d] - self.max[d])
+                    .max(0.0);
+                gap * gap
+            })
+            .sum()
+    }
+}
+
+enum Node<V, const D: usize> {
+    Leaf(Vec<(Rect<D>, V)>),
+    Inner(Vec<(Rect<D>, Box<Node<V, D>>)>),
+}
+
+impl<V, const D: usize> Node<V, D> {
+    fn len(&self) -> usize {
+        match self {
+            Node::Leaf(entries) => entries.len(),
+            Node::Inner(children) => children.len(),
+        }
+    }
+
+    fn bounds(&self) -> Rect<D> {
+        fn cover<T, const D: usize>(entries: &[(Rect<D>, T)]) -> Rect<D> {
+            let first = entries[0].0;
+            entries[1..].iter().fold(first, |acc, (r, _)| acc.union(r))
+        }
+        match self {
+            Node::Leaf(entries) => cover(entries),
+            Node::Inner(children) => cover(children),
+        }
+    }
+
+    // insert into this subtree; if it overflows, returns the split-off
+    // sibling.
+    fn insert(&mut self, rect: Rect<D>, value: V) -> Option<Box<Node<V, D>>> {
+        match self {
+            Node::Leaf(entries) => {
+                entries.push((rect, value));
+                if entries.len() > MAX_ENTRIES {
+                    return Some(Box::new(Node::Leaf(split(entries))));
+                }
+            }
+            Node::Inner(children) => {
+                let i = choose_subtree(children, &rect);
+                let (bounds, child) = &mut children[i];
+                *bounds = bounds.union(&rect);
+                if let Some(sibling) = child.insert(rect, value) {
+                    *bounds = child.bounds();
+                    children.push((sibling.bounds(), sibling));
+                    if children.len() > MAX_ENTRIES {
+                        return Some(Box::new(Node::Inner(split(children))));
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    // remove an entry equal to `rect`, pushing the entries of nodes that
+    // become too small onto `orphans`.
+    fn remove(&mut self, rect: &Rect<D>, orphans: &mut Vec<(Rect<D>, V)>) -> Option<V> {
+        match self {
+            Node::Leaf(entries) => {
+                let i = entries.iter().position(|(r, _)| r == rect)?;
+                Some(entries.swap_remove(i).1)
+            }
+            Node::Inner(children) => {
+                for i in 0..children.len() {
+                    if !children[i].0.contains(rect) {
+                        continue;
+                    }
+                    if let Some(value) = children[i].1.remove(rect, orphans) {
+                        if children[i].1.len() < MIN_ENTRIES {
+                            children.swap_remove(i).1.drain_into(orphans);
+                        } else {
+                            children[i].0 = children[i].1.bounds();
+                        }
+                        return Some(value);
+                    }
+                }
+                None
+            }
+        }
+    }
+
+    fn drain_into(self, out: &mut Vec<(Rect<D>, V)>) {
+        match self {
+            Node::Leaf(entries) => out.extend(entries),
+            Node::Inner(children) => {
+                for (_, child) in children {
+                    child.drain_into(out);
+                }
+            }
+        }
+    }
+}
+
+// the child whose box grows least to cover `rect`, preferring smaller boxes.
+fn choose_subtree<T, const D: usize>(children: &[(Rect<D>, T)], rect: &Rect<D>) -> usize {
+    let cost = |r: &Rect<D>| (r.union(rect).area() - r.area(), r.area());
+    (0..children.len())
+        .min_by(|&a, &b| {
+            cost(&children[a].0)
+                .partial_cmp(&cost(&children[b].0))
+                .unwrap()
+        })
+        .unwrap()
+}
+
+// r*-tree split: pick the axis whose sorted distributions have the least
+// total margin, then the distribution along it with the least overlap (and
+// then area). keeps the first group in `entries` and returns the second.
+fn split<T, const D: usize>(entries: &mut Vec<(Rect<D>, T)>) -> Vec<(Rect<D>, T)> {
+    let n = entries.len();
+    // bounding boxes of the first k and of the rest, for an ordering.
+    let groups = |order: &[usize]| {
+        let mut prefix = vec![entries[order[0]].0; n];
+        let mut suffix = vec![entries[order[n - 1]].0; n];
+        for i in 1..n {
+            prefix[i] = prefix[i - 1].union(&entries[order[i]].0);
+            suffix[n - 1 - i] = suffix[n - i].union(&entries[order[n - 1 - i]].0);
+        }
+        (MIN_ENTRIES..=n - MIN_ENTRIES).map(move |k| (k, prefix[k - 1], suffix[k]))
+    };
+    let mut best_axis: Option<(f64, Vec<Vec<usize>>)> = None;
+    for d in 0..D {
+        let mut by_min: Vec<usize> = (0..n).collect();
+        by_min.sort_by(|&a, &b| {
+            entries[a].0.min[d]
+                .partial_cmp(&entries[b].0.min[d])
+                .unwrap()
+        });
+        let mut by_max: Vec<usize> = (0..n).collect();
+        by_max.sort_by(|&a, &b| {
+            entries[a].0.max[d]
+                .partial_cmp(&entries[b].0.max[d])
+                .unwrap()
+        });
+        let margin: f64 = [&by_min, &by_max]
+            .iter()
+            .flat_map(|order| groups(order))
+            .map(|(_, a, b)| a.margin() + b.margin())
+            .sum();
+        if best_axis.as_ref().is_none_or(|(m, _)| margin < *m) {
+            best_axis = Some((margin, vec![by_min, by_max]));
+        }
+    }
+    let orders = best_axis.unwrap().1;
+    let mut best: Option<((f64, f64), usize, usize)> = None;
+    for (o, order) in orders.iter().enumerate() {
+        for (k, a, b) in groups(order) {
+            let cost = (a.overlap(&b), a.area() + b.area());
+            if best.is_none_or(|(c, _, _)| cost < c) {
+                best = Some((cost, o, k));
+            }
+        }
+    }
+    let (_, o, k) = best.unwrap();
+    let mut slots: Vec<Option<(Rect<D>, T)>> = entries.drain(..).map(Some).collect();
+    let mut ordered = orders[o].iter().map(|&i| slots[i].take().unwrap());
+    entries.extend(ordered.by_ref().take(k));
+    ordered.collect()
+}
+
+/// r-tree over boxes in `D` dimensions with r*-tree node splits, for window
+/// queries ("which boxes intersect this one") and nearest-neighbor search.
+pub struct RTree<V, const D: usize> {
+    root: Node<V, D>,
+    len: usize,
+}
+
+impl<V, const D: usize> RTree<V, D> {
+    pub fn new() -> Self {
+        RTree {
+            root: Node::Leaf(Vec::new()),
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// number of levels of nodes.
+    pub fn height(&self) -> usize {
+        let mut height = 1;
+        let mut node = &self.root;
+        while let Node::Inner(children) = node {
+            node = &children[0].1;
+            height += 1;
+        }
+        height
+    }
+
+    pub fn insert(&mut self, rect: Rect<D>, value: V) {
+        self.len += 1;
+        if let Some(sibling) = self.root.insert(rect, value) {
+            let old = std::mem::replace(&mut self.root, Node::Inner(Vec::new()));
+            self.root = Node::Inner(vec![
+                (old.bounds(), Box::new(old)),
+                (sibling.bounds(), sibling),
+            ]);
+        }
+    }
+
+    /// remove an entry whose box equals `rect`, returning its value.
+    pub fn remove(&mut self, rect: &Rect<D>) -> Option<V> {
+        let mut orphans = Vec::new();
+        let value = self.root.remove(rect, &mut orphans)?;
+        self.len -= 1 + orphans.len();
+        // a root with a single child is replaced by it.
+        while let Node::Inner(children) = &mut self.root {
+            match children.len() {
+                0 => self.root = Node::Leaf(Vec::new()),
+                1 => self.root = *children.pop().unwrap().1,
+                _ => break,
+            }
+        }
+        for (r, v) in orphans {
+            self.insert(r, v);
+        }
+        Some(value)
+    }
+
+    /// the entries whose boxes intersect `window`.
+    pub fn search(&self, window: &Rect<D>) -> Vec<(&Rect<D>, &V)> {
+        let mut out = Vec::new();
+        let mut stack = vec![&self.root];
+        while let Some(node) = stack.pop() {
+            match node {
+                Node::Leaf(entries) => out.extend(
+                    entries
+                        .iter()
+                        .filter(|(r, _)| r.intersects(window))
+                        .map(|(r, v)| (r, v)),
+                ),
+                Node::Inner(children) => stack.extend(
+                    children
+                        .iter()
+                        .filter(|(r, _)| r.intersects(window))
+                        .map(|(_, child)| &**child),
+                ),
+            }
+        }
+        out
+    }
+
+    /// the `k` entries closest to `point`, nearest first, with their
+    /// distances. nodes are visited best-first by distance to their boxes.
+    pub fn k_nearest(&self, point: &[f64; D], k: usize) -> Vec<(&Rect<D>, &V, f64)> {
+        enum Item<'a, V, const D: usize> {
+            Node(&'a Node<V, D>),
+            Entry(&'a Rect<D>, &'a V),
+        }
+        // a min-queue: distances are non-negative, so their bits sort like them.
+        let key = |d2: f64| u64::MAX - d2.to_bits();
+        let mut queue = PriorityQueueImpl::new();
+        queue.insert(Item::Node(&self.root), key(0.0));
+        let mut out = Vec::new();
+        while out.len() < k {
+            match queue.pop() {
+                None => break,
+                Some(Item::Entry(r, v)) => out.push((r, v, r.distance2(point).sqrt())),
+                Some(Item::Node(Node::Leaf(entries))) => {
+                    for (r, v) in entries {
+                        queue.insert(Item::Entry(r, v), key(r.distance2(point)));
+                    }
+                }
+                Some(Item::Node(Node::Inner(children))) => {
+                    for (r, child) in children {
+                        queue.insert(Item::Node(child), key(r.distance2(point)));
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    /// the entry closest to `point`, with its distance.
+    pub fn nearest(&self, point: &[f64; D]) -> Option<(&Rect<D>, &V, f64)> {
+        self.k_nearest(point, 1).pop()
+    }
+}
+
+impl<V, const D: usize> Default for RTree<V, D> {
+    fn default() -> Self {
+        RTree::new()
+    }
+}
+
+impl<V, const D: usize> fmt::Debug for RTree<V, D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RTree")
+            .field("len", &self.len)
+            .field("height", &self.height())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    fn random_rect(rng: &mut Rng) -> Rect<2> {
+        let (x, y) = (rng.next_f64() * 100.0, rng.next_f64() * 100.0);
+        Rect::new([x, y], [x + rng.next_f64() * 5.0, y + rng.next_f64() * 5.0])
+    }
+
+    // every node's box covers its entries, and node sizes stay in bounds.
+    fn check<V>(node: &Node<V, 2>, is_root: bool) -> usize {
+        assert!(node.len() <= MAX_ENTRIES && (is_root || node.len() >= MIN_ENTRIES));
+        match node {
+            Node::Leaf(entries) => entries.len(),
+            Node::Inner(children) => children
+                .iter()
+                .map(|(r, child)| {
+                    assert_eq!(*r, child.bounds());
+                    check(child, false)
+                })
+                .sum(),
+        }
+    }
+
+    #[test]
+    fn test_against_brute_force() {
+        let mut rng = Rng::new(43);
+        let mut tree = RTree::new();
+        let mut model = Vec::new();
+        for i in 0..2000 {
+            let rect = random_rect(&mut rng);
+            tree.insert(rect, i);
+            model.push((rect, i));
+        }
+        for _ in 0..500 {
+            let i = rng.index(model.len());
+            let (rect, value) = model.swap_remove(i);
+            assert_eq!(tree.remove(&rect), Some(value));
+        }
+        assert_eq!(check(&tree.root, true), model.len());
+        assert_eq!(tree.len(), model.len());
+        assert!(tree.height() >= 3);
+        for _ in 0..100 {
+            let window = random_rect(&mut rng);
+            let mut found: Vec<_> = tree.search(&window).iter().map(|(_, &v)| v).collect();
+            let mut expected: Vec<_> = model
+                .iter()
+                .filter(|(r, _)| r.intersects(&window))
+                .map(|&(_, v)| v)
+                .collect();
+            found.sort_unstable();
+            expected.sort_unstable();
+            assert_eq!(found, expected);
+            let point = [rng.next_f64() * 100.0, rng.next_f64() * 100.0];
+            let mut distances: Vec<f64> = model
+                .iter()
+                .map(|(r, _)| r.distance2(&point).sqrt())
+                .collect();
+            distances.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            let knn: Vec<f64> = tree.k_nearest(&point, 5).iter().map(|n| n.2).collect();
+            assert_eq!(knn, distances[..5].to_vec());
+        }
+    }
+
+    #[test]
+    fn test_rect() {
+        let a = Rect::new([0.0, 0.0], [2.0, 1.0]);
+        let b = Rect::new([2.0, 1.0], [3.0, 3.0]);
+        assert!(a.intersects(&b));
+        assert_eq!(a.union(&b), Rect::new([0.0, 0.0], [3.0, 3.0]));
+        assert_eq!((a.area(), a.margin()), (2.0, 3.0));
+        assert_eq!(a.distance2(&[5.0, 5.0]), 25.0);
+        assert_eq!(a.distance2(&[1.0, 0.5]), 0.0);
+        let mut tree = RTree::new();
+        assert!(tree.nearest(&[0.0, 0.0]).is_none());
+        tree.insert(a, "a");
+        tree.insert(Rect::point([10.0, 10.0]), "p");
+        assert_eq!(tree.nearest(&[9.0, 9.0]).map(|n| *n.1), Some("p"));
+        assert_eq!(tree.remove(&a), Some("a"));
+        assert_eq!(tree.remove(&a), None);
+        assert_eq!(tree.len(), 1);
+    }
+}
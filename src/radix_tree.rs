@@ -0,0 +1,338 @@
+use std::fmt;
+
+struct Node<V> {
+    // edge label leading into this node.
+    label: Vec<u8>,
+    // sorted by the first byte of their labels, which are all distinct.
+    children: Vec<Node<V>>,
+    value: Option<V>,
+}
+
+impl<V> Node<V> {
+    fn new(label: Vec<u8>, value: Option<V>) -> Self {
+        Node {
+            label,
+            children: Vec::new(),
+            value,
+        }
+    }
+
+    fn child(&self, byte: u8) -> Result<usize, usize> {
+        self.children.binary_search_by_key(&byte, |c| c.label[0])
+    }
+
+    fn count(&self) -> usize {
+        1 + self.children.iter().map(Node::count).sum::<usize>()
+    }
+}
+
+fn common_prefix(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(x, y)| x == y).count()
+}
+
+/// compressed prefix tree (patricia trie) over byte-string keys: chains of
+/// single-child nodes are collapsed into one edge, so the node count is
+/// bounded by twice the number of keys regardless of their length.
+pub struct RadixTree<V> {
+    root: Node<V>,
+    len: usize,
+}
+
+impl<V> RadixTree<V> {
+    /// create an empty tree.
+    pub fn new() -> Self {
+        RadixTree {
+            root: Node::new(Vec::new(), None),
+            len: 0,
+        }
+    }
+
+    /// number of stored keys.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// number of nodes, including the root.
+    pub fn node_count(&self) -> usize {
+        self.root.count()
+    }
+
+    /// insert `value` for `key`, returning the previous value if there was one.
+    pub fn insert<K: AsRef<[u8]>>(&mut self, key: K, value: V) -> Option<V> {
+        let mut node = &mut self.root;
+        let mut rest = key.as_ref();
+        loop {
+            if rest.is_empty() {
+                let old = node.value.replace(value);
+                if old.is_none() {
+                    self.len += 1;
+                }
+                return old;
+            }
+            let slot = match node.child(rest[0]) {
+                Err(slot) => {
+                    node.children
+                        .insert(slot, Node::new(rest.to_vec(), Some(value)));
+                    self.len += 1;
+                    return None;
+                }
+                Ok(slot) => slot,
+            };
+            let child = &mut node.children[slot];
+            let shared = common_prefix(&child.label, rest);
+            if shared < child.label.len() {
+                // split the edge: the shared part becomes a new parent.
+                let tail = child.label.split_off(shared);
+                let mut lower = std::mem::replace(child, Node::new(tail, None));
+                std::mem::swap(&mut lower.label, &mut child.label);
+                child.children.push(lower);
+            }
+            node = &mut node.children[slot];
+            rest = &rest[shared..];
+        }
+    }
+
+    /// returns the value stored for exactly `key`.
+    pub fn get<K: AsRef<[u8]>>(&self, key: K) -> Option<&V> {
+        let mut node = &self.root;
+        let mut rest = key.as_ref();
+        while !rest.is_empty() {
+            let child = &node.children[node.child(rest[0]).ok()?];
+            if !rest.starts_with(&child.label) {
+                return None;
+            }
+            rest = &rest[child.label.len()..];
+            node = child;
+        }
+        node.value.as_ref()
+    }
+
+    pub fn contains_key<K: AsRef<[u8]>>(&self, key: K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// remove `key`, returning its value if it was present. nodes are merged
+    /// back so the tree stays compressed.
+    pub fn remove<K: AsRef<[u8]>>(&mut self, key: K) -> Option<V> {
+        let removed = remove(&mut self.root, key.as_ref());
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    /// the longest stored key that is a prefix of `key`, as its length in bytes
+    /// together with its value.
+    pub fn longest_prefix<K: AsRef<[u8]>>(&self, key: K) -> Option<(usize, &V)> {
+        let mut node = &self.root;
+        let mut rest = key.as_ref();
+        let mut depth = 0;
+        let mut best = node.value.as_ref().map(|v| (0, v));
+        while !rest.is_empty() {
+            let child = match node.child(rest[0]) {
+                Ok(slot) => &node.children[slot],
+                Err(_) => break,
+            };
+            if !rest.starts_with(&child.label) {
+                break;
+            }
+            depth += child.label.len();
+            rest = &rest[child.label.len()..];
+            node = child;
+            if let Some(value) = node.value.as_ref() {
+                best = Some((depth, value));
+            }
+        }
+        best
+    }
+
+    /// length of the longest common prefix between `key` and any stored key.
+    pub fn longest_common_prefix<K: AsRef<[u8]>>(&self, key: K) -> usize {
+        if self.is_empty() {
+            return 0;
+        }
+        let mut node = &self.root;
+        let mut rest = key.as_ref();
+        let mut depth = 0;
+        while !rest.is_empty() {
+            let child = match node.child(rest[0]) {
+                Ok(slot) => &node.children[slot],
+                Err(_) => break,
+            };
+            let shared = common_prefix(&child.label, rest);
+            depth += shared;
+            if shared < child.label.len() {
+                break;
+            }
+            rest = &rest[shared..];
+            node = child;
+        }
+        depth
+    }
+
+    /// iterate over the stored keys starting with `prefix`, in lexicographic order.
+    pub fn iter_prefix<K: AsRef<[u8]>>(&self, prefix: K) -> Iter<'_, V> {
+        let mut node = &self.root;
+        let mut path = Vec::new();
+        let mut rest = prefix.as_ref();
+        while !rest.is_empty() {
+            let child = match node.child(rest[0]) {
+                Ok(slot) => &node.children[slot],
+                Err(_) => return Iter { stack: Vec::new() },
+            };
+            let shared = common_prefix(&child.label, rest);
+            if shared < rest.len() && shared < child.label.len() {
+                return Iter { stack: Vec::new() };
+            }
+            path.extend_from_slice(&child.label);
+            rest = &rest[shared..];
+            node = child;
+        }
+        Iter {
+            stack: vec![(path, node)],
+        }
+    }
+
+    /// iterate over every key in lexicographic order.
+    pub fn iter(&self) -> Iter<'_, V> {
+        self.iter_prefix([])
+    }
+}
+
+fn remove<V>(node: &mut Node<V>, key: &[u8]) -> Option<V> {
+    if key.is_empty() {
+        return node.value.take();
+    }
+    let slot = node.child(key[0]).ok()?;
+    let child = &mut node.children[slot];
+    if !key.starts_with(&child.label) {
+        return None;
+    }
+    let removed = remove(child, &key[child.label.len()..]);
+    if removed.is_some() && child.value.is_none() {
+        match child.children.len() {
+            0 => {
+                node.children.remove(slot);
+            }
+            1 => {
+                // fold the only grandchild into the child.
+                let grandchild = child.children.pop().unwrap();
+                child.label.extend_from_slice(&grandchild.label);
+                child.children = grandchild.children;
+                child.value = grandchild.value;
+            }
+            _ => {}
+        }
+    }
+    removed
+}
+
+impl<V> Default for RadixTree<V> {
+    fn default() -> Self {
+        RadixTree::new()
+    }
+}
+
+impl<V: fmt::Debug> fmt::Debug for RadixTree<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// lexicographic iterator over the keys of a radix tree, yielding owned keys.
+pub struct Iter<'a, V> {
+    stack: Vec<(Vec<u8>, &'a Node<V>)>,
+}
+
+impl<'a, V> Iterator for Iter<'a, V> {
+    type Item = (Vec<u8>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((path, node)) = self.stack.pop() {
+            for child in node.children.iter().rev() {
+                let mut child_path = path.clone();
+                child_path.extend_from_slice(&child.label);
+                self.stack.push((child_path, child));
+            }
+            if let Some(value) = node.value.as_ref() {
+                return Some((path, value));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_against_btree() {
+        let mut rng = Rng::new(8);
+        let mut tree = RadixTree::new();
+        let mut model = BTreeMap::new();
+        for step in 0..3000 {
+            let len = rng.index(6);
+            let key: Vec<u8> = (0..len).map(|_| b'a' + rng.index(3) as u8).collect();
+            if rng.chance(0.6) {
+                assert_eq!(tree.insert(&key, step), model.insert(key.clone(), step));
+            } else {
+                assert_eq!(tree.remove(&key), model.remove(&key));
+            }
+            assert_eq!(tree.get(&key), model.get(&key));
+        }
+        assert_eq!(tree.len(), model.len());
+        let entries: Vec<(Vec<u8>, &i32)> = tree.iter().collect();
+        let expected: Vec<(Vec<u8>, &i32)> = model.iter().map(|(k, v)| (k.clone(), v)).collect();
+        assert_eq!(entries, expected);
+        assert!(tree.node_count() <= 2 * tree.len() + 1);
+    }
+
+    #[test]
+    fn test_compression() {
+        let mut tree = RadixTree::new();
+        tree.insert("romane", 1);
+        tree.insert("romanus", 2);
+        tree.insert("romulus", 3);
+        tree.insert("rubens", 4);
+        tree.insert("ruber", 5);
+        tree.insert("rubicon", 6);
+        tree.insert("rubicundus", 7);
+        // root, r, om, an, e, us, ulus, ub, e, ns, r, ic, on, undus
+        assert_eq!(tree.node_count(), 14);
+        assert_eq!(tree.remove("rubicon"), Some(6));
+        assert_eq!(tree.remove("rubicon"), None);
+        // "ic" and "undus" are merged again.
+        assert_eq!(tree.node_count(), 12);
+        assert_eq!(tree.get("rubicundus"), Some(&7));
+        assert_eq!(tree.get("rubic"), None);
+    }
+
+    #[test]
+    fn test_prefix_queries() {
+        let mut tree = RadixTree::new();
+        for (i, word) in ["test", "team", "toast", "tea", "ten"].iter().enumerate() {
+            tree.insert(word, i);
+        }
+        let keys: Vec<String> = tree
+            .iter_prefix("te")
+            .map(|(k, _)| String::from_utf8(k).unwrap())
+            .collect();
+        assert_eq!(keys, vec!["tea", "team", "ten", "test"]);
+        // the prefix may end in the middle of an edge.
+        assert_eq!(tree.iter_prefix("toa").count(), 1);
+        assert_eq!(tree.iter_prefix("tx").count(), 0);
+        assert_eq!(tree.longest_prefix("teammate"), Some((4, &1)));
+        assert_eq!(tree.longest_prefix("teb"), None);
+        assert_eq!(tree.longest_common_prefix("teapot"), 3);
+        assert_eq!(tree.longest_common_prefix("tester"), 4);
+        assert_eq!(tree.longest_common_prefix("toad"), 3);
+        assert_eq!(tree.longest_common_prefix("xyz"), 0);
+    }
+}
@@ -0,0 +1,273 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops::Range;
+
+/// a set of values stored as disjoint half-open ranges, kept sorted and
+/// merged so that no two ranges overlap or touch: inserting `1..3` and
+/// `3..5` leaves the single range `1..5`.
+///
+/// suits sets made of long runs, like the offsets of a stream that have
+/// been processed or blocks of ip addresses.
+#[derive(Clone, PartialEq, Eq)]
+pub struct RangeSet<T> {
+    // start -> end of each range.
+    ranges: BTreeMap<T, T>,
+}
+
+impl<T: Ord + Copy> RangeSet<T> {
+    pub fn new() -> Self {
+        RangeSet {
+            ranges: BTreeMap::new(),
+        }
+    }
+
+    /// number of disjoint ranges.
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// the range containing `value`.
+    pub fn range_of(&self, value: &T) -> Option<Range<T>> {
+        let (&start, &end) = self.ranges.range(..=*value).next_back()?;
+        if *value < end {
+            Some(start..end)
+        } else {
+            None
+        }
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        self.range_of(value).is_some()
+    }
+
+    /// check whether all of `range` is in the set.
+    pub fn contains_range(&self, range: &Range<T>) -> bool {
+        range.start >= range.end
+            || self
+                .range_of(&range.start)
+                .is_some_and(|r| range.end <= r.end)
+    }
+
+    /// check whether any of `range` is in the set.
+    pub fn overlaps(&self, range: &Range<T>) -> bool {
+        if range.start >= range.end {
+            return false;
+        }
+        match self.ranges.range(..range.end).next_back() {
+            Some((_, &end)) => end > range.start,
+            None => false,
+        }
+    }
+
+    /// add the values of `range`, returning whether any were new.
+    pub fn insert(&mut self, range: Range<T>) -> bool {
+        if range.start >= range.end || self.contains_range(&range) {
+            return false;
+        }
+        let (mut start, mut end) = (range.start, range.end);
+        if let Some((&s, &e)) = self.ranges.range(..=start).next_back() {
+            if e >= start {
+                start = s;
+                end = end.max(e);
+            }
+        }
+        // absorb the ranges starting inside or right after the new one.
+        let absorbed: Vec<(T, T)> = self
+            .ranges
+            .range(start..=end)
+            .map(|(&s, &e)| (s, e))
+            .collect();
+        for (s, e) in absorbed {
+            self.ranges.remove(&s);
+            end = end.max(e);
+        }
+        self.ranges.insert(start, end);
+        true
+    }
+
+    /// remove the values of `range`, returning whether any were present.
+    pub fn remove(&mut self, range: &Range<T>) -> bool {
+        if !self.overlaps(range) {
+            return false;
+        }
+        // a range starting before `range` keeps its head and maybe its tail.
+        if let Some((&s, &e)) = self.ranges.range(..range.start).next_back() {
+            if e > range.start {
+                self.ranges.insert(s, range.start);
+                if e > range.end {
+                    self.ranges.insert(range.end, e);
+                }
+            }
+        }
+        let inside: Vec<(T, T)> = self
+            .ranges
+            .range(range.start..range.end)
+            .map(|(&s, &e)| (s, e))
+            .collect();
+        for (s, e) in inside {
+            self.ranges.remove(&s);
+            if e > range.end {
+                self.ranges.insert(range.end, e);
+            }
+        }
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.ranges.clear();
+    }
+
+    /// iterate over the ranges in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = Range<T>> + '_ {
+        self.ranges.iter().map(|(&s, &e)| s..e)
+    }
+
+    /// the parts of `within` that are not in the set, in order; e.g. the
+    /// offsets still missing from a download.
+    pub fn gaps(&self, within: Range<T>) -> Vec<Range<T>> {
+        let mut gaps = Vec::new();
+        if within.start >= within.end {
+            return gaps;
+        }
+        let mut cursor = within.start;
+        let first = self
+            .range_of(&within.start)
+            .map_or(within.start, |r| r.start);
+        for (&s, &e) in self.ranges.range(first..within.end) {
+            if s > cursor {
+                gaps.push(cursor..s);
+            }
+            cursor = cursor.max(e);
+        }
+        if cursor < within.end {
+            gaps.push(cursor..within.end);
+        }
+        gaps
+    }
+
+    pub fn union(&self, other: &RangeSet<T>) -> RangeSet<T> {
+        let mut result = self.clone();
+        for range in other.iter() {
+            result.insert(range);
+        }
+        result
+    }
+
+    pub fn difference(&self, other: &RangeSet<T>) -> RangeSet<T> {
+        let mut result = self.clone();
+        for range in other.iter() {
+            result.remove(&range);
+        }
+        result
+    }
+
+    pub fn intersection(&self, other: &RangeSet<T>) -> RangeSet<T> {
+        let mut result = RangeSet::new();
+        let (mut a, mut b) = (self.iter().peekable(), other.iter().peekable());
+        while let (Some(x), Some(y)) = (a.peek(), b.peek()) {
+            let (start, end) = (x.start.max(y.start), x.end.min(y.end));
+            if start < end {
+                result.ranges.insert(start, end);
+            }
+            // drop whichever ends first.
+            if x.end < y.end {
+                a.next();
+            } else {
+                b.next();
+            }
+        }
+        result
+    }
+}
+
+impl<T: Ord + Copy> Default for RangeSet<T> {
+    fn default() -> Self {
+        RangeSet::new()
+    }
+}
+
+impl<T: Ord + Copy> FromIterator<Range<T>> for RangeSet<T> {
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> Self {
+        let mut set = RangeSet::new();
+        for range in iter {
+            set.insert(range);
+        }
+        set
+    }
+}
+
+impl<T: Ord + Copy + fmt::Debug> fmt::Debug for RangeSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_merging() {
+        let mut set = RangeSet::new();
+        assert!(set.insert(1..3));
+        assert!(set.insert(3..5));
+        assert!(set.insert(10..12));
+        assert!(!set.insert(2..4));
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![1..5, 10..12]);
+        assert!(set.insert(4..10));
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![1..12]);
+        assert!(set.remove(&(5..7)));
+        assert_eq!(format!("{:?}", set), "{1..5, 7..12}");
+        assert!(set.contains_range(&(7..12)) && !set.contains_range(&(4..8)));
+        assert!(!set.overlaps(&(5..7)) && set.overlaps(&(6..8)));
+        assert_eq!(set.gaps(0..15), vec![0..1, 5..7, 12..15]);
+        assert_eq!(set.gaps(2..4), vec![]);
+        assert_eq!(set.gaps(9..9), vec![]);
+        let other: RangeSet<i32> = vec![0..2, 4..8, 11..20].into_iter().collect();
+        let both: Vec<_> = set.intersection(&other).iter().collect();
+        assert_eq!(both, vec![1..2, 4..5, 7..8, 11..12]);
+        assert_eq!(set.union(&other).iter().collect::<Vec<_>>(), vec![0..20]);
+        assert_eq!(
+            set.difference(&other).iter().collect::<Vec<_>>(),
+            vec![2..4, 8..11]
+        );
+    }
+
+    #[test]
+    fn test_against_bitmap() {
+        let mut rng = Rng::new(50);
+        let mut set = RangeSet::new();
+        let mut model = [false; 300];
+        let as_set = |model: &[bool]| -> RangeSet<usize> {
+            (0..model.len())
+                .filter(|&i| model[i])
+                .map(|i| i..i + 1)
+                .collect()
+        };
+        for _ in 0..2000 {
+            let start = rng.index(290);
+            let range = start..start + rng.index(10);
+            let insert = rng.chance(0.6);
+            let changed = if insert {
+                set.insert(range.clone())
+            } else {
+                set.remove(&range)
+            };
+            let bits = &mut model[range];
+            assert_eq!(changed, bits.iter().any(|&b| b != insert));
+            bits.iter_mut().for_each(|b| *b = insert);
+            assert_eq!(set, as_set(&model));
+            let probe = rng.index(300);
+            assert_eq!(set.contains(&probe), model[probe]);
+        }
+        let other = as_set(&model.iter().map(|b| !b).collect::<Vec<_>>());
+        assert!(set.intersection(&other).is_empty());
+        assert_eq!(set.union(&other).iter().collect::<Vec<_>>(), vec![0..300]);
+    }
+}
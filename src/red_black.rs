@@ -0,0 +1,432 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::OrderedMap;
+
+type Link<K, V> = Option<Box<Node<K, V>>>;
+
+struct Node<K, V> {
+    key: K,
+    value: V,
+    red: bool,
+    left: Link<K, V>,
+    right: Link<K, V>,
+}
+
+fn is_red<K, V>(link: &Link<K, V>) -> bool {
+    link.as_ref().is_some_and(|node| node.red)
+}
+
+fn left_left_red<K, V>(node: &Node<K, V>) -> bool {
+    node.left.as_ref().is_some_and(|left| is_red(&left.left))
+}
+
+fn right_left_red<K, V>(node: &Node<K, V>) -> bool {
+    node.right.as_ref().is_some_and(|right| is_red(&right.left))
+}
+
+fn rotate_left<K, V>(mut h: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    let mut x = h.right.take().expect("rotation needs a right child");
+    h.right = x.left.take();
+    x.red = h.red;
+    h.red = true;
+    x.left = Some(h);
+    x
+}
+
+fn rotate_right<K, V>(mut h: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    let mut x = h.left.take().expect("rotation needs a left child");
+    h.left = x.right.take();
+    x.red = h.red;
+    h.red = true;
+    x.right = Some(h);
+    x
+}
+
+fn flip_colors<K, V>(h: &mut Node<K, V>) {
+    h.red = !h.red;
+    if let Some(left) = h.left.as_mut() {
+        left.red = !left.red;
+    }
+    if let Some(right) = h.right.as_mut() {
+        right.red = !right.red;
+    }
+}
+
+// restore the left-leaning invariants on the way back up.
+fn fix_up<K, V>(mut h: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    if is_red(&h.right) && !is_red(&h.left) {
+        h = rotate_left(h);
+    }
+    if is_red(&h.left) && left_left_red(&h) {
+        h = rotate_right(h);
+    }
+    if is_red(&h.left) && is_red(&h.right) {
+        flip_colors(&mut h);
+    }
+    h
+}
+
+fn move_red_left<K, V>(mut h: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    flip_colors(&mut h);
+    if right_left_red(&h) {
+        h.right = h.right.take().map(rotate_right);
+        h = rotate_left(h);
+        flip_colors(&mut h);
+    }
+    h
+}
+
+fn move_red_right<K, V>(mut h: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    flip_colors(&mut h);
+    if left_left_red(&h) {
+        h = rotate_right(h);
+        flip_colors(&mut h);
+    }
+    h
+}
+
+fn insert<K: Ord, V>(link: Link<K, V>, key: K, value: V, old: &mut Option<V>) -> Box<Node<K, V>> {
+    let mut h = match link {
+        None => {
+            return Box::new(Node {
+                key,
+                value,
+                red: true,
+                left: None,
+                right: None,
+            })
+        }
+        Some(h) => h,
+    };
+    match key.cmp(&h.key) {
+        Ordering::Less => h.left = Some(insert(h.left.take(), key, value, old)),
+        Ordering::Greater => h.right = Some(insert(h.right.take(), key, value, old)),
+        Ordering::Equal => *old = Some(std::mem::replace(&mut h.value, value)),
+    }
+    fix_up(h)
+}
+
+// remove the smallest node below `h`, returning the rest and the removed entry.
+fn remove_min<K, V>(mut h: Box<Node<K, V>>) -> (Link<K, V>, (K, V)) {
+    if h.left.is_none() {
+        let node = *h;
+        return (None, (node.key, node.value));
+    }
+    if !is_red(&h.left) && !left_left_red(&h) {
+        h = move_red_left(h);
+    }
+    let (left, min) = remove_min(h.left.take().unwrap());
+    h.left = left;
+    (Some(fix_up(h)), min)
+}
+
+// `key` must be present below `h`.
+fn remove<K: Ord, V>(mut h: Box<Node<K, V>>, key: &K, removed: &mut Option<V>) -> Link<K, V> {
+    if *key < h.key {
+        if !is_red(&h.left) && !left_left_red(&h) {
+            h = move_red_left(h);
+        }
+        h.left = remove(h.left.take().unwrap(), key, removed);
+    } else {
+        if is_red(&h.left) {
+            h = rotate_right(h);
+        }
+        if *key == h.key && h.right.is_none() {
+            *removed = Some(h.value);
+            return None;
+        }
+        if !is_red(&h.right) && !right_left_red(&h) {
+            h = move_red_right(h);
+        }
+        if *key == h.key {
+            let (right, (min_key, min_value)) = remove_min(h.right.take().unwrap());
+            h.right = right;
+            h.key = min_key;
+            *removed = Some(std::mem::replace(&mut h.value, min_value));
+        } else {
+            h.right = remove(h.right.take().unwrap(), key, removed);
+        }
+    }
+    Some(fix_up(h))
+}
+
+/// ordered map backed by a left-leaning red-black tree.
+pub struct RedBlackTree<K, V> {
+    root: Link<K, V>,
+    len: usize,
+}
+
+impl<K: Ord, V> RedBlackTree<K, V> {
+    /// create an empty tree.
+    pub fn new() -> Self {
+        RedBlackTree { root: None, len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// returns the value stored for `key`.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let mut link = &self.root;
+        while let Some(node) = link {
+            match key.cmp(&node.key) {
+                Ordering::Less => link = &node.left,
+                Ordering::Equal => return Some(&node.value),
+                Ordering::Greater => link = &node.right,
+            }
+        }
+        None
+    }
+
+    /// returns a mutable reference to the value stored for `key`.
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let mut link = &mut self.root;
+        while let Some(node) = link {
+            match key.cmp(&node.key) {
+                Ordering::Less => link = &mut node.left,
+                Ordering::Equal => return Some(&mut node.value),
+                Ordering::Greater => link = &mut node.right,
+            }
+        }
+        None
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// insert `value` for `key`, returning the previous value if there was one.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let mut old = None;
+        let mut root = insert(self.root.take(), key, value, &mut old);
+        root.red = false;
+        self.root = Some(root);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    /// remove `key`, returning its value if it was present.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        if !self.contains_key(key) {
+            return None;
+        }
+        let mut root = self.root.take().unwrap();
+        if !is_red(&root.left) && !is_red(&root.right) {
+            root.red = true;
+        }
+        let mut removed = None;
+        self.root = remove(root, key, &mut removed);
+        if let Some(root) = self.root.as_mut() {
+            root.red = false;
+        }
+        self.len -= 1;
+        removed
+    }
+
+    /// entry with the smallest key.
+    pub fn first(&self) -> Option<(&K, &V)> {
+        let mut node = self.root.as_ref()?;
+        while let Some(left) = node.left.as_ref() {
+            node = left;
+        }
+        Some((&node.key, &node.value))
+    }
+
+    /// entry with the largest key.
+    pub fn last(&self) -> Option<(&K, &V)> {
+        let mut node = self.root.as_ref()?;
+        while let Some(right) = node.right.as_ref() {
+            node = right;
+        }
+        Some((&node.key, &node.value))
+    }
+
+    /// iterate in increasing key order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut iter = Iter { stack: Vec::new() };
+        iter.push_left(&self.root);
+        iter
+    }
+}
+
+impl<K: Ord, V> Default for RedBlackTree<K, V> {
+    fn default() -> Self {
+        RedBlackTree::new()
+    }
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug> fmt::Debug for RedBlackTree<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Ord, V> std::iter::FromIterator<(K, V)> for RedBlackTree<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut tree = RedBlackTree::new();
+        iter.into_iter().for_each(|(k, v)| {
+            tree.insert(k, v);
+        });
+        tree
+    }
+}
+
+impl<K: Ord, V> OrderedMap<K, V> for RedBlackTree<K, V> {
+    fn new() -> Self {
+        RedBlackTree::new()
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn get(&self, key: &K) -> Option<&V> {
+        self.get(key)
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.insert(key, value)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        self.remove(key)
+    }
+
+    fn first(&self) -> Option<(&K, &V)> {
+        self.first()
+    }
+
+    fn last(&self) -> Option<(&K, &V)> {
+        self.last()
+    }
+
+    fn iter<'a>(&'a self) -> Box<dyn Iterator<Item = (&'a K, &'a V)> + 'a>
+    where
+        K: 'a,
+        V: 'a,
+    {
+        Box::new(self.iter())
+    }
+}
+
+/// in-order iterator over the entries of a red-black tree.
+pub struct Iter<'a, K, V> {
+    stack: Vec<&'a Node<K, V>>,
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    fn push_left(&mut self, mut link: &'a Link<K, V>) {
+        while let Some(node) = link {
+            self.stack.push(node);
+            link = &node.left;
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left(&node.right);
+        Some((&node.key, &node.value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+    use std::collections::BTreeMap;
+
+    // assert the red-black invariants, returning the black height.
+    fn check_subtree<K: Ord, V>(link: &Link<K, V>, lo: Option<&K>, hi: Option<&K>) -> usize {
+        match link {
+            None => 1,
+            Some(node) => {
+                assert!(lo.is_none_or(|lo| *lo < node.key), "keys out of order");
+                assert!(hi.is_none_or(|hi| node.key < *hi), "keys out of order");
+                if node.red {
+                    assert!(
+                        !is_red(&node.left) && !is_red(&node.right),
+                        "red node with red child"
+                    );
+                }
+                assert!(!is_red(&node.right), "right-leaning red link");
+                let left = check_subtree(&node.left, lo, Some(&node.key));
+                let right = check_subtree(&node.right, Some(&node.key), hi);
+                assert_eq!(left, right, "unequal black heights");
+                left + if node.red { 0 } else { 1 }
+            }
+        }
+    }
+
+    fn check_invariants<K: Ord, V>(tree: &RedBlackTree<K, V>) {
+        assert!(!is_red(&tree.root), "red root");
+        check_subtree(&tree.root, None, None);
+        assert_eq!(tree.iter().count(), tree.len());
+    }
+
+    #[test]
+    fn test_against_btree() {
+        let mut rng = Rng::new(33);
+        let mut tree = RedBlackTree::new();
+        let mut model = BTreeMap::new();
+        for step in 0..4000 {
+            let key = rng.range(0..500);
+            if rng.chance(0.55) {
+                assert_eq!(tree.insert(key, step), model.insert(key, step));
+            } else {
+                assert_eq!(tree.remove(&key), model.remove(&key));
+            }
+            if step % 50 == 0 {
+                check_invariants(&tree);
+            }
+        }
+        check_invariants(&tree);
+        assert!(tree.iter().eq(model.iter()));
+        assert_eq!(tree.first(), model.iter().next());
+        assert_eq!(tree.last(), model.iter().next_back());
+    }
+
+    #[test]
+    fn test_sequential() {
+        let mut tree: RedBlackTree<u32, u32> = (0..2048).map(|k| (k, k)).collect();
+        check_invariants(&tree);
+        for k in (0..2048).step_by(2) {
+            assert_eq!(tree.remove(&k), Some(k));
+        }
+        check_invariants(&tree);
+        assert_eq!(tree.remove(&0), None);
+        *tree.get_mut(&1).unwrap() = 7;
+        assert_eq!(tree.get(&1), Some(&7));
+        assert_eq!(tree.len(), 1024);
+        while let Some((&k, _)) = tree.first() {
+            tree.remove(&k);
+        }
+        assert!(tree.is_empty());
+    }
+
+    #[test]
+    fn test_ordered_map_trait() {
+        fn churn<M: OrderedMap<i32, i32>>() -> Vec<i32> {
+            let mut map = M::new();
+            for k in (0..20).rev() {
+                map.insert(k, -k);
+            }
+            for k in 5..15 {
+                map.remove(&k);
+            }
+            map.iter().map(|(k, _)| *k).collect()
+        }
+        assert_eq!(churn::<RedBlackTree<_, _>>(), churn::<BTreeMap<_, _>>());
+    }
+}
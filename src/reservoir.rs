@@ -0,0 +1,216 @@
+use std::convert::TryFrom;
+
+use crate::rng::Rng;
+
+const DEFAULT_SEED: u64 = 0x5eed_5a3b_1e5a_3b1e;
+
+/// how a [`Reservoir`] decides which items to keep.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Algorithm {
+    /// algorithm r: one random number per item.
+    R,
+    /// algorithm l: draws how many items to skip before the next
+    /// replacement, so it needs O(k (1 + log(n / k))) random numbers.
+    L,
+}
+
+/// uniform random sample of at most `capacity` items from a stream of
+/// unknown length: after `n` items, each of them is in the sample with
+/// probability `capacity / n`.
+#[derive(Clone, Debug)]
+pub struct Reservoir<T> {
+    items: Vec<T>,
+    capacity: usize,
+    seen: u64,
+    algorithm: Algorithm,
+    rng: Rng,
+    // algorithm l: the running maximum weight and the index of the next
+    // item to be taken.
+    w: f64,
+    next: u64,
+}
+
+impl<T> Reservoir<T> {
+    pub fn new(capacity: usize, algorithm: Algorithm) -> Self {
+        Reservoir::with_seed(capacity, algorithm, DEFAULT_SEED)
+    }
+
+    pub fn with_seed(capacity: usize, algorithm: Algorithm, seed: u64) -> Self {
+        assert!(capacity > 0, "capacity must be positive");
+        Reservoir {
+            items: Vec::with_capacity(capacity),
+            capacity,
+            seen: 0,
+            algorithm,
+            rng: Rng::new(seed),
+            w: 1.0,
+            next: 0,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// number of items offered so far.
+    pub fn seen(&self) -> u64 {
+        self.seen
+    }
+
+    /// the current sample, in no particular order.
+    pub fn sample(&self) -> &[T] {
+        &self.items
+    }
+
+    pub fn into_sample(self) -> Vec<T> {
+        self.items
+    }
+
+    // a uniform value in (0, 1], safe to take the logarithm of.
+    fn unit(&mut self) -> f64 {
+        1.0 - self.rng.next_f64()
+    }
+
+    // algorithm l: advance `next` past the items to skip.
+    fn schedule(&mut self) {
+        let k = self.capacity as f64;
+        self.w *= (self.unit().ln() / k).exp();
+        let skip = (self.unit().ln() / (1.0 - self.w).ln()).floor();
+        // a huge skip just means no further replacement in practice.
+        self.next = self
+            .next
+            .saturating_add(skip.min(u64::MAX as f64) as u64 + 1);
+    }
+
+    /// offer the next item of the stream.
+    pub fn push(&mut self, item: T) {
+        let index = self.seen;
+        self.seen += 1;
+        if self.items.len() < self.capacity {
+            self.items.push(item);
+            if self.items.len() == self.capacity && self.algorithm == Algorithm::L {
+                self.next = index;
+                self.schedule();
+            }
+            return;
+        }
+        match self.algorithm {
+            Algorithm::R => {
+                let j = self.rng.below(self.seen);
+                if (j as usize) < self.capacity {
+                    self.items[j as usize] = item;
+                }
+            }
+            Algorithm::L => {
+                if index == self.next {
+                    let slot = self.rng.index(self.capacity);
+                    self.items[slot] = item;
+                    self.schedule();
+                }
+            }
+        }
+    }
+
+    // algorithm l: how many upcoming items will be ignored.
+    fn pending_skip(&self) -> u64 {
+        if self.algorithm == Algorithm::L && self.items.len() == self.capacity {
+            self.next - self.seen
+        } else {
+            0
+        }
+    }
+}
+
+impl<T> Extend<T> for Reservoir<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+/// reservoir sampling as an iterator consumer.
+pub trait SampleExt: Iterator + Sized {
+    /// a uniform sample of up to `k` items using algorithm l. skipped items
+    /// are passed over with `Iterator::nth`, which is cheap for iterators
+    /// that can seek.
+    fn sample(mut self, k: usize, seed: u64) -> Vec<Self::Item> {
+        let mut reservoir = Reservoir::with_seed(k, Algorithm::L, seed);
+        loop {
+            let skip = reservoir.pending_skip();
+            let item = match usize::try_from(skip) {
+                Ok(0) => self.next(),
+                Ok(n) => self.nth(n),
+                Err(_) => None,
+            };
+            match item {
+                Some(item) => {
+                    reservoir.seen += skip;
+                    reservoir.push(item);
+                }
+                None => return reservoir.into_sample(),
+            }
+        }
+    }
+}
+
+impl<I: Iterator> SampleExt for I {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // every item of a stream of 20 should be kept about 5 / 20 of the time.
+    fn check_uniform(mut sample: impl FnMut(u64) -> Vec<usize>) {
+        let mut hits = [0u32; 20];
+        let trials = 4000;
+        for seed in 0..trials {
+            let picked = sample(seed);
+            assert_eq!(picked.len(), 5);
+            for i in picked {
+                hits[i] += 1;
+            }
+        }
+        let expected = f64::from(trials as u32) * 5.0 / 20.0;
+        for &h in &hits {
+            assert!(
+                (f64::from(h) - expected).abs() < 0.1 * expected,
+                "{:?}",
+                hits
+            );
+        }
+    }
+
+    #[test]
+    fn test_algorithm_r_uniform() {
+        check_uniform(|seed| {
+            let mut r = Reservoir::with_seed(5, Algorithm::R, seed);
+            r.extend(0..20);
+            r.into_sample()
+        });
+    }
+
+    #[test]
+    fn test_algorithm_l_uniform() {
+        check_uniform(|seed| {
+            let mut r = Reservoir::with_seed(5, Algorithm::L, seed);
+            r.extend(0..20);
+            r.into_sample()
+        });
+        check_uniform(|seed| (0..20).sample(5, seed));
+    }
+
+    #[test]
+    fn test_short_streams() {
+        let mut r = Reservoir::new(10, Algorithm::L);
+        r.extend(0..4);
+        let mut sample = r.sample().to_vec();
+        sample.sort_unstable();
+        assert_eq!(sample, vec![0, 1, 2, 3]);
+        assert_eq!(r.seen(), 4);
+        assert!((0..0).sample(3, 1).is_empty());
+        let big = (0..1_000_000u64).sample(3, 7);
+        assert_eq!(big.len(), 3);
+        assert!(big.iter().all(|&x| x < 1_000_000));
+    }
+}
@@ -0,0 +1,180 @@
+use std::collections::vec_deque::{self, VecDeque};
+
+/// what a full [`RingBuffer`] does with a new item.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Overflow {
+    /// drop the item at the opposite end to make room.
+    Overwrite,
+    /// refuse the new item.
+    Reject,
+}
+
+/// double-ended queue holding at most `capacity` items, with O(1) pushes
+/// and pops at both ends. its storage is allocated once, up front.
+#[derive(Clone, Debug)]
+pub struct RingBuffer<T> {
+    items: VecDeque<T>,
+    capacity: usize,
+    overflow: Overflow,
+}
+
+impl<T> RingBuffer<T> {
+    pub fn new(capacity: usize, overflow: Overflow) -> Self {
+        assert!(capacity > 0, "capacity must be positive");
+        RingBuffer {
+            items: VecDeque::with_capacity(capacity),
+            capacity,
+            overflow,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn overflow(&self) -> Overflow {
+        self.overflow
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.items.len() == self.capacity
+    }
+
+    /// append `item`. returns the front item it overwrote, if any, or gives
+    /// `item` back when the buffer is full and rejects new items.
+    pub fn push_back(&mut self, item: T) -> Result<Option<T>, T> {
+        let evicted = if self.is_full() {
+            match self.overflow {
+                Overflow::Overwrite => self.items.pop_front(),
+                Overflow::Reject => return Err(item),
+            }
+        } else {
+            None
+        };
+        self.items.push_back(item);
+        Ok(evicted)
+    }
+
+    /// prepend `item`; like [`RingBuffer::push_back`], but overwriting at
+    /// the back.
+    pub fn push_front(&mut self, item: T) -> Result<Option<T>, T> {
+        let evicted = if self.is_full() {
+            match self.overflow {
+                Overflow::Overwrite => self.items.pop_back(),
+                Overflow::Reject => return Err(item),
+            }
+        } else {
+            None
+        };
+        self.items.push_front(item);
+        Ok(evicted)
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.items.pop_front()
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.items.pop_back()
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.items.front()
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.items.back()
+    }
+
+    /// returns the item `index` places from the front.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.items.get(index)
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.items.get_mut(index)
+    }
+
+    /// the items from front to back as two slices; the second is empty
+    /// unless the contents wrap around the end of the storage.
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        self.items.as_slices()
+    }
+
+    /// rearrange the storage so the items form one slice, front to back.
+    pub fn make_contiguous(&mut self) -> &mut [T] {
+        self.items.make_contiguous()
+    }
+
+    pub fn iter(&self) -> vec_deque::Iter<'_, T> {
+        self.items.iter()
+    }
+
+    /// remove and yield every item, front to back.
+    pub fn drain(&mut self) -> vec_deque::Drain<'_, T> {
+        self.items.drain(..)
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+}
+
+impl<'a, T> IntoIterator for &'a RingBuffer<T> {
+    type Item = &'a T;
+    type IntoIter = vec_deque::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overwrite() {
+        let mut buffer = RingBuffer::new(3, Overflow::Overwrite);
+        for i in 0..3 {
+            assert_eq!(buffer.push_back(i), Ok(None));
+        }
+        assert!(buffer.is_full());
+        assert_eq!(buffer.push_back(3), Ok(Some(0)));
+        assert_eq!(buffer.push_front(-1), Ok(Some(3)));
+        let items: Vec<_> = buffer.iter().copied().collect();
+        assert_eq!(items, vec![-1, 1, 2]);
+        // the contents now wrap around the storage.
+        buffer.pop_front();
+        buffer.push_back(4).unwrap();
+        assert_eq!(buffer.make_contiguous(), &[1, 2, 4]);
+        let (a, b) = buffer.as_slices();
+        assert_eq!((a, b), (&[1, 2, 4][..], &[][..]));
+        assert_eq!(buffer.get(2), Some(&4));
+        assert_eq!(buffer.drain().collect::<Vec<_>>(), vec![1, 2, 4]);
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn test_reject() {
+        let mut buffer = RingBuffer::new(2, Overflow::Reject);
+        buffer.push_back("a").unwrap();
+        buffer.push_front("b").unwrap();
+        assert_eq!(buffer.push_back("c"), Err("c"));
+        assert_eq!(buffer.push_front("c"), Err("c"));
+        assert_eq!((buffer.front(), buffer.back()), (Some(&"b"), Some(&"a")));
+        assert_eq!(buffer.pop_back(), Some("a"));
+        assert_eq!(buffer.push_back("c"), Ok(None));
+        assert_eq!(buffer.pop_front(), Some("b"));
+        assert_eq!(buffer.pop_front(), Some("c"));
+        assert_eq!(buffer.pop_front(), None);
+    }
+}
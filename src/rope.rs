@@ -0,0 +1,463 @@
+use std::cmp::max;
+use std::fmt;
+use std::ops::RangeBounds;
+
+use crate::resolve_range;
+
+// leaves are split to at most this many bytes, and neighbours that fit
+// together are merged when joined.
+const MAX_LEAF: usize = 512;
+
+#[derive(Clone)]
+enum Node {
+    Leaf(String),
+    Branch(Box<Branch>),
+}
+
+#[derive(Clone)]
+struct Branch {
+    left: Node,
+    right: Node,
+    bytes: usize,
+    chars: usize,
+    height: u32,
+}
+
+impl Node {
+    fn empty() -> Node {
+        Node::Leaf(String::new())
+    }
+
+    fn bytes(&self) -> usize {
+        match self {
+            Node::Leaf(s) => s.len(),
+            Node::Branch(b) => b.bytes,
+        }
+    }
+
+    fn chars(&self) -> usize {
+        match self {
+            Node::Leaf(s) => s.chars().count(),
+            Node::Branch(b) => b.chars,
+        }
+    }
+
+    fn height(&self) -> u32 {
+        match self {
+            Node::Leaf(_) => 0,
+            Node::Branch(b) => b.height,
+        }
+    }
+
+    // a branch over two subtrees, without rebalancing.
+    fn branch(left: Node, right: Node) -> Node {
+        Node::Branch(Box::new(Branch {
+            bytes: left.bytes() + right.bytes(),
+            chars: left.chars() + right.chars(),
+            height: 1 + max(left.height(), right.height()),
+            left,
+            right,
+        }))
+    }
+
+    fn into_children(self) -> (Node, Node) {
+        match self {
+            Node::Branch(b) => (b.left, b.right),
+            Node::Leaf(_) => unreachable!("leaves have no children"),
+        }
+    }
+
+    // a balanced tree over consecutive chunks of `text`.
+    fn build(text: &str) -> Node {
+        if text.len() <= MAX_LEAF {
+            return Node::Leaf(text.to_string());
+        }
+        let mut mid = text.len() / 2;
+        while !text.is_char_boundary(mid) {
+            mid += 1;
+        }
+        Node::branch(Node::build(&text[..mid]), Node::build(&text[mid..]))
+    }
+}
+
+// a branch over `left` and `right`, which are balanced and differ in height
+// by at most 2, with avl rotations to make it balanced.
+fn balance(left: Node, right: Node) -> Node {
+    let (hl, hr) = (left.height(), right.height());
+    if hl > hr + 1 {
+        let (a, b) = left.into_children();
+        if a.height() >= b.height() {
+            Node::branch(a, Node::branch(b, right))
+        } else {
+            let (b1, b2) = b.into_children();
+            Node::branch(Node::branch(a, b1), Node::branch(b2, right))
+        }
+    } else if hr > hl + 1 {
+        let (a, b) = right.into_children();
+        if b.height() >= a.height() {
+            Node::branch(Node::branch(left, a), b)
+        } else {
+            let (a1, a2) = a.into_children();
+            Node::branch(Node::branch(left, a1), Node::branch(a2, b))
+        }
+    } else {
+        Node::branch(left, right)
+    }
+}
+
+// concatenate two balanced trees in O(|height difference|).
+fn join(left: Node, right: Node) -> Node {
+    if left.bytes() == 0 {
+        return right;
+    }
+    if right.bytes() == 0 {
+        return left;
+    }
+    let (hl, hr) = (left.height(), right.height());
+    match (left, right) {
+        (Node::Leaf(mut a), Node::Leaf(b)) if a.len() + b.len() <= MAX_LEAF => {
+            a.push_str(&b);
+            Node::Leaf(a)
+        }
+        (left, right) if hl > hr + 1 => {
+            let (a, b) = left.into_children();
+            balance(a, join(b, right))
+        }
+        (left, right) if hr > hl + 1 => {
+            let (a, b) = right.into_children();
+            balance(join(left, a), b)
+        }
+        (left, right) => Node::branch(left, right),
+    }
+}
+
+// split into the first `at` chars and the rest.
+fn split(node: Node, at: usize) -> (Node, Node) {
+    if at == 0 {
+        return (Node::empty(), node);
+    }
+    if at >= node.chars() {
+        return (node, Node::empty());
+    }
+    match node {
+        Node::Leaf(mut s) => {
+            let byte = char_offset(&s, at);
+            let rest = s.split_off(byte);
+            (Node::Leaf(s), Node::Leaf(rest))
+        }
+        Node::Branch(b) => {
+            let Branch { left, right, .. } = *b;
+            let left_chars = left.chars();
+            if at <= left_chars {
+                let (a, rest) = split(left, at);
+                (a, join(rest, right))
+            } else {
+                let (rest, b) = split(right, at - left_chars);
+                (join(left, rest), b)
+            }
+        }
+    }
+}
+
+// byte offset of char `at` in `s`.
+fn char_offset(s: &str, at: usize) -> usize {
+    s.char_indices().nth(at).map_or(s.len(), |(b, _)| b)
+}
+
+/// a string stored as a balanced tree of chunks, so inserting, removing,
+/// splitting and concatenating anywhere take O(log n) instead of O(n).
+///
+/// positions are char indices; [`Rope::char_to_byte`] and
+/// [`Rope::byte_to_char`] convert to and from byte offsets.
+#[derive(Clone)]
+pub struct Rope {
+    root: Node,
+}
+
+impl Rope {
+    pub fn new() -> Self {
+        Rope {
+            root: Node::empty(),
+        }
+    }
+
+    /// length in bytes.
+    pub fn len_bytes(&self) -> usize {
+        self.root.bytes()
+    }
+
+    /// length in chars.
+    pub fn len_chars(&self) -> usize {
+        self.root.chars()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.bytes() == 0
+    }
+
+    /// insert `text` before char `at`.
+    pub fn insert(&mut self, at: usize, text: &str) {
+        assert!(at <= self.len_chars(), "position out of bounds");
+        let root = std::mem::replace(&mut self.root, Node::empty());
+        let (left, right) = split(root, at);
+        self.root = join(join(left, Node::build(text)), right);
+    }
+
+    /// remove a range of chars.
+    pub fn remove(&mut self, range: impl RangeBounds<usize>) {
+        let (start, end) = resolve_range(range, self.len_chars());
+        let root = std::mem::replace(&mut self.root, Node::empty());
+        let (left, rest) = split(root, start);
+        let (_, right) = split(rest, end - start);
+        self.root = join(left, right);
+    }
+
+    /// split off and return everything from char `at` on.
+    pub fn split_off(&mut self, at: usize) -> Rope {
+        assert!(at <= self.len_chars(), "position out of bounds");
+        let root = std::mem::replace(&mut self.root, Node::empty());
+        let (left, right) = split(root, at);
+        self.root = left;
+        Rope { root: right }
+    }
+
+    /// concatenate `other` to the end.
+    pub fn append(&mut self, other: Rope) {
+        let root = std::mem::replace(&mut self.root, Node::empty());
+        self.root = join(root, other.root);
+    }
+
+    /// the char at index `at`.
+    pub fn char(&self, at: usize) -> Option<char> {
+        if at >= self.len_chars() {
+            return None;
+        }
+        let (mut node, mut at) = (&self.root, at);
+        loop {
+            match node {
+                Node::Leaf(s) => return s.chars().nth(at),
+                Node::Branch(b) => {
+                    let left_chars = b.left.chars();
+                    if at < left_chars {
+                        node = &b.left;
+                    } else {
+                        at -= left_chars;
+                        node = &b.right;
+                    }
+                }
+            }
+        }
+    }
+
+    /// byte offset of char `at`; `len_chars()` maps to `len_bytes()`.
+    pub fn char_to_byte(&self, at: usize) -> usize {
+        assert!(at <= self.len_chars(), "position out of bounds");
+        let (mut node, mut at, mut bytes) = (&self.root, at, 0);
+        loop {
+            match node {
+                Node::Leaf(s) => return bytes + char_offset(s, at),
+                Node::Branch(b) => {
+                    let left_chars = b.left.chars();
+                    if at < left_chars {
+                        node = &b.left;
+                    } else {
+                        at -= left_chars;
+                        bytes += b.left.bytes();
+                        node = &b.right;
+                    }
+                }
+            }
+        }
+    }
+
+    /// index of the char starting at byte offset `at`, which must lie on a
+    /// char boundary.
+    pub fn byte_to_char(&self, at: usize) -> usize {
+        assert!(at <= self.len_bytes(), "position out of bounds");
+        let (mut node, mut at, mut chars) = (&self.root, at, 0);
+        loop {
+            match node {
+                Node::Leaf(s) => {
+                    assert!(s.is_char_boundary(at), "offset is not a char boundary");
+                    return chars + s[..at].chars().count();
+                }
+                Node::Branch(b) => {
+                    let left_bytes = b.left.bytes();
+                    if at < left_bytes {
+                        node = &b.left;
+                    } else {
+                        at -= left_bytes;
+                        chars += b.left.chars();
+                        node = &b.right;
+                    }
+                }
+            }
+        }
+    }
+
+    /// iterate over the stored chunks in order.
+    pub fn chunks(&self) -> Chunks<'_> {
+        Chunks {
+            stack: vec![&self.root],
+        }
+    }
+
+    pub fn chars(&self) -> impl Iterator<Item = char> + '_ {
+        self.chunks().flat_map(str::chars)
+    }
+}
+
+impl Default for Rope {
+    fn default() -> Self {
+        Rope::new()
+    }
+}
+
+impl From<&str> for Rope {
+    fn from(text: &str) -> Self {
+        Rope {
+            root: Node::build(text),
+        }
+    }
+}
+
+impl fmt::Display for Rope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.chunks().try_for_each(|chunk| f.write_str(chunk))
+    }
+}
+
+impl fmt::Debug for Rope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.to_string(), f)
+    }
+}
+
+impl PartialEq for Rope {
+    fn eq(&self, other: &Rope) -> bool {
+        self.len_bytes() == other.len_bytes() && self.chars().eq(other.chars())
+    }
+}
+
+impl Eq for Rope {}
+
+/// iterator over the chunks of a [`Rope`].
+pub struct Chunks<'a> {
+    stack: Vec<&'a Node>,
+}
+
+impl<'a> Iterator for Chunks<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        while let Some(node) = self.stack.pop() {
+            match node {
+                Node::Leaf(s) if s.is_empty() => {}
+                Node::Leaf(s) => return Some(s),
+                Node::Branch(b) => {
+                    self.stack.push(&b.right);
+                    self.stack.push(&b.left);
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    // checks cached sizes and the avl balance, returning the height.
+    fn check(node: &Node) -> u32 {
+        match node {
+            Node::Leaf(s) => {
+                assert!(s.len() <= MAX_LEAF);
+                0
+            }
+            Node::Branch(b) => {
+                let (hl, hr) = (check(&b.left), check(&b.right));
+                assert!(hl.max(hr) - hl.min(hr) <= 1);
+                assert_eq!(b.height, 1 + hl.max(hr));
+                assert_eq!(b.bytes, b.left.bytes() + b.right.bytes());
+                assert_eq!(b.chars, b.left.chars() + b.right.chars());
+                b.height
+            }
+        }
+    }
+
+    fn random_text(rng: &mut Rng, len: usize) -> String {
+        let alphabet = ['a', 'b', 'é', '中', '🦀', '\n'];
+        (0..len)
+            .map(|_| alphabet[rng.index(alphabet.len())])
+            .collect()
+    }
+
+    #[test]
+    fn test_against_string() {
+        let mut rng = Rng::new(38);
+        let mut rope = Rope::new();
+        let mut chars: Vec<char> = Vec::new();
+        for step in 0..2000 {
+            let len = chars.len();
+            match rng.index(4) {
+                0 | 1 => {
+                    let at = rng.index(len + 1);
+                    let n = rng.index(300);
+                    let text = random_text(&mut rng, n);
+                    rope.insert(at, &text);
+                    chars.splice(at..at, text.chars());
+                }
+                2 => {
+                    let a = rng.index(len + 1);
+                    let b = a + rng.index(len - a + 1);
+                    rope.remove(a..b);
+                    chars.drain(a..b);
+                }
+                _ => {
+                    let at = rng.index(len + 1);
+                    let mut tail = rope.split_off(at);
+                    check(&tail.root);
+                    tail.insert(0, "x");
+                    tail.remove(..1);
+                    rope.append(tail);
+                }
+            }
+            check(&rope.root);
+            if step % 50 == 0 {
+                let text: String = chars.iter().collect();
+                assert_eq!(rope.to_string(), text);
+                assert_eq!(rope.len_bytes(), text.len());
+                for (ci, (bi, c)) in text.char_indices().enumerate().step_by(7) {
+                    assert_eq!(rope.char(ci), Some(c));
+                    assert_eq!(rope.char_to_byte(ci), bi);
+                    assert_eq!(rope.byte_to_char(bi), ci);
+                }
+            }
+            assert_eq!(rope.len_chars(), chars.len());
+        }
+    }
+
+    #[test]
+    fn test_basics() {
+        let mut rope = Rope::from("hello world");
+        rope.insert(5, ",");
+        rope.insert(rope.len_chars(), "!");
+        assert_eq!(rope.to_string(), "hello, world!");
+        let world = rope.split_off(7);
+        assert_eq!(world, Rope::from("world!"));
+        rope.remove(5..);
+        rope.append(Rope::from(" wörld"));
+        assert_eq!(rope.to_string(), "hello wörld");
+        assert_eq!(rope.char_to_byte(8), 9);
+        assert_eq!(rope.byte_to_char(9), 8);
+        assert_eq!(rope.char(7), Some('ö'));
+        assert_eq!(rope.char(11), None);
+        let long = "ab".repeat(5000);
+        let rope = Rope::from(long.as_str());
+        assert!(rope.chunks().count() > 10);
+        assert_eq!(rope.chunks().collect::<String>(), long);
+        assert!(Rope::new().is_empty());
+    }
+}
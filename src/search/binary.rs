@@ -0,0 +1,159 @@
+use std::cmp::Ordering;
+use std::ops::Range;
+
+/// the first index whose element is not less than `value`, or the length
+/// if there is none, in O(log n) on a sorted slice.
+pub fn lower_bound<T: Ord>(slice: &[T], value: &T) -> usize {
+    lower_bound_by(slice, |x| x.cmp(value))
+}
+
+/// the first index whose element is greater than `value`.
+pub fn upper_bound<T: Ord>(slice: &[T], value: &T) -> usize {
+    upper_bound_by(slice, |x| x.cmp(value))
+}
+
+/// the indices of the elements equal to `value`, empty at the place it
+/// would go if there are none.
+pub fn equal_range<T: Ord>(slice: &[T], value: &T) -> Range<usize> {
+    equal_range_by(slice, |x| x.cmp(value))
+}
+
+/// [`lower_bound`] where `f` tells how an element compares to the value
+/// looked for, as with `binary_search_by`.
+pub fn lower_bound_by<T, F: FnMut(&T) -> Ordering>(slice: &[T], mut f: F) -> usize {
+    slice.partition_point(|x| f(x) == Ordering::Less)
+}
+
+/// [`upper_bound`] by the comparison `f`, as for [`lower_bound_by`].
+pub fn upper_bound_by<T, F: FnMut(&T) -> Ordering>(slice: &[T], mut f: F) -> usize {
+    slice.partition_point(|x| f(x) != Ordering::Greater)
+}
+
+/// [`equal_range`] by the comparison `f`, as for [`lower_bound_by`].
+pub fn equal_range_by<T, F: FnMut(&T) -> Ordering>(slice: &[T], mut f: F) -> Range<usize> {
+    let start = lower_bound_by(slice, &mut f);
+    start..start + upper_bound_by(&slice[start..], f)
+}
+
+/// a domain to binary search over.
+pub trait SearchSpace: Copy + PartialOrd {
+    /// a value strictly between `lo < hi` near the middle, or `None` if
+    /// they are neighbours.
+    fn midpoint(lo: Self, hi: Self) -> Option<Self>;
+}
+
+macro_rules! integer_space {
+    ($($t:ty),*) => {
+        $(
+            impl SearchSpace for $t {
+                fn midpoint(lo: $t, hi: $t) -> Option<$t> {
+                    // the floor of the mean, without overflow.
+                    let mid = (lo >> 1) + (hi >> 1) + (lo & hi & 1);
+                    if mid > lo { Some(mid) } else { None }
+                }
+            }
+        )*
+    };
+}
+
+integer_space!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+macro_rules! float_space {
+    ($($t:ty),*) => {
+        $(
+            impl SearchSpace for $t {
+                fn midpoint(lo: $t, hi: $t) -> Option<$t> {
+                    let mid = lo / 2.0 + hi / 2.0;
+                    if lo < mid && mid < hi { Some(mid) } else { None }
+                }
+            }
+        )*
+    };
+}
+
+float_space!(f32, f64);
+
+/// search the answer space: the least `x` in `lo..=hi` for which `pred`
+/// holds, given that it fails up to some point and holds from there on, or
+/// `None` if it fails at `hi`. O(log(hi - lo)) calls on integers; floats
+/// are halved until the two ends are neighbouring values, so the answer is
+/// exact to the last bit.
+pub fn binary_search_predicate<T: SearchSpace, F: FnMut(T) -> bool>(
+    lo: T,
+    hi: T,
+    mut pred: F,
+) -> Option<T> {
+    assert!(lo <= hi, "empty search range");
+    if !pred(hi) {
+        return None;
+    }
+    if pred(lo) {
+        return Some(lo);
+    }
+    // `pred` fails at `lo` and holds at `hi`.
+    let (mut lo, mut hi) = (lo, hi);
+    while let Some(mid) = T::midpoint(lo, hi) {
+        if pred(mid) {
+            hi = mid;
+        } else {
+            lo = mid;
+        }
+    }
+    Some(hi)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_bounds() {
+        let values = [1, 3, 3, 3, 5, 8];
+        assert_eq!(lower_bound(&values, &3), 1);
+        assert_eq!(upper_bound(&values, &3), 4);
+        assert_eq!(equal_range(&values, &3), 1..4);
+        assert_eq!(equal_range(&values, &4), 4..4);
+        assert_eq!(equal_range(&values, &0), 0..0);
+        assert_eq!(lower_bound(&values, &9), 6);
+        let mut rng = Rng::new(100);
+        for _ in 0..100 {
+            let mut values: Vec<u64> = (0..rng.index(50)).map(|_| rng.below(20)).collect();
+            values.sort_unstable();
+            let value = rng.below(22);
+            let range = equal_range(&values, &value);
+            assert_eq!(range.len(), values.iter().filter(|&&v| v == value).count());
+            assert!(values[..range.start].iter().all(|&v| v < value));
+            // descending, by a reversed comparison.
+            values.reverse();
+            let range = equal_range_by(&values, |v| value.cmp(v));
+            assert!(values[range.clone()].iter().all(|&v| v == value));
+            assert!(values[range.end..].iter().all(|&v| v < value));
+        }
+    }
+
+    #[test]
+    fn test_binary_search_predicate() {
+        // the integer square root of 10^18.
+        let root =
+            binary_search_predicate(0u64, 2_000_000_000, |x| x * x > 1_000_000_000_000_000_000);
+        assert_eq!(root, Some(1_000_000_001));
+        assert_eq!(
+            binary_search_predicate(i64::MIN, i64::MAX, |x| x >= -5),
+            Some(-5)
+        );
+        assert_eq!(
+            binary_search_predicate(u8::MIN, u8::MAX, |x| x >= 200),
+            Some(200)
+        );
+        assert_eq!(binary_search_predicate(0i32, 10, |_| false), None);
+        assert_eq!(binary_search_predicate(3i32, 3, |_| true), Some(3));
+        // floats come out as the least value where it holds.
+        let sqrt2 = binary_search_predicate(0.0f64, 2.0, |x| x * x >= 2.0).unwrap();
+        assert!(sqrt2 * sqrt2 >= 2.0);
+        let below = f64::from_bits(sqrt2.to_bits() - 1);
+        assert!(below * below < 2.0);
+        let tiny = binary_search_predicate(-1e300f64, 1e300, |x| x > 0.0).unwrap();
+        assert_eq!(tiny, f64::from_bits(1));
+    }
+}
@@ -0,0 +1,99 @@
+use std::cmp::Ordering;
+
+/// exponential (galloping) search on a sorted slice: widen the range at the
+/// front by doubling until it passes `value`, then binary search it. O(log
+/// i) comparisons for a match at index `i`, so cheap near the front.
+///
+/// returns the same as `binary_search`.
+pub fn exponential_search<T: Ord>(slice: &[T], value: &T) -> Result<usize, usize> {
+    exponential_search_by(slice, |x| x.cmp(value))
+}
+
+/// [`exponential_search`] where `f` tells how an element compares to the
+/// value looked for, as with `binary_search_by`.
+pub fn exponential_search_by<T, F: FnMut(&T) -> Ordering>(
+    slice: &[T],
+    mut f: F,
+) -> Result<usize, usize> {
+    let mut bound = 1;
+    while bound <= slice.len() && f(&slice[bound - 1]) == Ordering::Less {
+        bound *= 2;
+    }
+    let start = bound / 2;
+    let end = bound.min(slice.len());
+    slice[start..end]
+        .binary_search_by(f)
+        .map(|i| start + i)
+        .map_err(|i| start + i)
+}
+
+/// the first index at which `pred` holds, for a predicate that fails up to
+/// some unknown point and holds from there on, such as a probe into a
+/// sorted stream of unknown length. O(log i) calls for an answer `i`.
+///
+/// panics if `pred` still fails at `usize::MAX / 2`.
+pub fn gallop<F: FnMut(usize) -> bool>(mut pred: F) -> usize {
+    if pred(0) {
+        return 0;
+    }
+    let (mut lo, mut hi) = (0, 1);
+    while !pred(hi) {
+        assert!(hi <= usize::MAX / 4, "predicate never holds");
+        lo = hi;
+        hi *= 2;
+    }
+    // `pred` fails at `lo` and holds at `hi`.
+    while hi - lo > 1 {
+        let mid = lo + (hi - lo) / 2;
+        if pred(mid) {
+            hi = mid;
+        } else {
+            lo = mid;
+        }
+    }
+    hi
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_exponential_search() {
+        let mut rng = Rng::new(102);
+        for _ in 0..100 {
+            let mut values: Vec<u64> = (0..rng.index(200)).map(|_| rng.below(400)).collect();
+            values.sort_unstable();
+            values.dedup();
+            for value in 0..410 {
+                assert_eq!(
+                    exponential_search(&values, &value),
+                    values.binary_search(&value)
+                );
+            }
+        }
+        let values: Vec<u32> = (0..1 << 20).collect();
+        let mut comparisons = 0;
+        let found = exponential_search_by(&values, |x| {
+            comparisons += 1;
+            x.cmp(&5)
+        });
+        assert_eq!((found, comparisons <= 8), (Ok(5), true));
+    }
+
+    #[test]
+    fn test_gallop() {
+        assert_eq!(gallop(|i| i >= 1_000_003), 1_000_003);
+        assert_eq!(gallop(|_| true), 0);
+        let mut calls = 0;
+        assert_eq!(
+            gallop(|i| {
+                calls += 1;
+                i >= 5
+            }),
+            5
+        );
+        assert!(calls <= 7);
+    }
+}
@@ -0,0 +1,122 @@
+use std::cmp::Ordering;
+
+/// an integer key whose value tells roughly where it sits among the others.
+pub trait Interpolate: Ord + Copy {
+    fn to_f64(self) -> f64;
+}
+
+macro_rules! interpolate {
+    ($($t:ty),*) => {
+        $(
+            impl Interpolate for $t {
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+            }
+        )*
+    };
+}
+
+interpolate!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// interpolation search on a sorted slice: probe where `value` would be if
+/// the keys were spread evenly between the ends of the range. O(log log n)
+/// probes on uniformly distributed keys; a probe that fails to halve the
+/// range is followed by a bisection, so at worst it is O(log n).
+///
+/// returns the same as `binary_search`: `Ok` with the index of some match,
+/// or `Err` with where `value` would go.
+pub fn interpolation_search<T: Interpolate>(slice: &[T], value: &T) -> Result<usize, usize> {
+    let (mut lo, mut hi) = (0, slice.len());
+    let mut bisect = false;
+    while lo < hi {
+        let before = hi - lo;
+        let (first, last) = (slice[lo], slice[hi - 1]);
+        let probe = if bisect || first == last {
+            lo + before / 2
+        } else {
+            let (a, b) = (first.to_f64(), last.to_f64());
+            let t = ((value.to_f64() - a) / (b - a)).clamp(0.0, 1.0);
+            lo + ((before - 1) as f64 * t) as usize
+        };
+        match slice[probe].cmp(value) {
+            Ordering::Less => lo = probe + 1,
+            Ordering::Greater => hi = probe,
+            Ordering::Equal => return Ok(probe),
+        }
+        bisect = 2 * (hi - lo) > before;
+    }
+    Err(lo)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+    use std::cell::Cell;
+
+    #[test]
+    fn test_against_binary_search() {
+        let mut rng = Rng::new(101);
+        for _ in 0..100 {
+            let n = rng.index(300);
+            let spread = 1 + rng.below(1000);
+            let mut values: Vec<i64> = (0..n).map(|_| rng.below(spread) as i64 - 500).collect();
+            values.sort_unstable();
+            values.dedup();
+            for value in -510..510 {
+                assert_eq!(
+                    interpolation_search(&values, &value),
+                    values.binary_search(&value)
+                );
+            }
+        }
+        assert_eq!(interpolation_search(&[u64::MAX, u64::MAX], &0), Err(0));
+        assert_eq!(interpolation_search::<u8>(&[], &3), Err(0));
+    }
+
+    thread_local! {
+        static PROBES: Cell<usize> = const { Cell::new(0) };
+    }
+
+    // a key that counts how often it is compared.
+    #[derive(Clone, Copy, PartialEq, Eq)]
+    struct Counted(u64);
+
+    impl Ord for Counted {
+        fn cmp(&self, other: &Self) -> Ordering {
+            PROBES.with(|p| p.set(p.get() + 1));
+            self.0.cmp(&other.0)
+        }
+    }
+
+    impl PartialOrd for Counted {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Interpolate for Counted {
+        fn to_f64(self) -> f64 {
+            self.0 as f64
+        }
+    }
+
+    #[test]
+    fn test_probes() {
+        // uniform keys take a handful of probes, skewed ones O(log n).
+        let uniform: Vec<Counted> = (0..1_000_000).map(|i| Counted(3 * i + i % 2)).collect();
+        let skewed: Vec<Counted> = (0..60)
+            .map(|i| 1 << i)
+            .chain(u64::MAX - 5..u64::MAX)
+            .map(Counted)
+            .collect();
+        for (values, limit) in [(uniform, 8), (skewed, 16)] {
+            for (i, value) in values.iter().enumerate().step_by(7) {
+                PROBES.with(|p| p.set(0));
+                assert!(interpolation_search(&values, value) == Ok(i));
+                assert!(PROBES.with(Cell::get) <= limit);
+            }
+        }
+    }
+}
@@ -0,0 +1,4 @@
+pub mod binary;
+pub mod exponential;
+pub mod interpolation;
+pub mod ternary;
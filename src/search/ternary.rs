@@ -0,0 +1,118 @@
+use std::cmp::Ordering;
+
+use super::binary::binary_search_predicate;
+
+/// ternary search on floats: the range is cut to two thirds each step,
+/// dropping the third that cannot hold the optimum of a unimodal function.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TernarySearch {
+    /// stop once the range is no wider than this.
+    pub tolerance: f64,
+    pub max_iterations: usize,
+}
+
+impl Default for TernarySearch {
+    fn default() -> Self {
+        TernarySearch {
+            tolerance: 1e-9,
+            max_iterations: 200,
+        }
+    }
+}
+
+impl TernarySearch {
+    /// where in `lo..=hi` the function `f`, falling and then rising, is
+    /// least.
+    pub fn minimize<V: PartialOrd, F: FnMut(f64) -> V>(&self, lo: f64, hi: f64, mut f: F) -> f64 {
+        self.search(lo, hi, |a, b| f(a) < f(b))
+    }
+
+    /// where in `lo..=hi` the function `f`, rising and then falling, is
+    /// greatest.
+    pub fn maximize<V: PartialOrd, F: FnMut(f64) -> V>(&self, lo: f64, hi: f64, mut f: F) -> f64 {
+        self.search(lo, hi, |a, b| f(a) > f(b))
+    }
+
+    // narrow down on the optimum, where `better(a, b)` is whether `a` beats
+    // `b`.
+    fn search<F: FnMut(f64, f64) -> bool>(&self, mut lo: f64, mut hi: f64, mut better: F) -> f64 {
+        assert!(lo <= hi, "empty search range");
+        for _ in 0..self.max_iterations {
+            if hi - lo <= self.tolerance {
+                break;
+            }
+            let third = (hi - lo) / 3.0;
+            let (a, b) = (lo + third, hi - third);
+            if better(a, b) {
+                hi = b;
+            } else {
+                lo = a;
+            }
+        }
+        lo / 2.0 + hi / 2.0
+    }
+}
+
+/// where in `lo..=hi` the function `f` is least, given that it strictly
+/// falls and then strictly rises, in O(log(hi - lo)) calls: a binary search
+/// for the first step up, rather than a ternary search.
+pub fn minimize_int<V: PartialOrd, F: FnMut(i64) -> V>(lo: i64, hi: i64, mut f: F) -> i64 {
+    assert!(lo <= hi, "empty search range");
+    if lo == hi {
+        return lo;
+    }
+    binary_search_predicate(lo, hi - 1, |x| {
+        f(x + 1).partial_cmp(&f(x)) != Some(Ordering::Less)
+    })
+    .unwrap_or(hi)
+}
+
+/// where in `lo..=hi` the function `f` is greatest, given that it strictly
+/// rises and then strictly falls.
+pub fn maximize_int<V: PartialOrd, F: FnMut(i64) -> V>(lo: i64, hi: i64, mut f: F) -> i64 {
+    assert!(lo <= hi, "empty search range");
+    if lo == hi {
+        return lo;
+    }
+    binary_search_predicate(lo, hi - 1, |x| {
+        f(x + 1).partial_cmp(&f(x)) != Some(Ordering::Greater)
+    })
+    .unwrap_or(hi)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_floats() {
+        let search = TernarySearch::default();
+        let x = search.minimize(-10.0, 10.0, |x| (x - 1.5) * (x - 1.5) + 3.0);
+        assert!((x - 1.5).abs() < 1e-6);
+        let x = search.maximize(0.0, std::f64::consts::PI, f64::sin);
+        assert!((x - std::f64::consts::FRAC_PI_2).abs() < 1e-6);
+        // at an end of the range.
+        assert!((search.minimize(2.0, 5.0, |x| x * x) - 2.0).abs() < 1e-6);
+        let coarse = TernarySearch {
+            tolerance: 0.0,
+            max_iterations: 3,
+        };
+        let x = coarse.minimize(0.0, 27.0, |x| (x - 13.5).abs());
+        assert!((x - 13.5).abs() < 4.0 && (x - 13.5).abs() > 1e-3);
+    }
+
+    #[test]
+    fn test_integers() {
+        for peak in -20..20 {
+            assert_eq!(maximize_int(-20, 19, |x| -(x - peak) * (x - peak)), peak);
+            assert_eq!(minimize_int(-20, 19, |x| (x - peak).abs()), peak);
+        }
+        assert_eq!(
+            minimize_int(0, 1_000_000_000, |x| (x - 123_456_789i64).pow(2)),
+            123_456_789
+        );
+        assert_eq!(maximize_int(7, 7, |x| x), 7);
+        assert_eq!(minimize_int(0, 9, |x| x), 0);
+        assert_eq!(minimize_int(0, 9, |x| -x), 9);
+    }
+}
@@ -0,0 +1,716 @@
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Add, RangeBounds};
+
+use crate::numeric::gcd::gcd;
+use crate::resolve_range;
+
+/// an associative operation with an identity element.
+pub trait Monoid<T> {
+    /// the identity element, `combine(identity(), x) == x`.
+    fn identity() -> T;
+    /// the associative operation.
+    fn combine(a: &T, b: &T) -> T;
+}
+
+/// addition, identity `T::default()`.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Sum;
+
+/// minimum, identity is the largest value of the type.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Min;
+
+/// maximum, identity is the smallest value of the type.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Max;
+
+/// greatest common divisor, identity zero.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Gcd;
+
+impl<T: Copy + Default + Add<Output = T>> Monoid<T> for Sum {
+    fn identity() -> T {
+        T::default()
+    }
+
+    fn combine(a: &T, b: &T) -> T {
+        *a + *b
+    }
+}
+
+macro_rules! impl_min_max {
+    ($($t:ty => $min:expr, $max:expr;)*) => {
+        $(
+            impl Monoid<$t> for Min {
+                fn identity() -> $t {
+                    $max
+                }
+
+                fn combine(a: &$t, b: &$t) -> $t {
+                    if *b < *a { *b } else { *a }
+                }
+            }
+
+            impl Monoid<$t> for Max {
+                fn identity() -> $t {
+                    $min
+                }
+
+                fn combine(a: &$t, b: &$t) -> $t {
+                    if *b > *a { *b } else { *a }
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_gcd {
+    ($($t:ty),*) => {
+        $(
+            impl Monoid<$t> for Gcd {
+                fn identity() -> $t {
+                    0
+                }
+
+                fn combine(a: &$t, b: &$t) -> $t {
+                    gcd(*a, *b)
+                }
+            }
+        )*
+    };
+}
+
+impl_gcd!(u8, u16, u32, u64, u128, usize);
+
+impl_min_max! {
+    i8 => i8::MIN, i8::MAX;
+    i16 => i16::MIN, i16::MAX;
+    i32 => i32::MIN, i32::MAX;
+    i64 => i64::MIN, i64::MAX;
+    i128 => i128::MIN, i128::MAX;
+    isize => isize::MIN, isize::MAX;
+    u8 => u8::MIN, u8::MAX;
+    u16 => u16::MIN, u16::MAX;
+    u32 => u32::MIN, u32::MAX;
+    u64 => u64::MIN, u64::MAX;
+    u128 => u128::MIN, u128::MAX;
+    usize => usize::MIN, usize::MAX;
+    f32 => f32::NEG_INFINITY, f32::INFINITY;
+    f64 => f64::NEG_INFINITY, f64::INFINITY;
+}
+
+/// iterative segment tree with point updates and range folds over the monoid `Op`.
+///
+/// the operation only has to be associative, not commutative: `query` folds
+/// the elements strictly in index order.
+pub struct SegmentTree<T, Op> {
+    len: usize,
+    // leaves live at `tree[len..2 * len]`, node `i` covers its children `2i` and `2i + 1`.
+    tree: Vec<T>,
+    _op: PhantomData<Op>,
+}
+
+impl<T: Clone, Op: Monoid<T>> SegmentTree<T, Op> {
+    /// create a tree of `len` identity elements.
+    pub fn new(len: usize) -> Self {
+        SegmentTree {
+            len,
+            tree: vec![Op::identity(); 2 * len],
+            _op: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// returns the element at `index`.
+    pub fn get(&self, index: usize) -> &T {
+        assert!(index < self.len, "index {} out of bounds", index);
+        &self.tree[index + self.len]
+    }
+
+    /// replace the element at `index`.
+    pub fn set(&mut self, index: usize, value: T) {
+        assert!(index < self.len, "index {} out of bounds", index);
+        let mut i = index + self.len;
+        self.tree[i] = value;
+        while i > 1 {
+            i /= 2;
+            self.tree[i] = Op::combine(&self.tree[2 * i], &self.tree[2 * i + 1]);
+        }
+    }
+
+    /// replace the element at `index` with `f(old)`.
+    pub fn update<F: FnOnce(&T) -> T>(&mut self, index: usize, f: F) {
+        let value = f(self.get(index));
+        self.set(index, value);
+    }
+
+    /// fold the elements in `range`, returns the identity for an empty range.
+    pub fn query(&self, range: impl RangeBounds<usize>) -> T {
+        let (start, end) = resolve_range(range, self.len);
+        let mut left = Op::identity();
+        let mut right = Op::identity();
+        let (mut l, mut r) = (start + self.len, end + self.len);
+        while l < r {
+            if l & 1 == 1 {
+                left = Op::combine(&left, &self.tree[l]);
+                l += 1;
+            }
+            if r & 1 == 1 {
+                r -= 1;
+                right = Op::combine(&self.tree[r], &right);
+            }
+            l /= 2;
+            r /= 2;
+        }
+        Op::combine(&left, &right)
+    }
+
+    /// fold of every element.
+    pub fn all(&self) -> T {
+        self.query(..)
+    }
+}
+
+impl<T: Clone, Op: Monoid<T>> From<Vec<T>> for SegmentTree<T, Op> {
+    fn from(values: Vec<T>) -> Self {
+        let len = values.len();
+        let mut tree = Vec::with_capacity(2 * len);
+        tree.resize(len, Op::identity());
+        tree.extend(values);
+        for i in (1..len).rev() {
+            tree[i] = Op::combine(&tree[2 * i], &tree[2 * i + 1]);
+        }
+        SegmentTree {
+            len,
+            tree,
+            _op: PhantomData,
+        }
+    }
+}
+
+impl<T: Clone, Op> Clone for SegmentTree<T, Op> {
+    fn clone(&self) -> Self {
+        SegmentTree {
+            len: self.len,
+            tree: self.tree.clone(),
+            _op: PhantomData,
+        }
+    }
+}
+
+impl<T: fmt::Debug, Op> fmt::Debug for SegmentTree<T, Op> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(&self.tree[self.len..]).finish()
+    }
+}
+
+/// a family of maps acting on the values of the monoid `Op`, used by `LazySegmentTree`.
+pub trait Action<T, Op: Monoid<T>> {
+    type Map: Clone;
+    /// the map that leaves every value unchanged.
+    fn identity_map() -> Self::Map;
+    /// apply `f` to `x`, the fold of `len` consecutive elements.
+    fn apply(f: &Self::Map, x: &T, len: usize) -> T;
+    /// the map applying `g` first and then `f`.
+    fn compose(f: &Self::Map, g: &Self::Map) -> Self::Map;
+}
+
+/// add a constant to every element of a range.
+///
+/// under `Sum` over an integer type, a range is limited to as many elements
+/// as the type can count, and the sum has to fit in it as usual.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct RangeAdd;
+
+/// overwrite every element of a range with a constant, with the same limit
+/// as [`RangeAdd`] under `Sum`.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct RangeAssign;
+
+// `len` as a `$t`, for scaling a constant by the length of a range. for the
+// integer types the tree elements limit how long such a range can be.
+macro_rules! range_len {
+    (f32, $len:expr) => {
+        $len as f32
+    };
+    (f64, $len:expr) => {
+        $len as f64
+    };
+    ($t:ident, $len:expr) => {
+        <$t as std::convert::TryFrom<usize>>::try_from($len)
+            .expect("range too long for the element type to count")
+    };
+}
+
+macro_rules! impl_actions {
+    ($($t:ident),*) => {
+        $(
+            impl Action<$t, Sum> for RangeAdd {
+                type Map = $t;
+
+                fn identity_map() -> $t {
+                    0 as $t
+                }
+
+                fn apply(f: &$t, x: &$t, len: usize) -> $t {
+                    if *f == 0 as $t { *x } else { *x + *f * range_len!($t, len) }
+                }
+
+                fn compose(f: &$t, g: &$t) -> $t {
+                    *f + *g
+                }
+            }
+
+            impl Action<$t, Sum> for RangeAssign {
+                type Map = Option<$t>;
+
+                fn identity_map() -> Option<$t> {
+                    None
+                }
+
+                fn apply(f: &Option<$t>, x: &$t, len: usize) -> $t {
+                    f.map_or(*x, |v| v * range_len!($t, len))
+                }
+
+                fn compose(f: &Option<$t>, g: &Option<$t>) -> Option<$t> {
+                    f.or(*g)
+                }
+            }
+
+            impl_actions!(@extremum $t, Min);
+            impl_actions!(@extremum $t, Max);
+        )*
+    };
+    (@extremum $t:ident, $op:ty) => {
+        impl Action<$t, $op> for RangeAdd {
+            type Map = $t;
+
+            fn identity_map() -> $t {
+                0 as $t
+            }
+
+            fn apply(f: &$t, x: &$t, _len: usize) -> $t {
+                *x + *f
+            }
+
+            fn compose(f: &$t, g: &$t) -> $t {
+                *f + *g
+            }
+        }
+
+        impl Action<$t, $op> for RangeAssign {
+            type Map = Option<$t>;
+
+            fn identity_map() -> Option<$t> {
+                None
+            }
+
+            fn apply(f: &Option<$t>, x: &$t, _len: usize) -> $t {
+                f.unwrap_or(*x)
+            }
+
+            fn compose(f: &Option<$t>, g: &Option<$t>) -> Option<$t> {
+                f.or(*g)
+            }
+        }
+    };
+}
+
+impl_actions!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64);
+
+/// segment tree with range updates and range folds.
+///
+/// values are combined by the monoid `Op`, and updates are maps of `Act`
+/// that are pushed down to the children lazily.
+pub struct LazySegmentTree<T, Op: Monoid<T>, Act: Action<T, Op>> {
+    len: usize,
+    size: usize,
+    log: u32,
+    tree: Vec<T>,
+    lazy: Vec<Act::Map>,
+    _op: PhantomData<Op>,
+}
+
+impl<T, Op, Act> LazySegmentTree<T, Op, Act>
+where
+    T: Clone,
+    Op: Monoid<T>,
+    Act: Action<T, Op>,
+{
+    /// create a tree of `len` identity elements.
+    pub fn new(len: usize) -> Self {
+        Self::from(vec![Op::identity(); len])
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// returns the element at `index`.
+    pub fn get(&mut self, index: usize) -> T {
+        assert!(index < self.len, "index {} out of bounds", index);
+        let leaf = index + self.size;
+        self.push_path(leaf);
+        self.tree[leaf].clone()
+    }
+
+    /// replace the element at `index`.
+    pub fn set(&mut self, index: usize, value: T) {
+        assert!(index < self.len, "index {} out of bounds", index);
+        let leaf = index + self.size;
+        self.push_path(leaf);
+        self.tree[leaf] = value;
+        for i in 1..=self.log {
+            self.pull(leaf >> i);
+        }
+    }
+
+    /// fold the elements in `range`, returns the identity for an empty range.
+    pub fn query(&mut self, range: impl RangeBounds<usize>) -> T {
+        let (start, end) = resolve_range(range, self.len);
+        if start == end {
+            return Op::identity();
+        }
+        let (mut l, mut r) = (start + self.size, end + self.size);
+        self.push_boundaries(l, r);
+        let mut left = Op::identity();
+        let mut right = Op::identity();
+        while l < r {
+            if l & 1 == 1 {
+                left = Op::combine(&left, &self.tree[l]);
+                l += 1;
+            }
+            if r & 1 == 1 {
+                r -= 1;
+                right = Op::combine(&self.tree[r], &right);
+            }
+            l >>= 1;
+            r >>= 1;
+        }
+        Op::combine(&left, &right)
+    }
+
+    /// fold of every element.
+    pub fn all(&self) -> T {
+        self.tree[1].clone()
+    }
+
+    /// apply `f` to every element in `range`.
+    pub fn apply(&mut self, range: impl RangeBounds<usize>, f: Act::Map) {
+        let (start, end) = resolve_range(range, self.len);
+        if start == end {
+            return;
+        }
+        let (l0, r0) = (start + self.size, end + self.size);
+        self.push_boundaries(l0, r0);
+        let (mut l, mut r) = (l0, r0);
+        while l < r {
+            if l & 1 == 1 {
+                self.apply_node(l, &f);
+                l += 1;
+            }
+            if r & 1 == 1 {
+                r -= 1;
+                self.apply_node(r, &f);
+            }
+            l >>= 1;
+            r >>= 1;
+        }
+        for i in 1..=self.log {
+            if ((l0 >> i) << i) != l0 {
+                self.pull(l0 >> i);
+            }
+            if ((r0 >> i) << i) != r0 {
+                self.pull((r0 - 1) >> i);
+            }
+        }
+    }
+
+    // number of elements below `node`, not counting the padding leaves past
+    // `len`.
+    fn node_len(&self, node: usize) -> usize {
+        let depth = usize::BITS - 1 - node.leading_zeros();
+        let width = self.size >> depth;
+        let first = (node << (self.log - depth)) - self.size;
+        self.len.saturating_sub(first).min(width)
+    }
+
+    fn pull(&mut self, node: usize) {
+        self.tree[node] = Op::combine(&self.tree[2 * node], &self.tree[2 * node + 1]);
+    }
+
+    fn apply_node(&mut self, node: usize, f: &Act::Map) {
+        let len = self.node_len(node);
+        // padding stays the identity, whatever the map.
+        if len == 0 {
+            return;
+        }
+        self.tree[node] = Act::apply(f, &self.tree[node], len);
+        if node < self.size {
+            self.lazy[node] = Act::compose(f, &self.lazy[node]);
+        }
+    }
+
+    fn push(&mut self, node: usize) {
+        let f = std::mem::replace(&mut self.lazy[node], Act::identity_map());
+        self.apply_node(2 * node, &f);
+        self.apply_node(2 * node + 1, &f);
+    }
+
+    // push pending maps from the root down to `leaf`.
+    fn push_path(&mut self, leaf: usize) {
+        for i in (1..=self.log).rev() {
+            self.push(leaf >> i);
+        }
+    }
+
+    // push pending maps on the paths to the boundaries of `l..r` (leaf indices).
+    fn push_boundaries(&mut self, l: usize, r: usize) {
+        for i in (1..=self.log).rev() {
+            if ((l >> i) << i) != l {
+                self.push(l >> i);
+            }
+            if ((r >> i) << i) != r {
+                self.push((r - 1) >> i);
+            }
+        }
+    }
+}
+
+impl<T, Op, Act> From<Vec<T>> for LazySegmentTree<T, Op, Act>
+where
+    T: Clone,
+    Op: Monoid<T>,
+    Act: Action<T, Op>,
+{
+    fn from(values: Vec<T>) -> Self {
+        let len = values.len();
+        let size = len.next_power_of_two();
+        let log = size.trailing_zeros();
+        let mut tree = vec![Op::identity(); 2 * size];
+        for (slot, value) in tree[size..].iter_mut().zip(values) {
+            *slot = value;
+        }
+        let mut segment_tree = LazySegmentTree {
+            len,
+            size,
+            log,
+            tree,
+            lazy: vec![Act::identity_map(); size],
+            _op: PhantomData,
+        };
+        for node in (1..size).rev() {
+            segment_tree.pull(node);
+        }
+        segment_tree
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_sum() {
+        let mut tree = SegmentTree::<i64, Sum>::from(vec![5, 3, 8, 1, 4, 2, 7]);
+        assert_eq!(tree.query(..), 30);
+        assert_eq!(tree.query(1..4), 12);
+        assert_eq!(tree.query(2..=2), 8);
+        assert_eq!(tree.query(3..3), 0);
+        tree.set(2, -2);
+        assert_eq!(tree.query(1..4), 2);
+        tree.update(0, |v| v * 2);
+        assert_eq!(tree.all(), 25);
+        assert_eq!(*tree.get(0), 10);
+    }
+
+    #[test]
+    fn test_min_max() {
+        let values = vec![4u32, 9, 1, 7, 3, 6];
+        let min = SegmentTree::<u32, Min>::from(values.clone());
+        let max = SegmentTree::<u32, Max>::from(values.clone());
+        for l in 0..values.len() {
+            for r in l + 1..=values.len() {
+                assert_eq!(min.query(l..r), *values[l..r].iter().min().unwrap());
+                assert_eq!(max.query(l..r), *values[l..r].iter().max().unwrap());
+            }
+        }
+        assert_eq!(min.query(0..0), u32::MAX);
+    }
+
+    struct Concat;
+
+    impl Monoid<String> for Concat {
+        fn identity() -> String {
+            String::new()
+        }
+
+        fn combine(a: &String, b: &String) -> String {
+            format!("{}{}", a, b)
+        }
+    }
+
+    #[test]
+    fn test_non_commutative() {
+        let letters: Vec<String> = "abcdefghij".chars().map(String::from).collect();
+        let mut tree = SegmentTree::<String, Concat>::from(letters);
+        assert_eq!(tree.query(2..7), "cdefg");
+        assert_eq!(tree.all(), "abcdefghij");
+        tree.set(4, "X".to_string());
+        assert_eq!(tree.query(3..6), "dXf");
+    }
+
+    #[test]
+    fn test_new_is_identity() {
+        let mut tree = SegmentTree::<i32, Max>::new(4);
+        assert_eq!(tree.all(), i32::MIN);
+        tree.set(3, 5);
+        tree.set(1, 2);
+        assert_eq!(tree.query(..2), 2);
+        assert_eq!(tree.all(), 5);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_out_of_range() {
+        let tree = SegmentTree::<i32, Sum>::new(3);
+        tree.query(1..4);
+    }
+
+    fn check_against_model<Op, Act, F, Q>(
+        seed: u64,
+        apply: F,
+        fold: Q,
+        make_map: fn(i64) -> Act::Map,
+    ) where
+        Op: Monoid<i64>,
+        Act: Action<i64, Op>,
+        F: Fn(&mut [i64], i64),
+        Q: Fn(&[i64]) -> i64,
+    {
+        let mut rng = Rng::new(seed);
+        for len in [1usize, 2, 5, 16, 37].iter().copied() {
+            let mut model: Vec<i64> = (0..len).map(|_| rng.range(-50..50)).collect();
+            let mut tree = LazySegmentTree::<i64, Op, Act>::from(model.clone());
+            for _ in 0..500 {
+                let l = rng.index(len + 1);
+                let r = l + rng.index(len - l + 1);
+                match rng.index(3) {
+                    0 => {
+                        let v = rng.range(-20..20);
+                        tree.apply(l..r, make_map(v));
+                        apply(&mut model[l..r], v);
+                    }
+                    1 if l < len => {
+                        let v = rng.range(-100..100);
+                        tree.set(l, v);
+                        model[l] = v;
+                    }
+                    _ => {
+                        if l < r {
+                            assert_eq!(tree.query(l..r), fold(&model[l..r]));
+                        }
+                    }
+                }
+            }
+            for (i, &v) in model.iter().enumerate() {
+                assert_eq!(tree.get(i), v);
+            }
+        }
+    }
+
+    #[test]
+    fn test_lazy_add_sum() {
+        check_against_model::<Sum, RangeAdd, _, _>(
+            1,
+            |s, v| s.iter_mut().for_each(|x| *x += v),
+            |s| s.iter().sum(),
+            |v| v,
+        );
+    }
+
+    #[test]
+    fn test_lazy_add_min() {
+        check_against_model::<Min, RangeAdd, _, _>(
+            2,
+            |s, v| s.iter_mut().for_each(|x| *x += v),
+            |s| *s.iter().min().unwrap(),
+            |v| v,
+        );
+    }
+
+    #[test]
+    fn test_lazy_assign_sum() {
+        check_against_model::<Sum, RangeAssign, _, _>(
+            3,
+            |s, v| s.iter_mut().for_each(|x| *x = v),
+            |s| s.iter().sum(),
+            Some,
+        );
+    }
+
+    #[test]
+    fn test_lazy_assign_max() {
+        check_against_model::<Max, RangeAssign, _, _>(
+            4,
+            |s, v| s.iter_mut().for_each(|x| *x = v),
+            |s| *s.iter().max().unwrap(),
+            Some,
+        );
+    }
+
+    #[test]
+    fn test_lazy_basic() {
+        let mut tree = LazySegmentTree::<i64, Sum, RangeAdd>::new(5);
+        tree.apply(1..4, 2);
+        tree.apply(.., 1);
+        assert_eq!(tree.query(..), 11);
+        assert_eq!(tree.query(0..1), 1);
+        assert_eq!(tree.get(2), 3);
+        assert_eq!(tree.all(), 11);
+        tree.apply(2..2, 100);
+        assert_eq!(tree.all(), 11);
+    }
+
+    #[test]
+    fn test_lazy_extreme_values() {
+        // elements equal to the identity still take part in range adds.
+        let mut min = LazySegmentTree::<i64, Min, RangeAdd>::from(vec![i64::MAX, 3, i64::MAX]);
+        min.apply(.., -10);
+        assert_eq!(min.get(0), i64::MAX - 10);
+        assert_eq!(min.query(..), -7);
+        min.apply(1..2, i64::MAX - 5);
+        assert_eq!(min.all(), i64::MAX - 12);
+        let mut max = LazySegmentTree::<i8, Max, RangeAdd>::from(vec![i8::MIN; 5]);
+        max.apply(1..4, 7);
+        assert_eq!(max.query(..), i8::MIN + 7);
+        assert_eq!(max.query(4..), i8::MIN);
+        // sums over ranges longer than `u8` can count, while they fit.
+        let mut sum = LazySegmentTree::<u16, Sum, RangeAssign>::new(300);
+        sum.apply(.., Some(2));
+        assert_eq!(sum.all(), 600);
+        let mut small = LazySegmentTree::<u8, Sum, RangeAdd>::new(300);
+        small.apply(..100, 2);
+        assert_eq!(small.query(50..150), 100);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_lazy_range_too_long() {
+        let mut tree = LazySegmentTree::<u8, Sum, RangeAssign>::new(300);
+        tree.apply(.., Some(1));
+    }
+}
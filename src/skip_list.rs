@@ -0,0 +1,429 @@
+use std::fmt;
+use std::ops::{Bound, RangeBounds, RangeFull};
+
+use crate::rng::Rng;
+use crate::OrderedMap;
+
+const MAX_LEVEL: usize = 32;
+const NIL: usize = usize::MAX;
+// stands for the head tower in predecessor positions.
+const HEAD: usize = usize::MAX - 1;
+
+struct Node<K, V> {
+    key: K,
+    value: V,
+    // forward links, one per level the node takes part in.
+    next: Vec<usize>,
+}
+
+/// ordered map backed by a skip list: a sorted linked list with randomly built
+/// express lanes, giving expected O(log n) operations without rebalancing.
+///
+/// nodes live in a dense arena and link to each other by index.
+pub struct SkipList<K, V> {
+    nodes: Vec<Node<K, V>>,
+    head: Vec<usize>,
+    rng: Rng,
+}
+
+impl<K: Ord, V> SkipList<K, V> {
+    /// create an empty list.
+    pub fn new() -> Self {
+        SkipList::with_seed(0x5eed)
+    }
+
+    /// create an empty list whose tower heights are drawn from `seed`.
+    pub fn with_seed(seed: u64) -> Self {
+        SkipList {
+            nodes: Vec::new(),
+            head: vec![NIL; MAX_LEVEL],
+            rng: Rng::new(seed),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// returns the value stored for `key`.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let node = self.lower_bound(key);
+        if node != NIL && self.nodes[node].key == *key {
+            Some(&self.nodes[node].value)
+        } else {
+            None
+        }
+    }
+
+    /// returns a mutable reference to the value stored for `key`.
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let node = self.lower_bound(key);
+        if node != NIL && self.nodes[node].key == *key {
+            Some(&mut self.nodes[node].value)
+        } else {
+            None
+        }
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// insert `value` for `key`, returning the previous value if there was one.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let preds = self.predecessors(&key);
+        let found = self.next_of(preds[0], 0);
+        if found != NIL && self.nodes[found].key == key {
+            return Some(std::mem::replace(&mut self.nodes[found].value, value));
+        }
+        let height = (self.rng.next_u64().trailing_ones() as usize + 1).min(MAX_LEVEL);
+        let index = self.nodes.len();
+        let next = (0..height)
+            .map(|level| self.next_of(preds[level], level))
+            .collect();
+        self.nodes.push(Node { key, value, next });
+        for (level, &pred) in preds.iter().enumerate().take(height) {
+            self.set_next(pred, level, index);
+        }
+        None
+    }
+
+    /// remove `key`, returning its value if it was present.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let preds = self.predecessors(key);
+        let index = self.next_of(preds[0], 0);
+        if index == NIL || self.nodes[index].key != *key {
+            return None;
+        }
+        let next = std::mem::take(&mut self.nodes[index].next);
+        for (level, &to) in next.iter().enumerate() {
+            self.set_next(preds[level], level, to);
+        }
+        // the last node moves into the freed slot, so repoint its predecessors.
+        let last = self.nodes.len() - 1;
+        if index != last {
+            let preds = self.predecessors(&self.nodes[last].key);
+            let height = self.nodes[last].next.len();
+            for (level, &pred) in preds.iter().enumerate().take(height) {
+                self.set_next(pred, level, index);
+            }
+        }
+        Some(self.nodes.swap_remove(index).value)
+    }
+
+    /// entry with the smallest key.
+    pub fn first(&self) -> Option<(&K, &V)> {
+        self.entry(self.head[0])
+    }
+
+    /// entry with the largest key.
+    pub fn last(&self) -> Option<(&K, &V)> {
+        let mut cur = HEAD;
+        for level in (0..MAX_LEVEL).rev() {
+            while self.next_of(cur, level) != NIL {
+                cur = self.next_of(cur, level);
+            }
+        }
+        if cur == HEAD {
+            None
+        } else {
+            self.entry(cur)
+        }
+    }
+
+    /// iterate over the entries whose keys lie in `range`, in increasing key order.
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Range<'_, K, V, R> {
+        let cur = match range.start_bound() {
+            Bound::Unbounded => self.head[0],
+            Bound::Included(start) => self.lower_bound(start),
+            Bound::Excluded(start) => {
+                let node = self.lower_bound(start);
+                if node != NIL && self.nodes[node].key == *start {
+                    self.nodes[node].next[0]
+                } else {
+                    node
+                }
+            }
+        };
+        Range {
+            list: self,
+            cur,
+            range,
+        }
+    }
+
+    /// iterate in increasing key order.
+    pub fn iter(&self) -> Range<'_, K, V, RangeFull> {
+        self.range(..)
+    }
+
+    fn entry(&self, index: usize) -> Option<(&K, &V)> {
+        self.nodes.get(index).map(|node| (&node.key, &node.value))
+    }
+
+    fn next_of(&self, node: usize, level: usize) -> usize {
+        if node == HEAD {
+            self.head[level]
+        } else {
+            self.nodes[node].next[level]
+        }
+    }
+
+    fn set_next(&mut self, node: usize, level: usize, to: usize) {
+        if node == HEAD {
+            self.head[level] = to;
+        } else {
+            self.nodes[node].next[level] = to;
+        }
+    }
+
+    // the last node with a key below `key` on every level.
+    fn predecessors(&self, key: &K) -> [usize; MAX_LEVEL] {
+        let mut preds = [HEAD; MAX_LEVEL];
+        let mut cur = HEAD;
+        for level in (0..MAX_LEVEL).rev() {
+            loop {
+                let next = self.next_of(cur, level);
+                if next != NIL && self.nodes[next].key < *key {
+                    cur = next;
+                } else {
+                    break;
+                }
+            }
+            preds[level] = cur;
+        }
+        preds
+    }
+
+    // the first node with a key at or above `key`.
+    fn lower_bound(&self, key: &K) -> usize {
+        self.next_of(self.predecessors(key)[0], 0)
+    }
+}
+
+impl<K: Ord, V> Default for SkipList<K, V> {
+    fn default() -> Self {
+        SkipList::new()
+    }
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug> fmt::Debug for SkipList<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Ord, V> std::iter::FromIterator<(K, V)> for SkipList<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut list = SkipList::new();
+        iter.into_iter().for_each(|(k, v)| {
+            list.insert(k, v);
+        });
+        list
+    }
+}
+
+impl<K: Ord, V> OrderedMap<K, V> for SkipList<K, V> {
+    fn new() -> Self {
+        SkipList::new()
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn get(&self, key: &K) -> Option<&V> {
+        self.get(key)
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.insert(key, value)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        self.remove(key)
+    }
+
+    fn first(&self) -> Option<(&K, &V)> {
+        self.first()
+    }
+
+    fn last(&self) -> Option<(&K, &V)> {
+        self.last()
+    }
+
+    fn iter<'a>(&'a self) -> Box<dyn Iterator<Item = (&'a K, &'a V)> + 'a>
+    where
+        K: 'a,
+        V: 'a,
+    {
+        Box::new(self.iter())
+    }
+}
+
+/// iterator over a key range of a skip list.
+pub struct Range<'a, K, V, R> {
+    list: &'a SkipList<K, V>,
+    cur: usize,
+    range: R,
+}
+
+impl<'a, K: Ord, V, R: RangeBounds<K>> Iterator for Range<'a, K, V, R> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.list.nodes.get(self.cur)?;
+        let below_end = match self.range.end_bound() {
+            Bound::Included(end) => node.key <= *end,
+            Bound::Excluded(end) => node.key < *end,
+            Bound::Unbounded => true,
+        };
+        if !below_end {
+            self.cur = NIL;
+            return None;
+        }
+        self.cur = node.next[0];
+        Some((&node.key, &node.value))
+    }
+}
+
+/// ordered set backed by a `SkipList`.
+pub struct SkipSet<K> {
+    list: SkipList<K, ()>,
+}
+
+impl<K: Ord> SkipSet<K> {
+    /// create an empty set.
+    pub fn new() -> Self {
+        SkipSet {
+            list: SkipList::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// add `key`, returns false if it was already present.
+    pub fn insert(&mut self, key: K) -> bool {
+        self.list.insert(key, ()).is_none()
+    }
+
+    /// remove `key`, returns false if it was absent.
+    pub fn remove(&mut self, key: &K) -> bool {
+        self.list.remove(key).is_some()
+    }
+
+    pub fn contains(&self, key: &K) -> bool {
+        self.list.contains_key(key)
+    }
+
+    /// smallest key.
+    pub fn first(&self) -> Option<&K> {
+        self.list.first().map(|(k, _)| k)
+    }
+
+    /// largest key.
+    pub fn last(&self) -> Option<&K> {
+        self.list.last().map(|(k, _)| k)
+    }
+
+    /// iterate over the keys in `range`, in increasing order.
+    pub fn range<'a, R: RangeBounds<K> + 'a>(
+        &'a self,
+        range: R,
+    ) -> impl Iterator<Item = &'a K> + 'a {
+        self.list.range(range).map(|(k, _)| k)
+    }
+
+    /// iterate in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = &K> + '_ {
+        self.list.iter().map(|(k, _)| k)
+    }
+}
+
+impl<K: Ord> Default for SkipSet<K> {
+    fn default() -> Self {
+        SkipSet::new()
+    }
+}
+
+impl<K: Ord + fmt::Debug> fmt::Debug for SkipSet<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Ord> std::iter::FromIterator<K> for SkipSet<K> {
+    fn from_iter<I: IntoIterator<Item = K>>(iter: I) -> Self {
+        let mut set = SkipSet::new();
+        iter.into_iter().for_each(|k| {
+            set.insert(k);
+        });
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_against_btree() {
+        let mut rng = Rng::new(17);
+        let mut list = SkipList::new();
+        let mut model = BTreeMap::new();
+        for step in 0..4000 {
+            let key = rng.range(0..300);
+            if rng.chance(0.55) {
+                assert_eq!(list.insert(key, step), model.insert(key, step));
+            } else {
+                assert_eq!(list.remove(&key), model.remove(&key));
+            }
+            assert_eq!(list.get(&key), model.get(&key));
+        }
+        assert_eq!(list.len(), model.len());
+        assert!(list.iter().eq(model.iter()));
+        assert_eq!(list.first(), model.iter().next());
+        assert_eq!(list.last(), model.iter().next_back());
+        let from: Vec<_> = list.range(100..=150).collect();
+        let expected: Vec<_> = model.range(100..=150).collect();
+        assert_eq!(from, expected);
+    }
+
+    #[test]
+    fn test_set() {
+        let mut set: SkipSet<&str> = vec!["pear", "apple", "fig"].into_iter().collect();
+        assert!(!set.insert("fig"));
+        assert!(set.insert("kiwi"));
+        assert!(set.remove(&"apple"));
+        assert!(!set.contains(&"apple"));
+        assert_eq!(
+            set.iter().copied().collect::<Vec<_>>(),
+            vec!["fig", "kiwi", "pear"]
+        );
+        assert_eq!(set.range("g"..).count(), 2);
+        assert_eq!(set.first(), Some(&"fig"));
+        assert_eq!(set.last(), Some(&"pear"));
+    }
+
+    #[test]
+    fn test_excluded_start() {
+        let list: SkipList<u32, ()> = (0..10).map(|k| (k * 10, ())).collect();
+        let keys: Vec<u32> = list
+            .range((Bound::Excluded(30), Bound::Excluded(70)))
+            .map(|(k, _)| *k)
+            .collect();
+        assert_eq!(keys, vec![40, 50, 60]);
+        assert!(list.range(95..).next().is_none());
+    }
+}
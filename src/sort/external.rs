@@ -0,0 +1,307 @@
+use std::cmp::Ordering;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::marker::PhantomData;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+use super::merge::kmerge_by;
+
+/// a value that can be written to a run file and read back.
+pub trait Record: Sized {
+    fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()>;
+    /// the next value, or `None` at the end of the input.
+    fn read_from<R: Read>(reader: &mut R) -> io::Result<Option<Self>>;
+}
+
+// fill `buf`, or return `false` if the input ends right away.
+fn read_or_end<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<bool> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) if filled == 0 => return Ok(false),
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(read) => filled += read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(true)
+}
+
+macro_rules! integer_record {
+    ($($t:ty),*) => {
+        $(
+            impl Record for $t {
+                fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+                    writer.write_all(&self.to_le_bytes())
+                }
+
+                fn read_from<R: Read>(reader: &mut R) -> io::Result<Option<Self>> {
+                    let mut buf = [0; std::mem::size_of::<$t>()];
+                    Ok(read_or_end(reader, &mut buf)?.then(|| <$t>::from_le_bytes(buf)))
+                }
+            }
+        )*
+    };
+}
+
+integer_record!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+impl Record for Vec<u8> {
+    fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        (self.len() as u64).write_to(writer)?;
+        writer.write_all(self)
+    }
+
+    fn read_from<R: Read>(reader: &mut R) -> io::Result<Option<Self>> {
+        let len = match u64::read_from(reader)? {
+            Some(len) => len as usize,
+            None => return Ok(None),
+        };
+        let mut bytes = vec![0; len];
+        reader.read_exact(&mut bytes)?;
+        Ok(Some(bytes))
+    }
+}
+
+impl Record for String {
+    fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        (self.len() as u64).write_to(writer)?;
+        writer.write_all(self.as_bytes())
+    }
+
+    fn read_from<R: Read>(reader: &mut R) -> io::Result<Option<Self>> {
+        match Vec::<u8>::read_from(reader)? {
+            Some(bytes) => String::from_utf8(bytes)
+                .map(Some)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+            None => Ok(None),
+        }
+    }
+}
+
+impl<A: Record, B: Record> Record for (A, B) {
+    fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.0.write_to(writer)?;
+        self.1.write_to(writer)
+    }
+
+    fn read_from<R: Read>(reader: &mut R) -> io::Result<Option<Self>> {
+        match A::read_from(reader)? {
+            Some(a) => match B::read_from(reader)? {
+                Some(b) => Ok(Some((a, b))),
+                None => Err(io::ErrorKind::UnexpectedEof.into()),
+            },
+            None => Ok(None),
+        }
+    }
+}
+
+// tells apart the run files of sorts running at the same time.
+static RUNS: AtomicUsize = AtomicUsize::new(0);
+
+/// external merge sort, for inputs larger than memory: the input is cut
+/// into runs of `run_len` values, each sorted in memory and written to a
+/// temporary file, and the runs are then merged lazily, reading each file
+/// front to back. the last run stays in memory. the sort is stable, and
+/// the files are removed once the output is dropped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExternalSort {
+    /// the most values held in memory at once while cutting runs.
+    pub run_len: usize,
+    /// where the run files go.
+    pub dir: PathBuf,
+}
+
+impl Default for ExternalSort {
+    fn default() -> Self {
+        ExternalSort {
+            run_len: 1 << 20,
+            dir: std::env::temp_dir(),
+        }
+    }
+}
+
+impl ExternalSort {
+    pub fn sort<T, I>(&self, input: I) -> io::Result<impl Iterator<Item = io::Result<T>>>
+    where
+        T: Record + Ord,
+        I: IntoIterator<Item = T>,
+    {
+        self.sort_by(input, T::cmp)
+    }
+
+    /// the values of `input` in the order `cmp`. reading a run back can
+    /// fail, so each value comes as a result; an error ends the output.
+    pub fn sort_by<T, I, F>(
+        &self,
+        input: I,
+        mut cmp: F,
+    ) -> io::Result<impl Iterator<Item = io::Result<T>>>
+    where
+        T: Record,
+        I: IntoIterator<Item = T>,
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        assert!(self.run_len > 0, "runs must hold at least one value");
+        let mut runs = Vec::new();
+        let mut input = input.into_iter().peekable();
+        loop {
+            let mut run: Vec<T> = input.by_ref().take(self.run_len).collect();
+            run.sort_by(&mut cmp);
+            if input.peek().is_none() {
+                runs.push(Run::Memory(run.into_iter()));
+                break;
+            }
+            runs.push(Run::File(RunFile::write(&self.dir, run)?));
+        }
+        let merged = kmerge_by(runs, move |a: &io::Result<T>, b: &io::Result<T>| {
+            match (a, b) {
+                (Ok(a), Ok(b)) => cmp(a, b),
+                // errors come out first.
+                (Err(_), _) => Ordering::Less,
+                (_, Err(_)) => Ordering::Greater,
+            }
+        });
+        let mut failed = false;
+        Ok(merged.take_while(move |value| {
+            let go = !failed;
+            failed |= value.is_err();
+            go
+        }))
+    }
+}
+
+enum Run<T> {
+    Memory(std::vec::IntoIter<T>),
+    File(RunFile<T>),
+}
+
+impl<T: Record> Iterator for Run<T> {
+    type Item = io::Result<T>;
+
+    fn next(&mut self) -> Option<io::Result<T>> {
+        match self {
+            Run::Memory(values) => values.next().map(Ok),
+            Run::File(file) => file.next(),
+        }
+    }
+}
+
+// a sorted run on disk, removed when dropped.
+struct RunFile<T> {
+    path: PathBuf,
+    reader: BufReader<File>,
+    done: bool,
+    values: PhantomData<T>,
+}
+
+impl<T: Record> RunFile<T> {
+    fn write(dir: &std::path::Path, run: Vec<T>) -> io::Result<Self> {
+        let id = RUNS.fetch_add(1, AtomicOrdering::Relaxed);
+        let path = dir.join(format!("algo-sort-{}-{}.run", std::process::id(), id));
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        // from here on, dropping the run removes the file.
+        let mut run_file = RunFile {
+            path,
+            reader: BufReader::new(file.try_clone()?),
+            done: false,
+            values: PhantomData,
+        };
+        let mut writer = BufWriter::new(file);
+        for value in &run {
+            value.write_to(&mut writer)?;
+        }
+        writer.flush()?;
+        run_file.reader.seek(SeekFrom::Start(0))?;
+        Ok(run_file)
+    }
+}
+
+impl<T: Record> Iterator for RunFile<T> {
+    type Item = io::Result<T>;
+
+    fn next(&mut self) -> Option<io::Result<T>> {
+        if self.done {
+            return None;
+        }
+        let value = T::read_from(&mut self.reader).transpose();
+        self.done = !matches!(value, Some(Ok(_)));
+        value
+    }
+}
+
+impl<T> Drop for RunFile<T> {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    // a fresh directory for the run files of one test.
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("algo-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_sort() {
+        let dir = scratch("external-sort");
+        let options = ExternalSort {
+            run_len: 1000,
+            dir: dir.clone(),
+        };
+        let mut rng = Rng::new(95);
+        for &n in &[0, 1, 999, 1000, 1001, 25000] {
+            let values: Vec<i64> = (0..n).map(|_| rng.range(-1000..1000)).collect();
+            let mut expected = values.clone();
+            expected.sort_unstable();
+            let sorted = options.sort(values).unwrap();
+            if n > 1000 {
+                assert_eq!(fs::read_dir(&dir).unwrap().count(), (n - 1) / 1000);
+            }
+            assert_eq!(sorted.collect::<io::Result<Vec<_>>>().unwrap(), expected);
+            assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+        }
+        fs::remove_dir(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_sort_by_stable() {
+        let dir = scratch("external-sort-by");
+        let options = ExternalSort {
+            run_len: 7,
+            dir: dir.clone(),
+        };
+        let words: Vec<(String, u32)> =
+            "the quick brown fox jumps over the lazy dog then runs off into the woods"
+                .split(' ')
+                .zip(0..)
+                .map(|(w, i)| (w.to_string(), i))
+                .collect();
+        let mut expected = words.clone();
+        expected.sort_by_key(|w| std::cmp::Reverse(w.0.len()));
+        let sorted: Vec<_> = options
+            .sort_by(words, |a, b| b.0.len().cmp(&a.0.len()))
+            .unwrap()
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert_eq!(sorted, expected);
+        // the output can be dropped before it is read to the end.
+        let mut partial = options.sort((0..100u8).rev()).unwrap();
+        assert_eq!(partial.next().unwrap().unwrap(), 0);
+        drop(partial);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+        fs::remove_dir(&dir).unwrap();
+    }
+}
@@ -0,0 +1,68 @@
+use std::cmp::Ordering;
+
+/// in-place heapsort: O(n log n) in the worst case with O(1) extra space,
+/// but not stable.
+pub fn heapsort<T: Ord>(slice: &mut [T]) {
+    heapsort_by(slice, T::cmp);
+}
+
+/// [`heapsort`] by the order `cmp`.
+pub fn heapsort_by<T, F: FnMut(&T, &T) -> Ordering>(slice: &mut [T], mut cmp: F) {
+    let n = slice.len();
+    for root in (0..n / 2).rev() {
+        sift_down(slice, root, &mut cmp);
+    }
+    for end in (1..n).rev() {
+        slice.swap(0, end);
+        sift_down(&mut slice[..end], 0, &mut cmp);
+    }
+}
+
+// restore the max-heap below `root`, given that its subtrees are heaps. the
+// crate has no binary heap type, so the partial sort and the k-way merge
+// build their heaps on slices with this too.
+pub(super) fn sift_down<T, F: FnMut(&T, &T) -> Ordering>(
+    slice: &mut [T],
+    mut root: usize,
+    cmp: &mut F,
+) {
+    loop {
+        let mut child = 2 * root + 1;
+        if child >= slice.len() {
+            return;
+        }
+        if child + 1 < slice.len() && cmp(&slice[child], &slice[child + 1]) == Ordering::Less {
+            child += 1;
+        }
+        if cmp(&slice[root], &slice[child]) != Ordering::Less {
+            return;
+        }
+        slice.swap(root, child);
+        root = child;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_sorts() {
+        let mut rng = Rng::new(87);
+        for n in 0..200 {
+            let mut values: Vec<i64> = (0..n).map(|_| rng.range(-50..50)).collect();
+            let mut expected = values.clone();
+            expected.sort();
+            heapsort(&mut values);
+            assert_eq!(values, expected);
+        }
+    }
+
+    #[test]
+    fn test_by() {
+        let mut words = vec!["pear", "fig", "banana", "kiwi", "apple"];
+        heapsort_by(&mut words, |a, b| b.len().cmp(&a.len()).then(a.cmp(b)));
+        assert_eq!(words, ["banana", "apple", "kiwi", "pear", "fig"]);
+    }
+}
@@ -0,0 +1,46 @@
+use std::cmp::Ordering;
+
+/// stable insertion sort, O(n^2) but fast on short or nearly sorted slices.
+pub fn insertion_sort<T: Ord>(slice: &mut [T]) {
+    insertion_sort_by(slice, T::cmp);
+}
+
+/// [`insertion_sort`] by the order `cmp`.
+pub fn insertion_sort_by<T, F: FnMut(&T, &T) -> Ordering>(slice: &mut [T], mut cmp: F) {
+    for i in 1..slice.len() {
+        let mut j = i;
+        while j > 0 && cmp(&slice[j - 1], &slice[j]) == Ordering::Greater {
+            slice.swap(j - 1, j);
+            j -= 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_sorts() {
+        let mut rng = Rng::new(86);
+        for n in 0..60 {
+            let mut values: Vec<i64> = (0..n).map(|_| rng.range(-20..20)).collect();
+            let mut expected = values.clone();
+            expected.sort();
+            insertion_sort(&mut values);
+            assert_eq!(values, expected);
+        }
+    }
+
+    #[test]
+    fn test_stable() {
+        let mut pairs: Vec<(u8, usize)> = (0..40).map(|i| ((i * 7 % 5) as u8, i)).collect();
+        let mut expected = pairs.clone();
+        expected.sort_by_key(|p| p.0);
+        insertion_sort_by(&mut pairs, |a, b| a.0.cmp(&b.0));
+        assert_eq!(pairs, expected);
+        insertion_sort_by(&mut pairs, |a, b| b.cmp(a));
+        assert!(pairs.windows(2).all(|w| w[0] > w[1]));
+    }
+}
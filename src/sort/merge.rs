@@ -0,0 +1,179 @@
+use std::cmp::Ordering;
+
+use super::heap::sift_down;
+use super::insertion::insertion_sort_by;
+
+// runs this short are left to insertion sort.
+const RUN: usize = 16;
+
+/// stable top-down merge sort in O(n log n), with a buffer of half the
+/// slice. already ordered halves are not merged, so sorted input is linear.
+pub fn merge_sort<T: Ord + Clone>(slice: &mut [T]) {
+    merge_sort_by(slice, T::cmp);
+}
+
+/// [`merge_sort`] by the order `cmp`.
+pub fn merge_sort_by<T: Clone, F: FnMut(&T, &T) -> Ordering>(slice: &mut [T], mut cmp: F) {
+    let mut buffer = Vec::with_capacity(slice.len() / 2);
+    sort(slice, &mut buffer, &mut cmp);
+}
+
+fn sort<T: Clone, F: FnMut(&T, &T) -> Ordering>(slice: &mut [T], buffer: &mut Vec<T>, cmp: &mut F) {
+    let n = slice.len();
+    if n <= RUN {
+        insertion_sort_by(slice, &mut *cmp);
+        return;
+    }
+    let mid = n / 2;
+    sort(&mut slice[..mid], buffer, cmp);
+    sort(&mut slice[mid..], buffer, cmp);
+    if cmp(&slice[mid - 1], &slice[mid]) != Ordering::Greater {
+        return;
+    }
+    // the left half moves out; the merged output never overtakes the right.
+    buffer.clear();
+    buffer.extend_from_slice(&slice[..mid]);
+    let (mut left, mut right) = (0, mid);
+    for k in 0..n {
+        let take_right = left == buffer.len()
+            || (right < n && cmp(&slice[right], &buffer[left]) == Ordering::Less);
+        if take_right {
+            if right == k {
+                break;
+            }
+            slice[k] = slice[right].clone();
+            right += 1;
+        } else {
+            slice[k] = buffer[left].clone();
+            left += 1;
+        }
+    }
+}
+
+/// merges iterators that are each sorted by `cmp` into one sorted iterator,
+/// O(log k) per item for k of them. on ties the earlier iterator goes
+/// first, so the merge is stable.
+pub struct KMerge<I: Iterator, F> {
+    sources: Vec<I>,
+    // the head of each unfinished source, as a heap with the least on top.
+    heads: Vec<(I::Item, usize)>,
+    cmp: F,
+}
+
+/// a [`KMerge`] of `sources`.
+pub fn kmerge_by<S, F>(sources: S, cmp: F) -> KMerge<<S::Item as IntoIterator>::IntoIter, F>
+where
+    S: IntoIterator,
+    S::Item: IntoIterator,
+    F: FnMut(&<S::Item as IntoIterator>::Item, &<S::Item as IntoIterator>::Item) -> Ordering,
+{
+    let mut sources: Vec<_> = sources.into_iter().map(IntoIterator::into_iter).collect();
+    let heads = sources
+        .iter_mut()
+        .enumerate()
+        .filter_map(|(i, source)| source.next().map(|head| (head, i)))
+        .collect();
+    let mut merge = KMerge {
+        sources,
+        heads,
+        cmp,
+    };
+    for root in (0..merge.heads.len() / 2).rev() {
+        merge.sift(root);
+    }
+    merge
+}
+
+impl<I: Iterator, F: FnMut(&I::Item, &I::Item) -> Ordering> KMerge<I, F> {
+    fn sift(&mut self, root: usize) {
+        let cmp = &mut self.cmp;
+        // reversed, as `sift_down` keeps the greatest on top.
+        let mut order =
+            |a: &(I::Item, usize), b: &(I::Item, usize)| cmp(&b.0, &a.0).then(b.1.cmp(&a.1));
+        sift_down(&mut self.heads, root, &mut order);
+    }
+}
+
+impl<I: Iterator, F: FnMut(&I::Item, &I::Item) -> Ordering> Iterator for KMerge<I, F> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        let source = self.heads.first()?.1;
+        let item = match self.sources[source].next() {
+            Some(head) => std::mem::replace(&mut self.heads[0].0, head),
+            None => self.heads.swap_remove(0).0,
+        };
+        self.sift(0);
+        Some(item)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_sorts() {
+        let mut rng = Rng::new(88);
+        for _ in 0..100 {
+            let n = rng.index(500);
+            let mut values: Vec<i64> = (0..n).map(|_| rng.range(-100..100)).collect();
+            let mut expected = values.clone();
+            expected.sort();
+            merge_sort(&mut values);
+            assert_eq!(values, expected);
+        }
+    }
+
+    #[test]
+    fn test_stable() {
+        let mut rng = Rng::new(89);
+        let mut pairs: Vec<(u64, usize)> = (0..1000).map(|i| (rng.below(10), i)).collect();
+        let mut expected = pairs.clone();
+        expected.sort_by_key(|p| p.0);
+        merge_sort_by(&mut pairs, |a, b| a.0.cmp(&b.0));
+        assert_eq!(pairs, expected);
+        // descending, and again on already sorted input.
+        expected.sort_by_key(|p| std::cmp::Reverse(p.0));
+        for _ in 0..2 {
+            merge_sort_by(&mut pairs, |a, b| b.0.cmp(&a.0));
+            assert_eq!(pairs, expected);
+        }
+    }
+
+    #[test]
+    fn test_kmerge() {
+        let runs = vec![
+            vec![(1, 'a'), (4, 'a'), (4, 'b')],
+            vec![],
+            vec![(0, 'c'), (4, 'c'), (9, 'c')],
+            vec![(2, 'd')],
+        ];
+        let merged: Vec<_> = kmerge_by(runs, |a, b| a.0.cmp(&b.0)).collect();
+        assert_eq!(
+            merged,
+            [
+                (0, 'c'),
+                (1, 'a'),
+                (2, 'd'),
+                (4, 'a'),
+                (4, 'b'),
+                (4, 'c'),
+                (9, 'c')
+            ]
+        );
+        let mut rng = Rng::new(94);
+        let runs: Vec<Vec<u64>> = (0..20)
+            .map(|_| {
+                let mut run: Vec<u64> = (0..rng.index(50)).map(|_| rng.below(100)).collect();
+                run.sort_unstable();
+                run
+            })
+            .collect();
+        let mut expected: Vec<u64> = runs.concat();
+        expected.sort_unstable();
+        assert_eq!(kmerge_by(runs, u64::cmp).collect::<Vec<_>>(), expected);
+        assert_eq!(kmerge_by(Vec::<Vec<u8>>::new(), u8::cmp).next(), None);
+    }
+}
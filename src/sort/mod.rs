@@ -0,0 +1,10 @@
+pub mod external;
+pub mod heap;
+pub mod insertion;
+pub mod merge;
+#[cfg(feature = "rayon")]
+pub mod parallel;
+pub mod partial;
+pub mod quick;
+pub mod radix;
+pub mod select;
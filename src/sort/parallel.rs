@@ -0,0 +1,193 @@
+use std::cmp::Ordering;
+
+use rayon::prelude::*;
+
+use super::merge::merge_sort_by;
+use super::radix::apply_order;
+use crate::rng::Rng;
+
+// below this many values, a range is sorted or merged on one thread.
+const SEQUENTIAL: usize = 1 << 12;
+
+/// stable parallel merge sort by `cmp`: both halves are sorted and then
+/// merged in parallel, the merge split around the median of the longer
+/// side. O(n log n) work and O(log^3 n) span.
+pub fn par_merge_sort_by<T, F>(slice: &mut [T], cmp: F)
+where
+    T: Clone + Send + Sync,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    let mut buffer = slice.to_vec();
+    sort(slice, &mut buffer, &cmp);
+}
+
+/// the values from the highest priority to the lowest, keeping the input
+/// order between equal priorities, by [`par_merge_sort_by`].
+pub fn par_sort_by_priority<T, F>(slice: &mut [T], priority: F)
+where
+    T: Clone + Send + Sync,
+    F: Fn(&T) -> u64 + Sync,
+{
+    par_merge_sort_by(slice, |a, b| priority(b).cmp(&priority(a)));
+}
+
+// sort `slice`, with `buffer` holding a copy of it to merge out of.
+fn sort<T, F>(slice: &mut [T], buffer: &mut [T], cmp: &F)
+where
+    T: Clone + Send + Sync,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    if slice.len() <= SEQUENTIAL {
+        merge_sort_by(slice, cmp);
+        return;
+    }
+    let mid = slice.len() / 2;
+    {
+        let (left, right) = slice.split_at_mut(mid);
+        let (left_buffer, right_buffer) = buffer.split_at_mut(mid);
+        rayon::join(
+            || sort(left, left_buffer, cmp),
+            || sort(right, right_buffer, cmp),
+        );
+    }
+    if cmp(&slice[mid - 1], &slice[mid]) != Ordering::Greater {
+        return;
+    }
+    buffer
+        .par_iter_mut()
+        .zip(slice.par_iter())
+        .for_each(|(copy, value)| copy.clone_from(value));
+    let (left, right) = buffer.split_at(mid);
+    merge(left, right, slice, cmp);
+}
+
+// merge the sorted `left` and `right` into `out`, `left` first on ties.
+fn merge<T, F>(left: &[T], right: &[T], out: &mut [T], cmp: &F)
+where
+    T: Clone + Send + Sync,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    if left.len() + right.len() <= SEQUENTIAL {
+        let (mut i, mut j) = (0, 0);
+        for slot in out.iter_mut() {
+            if j == right.len() || (i < left.len() && cmp(&right[j], &left[i]) != Ordering::Less) {
+                *slot = left[i].clone();
+                i += 1;
+            } else {
+                *slot = right[j].clone();
+                j += 1;
+            }
+        }
+        return;
+    }
+    // values of `right` equal to a value of `left` go after it.
+    let (i, j) = if left.len() >= right.len() {
+        let i = left.len() / 2;
+        (
+            i,
+            right.partition_point(|r| cmp(r, &left[i]) == Ordering::Less),
+        )
+    } else {
+        let j = right.len() / 2;
+        (
+            left.partition_point(|l| cmp(l, &right[j]) != Ordering::Greater),
+            j,
+        )
+    };
+    let (low, high) = out.split_at_mut(i + j);
+    rayon::join(
+        || merge(&left[..i], &right[..j], low, cmp),
+        || merge(&left[i..], &right[j..], high, cmp),
+    );
+}
+
+/// parallel sample sort by `key`: a random sample picks one splitter per
+/// bucket, a few buckets per thread, the values are moved into their
+/// buckets and the buckets are sorted in parallel. O(n log n) work, and
+/// needs no `Clone`. not stable.
+pub fn par_sort_unstable_by_key<T, K, F>(slice: &mut [T], key: F)
+where
+    T: Send + Sync,
+    K: Ord + Send + Sync,
+    F: Fn(&T) -> K + Sync,
+{
+    let n = slice.len();
+    let buckets = 4 * rayon::current_num_threads();
+    if n <= SEQUENTIAL || buckets < 2 {
+        slice.sort_unstable_by_key(key);
+        return;
+    }
+    // oversampled, so the buckets come out close in size.
+    let mut rng = Rng::new(n as u64);
+    let mut sample: Vec<K> = (0..16 * buckets)
+        .map(|_| key(&slice[rng.index(n)]))
+        .collect();
+    sample.sort_unstable();
+    let splitters: Vec<K> = sample.into_iter().skip(16).step_by(16).collect();
+    let bucket_of: Vec<usize> = slice
+        .par_iter()
+        .map(|value| {
+            let k = key(value);
+            splitters.partition_point(|s| *s < k)
+        })
+        .collect();
+    let mut starts = vec![0; splitters.len() + 2];
+    for &b in &bucket_of {
+        starts[b + 1] += 1;
+    }
+    for b in 1..starts.len() {
+        starts[b] += starts[b - 1];
+    }
+    let mut next = starts.clone();
+    let mut order = vec![0; n];
+    for (i, &b) in bucket_of.iter().enumerate() {
+        order[next[b]] = i;
+        next[b] += 1;
+    }
+    apply_order(slice, order);
+    let mut chunks = Vec::with_capacity(starts.len() - 1);
+    let mut rest = slice;
+    for w in starts.windows(2) {
+        let (chunk, tail) = rest.split_at_mut(w[1] - w[0]);
+        chunks.push(chunk);
+        rest = tail;
+    }
+    chunks
+        .into_par_iter()
+        .for_each(|chunk| chunk.sort_unstable_by_key(&key));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_par_merge_sort() {
+        let mut rng = Rng::new(96);
+        for &n in &[0, 5, SEQUENTIAL + 1, 200_000] {
+            let mut values: Vec<(u64, usize)> = (0..n).map(|i| (rng.below(1000), i)).collect();
+            let mut expected = values.clone();
+            expected.sort_by_key(|v| std::cmp::Reverse(v.0));
+            par_sort_by_priority(&mut values, |v| v.0);
+            assert_eq!(values, expected);
+            expected.sort_by_key(|v| v.0);
+            par_merge_sort_by(&mut values, |a, b| a.0.cmp(&b.0));
+            assert_eq!(values, expected);
+        }
+    }
+
+    #[test]
+    fn test_par_sample_sort() {
+        let mut rng = Rng::new(97);
+        for &(n, spread) in &[(0, 1), (100, 10), (300_000, 1 << 40), (100_000, 3)] {
+            let mut values: Vec<u64> = (0..n).map(|_| rng.below(spread)).collect();
+            let mut expected = values.clone();
+            expected.sort_unstable_by_key(|&v| std::cmp::Reverse(v));
+            par_sort_unstable_by_key(&mut values, |&v| std::cmp::Reverse(v));
+            assert_eq!(values, expected);
+        }
+        let mut words: Vec<String> = (0..50_000).map(|i| format!("{}", i)).collect();
+        par_sort_unstable_by_key(&mut words, |w| w.clone());
+        assert!(words.windows(2).all(|w| w[0] <= w[1]));
+    }
+}
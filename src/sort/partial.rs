@@ -0,0 +1,71 @@
+use std::cmp::Ordering;
+
+use super::heap::sift_down;
+
+/// sort just the `k` least elements by `cmp` into `slice[..k]`, leaving the
+/// rest in no particular order, in O(n log k): a max-heap of the `k` least
+/// seen so far is kept at the front, and emptied in order at the end. a
+/// reversed `cmp` gives the `k` greatest, greatest first. `k` past the end
+/// sorts the whole slice.
+pub fn partial_sort<T, F: FnMut(&T, &T) -> Ordering>(slice: &mut [T], k: usize, mut cmp: F) {
+    let k = k.min(slice.len());
+    if k == 0 {
+        return;
+    }
+    let (heap, rest) = slice.split_at_mut(k);
+    for root in (0..k / 2).rev() {
+        sift_down(heap, root, &mut cmp);
+    }
+    for value in rest.iter_mut() {
+        if cmp(value, &heap[0]) == Ordering::Less {
+            std::mem::swap(value, &mut heap[0]);
+            sift_down(heap, 0, &mut cmp);
+        }
+    }
+    for end in (1..k).rev() {
+        heap.swap(0, end);
+        sift_down(&mut heap[..end], 0, &mut cmp);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_partial_sort() {
+        let mut rng = Rng::new(99);
+        for _ in 0..200 {
+            let n = rng.index(200);
+            let mut values: Vec<u64> = (0..n).map(|_| rng.below(100)).collect();
+            let mut sorted = values.clone();
+            sorted.sort_unstable();
+            let k = rng.index(n + 5);
+            let mut all = values.clone();
+            partial_sort(&mut values, k, u64::cmp);
+            let k = k.min(n);
+            assert_eq!(values[..k], sorted[..k]);
+            // the rest is the same values, moved around.
+            values[k..].sort_unstable();
+            assert_eq!(values, sorted);
+            partial_sort(&mut all, n, u64::cmp);
+            assert_eq!(all, sorted);
+        }
+    }
+
+    #[test]
+    fn test_largest() {
+        let mut scores = vec![
+            ("ann", 71),
+            ("bob", 93),
+            ("cy", 85),
+            ("dee", 60),
+            ("eve", 99),
+        ];
+        partial_sort(&mut scores, 3, |a, b| b.1.cmp(&a.1));
+        assert_eq!(scores[..3], [("eve", 99), ("bob", 93), ("cy", 85)]);
+        let mut empty: Vec<u8> = Vec::new();
+        partial_sort(&mut empty, 2, u8::cmp);
+    }
+}
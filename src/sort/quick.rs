@@ -0,0 +1,212 @@
+use std::cmp::Ordering;
+
+use super::heap::heapsort_by;
+use super::insertion::insertion_sort_by;
+use crate::rng::Rng;
+
+/// how quicksort picks the element to partition around.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pivot {
+    First,
+    Middle,
+    /// the median of the first, middle and last elements.
+    MedianOfThree,
+    /// a uniformly random element, from a generator seeded once per sort.
+    Random(u64),
+}
+
+/// quicksort guarded as an introsort: a range that is split more than
+/// about 2 log n deep falls back to heapsort, so every pivot rule stays
+/// O(n log n) in the worst case. partitions three ways, so equal keys cost
+/// nothing extra. not stable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Introsort {
+    pub pivot: Pivot,
+    /// ranges this short are left to insertion sort.
+    pub insertion_threshold: usize,
+}
+
+impl Default for Introsort {
+    fn default() -> Self {
+        Introsort {
+            pivot: Pivot::MedianOfThree,
+            insertion_threshold: 16,
+        }
+    }
+}
+
+impl Introsort {
+    pub fn sort_by<T, F: FnMut(&T, &T) -> Ordering>(&self, slice: &mut [T], mut cmp: F) {
+        let seed = match self.pivot {
+            Pivot::Random(seed) => seed,
+            _ => 0,
+        };
+        let mut rng = Rng::new(seed);
+        let depth = 2 * (usize::BITS - slice.len().leading_zeros()) as usize;
+        self.sort_range(slice, depth, &mut cmp, &mut rng);
+    }
+
+    fn sort_range<T, F: FnMut(&T, &T) -> Ordering>(
+        &self,
+        mut slice: &mut [T],
+        mut depth: usize,
+        cmp: &mut F,
+        rng: &mut Rng,
+    ) {
+        loop {
+            if slice.len() <= self.insertion_threshold.max(1) {
+                insertion_sort_by(slice, &mut *cmp);
+                return;
+            }
+            if depth == 0 {
+                heapsort_by(slice, &mut *cmp);
+                return;
+            }
+            depth -= 1;
+            let pivot = self.choose(slice, cmp, rng);
+            slice.swap(0, pivot);
+            let (less, greater) = partition(slice, cmp);
+            // recurse into the smaller side, so the stack stays O(log n).
+            let (low, rest) = slice.split_at_mut(less);
+            let high = &mut rest[greater - less..];
+            if low.len() < high.len() {
+                self.sort_range(low, depth, cmp, rng);
+                slice = high;
+            } else {
+                self.sort_range(high, depth, cmp, rng);
+                slice = low;
+            }
+        }
+    }
+
+    fn choose<T, F: FnMut(&T, &T) -> Ordering>(
+        &self,
+        slice: &[T],
+        cmp: &mut F,
+        rng: &mut Rng,
+    ) -> usize {
+        match self.pivot {
+            Pivot::First => 0,
+            Pivot::Middle => slice.len() / 2,
+            Pivot::Random(_) => rng.index(slice.len()),
+            Pivot::MedianOfThree => median_of_three(slice, cmp),
+        }
+    }
+}
+
+// the index of the median of the first, middle and last elements.
+pub(super) fn median_of_three<T, F: FnMut(&T, &T) -> Ordering>(slice: &[T], cmp: &mut F) -> usize {
+    let (mut a, mut b, c) = (0, slice.len() / 2, slice.len() - 1);
+    if cmp(&slice[b], &slice[a]) == Ordering::Less {
+        std::mem::swap(&mut a, &mut b);
+    }
+    // now `slice[a] <= slice[b]`.
+    if cmp(&slice[c], &slice[b]) != Ordering::Less {
+        b
+    } else if cmp(&slice[c], &slice[a]) == Ordering::Less {
+        a
+    } else {
+        c
+    }
+}
+
+// split around the pivot at index 0 into `..less` below it, `less..greater`
+// equal to it and `greater..` above it.
+pub(super) fn partition<T, F: FnMut(&T, &T) -> Ordering>(
+    slice: &mut [T],
+    cmp: &mut F,
+) -> (usize, usize) {
+    // the pivot itself always sits at `less`.
+    let (mut less, mut i, mut greater) = (0, 1, slice.len());
+    while i < greater {
+        match cmp(&slice[i], &slice[less]) {
+            Ordering::Less => {
+                slice.swap(less, i);
+                less += 1;
+                i += 1;
+            }
+            Ordering::Greater => {
+                greater -= 1;
+                slice.swap(i, greater);
+            }
+            Ordering::Equal => i += 1,
+        }
+    }
+    (less, greater)
+}
+
+/// [`Introsort`] with its default settings.
+pub fn quicksort<T: Ord>(slice: &mut [T]) {
+    Introsort::default().sort_by(slice, T::cmp);
+}
+
+/// [`quicksort`] by the order `cmp`.
+pub fn quicksort_by<T, F: FnMut(&T, &T) -> Ordering>(slice: &mut [T], cmp: F) {
+    Introsort::default().sort_by(slice, cmp);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PIVOTS: [Pivot; 4] = [
+        Pivot::First,
+        Pivot::Middle,
+        Pivot::MedianOfThree,
+        Pivot::Random(7),
+    ];
+
+    #[test]
+    fn test_sorts() {
+        let mut rng = Rng::new(90);
+        for _ in 0..100 {
+            let n = rng.index(500);
+            let spread = 1 + rng.below(1000) as i64;
+            let values: Vec<i64> = (0..n).map(|_| rng.range(0..spread)).collect();
+            let mut expected = values.clone();
+            expected.sort();
+            for &pivot in &PIVOTS {
+                let threshold = rng.index(20);
+                let mut sorted = values.clone();
+                let options = Introsort {
+                    pivot,
+                    insertion_threshold: threshold,
+                };
+                options.sort_by(&mut sorted, i64::cmp);
+                assert_eq!(sorted, expected);
+            }
+        }
+        let mut words = vec!["b", "c", "a"];
+        quicksort_by(&mut words, |a, b| b.cmp(a));
+        assert_eq!(words, ["c", "b", "a"]);
+    }
+
+    #[test]
+    fn test_worst_case_guarded() {
+        // sorted input is quadratic for a plain first-element quicksort.
+        let n = 1 << 14;
+        let inputs: Vec<Vec<u32>> = vec![
+            (0..n).collect(),
+            (0..n).rev().collect(),
+            (0..n).map(|i| i % 2).collect(),
+        ];
+        for input in inputs {
+            for &pivot in &PIVOTS {
+                let mut values = input.clone();
+                let mut comparisons = 0u64;
+                let options = Introsort {
+                    pivot,
+                    ..Introsort::default()
+                };
+                options.sort_by(&mut values, |a, b| {
+                    comparisons += 1;
+                    a.cmp(b)
+                });
+                assert!(values.windows(2).all(|w| w[0] <= w[1]));
+                assert!(comparisons < 100 * n as u64, "{:?}: {}", pivot, comparisons);
+            }
+        }
+        let mut empty: Vec<u8> = Vec::new();
+        quicksort(&mut empty);
+    }
+}
@@ -0,0 +1,225 @@
+use super::insertion::insertion_sort_by;
+
+/// an integer key sorted byte by byte, least significant byte first.
+pub trait RadixKey: Copy + Ord {
+    const BYTES: usize;
+    /// the `i`-th byte from the least significant one, such that comparing
+    /// the bytes from the most significant down orders the keys.
+    fn byte(self, i: usize) -> u8;
+}
+
+macro_rules! unsigned_key {
+    ($($t:ty),*) => {
+        $(
+            impl RadixKey for $t {
+                const BYTES: usize = std::mem::size_of::<$t>();
+
+                fn byte(self, i: usize) -> u8 {
+                    (self >> (8 * i)) as u8
+                }
+            }
+        )*
+    };
+}
+
+// signed keys flip their sign bit, which puts the negatives first.
+macro_rules! signed_key {
+    ($($t:ty => $u:ty),*) => {
+        $(
+            impl RadixKey for $t {
+                const BYTES: usize = std::mem::size_of::<$t>();
+
+                fn byte(self, i: usize) -> u8 {
+                    ((self as $u ^ (1 << (8 * Self::BYTES - 1))) >> (8 * i)) as u8
+                }
+            }
+        )*
+    };
+}
+
+unsigned_key!(u8, u16, u32, u64, u128, usize);
+signed_key!(i8 => u8, i16 => u16, i32 => u32, i64 => u64, i128 => u128, isize => usize);
+
+// buckets this short are left to insertion sort by msd radix sort.
+const SMALL: usize = 32;
+
+/// stable counting sort of keys in `0..range`, in O(n + range).
+pub fn counting_sort_by_key<T, F: FnMut(&T) -> usize>(slice: &mut [T], range: usize, mut key: F) {
+    let keys: Vec<usize> = slice.iter().map(&mut key).collect();
+    let mut starts = vec![0; range + 1];
+    for &k in &keys {
+        assert!(k < range, "key {} out of range 0..{}", k, range);
+        starts[k + 1] += 1;
+    }
+    for k in 0..range {
+        starts[k + 1] += starts[k];
+    }
+    let mut order = vec![0; keys.len()];
+    for (i, &k) in keys.iter().enumerate() {
+        order[starts[k]] = i;
+        starts[k] += 1;
+    }
+    apply_order(slice, order);
+}
+
+/// stable lsd radix sort, a counting pass per byte of the key: O(n) for
+/// keys of a fixed width, and well ahead of comparison sorts on large
+/// slices. passes where every key shares the byte are skipped.
+pub fn radix_sort<K: RadixKey>(slice: &mut [K]) {
+    radix_sort_by_key(slice, |&k| k);
+}
+
+/// [`radix_sort`] by the key `key`, computed once per element.
+pub fn radix_sort_by_key<T, K: RadixKey, F: FnMut(&T) -> K>(slice: &mut [T], key: F) {
+    let keys: Vec<K> = slice.iter().map(key).collect();
+    let n = keys.len();
+    let mut order: Vec<usize> = (0..n).collect();
+    let mut next = vec![0; n];
+    for byte in 0..K::BYTES {
+        let mut starts = [0usize; 257];
+        for &k in &keys {
+            starts[k.byte(byte) as usize + 1] += 1;
+        }
+        if starts.contains(&n) {
+            continue;
+        }
+        for b in 0..256 {
+            starts[b + 1] += starts[b];
+        }
+        for &i in &order {
+            let b = keys[i].byte(byte) as usize;
+            next[starts[b]] = i;
+            starts[b] += 1;
+        }
+        std::mem::swap(&mut order, &mut next);
+    }
+    apply_order(slice, order);
+}
+
+/// msd radix sort in place, as an american flag sort: the elements are
+/// permuted into buckets by their most significant byte, then each bucket
+/// is sorted by the next byte, short ones by insertion sort. not stable.
+pub fn msd_radix_sort<K: RadixKey>(slice: &mut [K]) {
+    msd_radix_sort_by_key(slice, |&k| k);
+}
+
+/// [`msd_radix_sort`] by the key `key`.
+pub fn msd_radix_sort_by_key<T, K: RadixKey, F: FnMut(&T) -> K>(slice: &mut [T], mut key: F) {
+    msd(slice, K::BYTES, &mut key);
+}
+
+// sort by the `bytes` least significant bytes, the others being equal.
+fn msd<T, K: RadixKey, F: FnMut(&T) -> K>(slice: &mut [T], bytes: usize, key: &mut F) {
+    if slice.len() <= SMALL {
+        insertion_sort_by(slice, |a, b| key(a).cmp(&key(b)));
+        return;
+    }
+    if bytes == 0 {
+        return;
+    }
+    let byte = bytes - 1;
+    let mut ends = [0usize; 256];
+    for item in slice.iter() {
+        ends[key(item).byte(byte) as usize] += 1;
+    }
+    for b in 1..256 {
+        ends[b] += ends[b - 1];
+    }
+    let mut next = [0usize; 256];
+    next[1..].copy_from_slice(&ends[..255]);
+    for b in 0..256 {
+        while next[b] < ends[b] {
+            let to = key(&slice[next[b]]).byte(byte) as usize;
+            if to == b {
+                next[b] += 1;
+            } else {
+                slice.swap(next[b], next[to]);
+                next[to] += 1;
+            }
+        }
+    }
+    let mut start = 0;
+    for &end in &ends {
+        if end - start > 1 {
+            msd(&mut slice[start..end], byte, key);
+        }
+        start = end;
+    }
+}
+
+// rearrange `slice` so that position `i` holds what was at `order[i]`,
+// following the cycles of the permutation.
+pub(super) fn apply_order<T>(slice: &mut [T], mut order: Vec<usize>) {
+    for start in 0..order.len() {
+        let mut i = start;
+        while order[i] != start {
+            let from = order[i];
+            slice.swap(i, from);
+            order[i] = i;
+            i = from;
+        }
+        order[i] = i;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_counting_sort() {
+        let mut rng = Rng::new(91);
+        let mut pairs: Vec<(usize, usize)> = (0..500).map(|i| (rng.index(20), i)).collect();
+        let mut expected = pairs.clone();
+        expected.sort_by_key(|p| p.0);
+        counting_sort_by_key(&mut pairs, 20, |p| p.0);
+        assert_eq!(pairs, expected);
+        let mut empty: Vec<usize> = Vec::new();
+        counting_sort_by_key(&mut empty, 0, |&k| k);
+    }
+
+    #[test]
+    fn test_radix_sorts() {
+        let mut rng = Rng::new(92);
+        for _ in 0..50 {
+            let n = rng.index(2000);
+            let mut small: Vec<i8> = (0..n).map(|_| rng.next_u64() as i8).collect();
+            let mut wide: Vec<i64> = (0..n)
+                .map(|_| rng.next_u64() as i64 >> rng.index(64))
+                .collect();
+            let mut unsigned: Vec<u32> = (0..n).map(|_| rng.below(1 << 20) as u32).collect();
+            let mut expected = (small.clone(), wide.clone(), unsigned.clone());
+            expected.0.sort();
+            expected.1.sort();
+            expected.2.sort();
+            for _ in 0..2 {
+                let (mut s, mut w, mut u) = (small.clone(), wide.clone(), unsigned.clone());
+                radix_sort(&mut s);
+                radix_sort(&mut w);
+                radix_sort(&mut u);
+                assert_eq!((&s, &w, &u), (&expected.0, &expected.1, &expected.2));
+                msd_radix_sort(&mut small);
+                msd_radix_sort(&mut wide);
+                msd_radix_sort(&mut unsigned);
+                assert_eq!(
+                    (&small, &wide, &unsigned),
+                    (&expected.0, &expected.1, &expected.2)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_by_key() {
+        let mut rng = Rng::new(93);
+        let mut pairs: Vec<(u16, usize)> = (0..3000).map(|i| (rng.below(300) as u16, i)).collect();
+        let mut expected = pairs.clone();
+        expected.sort_by_key(|p| std::cmp::Reverse(p.0));
+        // stable on a key that puts larger values first.
+        radix_sort_by_key(&mut pairs, |p| u16::MAX - p.0);
+        assert_eq!(pairs, expected);
+        msd_radix_sort_by_key(&mut pairs, |p| p.0 as i32 - 150);
+        assert!(pairs.windows(2).all(|w| w[0].0 <= w[1].0));
+    }
+}
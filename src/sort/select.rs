@@ -0,0 +1,130 @@
+use std::cmp::Ordering;
+
+use super::insertion::insertion_sort_by;
+use super::quick::{median_of_three, partition};
+
+// ranges this short are finished by insertion sort.
+const SMALL: usize = 16;
+
+/// the element that would be at index `k` were `slice` sorted by `cmp`,
+/// found by quickselect in expected O(n) and without a full sort. the
+/// slice is left partitioned around it: nothing before index `k` is
+/// greater and nothing after is less.
+///
+/// pivots are medians of three until a partition keeps more than three
+/// quarters of its range a few times, and medians of medians after that,
+/// so the worst case is O(n) as well.
+pub fn select_nth<T, F: FnMut(&T, &T) -> Ordering>(
+    slice: &mut [T],
+    k: usize,
+    mut cmp: F,
+) -> &mut T {
+    assert!(
+        k < slice.len(),
+        "index {} out of bounds for length {}",
+        k,
+        slice.len()
+    );
+    select(slice, k, &mut cmp);
+    &mut slice[k]
+}
+
+fn select<T, F: FnMut(&T, &T) -> Ordering>(mut slice: &mut [T], mut k: usize, cmp: &mut F) {
+    let mut bad_rounds = 0;
+    loop {
+        let n = slice.len();
+        if n <= SMALL {
+            insertion_sort_by(slice, &mut *cmp);
+            return;
+        }
+        let pivot = if bad_rounds < 3 {
+            median_of_three(slice, cmp)
+        } else {
+            median_of_medians(slice, cmp)
+        };
+        slice.swap(0, pivot);
+        let (less, greater) = partition(slice, cmp);
+        let kept = if k < less {
+            slice = &mut slice[..less];
+            less
+        } else if k >= greater {
+            k -= greater;
+            slice = &mut slice[greater..];
+            n - greater
+        } else {
+            return;
+        };
+        if 4 * kept > 3 * n {
+            bad_rounds += 1;
+        }
+    }
+}
+
+// the index of a pivot with at least about 3/10 of the slice on each side:
+// the median of the medians of groups of five, moved to the front.
+fn median_of_medians<T, F: FnMut(&T, &T) -> Ordering>(slice: &mut [T], cmp: &mut F) -> usize {
+    let groups = slice.len() / 5;
+    for group in 0..groups {
+        let five = &mut slice[5 * group..5 * group + 5];
+        insertion_sort_by(five, &mut *cmp);
+        slice.swap(group, 5 * group + 2);
+    }
+    select(&mut slice[..groups], groups / 2, cmp);
+    groups / 2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_select_nth() {
+        let mut rng = Rng::new(98);
+        for _ in 0..200 {
+            let n = 1 + rng.index(300);
+            let spread = 1 + rng.below(500) as i64;
+            let mut values: Vec<i64> = (0..n).map(|_| rng.range(0..spread)).collect();
+            let mut sorted = values.clone();
+            sorted.sort_unstable();
+            let k = rng.index(n);
+            assert_eq!(*select_nth(&mut values, k, i64::cmp), sorted[k]);
+            assert!(values[..k].iter().all(|&v| v <= values[k]));
+            assert!(values[k + 1..].iter().all(|&v| v >= values[k]));
+        }
+        let mut words = vec!["delta", "alpha", "echo", "charlie", "bravo"];
+        assert_eq!(*select_nth(&mut words, 0, |a, b| b.cmp(a)), "echo");
+    }
+
+    #[test]
+    fn test_linear() {
+        let n = 1 << 15;
+        let inputs: Vec<Vec<u32>> = vec![
+            (0..n).collect(),
+            (0..n).rev().collect(),
+            // organ pipe, which defeats the median of three.
+            (0..n).map(|i| i.min(n - i)).collect(),
+            vec![7; n as usize],
+        ];
+        for input in inputs {
+            for &k in &[0, n as usize / 3, n as usize - 1] {
+                let mut values = input.clone();
+                let mut sorted = input.clone();
+                sorted.sort_unstable();
+                let mut comparisons = 0u64;
+                let nth = *select_nth(&mut values, k, |a, b| {
+                    comparisons += 1;
+                    a.cmp(b)
+                });
+                assert_eq!(nth, sorted[k]);
+                assert!(comparisons < 30 * n as u64, "{}", comparisons);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_out_of_bounds() {
+        select_nth(&mut [1, 2, 3], 3, i32::cmp);
+    }
+}
@@ -0,0 +1,100 @@
+use std::marker::PhantomData;
+use std::ops::RangeBounds;
+
+use crate::resolve_range;
+use crate::segment_tree::{Gcd, Max, Min, Monoid};
+
+/// marks monoids where `combine(x, x) == x`, so overlapping folds are harmless.
+pub trait Idempotent {}
+
+impl Idempotent for Min {}
+impl Idempotent for Max {}
+impl Idempotent for Gcd {}
+
+/// immutable range fold in O(1) after an O(n log n) build, for idempotent operations.
+pub struct SparseTable<T, Op> {
+    // `levels[k][i]` folds `i..i + 2^k`.
+    levels: Vec<Vec<T>>,
+    _op: PhantomData<Op>,
+}
+
+impl<T: Clone, Op: Monoid<T> + Idempotent> SparseTable<T, Op> {
+    /// build the table over `values`.
+    pub fn new(values: Vec<T>) -> Self {
+        let n = values.len();
+        let mut levels = vec![values];
+        let mut width = 1;
+        while 2 * width <= n {
+            let prev = &levels[levels.len() - 1];
+            let next = (0..=n - 2 * width)
+                .map(|i| Op::combine(&prev[i], &prev[i + width]))
+                .collect();
+            levels.push(next);
+            width *= 2;
+        }
+        SparseTable {
+            levels,
+            _op: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.levels[0].len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// fold the elements in `range`, returns the identity for an empty range.
+    pub fn query(&self, range: impl RangeBounds<usize>) -> T {
+        let (start, end) = resolve_range(range, self.len());
+        if start == end {
+            return Op::identity();
+        }
+        let k = (usize::BITS - 1 - (end - start).leading_zeros()) as usize;
+        let level = &self.levels[k];
+        Op::combine(&level[start], &level[end - (1 << k)])
+    }
+}
+
+impl<T: Clone, Op: Monoid<T> + Idempotent> From<Vec<T>> for SparseTable<T, Op> {
+    fn from(values: Vec<T>) -> Self {
+        SparseTable::new(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_min_max() {
+        let values: Vec<i32> = (0..50).map(|i| (i * 37 + 11) % 23 - 10).collect();
+        let min = SparseTable::<i32, Min>::new(values.clone());
+        let max = SparseTable::<i32, Max>::new(values.clone());
+        for l in 0..values.len() {
+            for r in l + 1..=values.len() {
+                assert_eq!(min.query(l..r), *values[l..r].iter().min().unwrap());
+                assert_eq!(max.query(l..r), *values[l..r].iter().max().unwrap());
+            }
+        }
+        assert_eq!(min.query(3..3), i32::MAX);
+    }
+
+    #[test]
+    fn test_gcd() {
+        let table = SparseTable::<u64, Gcd>::new(vec![12, 18, 24, 7, 14, 28]);
+        assert_eq!(table.query(0..3), 6);
+        assert_eq!(table.query(..4), 1);
+        assert_eq!(table.query(4..), 14);
+        assert_eq!(table.query(1..=2), 6);
+    }
+
+    #[test]
+    fn test_empty() {
+        let table = SparseTable::<u32, Max>::new(Vec::new());
+        assert!(table.is_empty());
+        assert_eq!(table.query(..), 0);
+    }
+}
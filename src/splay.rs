@@ -0,0 +1,423 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::OrderedMap;
+
+const NIL: usize = usize::MAX;
+
+struct Node<K, V> {
+    key: K,
+    value: V,
+    parent: usize,
+    left: usize,
+    right: usize,
+}
+
+/// ordered map backed by a splay tree: every access moves the touched key to
+/// the root, giving amortized O(log n) operations and very fast repeated access
+/// to recently used keys.
+///
+/// nodes live in a dense arena and link to each other by index.
+pub struct SplayTree<K, V> {
+    nodes: Vec<Node<K, V>>,
+    root: usize,
+}
+
+impl<K: Ord, V> SplayTree<K, V> {
+    /// create an empty tree.
+    pub fn new() -> Self {
+        SplayTree {
+            nodes: Vec::new(),
+            root: NIL,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// the entry currently at the root, i.e. the most recently splayed one.
+    pub fn root(&self) -> Option<(&K, &V)> {
+        self.entry(self.root)
+    }
+
+    /// move `key` to the root, or its in-order neighbour if it is absent.
+    ///
+    /// returns whether `key` is present.
+    pub fn splay(&mut self, key: &K) -> bool {
+        let mut cur = self.root;
+        let mut last = NIL;
+        while cur != NIL {
+            last = cur;
+            match key.cmp(&self.nodes[cur].key) {
+                Ordering::Less => cur = self.nodes[cur].left,
+                Ordering::Greater => cur = self.nodes[cur].right,
+                Ordering::Equal => break,
+            }
+        }
+        if last == NIL {
+            return false;
+        }
+        self.splay_node(last);
+        self.nodes[self.root].key == *key
+    }
+
+    /// returns the value for `key` after splaying it to the root.
+    pub fn access(&mut self, key: &K) -> Option<&V> {
+        if self.splay(key) {
+            Some(&self.nodes[self.root].value)
+        } else {
+            None
+        }
+    }
+
+    /// returns a mutable reference to the value for `key` after splaying it to the root.
+    pub fn access_mut(&mut self, key: &K) -> Option<&mut V> {
+        if self.splay(key) {
+            let root = self.root;
+            Some(&mut self.nodes[root].value)
+        } else {
+            None
+        }
+    }
+
+    /// returns the value for `key` without restructuring the tree.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let mut cur = self.root;
+        while cur != NIL {
+            let node = &self.nodes[cur];
+            match key.cmp(&node.key) {
+                Ordering::Less => cur = node.left,
+                Ordering::Greater => cur = node.right,
+                Ordering::Equal => return Some(&node.value),
+            }
+        }
+        None
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// insert `value` for `key`, returning the previous value if there was one.
+    /// the key ends up at the root.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if self.root == NIL {
+            self.root = self.alloc(key, value);
+            return None;
+        }
+        if self.splay(&key) {
+            let root = self.root;
+            return Some(std::mem::replace(&mut self.nodes[root].value, value));
+        }
+        let old = self.root;
+        let new = self.alloc(key, value);
+        if self.nodes[new].key < self.nodes[old].key {
+            let left = self.nodes[old].left;
+            self.nodes[old].left = NIL;
+            self.set_left(new, left);
+            self.set_right(new, old);
+        } else {
+            let right = self.nodes[old].right;
+            self.nodes[old].right = NIL;
+            self.set_right(new, right);
+            self.set_left(new, old);
+        }
+        self.root = new;
+        None
+    }
+
+    /// remove `key`, returning its value if it was present.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        if !self.splay(key) {
+            return None;
+        }
+        let old = self.root;
+        let (left, right) = (self.nodes[old].left, self.nodes[old].right);
+        if left != NIL {
+            self.nodes[left].parent = NIL;
+        }
+        if right != NIL {
+            self.nodes[right].parent = NIL;
+        }
+        self.nodes[old].left = NIL;
+        self.nodes[old].right = NIL;
+        if left == NIL {
+            self.root = right;
+        } else {
+            // the largest key on the left becomes the root and adopts the right side.
+            self.root = left;
+            let mut max = left;
+            while self.nodes[max].right != NIL {
+                max = self.nodes[max].right;
+            }
+            self.splay_node(max);
+            self.set_right(max, right);
+        }
+        Some(self.release(old).value)
+    }
+
+    /// entry with the smallest key.
+    pub fn first(&self) -> Option<(&K, &V)> {
+        let mut cur = self.root;
+        while cur != NIL && self.nodes[cur].left != NIL {
+            cur = self.nodes[cur].left;
+        }
+        self.entry(cur)
+    }
+
+    /// entry with the largest key.
+    pub fn last(&self) -> Option<(&K, &V)> {
+        let mut cur = self.root;
+        while cur != NIL && self.nodes[cur].right != NIL {
+            cur = self.nodes[cur].right;
+        }
+        self.entry(cur)
+    }
+
+    /// iterate in increasing key order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut iter = Iter {
+            tree: self,
+            stack: Vec::new(),
+        };
+        iter.push_left(self.root);
+        iter
+    }
+
+    fn entry(&self, index: usize) -> Option<(&K, &V)> {
+        self.nodes.get(index).map(|node| (&node.key, &node.value))
+    }
+
+    fn alloc(&mut self, key: K, value: V) -> usize {
+        self.nodes.push(Node {
+            key,
+            value,
+            parent: NIL,
+            left: NIL,
+            right: NIL,
+        });
+        self.nodes.len() - 1
+    }
+
+    // remove a detached node from the arena, moving the last node into its slot.
+    fn release(&mut self, index: usize) -> Node<K, V> {
+        let last = self.nodes.len() - 1;
+        if index != last {
+            let (parent, left, right) = {
+                let node = &self.nodes[last];
+                (node.parent, node.left, node.right)
+            };
+            if parent != NIL {
+                if self.nodes[parent].left == last {
+                    self.nodes[parent].left = index;
+                } else {
+                    self.nodes[parent].right = index;
+                }
+            }
+            if left != NIL {
+                self.nodes[left].parent = index;
+            }
+            if right != NIL {
+                self.nodes[right].parent = index;
+            }
+            if self.root == last {
+                self.root = index;
+            }
+        }
+        self.nodes.swap_remove(index)
+    }
+
+    fn set_left(&mut self, parent: usize, child: usize) {
+        self.nodes[parent].left = child;
+        if child != NIL {
+            self.nodes[child].parent = parent;
+        }
+    }
+
+    fn set_right(&mut self, parent: usize, child: usize) {
+        self.nodes[parent].right = child;
+        if child != NIL {
+            self.nodes[child].parent = parent;
+        }
+    }
+
+    // rotate `x` above its parent.
+    fn rotate(&mut self, x: usize) {
+        let p = self.nodes[x].parent;
+        let g = self.nodes[p].parent;
+        if self.nodes[p].left == x {
+            let b = self.nodes[x].right;
+            self.set_left(p, b);
+            self.set_right(x, p);
+        } else {
+            let b = self.nodes[x].left;
+            self.set_right(p, b);
+            self.set_left(x, p);
+        }
+        self.nodes[x].parent = g;
+        if g == NIL {
+            self.root = x;
+        } else if self.nodes[g].left == p {
+            self.nodes[g].left = x;
+        } else {
+            self.nodes[g].right = x;
+        }
+    }
+
+    fn splay_node(&mut self, x: usize) {
+        while self.nodes[x].parent != NIL {
+            let p = self.nodes[x].parent;
+            let g = self.nodes[p].parent;
+            if g != NIL {
+                let zig_zig = (self.nodes[g].left == p) == (self.nodes[p].left == x);
+                if zig_zig {
+                    self.rotate(p);
+                } else {
+                    self.rotate(x);
+                }
+            }
+            self.rotate(x);
+        }
+    }
+}
+
+impl<K: Ord, V> Default for SplayTree<K, V> {
+    fn default() -> Self {
+        SplayTree::new()
+    }
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug> fmt::Debug for SplayTree<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Ord, V> OrderedMap<K, V> for SplayTree<K, V> {
+    fn new() -> Self {
+        SplayTree::new()
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn get(&self, key: &K) -> Option<&V> {
+        self.get(key)
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.insert(key, value)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        self.remove(key)
+    }
+
+    fn first(&self) -> Option<(&K, &V)> {
+        self.first()
+    }
+
+    fn last(&self) -> Option<(&K, &V)> {
+        self.last()
+    }
+
+    fn iter<'a>(&'a self) -> Box<dyn Iterator<Item = (&'a K, &'a V)> + 'a>
+    where
+        K: 'a,
+        V: 'a,
+    {
+        Box::new(self.iter())
+    }
+}
+
+/// in-order iterator over the entries of a splay tree.
+pub struct Iter<'a, K, V> {
+    tree: &'a SplayTree<K, V>,
+    stack: Vec<usize>,
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    fn push_left(&mut self, mut cur: usize) {
+        while cur != NIL {
+            self.stack.push(cur);
+            cur = self.tree.nodes[cur].left;
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let cur = self.stack.pop()?;
+        let tree = self.tree;
+        self.push_left(tree.nodes[cur].right);
+        let node = &tree.nodes[cur];
+        Some((&node.key, &node.value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_against_btree() {
+        let mut rng = Rng::new(9);
+        let mut tree = SplayTree::new();
+        let mut model = BTreeMap::new();
+        for step in 0..3000 {
+            let key = rng.range(0..300);
+            match rng.index(3) {
+                0 | 1 => assert_eq!(tree.insert(key, step), model.insert(key, step)),
+                _ => assert_eq!(tree.remove(&key), model.remove(&key)),
+            }
+            assert_eq!(tree.len(), model.len());
+            assert_eq!(tree.get(&key), model.get(&key));
+        }
+        assert!(tree.iter().eq(model.iter()));
+        assert_eq!(tree.first(), model.iter().next());
+        assert_eq!(tree.last(), model.iter().next_back());
+    }
+
+    #[test]
+    fn test_splay_moves_to_root() {
+        let mut tree: SplayTree<i32, i32> = SplayTree::new();
+        for k in 0..100 {
+            tree.insert(k, k * k);
+        }
+        assert!(tree.splay(&42));
+        assert_eq!(tree.root(), Some((&42, &1764)));
+        assert_eq!(tree.access(&7), Some(&49));
+        assert_eq!(tree.root().map(|(k, _)| *k), Some(7));
+        // a missing key splays a neighbour instead.
+        assert!(!tree.splay(&1000));
+        assert_eq!(tree.root().map(|(k, _)| *k), Some(99));
+        *tree.access_mut(&3).unwrap() = 0;
+        assert_eq!(tree.get(&3), Some(&0));
+    }
+
+    #[test]
+    fn test_sequential_access() {
+        let mut tree = SplayTree::new();
+        for k in (0..1000).rev() {
+            tree.insert(k, ());
+        }
+        for k in 0..1000 {
+            assert_eq!(tree.access(&k), Some(&()));
+        }
+        for k in 0..1000 {
+            assert_eq!(tree.remove(&k), Some(()));
+        }
+        assert!(tree.is_empty());
+        assert!(tree.root().is_none());
+    }
+}
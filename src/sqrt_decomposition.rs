@@ -0,0 +1,276 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::ops::RangeBounds;
+
+use crate::resolve_range;
+
+/// the summary a [`SqrtDecomposition`] keeps for each block, and how a
+/// query folds items and whole blocks into its answer.
+///
+/// unlike a segment tree's monoid, the answer type `Acc` need not combine
+/// with itself; it only has to absorb single items and block summaries,
+/// which fits queries like the mode of a range.
+pub trait BlockSummary<T>: Sized {
+    /// the running state of a query; it may carry the query's parameters.
+    type Acc;
+    type Output;
+    fn build(block: &[T]) -> Self;
+    /// refresh after `block[index]` changed from `old`; rebuilds by default.
+    fn update(&mut self, block: &[T], index: usize, old: &T) {
+        let _ = (index, old);
+        *self = Self::build(block);
+    }
+    fn add_item(acc: &mut Self::Acc, item: &T);
+    fn add_block(&self, acc: &mut Self::Acc);
+    fn finish(acc: Self::Acc) -> Self::Output;
+}
+
+/// a sequence split into blocks of about `sqrt(n)` items, each with a
+/// summary: a query folds the partial blocks at its ends item by item and
+/// the blocks in between by their summaries, so both queries and point
+/// updates cost O(sqrt n) summary and item steps.
+pub struct SqrtDecomposition<T, S> {
+    items: Vec<T>,
+    block_size: usize,
+    summaries: Vec<S>,
+}
+
+impl<T, S: BlockSummary<T>> SqrtDecomposition<T, S> {
+    pub fn new(items: Vec<T>) -> Self {
+        let size = (items.len() as f64).sqrt().ceil() as usize;
+        SqrtDecomposition::with_block_size(items, size.max(1))
+    }
+
+    pub fn with_block_size(items: Vec<T>, block_size: usize) -> Self {
+        assert!(block_size > 0, "block size must be positive");
+        let summaries = items.chunks(block_size).map(S::build).collect();
+        SqrtDecomposition {
+            items,
+            block_size,
+            summaries,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn block_size(&self) -> usize {
+        self.block_size
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.items.get(index)
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        &self.items
+    }
+
+    /// replace the item at `index`, returning the old one.
+    pub fn set(&mut self, index: usize, value: T) -> T {
+        let old = std::mem::replace(&mut self.items[index], value);
+        let b = index / self.block_size;
+        let start = b * self.block_size;
+        let end = (start + self.block_size).min(self.items.len());
+        self.summaries[b].update(&self.items[start..end], index - start, &old);
+        old
+    }
+
+    /// answer a query over `range`, starting from `acc`.
+    pub fn query_with<R: RangeBounds<usize>>(&self, range: R, mut acc: S::Acc) -> S::Output {
+        let (start, end) = resolve_range(range, self.items.len());
+        let size = self.block_size;
+        let mut i = start;
+        while i < end {
+            if i % size == 0 && i + size <= end {
+                self.summaries[i / size].add_block(&mut acc);
+                i += size;
+            } else {
+                S::add_item(&mut acc, &self.items[i]);
+                i += 1;
+            }
+        }
+        S::finish(acc)
+    }
+
+    pub fn query<R: RangeBounds<usize>>(&self, range: R) -> S::Output
+    where
+        S::Acc: Default,
+    {
+        self.query_with(range, S::Acc::default())
+    }
+}
+
+/// the most frequent value of a range and its count, ties going to the
+/// smallest value.
+pub struct Mode<T> {
+    counts: HashMap<T, usize>,
+}
+
+impl<T: Hash + Ord + Clone> BlockSummary<T> for Mode<T> {
+    type Acc = HashMap<T, usize>;
+    type Output = Option<(T, usize)>;
+
+    fn build(block: &[T]) -> Self {
+        let mut counts = HashMap::new();
+        for item in block {
+            *counts.entry(item.clone()).or_insert(0) += 1;
+        }
+        Mode { counts }
+    }
+
+    fn update(&mut self, block: &[T], index: usize, old: &T) {
+        if let Some(count) = self.counts.get_mut(old) {
+            *count -= 1;
+            if *count == 0 {
+                self.counts.remove(old);
+            }
+        }
+        *self.counts.entry(block[index].clone()).or_insert(0) += 1;
+    }
+
+    fn add_item(acc: &mut Self::Acc, item: &T) {
+        *acc.entry(item.clone()).or_insert(0) += 1;
+    }
+
+    fn add_block(&self, acc: &mut Self::Acc) {
+        for (item, &count) in &self.counts {
+            *acc.entry(item.clone()).or_insert(0) += count;
+        }
+    }
+
+    fn finish(acc: Self::Acc) -> Self::Output {
+        acc.into_iter()
+            .max_by_key(|(item, count)| (*count, Reverse(item.clone())))
+    }
+}
+
+/// a count of the items below `bound`, answered from sorted copies of the
+/// blocks.
+pub struct CountBelow<T> {
+    sorted: Vec<T>,
+}
+
+/// the state of a [`CountBelow`] query.
+#[derive(Clone, Debug)]
+pub struct Below<T> {
+    pub bound: T,
+    pub count: usize,
+}
+
+impl<T> Below<T> {
+    pub fn new(bound: T) -> Self {
+        Below { bound, count: 0 }
+    }
+}
+
+impl<T: Ord + Clone> BlockSummary<T> for CountBelow<T> {
+    type Acc = Below<T>;
+    type Output = usize;
+
+    fn build(block: &[T]) -> Self {
+        let mut sorted = block.to_vec();
+        sorted.sort_unstable();
+        CountBelow { sorted }
+    }
+
+    fn update(&mut self, block: &[T], index: usize, old: &T) {
+        let i = self.sorted.binary_search(old).unwrap();
+        self.sorted.remove(i);
+        let value = &block[index];
+        let j = self.sorted.partition_point(|x| x < value);
+        self.sorted.insert(j, value.clone());
+    }
+
+    fn add_item(acc: &mut Below<T>, item: &T) {
+        if *item < acc.bound {
+            acc.count += 1;
+        }
+    }
+
+    fn add_block(&self, acc: &mut Below<T>) {
+        acc.count += self.sorted.partition_point(|x| *x < acc.bound);
+    }
+
+    fn finish(acc: Below<T>) -> usize {
+        acc.count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    fn naive_mode(items: &[u32]) -> Option<(u32, usize)> {
+        let mut best: Option<(u32, usize)> = None;
+        for &x in items {
+            let count = items.iter().filter(|&&y| y == x).count();
+            if best.is_none_or(|(b, c)| count > c || (count == c && x < b)) {
+                best = Some((x, count));
+            }
+        }
+        best
+    }
+
+    #[test]
+    fn test_against_naive() {
+        let mut rng = Rng::new(53);
+        let items: Vec<u32> = (0..500).map(|_| rng.below(20) as u32).collect();
+        let mut modes: SqrtDecomposition<u32, Mode<u32>> = SqrtDecomposition::new(items.clone());
+        let mut below: SqrtDecomposition<u32, CountBelow<u32>> =
+            SqrtDecomposition::new(items.clone());
+        let mut model = items;
+        assert_eq!(modes.block_size(), 23);
+        for _ in 0..500 {
+            if rng.chance(0.3) {
+                let (i, v) = (rng.index(model.len()), rng.below(20) as u32);
+                model[i] = v;
+                modes.set(i, v);
+                below.set(i, v);
+            }
+            let start = rng.index(model.len());
+            let end = start + rng.index(model.len() - start + 1);
+            assert_eq!(modes.query(start..end), naive_mode(&model[start..end]));
+            let bound = rng.below(21) as u32;
+            let count = model[start..end].iter().filter(|&&x| x < bound).count();
+            assert_eq!(below.query_with(start..end, Below::new(bound)), count);
+        }
+        assert_eq!(modes.as_slice(), &model[..]);
+    }
+
+    #[test]
+    fn test_default_update() {
+        // a plain sum, relying on the rebuilding `update`.
+        struct Total(i64);
+        impl BlockSummary<i64> for Total {
+            type Acc = i64;
+            type Output = i64;
+            fn build(block: &[i64]) -> Self {
+                Total(block.iter().sum())
+            }
+            fn add_item(acc: &mut i64, item: &i64) {
+                *acc += item;
+            }
+            fn add_block(&self, acc: &mut i64) {
+                *acc += self.0;
+            }
+            fn finish(acc: i64) -> i64 {
+                acc
+            }
+        }
+        let mut sums: SqrtDecomposition<i64, Total> =
+            SqrtDecomposition::with_block_size((1..=10).collect(), 3);
+        assert_eq!(sums.query(..), 55);
+        assert_eq!(sums.set(4, -5), 5);
+        assert_eq!(sums.query(2..9), 3 + 4 - 5 + 6 + 7 + 8 + 9);
+        assert_eq!(sums.query(3..3), 0);
+        assert_eq!(sums.get(4), Some(&-5));
+    }
+}
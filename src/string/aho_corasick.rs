@@ -0,0 +1,298 @@
+use std::collections::{BTreeMap, VecDeque};
+
+struct Node {
+    next: BTreeMap<u8, usize>,
+    fail: usize,
+    depth: usize,
+    // patterns that end here, own ones first, then those inherited through
+    // failure links (which are shorter).
+    outputs: Vec<usize>,
+}
+
+impl Node {
+    fn new(depth: usize) -> Self {
+        Node {
+            next: BTreeMap::new(),
+            fail: 0,
+            depth,
+            outputs: Vec::new(),
+        }
+    }
+}
+
+/// how matches are reported while scanning.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MatchKind {
+    /// every occurrence of every pattern, ordered by end position.
+    Overlapping,
+    /// non-overlapping matches scanned left to right: the match starting
+    /// leftmost wins, and among those the longest one.
+    LeftmostLongest,
+}
+
+/// an occurrence of pattern `pattern` at `text[start..end]`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Match {
+    pub pattern: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// automaton matching a fixed set of byte patterns against a text in a
+/// single pass. patterns are identified by their index in the input.
+pub struct AhoCorasick {
+    nodes: Vec<Node>,
+    lens: Vec<usize>,
+}
+
+impl AhoCorasick {
+    /// compile `patterns`, none of which may be empty.
+    pub fn new<I, P>(patterns: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<[u8]>,
+    {
+        let mut nodes = vec![Node::new(0)];
+        let mut lens = Vec::new();
+        for (id, pattern) in patterns.into_iter().enumerate() {
+            let pattern = pattern.as_ref();
+            assert!(!pattern.is_empty(), "patterns must not be empty");
+            let mut v = 0;
+            for &c in pattern {
+                v = match nodes[v].next.get(&c) {
+                    Some(&next) => next,
+                    None => {
+                        let next = nodes.len();
+                        nodes.push(Node::new(nodes[v].depth + 1));
+                        nodes[v].next.insert(c, next);
+                        next
+                    }
+                };
+            }
+            nodes[v].outputs.push(id);
+            lens.push(pattern.len());
+        }
+        // failure links in bfs order, so a node's link is final before its children.
+        let mut queue: VecDeque<usize> = nodes[0].next.values().copied().collect();
+        while let Some(v) = queue.pop_front() {
+            let edges: Vec<(u8, usize)> = nodes[v].next.iter().map(|(&c, &u)| (c, u)).collect();
+            for (c, u) in edges {
+                let mut f = nodes[v].fail;
+                let fail = loop {
+                    if let Some(&w) = nodes[f].next.get(&c) {
+                        break w;
+                    }
+                    if f == 0 {
+                        break 0;
+                    }
+                    f = nodes[f].fail;
+                };
+                nodes[u].fail = fail;
+                let inherited = nodes[fail].outputs.clone();
+                nodes[u].outputs.extend(inherited);
+                queue.push_back(u);
+            }
+        }
+        AhoCorasick { nodes, lens }
+    }
+
+    /// number of compiled patterns.
+    pub fn pattern_count(&self) -> usize {
+        self.lens.len()
+    }
+
+    /// length of pattern `id`.
+    pub fn pattern_len(&self, id: usize) -> usize {
+        self.lens[id]
+    }
+
+    fn step(&self, mut v: usize, c: u8) -> usize {
+        loop {
+            if let Some(&next) = self.nodes[v].next.get(&c) {
+                return next;
+            }
+            if v == 0 {
+                return 0;
+            }
+            v = self.nodes[v].fail;
+        }
+    }
+
+    /// check whether any pattern occurs in `text`.
+    pub fn is_match(&self, text: &[u8]) -> bool {
+        let mut v = 0;
+        text.iter().any(|&c| {
+            v = self.step(v, c);
+            !self.nodes[v].outputs.is_empty()
+        })
+    }
+
+    /// lazily scan `text`, yielding matches as they are found.
+    pub fn find_iter<'a>(&'a self, text: &'a [u8], kind: MatchKind) -> Matches<'a> {
+        Matches {
+            automaton: self,
+            text,
+            kind,
+            pos: 0,
+            state: 0,
+            output: 0,
+        }
+    }
+}
+
+/// iterator over the matches in a text, see [`AhoCorasick::find_iter`].
+pub struct Matches<'a> {
+    automaton: &'a AhoCorasick,
+    text: &'a [u8],
+    kind: MatchKind,
+    // bytes consumed so far.
+    pos: usize,
+    state: usize,
+    // next output of `state` to report in overlapping mode.
+    output: usize,
+}
+
+impl Matches<'_> {
+    fn matched(&self, pattern: usize) -> Match {
+        Match {
+            pattern,
+            start: self.pos - self.automaton.lens[pattern],
+            end: self.pos,
+        }
+    }
+
+    fn next_overlapping(&mut self) -> Option<Match> {
+        loop {
+            let outputs = &self.automaton.nodes[self.state].outputs;
+            if let Some(&id) = outputs.get(self.output) {
+                self.output += 1;
+                return Some(self.matched(id));
+            }
+            let &c = self.text.get(self.pos)?;
+            self.state = self.automaton.step(self.state, c);
+            self.pos += 1;
+            self.output = 0;
+        }
+    }
+
+    fn next_leftmost_longest(&mut self) -> Option<Match> {
+        let mut best: Option<Match> = None;
+        loop {
+            for &id in &self.automaton.nodes[self.state].outputs {
+                let m = self.matched(id);
+                let better = best.is_none_or(|b| {
+                    m.start < b.start || (m.start == b.start && m.end - m.start > b.end - b.start)
+                });
+                if better {
+                    best = Some(m);
+                }
+            }
+            if let Some(b) = best {
+                // any later match starts within the current state's string.
+                let earliest = self.pos - self.automaton.nodes[self.state].depth;
+                if earliest > b.start || self.pos == self.text.len() {
+                    self.pos = b.end;
+                    self.state = 0;
+                    return best;
+                }
+            }
+            let &c = self.text.get(self.pos)?;
+            self.state = self.automaton.step(self.state, c);
+            self.pos += 1;
+        }
+    }
+}
+
+impl Iterator for Matches<'_> {
+    type Item = Match;
+
+    fn next(&mut self) -> Option<Match> {
+        match self.kind {
+            MatchKind::Overlapping => self.next_overlapping(),
+            MatchKind::LeftmostLongest => self.next_leftmost_longest(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    fn triples(matches: Matches<'_>) -> Vec<(usize, usize, usize)> {
+        matches.map(|m| (m.pattern, m.start, m.end)).collect()
+    }
+
+    #[test]
+    fn test_overlapping() {
+        let ac = AhoCorasick::new(["he", "she", "his", "hers"]);
+        assert_eq!(
+            triples(ac.find_iter(b"ushers", MatchKind::Overlapping)),
+            vec![(1, 1, 4), (0, 2, 4), (3, 2, 6)]
+        );
+        assert!(ac.is_match(b"this"));
+        assert!(!ac.is_match(b"hxs"));
+    }
+
+    #[test]
+    fn test_leftmost_longest() {
+        let ac = AhoCorasick::new(["abcd", "bc", "b", "abc", "cde"]);
+        assert_eq!(
+            triples(ac.find_iter(b"xabcdex", MatchKind::LeftmostLongest)),
+            vec![(0, 1, 5)]
+        );
+        assert_eq!(
+            triples(ac.find_iter(b"abcbcde", MatchKind::LeftmostLongest)),
+            vec![(3, 0, 3), (1, 3, 5)]
+        );
+    }
+
+    #[test]
+    fn test_against_naive() {
+        let mut rng = Rng::new(18);
+        for _ in 0..200 {
+            let patterns: Vec<Vec<u8>> = (0..1 + rng.index(5))
+                .map(|_| {
+                    (0..1 + rng.index(4))
+                        .map(|_| b'a' + rng.index(3) as u8)
+                        .collect()
+                })
+                .collect();
+            let text: Vec<u8> = (0..rng.index(40))
+                .map(|_| b'a' + rng.index(3) as u8)
+                .collect();
+            let ac = AhoCorasick::new(&patterns);
+
+            let mut expected = Vec::new();
+            for end in 0..=text.len() {
+                for (id, p) in patterns.iter().enumerate() {
+                    if text[..end].ends_with(p) {
+                        expected.push((id, end - p.len(), end));
+                    }
+                }
+            }
+            let mut found = triples(ac.find_iter(&text, MatchKind::Overlapping));
+            found.sort_by_key(|&(id, _, end)| (end, id));
+            assert_eq!(found, expected);
+
+            let mut expected = Vec::new();
+            let mut pos = 0;
+            while pos < text.len() {
+                let longest = (0..patterns.len())
+                    .filter(|&id| text[pos..].starts_with(&patterns[id]))
+                    .max_by_key(|&id| (patterns[id].len(), std::cmp::Reverse(id)));
+                match longest {
+                    Some(id) => {
+                        expected.push((id, pos, pos + patterns[id].len()));
+                        pos += patterns[id].len();
+                    }
+                    None => pos += 1,
+                }
+            }
+            assert_eq!(
+                triples(ac.find_iter(&text, MatchKind::LeftmostLongest)),
+                expected
+            );
+        }
+    }
+}
@@ -0,0 +1,329 @@
+use std::fmt::{self, Display, Write};
+use std::ops::Range;
+
+/// a run of a diff from `a` to `b`. every variant records its position in
+/// both sequences, so insertions know where in `a` they go and vice versa.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiffOp {
+    /// `a[a..a + len]` equals `b[b..b + len]`.
+    Equal { a: usize, b: usize, len: usize },
+    /// `a[a..a + len]` is removed; the following items of `b` start at `b`.
+    Delete { a: usize, b: usize, len: usize },
+    /// `b[b..b + len]` is inserted before `a[a]`.
+    Insert { a: usize, b: usize, len: usize },
+}
+
+impl DiffOp {
+    /// the items of `a` covered by this run.
+    pub fn a_range(&self) -> Range<usize> {
+        match *self {
+            DiffOp::Equal { a, len, .. } | DiffOp::Delete { a, len, .. } => a..a + len,
+            DiffOp::Insert { a, .. } => a..a,
+        }
+    }
+
+    /// the items of `b` covered by this run.
+    pub fn b_range(&self) -> Range<usize> {
+        match *self {
+            DiffOp::Equal { b, len, .. } | DiffOp::Insert { b, len, .. } => b..b + len,
+            DiffOp::Delete { b, .. } => b..b,
+        }
+    }
+
+    fn extended(self, other: DiffOp) -> Option<DiffOp> {
+        use DiffOp::*;
+        match (self, other) {
+            (Equal { a, b, len }, Equal { len: more, .. }) => Some(Equal {
+                a,
+                b,
+                len: len + more,
+            }),
+            (Delete { a, b, len }, Delete { len: more, .. }) => Some(Delete {
+                a,
+                b,
+                len: len + more,
+            }),
+            (Insert { a, b, len }, Insert { len: more, .. }) => Some(Insert {
+                a,
+                b,
+                len: len + more,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// a shortest diff from `a` to `b` with myers' O((n + m) d) algorithm, where
+/// `d` is the number of inserted and deleted items. runs are maximal.
+pub fn diff<T: Eq>(a: &[T], b: &[T]) -> Vec<DiffOp> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let offset = n + m + 1;
+    let at = |k: isize| (k + offset) as usize;
+    let mut v = vec![0isize; 2 * offset as usize + 1];
+    // trace[d] is the furthest-reaching x per diagonal before round d.
+    let mut trace = Vec::new();
+    'search: for d in 0..=n + m {
+        trace.push(v.clone());
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && v[at(k - 1)] < v[at(k + 1)]) {
+                v[at(k + 1)]
+            } else {
+                v[at(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[at(k)] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+    }
+
+    // walk back from (n, m), emitting single-item steps in reverse.
+    let mut steps = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && v[at(k - 1)] < v[at(k + 1)]) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = v[at(prev_k)];
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            steps.push(DiffOp::Equal {
+                a: x as usize,
+                b: y as usize,
+                len: 1,
+            });
+        }
+        if d > 0 {
+            if x == prev_x {
+                steps.push(DiffOp::Insert {
+                    a: x as usize,
+                    b: prev_y as usize,
+                    len: 1,
+                });
+            } else {
+                steps.push(DiffOp::Delete {
+                    a: prev_x as usize,
+                    b: y as usize,
+                    len: 1,
+                });
+            }
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+
+    let mut ops: Vec<DiffOp> = Vec::new();
+    for step in steps.into_iter().rev() {
+        match ops.last().and_then(|last| last.extended(step)) {
+            Some(merged) => *ops.last_mut().unwrap() = merged,
+            None => ops.push(step),
+        }
+    }
+    ops
+}
+
+/// a longest common subsequence of `a` and `b`, as pairs of matched indices
+/// in increasing order. derived from the myers diff, so it runs in O((n + m) d).
+pub fn lcs<T: Eq>(a: &[T], b: &[T]) -> Vec<(usize, usize)> {
+    diff(a, b)
+        .into_iter()
+        .filter_map(|op| match op {
+            DiffOp::Equal { a, b, len } => Some((0..len).map(move |i| (a + i, b + i))),
+            _ => None,
+        })
+        .flatten()
+        .collect()
+}
+
+/// a group of nearby changes together with up to `context` unchanged items
+/// around them, as in a unified diff.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Hunk {
+    pub ops: Vec<DiffOp>,
+}
+
+impl Hunk {
+    /// the items of `a` shown in this hunk.
+    pub fn a_range(&self) -> Range<usize> {
+        self.ops[0].a_range().start..self.ops[self.ops.len() - 1].a_range().end
+    }
+
+    /// the items of `b` shown in this hunk.
+    pub fn b_range(&self) -> Range<usize> {
+        self.ops[0].b_range().start..self.ops[self.ops.len() - 1].b_range().end
+    }
+}
+
+impl Display for Hunk {
+    /// the unified-diff header, e.g. `@@ -3,4 +3,5 @@`, with one-based lines.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let line = |r: Range<usize>| {
+            let start = if r.is_empty() { r.start } else { r.start + 1 };
+            format!("{},{}", start, r.len())
+        };
+        write!(
+            f,
+            "@@ -{} +{} @@",
+            line(self.a_range()),
+            line(self.b_range())
+        )
+    }
+}
+
+/// split a diff into hunks, keeping `context` unchanged items around each
+/// change and merging changes whose contexts would touch.
+pub fn hunks(ops: &[DiffOp], context: usize) -> Vec<Hunk> {
+    let mut hunks = Vec::new();
+    let mut current = Vec::new();
+    for (i, &op) in ops.iter().enumerate() {
+        let (a, b, len) = match op {
+            DiffOp::Equal { a, b, len } => (a, b, len),
+            _ => {
+                current.push(op);
+                continue;
+            }
+        };
+        let (first, last) = (i == 0, i + 1 == ops.len());
+        if !first && !last && len <= 2 * context {
+            current.push(op);
+            continue;
+        }
+        if !first {
+            let trail = len.min(context);
+            if trail > 0 {
+                current.push(DiffOp::Equal { a, b, len: trail });
+            }
+            hunks.push(Hunk {
+                ops: std::mem::take(&mut current),
+            });
+        }
+        if !last {
+            let lead = len.min(context);
+            if lead > 0 {
+                current.push(DiffOp::Equal {
+                    a: a + len - lead,
+                    b: b + len - lead,
+                    len: lead,
+                });
+            }
+        }
+    }
+    if !current.is_empty() {
+        hunks.push(Hunk { ops: current });
+    }
+    hunks
+}
+
+/// render a unified diff of two sequences of lines, without file headers.
+pub fn unified_diff<T: Eq + Display>(a: &[T], b: &[T], context: usize) -> String {
+    let mut out = String::new();
+    for hunk in hunks(&diff(a, b), context) {
+        writeln!(out, "{}", hunk).unwrap();
+        for op in &hunk.ops {
+            let (sign, lines) = match op {
+                DiffOp::Equal { .. } => (' ', &a[op.a_range()]),
+                DiffOp::Delete { .. } => ('-', &a[op.a_range()]),
+                DiffOp::Insert { .. } => ('+', &b[op.b_range()]),
+            };
+            for line in lines {
+                writeln!(out, "{}{}", sign, line).unwrap();
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    fn lcs_len_dp(a: &[u8], b: &[u8]) -> usize {
+        let mut dp = vec![vec![0; b.len() + 1]; a.len() + 1];
+        for i in 0..a.len() {
+            for j in 0..b.len() {
+                dp[i + 1][j + 1] = if a[i] == b[j] {
+                    dp[i][j] + 1
+                } else {
+                    dp[i][j + 1].max(dp[i + 1][j])
+                };
+            }
+        }
+        dp[a.len()][b.len()]
+    }
+
+    #[test]
+    fn test_diff_and_lcs() {
+        let (a, b) = (b"abcabba", b"cbabac");
+        let ops = diff(a, b);
+        let edits: usize = ops
+            .iter()
+            .filter(|op| !matches!(op, DiffOp::Equal { .. }))
+            .map(|op| op.a_range().len() + op.b_range().len())
+            .sum();
+        assert_eq!(edits, 5);
+        let pairs = lcs(a, b);
+        assert_eq!(pairs.len(), 4);
+        assert!(pairs.iter().all(|&(i, j)| a[i] == b[j]));
+        assert!(diff::<u8>(&[], &[]).is_empty());
+        assert_eq!(
+            diff(b"", b"ab"),
+            vec![DiffOp::Insert { a: 0, b: 0, len: 2 }]
+        );
+    }
+
+    #[test]
+    fn test_random_diffs() {
+        let mut rng = Rng::new(24);
+        for _ in 0..300 {
+            let a: Vec<u8> = (0..rng.index(20)).map(|_| rng.index(3) as u8).collect();
+            let b: Vec<u8> = (0..rng.index(20)).map(|_| rng.index(3) as u8).collect();
+            let ops = diff(&a, &b);
+            // rebuilding b from a and the runs must cover both sequences in order.
+            let (mut pa, mut pb) = (0, 0);
+            let mut rebuilt = Vec::new();
+            for op in &ops {
+                assert_eq!((op.a_range().start, op.b_range().start), (pa, pb));
+                match op {
+                    DiffOp::Equal { .. } => {
+                        assert_eq!(a[op.a_range()], b[op.b_range()]);
+                        rebuilt.extend_from_slice(&a[op.a_range()]);
+                    }
+                    DiffOp::Insert { .. } => rebuilt.extend_from_slice(&b[op.b_range()]),
+                    DiffOp::Delete { .. } => {}
+                }
+                pa = op.a_range().end;
+                pb = op.b_range().end;
+            }
+            assert_eq!((pa, pb), (a.len(), b.len()));
+            assert_eq!(rebuilt, b);
+            assert_eq!(lcs(&a, &b).len(), lcs_len_dp(&a, &b));
+        }
+    }
+
+    #[test]
+    fn test_unified() {
+        let a: Vec<String> = (1..=12).map(|i| i.to_string()).collect();
+        let mut b = a.clone();
+        b[1] = "two".to_string();
+        b.insert(10, "ten and a half".to_string());
+        let ops = diff(&a, &b);
+        assert_eq!(hunks(&ops, 2).len(), 2);
+        assert_eq!(hunks(&ops, 4).len(), 1);
+        assert!(hunks(&diff(&a, &a), 3).is_empty());
+        let expected = "@@ -1,4 +1,4 @@\n 1\n-2\n+two\n 3\n 4\n\
+                        @@ -9,4 +9,5 @@\n 9\n 10\n+ten and a half\n 11\n 12\n";
+        assert_eq!(unified_diff(&a, &b, 2), expected);
+    }
+}
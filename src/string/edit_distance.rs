@@ -0,0 +1,242 @@
+/// one step of an edit script turning `a` into `b`. indices refer to the
+/// original sequences.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EditOp {
+    /// `a[a]` is kept as `b[b]`.
+    Keep { a: usize, b: usize },
+    /// `a[a]` is replaced by `b[b]`.
+    Substitute { a: usize, b: usize },
+    /// `a[a]` is removed.
+    Delete { a: usize },
+    /// `b[b]` is inserted.
+    Insert { b: usize },
+    /// `a[a]` and `a[a + 1]` are swapped to give `b[b]` and `b[b + 1]`.
+    Transpose { a: usize, b: usize },
+}
+
+impl EditOp {
+    /// whether the step costs one edit, i.e. is anything but `Keep`.
+    pub fn is_edit(&self) -> bool {
+        !matches!(self, EditOp::Keep { .. })
+    }
+}
+
+/// levenshtein distance: the minimum number of insertions, deletions and
+/// substitutions turning `a` into `b`. O(nm) time, O(m) space.
+pub fn levenshtein<T: Eq>(a: &[T], b: &[T]) -> usize {
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut curr = vec![0; b.len() + 1];
+    for (i, x) in a.iter().enumerate() {
+        curr[0] = i + 1;
+        for (j, y) in b.iter().enumerate() {
+            let substitute = prev[j] + (x != y) as usize;
+            curr[j + 1] = substitute.min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+    prev[b.len()]
+}
+
+/// levenshtein distance if it is at most `max`, computed only on the band
+/// of `2 * max + 1` diagonals in O(max * n).
+pub fn levenshtein_within<T: Eq>(a: &[T], b: &[T], max: usize) -> Option<usize> {
+    let (n, m) = (a.len(), b.len());
+    if n.max(m) - n.min(m) > max {
+        return None;
+    }
+    const INF: usize = usize::MAX / 2;
+    let mut prev: Vec<usize> = (0..=m).map(|j| if j <= max { j } else { INF }).collect();
+    let mut curr = vec![INF; m + 1];
+    for i in 1..=n {
+        let lo = i.saturating_sub(max);
+        let hi = (i + max).min(m);
+        // fence the band so no stale cell from an earlier row is read.
+        if lo > 0 {
+            curr[lo - 1] = INF;
+        }
+        let mut best = INF;
+        for j in lo..=hi {
+            curr[j] = if j == 0 {
+                i
+            } else {
+                let substitute = prev[j - 1] + (a[i - 1] != b[j - 1]) as usize;
+                substitute.min(prev[j] + 1).min(curr[j - 1] + 1)
+            };
+            best = best.min(curr[j]);
+        }
+        if best > max {
+            return None;
+        }
+        if hi < m {
+            curr[hi + 1] = INF;
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+    Some(prev[m]).filter(|&d| d <= max)
+}
+
+/// damerau-levenshtein distance in its optimal-string-alignment form:
+/// adjacent transpositions also cost one, but no substring is edited twice.
+pub fn damerau_levenshtein<T: Eq>(a: &[T], b: &[T]) -> usize {
+    table(a, b, true)[a.len()][b.len()]
+}
+
+/// a shortest levenshtein edit script from `a` to `b`.
+pub fn edit_script<T: Eq>(a: &[T], b: &[T]) -> Vec<EditOp> {
+    trace(a, b, false)
+}
+
+/// a shortest edit script from `a` to `b` that may also use transpositions,
+/// matching [`damerau_levenshtein`].
+pub fn damerau_edit_script<T: Eq>(a: &[T], b: &[T]) -> Vec<EditOp> {
+    trace(a, b, true)
+}
+
+fn table<T: Eq>(a: &[T], b: &[T], transpositions: bool) -> Vec<Vec<usize>> {
+    let (n, m) = (a.len(), b.len());
+    let mut d = vec![vec![0; m + 1]; n + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=n {
+        for j in 1..=m {
+            let mut best = (d[i - 1][j - 1] + (a[i - 1] != b[j - 1]) as usize)
+                .min(d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1);
+            if transpositions && i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(d[i - 2][j - 2] + 1);
+            }
+            d[i][j] = best;
+        }
+    }
+    d
+}
+
+fn trace<T: Eq>(a: &[T], b: &[T], transpositions: bool) -> Vec<EditOp> {
+    let d = table(a, b, transpositions);
+    let (mut i, mut j) = (a.len(), b.len());
+    let mut ops = Vec::with_capacity(i.max(j));
+    while i > 0 || j > 0 {
+        if i > 0 && j > 0 {
+            let same = a[i - 1] == b[j - 1];
+            if d[i][j] == d[i - 1][j - 1] + (!same) as usize {
+                ops.push(if same {
+                    EditOp::Keep { a: i - 1, b: j - 1 }
+                } else {
+                    EditOp::Substitute { a: i - 1, b: j - 1 }
+                });
+                i -= 1;
+                j -= 1;
+                continue;
+            }
+            if transpositions
+                && i > 1
+                && j > 1
+                && a[i - 1] == b[j - 2]
+                && a[i - 2] == b[j - 1]
+                && d[i][j] == d[i - 2][j - 2] + 1
+            {
+                ops.push(EditOp::Transpose { a: i - 2, b: j - 2 });
+                i -= 2;
+                j -= 2;
+                continue;
+            }
+        }
+        if i > 0 && d[i][j] == d[i - 1][j] + 1 {
+            ops.push(EditOp::Delete { a: i - 1 });
+            i -= 1;
+        } else {
+            ops.push(EditOp::Insert { b: j - 1 });
+            j -= 1;
+        }
+    }
+    ops.reverse();
+    ops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    // replay a script on `a` and check that it yields `b`.
+    fn apply(a: &[u8], b: &[u8], ops: &[EditOp]) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut pos = 0;
+        for op in ops {
+            match *op {
+                EditOp::Keep { a: i, b: j } => {
+                    assert_eq!((i, a[i]), (pos, b[j]));
+                    out.push(a[i]);
+                    pos += 1;
+                }
+                EditOp::Substitute { a: i, b: j } => {
+                    assert_eq!(i, pos);
+                    out.push(b[j]);
+                    pos += 1;
+                }
+                EditOp::Delete { a: i } => {
+                    assert_eq!(i, pos);
+                    pos += 1;
+                }
+                EditOp::Insert { b: j } => out.push(b[j]),
+                EditOp::Transpose { a: i, .. } => {
+                    assert_eq!(i, pos);
+                    out.push(a[i + 1]);
+                    out.push(a[i]);
+                    pos += 2;
+                }
+            }
+        }
+        assert_eq!(pos, a.len());
+        out
+    }
+
+    #[test]
+    fn test_distances() {
+        assert_eq!(levenshtein(b"kitten", b"sitting"), 3);
+        assert_eq!(levenshtein(b"", b"abc"), 3);
+        assert_eq!(levenshtein(b"ca", b"abc"), 3);
+        assert_eq!(damerau_levenshtein(b"ca", b"ac"), 1);
+        assert_eq!(damerau_levenshtein(b"ca", b"abc"), 3);
+        assert_eq!(levenshtein_within(b"kitten", b"sitting", 3), Some(3));
+        assert_eq!(levenshtein_within(b"kitten", b"sitting", 2), None);
+        assert_eq!(levenshtein_within(b"a", b"abcd", 2), None);
+        let ops = edit_script(b"kitten", b"sitting");
+        assert_eq!(ops.iter().filter(|op| op.is_edit()).count(), 3);
+        assert_eq!(ops[0], EditOp::Substitute { a: 0, b: 0 });
+        assert_eq!(
+            damerau_edit_script(b"abdc", b"abcd")[2],
+            EditOp::Transpose { a: 2, b: 2 }
+        );
+    }
+
+    #[test]
+    fn test_scripts_replay() {
+        let mut rng = Rng::new(23);
+        for _ in 0..300 {
+            let a: Vec<u8> = (0..rng.index(12))
+                .map(|_| b'a' + rng.index(3) as u8)
+                .collect();
+            let b: Vec<u8> = (0..rng.index(12))
+                .map(|_| b'a' + rng.index(3) as u8)
+                .collect();
+            let dist = levenshtein(&a, &b);
+            let ops = edit_script(&a, &b);
+            assert_eq!(apply(&a, &b, &ops), b);
+            assert_eq!(ops.iter().filter(|op| op.is_edit()).count(), dist);
+            for max in 0..12 {
+                let expected = Some(dist).filter(|&d| d <= max);
+                assert_eq!(levenshtein_within(&a, &b, max), expected);
+            }
+            let osa = damerau_levenshtein(&a, &b);
+            assert!(osa <= dist);
+            let ops = damerau_edit_script(&a, &b);
+            assert_eq!(apply(&a, &b, &ops), b);
+            assert_eq!(ops.iter().filter(|op| op.is_edit()).count(), osa);
+        }
+    }
+}
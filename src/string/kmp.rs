@@ -0,0 +1,152 @@
+/// prefix function of `s`: `pi[i]` is the length of the longest proper
+/// border (prefix that is also a suffix) of `s[..=i]`.
+pub fn prefix_function<T: Eq>(s: &[T]) -> Vec<usize> {
+    let mut pi = vec![0; s.len()];
+    for i in 1..s.len() {
+        let mut k = pi[i - 1];
+        while k > 0 && s[i] != s[k] {
+            k = pi[k - 1];
+        }
+        if s[i] == s[k] {
+            k += 1;
+        }
+        pi[i] = k;
+    }
+    pi
+}
+
+/// lengths of every proper border of `s`, longest first.
+pub fn borders<T: Eq>(s: &[T]) -> Vec<usize> {
+    let pi = prefix_function(s);
+    let mut k = pi.last().copied().unwrap_or(0);
+    let mut result = Vec::new();
+    while k > 0 {
+        result.push(k);
+        k = pi[k - 1];
+    }
+    result
+}
+
+/// smallest `p > 0` with `s[i] == s[i + p]` for all valid `i`; `s.len()` when
+/// `s` has no shorter period.
+pub fn period<T: Eq>(s: &[T]) -> usize {
+    s.len() - prefix_function(s).last().copied().unwrap_or(0)
+}
+
+/// a pattern preprocessed for knuth-morris-pratt search in O(n + m).
+#[derive(Clone, Debug)]
+pub struct Kmp<T> {
+    pattern: Vec<T>,
+    pi: Vec<usize>,
+}
+
+impl<T: Eq> Kmp<T> {
+    pub fn new(pattern: impl Into<Vec<T>>) -> Self {
+        let pattern = pattern.into();
+        let pi = prefix_function(&pattern);
+        Kmp { pattern, pi }
+    }
+
+    pub fn pattern(&self) -> &[T] {
+        &self.pattern
+    }
+
+    /// the prefix function of the pattern, see [`prefix_function`].
+    pub fn prefix_function(&self) -> &[usize] {
+        &self.pi
+    }
+
+    /// starting positions of every occurrence of the pattern in `text`,
+    /// overlapping ones included.
+    pub fn find_all(&self, text: &[T]) -> Vec<usize> {
+        let mut result = Vec::new();
+        self.scan(text, |pos| {
+            result.push(pos);
+            true
+        });
+        result
+    }
+
+    /// starting position of the first occurrence of the pattern in `text`.
+    pub fn find(&self, text: &[T]) -> Option<usize> {
+        let mut first = None;
+        self.scan(text, |pos| {
+            first = Some(pos);
+            false
+        });
+        first
+    }
+
+    // report match positions in order until `on_match` returns false.
+    fn scan(&self, text: &[T], mut on_match: impl FnMut(usize) -> bool) {
+        let m = self.pattern.len();
+        if m == 0 {
+            for pos in 0..=text.len() {
+                if !on_match(pos) {
+                    return;
+                }
+            }
+            return;
+        }
+        let mut k = 0;
+        for (i, c) in text.iter().enumerate() {
+            while k > 0 && *c != self.pattern[k] {
+                k = self.pi[k - 1];
+            }
+            if *c == self.pattern[k] {
+                k += 1;
+            }
+            if k == m {
+                if !on_match(i + 1 - m) {
+                    return;
+                }
+                k = self.pi[k - 1];
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_prefix_function() {
+        assert_eq!(prefix_function(b"aabaaab"), vec![0, 1, 0, 1, 2, 2, 3]);
+        assert_eq!(borders(b"abacaba"), vec![3, 1]);
+        assert_eq!(period(b"abcabcab"), 3);
+        assert_eq!(period(b"abcd"), 4);
+        assert!(prefix_function::<u8>(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_find() {
+        let kmp = Kmp::new(&b"aba"[..]);
+        assert_eq!(kmp.find_all(b"abababa"), vec![0, 2, 4]);
+        assert_eq!(kmp.find(b"xxaba"), Some(2));
+        assert_eq!(kmp.find(b"abba"), None);
+        assert_eq!(Kmp::new(Vec::<u8>::new()).find_all(b"ab"), vec![0, 1, 2]);
+        // works on any Eq items, not only bytes.
+        let words = Kmp::new(vec!["to", "be"]);
+        assert_eq!(
+            words.find_all(&["to", "be", "or", "not", "to", "be"]),
+            vec![0, 4]
+        );
+    }
+
+    #[test]
+    fn test_against_naive() {
+        let mut rng = Rng::new(19);
+        for _ in 0..300 {
+            let text: Vec<u8> = (0..rng.index(50)).map(|_| rng.index(2) as u8).collect();
+            let pattern: Vec<u8> = (0..1 + rng.index(5)).map(|_| rng.index(2) as u8).collect();
+            let expected: Vec<usize> = (0..text.len())
+                .filter(|&i| text[i..].starts_with(&pattern))
+                .collect();
+            let kmp = Kmp::new(pattern);
+            assert_eq!(kmp.find_all(&text), expected);
+            assert_eq!(kmp.find(&text), expected.first().copied());
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use std::ops::{Range, RangeBounds};
+
+use crate::resolve_range;
+
+/// palindromic radii of a sequence, computed with manacher's algorithm in O(n).
+///
+/// `odd[i]` is the number of odd-length palindromes centred at `i`, so the
+/// longest one is `s[i + 1 - odd[i]..i + odd[i]]`. `even[i]` is the number of
+/// even-length palindromes centred just before `i`, the longest being
+/// `s[i - even[i]..i + even[i]]`.
+#[derive(Clone, Debug)]
+pub struct Palindromes {
+    odd: Vec<usize>,
+    even: Vec<usize>,
+}
+
+impl Palindromes {
+    pub fn new<T: Eq>(s: &[T]) -> Self {
+        Palindromes {
+            odd: odd_radii(s),
+            even: even_radii(s),
+        }
+    }
+
+    pub fn odd(&self) -> &[usize] {
+        &self.odd
+    }
+
+    pub fn even(&self) -> &[usize] {
+        &self.even
+    }
+
+    /// check in O(1) whether the substring in `range` is a palindrome.
+    pub fn is_palindrome(&self, range: impl RangeBounds<usize>) -> bool {
+        let (start, end) = resolve_range(range, self.odd.len());
+        let len = end - start;
+        if len == 0 {
+            return true;
+        }
+        let mid = start + len / 2;
+        if len % 2 == 1 {
+            self.odd[mid] > len / 2
+        } else {
+            self.even[mid] >= len / 2
+        }
+    }
+
+    /// the leftmost longest palindromic substring.
+    pub fn longest(&self) -> Range<usize> {
+        let mut best = 0..0;
+        for i in 0..self.odd.len() {
+            let (k, e) = (self.odd[i], self.even[i]);
+            if 2 * e > best.len() {
+                best = i - e..i + e;
+            }
+            if 2 * k - 1 > best.len() {
+                best = i + 1 - k..i + k;
+            }
+        }
+        best
+    }
+
+    /// number of non-empty palindromic substrings, counted by position.
+    pub fn count(&self) -> usize {
+        self.odd.iter().sum::<usize>() + self.even.iter().sum::<usize>()
+    }
+}
+
+fn odd_radii<T: Eq>(s: &[T]) -> Vec<usize> {
+    let n = s.len();
+    let mut d = vec![0; n];
+    // s[l..r] is the palindrome reaching furthest right so far.
+    let (mut l, mut r) = (0, 0);
+    for i in 0..n {
+        // start from the radius at the mirror position of i.
+        let mut k = if i < r {
+            d[l + r - 1 - i].min(r - i)
+        } else {
+            1
+        };
+        while i >= k && i + k < n && s[i - k] == s[i + k] {
+            k += 1;
+        }
+        d[i] = k;
+        if i + k > r {
+            l = i + 1 - k;
+            r = i + k;
+        }
+    }
+    d
+}
+
+fn even_radii<T: Eq>(s: &[T]) -> Vec<usize> {
+    let n = s.len();
+    let mut d = vec![0; n];
+    let (mut l, mut r) = (0, 0);
+    for i in 0..n {
+        let mut k = if i < r { d[l + r - i].min(r - i) } else { 0 };
+        while i > k && i + k < n && s[i - k - 1] == s[i + k] {
+            k += 1;
+        }
+        d[i] = k;
+        if i + k > r {
+            l = i - k;
+            r = i + k;
+        }
+    }
+    d
+}
+
+/// the leftmost longest palindromic substring of `s`.
+pub fn longest_palindrome<T: Eq>(s: &[T]) -> Range<usize> {
+    Palindromes::new(s).longest()
+}
+
+/// number of non-empty palindromic substrings of `s`, counted by position.
+pub fn count_palindromes<T: Eq>(s: &[T]) -> usize {
+    Palindromes::new(s).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    fn is_palindrome(s: &[u8]) -> bool {
+        s.iter().eq(s.iter().rev())
+    }
+
+    #[test]
+    fn test_radii() {
+        let p = Palindromes::new(b"abacaba");
+        assert_eq!(p.odd(), &[1, 2, 1, 4, 1, 2, 1]);
+        assert_eq!(p.even(), &[0, 0, 0, 0, 0, 0, 0]);
+        let p = Palindromes::new(b"abba");
+        assert_eq!(p.even(), &[0, 0, 2, 0]);
+        assert_eq!(longest_palindrome(b"forgeeksskeegfor"), 3..13);
+        assert_eq!(count_palindromes(b"aaa"), 6);
+        assert_eq!(longest_palindrome::<u8>(&[]), 0..0);
+    }
+
+    #[test]
+    fn test_against_naive() {
+        let mut rng = Rng::new(22);
+        for _ in 0..300 {
+            let s: Vec<u8> = (0..rng.index(30))
+                .map(|_| b'a' + rng.index(2) as u8)
+                .collect();
+            let p = Palindromes::new(&s);
+            let mut count = 0;
+            let mut longest = 0;
+            for i in 0..=s.len() {
+                for j in i..=s.len() {
+                    let expected = is_palindrome(&s[i..j]);
+                    assert_eq!(p.is_palindrome(i..j), expected);
+                    if expected && j > i {
+                        count += 1;
+                        longest = longest.max(j - i);
+                    }
+                }
+            }
+            assert_eq!(p.count(), count);
+            let best = p.longest();
+            assert_eq!(best.len(), longest);
+            assert!(is_palindrome(&s[best]));
+        }
+    }
+}
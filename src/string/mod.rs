@@ -0,0 +1,9 @@
+pub mod aho_corasick;
+pub mod diff;
+pub mod edit_distance;
+pub mod kmp;
+pub mod manacher;
+pub mod rolling_hash;
+pub mod suffix_array;
+pub mod suffix_automaton;
+pub mod z;
@@ -0,0 +1,164 @@
+use std::ops::RangeBounds;
+
+use crate::resolve_range;
+
+/// the mersenne prime 2^61 - 1, the default modulus.
+pub const MOD_61: u64 = (1 << 61) - 1;
+/// moduli of the two components of [`DoubleRollingHash`].
+pub const DOUBLE_MODULI: (u64, u64) = (1_000_000_007, 998_244_353);
+
+fn mul_mod(a: u64, b: u64, modulus: u64) -> u64 {
+    (a as u128 * b as u128 % modulus as u128) as u64
+}
+
+/// polynomial hashes of every prefix of a sequence, so that the hash of any
+/// substring is available in O(1).
+///
+/// the hash of `s` is `sum (s[i] + 1) * base^(len - 1 - i) mod modulus`; the
+/// base should be drawn at random from `256..modulus` to make collisions
+/// unlikely for any fixed input.
+#[derive(Clone, Debug)]
+pub struct RollingHash {
+    base: u64,
+    modulus: u64,
+    prefix: Vec<u64>,
+    powers: Vec<u64>,
+}
+
+impl RollingHash {
+    /// hash `s` modulo 2^61 - 1.
+    pub fn new<T: Copy + Into<u64>>(s: &[T], base: u64) -> Self {
+        RollingHash::with_modulus(s, base, MOD_61)
+    }
+
+    /// hash `s` modulo `modulus`, which should be a prime below 2^63.
+    pub fn with_modulus<T: Copy + Into<u64>>(s: &[T], base: u64, modulus: u64) -> Self {
+        assert!(0 < base && base < modulus, "base must lie in 1..modulus");
+        let mut prefix = Vec::with_capacity(s.len() + 1);
+        let mut powers = Vec::with_capacity(s.len() + 1);
+        prefix.push(0);
+        powers.push(1);
+        for (i, &c) in s.iter().enumerate() {
+            let symbol = c.into() % modulus + 1;
+            prefix.push((mul_mod(prefix[i], base, modulus) + symbol) % modulus);
+            powers.push(mul_mod(powers[i], base, modulus));
+        }
+        RollingHash {
+            base,
+            modulus,
+            prefix,
+            powers,
+        }
+    }
+
+    /// length of the hashed sequence.
+    pub fn len(&self) -> usize {
+        self.prefix.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// hash of the substring in `range`.
+    pub fn hash(&self, range: impl RangeBounds<usize>) -> u64 {
+        let (start, end) = resolve_range(range, self.len());
+        let m = self.modulus;
+        let shifted = mul_mod(self.prefix[start], self.powers[end - start], m);
+        (self.prefix[end] + m - shifted) % m
+    }
+
+    /// hash of an arbitrary sequence under the same base and modulus, for
+    /// comparison against [`RollingHash::hash`].
+    pub fn hash_of<T: Copy + Into<u64>>(&self, s: &[T]) -> u64 {
+        let m = self.modulus;
+        s.iter()
+            .fold(0, |h, &c| (mul_mod(h, self.base, m) + c.into() % m + 1) % m)
+    }
+}
+
+/// two independent rolling hashes over different moduli, for when the
+/// ~2^-30 collision chance of a single 30-bit hash is too high.
+#[derive(Clone, Debug)]
+pub struct DoubleRollingHash {
+    first: RollingHash,
+    second: RollingHash,
+}
+
+impl DoubleRollingHash {
+    pub fn new<T: Copy + Into<u64>>(s: &[T], bases: (u64, u64)) -> Self {
+        DoubleRollingHash {
+            first: RollingHash::with_modulus(s, bases.0, DOUBLE_MODULI.0),
+            second: RollingHash::with_modulus(s, bases.1, DOUBLE_MODULI.1),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.first.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.first.is_empty()
+    }
+
+    /// hash pair of the substring in `range`.
+    pub fn hash(&self, range: impl RangeBounds<usize> + Clone) -> (u64, u64) {
+        (self.first.hash(range.clone()), self.second.hash(range))
+    }
+
+    pub fn hash_of<T: Copy + Into<u64>>(&self, s: &[T]) -> (u64, u64) {
+        (self.first.hash_of(s), self.second.hash_of(s))
+    }
+}
+
+/// rabin-karp: starting positions of every occurrence of `pattern` in
+/// `text`, overlapping ones included. windows whose hash matches are
+/// checked directly, so the result is exact.
+pub fn rabin_karp<T: Copy + Into<u64> + Eq>(pattern: &[T], text: &[T], base: u64) -> Vec<usize> {
+    let m = pattern.len();
+    if m > text.len() {
+        return Vec::new();
+    }
+    let hashes = RollingHash::new(text, base);
+    let target = hashes.hash_of(pattern);
+    (0..=text.len() - m)
+        .filter(|&i| hashes.hash(i..i + m) == target && &text[i..i + m] == pattern)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_substring_hashes() {
+        let mut rng = Rng::new(21);
+        let text: Vec<u8> = (0..200).map(|_| b'a' + rng.index(2) as u8).collect();
+        let single = RollingHash::new(&text, 256 + rng.below(MOD_61 - 256));
+        let double = DoubleRollingHash::new(&text, (131, 137));
+        for _ in 0..2000 {
+            let (a, b) = (rng.index(195), rng.index(195));
+            let len = rng.index(6);
+            let equal = text[a..a + len] == text[b..b + len];
+            assert_eq!(single.hash(a..a + len) == single.hash(b..b + len), equal);
+            assert_eq!(double.hash(a..a + len) == double.hash(b..b + len), equal);
+            assert_eq!(single.hash(a..a + len), single.hash_of(&text[a..a + len]));
+            assert_eq!(double.hash(a..a + len), double.hash_of(&text[a..a + len]));
+        }
+        assert_eq!(single.hash(..), single.hash_of(&text));
+        // leading zero symbols still change the hash.
+        let zeros = RollingHash::new(&[0u8, 0, 1], 131);
+        assert_ne!(zeros.hash(1..), zeros.hash(2..));
+    }
+
+    #[test]
+    fn test_rabin_karp() {
+        assert_eq!(rabin_karp(b"ana", b"bananas", 131), vec![1, 3]);
+        assert_eq!(rabin_karp(b"", b"ab", 131), vec![0, 1, 2]);
+        assert!(rabin_karp(b"abc", b"ab", 131).is_empty());
+        let text: Vec<u32> = "mississippi".chars().map(u32::from).collect();
+        let pattern: Vec<u32> = "issi".chars().map(u32::from).collect();
+        assert_eq!(rabin_karp(&pattern, &text, 1_000_003), vec![1, 4]);
+    }
+}
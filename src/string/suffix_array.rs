@@ -0,0 +1,297 @@
+use std::ops::Range;
+
+// marks an empty slot while inducing.
+const NONE: usize = usize::MAX;
+
+/// suffix array of `text`: the starting positions of its suffixes in
+/// lexicographic order, built with sa-is in O(n).
+pub fn suffix_array(text: &[u8]) -> Vec<usize> {
+    let s: Vec<usize> = text.iter().map(|&b| b as usize).collect();
+    sa_is(&s, u8::MAX as usize)
+}
+
+/// kasai's lcp array for `text` and its suffix array `sa`: `lcp[i]` is the
+/// length of the longest common prefix of suffixes `sa[i - 1]` and `sa[i]`,
+/// and `lcp[0]` is 0.
+pub fn lcp_array(text: &[u8], sa: &[usize]) -> Vec<usize> {
+    let n = text.len();
+    let mut rank = vec![0; n];
+    for (i, &p) in sa.iter().enumerate() {
+        rank[p] = i;
+    }
+    let mut lcp = vec![0; n];
+    let mut h = 0;
+    for i in 0..n {
+        if rank[i] == 0 {
+            h = 0;
+            continue;
+        }
+        let j = sa[rank[i] - 1];
+        while i + h < n && j + h < n && text[i + h] == text[j + h] {
+            h += 1;
+        }
+        lcp[rank[i]] = h;
+        h = h.saturating_sub(1);
+    }
+    lcp
+}
+
+// sa-is over symbols in `0..=upper`.
+fn sa_is(s: &[usize], upper: usize) -> Vec<usize> {
+    let n = s.len();
+    match n {
+        0 => return Vec::new(),
+        1 => return vec![0],
+        2 => return if s[0] < s[1] { vec![0, 1] } else { vec![1, 0] },
+        _ => {}
+    }
+    // ls[i]: suffix i is s-type, i.e. smaller than suffix i + 1.
+    let mut ls = vec![false; n];
+    for i in (0..n - 1).rev() {
+        ls[i] = if s[i] == s[i + 1] {
+            ls[i + 1]
+        } else {
+            s[i] < s[i + 1]
+        };
+    }
+    // bucket starts for s-type (sum_s) and l-type (sum_l) suffixes.
+    let mut sum_l = vec![0; upper + 1];
+    let mut sum_s = vec![0; upper + 1];
+    for i in 0..n {
+        if ls[i] {
+            sum_l[s[i] + 1] += 1;
+        } else {
+            sum_s[s[i]] += 1;
+        }
+    }
+    for c in 0..=upper {
+        sum_s[c] += sum_l[c];
+        if c < upper {
+            sum_l[c + 1] += sum_s[c];
+        }
+    }
+
+    let mut lms_map = vec![NONE; n];
+    let mut lms = Vec::new();
+    for i in 1..n {
+        if !ls[i - 1] && ls[i] {
+            lms_map[i] = lms.len();
+            lms.push(i);
+        }
+    }
+    let buckets = Buckets {
+        s,
+        ls: &ls,
+        sum_l: &sum_l,
+        sum_s: &sum_s,
+    };
+    let mut sa = vec![NONE; n];
+    buckets.induce(&lms, &mut sa);
+
+    let m = lms.len();
+    if m > 0 {
+        let mut sorted_lms: Vec<usize> =
+            sa.iter().copied().filter(|&v| lms_map[v] != NONE).collect();
+        // name the lms substrings, equal substrings getting equal names.
+        let mut rec_s = vec![0; m];
+        let mut rec_upper = 0;
+        for i in 1..m {
+            let (mut l, mut r) = (sorted_lms[i - 1], sorted_lms[i]);
+            let end_l = lms.get(lms_map[l] + 1).copied().unwrap_or(n);
+            let end_r = lms.get(lms_map[r] + 1).copied().unwrap_or(n);
+            let mut same = end_l - l == end_r - r;
+            if same {
+                while l < end_l && s[l] == s[r] {
+                    l += 1;
+                    r += 1;
+                }
+                same = l < n && r < n && s[l] == s[r];
+            }
+            if !same {
+                rec_upper += 1;
+            }
+            rec_s[lms_map[sorted_lms[i]]] = rec_upper;
+        }
+        let rec_sa = sa_is(&rec_s, rec_upper);
+        for (slot, &r) in sorted_lms.iter_mut().zip(&rec_sa) {
+            *slot = lms[r];
+        }
+        buckets.induce(&sorted_lms, &mut sa);
+    }
+    sa
+}
+
+struct Buckets<'a> {
+    s: &'a [usize],
+    ls: &'a [bool],
+    sum_l: &'a [usize],
+    sum_s: &'a [usize],
+}
+
+impl Buckets<'_> {
+    // induce the order of all suffixes from the given order of lms suffixes.
+    fn induce(&self, lms: &[usize], sa: &mut [usize]) {
+        let (s, ls) = (self.s, self.ls);
+        let n = s.len();
+        for slot in sa.iter_mut() {
+            *slot = NONE;
+        }
+        let mut buf = self.sum_s.to_vec();
+        for &d in lms {
+            sa[buf[s[d]]] = d;
+            buf[s[d]] += 1;
+        }
+        let mut buf = self.sum_l.to_vec();
+        sa[buf[s[n - 1]]] = n - 1;
+        buf[s[n - 1]] += 1;
+        for i in 0..n {
+            let v = sa[i];
+            if v != NONE && v >= 1 && !ls[v - 1] {
+                sa[buf[s[v - 1]]] = v - 1;
+                buf[s[v - 1]] += 1;
+            }
+        }
+        let mut buf = self.sum_l.to_vec();
+        for i in (0..n).rev() {
+            let v = sa[i];
+            if v != NONE && v >= 1 && ls[v - 1] {
+                buf[s[v - 1] + 1] -= 1;
+                sa[buf[s[v - 1] + 1]] = v - 1;
+            }
+        }
+    }
+}
+
+/// a text together with its suffix and lcp arrays, answering substring
+/// queries by binary search over the sorted suffixes.
+#[derive(Clone, Debug)]
+pub struct SuffixArray {
+    text: Vec<u8>,
+    sa: Vec<usize>,
+    lcp: Vec<usize>,
+}
+
+impl SuffixArray {
+    /// build the suffix and lcp arrays of `text` in O(n).
+    pub fn new(text: impl Into<Vec<u8>>) -> Self {
+        let text = text.into();
+        let sa = suffix_array(&text);
+        let lcp = lcp_array(&text, &sa);
+        SuffixArray { text, sa, lcp }
+    }
+
+    /// length of the text.
+    pub fn len(&self) -> usize {
+        self.text.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    pub fn text(&self) -> &[u8] {
+        &self.text
+    }
+
+    /// suffix starting positions in lexicographic order.
+    pub fn suffixes(&self) -> &[usize] {
+        &self.sa
+    }
+
+    /// the lcp array, see [`lcp_array`].
+    pub fn lcp(&self) -> &[usize] {
+        &self.lcp
+    }
+
+    /// the block of `suffixes()` whose suffixes start with `pattern`.
+    pub fn find(&self, pattern: &[u8]) -> Range<usize> {
+        let text = &self.text;
+        let start = self.sa.partition_point(|&p| &text[p..] < pattern);
+        let end = self.sa.partition_point(|&p| {
+            let suffix = &text[p..];
+            &suffix[..suffix.len().min(pattern.len())] <= pattern
+        });
+        start..end.max(start)
+    }
+
+    pub fn contains(&self, pattern: &[u8]) -> bool {
+        !self.find(pattern).is_empty()
+    }
+
+    /// number of occurrences of `pattern`, overlapping ones included.
+    pub fn count(&self, pattern: &[u8]) -> usize {
+        self.find(pattern).len()
+    }
+
+    /// sorted starting positions of every occurrence of `pattern`.
+    pub fn occurrences(&self, pattern: &[u8]) -> Vec<usize> {
+        let mut positions = self.sa[self.find(pattern)].to_vec();
+        positions.sort_unstable();
+        positions
+    }
+
+    /// number of distinct non-empty substrings of the text.
+    pub fn distinct_substrings(&self) -> usize {
+        let n = self.len();
+        n * (n + 1) / 2 - self.lcp.iter().sum::<usize>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+    use std::collections::HashSet;
+
+    fn naive(text: &[u8]) -> Vec<usize> {
+        let mut sa: Vec<usize> = (0..text.len()).collect();
+        sa.sort_by_key(|&p| &text[p..]);
+        sa
+    }
+
+    #[test]
+    fn test_banana() {
+        let sa = SuffixArray::new("banana");
+        assert_eq!(sa.suffixes(), &[5, 3, 1, 0, 4, 2]);
+        assert_eq!(sa.lcp(), &[0, 1, 3, 0, 0, 2]);
+        assert_eq!(sa.occurrences(b"ana"), vec![1, 3]);
+        assert_eq!(sa.count(b"a"), 3);
+        assert_eq!(sa.count(b""), 6);
+        assert!(!sa.contains(b"nab"));
+        assert!(!sa.contains(b"bananas"));
+        assert_eq!(sa.distinct_substrings(), 15);
+    }
+
+    #[test]
+    fn test_against_naive() {
+        let mut rng = Rng::new(16);
+        for _ in 0..300 {
+            let len = rng.index(60);
+            let sigma = 1 + rng.index(4) as u8;
+            let text: Vec<u8> = (0..len)
+                .map(|_| b'a' + rng.index(sigma as usize) as u8)
+                .collect();
+            let sa = SuffixArray::new(text.clone());
+            assert_eq!(sa.suffixes(), naive(&text).as_slice());
+            for (i, &l) in sa.lcp().iter().enumerate().skip(1) {
+                let (a, b) = (&text[sa.suffixes()[i - 1]..], &text[sa.suffixes()[i]..]);
+                assert_eq!(l, a.iter().zip(b).take_while(|(x, y)| x == y).count());
+            }
+            let mut distinct = HashSet::new();
+            for i in 0..len {
+                for j in i + 1..=len {
+                    distinct.insert(&text[i..j]);
+                }
+            }
+            assert_eq!(sa.distinct_substrings(), distinct.len());
+            let plen = rng.index(4);
+            let pattern: Vec<u8> = (0..plen)
+                .map(|_| b'a' + rng.index(sigma as usize) as u8)
+                .collect();
+            let expected: Vec<usize> = (0..len)
+                .filter(|&i| text[i..].starts_with(&pattern))
+                .collect();
+            assert_eq!(sa.occurrences(&pattern), expected);
+        }
+    }
+}
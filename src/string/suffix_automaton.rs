@@ -0,0 +1,194 @@
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+const NIL: usize = usize::MAX;
+
+#[derive(Clone, Debug)]
+struct State {
+    // length of the longest string in this state.
+    len: usize,
+    link: usize,
+    next: BTreeMap<u8, usize>,
+    // size of the endpos set, i.e. number of occurrences.
+    count: usize,
+}
+
+/// the minimal automaton accepting every substring of a text, with at most
+/// `2n - 1` states. built online in O(n log σ).
+#[derive(Clone, Debug)]
+pub struct SuffixAutomaton {
+    states: Vec<State>,
+    last: usize,
+}
+
+impl SuffixAutomaton {
+    /// build the automaton of `text`.
+    pub fn new(text: &[u8]) -> Self {
+        let mut automaton = SuffixAutomaton {
+            states: vec![State {
+                len: 0,
+                link: NIL,
+                next: BTreeMap::new(),
+                count: 0,
+            }],
+            last: 0,
+        };
+        for &c in text {
+            automaton.extend(c);
+        }
+        automaton.count_occurrences();
+        automaton
+    }
+
+    fn extend(&mut self, c: u8) {
+        let cur = self.states.len();
+        self.states.push(State {
+            len: self.states[self.last].len + 1,
+            link: 0,
+            next: BTreeMap::new(),
+            count: 1,
+        });
+        let mut p = self.last;
+        while p != NIL && !self.states[p].next.contains_key(&c) {
+            self.states[p].next.insert(c, cur);
+            p = self.states[p].link;
+        }
+        if p != NIL {
+            let q = self.states[p].next[&c];
+            if self.states[p].len + 1 == self.states[q].len {
+                self.states[cur].link = q;
+            } else {
+                // split q so that the transition from p stays solid.
+                let clone = self.states.len();
+                let mut state = self.states[q].clone();
+                state.len = self.states[p].len + 1;
+                state.count = 0;
+                self.states.push(state);
+                while p != NIL && self.states[p].next.get(&c) == Some(&q) {
+                    self.states[p].next.insert(c, clone);
+                    p = self.states[p].link;
+                }
+                self.states[q].link = clone;
+                self.states[cur].link = clone;
+            }
+        }
+        self.last = cur;
+    }
+
+    fn count_occurrences(&mut self) {
+        // children in the suffix-link tree are longer than their parents.
+        let mut order: Vec<usize> = (1..self.states.len()).collect();
+        order.sort_unstable_by_key(|&v| std::cmp::Reverse(self.states[v].len));
+        for v in order {
+            let link = self.states[v].link;
+            self.states[link].count += self.states[v].count;
+        }
+    }
+
+    /// number of states, including the initial one.
+    pub fn state_count(&self) -> usize {
+        self.states.len()
+    }
+
+    fn walk(&self, pattern: &[u8]) -> Option<usize> {
+        let mut v = 0;
+        for c in pattern {
+            v = *self.states[v].next.get(c)?;
+        }
+        Some(v)
+    }
+
+    /// check whether `pattern` is a substring of the text.
+    pub fn contains(&self, pattern: &[u8]) -> bool {
+        self.walk(pattern).is_some()
+    }
+
+    /// number of occurrences of a non-empty `pattern` in the text, overlapping
+    /// ones included.
+    pub fn occurrences(&self, pattern: &[u8]) -> usize {
+        assert!(!pattern.is_empty(), "pattern must not be empty");
+        self.walk(pattern).map_or(0, |v| self.states[v].count)
+    }
+
+    /// number of distinct non-empty substrings of the text.
+    pub fn distinct_substrings(&self) -> usize {
+        self.states[1..]
+            .iter()
+            .map(|s| s.len - self.states[s.link].len)
+            .sum()
+    }
+
+    /// the longest substring shared by the text and `other`, as a range into
+    /// `other`. the leftmost one is returned on ties.
+    pub fn longest_common_substring(&self, other: &[u8]) -> Range<usize> {
+        let (mut v, mut len) = (0, 0);
+        let mut best = 0..0;
+        for (i, c) in other.iter().enumerate() {
+            while v != 0 && !self.states[v].next.contains_key(c) {
+                v = self.states[v].link;
+                len = self.states[v].len;
+            }
+            if let Some(&next) = self.states[v].next.get(c) {
+                v = next;
+                len += 1;
+            }
+            if len > best.len() {
+                best = i + 1 - len..i + 1;
+            }
+        }
+        best
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_queries() {
+        let sam = SuffixAutomaton::new(b"abracadabra");
+        assert!(sam.contains(b"cada"));
+        assert!(sam.contains(b""));
+        assert!(!sam.contains(b"abrac d"));
+        assert_eq!(sam.occurrences(b"abra"), 2);
+        assert_eq!(sam.occurrences(b"a"), 5);
+        assert_eq!(sam.occurrences(b"z"), 0);
+        let other = b"xxcadabrxx";
+        assert_eq!(&other[sam.longest_common_substring(other)], b"cadabr");
+        assert!(sam.longest_common_substring(b"zzz").is_empty());
+    }
+
+    #[test]
+    fn test_against_naive() {
+        let mut rng = Rng::new(17);
+        for _ in 0..200 {
+            let len = 1 + rng.index(40);
+            let text: Vec<u8> = (0..len).map(|_| b'a' + rng.index(3) as u8).collect();
+            let sam = SuffixAutomaton::new(&text);
+            assert!(sam.state_count() <= (2 * len).max(2));
+            let mut distinct = HashSet::new();
+            for i in 0..len {
+                for j in i + 1..=len {
+                    distinct.insert(&text[i..j]);
+                }
+            }
+            assert_eq!(sam.distinct_substrings(), distinct.len());
+            for pattern in &distinct {
+                let expected = (0..len).filter(|&i| text[i..].starts_with(pattern)).count();
+                assert_eq!(sam.occurrences(pattern), expected);
+            }
+            let other: Vec<u8> = (0..20).map(|_| b'a' + rng.index(4) as u8).collect();
+            let lcs = sam.longest_common_substring(&other);
+            assert!(distinct.contains(&other[lcs.clone()]) || lcs.is_empty());
+            let longest = (0..other.len())
+                .flat_map(|i| (i + 1..=other.len()).map(move |j| (i, j)))
+                .filter(|&(i, j)| distinct.contains(&other[i..j]))
+                .map(|(i, j)| j - i)
+                .max()
+                .unwrap_or(0);
+            assert_eq!(lcs.len(), longest);
+        }
+    }
+}
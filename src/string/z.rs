@@ -0,0 +1,96 @@
+/// z-array of `s`: `z[i]` is the length of the longest common prefix of `s`
+/// and `s[i..]`, with `z[0] = s.len()`. runs in O(n).
+pub fn z_array<T: Eq>(s: &[T]) -> Vec<usize> {
+    let n = s.len();
+    let mut z = vec![0; n];
+    if n == 0 {
+        return z;
+    }
+    z[0] = n;
+    // s[l..r] is the rightmost segment found so far that matches a prefix.
+    let (mut l, mut r) = (0, 0);
+    for i in 1..n {
+        let mut k = if i < r { z[i - l].min(r - i) } else { 0 };
+        while i + k < n && s[k] == s[i + k] {
+            k += 1;
+        }
+        z[i] = k;
+        if i + k > r {
+            l = i;
+            r = i + k;
+        }
+    }
+    z
+}
+
+/// for every position of `text`, the length of the longest prefix of
+/// `pattern` starting there.
+pub fn prefix_matches<T: Eq>(pattern: &[T], text: &[T]) -> Vec<usize> {
+    // z over pattern ++ text without building the concatenation.
+    let z = z_array(pattern);
+    let (m, n) = (pattern.len(), text.len());
+    let mut result = vec![0; n];
+    let (mut l, mut r) = (0, 0);
+    for i in 0..n {
+        let mut k = if i < r { z[i - l].min(r - i) } else { 0 };
+        while k < m && i + k < n && pattern[k] == text[i + k] {
+            k += 1;
+        }
+        result[i] = k;
+        if i + k > r {
+            l = i;
+            r = i + k;
+        }
+    }
+    result
+}
+
+/// starting positions of every occurrence of `pattern` in `text`,
+/// overlapping ones included.
+pub fn find_all<T: Eq>(pattern: &[T], text: &[T]) -> Vec<usize> {
+    if pattern.is_empty() {
+        return (0..=text.len()).collect();
+    }
+    prefix_matches(pattern, text)
+        .into_iter()
+        .enumerate()
+        .filter(|&(_, k)| k == pattern.len())
+        .map(|(i, _)| i)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    fn lcp(a: &[u8], b: &[u8]) -> usize {
+        a.iter().zip(b).take_while(|(x, y)| x == y).count()
+    }
+
+    #[test]
+    fn test_z_array() {
+        assert_eq!(z_array(b"aabxaab"), vec![7, 1, 0, 0, 3, 1, 0]);
+        assert_eq!(z_array(b"aaaa"), vec![4, 3, 2, 1]);
+        assert!(z_array::<u8>(&[]).is_empty());
+        assert_eq!(find_all(b"aa", b"aaaba"), vec![0, 1]);
+        assert_eq!(find_all(b"", b"ab"), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_against_naive() {
+        let mut rng = Rng::new(20);
+        for _ in 0..300 {
+            let text: Vec<u8> = (0..rng.index(50)).map(|_| rng.index(2) as u8).collect();
+            let pattern: Vec<u8> = (0..rng.index(6)).map(|_| rng.index(2) as u8).collect();
+            let z = z_array(&text);
+            for i in 0..text.len() {
+                assert_eq!(z[i], lcp(&text, &text[i..]));
+            }
+            let matches = prefix_matches(&pattern, &text);
+            for i in 0..text.len() {
+                assert_eq!(matches[i], lcp(&pattern, &text[i..]));
+            }
+        }
+    }
+}
@@ -0,0 +1,301 @@
+use std::borrow::Cow;
+use std::f64::consts::PI;
+
+/// compression used by [`TDigest::new`].
+pub const DEFAULT_COMPRESSION: f64 = 100.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Centroid {
+    mean: f64,
+    weight: f64,
+}
+
+/// t-digest: a compact summary of a stream of numbers answering quantile and
+/// cdf queries, most accurately near the tails (e.g. latency p99 or p999).
+///
+/// it keeps at most about `compression` centroids; each is a cluster of
+/// nearby values, and clusters are kept small near `q = 0` and `q = 1`.
+///
+/// inserted values wait in a buffer of up to `5 * compression` before they
+/// are merged. a query on a digest with pending values merges a copy of
+/// them, in O(b log b), and leaves the buffer as it is; call
+/// [`TDigest::flush`] before a batch of queries so that each costs only
+/// O(compression).
+#[derive(Clone, Debug)]
+pub struct TDigest {
+    compression: f64,
+    centroids: Vec<Centroid>,
+    // unmerged values, folded into the centroids when full.
+    buffer: Vec<Centroid>,
+    total: f64,
+    min: f64,
+    max: f64,
+}
+
+impl TDigest {
+    pub fn new() -> Self {
+        TDigest::with_compression(DEFAULT_COMPRESSION)
+    }
+
+    /// create a digest with the given compression; higher values trade
+    /// memory for accuracy.
+    pub fn with_compression(compression: f64) -> Self {
+        assert!(compression >= 10.0, "compression must be at least 10");
+        TDigest {
+            compression,
+            centroids: Vec::new(),
+            buffer: Vec::new(),
+            total: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    /// total weight of the values inserted.
+    pub fn count(&self) -> f64 {
+        self.total
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total == 0.0
+    }
+
+    pub fn min(&self) -> Option<f64> {
+        Some(self.min).filter(|_| !self.is_empty())
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        Some(self.max).filter(|_| !self.is_empty())
+    }
+
+    pub fn insert(&mut self, value: f64) {
+        self.insert_weighted(value, 1.0);
+    }
+
+    /// insert `value` with a positive `weight`, e.g. a pre-aggregated count.
+    pub fn insert_weighted(&mut self, value: f64, weight: f64) {
+        assert!(!value.is_nan(), "value must not be nan");
+        assert!(weight > 0.0, "weight must be positive");
+        self.buffer.push(Centroid {
+            mean: value,
+            weight,
+        });
+        self.total += weight;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        if self.buffer.len() >= 5 * self.compression as usize {
+            self.flush();
+        }
+    }
+
+    /// fold in every value of `other`.
+    pub fn merge(&mut self, other: &TDigest) {
+        if other.is_empty() {
+            return;
+        }
+        self.buffer
+            .extend(other.centroids.iter().chain(&other.buffer));
+        self.total += other.total;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.flush();
+    }
+
+    /// number of centroids once pending values are merged.
+    pub fn centroid_count(&self) -> usize {
+        self.merged().len()
+    }
+
+    /// merge the pending values into the centroids.
+    pub fn flush(&mut self) {
+        if !self.buffer.is_empty() {
+            self.centroids = self.merged().into_owned();
+            self.buffer.clear();
+        }
+    }
+
+    fn merged(&self) -> Cow<'_, [Centroid]> {
+        if self.buffer.is_empty() {
+            return Cow::Borrowed(&self.centroids);
+        }
+        let mut all: Vec<Centroid> = self.centroids.iter().chain(&self.buffer).copied().collect();
+        all.sort_by(|a, b| a.mean.partial_cmp(&b.mean).unwrap());
+        // scale function k1: centroids may span one unit of k.
+        let delta = self.compression;
+        let k = |q: f64| delta / (2.0 * PI) * (2.0 * q - 1.0).asin();
+        let k_inv = |k: f64| ((k * 2.0 * PI / delta).sin() + 1.0) / 2.0;
+        let mut result = Vec::new();
+        let mut before = 0.0;
+        let mut limit = k_inv(k(0.0) + 1.0);
+        let mut current = all[0];
+        for &next in &all[1..] {
+            if (before + current.weight + next.weight) / self.total <= limit {
+                let weight = current.weight + next.weight;
+                current.mean += (next.mean - current.mean) * next.weight / weight;
+                current.weight = weight;
+            } else {
+                before += current.weight;
+                result.push(current);
+                limit = k_inv(k(before / self.total) + 1.0);
+                current = next;
+            }
+        }
+        result.push(current);
+        Cow::Owned(result)
+    }
+
+    // the piecewise-linear cdf through (min, 0), each centroid's mean at its
+    // midpoint weight, and (max, total).
+    fn knots(&self) -> Vec<(f64, f64)> {
+        let mut knots = vec![(self.min, 0.0)];
+        let mut before = 0.0;
+        for c in self.merged().iter() {
+            knots.push((c.mean, before + c.weight / 2.0));
+            before += c.weight;
+        }
+        knots.push((self.max, self.total));
+        knots
+    }
+
+    /// estimated value below which a fraction `q` of the weight lies.
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        if self.is_empty() {
+            return None;
+        }
+        let target = q.clamp(0.0, 1.0) * self.total;
+        let knots = self.knots();
+        let i = knots
+            .partition_point(|&(_, t)| t <= target)
+            .clamp(1, knots.len() - 1);
+        let ((x0, t0), (x1, t1)) = (knots[i - 1], knots[i]);
+        if t1 <= t0 {
+            return Some(x1);
+        }
+        Some(x0 + (x1 - x0) * (target - t0) / (t1 - t0))
+    }
+
+    /// estimated fraction of the weight at or below `x`.
+    pub fn cdf(&self, x: f64) -> Option<f64> {
+        if self.is_empty() {
+            return None;
+        }
+        if x < self.min {
+            return Some(0.0);
+        }
+        if x >= self.max {
+            return Some(1.0);
+        }
+        let knots = self.knots();
+        let i = knots
+            .partition_point(|&(v, _)| v <= x)
+            .clamp(1, knots.len() - 1);
+        let ((x0, t0), (x1, t1)) = (knots[i - 1], knots[i]);
+        let t = if x1 <= x0 {
+            t1
+        } else {
+            t0 + (t1 - t0) * (x - x0) / (x1 - x0)
+        };
+        Some(t / self.total)
+    }
+}
+
+impl Default for TDigest {
+    fn default() -> Self {
+        TDigest::new()
+    }
+}
+
+impl std::iter::FromIterator<f64> for TDigest {
+    fn from_iter<I: IntoIterator<Item = f64>>(iter: I) -> Self {
+        let mut digest = TDigest::new();
+        for value in iter {
+            digest.insert(value);
+        }
+        digest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_uniform_quantiles() {
+        let mut rng = Rng::new(30);
+        let mut values: Vec<f64> = (0..100_000).map(f64::from).collect();
+        rng.shuffle(&mut values);
+        let digest: TDigest = values.iter().copied().collect();
+        assert!(digest.centroid_count() <= 100);
+        assert_eq!(digest.quantile(0.0), Some(0.0));
+        assert_eq!(digest.quantile(1.0), Some(99_999.0));
+        for &q in &[0.001, 0.01, 0.25, 0.5, 0.75, 0.99, 0.999] {
+            let estimate = digest.quantile(q).unwrap();
+            // absolute rank error, which shrinks towards the tails.
+            let allowed = 1000.0 * (q * (1.0 - q)).sqrt();
+            assert!(
+                (estimate - q * 100_000.0).abs() < allowed,
+                "q={} got {}",
+                q,
+                estimate
+            );
+            let back = digest.cdf(estimate).unwrap();
+            assert!((back - q).abs() < 1e-6 + 0.01 * (q * (1.0 - q)).sqrt());
+        }
+        assert_eq!(digest.cdf(-1.0), Some(0.0));
+        assert_eq!(digest.cdf(1e9), Some(1.0));
+        assert_eq!(TDigest::new().quantile(0.5), None);
+    }
+
+    #[test]
+    fn test_skewed_and_merge() {
+        let mut rng = Rng::new(31);
+        // exponential latencies with mean 10ms.
+        let samples: Vec<f64> = (0..50_000)
+            .map(|_| -10.0 * (1.0 - rng.next_f64()).ln())
+            .collect();
+        let mut a = TDigest::new();
+        let mut b = TDigest::new();
+        for (i, &s) in samples.iter().enumerate() {
+            if i % 2 == 0 {
+                a.insert(s);
+            } else {
+                b.insert(s);
+            }
+        }
+        a.merge(&b);
+        assert_eq!(a.count(), 50_000.0);
+        let mut sorted = samples.clone();
+        sorted.sort_by(|x, y| x.partial_cmp(y).unwrap());
+        for &q in &[0.5, 0.9, 0.99] {
+            let exact = sorted[(q * 50_000.0) as usize];
+            let estimate = a.quantile(q).unwrap();
+            assert!((estimate - exact).abs() / exact < 0.02, "q={}", q);
+        }
+        let mut weighted = TDigest::new();
+        weighted.insert_weighted(1.0, 3.0);
+        weighted.insert(5.0);
+        assert_eq!(weighted.count(), 4.0);
+        assert_eq!(weighted.min(), Some(1.0));
+        assert_eq!(weighted.max(), Some(5.0));
+    }
+
+    #[test]
+    fn test_flush() {
+        let mut rng = Rng::new(120);
+        let mut digest = TDigest::with_compression(20.0);
+        for _ in 0..(5 * 20 + 37) {
+            digest.insert(rng.next_f64());
+        }
+        assert!(!digest.buffer.is_empty());
+        let before: Vec<_> = (0..=10).map(|i| digest.quantile(i as f64 / 10.0)).collect();
+        let cdf = digest.cdf(0.3);
+        digest.flush();
+        assert!(digest.buffer.is_empty());
+        let after: Vec<_> = (0..=10).map(|i| digest.quantile(i as f64 / 10.0)).collect();
+        assert_eq!(before, after);
+        assert_eq!(digest.cdf(0.3), cdf);
+        digest.flush();
+        assert_eq!(digest.count(), 137.0);
+    }
+}
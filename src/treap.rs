@@ -0,0 +1,572 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::rng::Rng;
+use crate::{OrderStatistic, OrderedMap};
+
+type Link<K, V> = Option<Box<Node<K, V>>>;
+
+struct Node<K, V> {
+    key: K,
+    value: V,
+    priority: u64,
+    size: usize,
+    left: Link<K, V>,
+    right: Link<K, V>,
+}
+
+impl<K, V> Node<K, V> {
+    fn new(key: K, value: V, priority: u64) -> Box<Node<K, V>> {
+        Box::new(Node {
+            key,
+            value,
+            priority,
+            size: 1,
+            left: None,
+            right: None,
+        })
+    }
+
+    fn update(&mut self) {
+        self.size = 1 + size(&self.left) + size(&self.right);
+    }
+}
+
+fn size<K, V>(link: &Link<K, V>) -> usize {
+    link.as_ref().map_or(0, |node| node.size)
+}
+
+// all keys of `left` must be smaller than all keys of `right`.
+fn merge<K, V>(left: Link<K, V>, right: Link<K, V>) -> Link<K, V> {
+    match (left, right) {
+        (None, right) => right,
+        (left, None) => left,
+        (Some(mut l), Some(mut r)) => {
+            if l.priority > r.priority {
+                l.right = merge(l.right.take(), Some(r));
+                l.update();
+                Some(l)
+            } else {
+                r.left = merge(Some(l), r.left.take());
+                r.update();
+                Some(r)
+            }
+        }
+    }
+}
+
+// split into keys `< key` and keys `>= key`.
+fn split<K: Ord, V>(link: Link<K, V>, key: &K) -> (Link<K, V>, Link<K, V>) {
+    match link {
+        None => (None, None),
+        Some(mut node) => {
+            if node.key < *key {
+                let (l, r) = split(node.right.take(), key);
+                node.right = l;
+                node.update();
+                (Some(node), r)
+            } else {
+                let (l, r) = split(node.left.take(), key);
+                node.left = r;
+                node.update();
+                (l, Some(node))
+            }
+        }
+    }
+}
+
+// split into the first `rank` entries and the rest.
+fn split_rank<K, V>(link: Link<K, V>, rank: usize) -> (Link<K, V>, Link<K, V>) {
+    match link {
+        None => (None, None),
+        Some(mut node) => {
+            let left_size = size(&node.left);
+            if rank <= left_size {
+                let (l, r) = split_rank(node.left.take(), rank);
+                node.left = r;
+                node.update();
+                (l, Some(node))
+            } else {
+                let (l, r) = split_rank(node.right.take(), rank - left_size - 1);
+                node.right = l;
+                node.update();
+                (Some(node), r)
+            }
+        }
+    }
+}
+
+fn nth<K, V>(mut link: &Link<K, V>, mut rank: usize) -> Option<&Node<K, V>> {
+    while let Some(node) = link {
+        let left_size = size(&node.left);
+        match rank.cmp(&left_size) {
+            Ordering::Less => link = &node.left,
+            Ordering::Equal => return Some(node),
+            Ordering::Greater => {
+                rank -= left_size + 1;
+                link = &node.right;
+            }
+        }
+    }
+    None
+}
+
+fn nth_mut<K, V>(mut link: &mut Link<K, V>, mut rank: usize) -> Option<&mut Node<K, V>> {
+    while let Some(node) = link {
+        let left_size = size(&node.left);
+        match rank.cmp(&left_size) {
+            Ordering::Less => link = &mut node.left,
+            Ordering::Equal => return Some(node),
+            Ordering::Greater => {
+                rank -= left_size + 1;
+                link = &mut node.right;
+            }
+        }
+    }
+    None
+}
+
+/// in-order iterator over the entries of a treap.
+pub struct Iter<'a, K, V> {
+    stack: Vec<&'a Node<K, V>>,
+    remaining: usize,
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    fn new(root: &'a Link<K, V>) -> Self {
+        let mut iter = Iter {
+            stack: Vec::new(),
+            remaining: size(root),
+        };
+        iter.push_left(root);
+        iter
+    }
+
+    fn push_left(&mut self, mut link: &'a Link<K, V>) {
+        while let Some(node) = link {
+            self.stack.push(node);
+            link = &node.left;
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left(&node.right);
+        self.remaining -= 1;
+        Some((&node.key, &node.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+/// ordered map backed by a randomized treap, with split and merge by key or by rank.
+pub struct Treap<K, V> {
+    root: Link<K, V>,
+    rng: Rng,
+}
+
+impl<K: Ord, V> Treap<K, V> {
+    /// create an empty treap.
+    pub fn new() -> Self {
+        Treap::with_seed(0x5eed)
+    }
+
+    /// create an empty treap whose priorities are drawn from `seed`.
+    pub fn with_seed(seed: u64) -> Self {
+        Treap {
+            root: None,
+            rng: Rng::new(seed),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// returns the value stored for `key`.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let mut link = &self.root;
+        while let Some(node) = link {
+            match key.cmp(&node.key) {
+                Ordering::Less => link = &node.left,
+                Ordering::Equal => return Some(&node.value),
+                Ordering::Greater => link = &node.right,
+            }
+        }
+        None
+    }
+
+    /// returns a mutable reference to the value stored for `key`.
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let mut link = &mut self.root;
+        while let Some(node) = link {
+            match key.cmp(&node.key) {
+                Ordering::Less => link = &mut node.left,
+                Ordering::Equal => return Some(&mut node.value),
+                Ordering::Greater => link = &mut node.right,
+            }
+        }
+        None
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// insert `value` for `key`, returning the previous value if there was one.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(slot) = self.get_mut(&key) {
+            return Some(std::mem::replace(slot, value));
+        }
+        let (left, right) = split(self.root.take(), &key);
+        let node = Node::new(key, value, self.rng.next_u64());
+        self.root = merge(merge(left, Some(node)), right);
+        None
+    }
+
+    /// remove `key`, returning its value if it was present.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        if !self.contains_key(key) {
+            return None;
+        }
+        let (left, rest) = split(self.root.take(), key);
+        let (mid, right) = split_rank(rest, 1);
+        self.root = merge(left, right);
+        mid.map(|node| node.value)
+    }
+
+    /// number of keys strictly smaller than `key`.
+    pub fn rank(&self, key: &K) -> usize {
+        let mut rank = 0;
+        let mut link = &self.root;
+        while let Some(node) = link {
+            if node.key < *key {
+                rank += size(&node.left) + 1;
+                link = &node.right;
+            } else {
+                link = &node.left;
+            }
+        }
+        rank
+    }
+
+    /// returns the entry with `rank` smaller keys, i.e. the `rank`-th smallest (0-based).
+    pub fn nth(&self, rank: usize) -> Option<(&K, &V)> {
+        nth(&self.root, rank).map(|node| (&node.key, &node.value))
+    }
+
+    /// entry with the smallest key.
+    pub fn first(&self) -> Option<(&K, &V)> {
+        self.nth(0)
+    }
+
+    /// entry with the largest key.
+    pub fn last(&self) -> Option<(&K, &V)> {
+        self.len().checked_sub(1).and_then(|rank| self.nth(rank))
+    }
+
+    /// remove and return the entries with keys `>= key`.
+    pub fn split_off(&mut self, key: &K) -> Treap<K, V> {
+        let (left, right) = split(self.root.take(), key);
+        self.root = left;
+        self.split_treap(right)
+    }
+
+    /// remove and return every entry after the first `rank`.
+    pub fn split_off_rank(&mut self, rank: usize) -> Treap<K, V> {
+        let (left, right) = split_rank(self.root.take(), rank);
+        self.root = left;
+        self.split_treap(right)
+    }
+
+    /// move every entry of `other` to the end of `self`.
+    ///
+    /// panics unless every key of `other` is larger than every key of `self`.
+    pub fn append(&mut self, other: Treap<K, V>) {
+        if let (Some((last, _)), Some((first, _))) = (self.last(), other.first()) {
+            assert!(last < first, "appended keys must be larger");
+        }
+        self.root = merge(self.root.take(), other.root);
+    }
+
+    /// iterate in increasing key order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter::new(&self.root)
+    }
+
+    fn split_treap(&mut self, root: Link<K, V>) -> Treap<K, V> {
+        Treap {
+            root,
+            rng: Rng::new(self.rng.next_u64()),
+        }
+    }
+}
+
+impl<K: Ord, V> Default for Treap<K, V> {
+    fn default() -> Self {
+        Treap::new()
+    }
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug> fmt::Debug for Treap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: Ord, V> std::iter::FromIterator<(K, V)> for Treap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut treap = Treap::new();
+        iter.into_iter().for_each(|(k, v)| {
+            treap.insert(k, v);
+        });
+        treap
+    }
+}
+
+impl<K: Ord, V> OrderedMap<K, V> for Treap<K, V> {
+    fn new() -> Self {
+        Treap::new()
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn get(&self, key: &K) -> Option<&V> {
+        self.get(key)
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.insert(key, value)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        self.remove(key)
+    }
+
+    fn first(&self) -> Option<(&K, &V)> {
+        self.first()
+    }
+
+    fn last(&self) -> Option<(&K, &V)> {
+        self.last()
+    }
+
+    fn iter<'a>(&'a self) -> Box<dyn Iterator<Item = (&'a K, &'a V)> + 'a>
+    where
+        K: 'a,
+        V: 'a,
+    {
+        Box::new(self.iter())
+    }
+}
+
+impl<K: Ord, V> OrderStatistic<K, V> for Treap<K, V> {
+    fn rank(&self, key: &K) -> usize {
+        self.rank(key)
+    }
+
+    fn select(&self, rank: usize) -> Option<(&K, &V)> {
+        self.nth(rank)
+    }
+}
+
+/// sequence backed by an implicit-key treap: positions play the role of keys,
+/// so inserting, removing, splitting and concatenating anywhere costs O(log n).
+pub struct ImplicitTreap<T> {
+    root: Link<(), T>,
+    rng: Rng,
+}
+
+impl<T> ImplicitTreap<T> {
+    /// create an empty sequence.
+    pub fn new() -> Self {
+        ImplicitTreap::with_seed(0x5eed)
+    }
+
+    /// create an empty sequence whose priorities are drawn from `seed`.
+    pub fn with_seed(seed: u64) -> Self {
+        ImplicitTreap {
+            root: None,
+            rng: Rng::new(seed),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// returns the element at `index`.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        nth(&self.root, index).map(|node| &node.value)
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        nth_mut(&mut self.root, index).map(|node| &mut node.value)
+    }
+
+    /// insert `value` so that it ends up at `index`, shifting later elements.
+    pub fn insert(&mut self, index: usize, value: T) {
+        assert!(index <= self.len(), "index {} out of bounds", index);
+        let (left, right) = split_rank(self.root.take(), index);
+        let node = Node::new((), value, self.rng.next_u64());
+        self.root = merge(merge(left, Some(node)), right);
+    }
+
+    /// append `value` at the end.
+    pub fn push(&mut self, value: T) {
+        let node = Node::new((), value, self.rng.next_u64());
+        self.root = merge(self.root.take(), Some(node));
+    }
+
+    /// remove and return the element at `index`.
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        if index >= self.len() {
+            return None;
+        }
+        let (left, rest) = split_rank(self.root.take(), index);
+        let (mid, right) = split_rank(rest, 1);
+        self.root = merge(left, right);
+        mid.map(|node| node.value)
+    }
+
+    /// remove and return the elements from `index` onwards.
+    pub fn split_off(&mut self, index: usize) -> ImplicitTreap<T> {
+        assert!(index <= self.len(), "index {} out of bounds", index);
+        let (left, right) = split_rank(self.root.take(), index);
+        self.root = left;
+        ImplicitTreap {
+            root: right,
+            rng: Rng::new(self.rng.next_u64()),
+        }
+    }
+
+    /// move every element of `other` to the end of `self`.
+    pub fn append(&mut self, other: ImplicitTreap<T>) {
+        self.root = merge(self.root.take(), other.root);
+    }
+
+    /// iterate in sequence order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        Iter::new(&self.root).map(|(_, v)| v)
+    }
+}
+
+impl<T> Default for ImplicitTreap<T> {
+    fn default() -> Self {
+        ImplicitTreap::new()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ImplicitTreap<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T> std::iter::FromIterator<T> for ImplicitTreap<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut treap = ImplicitTreap::new();
+        iter.into_iter().for_each(|v| treap.push(v));
+        treap
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_map_against_btree() {
+        let mut rng = Rng::new(11);
+        let mut treap = Treap::new();
+        let mut model = BTreeMap::new();
+        for _ in 0..2000 {
+            let key = rng.range(0..200);
+            if rng.chance(0.6) {
+                assert_eq!(treap.insert(key, key * 2), model.insert(key, key * 2));
+            } else {
+                assert_eq!(treap.remove(&key), model.remove(&key));
+            }
+            assert_eq!(treap.len(), model.len());
+        }
+        assert!(treap.iter().eq(model.iter()));
+        for (rank, (key, _)) in model.iter().enumerate() {
+            assert_eq!(treap.rank(key), rank);
+            assert_eq!(treap.nth(rank).map(|(k, _)| k), Some(key));
+        }
+        assert_eq!(treap.first(), model.iter().next());
+        assert_eq!(treap.last(), model.iter().next_back());
+    }
+
+    #[test]
+    fn test_split_merge() {
+        let mut treap: Treap<i32, ()> = (0..10).map(|k| (k, ())).collect();
+        let mut high = treap.split_off(&6);
+        assert_eq!(
+            treap.iter().map(|(k, _)| *k).collect::<Vec<_>>(),
+            vec![0, 1, 2, 3, 4, 5]
+        );
+        assert_eq!(
+            high.iter().map(|(k, _)| *k).collect::<Vec<_>>(),
+            vec![6, 7, 8, 9]
+        );
+        let tail = high.split_off_rank(1);
+        assert_eq!(high.len(), 1);
+        assert_eq!(tail.first(), Some((&7, &())));
+        treap.append(high);
+        treap.append(tail);
+        assert!(treap.iter().map(|(k, _)| *k).eq(0..10));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_append_overlapping() {
+        let mut a: Treap<i32, ()> = (0..5).map(|k| (k, ())).collect();
+        let b: Treap<i32, ()> = (3..8).map(|k| (k, ())).collect();
+        a.append(b);
+    }
+
+    #[test]
+    fn test_implicit_sequence() {
+        let mut rng = Rng::new(5);
+        let mut seq = ImplicitTreap::new();
+        let mut model = Vec::new();
+        for step in 0..1000 {
+            if model.is_empty() || rng.chance(0.7) {
+                let index = rng.index(model.len() + 1);
+                seq.insert(index, step);
+                model.insert(index, step);
+            } else {
+                let index = rng.index(model.len());
+                assert_eq!(seq.remove(index), Some(model.remove(index)));
+            }
+        }
+        assert!(seq.iter().eq(model.iter()));
+        *seq.get_mut(3).unwrap() = -1;
+        model[3] = -1;
+        let tail = seq.split_off(10);
+        assert_eq!(seq.len(), 10);
+        seq.append(tail);
+        assert!(seq.iter().eq(model.iter()));
+        assert_eq!(seq.get(model.len()), None);
+    }
+}
@@ -0,0 +1,292 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+struct Node<S, V> {
+    children: BTreeMap<S, Node<S, V>>,
+    value: Option<V>,
+}
+
+impl<S, V> Node<S, V> {
+    fn new() -> Self {
+        Node {
+            children: BTreeMap::new(),
+            value: None,
+        }
+    }
+}
+
+// drop the subtree with an explicit stack, a deep chain would otherwise
+// recurse once per symbol.
+impl<S, V> Drop for Node<S, V> {
+    fn drop(&mut self) {
+        let mut stack: Vec<Node<S, V>> = std::mem::take(&mut self.children).into_values().collect();
+        while let Some(mut node) = stack.pop() {
+            stack.extend(std::mem::take(&mut node.children).into_values());
+        }
+    }
+}
+
+/// prefix tree over keys that are sequences of symbols `S`, typically `u8`
+/// (`key.bytes()`) or `char` (`key.chars()`).
+///
+/// children are kept sorted, so iteration visits keys in lexicographic order.
+pub struct Trie<S, V> {
+    root: Node<S, V>,
+    len: usize,
+}
+
+impl<S: Ord + Clone, V> Trie<S, V> {
+    /// create an empty trie.
+    pub fn new() -> Self {
+        Trie {
+            root: Node::new(),
+            len: 0,
+        }
+    }
+
+    /// number of stored keys.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// insert `value` for `key`, returning the previous value if there was one.
+    pub fn insert<I: IntoIterator<Item = S>>(&mut self, key: I, value: V) -> Option<V> {
+        let mut node = &mut self.root;
+        for symbol in key {
+            node = node.children.entry(symbol).or_insert_with(Node::new);
+        }
+        let old = node.value.replace(value);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    /// returns the value stored for exactly `key`.
+    pub fn get<I: IntoIterator<Item = S>>(&self, key: I) -> Option<&V> {
+        self.find(key).and_then(|node| node.value.as_ref())
+    }
+
+    /// returns a mutable reference to the value stored for exactly `key`.
+    pub fn get_mut<I: IntoIterator<Item = S>>(&mut self, key: I) -> Option<&mut V> {
+        let mut node = &mut self.root;
+        for symbol in key {
+            node = node.children.get_mut(&symbol)?;
+        }
+        node.value.as_mut()
+    }
+
+    pub fn contains_key<I: IntoIterator<Item = S>>(&self, key: I) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// check whether some stored key starts with `prefix`.
+    pub fn has_prefix<I: IntoIterator<Item = S>>(&self, prefix: I) -> bool {
+        self.find(prefix).is_some()
+    }
+
+    /// remove `key`, returning its value if it was present. branches left
+    /// without keys are pruned.
+    pub fn remove<I: IntoIterator<Item = S>>(&mut self, key: I) -> Option<V> {
+        let key: Vec<S> = key.into_iter().collect();
+        // the deepest node on the path that still holds another key, below
+        // it the path is a bare chain that goes away with the value.
+        let mut keep = 0;
+        let mut node = &self.root;
+        for (depth, symbol) in key.iter().enumerate() {
+            if node.value.is_some() || node.children.len() > 1 {
+                keep = depth;
+            }
+            node = node.children.get(symbol)?;
+        }
+        node.value.as_ref()?;
+        let prune = node.children.is_empty();
+
+        let mut node = &mut self.root;
+        for symbol in &key {
+            node = node.children.get_mut(symbol)?;
+        }
+        let removed = node.value.take();
+        if prune && !key.is_empty() {
+            let mut node = &mut self.root;
+            for symbol in &key[..keep] {
+                node = node.children.get_mut(symbol)?;
+            }
+            node.children.remove(&key[keep]);
+        }
+        self.len -= 1;
+        removed
+    }
+
+    /// the longest stored key that is a prefix of `key`, as its length in
+    /// symbols together with its value.
+    pub fn longest_prefix<I: IntoIterator<Item = S>>(&self, key: I) -> Option<(usize, &V)> {
+        let mut node = &self.root;
+        let mut best = node.value.as_ref().map(|v| (0, v));
+        for (depth, symbol) in key.into_iter().enumerate() {
+            match node.children.get(&symbol) {
+                Some(child) => node = child,
+                None => break,
+            }
+            if let Some(value) = node.value.as_ref() {
+                best = Some((depth + 1, value));
+            }
+        }
+        best
+    }
+
+    /// iterate over the stored keys starting with `prefix`, in lexicographic order.
+    pub fn iter_prefix<I: IntoIterator<Item = S>>(&self, prefix: I) -> Iter<'_, S, V> {
+        let prefix: Vec<S> = prefix.into_iter().collect();
+        let mut node = Some(&self.root);
+        for symbol in &prefix {
+            node = node.and_then(|n| n.children.get(symbol));
+        }
+        Iter {
+            stack: node.map(|n| (prefix, n)).into_iter().collect(),
+        }
+    }
+
+    /// iterate over every key in lexicographic order.
+    pub fn iter(&self) -> Iter<'_, S, V> {
+        self.iter_prefix(std::iter::empty())
+    }
+
+    fn find<I: IntoIterator<Item = S>>(&self, key: I) -> Option<&Node<S, V>> {
+        let mut node = &self.root;
+        for symbol in key {
+            node = node.children.get(&symbol)?;
+        }
+        Some(node)
+    }
+}
+
+impl<S: Ord + Clone, V> Default for Trie<S, V> {
+    fn default() -> Self {
+        Trie::new()
+    }
+}
+
+impl<S: Ord + Clone + fmt::Debug, V: fmt::Debug> fmt::Debug for Trie<S, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// lexicographic iterator over the keys of a trie, yielding owned keys.
+pub struct Iter<'a, S, V> {
+    stack: Vec<(Vec<S>, &'a Node<S, V>)>,
+}
+
+impl<'a, S: Clone, V> Iterator for Iter<'a, S, V> {
+    type Item = (Vec<S>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((path, node)) = self.stack.pop() {
+            for (symbol, child) in node.children.iter().rev() {
+                let mut child_path = path.clone();
+                child_path.push(symbol.clone());
+                self.stack.push((child_path, child));
+            }
+            if let Some(value) = node.value.as_ref() {
+                return Some((path, value));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(trie: &Trie<u8, u32>, prefix: &str) -> Vec<String> {
+        trie.iter_prefix(prefix.bytes())
+            .map(|(k, _)| String::from_utf8(k).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_insert_get_remove() {
+        let mut trie = Trie::new();
+        for (i, word) in ["tea", "ten", "to", "inn", "in", "tea"].iter().enumerate() {
+            trie.insert(word.bytes(), i as u32);
+        }
+        assert_eq!(trie.len(), 5);
+        assert_eq!(trie.get("tea".bytes()), Some(&5));
+        assert_eq!(trie.get("te".bytes()), None);
+        assert!(trie.has_prefix("te".bytes()));
+        assert_eq!(trie.remove("tea".bytes()), Some(5));
+        assert_eq!(trie.remove("tea".bytes()), None);
+        assert!(!trie.contains_key("tea".bytes()));
+        assert!(trie.contains_key("ten".bytes()));
+        *trie.get_mut("in".bytes()).unwrap() += 10;
+        assert_eq!(trie.get("in".bytes()), Some(&14));
+        assert_eq!(trie.len(), 4);
+    }
+
+    #[test]
+    fn test_long_keys() {
+        let long = vec![7u8; 100_000];
+        let mut trie = Trie::new();
+        assert_eq!(trie.insert(long.iter().copied(), 1), None);
+        assert_eq!(trie.insert(long[..50_000].iter().copied(), 2), None);
+        assert_eq!(trie.get(long.iter().copied()), Some(&1));
+        assert_eq!(
+            trie.longest_prefix(long.iter().copied().chain(Some(0))),
+            Some((100_000, &1))
+        );
+        assert_eq!(trie.remove(long.iter().copied()), Some(1));
+        assert!(!trie.has_prefix(long[..50_001].iter().copied()));
+        assert_eq!(trie.get(long[..50_000].iter().copied()), Some(&2));
+        assert_eq!(trie.len(), 1);
+        trie.insert(long.iter().copied(), 3);
+        assert_eq!(trie.remove(long[..50_000].iter().copied()), Some(2));
+        assert!(trie.contains_key(long.iter().copied()));
+    }
+
+    #[test]
+    fn test_prefix_iteration() {
+        let mut trie = Trie::new();
+        for word in ["banana", "band", "bandana", "ban", "apple", "bank"].iter() {
+            trie.insert(word.bytes(), 0);
+        }
+        assert_eq!(
+            words(&trie, "ban"),
+            vec!["ban", "banana", "band", "bandana", "bank"]
+        );
+        assert_eq!(words(&trie, "band"), vec!["band", "bandana"]);
+        assert_eq!(words(&trie, "c"), Vec::<String>::new());
+        assert_eq!(words(&trie, "").len(), 6);
+        trie.remove("band".bytes());
+        assert_eq!(words(&trie, "band"), vec!["bandana"]);
+    }
+
+    #[test]
+    fn test_longest_prefix() {
+        // a routing table keyed by dotted prefixes.
+        let mut routes: Trie<char, &str> = Trie::new();
+        routes.insert("10.".chars(), "private");
+        routes.insert("10.1.".chars(), "office");
+        routes.insert("10.1.2.".chars(), "lab");
+        assert_eq!(routes.longest_prefix("10.1.2.7".chars()), Some((7, &"lab")));
+        assert_eq!(
+            routes.longest_prefix("10.1.3.1".chars()),
+            Some((5, &"office"))
+        );
+        assert_eq!(
+            routes.longest_prefix("10.9.9.9".chars()),
+            Some((3, &"private"))
+        );
+        assert_eq!(routes.longest_prefix("192.168.0.1".chars()), None);
+        routes.insert("".chars(), "default");
+        assert_eq!(
+            routes.longest_prefix("192.168.0.1".chars()),
+            Some((0, &"default"))
+        );
+    }
+}
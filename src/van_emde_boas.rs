@@ -0,0 +1,401 @@
+use std::collections::HashMap;
+use std::fmt;
+
+// universes of at most 2^LEAF_BITS keys are a single bitmap.
+const LEAF_BITS: u32 = 6;
+
+enum Veb {
+    Leaf(u64),
+    Node {
+        // the minimum is kept here only, not in a cluster; `max` is
+        // meaningless while `min` is None.
+        min: Option<u64>,
+        max: u64,
+        low_bits: u32,
+        // which clusters are non-empty.
+        summary: Box<Veb>,
+        // only non-empty clusters are allocated, so space is O(n) rather
+        // than O(universe).
+        clusters: HashMap<u64, Veb>,
+    },
+}
+
+impl Veb {
+    fn new(bits: u32) -> Self {
+        if bits <= LEAF_BITS {
+            return Veb::Leaf(0);
+        }
+        let low_bits = bits / 2;
+        Veb::Node {
+            min: None,
+            max: 0,
+            low_bits,
+            summary: Box::new(Veb::new(bits - low_bits)),
+            clusters: HashMap::new(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        match self {
+            Veb::Leaf(bitmap) => *bitmap == 0,
+            Veb::Node { min, .. } => min.is_none(),
+        }
+    }
+
+    fn min(&self) -> Option<u64> {
+        match self {
+            Veb::Leaf(bitmap) => lowest(*bitmap),
+            Veb::Node { min, .. } => *min,
+        }
+    }
+
+    fn max(&self) -> Option<u64> {
+        match self {
+            Veb::Leaf(bitmap) => highest(*bitmap),
+            Veb::Node { min, max, .. } => min.map(|_| *max),
+        }
+    }
+
+    fn contains(&self, x: u64) -> bool {
+        match self {
+            Veb::Leaf(bitmap) => bitmap >> x & 1 == 1,
+            Veb::Node {
+                min,
+                max,
+                low_bits,
+                clusters,
+                ..
+            } => match *min {
+                None => false,
+                Some(min) if x == min || x == *max => true,
+                Some(_) => clusters
+                    .get(&(x >> low_bits))
+                    .is_some_and(|c| c.contains(x & mask(*low_bits))),
+            },
+        }
+    }
+
+    // `x` must not be present.
+    fn insert(&mut self, mut x: u64) {
+        match self {
+            Veb::Leaf(bitmap) => *bitmap |= 1 << x,
+            Veb::Node {
+                min,
+                max,
+                low_bits,
+                summary,
+                clusters,
+            } => {
+                let current = match min {
+                    None => {
+                        *min = Some(x);
+                        *max = x;
+                        return;
+                    }
+                    Some(current) => current,
+                };
+                if x < *current {
+                    std::mem::swap(&mut x, current);
+                }
+                if x > *max {
+                    *max = x;
+                }
+                let (high, low) = (x >> *low_bits, x & mask(*low_bits));
+                let cluster = clusters.entry(high).or_insert_with(|| Veb::new(*low_bits));
+                if cluster.is_empty() {
+                    summary.insert(high);
+                }
+                cluster.insert(low);
+            }
+        }
+    }
+
+    // `x` must be present.
+    fn remove(&mut self, mut x: u64) {
+        match self {
+            Veb::Leaf(bitmap) => *bitmap &= !(1 << x),
+            Veb::Node {
+                min,
+                max,
+                low_bits,
+                summary,
+                clusters,
+            } => {
+                let lb = *low_bits;
+                if *min == Some(*max) {
+                    *min = None;
+                    return;
+                }
+                if *min == Some(x) {
+                    // promote the smallest clustered key to the minimum.
+                    let high = summary.min().unwrap();
+                    x = high << lb | clusters[&high].min().unwrap();
+                    *min = Some(x);
+                }
+                let high = x >> lb;
+                let cluster = clusters.get_mut(&high).unwrap();
+                cluster.remove(x & mask(lb));
+                if cluster.is_empty() {
+                    clusters.remove(&high);
+                    summary.remove(high);
+                    if x == *max {
+                        *max = match summary.max() {
+                            None => min.unwrap(),
+                            Some(last) => last << lb | clusters[&last].max().unwrap(),
+                        };
+                    }
+                } else if x == *max {
+                    *max = high << lb | cluster.max().unwrap();
+                }
+            }
+        }
+    }
+
+    fn successor(&self, x: u64) -> Option<u64> {
+        match self {
+            Veb::Leaf(bitmap) => lowest(if x >= 63 { 0 } else { bitmap & (!0 << (x + 1)) }),
+            Veb::Node {
+                min,
+                low_bits,
+                summary,
+                clusters,
+                ..
+            } => {
+                let lb = *low_bits;
+                if min.is_some_and(|min| x < min) {
+                    return *min;
+                }
+                let (high, low) = (x >> lb, x & mask(lb));
+                if let Some(cluster) = clusters.get(&high) {
+                    if cluster.max().is_some_and(|max| low < max) {
+                        return Some(high << lb | cluster.successor(low).unwrap());
+                    }
+                }
+                let next = summary.successor(high)?;
+                Some(next << lb | clusters[&next].min().unwrap())
+            }
+        }
+    }
+
+    fn predecessor(&self, x: u64) -> Option<u64> {
+        match self {
+            Veb::Leaf(bitmap) => highest(bitmap & ((1 << x) - 1)),
+            Veb::Node {
+                min,
+                max,
+                low_bits,
+                summary,
+                clusters,
+            } => {
+                let lb = *low_bits;
+                let min = (*min)?;
+                if x > *max {
+                    return Some(*max);
+                }
+                let (high, low) = (x >> lb, x & mask(lb));
+                if let Some(cluster) = clusters.get(&high) {
+                    if cluster.min().is_some_and(|min| low > min) {
+                        return Some(high << lb | cluster.predecessor(low).unwrap());
+                    }
+                }
+                match summary.predecessor(high) {
+                    Some(prev) => Some(prev << lb | clusters[&prev].max().unwrap()),
+                    None => Some(min).filter(|&min| x > min),
+                }
+            }
+        }
+    }
+}
+
+fn lowest(bitmap: u64) -> Option<u64> {
+    if bitmap == 0 {
+        None
+    } else {
+        Some(u64::from(bitmap.trailing_zeros()))
+    }
+}
+
+fn highest(bitmap: u64) -> Option<u64> {
+    if bitmap == 0 {
+        None
+    } else {
+        Some(u64::from(63 - bitmap.leading_zeros()))
+    }
+}
+
+fn mask(bits: u32) -> u64 {
+    (1 << bits) - 1
+}
+
+/// van emde boas tree: a set of integers in `0..2^bits` with insert,
+/// remove, successor and predecessor in O(log log universe).
+///
+/// its min and max are O(1) and `pop_min` is a `remove` of that, which makes
+/// it a fast backend for priority queues with small integer priorities,
+/// e.g. timer wheels or dijkstra over integer distances.
+pub struct VebTree {
+    bits: u32,
+    root: Veb,
+    len: usize,
+}
+
+impl VebTree {
+    /// an empty set over the universe `0..2^bits`, for `bits` in 1..=64.
+    pub fn new(bits: u32) -> Self {
+        assert!((1..=64).contains(&bits), "bits must be in 1..=64");
+        VebTree {
+            bits,
+            root: Veb::new(bits),
+            len: 0,
+        }
+    }
+
+    pub fn universe_bits(&self) -> u32 {
+        self.bits
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn in_universe(&self, x: u64) -> bool {
+        self.bits == 64 || x >> self.bits == 0
+    }
+
+    pub fn contains(&self, x: u64) -> bool {
+        self.in_universe(x) && self.root.contains(x)
+    }
+
+    /// insert `x`, returning false if it was already present. panics if
+    /// `x` is outside the universe.
+    pub fn insert(&mut self, x: u64) -> bool {
+        assert!(self.in_universe(x), "{} is outside the universe", x);
+        if self.root.contains(x) {
+            return false;
+        }
+        self.root.insert(x);
+        self.len += 1;
+        true
+    }
+
+    /// remove `x`, returning whether it was present.
+    pub fn remove(&mut self, x: u64) -> bool {
+        if !self.contains(x) {
+            return false;
+        }
+        self.root.remove(x);
+        self.len -= 1;
+        true
+    }
+
+    pub fn min(&self) -> Option<u64> {
+        self.root.min()
+    }
+
+    pub fn max(&self) -> Option<u64> {
+        self.root.max()
+    }
+
+    pub fn pop_min(&mut self) -> Option<u64> {
+        let min = self.root.min()?;
+        self.remove(min);
+        Some(min)
+    }
+
+    pub fn pop_max(&mut self) -> Option<u64> {
+        let max = self.root.max()?;
+        self.remove(max);
+        Some(max)
+    }
+
+    /// the smallest key greater than `x`.
+    pub fn successor(&self, x: u64) -> Option<u64> {
+        if self.in_universe(x) {
+            self.root.successor(x)
+        } else {
+            None
+        }
+    }
+
+    /// the largest key less than `x`.
+    pub fn predecessor(&self, x: u64) -> Option<u64> {
+        if self.in_universe(x) {
+            self.root.predecessor(x)
+        } else {
+            self.max()
+        }
+    }
+
+    /// iterate over the keys in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = u64> + '_ {
+        let mut next = self.min();
+        std::iter::from_fn(move || {
+            let current = next?;
+            next = self.successor(current);
+            Some(current)
+        })
+    }
+}
+
+impl fmt::Debug for VebTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+    use std::collections::BTreeSet;
+
+    fn check_against_btree(bits: u32, seed: u64) {
+        let mut rng = Rng::new(seed);
+        let mut tree = VebTree::new(bits);
+        let mut model = BTreeSet::new();
+        // keys from a narrow band, so that clusters fill and empty often.
+        let base = if bits == 64 { u64::MAX - 5000 } else { 0 };
+        let span = if bits >= 13 { 5000 } else { 1 << bits };
+        for _ in 0..20_000 {
+            let x = base + rng.below(span);
+            match rng.index(4) {
+                0 => assert_eq!(tree.remove(x), model.remove(&x)),
+                1 => {
+                    assert_eq!(tree.successor(x), model.range(x + 1..).next().copied());
+                    assert_eq!(tree.predecessor(x), model.range(..x).next_back().copied());
+                }
+                _ => assert_eq!(tree.insert(x), model.insert(x)),
+            }
+            assert_eq!(tree.min(), model.iter().next().copied());
+            assert_eq!(tree.max(), model.iter().next_back().copied());
+        }
+        assert_eq!(tree.len(), model.len());
+        assert!(tree.iter().eq(model.iter().copied()));
+    }
+
+    #[test]
+    fn test_against_btree_set() {
+        check_against_btree(5, 45);
+        check_against_btree(20, 46);
+        check_against_btree(64, 47);
+    }
+
+    #[test]
+    fn test_priority_queue_use() {
+        let mut tree = VebTree::new(16);
+        for x in [300, 7, 65535, 0, 42] {
+            tree.insert(x);
+        }
+        assert!(!tree.insert(42));
+        assert!(!tree.contains(1 << 20) && !tree.remove(1 << 20));
+        assert_eq!(tree.predecessor(1 << 20), Some(65535));
+        assert_eq!(tree.successor(65535), None);
+        let drained: Vec<u64> = std::iter::from_fn(|| tree.pop_min()).collect();
+        assert_eq!(drained, vec![0, 7, 42, 300, 65535]);
+        assert!(tree.is_empty() && tree.max().is_none());
+    }
+}
@@ -0,0 +1,237 @@
+use std::fmt;
+use std::ops::{Range, RangeBounds};
+
+use crate::bit_vector::BitVector;
+use crate::resolve_range;
+
+/// wavelet tree over a sequence of integers: access, rank, select, the
+/// k-th smallest value of a range and counts of values in a range, each in
+/// O(log sigma) rank or select operations, where sigma bounds the values.
+///
+/// it is stored level by level (as a "wavelet matrix"): level `l` holds bit
+/// `l` (from the top) of every value, with the values stably sorted by
+/// their higher bits, so each level is one [`BitVector`] and a zero count.
+pub struct WaveletTree {
+    len: usize,
+    // the bit width of the values.
+    bits: u32,
+    levels: Vec<BitVector>,
+    // number of zeros in each level; the ones follow them below.
+    zeros: Vec<usize>,
+}
+
+impl WaveletTree {
+    pub fn new(values: &[u64]) -> Self {
+        let max = values.iter().copied().max().unwrap_or(0);
+        let bits = (64 - max.leading_zeros()).max(1);
+        let mut current = values.to_vec();
+        let mut levels = Vec::with_capacity(bits as usize);
+        let mut zeros = Vec::with_capacity(bits as usize);
+        for level in 0..bits {
+            let shift = bits - 1 - level;
+            let vector: BitVector = current.iter().map(|v| v >> shift & 1 == 1).collect();
+            zeros.push(vector.count_zeros());
+            levels.push(vector);
+            let (low, high): (Vec<u64>, Vec<u64>) =
+                current.iter().partition(|&&v| v >> shift & 1 == 0);
+            current = low;
+            current.extend(high);
+        }
+        WaveletTree {
+            len: values.len(),
+            bits,
+            levels,
+            zeros,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // where the positions `start..end` of one level land on the next, for
+    // values whose bit there is `bit`.
+    fn descend(&self, level: usize, bit: bool, start: usize, end: usize) -> (usize, usize) {
+        let vector = &self.levels[level];
+        if bit {
+            let z = self.zeros[level];
+            (z + vector.rank1(start), z + vector.rank1(end))
+        } else {
+            (vector.rank0(start), vector.rank0(end))
+        }
+    }
+
+    fn fits(&self, value: u64) -> bool {
+        self.bits == 64 || value >> self.bits == 0
+    }
+
+    pub fn get(&self, index: usize) -> Option<u64> {
+        if index >= self.len {
+            return None;
+        }
+        let mut i = index;
+        let mut value = 0;
+        for (level, vector) in self.levels.iter().enumerate() {
+            let bit = vector.get(i).unwrap();
+            value = value << 1 | u64::from(bit);
+            i = self.descend(level, bit, i, i).0;
+        }
+        Some(value)
+    }
+
+    /// number of occurrences of `value` before position `end`.
+    pub fn rank(&self, value: u64, end: usize) -> usize {
+        assert!(end <= self.len, "position {} out of bounds", end);
+        if !self.fits(value) {
+            return 0;
+        }
+        let (mut start, mut end) = (0, end);
+        for level in 0..self.levels.len() {
+            let bit = value >> (self.bits as usize - 1 - level) & 1 == 1;
+            let next = self.descend(level, bit, start, end);
+            start = next.0;
+            end = next.1;
+        }
+        end - start
+    }
+
+    /// position of the `k`-th occurrence (0-based) of `value`.
+    pub fn select(&self, value: u64, k: usize) -> Option<usize> {
+        if k >= self.rank(value, self.len) {
+            return None;
+        }
+        // find where the run of `value` starts on the last level, then map
+        // its k-th position back up.
+        let mut start = 0;
+        for level in 0..self.levels.len() {
+            let bit = value >> (self.bits as usize - 1 - level) & 1 == 1;
+            start = self.descend(level, bit, start, start).0;
+        }
+        let mut position = start + k;
+        for level in (0..self.levels.len()).rev() {
+            let bit = value >> (self.bits as usize - 1 - level) & 1 == 1;
+            let vector = &self.levels[level];
+            position = if bit {
+                vector.select1(position - self.zeros[level])
+            } else {
+                vector.select0(position)
+            }
+            .unwrap();
+        }
+        Some(position)
+    }
+
+    /// the `k`-th smallest value (0-based) in `range`.
+    pub fn quantile<R: RangeBounds<usize>>(&self, range: R, mut k: usize) -> Option<u64> {
+        let (mut start, mut end) = resolve_range(range, self.len);
+        if k >= end - start {
+            return None;
+        }
+        let mut value = 0;
+        for level in 0..self.levels.len() {
+            let vector = &self.levels[level];
+            let zeros = vector.rank0(end) - vector.rank0(start);
+            let bit = k >= zeros;
+            if bit {
+                k -= zeros;
+            }
+            value = value << 1 | u64::from(bit);
+            let next = self.descend(level, bit, start, end);
+            start = next.0;
+            end = next.1;
+        }
+        Some(value)
+    }
+
+    /// number of values less than `bound` in `range`.
+    pub fn count_less<R: RangeBounds<usize>>(&self, range: R, bound: u64) -> usize {
+        let (mut start, mut end) = resolve_range(range, self.len);
+        if !self.fits(bound) {
+            return end - start;
+        }
+        let mut count = 0;
+        for level in 0..self.levels.len() {
+            let bit = bound >> (self.bits as usize - 1 - level) & 1 == 1;
+            if bit {
+                // everything going to the zeros side is smaller.
+                let vector = &self.levels[level];
+                count += vector.rank0(end) - vector.rank0(start);
+            }
+            let next = self.descend(level, bit, start, end);
+            start = next.0;
+            end = next.1;
+        }
+        count
+    }
+
+    /// number of values within `values` in the positions `range`.
+    pub fn count_between<R: RangeBounds<usize>>(&self, range: R, values: Range<u64>) -> usize {
+        let (start, end) = resolve_range(range, self.len);
+        if values.start >= values.end {
+            return 0;
+        }
+        self.count_less(start..end, values.end) - self.count_less(start..end, values.start)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = u64> + '_ {
+        (0..self.len).map(move |i| self.get(i).unwrap())
+    }
+}
+
+impl fmt::Debug for WaveletTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_against_naive() {
+        let mut rng = Rng::new(52);
+        let values: Vec<u64> = (0..3000).map(|_| rng.below(100)).collect();
+        let tree = WaveletTree::new(&values);
+        assert!(tree.iter().eq(values.iter().copied()));
+        for _ in 0..300 {
+            let v = rng.below(110);
+            let end = rng.index(values.len() + 1);
+            assert_eq!(
+                tree.rank(v, end),
+                values[..end].iter().filter(|&&x| x == v).count()
+            );
+            let positions: Vec<usize> = (0..values.len()).filter(|&i| values[i] == v).collect();
+            let k = rng.index(positions.len() + 1);
+            assert_eq!(tree.select(v, k), positions.get(k).copied());
+            let start = rng.index(end + 1);
+            let mut sorted = values[start..end].to_vec();
+            sorted.sort_unstable();
+            let k = rng.index(sorted.len() + 1);
+            assert_eq!(tree.quantile(start..end, k), sorted.get(k).copied());
+            let (lo, hi) = (rng.below(60), rng.below(120));
+            let between = sorted.iter().filter(|&&x| lo <= x && x < hi).count();
+            assert_eq!(tree.count_between(start..end, lo..hi), between);
+        }
+    }
+
+    #[test]
+    fn test_edges() {
+        let tree = WaveletTree::new(&[u64::MAX, 0, 5, u64::MAX]);
+        assert_eq!(tree.get(0), Some(u64::MAX));
+        assert_eq!(tree.quantile(.., 3), Some(u64::MAX));
+        assert_eq!(tree.quantile(..2, 0), Some(0));
+        assert_eq!(tree.select(u64::MAX, 1), Some(3));
+        assert_eq!(tree.count_less(.., u64::MAX), 2);
+        assert_eq!(format!("{:?}", WaveletTree::new(&[3, 1, 2])), "[3, 1, 2]");
+        let empty = WaveletTree::new(&[]);
+        assert!(empty.is_empty() && empty.get(0).is_none());
+        assert_eq!(empty.quantile(.., 0), None);
+        assert_eq!(empty.rank(0, 0), 0);
+    }
+}